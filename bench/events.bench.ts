@@ -0,0 +1,105 @@
+import { access, readFile } from 'fs/promises'
+import { bench, describe } from 'vitest'
+import * as current from '../src/generated/wasm/store'
+import type { Note } from '../src/generated/wasm/store'
+
+// Run with `pnpm bench` after `pnpm build:wasm`. Compares the bulk queries against fetching the
+// same events one at a time, which pays the full conversion cost per event, and times passing
+// events and updaters in.
+//
+// To compare with another revision, build it into bench/baseline, for example
+//   git worktree add ../store-baseline main
+//   wasm-pack build ../store-baseline/wasm-src --release --target web \
+//     --out-dir "$PWD/bench/baseline" --no-pack
+// and every case also runs against that build, labelled "baseline".
+
+const NOTE_COUNT = 50_000
+const PPQ = 480
+
+type StoreModule = typeof current
+
+const loadBuild = async (
+  module: StoreModule,
+  directory: string,
+): Promise<StoreModule> => {
+  await module.default(
+    await readFile(new URL(`${directory}/store_bg.wasm`, import.meta.url)),
+  )
+  return module
+}
+
+const baselineUrl = new URL('./baseline/store.js', import.meta.url)
+const hasBaseline = await access(baselineUrl).then(
+  () => true,
+  () => false,
+)
+
+const builds: [string, StoreModule][] = [
+  ['current', await loadBuild(current, '../src/generated/wasm')],
+]
+if (hasBaseline) {
+  builds.push([
+    'baseline',
+    await loadBuild(await import(baselineUrl.href), './baseline'),
+  ])
+}
+
+const trackId = crypto.randomUUID()
+const notes: Note[] = Array.from({ length: NOTE_COUNT }, (_, i) => ({
+  id: crypto.randomUUID(),
+  kind: 'Note',
+  ticks: i * (PPQ / 4),
+  duration: PPQ / 4,
+  velocity: 100,
+  noteNumber: 36 + (i % 48),
+  trackId,
+  parameters: {},
+}))
+const eventIds = notes.map((note) => note.id)
+const endTicks = NOTE_COUNT * (PPQ / 4)
+const updaters = notes.map(({ id }) => ({ id, kind: 'Note', velocity: 90 }))
+
+const createStore = ({ Store }: StoreModule, events: Note[] = notes) => {
+  const store = new Store()
+  store.createSong('bench', PPQ)
+  store.addTrack({ id: trackId, events })
+  return store
+}
+
+describe(`${NOTE_COUNT} notes`, () => {
+  for (const [label, module] of builds) {
+    const store = createStore(module)
+
+    bench(`getEvents (${label})`, () => {
+      store.getEvents()
+    })
+
+    bench(`getEventsInTicksRange (${label})`, () => {
+      store.getEventsInTicksRange(0, endTicks, false)
+    })
+
+    bench(`getNotesInTicksRangeFlat (${label})`, () => {
+      store.getNotesInTicksRangeFlat(0, endTicks)
+    })
+
+    bench(`getSong (${label})`, () => {
+      store.getSong()
+    })
+
+    bench(`getEvent per event (${label})`, () => {
+      for (const eventId of eventIds) {
+        store.getEvent(eventId)
+      }
+    })
+
+    bench(`addEvents (${label})`, () => {
+      const emptyStore = createStore(module, [])
+      emptyStore.addEvents(notes)
+      emptyStore.free()
+    })
+
+    bench(`updateEvents (${label})`, () => {
+      store.updateEvents(updaters)
+    })
+  }
+})
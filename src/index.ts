@@ -1,15 +1,187 @@
-import init, { Store } from './generated/wasm/store'
+import init, {
+  Store,
+  type MidiImportOptions,
+  type MidiImportResult,
+} from './generated/wasm/store'
+
+/** Events `importMidiAsync` adds between yields to the event loop. */
+const MIDI_IMPORT_CHUNK_EVENTS = 20_000
 
 async function createStore(): Promise<Store> {
   await init()
   return new Store()
 }
 
+/**
+ * Imports an SMF like `store.importMidi`, adding its events a chunk at a time and yielding to
+ * the event loop in between, so the UI can show `onProgress` instead of freezing.
+ */
+async function importMidiAsync(
+  store: Store,
+  bytes: Uint8Array,
+  onProgress?: (done: number, total: number) => void,
+  options?: MidiImportOptions,
+): Promise<MidiImportResult> {
+  const total = store.startMidiImport(bytes, options)
+  let done = 0
+  while (done < total) {
+    done = store.continueMidiImport(MIDI_IMPORT_CHUNK_EVENTS)
+    onProgress?.(done, total)
+    await new Promise((resolve) => setTimeout(resolve, 0))
+  }
+  return store.finishMidiImport()
+}
+
 export {
   Store,
+  type AudioRegion,
+  type AudioRegionUpdater,
+  type ChangeDetail,
+  type ChangeNotification,
+  type Changes,
+  type ChordQuality,
+  type ChordSymbol,
+  type ChordSymbolUpdater,
+  type Clip,
+  type CompactionReport,
+  type Conflict,
+  type ConflictReason,
+  type ControlChange,
+  type ControlChangeUpdater,
+  type CountIn,
+  type CountInOptions,
+  type CurveLane,
+  type CurvePoint,
+  type CurveShape,
+  type DemoSongOptions,
+  type DroppedOp,
+  type EffectiveEventState,
+  type EffectiveNoteEnd,
+  type EndOfSongMode,
+  type EndOfSongPolicy,
   type Event,
+  type EventFieldSchema,
+  type EventFieldType,
+  type EventFilter,
+  type EventGroup,
+  type EventHandle,
+  type EventKind,
+  type EventKindSchema,
+  type EventMeta,
+  type EventSequence,
+  type EventSummary,
+  type EventTable,
+  type EventTableColumn,
+  type EventTableOptions,
+  type EventTimestamps,
+  type EventsFilter,
+  type EventTransform,
   type EventUpdater,
+  type EventUpdaterFields,
+  type ExpressionMap,
+  type FlatNotes,
+  type Grid,
+  type GridAlignment,
+  type GridAlignOptions,
+  type Groove,
+  type GrooveStep,
+  type HumanizeOptions,
+  type IdStrategy,
+  type JsonImportOptions,
+  type KeyCandidate,
+  type KeyMode,
+  type KeySignature,
+  type KeySignatureUpdater,
+  type Keyswitch,
+  type LengthPreset,
+  type LineNote,
+  type LoopRegion,
+  type Lyric,
+  type LyricExportOptions,
+  type LyricUpdater,
+  type Marker,
+  type MarkerUpdater,
+  type Meta,
+  type MetronomeClick,
+  type MidiExportOptions,
+  type MidiImportDrift,
+  type MidiImportOptions,
+  type MidiImportResult,
+  type MidiPreview,
+  type MidiRounding,
+  type MidiTempoChange,
+  type MidiTrackMapping,
+  type MidiTrackPreview,
+  type MoveConstraints,
+  type NotationExportOptions,
   type Note,
+  type NoteCorrection,
+  type NoteOff,
+  type NoteParameters,
   type NoteUpdater,
+  type Operation,
+  type OperationRecord,
+  type PendingOps,
+  type PhraseMatch,
+  type PerformanceProfile,
+  type PitchClassInfo,
+  type PitchRange,
+  type PlaybackOptions,
+  type PlaybackRecordKind,
+  type PositionFormat,
+  type QuantizeOptions,
+  type QuotaUsage,
+  type RampCurve,
+  type RampLane,
+  type RangeCheck,
+  type RawMidi,
+  type RawMidiUpdater,
+  type RebaseReport,
+  type RecordFilter,
+  type RecordInputKind,
+  type RelativeUpdater,
+  type RenderModel,
+  type RenderTrack,
+  type RevisionChanges,
+  type Section,
+  type SongEntry,
+  type SnapshotDiff,
+  type SnapshotId,
+  type Song,
+  type SongStats,
+  type SongSummary,
+  type StoreError,
+  type StoreErrorCode,
+  type StoreOptions,
+  type StoreWarning,
+  type StrumDirection,
+  type TakeMerge,
+  type TempoEstimate,
+  type TempoEstimateOptions,
+  type TicksRange,
+  type TicksRangeOptions,
+  type TimeSignature,
+  type TimeSignatureUpdater,
+  type Track,
+  type TrackDiff,
+  type TrackDisplay,
+  type TrackExtent,
+  type TrackGroove,
+  type TrackMix,
+  type TrackSettings,
+  type TrackStats,
+  type TrackUpdater,
+  type TransformDelta,
+  type TransformPreview,
+  type TransformedEvent,
+  type TransposeOptions,
+  type Tuplet,
+  type ValidationPolicy,
+  type ValueLineField,
+  type ValuePoint,
+  type ValueRange,
+  type VelocitySplit,
+  type WarpMarker,
+  type WindowDelta,
 } from './generated/wasm/store'
-export { createStore }
+export { createStore, importMidiAsync }
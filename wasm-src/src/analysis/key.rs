@@ -0,0 +1,184 @@
+use crate::{
+    event::{event::Event, key_signature::KeyMode},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::{Song, TicksRange},
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_KEY_INTERFACES: &'static str = r#"
+export interface KeyCandidate {
+  /** Pitch class of the tonic, 0 for C to 11 for B. */
+  tonic: number;
+  mode: KeyMode;
+  /** Such as "Eb major" or "F# minor". */
+  name: string;
+  /** Correlation of the material's pitch classes with the key's profile, from -1 to 1. */
+  score: number;
+}
+"#;
+
+/// Krumhansl-Kessler probe-tone ratings of each scale degree, from the tonic up.
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+const MAJOR_TONICS: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+const MINOR_TONICS: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "G#", "A", "Bb", "B",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyCandidate {
+    pub tonic: u8,
+    pub mode: KeyMode,
+    pub score: f64,
+}
+
+impl KeyCandidate {
+    pub fn name(&self) -> String {
+        match self.mode {
+            KeyMode::Major => format!("{} major", MAJOR_TONICS[self.tonic as usize]),
+            KeyMode::Minor => format!("{} minor", MINOR_TONICS[self.tonic as usize]),
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(self) -> js_sys::Object {
+        let js_candidate = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_candidate, &JsValue::from_str(key), value).unwrap();
+        };
+
+        set("tonic", &JsValue::from_f64(self.tonic as f64));
+        set("mode", &JsValue::from_str(self.mode.as_str()));
+        set("name", &JsValue::from_str(&self.name()));
+        set("score", &JsValue::from_f64(self.score));
+
+        js_candidate
+    }
+}
+
+/// Total sounding ticks of each pitch class among the notes of `track_ids` (all tracks when
+/// `None`), counting only the part of each note inside `range` when one is given. Notes that
+/// start before the range count only with `within_duration`.
+pub fn pitch_class_durations(
+    song: &Song,
+    range: Option<TicksRange>,
+    track_ids: Option<&[Id]>,
+) -> [f64; 12] {
+    let (start, end, within_duration) = match range {
+        Some(range) => (range.start_ticks, range.end_ticks, range.within_duration),
+        None => (Ticks::new(0), Ticks::MAX, false),
+    };
+    let mut durations = [0.0; 12];
+
+    for track in song.get_tracks().iter() {
+        if track_ids.is_some_and(|track_ids| !track_ids.contains(&track.id)) {
+            continue;
+        }
+        for event in song
+            .track_events(track)
+            .in_ticks_range(start, end, within_duration)
+        {
+            let Event::Note(note) = event else {
+                continue;
+            };
+            let note_start = note.ticks.max(start).as_u64();
+            let note_end = (note.ticks + note.duration).min(end).as_u64();
+            durations[(note.note_number.as_u8() % 12) as usize] +=
+                note_end.saturating_sub(note_start) as f64;
+        }
+    }
+
+    durations
+}
+
+fn correlation(a: &[f64; 12], b: &[f64; 12]) -> f64 {
+    let mean_a = a.iter().sum::<f64>() / 12.0;
+    let mean_b = b.iter().sum::<f64>() / 12.0;
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return 0.0;
+    }
+    covariance / (variance_a * variance_b).sqrt()
+}
+
+/// Ranks all 24 major and minor keys by how well `durations` match their Krumhansl-Kessler
+/// profile, best first. Returns nothing when no pitch class sounds.
+pub fn detect_key(durations: &[f64; 12]) -> Vec<KeyCandidate> {
+    if durations.iter().all(|duration| *duration == 0.0) {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::with_capacity(24);
+    for (mode, profile) in [
+        (KeyMode::Major, &MAJOR_PROFILE),
+        (KeyMode::Minor, &MINOR_PROFILE),
+    ] {
+        for tonic in 0..12 {
+            let rotated: [f64; 12] =
+                std::array::from_fn(|pitch_class| profile[(pitch_class + 12 - tonic) % 12]);
+            candidates.push(KeyCandidate {
+                tonic: tonic as u8,
+                mode,
+                score: correlation(durations, &rotated),
+            });
+        }
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_detect_key() {
+        let (mut song, track_id) = song_with_track();
+        // An A minor line: A B C D E, leaning on A and E, with a G# leading tone.
+        for (ticks, nn, duration) in [
+            (0, 69, 960),
+            (960, 71, 240),
+            (1200, 72, 480),
+            (1680, 74, 240),
+            (1920, 76, 960),
+            (2880, 68, 240),
+            (3120, 69, 960),
+        ] {
+            add_note(&mut song, track_id, ticks, duration, nn, 100);
+        }
+        // Outside the range below.
+        add_note(&mut song, track_id, 7680, 1920, 66, 100);
+
+        let range = TicksRange {
+            start_ticks: Ticks::new(0),
+            end_ticks: Ticks::new(7680),
+            within_duration: true,
+        };
+        let durations = pitch_class_durations(&song, Some(range), None);
+        assert_eq!(durations[6], 0.0);
+
+        let candidates = detect_key(&durations);
+        assert_eq!(candidates.len(), 24);
+        assert_eq!(candidates[0].name(), "A minor");
+        assert!(candidates[0].score > candidates[1].score);
+
+        assert!(detect_key(&[0.0; 12]).is_empty());
+    }
+}
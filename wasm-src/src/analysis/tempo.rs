@@ -0,0 +1,201 @@
+use crate::{event::event::Event, shared::unit::time::Ticks};
+use serde::Deserialize;
+use std::f64::consts::TAU;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TEMPO_INTERFACES: &'static str = r#"
+export interface TempoEstimateOptions {
+  /** Fixed tempo the material was recorded against, to read its ticks as time. Defaults to 120. */
+  bpm?: number;
+  /** Slowest tempo to consider. Defaults to 60. */
+  minBpm?: number;
+  /** Fastest tempo to consider. Defaults to 200. */
+  maxBpm?: number;
+}
+
+export interface TempoEstimate {
+  bpm: number;
+  /** Ticks of the first beat of the implied grid, within one beat of the song start. */
+  offsetTicks: number;
+  /** How tightly the onsets sit on the implied grid, from 0 to 1. */
+  confidence: number;
+}
+"#;
+
+/// Onsets closer than this, in seconds, are one chord or flam and count once.
+const CHORD_SPREAD: f64 = 0.03;
+
+/// Candidate tempos are this many BPM apart.
+const BPM_STEP: f64 = 0.5;
+
+/// Tempo most performances sit near, and how quickly, in octaves, the prior falls off around it.
+const PREFERRED_BPM: f64 = 120.0;
+const PRIOR_WIDTH: f64 = 0.5;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TempoEstimateOptions {
+    pub bpm: f64,
+    pub min_bpm: f64,
+    pub max_bpm: f64,
+}
+
+impl Default for TempoEstimateOptions {
+    fn default() -> Self {
+        TempoEstimateOptions {
+            bpm: 120.0,
+            min_bpm: 60.0,
+            max_bpm: 200.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoEstimate {
+    pub bpm: f64,
+    pub offset_ticks: f64,
+    pub confidence: f64,
+}
+
+impl TempoEstimate {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(self) -> js_sys::Object {
+        let js_estimate = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_estimate,
+            &JsValue::from_str("bpm"),
+            &JsValue::from_f64(self.bpm),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_estimate,
+            &JsValue::from_str("offsetTicks"),
+            &JsValue::from_f64(self.offset_ticks.round()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_estimate,
+            &JsValue::from_str("confidence"),
+            &JsValue::from_f64(self.confidence),
+        )
+        .unwrap();
+
+        js_estimate
+    }
+}
+
+/// Note onsets of `events` in ticks order, ignoring other kinds.
+pub fn note_onsets<'a>(events: impl IntoIterator<Item = &'a Event>) -> Vec<Ticks> {
+    let mut onsets: Vec<Ticks> = events
+        .into_iter()
+        .filter_map(|event| event.as_note())
+        .map(|note| note.ticks)
+        .collect();
+    onsets.sort();
+    onsets
+}
+
+/// Sorted onsets with chords and flams merged into their first note, as fractional ticks.
+pub fn distinct_onsets(onsets: &[Ticks], ppq: u32, bpm: f64) -> Vec<f64> {
+    let ticks_per_second = ppq as f64 * bpm / 60.0;
+    let mut distinct: Vec<f64> = Vec::new();
+    for onset in onsets {
+        let ticks = onset.as_f64();
+        match distinct.last() {
+            Some(last) if (ticks - last) / ticks_per_second < CHORD_SPREAD => {}
+            _ => distinct.push(ticks),
+        }
+    }
+    distinct
+}
+
+/// How strongly the onsets agree on one phase of a grid of `period` ticks, and that phase.
+fn alignment(onsets: &[f64], period: f64, harmonic: f64) -> (f64, f64) {
+    let (sin, cos) = onsets.iter().fold((0.0, 0.0), |(sin, cos), ticks| {
+        let angle = TAU * harmonic * ticks / period;
+        (sin + angle.sin(), cos + angle.cos())
+    });
+    let count = onsets.len() as f64;
+    ((sin * sin + cos * cos).sqrt() / count, sin.atan2(cos))
+}
+
+/// Proposes the tempo of freely played material from its onsets.
+///
+/// Each candidate tempo is scored by how well the onsets line up with its beats, eighths and
+/// sixteenths, weighted towards tempos near 120 BPM to settle half and double time. Returns
+/// `None` with fewer than two distinct onsets.
+pub fn estimate_tempo(
+    onsets: &[Ticks],
+    ppq: u32,
+    options: &TempoEstimateOptions,
+) -> Option<TempoEstimate> {
+    let distinct = distinct_onsets(onsets, ppq, options.bpm);
+    if distinct.len() < 2 {
+        return None;
+    }
+
+    let mut best: Option<(f64, TempoEstimate)> = None;
+    let mut bpm = options.min_bpm;
+    while bpm <= options.max_bpm {
+        let period = ppq as f64 * options.bpm / bpm;
+        let (beat, phase) = alignment(&distinct, period, 1.0);
+        let (eighth, _) = alignment(&distinct, period, 2.0);
+        let (sixteenth, _) = alignment(&distinct, period, 4.0);
+        let fit = (beat + eighth + sixteenth) / 3.0;
+        let octaves = (bpm / PREFERRED_BPM).log2() / PRIOR_WIDTH;
+        let score = fit * (-0.5 * octaves * octaves).exp();
+
+        if best.is_none_or(|(best_score, _)| score > best_score) {
+            best = Some((
+                score,
+                TempoEstimate {
+                    bpm,
+                    offset_ticks: phase.rem_euclid(TAU) / TAU * period,
+                    confidence: fit,
+                },
+            ));
+        }
+        bpm += BPM_STEP;
+    }
+
+    best.map(|(_, estimate)| estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tempo() {
+        // 100 BPM played against a 120 BPM clock: a beat lasts 576 ticks at 480 ppq.
+        let jitter = [0, 6, -4, 3, -7, 2, 5, -3, 0, 4, -5, 1];
+        let mut onsets: Vec<Ticks> = jitter
+            .iter()
+            .enumerate()
+            .map(|(beat, jitter)| Ticks::new((100 + beat as i32 * 576 + jitter) as u64))
+            .collect();
+        // Off-beat eighths and a chord should not pull the estimate to double time.
+        onsets.extend([388, 1540, 2692, 3268, 105].map(Ticks::new));
+        onsets.sort();
+
+        let estimate = estimate_tempo(&onsets, 480, &TempoEstimateOptions::default()).unwrap();
+        assert!((estimate.bpm - 100.0).abs() <= 1.0, "{}", estimate.bpm);
+        assert!(
+            (estimate.offset_ticks - 100.0).abs() <= 10.0,
+            "{}",
+            estimate.offset_ticks
+        );
+        assert!(estimate.confidence > 0.5);
+
+        assert_eq!(
+            estimate_tempo(&onsets[..1], 480, &TempoEstimateOptions::default()),
+            None
+        );
+    }
+}
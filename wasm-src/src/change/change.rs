@@ -0,0 +1,617 @@
+use crate::{
+    event::event::Event,
+    shared::{id::Id, js::string_enum},
+    track::track::Track,
+};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CHANGE_INTERFACES: &'static str = r#"
+export interface EventSummary {
+  id: string;
+  kind: Event["kind"];
+  /** Null for markers, which belong to the song. */
+  trackId: string | null;
+  ticks: number;
+  duration?: number;
+}
+
+export type ChangeNotification<T = string | EventSummary | Event> =
+  | { type: "eventAdded" | "eventUpdated" | "eventRemoved"; payload: T }
+  | { type: "trackAdded" | "trackUpdated" | "trackRemoved"; payload: string };
+
+export interface Changes<T = string | EventSummary | Event> {
+  seq: number;
+  origin?: string;
+  added: T[];
+  updated: T[];
+  removed: T[];
+  addedTracks: string[];
+  /** Tracks whose own properties, such as their output or place in the track list, changed. */
+  updatedTracks: string[];
+  removedTracks: string[];
+  /** The same changes as one list, with added and updated tracks first and removed tracks last. */
+  notifications: ChangeNotification<T>[];
+}
+"#;
+
+string_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ChangeDetail {
+        Ids = "ids",
+        Summary = "summary",
+        Full = "full",
+    }
+}
+
+/// A single mutation recorded by the song. Each variant carries enough state to be replayed or
+/// reverted; tracks are recorded without their events, which get changes of their own.
+#[derive(Debug, Clone)]
+pub enum Change {
+    EventAdded(Event),
+    EventUpdated {
+        before: Event,
+        after: Event,
+    },
+    EventRemoved(Event),
+    TrackAdded(Track),
+    TrackUpdated {
+        before: Box<Track>,
+        after: Box<Track>,
+    },
+    TrackRemoved(Track),
+    /// The track moved from index `from` to index `to` of the track list.
+    TrackMoved {
+        track_id: Id,
+        from: usize,
+        to: usize,
+    },
+}
+
+impl Change {
+    /// Whether the change adds, edits or removes the track or one of its events. An event moved
+    /// between tracks touches both.
+    pub fn touches_track(&self, track_id: &Id) -> bool {
+        self.track_ids().contains(track_id)
+    }
+
+    /// The tracks the change touches, see `touches_track`.
+    pub fn track_ids(&self) -> Vec<Id> {
+        match self {
+            Change::EventAdded(event) | Change::EventRemoved(event) => {
+                event.get_track_id().into_iter().collect()
+            }
+            Change::EventUpdated { before, after } => {
+                let mut track_ids: Vec<Id> = before.get_track_id().into_iter().collect();
+                if after.get_track_id() != before.get_track_id() {
+                    track_ids.extend(after.get_track_id());
+                }
+                track_ids
+            }
+            Change::TrackAdded(track) | Change::TrackRemoved(track) => vec![track.id],
+            Change::TrackUpdated { after, .. } => vec![after.id],
+            Change::TrackMoved { track_id, .. } => vec![*track_id],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EntryState {
+    Added,
+    Updated,
+    Removed,
+    Gone,
+}
+
+/// One entry of a changeset as a flat list of notifications.
+#[derive(Debug, PartialEq)]
+pub enum Notification<'a> {
+    TrackAdded(Id),
+    TrackUpdated(Id),
+    EventAdded(&'a Event),
+    EventUpdated(&'a Event),
+    EventRemoved(&'a Event),
+    TrackRemoved(Id),
+}
+
+impl Notification<'_> {
+    #[cfg(feature = "wasm")]
+    fn type_name(&self) -> &'static str {
+        match self {
+            Notification::TrackAdded(_) => "trackAdded",
+            Notification::TrackUpdated(_) => "trackUpdated",
+            Notification::EventAdded(_) => "eventAdded",
+            Notification::EventUpdated(_) => "eventUpdated",
+            Notification::EventRemoved(_) => "eventRemoved",
+            Notification::TrackRemoved(_) => "trackRemoved",
+        }
+    }
+}
+
+/// The net effect of a batch of changes: an event touched several times appears once, in its
+/// latest state, and an event added then removed within the batch does not appear at all.
+#[derive(Debug, Default)]
+pub struct Changeset {
+    pub added: Vec<Event>,
+    pub updated: Vec<Event>,
+    pub removed: Vec<Event>,
+    pub added_tracks: Vec<Id>,
+    pub updated_tracks: Vec<Id>,
+    pub removed_tracks: Vec<Id>,
+}
+
+impl Changeset {
+    pub fn from_changes(changes: &[Change]) -> Self {
+        let mut entries: Vec<(EntryState, &Event)> = Vec::new();
+        let mut positions: HashMap<Id, usize> = HashMap::new();
+        let mut tracks: Vec<(Id, EntryState)> = Vec::new();
+
+        for change in changes {
+            let (state, event) = match change {
+                Change::EventAdded(event) => (EntryState::Added, event),
+                Change::EventUpdated { after, .. } => (EntryState::Updated, after),
+                Change::EventRemoved(event) => (EntryState::Removed, event),
+                Change::TrackAdded(track) => {
+                    tracks.retain(|(id, _)| *id != track.id);
+                    tracks.push((track.id, EntryState::Added));
+                    continue;
+                }
+                Change::TrackUpdated { after, .. } => {
+                    if !tracks.iter().any(|(id, _)| *id == after.id) {
+                        tracks.push((after.id, EntryState::Updated));
+                    }
+                    continue;
+                }
+                Change::TrackMoved { track_id, .. } => {
+                    if !tracks.iter().any(|(id, _)| id == track_id) {
+                        tracks.push((*track_id, EntryState::Updated));
+                    }
+                    continue;
+                }
+                Change::TrackRemoved(track) => {
+                    let added_in_batch = tracks
+                        .iter()
+                        .any(|(id, state)| *id == track.id && *state == EntryState::Added);
+                    tracks.retain(|(id, _)| *id != track.id);
+                    if !added_in_batch {
+                        tracks.push((track.id, EntryState::Removed));
+                    }
+                    continue;
+                }
+            };
+
+            let Some(&position) = positions.get(&event.get_id()) else {
+                positions.insert(event.get_id(), entries.len());
+                entries.push((state, event));
+                continue;
+            };
+
+            let previous = entries[position].0;
+            let state = match (previous, state) {
+                (EntryState::Added, EntryState::Removed) => EntryState::Gone,
+                (EntryState::Added, _) => EntryState::Added,
+                (EntryState::Gone, EntryState::Added) => EntryState::Added,
+                (EntryState::Removed, EntryState::Added) => EntryState::Updated,
+                (_, state) => state,
+            };
+            entries[position] = (state, event);
+        }
+
+        let mut changeset = Changeset::default();
+
+        for (state, event) in entries {
+            match state {
+                EntryState::Added => changeset.added.push(event.clone()),
+                EntryState::Updated => changeset.updated.push(event.clone()),
+                EntryState::Removed => changeset.removed.push(event.clone()),
+                EntryState::Gone => {}
+            }
+        }
+
+        for (track_id, state) in tracks {
+            match state {
+                EntryState::Added => changeset.added_tracks.push(track_id),
+                EntryState::Updated => changeset.updated_tracks.push(track_id),
+                _ => changeset.removed_tracks.push(track_id),
+            }
+        }
+
+        changeset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.added_tracks.is_empty()
+            && self.updated_tracks.is_empty()
+            && self.removed_tracks.is_empty()
+    }
+
+    /// The events added to the track, in their latest state, as `subscribeToRecordedEvents`
+    /// delivers them.
+    pub fn added_to_track(&self, track_id: Id) -> Vec<&Event> {
+        self.added
+            .iter()
+            .filter(|event| event.get_track_id() == Some(track_id))
+            .collect()
+    }
+
+    /// The changeset as one list: added tracks first so their events have somewhere to go,
+    /// then updated tracks, then added, updated and removed events, then removed tracks.
+    pub fn notifications(&self) -> Vec<Notification<'_>> {
+        let added_tracks = self
+            .added_tracks
+            .iter()
+            .copied()
+            .map(Notification::TrackAdded);
+        let updated_tracks = self
+            .updated_tracks
+            .iter()
+            .copied()
+            .map(Notification::TrackUpdated);
+        let added = self.added.iter().map(Notification::EventAdded);
+        let updated = self.updated.iter().map(Notification::EventUpdated);
+        let removed = self.removed.iter().map(Notification::EventRemoved);
+        let removed_tracks = self
+            .removed_tracks
+            .iter()
+            .copied()
+            .map(Notification::TrackRemoved);
+
+        added_tracks
+            .chain(updated_tracks)
+            .chain(added)
+            .chain(updated)
+            .chain(removed)
+            .chain(removed_tracks)
+            .collect()
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, detail: ChangeDetail) -> js_sys::Object {
+        let js_changes = js_sys::Object::new();
+
+        let event_to_js = |event: &Event| -> JsValue {
+            match detail {
+                ChangeDetail::Ids => JsValue::from_str(&event.get_id().to_string()),
+                ChangeDetail::Summary => summary_to_js_object(event).into(),
+                ChangeDetail::Full => event.to_js_object().into(),
+            }
+        };
+
+        let events_to_js =
+            |events: &[Event]| -> js_sys::Array { events.iter().map(event_to_js).collect() };
+
+        let ids_to_js = |ids: &[Id]| -> js_sys::Array {
+            ids.iter()
+                .map(|id| JsValue::from_str(&id.to_string()))
+                .collect()
+        };
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("added"),
+            &events_to_js(&self.added),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("updated"),
+            &events_to_js(&self.updated),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("removed"),
+            &events_to_js(&self.removed),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("addedTracks"),
+            &ids_to_js(&self.added_tracks),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("updatedTracks"),
+            &ids_to_js(&self.updated_tracks),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("removedTracks"),
+            &ids_to_js(&self.removed_tracks),
+        )
+        .unwrap();
+
+        let notifications: js_sys::Array = self
+            .notifications()
+            .iter()
+            .map(|notification| {
+                let payload = match notification {
+                    Notification::TrackAdded(track_id)
+                    | Notification::TrackUpdated(track_id)
+                    | Notification::TrackRemoved(track_id) => {
+                        JsValue::from_str(&track_id.to_string())
+                    }
+                    Notification::EventAdded(event)
+                    | Notification::EventUpdated(event)
+                    | Notification::EventRemoved(event) => event_to_js(event),
+                };
+                let js_notification = js_sys::Object::new();
+                js_sys::Reflect::set(
+                    &js_notification,
+                    &JsValue::from_str("type"),
+                    &JsValue::from_str(notification.type_name()),
+                )
+                .unwrap();
+                js_sys::Reflect::set(&js_notification, &JsValue::from_str("payload"), &payload)
+                    .unwrap();
+                js_notification
+            })
+            .collect();
+
+        js_sys::Reflect::set(
+            &js_changes,
+            &JsValue::from_str("notifications"),
+            &notifications,
+        )
+        .unwrap();
+
+        js_changes
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn summary_to_js_object(event: &Event) -> js_sys::Object {
+    let js_summary = js_sys::Object::new();
+
+    js_sys::Reflect::set(
+        &js_summary,
+        &JsValue::from_str("id"),
+        &JsValue::from_str(&event.get_id().to_string()),
+    )
+    .unwrap();
+
+    js_sys::Reflect::set(
+        &js_summary,
+        &JsValue::from_str("kind"),
+        &JsValue::from_str(&event.get_kind().to_string()),
+    )
+    .unwrap();
+
+    js_sys::Reflect::set(
+        &js_summary,
+        &JsValue::from_str("trackId"),
+        &event.get_track_id().map_or(JsValue::NULL, |track_id| {
+            JsValue::from_str(&track_id.to_string())
+        }),
+    )
+    .unwrap();
+
+    js_sys::Reflect::set(
+        &js_summary,
+        &JsValue::from_str("ticks"),
+        &JsValue::from_f64(event.get_ticks().as_f64()),
+    )
+    .unwrap();
+
+    if let Some(duration) = event.get_duration() {
+        js_sys::Reflect::set(
+            &js_summary,
+            &JsValue::from_str("duration"),
+            &JsValue::from_f64(duration.as_f64()),
+        )
+        .unwrap();
+    }
+
+    js_summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{
+            event::EventUpdater,
+            note::{NoteNumber, NoteUpdater, Velocity},
+        },
+        operation::{
+            operation::{apply_operations, Operation},
+            transaction::Transaction,
+        },
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_changeset_coalesces_changes() {
+        let (mut song, track_id) = song_with_track();
+        let kept = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.take_changes();
+
+        let added = add_note(&mut song, track_id, 480, 480, 62, 100);
+        song.update_event(EventUpdater::Note(NoteUpdater {
+            id: added,
+            ticks: Some(Ticks::new(960)),
+            duration: None,
+            velocity: None,
+            note_number: None,
+            track_id: None,
+            parameters: None,
+        }))
+        .unwrap();
+        let transient = add_note(&mut song, track_id, 0, 240, 64, 100);
+        song.remove_event(&transient);
+        song.update_event(EventUpdater::Note(NoteUpdater {
+            id: kept,
+            ticks: None,
+            duration: None,
+            velocity: None,
+            note_number: Some(NoteNumber::new(61)),
+            track_id: None,
+            parameters: None,
+        }))
+        .unwrap();
+
+        let changeset = Changeset::from_changes(&song.take_changes());
+
+        assert_eq!(changeset.added.len(), 1);
+        assert_eq!(changeset.added[0].get_id(), added);
+        assert_eq!(changeset.added[0].get_ticks(), Ticks::new(960));
+        assert_eq!(changeset.updated.len(), 1);
+        assert_eq!(changeset.updated[0].get_id(), kept);
+        assert!(changeset.removed.is_empty());
+        assert!(song.take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_changeset_added_to_track() {
+        let (mut song, track_id) = song_with_track();
+        let other_track_id = song.add_track(Track::new(Id::new())).id;
+        let kept = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.take_changes();
+
+        let played = add_note(&mut song, track_id, 480, 240, 62, 100);
+        add_note(&mut song, other_track_id, 480, 240, 64, 100);
+        let moved = add_note(&mut song, other_track_id, 960, 240, 65, 100);
+        song.update_event(EventUpdater::Note(NoteUpdater {
+            id: moved,
+            ticks: None,
+            duration: None,
+            velocity: None,
+            note_number: None,
+            track_id: Some(track_id),
+            parameters: None,
+        }))
+        .unwrap();
+        song.update_event(EventUpdater::Note(NoteUpdater {
+            id: kept,
+            ticks: None,
+            duration: None,
+            velocity: Some(Velocity::new(80)),
+            note_number: None,
+            track_id: None,
+            parameters: None,
+        }))
+        .unwrap();
+
+        let changeset = Changeset::from_changes(&song.take_changes());
+        let added: Vec<Id> = changeset
+            .added_to_track(track_id)
+            .iter()
+            .map(|event| event.get_id())
+            .collect();
+        assert_eq!(added, vec![played, moved]);
+        assert_eq!(changeset.added_to_track(other_track_id).len(), 1);
+        assert!(changeset.added_to_track(Id::new()).is_empty());
+    }
+
+    #[test]
+    fn test_transaction_notifications_in_order() {
+        let (mut song, track_id) = song_with_track();
+        let removed_track_id = song.add_track(Track::new(Id::new())).id;
+        let kept = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let removed = add_note(&mut song, track_id, 480, 480, 62, 100);
+        song.commit(None);
+
+        let mut transaction = Transaction::begin(&song);
+        let added_track_id = song.add_track(Track::new(Id::new())).id;
+        add_note(&mut song, added_track_id, 0, 240, 64, 100);
+        transaction.defer(&mut song);
+        song.update_event(EventUpdater::Note(NoteUpdater {
+            id: kept,
+            ticks: None,
+            duration: None,
+            velocity: Some(Velocity::new(80)),
+            note_number: None,
+            track_id: None,
+            parameters: None,
+        }))
+        .unwrap();
+        song.remove_event(&removed);
+        transaction.defer(&mut song);
+        song.remove_track(&removed_track_id);
+        song.update_track(&track_id, |track| track.output = Some("port-1".to_string()));
+        transaction.commit(&mut song);
+        song.commit(None);
+
+        let entries = song.get_journal().entries_since(1);
+        assert_eq!(entries.len(), 1);
+        let changeset = entries[0].to_changeset();
+        assert_eq!(
+            changeset.notifications(),
+            vec![
+                Notification::TrackAdded(added_track_id),
+                Notification::TrackUpdated(track_id),
+                Notification::EventAdded(&changeset.added[0]),
+                Notification::EventUpdated(&changeset.updated[0]),
+                Notification::EventRemoved(&changeset.removed[0]),
+                Notification::TrackRemoved(removed_track_id),
+            ]
+        );
+        assert_eq!(changeset.updated[0].get_id(), kept);
+        assert_eq!(changeset.removed[0].get_id(), removed);
+    }
+
+    #[test]
+    fn test_changeset_reports_track_removal() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.take_changes();
+
+        song.remove_track(&track_id);
+
+        let changeset = Changeset::from_changes(&song.take_changes());
+        assert_eq!(changeset.removed.len(), 1);
+        assert_eq!(changeset.removed[0].get_id(), note_id);
+        assert_eq!(changeset.removed_tracks, vec![track_id]);
+        assert!(changeset.added_tracks.is_empty());
+        assert_eq!(
+            changeset.notifications(),
+            vec![
+                Notification::EventRemoved(&changeset.removed[0]),
+                Notification::TrackRemoved(track_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changeset_reports_track_update() {
+        let (mut song, track_id) = song_with_track();
+        song.take_changes();
+
+        let set_output = |output: &str| {
+            let output = Some(output.to_string());
+            move |track: &mut Track| track.output = output
+        };
+        song.update_track(&track_id, set_output("port-1"));
+        song.update_track(&track_id, set_output("port-2"));
+        let changes = song.take_changes();
+        let changeset = Changeset::from_changes(&changes);
+        assert_eq!(changeset.updated_tracks, vec![track_id]);
+        assert_eq!(
+            changeset.notifications(),
+            vec![Notification::TrackUpdated(track_id)]
+        );
+
+        let undo = changes.iter().rev().map(Operation::inverse_of).collect();
+        assert!(apply_operations(&mut song, undo).is_empty());
+        assert_eq!(song.get_track(&track_id).unwrap().output, None);
+
+        // Setting the same output again records nothing.
+        song.take_changes();
+        song.update_track(&track_id, |track| track.output = None);
+        assert!(song.take_changes().is_empty());
+    }
+}
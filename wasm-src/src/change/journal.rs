@@ -0,0 +1,98 @@
+#[cfg(feature = "wasm")]
+use super::change::ChangeDetail;
+use super::change::{Change, Changeset};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+/// The changes made by one mutating call, tagged with where they came from.
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub origin: Option<String>,
+    pub changes: Vec<Change>,
+}
+
+impl JournalEntry {
+    pub fn to_changeset(&self) -> Changeset {
+        Changeset::from_changes(&self.changes)
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, detail: ChangeDetail) -> js_sys::Object {
+        let js_entry = self.to_changeset().to_js_object(detail);
+
+        js_sys::Reflect::set(
+            &js_entry,
+            &JsValue::from_str("seq"),
+            &JsValue::from_f64(self.seq as f64),
+        )
+        .unwrap();
+
+        if let Some(origin) = &self.origin {
+            js_sys::Reflect::set(
+                &js_entry,
+                &JsValue::from_str("origin"),
+                &JsValue::from_str(origin),
+            )
+            .unwrap();
+        }
+
+        js_entry
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+    next_seq: u64,
+}
+
+impl Journal {
+    pub fn record(&mut self, origin: Option<String>, changes: Vec<Change>) -> &JournalEntry {
+        self.next_seq += 1;
+        self.entries.push(JournalEntry {
+            seq: self.next_seq,
+            origin,
+            changes,
+        });
+        self.entries.last().unwrap()
+    }
+
+    /// The seq of the latest entry, 0 before the first.
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    pub fn last(&self) -> Option<&JournalEntry> {
+        self.entries.last()
+    }
+
+    /// Entries recorded after `seq`, oldest first.
+    pub fn entries_since(&self, seq: u64) -> &[JournalEntry] {
+        let start = self.entries.partition_point(|entry| entry.seq <= seq);
+        &self.entries[start..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_journal_records_origin() {
+        let (mut song, track_id) = song_with_track();
+        song.commit(Some("import".to_string()));
+
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        let entry = song.commit(Some("user-1".to_string())).unwrap();
+        assert_eq!(entry.seq, 2);
+        assert_eq!(entry.to_changeset().added.len(), 1);
+
+        assert!(song.commit(None).is_none());
+
+        let since = song.get_journal().entries_since(1);
+        assert_eq!(since.len(), 1);
+        assert_eq!(since[0].origin.as_deref(), Some("user-1"));
+        assert_eq!(song.get_journal().entries_since(0).len(), 2);
+    }
+}
@@ -0,0 +1,180 @@
+use super::{change::Change, journal::JournalEntry};
+use crate::{
+    event::event::Event,
+    shared::id::Id,
+    song::{groove::Groove, song::Song},
+    track::track::Track,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum MirrorOp {
+    /// Replaces the replica's song with an empty one.
+    Reset {
+        title: String,
+        ppq: u32,
+        bpm: f64,
+    },
+    PutEvent(Event),
+    RemoveEvent(Id),
+    /// A track without its events, which follow as events of their own.
+    AddTrack(Track),
+    UpdateTrack(Track),
+    MoveTrack {
+        track_id: Id,
+        index: usize,
+    },
+    RemoveTrack(Id),
+    PutGroove(Groove),
+}
+
+impl MirrorOp {
+    fn from_change(change: &Change) -> Self {
+        match change {
+            Change::EventAdded(event) => MirrorOp::PutEvent(event.clone()),
+            Change::EventUpdated { after, .. } => MirrorOp::PutEvent(after.clone()),
+            Change::EventRemoved(event) => MirrorOp::RemoveEvent(event.get_id()),
+            Change::TrackAdded(track) => MirrorOp::AddTrack(track.clone()),
+            Change::TrackUpdated { after, .. } => MirrorOp::UpdateTrack((**after).clone()),
+            Change::TrackRemoved(track) => MirrorOp::RemoveTrack(track.id),
+            Change::TrackMoved { track_id, to, .. } => MirrorOp::MoveTrack {
+                track_id: *track_id,
+                index: *to,
+            },
+        }
+    }
+}
+
+/// One committed changeset in the compact form sent from a store to its replicas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirrorFrame {
+    pub origin: Option<String>,
+    ops: Vec<MirrorOp>,
+}
+
+impl MirrorFrame {
+    /// A frame that rebuilds `song` from scratch on the replica.
+    pub fn snapshot(song: &Song) -> Self {
+        let mut ops = vec![MirrorOp::Reset {
+            title: song.title.clone(),
+            ppq: song.ppq,
+            bpm: song.bpm,
+        }];
+        for groove in song.grooves.iter() {
+            ops.push(MirrorOp::PutGroove(groove.clone()));
+        }
+        for track in song.get_tracks().iter() {
+            ops.push(MirrorOp::AddTrack(track.without_events()));
+        }
+        for event in song.get_events(None) {
+            ops.push(MirrorOp::PutEvent(event.clone()));
+        }
+
+        MirrorFrame { origin: None, ops }
+    }
+
+    pub fn from_entry(entry: &JournalEntry) -> Self {
+        MirrorFrame {
+            origin: entry.origin.clone(),
+            ops: entry.changes.iter().map(MirrorOp::from_change).collect(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).unwrap()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+
+    /// Replays the frame onto `song`, creating the song first if the frame starts with a reset.
+    pub fn apply(&self, song: &mut Option<Song>) {
+        for op in &self.ops {
+            if let MirrorOp::Reset { title, ppq, bpm } = op {
+                let mut new_song = Song::new(title.clone(), *ppq);
+                new_song.bpm = *bpm;
+                *song = Some(new_song);
+                continue;
+            }
+
+            let Some(song) = song.as_mut() else {
+                continue;
+            };
+
+            match op {
+                MirrorOp::PutEvent(event) => {
+                    // like a removal of a missing event, an event of a missing track is skipped
+                    let _ = if song.get_event(&event.get_id()).is_some() {
+                        song.replace_event(event.clone())
+                    } else {
+                        song.add_event(event.clone())
+                    };
+                }
+                MirrorOp::RemoveEvent(event_id) => {
+                    if song.get_event(event_id).is_some() {
+                        song.remove_event(event_id);
+                    }
+                }
+                MirrorOp::AddTrack(track) => {
+                    song.add_track(track.clone());
+                }
+                MirrorOp::UpdateTrack(track) => {
+                    song.update_track(&track.id, |current| current.set_properties(track));
+                }
+                MirrorOp::MoveTrack { track_id, index } => {
+                    song.move_track(track_id, *index);
+                }
+                MirrorOp::RemoveTrack(track_id) => song.remove_track(track_id),
+                MirrorOp::PutGroove(groove) => song.grooves.put(groove.clone()),
+                MirrorOp::Reset { .. } => unreachable!(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_mirror_frames_keep_replica_in_sync() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.commit(None);
+
+        let mut replica = None;
+        let snapshot = MirrorFrame::snapshot(&song).to_bytes();
+        MirrorFrame::from_bytes(&snapshot)
+            .unwrap()
+            .apply(&mut replica);
+
+        let moved_id = add_note(&mut song, track_id, 480, 480, 62, 100);
+        let moved = song.get_event(&moved_id).unwrap().clone();
+        song.replace_event(moved.with_ticks(Ticks::new(960)))
+            .unwrap();
+        let entry = song.commit(Some("worker".to_string())).unwrap();
+        let frame = MirrorFrame::from_bytes(&MirrorFrame::from_entry(entry).to_bytes()).unwrap();
+        assert_eq!(frame.origin.as_deref(), Some("worker"));
+        frame.apply(&mut replica);
+
+        let replica = replica.unwrap();
+        assert_eq!(replica.get_tracks().len(), 1);
+        assert_eq!(replica.get_events(None).len(), 2);
+        for event in song.get_events(None) {
+            assert_eq!(replica.get_event(&event.get_id()), Some(event));
+        }
+        assert_eq!(
+            replica
+                .get_track_events_in_ticks_range(&track_id, Ticks::new(960), Ticks::new(961), false)
+                .iter()
+                .map(|event| event.get_id())
+                .collect::<Vec<_>>(),
+            vec![moved_id]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod change;
+pub mod journal;
+pub mod mirror;
+pub mod revision;
@@ -0,0 +1,143 @@
+use super::{change::Changeset, journal::Journal};
+use crate::{
+    event::event::{Event, JsEvent},
+    shared::id::Id,
+    song::song::Song,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_REVISION_INTERFACES: &'static str = r#"
+/** What changed in the song since a revision, see `Store.getEventsChangedSince`. */
+export interface RevisionChanges {
+  /** The revision the changes lead up to, to pass on the next call. */
+  revision: number;
+  /** Events added or updated since, as they are now. */
+  changed: Event[];
+  removedIds: string[];
+}
+"#;
+
+/// Revision numbers of a song and its tracks, counted by the store across every song it has
+/// held so a number is never reused.
+///
+/// Each journal entry is one revision, so revisions map onto journal seqs from the point the
+/// song was last made the store's song, its base; anything before the base is unknown.
+#[derive(Debug, Clone, Default)]
+pub struct Revisions {
+    base_revision: u64,
+    base_seq: u64,
+    tracks: HashMap<Id, u64>,
+}
+
+impl Revisions {
+    /// Starts counting again from `revision`, as of the journal's latest entry.
+    pub fn rebase(&mut self, revision: u64, journal: &Journal) {
+        self.base_revision = revision;
+        self.base_seq = journal.last_seq();
+        self.tracks.clear();
+    }
+
+    /// The revision of the journal entry `seq`.
+    fn of_seq(&self, seq: u64) -> u64 {
+        self.base_revision + (seq - self.base_seq)
+    }
+
+    pub fn current(&self, journal: &Journal) -> u64 {
+        self.of_seq(journal.last_seq())
+    }
+
+    /// The revision of the latest entry touching the track, or the base if none did since.
+    pub fn of_track(&self, track_id: &Id) -> u64 {
+        self.tracks
+            .get(track_id)
+            .copied()
+            .unwrap_or(self.base_revision)
+    }
+
+    /// Marks the tracks touched by the journal's latest entry as changed in its revision.
+    pub fn record(&mut self, journal: &Journal) {
+        let Some(entry) = journal.last() else {
+            return;
+        };
+        let revision = self.of_seq(entry.seq);
+        for change in &entry.changes {
+            for track_id in change.track_ids() {
+                self.tracks.insert(track_id, revision);
+            }
+        }
+    }
+
+    /// The net changes made after `revision`, or `None` when it is from before the base.
+    pub fn changes_since(&self, revision: u64, journal: &Journal) -> Option<Changeset> {
+        let seq = self.base_seq + revision.checked_sub(self.base_revision)?;
+        let changes: Vec<_> = journal
+            .entries_since(seq)
+            .iter()
+            .flat_map(|entry| entry.changes.iter().cloned())
+            .collect();
+        Some(Changeset::from_changes(&changes))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionChanges<'a> {
+    revision: u64,
+    changed: Vec<JsEvent<'a>>,
+    removed_ids: Vec<Id>,
+}
+
+impl<'a> RevisionChanges<'a> {
+    pub fn new(song: &Song, changeset: &'a Changeset) -> Self {
+        RevisionChanges {
+            revision: song.get_revision(),
+            changed: changeset
+                .added
+                .iter()
+                .chain(&changeset.updated)
+                .map(JsEvent::from)
+                .collect(),
+            removed_ids: changeset.removed.iter().map(Event::get_id).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        event::event::Event,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_revisions() {
+        let (mut song, track_id) = song_with_track();
+        song.commit(None);
+        song.rebase_revisions(10);
+        assert_eq!(song.get_revision(), 10);
+        assert_eq!(song.get_track_revision(&track_id), 10);
+
+        let first = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.commit(None);
+        let second = add_note(&mut song, track_id, 480, 480, 62, 100);
+        song.remove_event(&first);
+        song.commit(None);
+        assert_eq!(song.get_revision(), 12);
+        assert_eq!(song.get_track_revision(&track_id), 12);
+
+        let since = song.get_changes_since_revision(11).unwrap();
+        let ids = |events: &[Event]| events.iter().map(Event::get_id).collect::<Vec<_>>();
+        assert_eq!(ids(&since.added), vec![second]);
+        assert_eq!(ids(&since.removed), vec![first]);
+        let since = song.get_changes_since_revision(10).unwrap();
+        assert_eq!(ids(&since.added), vec![second]);
+        assert!(since.removed.is_empty());
+        assert!(song.get_changes_since_revision(12).unwrap().is_empty());
+        assert!(song.get_changes_since_revision(9).is_none());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_AUDIO_REGION_INTERFACES: &'static str = r#"
+/** Pins `seconds` into the source file to `ticks` from the start of the region. */
+export interface WarpMarker {
+  ticks: number;
+  seconds: number;
+}
+
+/**
+ * A reference to audio played elsewhere. `source` is an opaque file reference and
+ * `startOffset` the position in the file, in seconds, heard at `ticks`.
+ */
+export interface AudioRegion {
+  id: string;
+  kind: "AudioRegion";
+  ticks: number;
+  duration: number;
+  source: string;
+  startOffset: number;
+  warpMarkers: WarpMarker[];
+  trackId: string;
+}
+
+export interface AudioRegionUpdater {
+  id: string;
+  kind: "AudioRegion";
+  ticks?: number;
+  duration?: number;
+  source?: string;
+  startOffset?: number;
+  warpMarkers?: WarpMarker[];
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WarpMarker {
+    pub ticks: Ticks,
+    pub seconds: f64,
+}
+
+#[cfg(feature = "wasm")]
+fn warp_markers_to_js(warp_markers: &[WarpMarker]) -> js_sys::Array {
+    warp_markers
+        .iter()
+        .map(|marker| {
+            let js_marker = js_sys::Object::new();
+            js_sys::Reflect::set(
+                &js_marker,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(marker.ticks.as_f64()),
+            )
+            .unwrap();
+            js_sys::Reflect::set(
+                &js_marker,
+                &JsValue::from_str("seconds"),
+                &JsValue::from_f64(marker.seconds),
+            )
+            .unwrap();
+            js_marker
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioRegion {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub duration: Ticks,
+    pub source: String,
+    #[serde(default)]
+    pub start_offset: f64,
+    #[serde(default)]
+    pub warp_markers: Vec<WarpMarker>,
+    pub track_id: Id,
+}
+
+impl AudioRegion {
+    /// The region with its warp markers in ticks order, as given ones may not be.
+    pub fn normalized(mut self) -> Self {
+        self.warp_markers.sort_by_key(|marker| marker.ticks);
+        self
+    }
+
+    pub fn clone_with_updater(&self, updater: AudioRegionUpdater) -> Self {
+        AudioRegion {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            duration: updater.duration.unwrap_or(self.duration),
+            source: updater.source.unwrap_or_else(|| self.source.clone()),
+            start_offset: updater.start_offset.unwrap_or(self.start_offset),
+            warp_markers: updater
+                .warp_markers
+                .unwrap_or_else(|| self.warp_markers.clone()),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioRegionUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub duration: Option<Ticks>,
+    pub source: Option<String>,
+    pub start_offset: Option<f64>,
+    pub warp_markers: Option<Vec<WarpMarker>>,
+    pub track_id: Option<Id>,
+}
+
+impl From<&AudioRegion> for AudioRegionUpdater {
+    /// An updater that sets every field to the region's current value.
+    fn from(region: &AudioRegion) -> Self {
+        AudioRegionUpdater {
+            id: region.id,
+            ticks: Some(region.ticks),
+            duration: Some(region.duration),
+            source: Some(region.source.clone()),
+            start_offset: Some(region.start_offset),
+            warp_markers: Some(region.warp_markers.clone()),
+            track_id: Some(region.track_id),
+        }
+    }
+}
+
+impl AudioRegionUpdater {
+    pub fn normalized(mut self) -> Self {
+        if let Some(warp_markers) = &mut self.warp_markers {
+            warp_markers.sort_by_key(|marker| marker.ticks);
+        }
+        self
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("AudioRegion"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(duration) = self.duration {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("duration"),
+                &JsValue::from_f64(duration.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(source) = &self.source {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("source"),
+                &JsValue::from_str(source),
+            )
+            .unwrap();
+        }
+
+        if let Some(start_offset) = self.start_offset {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("startOffset"),
+                &JsValue::from_f64(start_offset),
+            )
+            .unwrap();
+        }
+
+        if let Some(warp_markers) = &self.warp_markers {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("warpMarkers"),
+                &warp_markers_to_js(warp_markers),
+            )
+            .unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        change::mirror::MirrorFrame, event::event::Event, transform::test_helpers::song_with_track,
+    };
+
+    #[test]
+    fn test_audio_region_round_trips_through_mirror() {
+        let (mut song, track_id) = song_with_track();
+        let region = AudioRegion {
+            id: Id::new(),
+            ticks: Ticks::new(960),
+            duration: Ticks::new(1920),
+            source: "takes/vocal-03.wav".to_string(),
+            start_offset: 1.25,
+            warp_markers: vec![
+                WarpMarker {
+                    ticks: Ticks::new(0),
+                    seconds: 1.25,
+                },
+                WarpMarker {
+                    ticks: Ticks::new(960),
+                    seconds: 1.8,
+                },
+            ],
+            track_id,
+        };
+        song.add_event(Event::AudioRegion(region.clone())).unwrap();
+
+        let bytes = MirrorFrame::snapshot(&song).to_bytes();
+        let mut replica = None;
+        MirrorFrame::from_bytes(&bytes).unwrap().apply(&mut replica);
+
+        let replica = replica.unwrap();
+        assert_eq!(
+            replica.get_event(&region.id),
+            Some(&Event::AudioRegion(region.clone()))
+        );
+        assert_eq!(
+            replica
+                .get_events_in_ticks_range(Ticks::new(2000), Ticks::new(2100), true, None)
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CHORD_SYMBOL_INTERFACES: &'static str = r#"
+export type ChordQuality =
+  | "major"
+  | "minor"
+  | "diminished"
+  | "augmented"
+  | "sus2"
+  | "sus4"
+  | "dominant7"
+  | "major7"
+  | "minor7"
+  | "halfDiminished7"
+  | "diminished7";
+
+/**
+ * A chord symbol of a lead sheet, lasting until the next one. Chord symbols apply song-wide
+ * whichever track holds them.
+ */
+export interface ChordSymbol {
+  id: string;
+  kind: "ChordSymbol";
+  ticks: number;
+  /** Pitch class of the root, 0 for C to 11 for B. */
+  root: number;
+  quality: ChordQuality;
+  /** Pitch class of a slash chord's bass note. */
+  bass: number | null;
+  trackId: string;
+}
+
+export interface ChordSymbolUpdater {
+  id: string;
+  kind: "ChordSymbol";
+  ticks?: number;
+  root?: number;
+  quality?: ChordQuality;
+  bass?: number | null;
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Sus2,
+    Sus4,
+    Dominant7,
+    Major7,
+    Minor7,
+    HalfDiminished7,
+    Diminished7,
+}
+
+impl ChordQuality {
+    pub const ALL: [ChordQuality; 11] = [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Diminished,
+        ChordQuality::Augmented,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
+        ChordQuality::Dominant7,
+        ChordQuality::Major7,
+        ChordQuality::Minor7,
+        ChordQuality::HalfDiminished7,
+        ChordQuality::Diminished7,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChordQuality::Major => "major",
+            ChordQuality::Minor => "minor",
+            ChordQuality::Diminished => "diminished",
+            ChordQuality::Augmented => "augmented",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Dominant7 => "dominant7",
+            ChordQuality::Major7 => "major7",
+            ChordQuality::Minor7 => "minor7",
+            ChordQuality::HalfDiminished7 => "halfDiminished7",
+            ChordQuality::Diminished7 => "diminished7",
+        }
+    }
+
+    /// Semitones of the chord tones above the root.
+    pub fn intervals(&self) -> &'static [u8] {
+        match self {
+            ChordQuality::Major => &[0, 4, 7],
+            ChordQuality::Minor => &[0, 3, 7],
+            ChordQuality::Diminished => &[0, 3, 6],
+            ChordQuality::Augmented => &[0, 4, 8],
+            ChordQuality::Sus2 => &[0, 2, 7],
+            ChordQuality::Sus4 => &[0, 5, 7],
+            ChordQuality::Dominant7 => &[0, 4, 7, 10],
+            ChordQuality::Major7 => &[0, 4, 7, 11],
+            ChordQuality::Minor7 => &[0, 3, 7, 10],
+            ChordQuality::HalfDiminished7 => &[0, 3, 6, 10],
+            ChordQuality::Diminished7 => &[0, 3, 6, 9],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChordSymbol {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub root: u8,
+    pub quality: ChordQuality,
+    pub bass: Option<u8>,
+    pub track_id: Id,
+}
+
+impl ChordSymbol {
+    /// The chord symbol with its root and bass reduced to pitch classes.
+    pub fn normalized(self) -> Self {
+        ChordSymbol {
+            root: self.root % 12,
+            bass: self.bass.map(|bass| bass % 12),
+            ..self
+        }
+    }
+
+    pub fn clone_with_updater(&self, updater: ChordSymbolUpdater) -> Self {
+        ChordSymbol {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            root: updater.root.unwrap_or(self.root),
+            quality: updater.quality.unwrap_or(self.quality),
+            bass: updater.bass.unwrap_or(self.bass),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+
+    /// Pitch classes of the chord tones and the bass note, indexed from C.
+    pub fn pitch_classes(&self) -> [bool; 12] {
+        let mut pitch_classes = [false; 12];
+        for interval in self.quality.intervals() {
+            pitch_classes[((self.root + interval) % 12) as usize] = true;
+        }
+        if let Some(bass) = self.bass {
+            pitch_classes[bass as usize] = true;
+        }
+        pitch_classes
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChordSymbolUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub root: Option<u8>,
+    pub quality: Option<ChordQuality>,
+    /// `Some(None)` removes the bass note.
+    #[serde(default, deserialize_with = "explicit_option")]
+    pub bass: Option<Option<u8>>,
+    pub track_id: Option<Id>,
+}
+
+/// Reads a field that is present, even as `null`, as `Some`, so a missing `bass` keeps the bass
+/// note while `bass: null` removes it.
+fn explicit_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Option<u8>>, D::Error> {
+    Option::deserialize(deserializer).map(Some)
+}
+
+impl From<&ChordSymbol> for ChordSymbolUpdater {
+    /// An updater that sets every field to the chord symbol's current value.
+    fn from(chord: &ChordSymbol) -> Self {
+        ChordSymbolUpdater {
+            id: chord.id,
+            ticks: Some(chord.ticks),
+            root: Some(chord.root),
+            quality: Some(chord.quality),
+            bass: Some(chord.bass),
+            track_id: Some(chord.track_id),
+        }
+    }
+}
+
+impl ChordSymbolUpdater {
+    pub fn normalized(self) -> Self {
+        ChordSymbolUpdater {
+            root: self.root.map(|root| root % 12),
+            bass: self.bass.map(|bass| bass.map(|bass| bass % 12)),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("ChordSymbol"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(root) = self.root {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("root"),
+                &JsValue::from_f64(root as f64),
+            )
+            .unwrap();
+        }
+
+        if let Some(quality) = self.quality {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("quality"),
+                &JsValue::from_str(quality.as_str()),
+            )
+            .unwrap();
+        }
+
+        if let Some(bass) = self.bass {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("bass"),
+                &bass.map_or(JsValue::NULL, |bass| JsValue::from_f64(bass as f64)),
+            )
+            .unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::event::Event, query::pitch_class::pitch_class_info, shared::error::StoreError,
+        transform::test_helpers::song_with_track,
+    };
+    use serde_json::json;
+
+    fn chord(id: u128, ticks: u64, root: u8, quality: ChordQuality, track_id: Id) -> ChordSymbol {
+        ChordSymbol {
+            id: Id::from_u128(id),
+            ticks: Ticks::new(ticks),
+            root,
+            quality,
+            bass: None,
+            track_id,
+        }
+    }
+
+    #[test]
+    fn test_chord_symbol_qualities_and_bass() {
+        let track_id = Id::from_u128(9);
+        let mut js = json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "ChordSymbol",
+            "ticks": 0,
+            "root": 12,
+            "quality": "halfDiminished7",
+            "bass": 18,
+            "trackId": track_id.to_string(),
+        });
+        let decode =
+            |value| StoreError::decoding("Event", || serde_json::from_value::<ChordSymbol>(value));
+        assert_eq!(
+            decode(js.clone()).map(ChordSymbol::normalized),
+            Ok(ChordSymbol {
+                bass: Some(6),
+                ..chord(1, 0, 0, ChordQuality::HalfDiminished7, track_id)
+            })
+        );
+        for quality in ChordQuality::ALL {
+            js["quality"] = json!(quality.as_str());
+            assert_eq!(decode(js.clone()).unwrap().quality, quality);
+        }
+        js["quality"] = json!("power");
+        assert!(matches!(decode(js), Err(StoreError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_chord_symbol_updater_keeps_or_clears_the_bass() {
+        let slash_chord = ChordSymbol {
+            bass: Some(4),
+            ..chord(1, 0, 0, ChordQuality::Major, Id::from_u128(9))
+        };
+        let updater = |fields: serde_json::Value| {
+            let mut js = json!({ "id": Id::from_u128(1).to_string(), "kind": "ChordSymbol" });
+            js.as_object_mut()
+                .unwrap()
+                .extend(fields.as_object().unwrap().clone());
+            serde_json::from_value::<ChordSymbolUpdater>(js)
+                .unwrap()
+                .normalized()
+        };
+
+        let minor = slash_chord.clone_with_updater(updater(json!({ "quality": "minor" })));
+        assert_eq!(minor.bass, Some(4));
+        let no_bass = slash_chord.clone_with_updater(updater(json!({ "bass": null })));
+        assert_eq!(no_bass.bass, None);
+        let moved_bass = slash_chord.clone_with_updater(updater(json!({ "bass": 23 })));
+        assert_eq!(moved_bass.bass, Some(11));
+    }
+
+    #[test]
+    fn test_chord_symbol_lookup() {
+        let (mut song, track_id) = song_with_track();
+        let c_over_e = ChordSymbol {
+            bass: Some(4),
+            ..chord(1, 0, 0, ChordQuality::Sus4, track_id)
+        };
+        song.add_event(Event::ChordSymbol(c_over_e)).unwrap();
+        song.add_event(Event::ChordSymbol(chord(
+            2,
+            960,
+            7,
+            ChordQuality::Dominant7,
+            track_id,
+        )))
+        .unwrap();
+
+        let info = pitch_class_info(&song, Ticks::new(959));
+        let in_chord: Vec<usize> = (0..12).filter(|&pc| info.in_chord()[pc]).collect();
+        assert_eq!(in_chord, vec![0, 4, 5, 7]);
+
+        let info = pitch_class_info(&song, Ticks::new(960));
+        assert_eq!(info.chord.map(Event::get_id), Some(Id::from_u128(2)));
+        let in_chord: Vec<usize> = (0..12).filter(|&pc| info.in_chord()[pc]).collect();
+        assert_eq!(in_chord, vec![2, 5, 7, 11]);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CONTROL_CHANGE_INTERFACES: &'static str = r#"
+export interface ControlChange {
+  id: string;
+  kind: "ControlChange";
+  ticks: number;
+  controller: number;
+  value: number;
+  trackId: string;
+}
+
+export interface ControlChangeUpdater {
+  id: string;
+  kind: "ControlChange";
+  ticks?: number;
+  controller?: number;
+  value?: number;
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ControllerNumber(u8);
+
+impl ControllerNumber {
+    pub fn new(value: u8) -> Self {
+        ControllerNumber(value)
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ControllerValue(u8);
+
+impl ControllerValue {
+    pub fn new(value: u8) -> Self {
+        ControllerValue(value)
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlChange {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub controller: ControllerNumber,
+    pub value: ControllerValue,
+    pub track_id: Id,
+}
+
+impl ControlChange {
+    pub fn clone_with_updater(&self, updater: ControlChangeUpdater) -> Self {
+        ControlChange {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            controller: updater.controller.unwrap_or(self.controller),
+            value: updater.value.unwrap_or(self.value),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlChangeUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub controller: Option<ControllerNumber>,
+    pub value: Option<ControllerValue>,
+    pub track_id: Option<Id>,
+}
+
+impl From<&ControlChange> for ControlChangeUpdater {
+    /// An updater that sets every field to the control change's current value.
+    fn from(cc: &ControlChange) -> Self {
+        ControlChangeUpdater {
+            id: cc.id,
+            ticks: Some(cc.ticks),
+            controller: Some(cc.controller),
+            value: Some(cc.value),
+            track_id: Some(cc.track_id),
+        }
+    }
+}
+
+impl ControlChangeUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("ControlChange"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(controller) = self.controller {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("controller"),
+                &JsValue::from_f64(controller.as_u8() as f64),
+            )
+            .unwrap();
+        }
+
+        if let Some(value) = self.value {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("value"),
+                &JsValue::from_f64(value.as_u8() as f64),
+            )
+            .unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        query::automation::ControllerLane,
+        shared::error::StoreError,
+        transform::test_helpers::{add_control_change, song_with_track},
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_control_change_values_are_bytes() {
+        let mut js = json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "ControlChange",
+            "ticks": 240,
+            "controller": 11,
+            "value": 90,
+            "trackId": Id::from_u128(2).to_string(),
+        });
+        let decode = |value| {
+            StoreError::decoding("Event", || serde_json::from_value::<ControlChange>(value))
+        };
+        let expression = decode(js.clone()).unwrap();
+        assert_eq!(expression.controller, ControllerNumber::new(11));
+        assert_eq!(expression.value, ControllerValue::new(90));
+        js["value"] = json!(256);
+        assert!(matches!(decode(js), Err(StoreError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_control_change_lane() {
+        let (mut song, track_id) = song_with_track();
+        add_control_change(&mut song, track_id, 0, 11, 100);
+        add_control_change(&mut song, track_id, 960, 11, 0);
+        add_control_change(&mut song, track_id, 480, 7, 64);
+        let track = song.get_track(&track_id).unwrap();
+
+        let lane = ControllerLane::new(&song, track, ControllerNumber::new(11));
+        assert_eq!(lane.value_at(Ticks::new(480)), Some(50.0));
+        assert_eq!(lane.value_at(Ticks::new(2000)), Some(0.0));
+        let volume = ControllerLane::new(&song, track, ControllerNumber::new(7));
+        assert_eq!(volume.value_at(Ticks::new(0)), Some(64.0));
+    }
+}
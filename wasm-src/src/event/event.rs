@@ -1,111 +1,596 @@
-use super::note::{Note, NoteUpdater};
-use crate::shared::{id::Id, unit::time::Ticks};
+use super::{
+    audio_region::{AudioRegion, AudioRegionUpdater},
+    chord_symbol::{ChordSymbol, ChordSymbolUpdater},
+    control_change::{ControlChange, ControlChangeUpdater, ControllerValue},
+    key_signature::{KeySignature, KeySignatureUpdater},
+    lyric::{Lyric, LyricUpdater},
+    marker::{Marker, MarkerUpdater},
+    note::{Note, NoteUpdater, Velocity},
+    raw_midi::{RawMidi, RawMidiUpdater},
+    time_signature::{TimeSignature, TimeSignatureUpdater},
+};
+#[cfg(feature = "wasm")]
+use crate::shared::js::to_js_value;
+use crate::shared::{error::StoreError, id::Id, js::string_enum, unit::time::Ticks};
+#[cfg(feature = "wasm")]
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::{prelude::*, JsValue};
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(typescript_custom_section)]
 const TS_EVENT_INTERFACES: &'static str = r#"
-export type Event = Note;
+export type Event = (
+  | Note
+  | ControlChange
+  | Lyric
+  | AudioRegion
+  | TimeSignature
+  | Marker
+  | KeySignature
+  | ChordSymbol
+  | RawMidi
+) & EventTimestamps & EventHandle & EventSequence & EventMeta;
 
-export type EventUpdater = NoteUpdater;
+/**
+ * Present on query results. Events added later have greater sequence numbers, so sorting by it
+ * restores insertion order, for example to stack overlapping notes. Updates keep it.
+ */
+export interface EventSequence {
+  sequence?: number;
+}
+
+/**
+ * Present on query results for events with meta. Meta is not a song change: setting it is
+ * neither notified nor undone, but undoing a removal brings it back with the event.
+ */
+export interface EventMeta {
+  meta?: Meta;
+}
+
+/** Present on query results once a clock is set with `Store.setClock`. */
+export interface EventTimestamps {
+  createdAt?: number;
+  modifiedAt?: number;
+}
+
+export type EventUpdater = (
+  | NoteUpdater
+  | ControlChangeUpdater
+  | LyricUpdater
+  | AudioRegionUpdater
+  | TimeSignatureUpdater
+  | MarkerUpdater
+  | KeySignatureUpdater
+  | ChordSymbolUpdater
+  | RawMidiUpdater
+) & {
+  /** Replaces the event's meta; `null` clears it. */
+  meta?: Meta | null;
+};
+
+/** An updater without `id` and `kind`, for calls that address the event by handle. */
+export type EventUpdaterFields =
+  | Omit<NoteUpdater, "id" | "kind">
+  | Omit<ControlChangeUpdater, "id" | "kind">
+  | Omit<LyricUpdater, "id" | "kind">
+  | Omit<AudioRegionUpdater, "id" | "kind">
+  | Omit<TimeSignatureUpdater, "id" | "kind">
+  | Omit<MarkerUpdater, "id" | "kind">
+  | Omit<KeySignatureUpdater, "id" | "kind">
+  | Omit<ChordSymbolUpdater, "id" | "kind">
+  | Omit<RawMidiUpdater, "id" | "kind">;
 "#;
 
-#[wasm_bindgen]
-#[derive(Debug)]
-pub enum EventKind {
-    Note = "Note",
+string_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum EventKind {
+        Note = "Note",
+        ControlChange = "ControlChange",
+        Lyric = "Lyric",
+        AudioRegion = "AudioRegion",
+        TimeSignature = "TimeSignature",
+        Marker = "Marker",
+        KeySignature = "KeySignature",
+        ChordSymbol = "ChordSymbol",
+        RawMidi = "RawMidi",
+    }
 }
 
-impl Display for EventKind {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl EventKind {
+    pub const ALL: [EventKind; 9] = [
+        EventKind::Note,
+        EventKind::ControlChange,
+        EventKind::Lyric,
+        EventKind::AudioRegion,
+        EventKind::TimeSignature,
+        EventKind::Marker,
+        EventKind::KeySignature,
+        EventKind::ChordSymbol,
+        EventKind::RawMidi,
+    ];
+
+    pub fn priority(self) -> TickPriority {
         match self {
-            EventKind::Note => write!(f, "Note"),
-            _ => panic!("Unknown event kind: {}", self),
+            EventKind::TimeSignature
+            | EventKind::KeySignature
+            | EventKind::Marker
+            | EventKind::Lyric
+            | EventKind::ChordSymbol => TickPriority::Meta,
+            EventKind::ControlChange | EventKind::RawMidi => TickPriority::Control,
+            _ => TickPriority::Sound,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub(crate) enum Event {
+/// Where events on the same tick go, first to last, so a player that follows the order sets up
+/// the meter and the instrument before a sound starts and ends the sounds before new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TickPriority {
+    Meta,
+    /// The end of a note, which has no event of its own, see `NoteOff`.
+    NoteOff,
+    Control,
+    Sound,
+}
+
+/// The order of kinds among events on the same tick, as the `kindOrder` options give it. Kinds
+/// left out rank after the listed ones and equal to each other, so they keep their usual order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct KindOrder(Vec<EventKind>);
+
+impl KindOrder {
+    pub fn rank(&self, kind: EventKind) -> usize {
+        self.0
+            .iter()
+            .position(|listed| *listed == kind)
+            .unwrap_or(self.0.len())
+    }
+}
+
+impl TryFrom<Vec<String>> for KindOrder {
+    type Error = String;
+
+    fn try_from(kinds: Vec<String>) -> Result<Self, Self::Error> {
+        kinds
+            .iter()
+            .map(|kind| {
+                kind.parse::<EventKind>()
+                    .map_err(|_| format!("Unknown event kind: {kind}"))
+            })
+            .collect::<Result<_, _>>()
+            .map(KindOrder)
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
     Note(Note),
+    ControlChange(ControlChange),
+    Lyric(Lyric),
+    AudioRegion(AudioRegion),
+    TimeSignature(TimeSignature),
+    Marker(Marker),
+    KeySignature(KeySignature),
+    ChordSymbol(ChordSymbol),
+    RawMidi(RawMidi),
 }
 
 impl Event {
-    pub(crate) fn clone_with_updater(&self, updater: EventUpdater) -> Self {
-        match (self, updater) {
+    /// Whether `updater` targets the same kind of event as `self`.
+    pub fn accepts(&self, updater: &EventUpdater) -> bool {
+        matches!(
+            (self, updater),
+            (Event::Note(_), EventUpdater::Note(_))
+                | (Event::ControlChange(_), EventUpdater::ControlChange(_))
+                | (Event::Lyric(_), EventUpdater::Lyric(_))
+                | (Event::AudioRegion(_), EventUpdater::AudioRegion(_))
+                | (Event::TimeSignature(_), EventUpdater::TimeSignature(_))
+                | (Event::Marker(_), EventUpdater::Marker(_))
+                | (Event::KeySignature(_), EventUpdater::KeySignature(_))
+                | (Event::ChordSymbol(_), EventUpdater::ChordSymbol(_))
+                | (Event::RawMidi(_), EventUpdater::RawMidi(_))
+        )
+    }
+
+    /// The event with the fields `updater` sets, if it is of the event's kind.
+    pub fn clone_with_updater(&self, updater: EventUpdater) -> Result<Self, StoreError> {
+        Ok(match (self, updater) {
             (Event::Note(note), EventUpdater::Note(note_updater)) => {
                 Event::Note(note.clone_with_updater(note_updater))
             }
+            (Event::ControlChange(cc), EventUpdater::ControlChange(cc_updater)) => {
+                Event::ControlChange(cc.clone_with_updater(cc_updater))
+            }
+            (Event::Lyric(lyric), EventUpdater::Lyric(lyric_updater)) => {
+                Event::Lyric(lyric.clone_with_updater(lyric_updater))
+            }
+            (Event::AudioRegion(region), EventUpdater::AudioRegion(region_updater)) => {
+                Event::AudioRegion(region.clone_with_updater(region_updater))
+            }
+            (Event::TimeSignature(meter), EventUpdater::TimeSignature(meter_updater)) => {
+                Event::TimeSignature(meter.clone_with_updater(meter_updater))
+            }
+            (Event::Marker(marker), EventUpdater::Marker(marker_updater)) => {
+                Event::Marker(marker.clone_with_updater(marker_updater))
+            }
+            (Event::KeySignature(key), EventUpdater::KeySignature(key_updater)) => {
+                Event::KeySignature(key.clone_with_updater(key_updater))
+            }
+            (Event::ChordSymbol(chord), EventUpdater::ChordSymbol(chord_updater)) => {
+                Event::ChordSymbol(chord.clone_with_updater(chord_updater))
+            }
+            (Event::RawMidi(raw), EventUpdater::RawMidi(raw_updater)) => {
+                Event::RawMidi(raw.clone_with_updater(raw_updater))
+            }
+            _ => return Err(StoreError::invalid("Updater kind does not match the event")),
+        })
+    }
+
+    /// Where the event goes among the song's events: by ticks, then by the priority of its kind
+    /// on the same tick, then by id.
+    pub fn order_key(&self) -> (Ticks, TickPriority, Id) {
+        (self.get_ticks(), self.get_kind().priority(), self.get_id())
+    }
+
+    pub fn get_kind(&self) -> EventKind {
+        match self {
+            Event::Note(_) => EventKind::Note,
+            Event::ControlChange(_) => EventKind::ControlChange,
+            Event::Lyric(_) => EventKind::Lyric,
+            Event::AudioRegion(_) => EventKind::AudioRegion,
+            Event::TimeSignature(_) => EventKind::TimeSignature,
+            Event::Marker(_) => EventKind::Marker,
+            Event::KeySignature(_) => EventKind::KeySignature,
+            Event::ChordSymbol(_) => EventKind::ChordSymbol,
+            Event::RawMidi(_) => EventKind::RawMidi,
         }
     }
 
-    pub(crate) fn get_id(&self) -> Id {
+    pub fn get_id(&self) -> Id {
         match self {
             Event::Note(note) => note.id,
+            Event::ControlChange(cc) => cc.id,
+            Event::Lyric(lyric) => lyric.id,
+            Event::AudioRegion(region) => region.id,
+            Event::TimeSignature(meter) => meter.id,
+            Event::Marker(marker) => marker.id,
+            Event::KeySignature(key) => key.id,
+            Event::ChordSymbol(chord) => chord.id,
+            Event::RawMidi(raw) => raw.id,
         }
     }
 
-    pub(crate) fn get_ticks(&self) -> Ticks {
+    pub fn get_ticks(&self) -> Ticks {
         match self {
             Event::Note(note) => note.ticks,
+            Event::ControlChange(cc) => cc.ticks,
+            Event::Lyric(lyric) => lyric.ticks,
+            Event::AudioRegion(region) => region.ticks,
+            Event::TimeSignature(meter) => meter.ticks,
+            Event::Marker(marker) => marker.ticks,
+            Event::KeySignature(key) => key.ticks,
+            Event::ChordSymbol(chord) => chord.ticks,
+            Event::RawMidi(raw) => raw.ticks,
         }
     }
 
-    pub(crate) fn get_duration(&self) -> Option<Ticks> {
+    pub fn get_duration(&self) -> Option<Ticks> {
         match self {
             Event::Note(note) => Some(note.duration),
+            Event::AudioRegion(region) => Some(region.duration),
+            Event::ControlChange(_)
+            | Event::Lyric(_)
+            | Event::TimeSignature(_)
+            | Event::Marker(_)
+            | Event::KeySignature(_)
+            | Event::ChordSymbol(_)
+            | Event::RawMidi(_) => None,
         }
     }
 
-    pub(crate) fn get_track_id(&self) -> Id {
+    pub fn as_note(&self) -> Option<&Note> {
         match self {
-            Event::Note(note) => note.track_id,
+            Event::Note(note) => Some(note),
+            _ => None,
         }
     }
 
-    pub(crate) fn from_js_object(obj: js_sys::Object) -> Self {
-        let kind = js_sys::Reflect::get(&obj, &JsValue::from_str("kind"))
-            .unwrap()
-            .as_string()
-            .unwrap();
-        let kind = EventKind::from_str(&kind).unwrap();
+    pub fn with_id(self, id: Id) -> Self {
+        match self {
+            Event::Note(note) => Event::Note(Note { id, ..note }),
+            Event::ControlChange(cc) => Event::ControlChange(ControlChange { id, ..cc }),
+            Event::Lyric(lyric) => Event::Lyric(Lyric { id, ..lyric }),
+            Event::AudioRegion(region) => Event::AudioRegion(AudioRegion { id, ..region }),
+            Event::TimeSignature(meter) => Event::TimeSignature(TimeSignature { id, ..meter }),
+            Event::Marker(marker) => Event::Marker(Marker { id, ..marker }),
+            Event::KeySignature(key) => Event::KeySignature(KeySignature { id, ..key }),
+            Event::ChordSymbol(chord) => Event::ChordSymbol(ChordSymbol { id, ..chord }),
+            Event::RawMidi(raw) => Event::RawMidi(RawMidi { id, ..raw }),
+        }
+    }
+
+    /// Moves the event to the track `track_id`. Markers belong to the song and are returned
+    /// unchanged.
+    pub fn with_track_id(self, track_id: Id) -> Self {
+        match self {
+            Event::Note(note) => Event::Note(Note { track_id, ..note }),
+            Event::ControlChange(cc) => Event::ControlChange(ControlChange { track_id, ..cc }),
+            Event::Lyric(lyric) => Event::Lyric(Lyric { track_id, ..lyric }),
+            Event::AudioRegion(region) => Event::AudioRegion(AudioRegion { track_id, ..region }),
+            Event::TimeSignature(meter) => {
+                Event::TimeSignature(TimeSignature { track_id, ..meter })
+            }
+            event @ Event::Marker(_) => event,
+            Event::KeySignature(key) => Event::KeySignature(KeySignature { track_id, ..key }),
+            Event::ChordSymbol(chord) => Event::ChordSymbol(ChordSymbol { track_id, ..chord }),
+            Event::RawMidi(raw) => Event::RawMidi(RawMidi { track_id, ..raw }),
+        }
+    }
+
+    pub fn with_ticks(self, ticks: Ticks) -> Self {
+        match self {
+            Event::Note(note) => Event::Note(Note { ticks, ..note }),
+            Event::ControlChange(cc) => Event::ControlChange(ControlChange { ticks, ..cc }),
+            Event::Lyric(lyric) => Event::Lyric(Lyric { ticks, ..lyric }),
+            Event::AudioRegion(region) => Event::AudioRegion(AudioRegion { ticks, ..region }),
+            Event::TimeSignature(meter) => Event::TimeSignature(TimeSignature { ticks, ..meter }),
+            Event::Marker(marker) => Event::Marker(Marker { ticks, ..marker }),
+            Event::KeySignature(key) => Event::KeySignature(KeySignature { ticks, ..key }),
+            Event::ChordSymbol(chord) => Event::ChordSymbol(ChordSymbol { ticks, ..chord }),
+            Event::RawMidi(raw) => Event::RawMidi(RawMidi { ticks, ..raw }),
+        }
+    }
+
+    pub fn with_duration(self, duration: Ticks) -> Self {
+        match self {
+            Event::Note(note) => Event::Note(Note { duration, ..note }),
+            Event::AudioRegion(region) => Event::AudioRegion(AudioRegion { duration, ..region }),
+            event => event,
+        }
+    }
+
+    /// Sets the value edited in the event's lane: velocity for notes, value for control changes.
+    /// Other kinds have no such value and are returned unchanged.
+    pub fn with_value(self, value: u8) -> Self {
+        let value = value.min(127);
 
-        match kind {
-            EventKind::Note => Event::Note(Note::from_js_object(obj)),
-            _ => panic!("Unknown event kind: {}", kind),
+        match self {
+            Event::Note(note) => Event::Note(Note {
+                velocity: Velocity::new(value.max(1)),
+                ..note
+            }),
+            Event::ControlChange(cc) => Event::ControlChange(ControlChange {
+                value: ControllerValue::new(value),
+                ..cc
+            }),
+            event => event,
         }
     }
 
-    pub(crate) fn to_js_object(&self) -> js_sys::Object {
+    /// The track holding the event; `None` for markers, which belong to the song.
+    pub fn get_track_id(&self) -> Option<Id> {
         match self {
-            Event::Note(note) => note.to_js_object(),
+            Event::Note(note) => Some(note.track_id),
+            Event::ControlChange(cc) => Some(cc.track_id),
+            Event::Lyric(lyric) => Some(lyric.track_id),
+            Event::AudioRegion(region) => Some(region.track_id),
+            Event::TimeSignature(meter) => Some(meter.track_id),
+            Event::Marker(_) => None,
+            Event::KeySignature(key) => Some(key.track_id),
+            Event::ChordSymbol(chord) => Some(chord.track_id),
+            Event::RawMidi(raw) => Some(raw.track_id),
         }
     }
+
+    /// Reads an event in its TypeScript shape. Only the fields of its kind are read, so query
+    /// results with their sequence, meta and timestamps can be passed back as they are.
+    #[cfg(feature = "wasm")]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<Self, StoreError> {
+        let kind = kind_from_js(&obj)?;
+        let obj = JsValue::from(obj);
+        Ok(match kind {
+            EventKind::Note => Event::Note(decode(obj)?),
+            EventKind::ControlChange => Event::ControlChange(decode(obj)?),
+            EventKind::Lyric => Event::Lyric(decode(obj)?),
+            EventKind::AudioRegion => Event::AudioRegion(decode::<AudioRegion>(obj)?.normalized()),
+            EventKind::TimeSignature => Event::TimeSignature(decode(obj)?),
+            EventKind::Marker => Event::Marker(decode(obj)?),
+            EventKind::KeySignature => {
+                Event::KeySignature(decode::<KeySignature>(obj)?.normalized())
+            }
+            EventKind::ChordSymbol => Event::ChordSymbol(decode::<ChordSymbol>(obj)?.normalized()),
+            EventKind::RawMidi => Event::RawMidi(decode(obj)?),
+        })
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        to_js_value(&JsEvent::from(self)).unchecked_into()
+    }
+
+    /// Converts `events` in a single serialization pass rather than one object at a time.
+    #[cfg(feature = "wasm")]
+    pub fn to_js_array<'a>(events: impl IntoIterator<Item = &'a Event>) -> js_sys::Array {
+        let events: Vec<JsEvent> = events.into_iter().map(JsEvent::from).collect();
+        to_js_value(&events).unchecked_into()
+    }
+}
+
+/// An event in its TypeScript shape: the fields of its kind, tagged with `kind`.
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+pub enum JsEvent<'a> {
+    Note(&'a Note),
+    ControlChange(&'a ControlChange),
+    Lyric(&'a Lyric),
+    AudioRegion(&'a AudioRegion),
+    TimeSignature(&'a TimeSignature),
+    Marker(&'a Marker),
+    KeySignature(&'a KeySignature),
+    ChordSymbol(&'a ChordSymbol),
+    RawMidi(&'a RawMidi),
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub(crate) enum EventUpdater {
+impl<'a> From<&'a Event> for JsEvent<'a> {
+    fn from(event: &'a Event) -> Self {
+        match event {
+            Event::Note(note) => JsEvent::Note(note),
+            Event::ControlChange(cc) => JsEvent::ControlChange(cc),
+            Event::Lyric(lyric) => JsEvent::Lyric(lyric),
+            Event::AudioRegion(region) => JsEvent::AudioRegion(region),
+            Event::TimeSignature(meter) => JsEvent::TimeSignature(meter),
+            Event::Marker(marker) => JsEvent::Marker(marker),
+            Event::KeySignature(key) => JsEvent::KeySignature(key),
+            Event::ChordSymbol(chord) => JsEvent::ChordSymbol(chord),
+            Event::RawMidi(raw) => JsEvent::RawMidi(raw),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EventUpdater {
     Note(NoteUpdater),
+    ControlChange(ControlChangeUpdater),
+    Lyric(LyricUpdater),
+    AudioRegion(AudioRegionUpdater),
+    TimeSignature(TimeSignatureUpdater),
+    Marker(MarkerUpdater),
+    KeySignature(KeySignatureUpdater),
+    ChordSymbol(ChordSymbolUpdater),
+    RawMidi(RawMidiUpdater),
+}
+
+/// The `kind` of the event or updater `obj`.
+#[cfg(feature = "wasm")]
+fn kind_from_js(obj: &js_sys::Object) -> Result<EventKind, StoreError> {
+    if !obj.is_object() {
+        return Err(StoreError::invalid("Event is not an object"));
+    }
+    let kind = js_sys::Reflect::get(obj, &JsValue::from_str("kind"))
+        .unwrap()
+        .as_string()
+        .ok_or_else(|| StoreError::invalid("Event kind is missing"))?;
+    kind.parse()
+        .map_err(|_| StoreError::invalid(format!("Event kind \"{}\" is not valid", kind)))
+}
+
+/// Reads the fields `T` declares from `obj`, reporting a bad id as `invalidId` and any other
+/// missing or mistyped field as `validationFailed`.
+#[cfg(feature = "wasm")]
+fn decode<T: DeserializeOwned>(obj: JsValue) -> Result<T, StoreError> {
+    StoreError::decoding("Event", || serde_wasm_bindgen::from_value(obj))
+}
+
+impl From<&Event> for EventUpdater {
+    fn from(event: &Event) -> Self {
+        match event {
+            Event::Note(note) => EventUpdater::Note(note.into()),
+            Event::ControlChange(cc) => EventUpdater::ControlChange(cc.into()),
+            Event::Lyric(lyric) => EventUpdater::Lyric(lyric.into()),
+            Event::AudioRegion(region) => EventUpdater::AudioRegion(region.into()),
+            Event::TimeSignature(meter) => EventUpdater::TimeSignature(meter.into()),
+            Event::Marker(marker) => EventUpdater::Marker(marker.into()),
+            Event::KeySignature(key) => EventUpdater::KeySignature(key.into()),
+            Event::ChordSymbol(chord) => EventUpdater::ChordSymbol(chord.into()),
+            Event::RawMidi(raw) => EventUpdater::RawMidi(raw.into()),
+        }
+    }
 }
 
 impl EventUpdater {
-    pub(crate) fn get_id(&self) -> Id {
+    pub fn get_id(&self) -> Id {
         match self {
             EventUpdater::Note(note) => note.id,
+            EventUpdater::ControlChange(cc) => cc.id,
+            EventUpdater::Lyric(lyric) => lyric.id,
+            EventUpdater::AudioRegion(region) => region.id,
+            EventUpdater::TimeSignature(meter) => meter.id,
+            EventUpdater::Marker(marker) => marker.id,
+            EventUpdater::KeySignature(key) => key.id,
+            EventUpdater::ChordSymbol(chord) => chord.id,
+            EventUpdater::RawMidi(raw) => raw.id,
         }
     }
 
-    pub(crate) fn from_js_object(obj: js_sys::Object) -> Self {
-        let kind = js_sys::Reflect::get(&obj, &JsValue::from_str("kind"))
-            .unwrap()
-            .as_string()
+    pub fn get_track_id(&self) -> Option<Id> {
+        match self {
+            EventUpdater::Note(note) => note.track_id,
+            EventUpdater::ControlChange(cc) => cc.track_id,
+            EventUpdater::Lyric(lyric) => lyric.track_id,
+            EventUpdater::AudioRegion(region) => region.track_id,
+            EventUpdater::TimeSignature(meter) => meter.track_id,
+            EventUpdater::Marker(_) => None,
+            EventUpdater::KeySignature(key) => key.track_id,
+            EventUpdater::ChordSymbol(chord) => chord.track_id,
+            EventUpdater::RawMidi(raw) => raw.track_id,
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<Self, StoreError> {
+        let kind = kind_from_js(&obj)?;
+        let obj = JsValue::from(obj);
+        Ok(match kind {
+            EventKind::Note => EventUpdater::Note(decode(obj)?),
+            EventKind::ControlChange => EventUpdater::ControlChange(decode(obj)?),
+            EventKind::Lyric => EventUpdater::Lyric(decode(obj)?),
+            EventKind::AudioRegion => {
+                EventUpdater::AudioRegion(decode::<AudioRegionUpdater>(obj)?.normalized())
+            }
+            EventKind::TimeSignature => EventUpdater::TimeSignature(decode(obj)?),
+            EventKind::Marker => EventUpdater::Marker(decode(obj)?),
+            EventKind::KeySignature => {
+                EventUpdater::KeySignature(decode::<KeySignatureUpdater>(obj)?.normalized())
+            }
+            EventKind::ChordSymbol => {
+                EventUpdater::ChordSymbol(decode::<ChordSymbolUpdater>(obj)?.normalized())
+            }
+            EventKind::RawMidi => EventUpdater::RawMidi(decode(obj)?),
+        })
+    }
+
+    /// Reads an updater for the existing event `id` of `kind`, ignoring any `id` or `kind` in
+    /// `obj`.
+    #[cfg(feature = "wasm")]
+    pub fn from_js_fields(
+        kind: EventKind,
+        id: Id,
+        obj: js_sys::Object,
+    ) -> Result<Self, StoreError> {
+        let updater = js_sys::Object::assign(&js_sys::Object::new(), &obj);
+        for (key, value) in [("id", id.to_string()), ("kind", kind.to_string())] {
+            js_sys::Reflect::set(
+                &updater,
+                &JsValue::from_str(key),
+                &JsValue::from_str(&value),
+            )
             .unwrap();
-        let kind = EventKind::from_str(&kind).unwrap();
+        }
+        Self::from_js_object(updater)
+    }
 
-        match kind {
-            EventKind::Note => EventUpdater::Note(NoteUpdater::from_js_object(obj)),
-            _ => panic!("Unknown event kind: {}", kind),
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        match self {
+            EventUpdater::Note(note) => note.to_js_object(),
+            EventUpdater::ControlChange(cc) => cc.to_js_object(),
+            EventUpdater::Lyric(lyric) => lyric.to_js_object(),
+            EventUpdater::AudioRegion(region) => region.to_js_object(),
+            EventUpdater::TimeSignature(meter) => meter.to_js_object(),
+            EventUpdater::Marker(marker) => marker.to_js_object(),
+            EventUpdater::KeySignature(key) => key.to_js_object(),
+            EventUpdater::ChordSymbol(chord) => chord.to_js_object(),
+            EventUpdater::RawMidi(raw) => raw.to_js_object(),
         }
     }
 }
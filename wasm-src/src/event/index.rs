@@ -0,0 +1,475 @@
+use super::event::Event;
+use crate::{
+    shared::{error::StoreError, id::Id, unit::time::Ticks},
+    song::compact::CompactionReport,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Where an event sits in a song's `EventArena`; ids only come in at the lookup by id.
+pub(crate) type Slot = u32;
+
+/// A song's events, the only copy of each, in slots reused once freed.
+///
+/// The song's tick index and its tracks' hold slots into the arena rather than events or ids,
+/// so an event is found by id with a single map lookup and by tick without any.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventArena {
+    slots: Vec<Option<Event>>,
+    free_slots: Vec<Slot>,
+    slot_ids: HashMap<Id, Slot>,
+}
+
+impl EventArena {
+    pub(crate) fn len(&self) -> usize {
+        self.slot_ids.len()
+    }
+
+    pub(crate) fn contains(&self, event_id: &Id) -> bool {
+        self.slot_ids.contains_key(event_id)
+    }
+
+    fn at(&self, slot: Slot) -> &Event {
+        self.slots[slot as usize].as_ref().unwrap()
+    }
+
+    pub(crate) fn get(&self, event_id: &Id) -> Option<&Event> {
+        self.slot_ids.get(event_id).map(|slot| self.at(*slot))
+    }
+
+    /// Stores an event whose id the arena does not hold yet, returning its slot.
+    pub(crate) fn insert(&mut self, event: Event) -> Result<Slot, StoreError> {
+        let id = event.get_id();
+        if self.slot_ids.contains_key(&id) {
+            return Err(StoreError::invalid(format!("Event {} already exists", id)));
+        }
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.slots[slot as usize] = Some(event);
+                slot
+            }
+            None => {
+                self.slots.push(Some(event));
+                (self.slots.len() - 1) as Slot
+            }
+        };
+        self.slot_ids.insert(id, slot);
+        Ok(slot)
+    }
+
+    /// Takes the event out of the arena, with the slot it held for the indexes to drop.
+    pub(crate) fn remove(&mut self, event_id: &Id) -> Option<(Slot, Event)> {
+        let slot = self.slot_ids.remove(event_id)?;
+        let event = self.slots[slot as usize].take().unwrap();
+        self.free_slots.push(slot);
+        Some((slot, event))
+    }
+
+    /// Drops the free slots at the end of the arena and the spare capacity.
+    pub(crate) fn compact(&mut self, report: &mut CompactionReport) {
+        while let Some(None) = self.slots.last() {
+            self.slots.pop();
+        }
+        let len = self.slots.len() as Slot;
+        self.free_slots.retain(|slot| *slot < len);
+        report.vec(&mut self.slots);
+        report.vec(&mut self.free_slots);
+        report.map(&mut self.slot_ids);
+    }
+}
+
+/// The slots of a set of events by tick, the whole song's or one track's, which range queries
+/// go through. It holds no events: `view` reads them from the arena its slots point into.
+///
+/// Removing an event leaves its index buckets behind, possibly empty, until `compact`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct EventIndex {
+    len: usize,
+    /// The slots starting on each tick, in `Event::order_key` order.
+    ticks_index: BTreeMap<Ticks, Vec<Slot>>,
+    end_ticks_index: BTreeMap<Ticks, Vec<Slot>>,
+    /// The slots of events with a duration by start tick, apart per `duration_class`, so a
+    /// range query looks back for events still sounding only as far as each class needs.
+    duration_classes: BTreeMap<u32, BTreeMap<Ticks, Vec<Slot>>>,
+}
+
+/// Durations in `[2^(class - 1), 2^class)` share a class; a zero duration is class 0.
+fn duration_class(duration: Ticks) -> u32 {
+    u64::BITS - duration.as_u64().leading_zeros()
+}
+
+impl EventIndex {
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Indexes the event `events` holds in `slot`.
+    pub(crate) fn insert(&mut self, events: &EventArena, slot: Slot) {
+        let event = events.at(slot);
+        let key = event.order_key();
+        let ticks = event.get_ticks();
+        self.len += 1;
+
+        let bucket = self.ticks_index.entry(ticks).or_default();
+        let position = bucket.partition_point(|other| events.at(*other).order_key() < key);
+        bucket.insert(position, slot);
+
+        if let Some(duration) = event.get_duration() {
+            self.end_ticks_index
+                .entry(ticks + duration)
+                .or_default()
+                .push(slot);
+            self.duration_classes
+                .entry(duration_class(duration))
+                .or_default()
+                .entry(ticks)
+                .or_default()
+                .push(slot);
+        }
+    }
+
+    /// Drops `slot`, which held `event` and which the index holds, from the index.
+    pub(crate) fn remove(&mut self, slot: Slot, event: &Event) {
+        let ticks = event.get_ticks();
+        self.len -= 1;
+
+        let remove_slot = |bucket: Option<&mut Vec<Slot>>| {
+            if let Some(bucket) = bucket {
+                if let Some(position) = bucket.iter().position(|other| *other == slot) {
+                    bucket.remove(position);
+                }
+            }
+        };
+        remove_slot(self.ticks_index.get_mut(&ticks));
+        if let Some(duration) = event.get_duration() {
+            remove_slot(self.end_ticks_index.get_mut(&(ticks + duration)));
+            remove_slot(
+                self.duration_classes
+                    .get_mut(&duration_class(duration))
+                    .and_then(|class| class.get_mut(&ticks)),
+            );
+        }
+    }
+
+    /// The index with the events its slots point into, for queries.
+    pub(crate) fn view<'a>(&'a self, events: &'a EventArena) -> IndexedEvents<'a> {
+        IndexedEvents {
+            index: self,
+            events,
+        }
+    }
+
+    /// Where the last event ends, or `None` without events.
+    pub(crate) fn last_end(&self) -> Option<Ticks> {
+        // removing an event can leave an empty bucket behind
+        let last = |index: &BTreeMap<Ticks, Vec<Slot>>| {
+            index
+                .iter()
+                .rev()
+                .find(|(_, slots)| !slots.is_empty())
+                .map(|(ticks, _)| *ticks)
+        };
+        last(&self.ticks_index).max(last(&self.end_ticks_index))
+    }
+
+    /// Drops the empty buckets of the indexes and their spare capacity.
+    pub(crate) fn compact(&mut self, report: &mut CompactionReport) {
+        report.index(&mut self.ticks_index);
+        report.index(&mut self.end_ticks_index);
+        for starts in self.duration_classes.values_mut() {
+            report.index(starts);
+        }
+        self.duration_classes.retain(|_, starts| !starts.is_empty());
+    }
+}
+
+/// An `EventIndex` with the arena its slots point into. Its queries collect nothing unless
+/// they say so.
+#[derive(Clone, Copy)]
+pub(crate) struct IndexedEvents<'a> {
+    index: &'a EventIndex,
+    events: &'a EventArena,
+}
+
+impl<'a> IndexedEvents<'a> {
+    fn bucket(self, slots: &'a [Slot]) -> impl DoubleEndedIterator<Item = &'a Event> {
+        slots.iter().map(move |slot| self.events.at(*slot))
+    }
+
+    /// Every event in `Event::order_key` order.
+    pub(crate) fn iter(self) -> impl Iterator<Item = &'a Event> {
+        self.index
+            .ticks_index
+            .values()
+            .flat_map(move |slots| self.bucket(slots))
+    }
+
+    /// Events starting in `[start_ticks, end_ticks)` in `Event::order_key` order.
+    /// `start_ticks` must not be after `end_ticks`.
+    pub(crate) fn iter_range(
+        self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    ) -> impl Iterator<Item = &'a Event> {
+        self.index
+            .ticks_index
+            .range(start_ticks..end_ticks)
+            .flat_map(move |(_, slots)| self.bucket(slots))
+    }
+
+    /// Events that started before `ticks` and still sound at it, in no particular order.
+    ///
+    /// Each duration class is read back only as far as its longest possible duration, so a
+    /// long note costs the queries nothing beyond the notes of its own class.
+    pub(crate) fn iter_sounding_at(self, ticks: Ticks) -> impl Iterator<Item = &'a Event> {
+        self.index
+            .duration_classes
+            .iter()
+            .filter(|(class, _)| **class > 0)
+            .flat_map(move |(class, starts)| {
+                let longest = Ticks::new(u64::MAX >> (u64::BITS - class));
+                starts
+                    .range(ticks.saturating_sub(longest)..ticks)
+                    .flat_map(move |(_, slots)| self.bucket(slots))
+            })
+            .filter(move |event| {
+                event
+                    .get_duration()
+                    .is_some_and(|duration| event.get_ticks() + duration > ticks)
+            })
+    }
+
+    /// Events that started before `ticks` and still sound at it, in `Event::order_key` order.
+    pub(crate) fn sounding_at(self, ticks: Ticks) -> Vec<&'a Event> {
+        let mut events: Vec<&Event> = self.iter_sounding_at(ticks).collect();
+        events.sort_by_key(|event| event.order_key());
+        events
+    }
+
+    /// Events starting in `[start_ticks, end_ticks)` in `Event::order_key` order, preceded
+    /// with `within_duration` by the events that started earlier and still sound at
+    /// `start_ticks`.
+    pub(crate) fn in_ticks_range(
+        self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+        within_duration: bool,
+    ) -> Vec<&'a Event> {
+        let sounding = if within_duration {
+            self.sounding_at(start_ticks)
+        } else {
+            Vec::new()
+        };
+        sounding
+            .into_iter()
+            .chain(self.iter_range(start_ticks, end_ticks))
+            .collect()
+    }
+
+    /// The events from `ticks` on in `Event::order_key` order, or from `ticks` back in the
+    /// reverse order when `reverse`.
+    pub(crate) fn around(
+        self,
+        ticks: Ticks,
+        reverse: bool,
+    ) -> Box<dyn Iterator<Item = &'a Event> + 'a> {
+        if reverse {
+            Box::new(
+                self.index
+                    .ticks_index
+                    .range(..=ticks)
+                    .rev()
+                    .flat_map(move |(_, slots)| self.bucket(slots).rev()),
+            )
+        } else {
+            Box::new(
+                self.index
+                    .ticks_index
+                    .range(ticks..)
+                    .flat_map(move |(_, slots)| self.bucket(slots)),
+            )
+        }
+    }
+
+    /// Events with a duration still sounding after `ticks`, ending after it, in end order.
+    pub(crate) fn ending_after(self, ticks: Ticks) -> impl Iterator<Item = &'a Event> {
+        let after = ticks.checked_add(Ticks::new(1));
+        after.into_iter().flat_map(move |after| {
+            self.index
+                .end_ticks_index
+                .range(after..)
+                .flat_map(move |(_, slots)| self.bucket(slots))
+        })
+    }
+
+    /// Events with a duration ending in `[start_ticks, end_ticks)`, in end order.
+    pub(crate) fn ending_in(
+        self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    ) -> impl Iterator<Item = &'a Event> {
+        self.index
+            .end_ticks_index
+            .range(start_ticks..end_ticks)
+            .flat_map(move |(_, slots)| self.bucket(slots))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{
+        control_change::{ControlChange, ControllerNumber, ControllerValue},
+        note::{Note, NoteNumber, NoteParameters, Velocity},
+    };
+
+    fn note(ticks: u64, duration: u64) -> Event {
+        Event::Note(Note {
+            id: Id::new(),
+            ticks: Ticks::new(ticks),
+            duration: Ticks::new(duration),
+            velocity: Velocity::new(100),
+            note_number: NoteNumber::new(60),
+            track_id: Id::from_u128(1),
+            parameters: NoteParameters::new(),
+        })
+    }
+
+    fn insert(events: &mut EventArena, index: &mut EventIndex, event: Event) {
+        let slot = events.insert(event).unwrap();
+        index.insert(events, slot);
+    }
+
+    fn remove(events: &mut EventArena, index: &mut EventIndex, event_id: &Id) -> Option<Event> {
+        let (slot, event) = events.remove(event_id)?;
+        index.remove(slot, &event);
+        Some(event)
+    }
+
+    #[test]
+    fn test_event_index_range() {
+        let (mut events, mut index) = (EventArena::default(), EventIndex::default());
+        let long = note(0, 1920);
+        let short = note(240, 240);
+        let sounding = note(400, 200);
+        let inside = note(480, 120);
+        let cc = Event::ControlChange(ControlChange {
+            id: Id::new(),
+            ticks: Ticks::new(300),
+            controller: ControllerNumber::new(1),
+            value: ControllerValue::new(64),
+            track_id: Id::from_u128(1),
+        });
+        for event in [&long, &short, &sounding, &inside, &cc] {
+            insert(&mut events, &mut index, event.clone());
+        }
+        let ids = |events: Vec<&Event>| events.into_iter().map(Event::get_id).collect::<Vec<_>>();
+
+        let range = |events: &EventArena, index: &EventIndex, within_duration| {
+            ids(index.view(events).in_ticks_range(
+                Ticks::new(480),
+                Ticks::new(960),
+                within_duration,
+            ))
+        };
+        assert_eq!(range(&events, &index, false), vec![inside.get_id()]);
+        assert_eq!(
+            range(&events, &index, true),
+            vec![long.get_id(), sounding.get_id(), inside.get_id()]
+        );
+
+        // the long note reads back only its own class, which the short notes are not in
+        assert_eq!(
+            index.duration_classes.keys().copied().collect::<Vec<_>>(),
+            vec![7, 8, 11]
+        );
+        let lookback = |index: &EventIndex, class| {
+            index.duration_classes[&class]
+                .range(Ticks::new(0)..Ticks::new(480))
+                .count()
+        };
+        assert_eq!((lookback(&index, 8), lookback(&index, 11)), (2, 1));
+
+        remove(&mut events, &mut index, &long.get_id());
+        assert_eq!(
+            range(&events, &index, true),
+            vec![sounding.get_id(), inside.get_id()]
+        );
+        let view = index.view(&events);
+        assert_eq!(
+            ids(view.sounding_at(Ticks::new(590))),
+            vec![sounding.get_id(), inside.get_id()]
+        );
+        assert_eq!(index.last_end(), Some(Ticks::new(600)));
+        assert_eq!(view.ending_after(Ticks::new(480)).count(), 2);
+        assert_eq!((index.len(), events.len()), (4, 4));
+    }
+
+    #[test]
+    fn test_event_arena_slots() {
+        let (mut events, mut index) = (EventArena::default(), EventIndex::default());
+        let first = note(0, 480);
+        let second = note(0, 240);
+        insert(&mut events, &mut index, first.clone());
+        assert_eq!(
+            remove(&mut events, &mut index, &first.get_id()),
+            Some(first.clone())
+        );
+        assert_eq!(remove(&mut events, &mut index, &first.get_id()), None);
+
+        // the freed slot is reused
+        insert(&mut events, &mut index, second.clone());
+        assert_eq!(events.slots.len(), 1);
+        assert_eq!(events.get(&second.get_id()), Some(&second));
+
+        let mut report = CompactionReport::default();
+        remove(&mut events, &mut index, &second.get_id());
+        events.compact(&mut report);
+        index.compact(&mut report);
+        assert!(events.slots.is_empty());
+        assert!(index.duration_classes.is_empty());
+        // the shared tick bucket, and the end and duration class buckets of each note
+        assert_eq!(report.empty_buckets, 5);
+    }
+
+    #[test]
+    fn test_event_arena_rejects_known_id() {
+        let mut events = EventArena::default();
+        let event = note(0, 480);
+        let event_id = event.get_id();
+        events.insert(event.clone()).unwrap();
+        assert_eq!(
+            events.insert(event.clone().with_ticks(Ticks::new(240))),
+            Err(StoreError::invalid(format!(
+                "Event {} already exists",
+                event_id
+            )))
+        );
+        assert_eq!(events.get(&event.get_id()), Some(&event));
+    }
+
+    #[test]
+    fn test_indexes_share_the_arena() {
+        let mut events = EventArena::default();
+        let (mut song, mut first, mut second) = (
+            EventIndex::default(),
+            EventIndex::default(),
+            EventIndex::default(),
+        );
+        let notes: Vec<Event> = [0, 240, 480, 720].map(|ticks| note(ticks, 120)).into();
+        for (position, event) in notes.iter().enumerate() {
+            let slot = events.insert(event.clone()).unwrap();
+            song.insert(&events, slot);
+            let track = if position % 3 == 0 {
+                &mut first
+            } else {
+                &mut second
+            };
+            track.insert(&events, slot);
+        }
+
+        let listed = |index: &EventIndex| index.view(&events).iter().cloned().collect::<Vec<_>>();
+        assert_eq!(listed(&song), notes);
+        assert_eq!(listed(&first), vec![notes[0].clone(), notes[3].clone()]);
+        assert_eq!(listed(&second), notes[1..3].to_vec());
+    }
+}
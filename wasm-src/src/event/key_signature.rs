@@ -0,0 +1,258 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_KEY_SIGNATURE_INTERFACES: &'static str = r#"
+export type KeyMode = "major" | "minor";
+
+/** A key change. Key signatures apply song-wide whichever track holds them. */
+export interface KeySignature {
+  id: string;
+  kind: "KeySignature";
+  ticks: number;
+  /** Pitch class of the tonic, 0 for C to 11 for B. */
+  root: number;
+  mode: KeyMode;
+  trackId: string;
+}
+
+export interface KeySignatureUpdater {
+  id: string;
+  kind: "KeySignature";
+  ticks?: number;
+  root?: number;
+  mode?: KeyMode;
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyMode {
+    Major,
+    Minor,
+}
+
+impl KeyMode {
+    pub const ALL: [KeyMode; 2] = [KeyMode::Major, KeyMode::Minor];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyMode::Major => "major",
+            KeyMode::Minor => "minor",
+        }
+    }
+
+    /// Semitones of the scale's degrees above the tonic; minor is the natural minor scale.
+    pub fn scale(&self) -> [u8; 7] {
+        match self {
+            KeyMode::Major => [0, 2, 4, 5, 7, 9, 11],
+            KeyMode::Minor => [0, 2, 3, 5, 7, 8, 10],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySignature {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub root: u8,
+    pub mode: KeyMode,
+    pub track_id: Id,
+}
+
+impl KeySignature {
+    /// The key signature with its root reduced to a pitch class.
+    pub fn normalized(self) -> Self {
+        KeySignature {
+            root: self.root % 12,
+            ..self
+        }
+    }
+
+    pub fn clone_with_updater(&self, updater: KeySignatureUpdater) -> Self {
+        KeySignature {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            root: updater.root.unwrap_or(self.root),
+            mode: updater.mode.unwrap_or(self.mode),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+
+    /// Pitch classes of the key's scale, indexed from C.
+    pub fn pitch_classes(&self) -> [bool; 12] {
+        let mut pitch_classes = [false; 12];
+        for interval in self.mode.scale() {
+            pitch_classes[((self.root + interval) % 12) as usize] = true;
+        }
+        pitch_classes
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySignatureUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub root: Option<u8>,
+    pub mode: Option<KeyMode>,
+    pub track_id: Option<Id>,
+}
+
+impl From<&KeySignature> for KeySignatureUpdater {
+    /// An updater that sets every field to the key signature's current value.
+    fn from(key_signature: &KeySignature) -> Self {
+        KeySignatureUpdater {
+            id: key_signature.id,
+            ticks: Some(key_signature.ticks),
+            root: Some(key_signature.root),
+            mode: Some(key_signature.mode),
+            track_id: Some(key_signature.track_id),
+        }
+    }
+}
+
+impl KeySignatureUpdater {
+    pub fn normalized(self) -> Self {
+        KeySignatureUpdater {
+            root: self.root.map(|root| root % 12),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("KeySignature"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(root) = self.root {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("root"),
+                &JsValue::from_f64(root as f64),
+            )
+            .unwrap();
+        }
+
+        if let Some(mode) = self.mode {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("mode"),
+                &JsValue::from_str(mode.as_str()),
+            )
+            .unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::event::Event, shared::error::StoreError, transform::test_helpers::song_with_track,
+    };
+    use serde_json::json;
+
+    fn key(id: u128, ticks: u64, root: u8, mode: KeyMode, track_id: Id) -> KeySignature {
+        KeySignature {
+            id: Id::from_u128(id),
+            ticks: Ticks::new(ticks),
+            root,
+            mode,
+            track_id,
+        }
+    }
+
+    #[test]
+    fn test_key_signature_root_and_mode() {
+        let track_id = Id::from_u128(9);
+        let mut js = json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "KeySignature",
+            "ticks": 0,
+            "root": 14,
+            "mode": "minor",
+            "trackId": track_id.to_string(),
+        });
+        let decode =
+            |value| StoreError::decoding("Event", || serde_json::from_value::<KeySignature>(value));
+        // roots are reduced to pitch classes once decoded
+        assert_eq!(
+            decode(js.clone()).map(KeySignature::normalized),
+            Ok(key(1, 0, 2, KeyMode::Minor, track_id))
+        );
+        js["mode"] = json!("dorian");
+        assert!(matches!(decode(js), Err(StoreError::ValidationFailed(_))));
+
+        let updater: KeySignatureUpdater = serde_json::from_value(json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "KeySignature",
+            "root": 19,
+        }))
+        .unwrap();
+        let g_major =
+            key(1, 0, 0, KeyMode::Major, track_id).clone_with_updater(updater.normalized());
+        assert_eq!(g_major, key(1, 0, 7, KeyMode::Major, track_id));
+        // G major has F sharp instead of F
+        let in_key: Vec<usize> = (0..12).filter(|&pc| g_major.pitch_classes()[pc]).collect();
+        assert_eq!(in_key, vec![0, 2, 4, 6, 7, 9, 11]);
+    }
+
+    #[test]
+    fn test_key_signature_lookup() {
+        let (mut song, track_id) = song_with_track();
+        for key_signature in [
+            key(1, 0, 0, KeyMode::Major, track_id),
+            key(3, 1920, 9, KeyMode::Minor, track_id),
+            key(2, 1920, 4, KeyMode::Major, track_id),
+        ] {
+            song.add_event(Event::KeySignature(key_signature)).unwrap();
+        }
+
+        assert_eq!(song.get_key_at(Ticks::new(1919)).unwrap().root, 0);
+        // of two on one tick, the last in id order wins
+        let a_minor = song.get_key_at(Ticks::new(1920)).unwrap();
+        assert_eq!((a_minor.root, a_minor.mode), (9, KeyMode::Minor));
+
+        song.remove_event(&Id::from_u128(1));
+        assert_eq!(song.get_key_at(Ticks::new(0)), None);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LYRIC_INTERFACES: &'static str = r#"
+/**
+ * A sung syllable or word. As in MIDI karaoke files, `text` carries its own spacing and
+ * hyphenation, and a trailing newline ends a line.
+ */
+export interface Lyric {
+  id: string;
+  kind: "Lyric";
+  ticks: number;
+  text: string;
+  trackId: string;
+}
+
+export interface LyricUpdater {
+  id: string;
+  kind: "Lyric";
+  ticks?: number;
+  text?: string;
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lyric {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub text: String,
+    pub track_id: Id,
+}
+
+impl Lyric {
+    pub fn clone_with_updater(&self, updater: LyricUpdater) -> Self {
+        Lyric {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            text: updater.text.unwrap_or_else(|| self.text.clone()),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+
+    /// Whether the syllable ends its line.
+    pub fn ends_line(&self) -> bool {
+        self.text.ends_with(['\n', '\r'])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub text: Option<String>,
+    pub track_id: Option<Id>,
+}
+
+impl From<&Lyric> for LyricUpdater {
+    /// An updater that sets every field to the lyric's current value.
+    fn from(lyric: &Lyric) -> Self {
+        LyricUpdater {
+            id: lyric.id,
+            ticks: Some(lyric.ticks),
+            text: Some(lyric.text.clone()),
+            track_id: Some(lyric.track_id),
+        }
+    }
+}
+
+impl LyricUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("Lyric"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(text) = &self.text {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("text"),
+                &JsValue::from_str(text),
+            )
+            .unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::error::StoreError;
+    use serde_json::json;
+
+    fn syllable(text: &str) -> Lyric {
+        Lyric {
+            id: Id::from_u128(1),
+            ticks: Ticks::new(480),
+            text: text.to_string(),
+            track_id: Id::from_u128(2),
+        }
+    }
+
+    #[test]
+    fn test_lyric_text() {
+        let js = json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "Lyric",
+            "ticks": 480,
+            "trackId": Id::from_u128(2).to_string(),
+        });
+        let decoded = StoreError::decoding("Event", || serde_json::from_value::<Lyric>(js));
+        assert!(matches!(decoded, Err(StoreError::ValidationFailed(_))));
+
+        let updater: LyricUpdater = serde_json::from_value(json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "Lyric",
+            "text": "lo\n",
+        }))
+        .unwrap();
+        let lyric = syllable("Hel-").clone_with_updater(updater);
+        assert_eq!(lyric, syllable("lo\n"));
+        assert!(lyric.ends_line());
+        assert!(!syllable("Hel-").ends_line());
+        assert!(syllable("lo\r").ends_line());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MARKER_INTERFACES: &'static str = r#"
+/**
+ * A named position in the arrangement. Markers belong to the song rather than to a track, and
+ * each one starts a section that lasts until the next.
+ */
+export interface Marker {
+  id: string;
+  kind: "Marker";
+  ticks: number;
+  name: string;
+}
+
+export interface MarkerUpdater {
+  id: string;
+  kind: "Marker";
+  ticks?: number;
+  name?: string;
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Marker {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub name: String,
+}
+
+impl Marker {
+    pub fn clone_with_updater(&self, updater: MarkerUpdater) -> Self {
+        Marker {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            name: updater.name.unwrap_or_else(|| self.name.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkerUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub name: Option<String>,
+}
+
+impl From<&Marker> for MarkerUpdater {
+    /// An updater that sets every field to the marker's current value.
+    fn from(marker: &Marker) -> Self {
+        MarkerUpdater {
+            id: marker.id,
+            ticks: Some(marker.ticks),
+            name: Some(marker.name.clone()),
+        }
+    }
+}
+
+impl MarkerUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("Marker"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(name) = &self.name {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("name"),
+                &JsValue::from_str(name),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::event::Event,
+        song::{section::markers, song::Song},
+    };
+
+    #[test]
+    fn test_markers_in_ticks_order() {
+        let mut song = Song::new("Song".to_string(), 480);
+        let marker = |id: u128, ticks: u64, name: &str| {
+            Event::Marker(Marker {
+                id: Id::from_u128(id),
+                ticks: Ticks::new(ticks),
+                name: name.to_string(),
+            })
+        };
+        song.add_event(marker(1, 3840, "Chorus")).unwrap();
+        song.add_event(marker(2, 0, "Verse")).unwrap();
+
+        let names: Vec<&str> = markers(&song)
+            .iter()
+            .map(|marker| marker.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Verse", "Chorus"]);
+    }
+}
@@ -1,2 +1,14 @@
+pub mod audio_region;
+pub mod chord_symbol;
+pub mod control_change;
 pub mod event;
+pub mod index;
+pub mod key_signature;
+pub mod lyric;
+pub mod marker;
 pub mod note;
+pub mod raw_midi;
+pub mod schema;
+pub mod time_signature;
+pub mod timestamp;
+pub mod validation;
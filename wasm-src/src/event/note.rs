@@ -1,7 +1,10 @@
 use crate::shared::{id::Id, unit::time::Ticks};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::{prelude::*, JsValue};
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(typescript_custom_section)]
 const TS_NOTE_INTERFACES: &'static str = r#"
 export interface Note {
@@ -12,8 +15,11 @@ export interface Note {
   velocity: number;
   noteNumber: number;
   trackId: string;
+  parameters: NoteParameters;
 }
 
+export type NoteParameters = Record<string, number>;
+
 export interface NoteUpdater {
   id: string;
   kind: "Note";
@@ -22,6 +28,7 @@ export interface NoteUpdater {
   velocity?: number;
   noteNumber?: number;
   trackId?: string;
+  parameters?: NoteParameters;
 }
 "#;
 
@@ -38,7 +45,7 @@ impl Velocity {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct NoteNumber(u8);
 
 impl NoteNumber {
@@ -51,18 +58,40 @@ impl NoteNumber {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub(crate) struct Note {
-    pub(crate) id: Id,
-    pub(crate) ticks: Ticks,
-    pub(crate) duration: Ticks,
-    pub(crate) velocity: Velocity,
-    pub(crate) note_number: NoteNumber,
-    pub(crate) track_id: Id,
+/// Numeric per-note expression values such as pan or brightness, keyed by name.
+pub type NoteParameters = BTreeMap<String, f64>;
+
+#[cfg(feature = "wasm")]
+fn parameters_to_js(parameters: &NoteParameters) -> js_sys::Object {
+    let js_parameters = js_sys::Object::new();
+
+    for (name, value) in parameters {
+        js_sys::Reflect::set(
+            &js_parameters,
+            &JsValue::from_str(name),
+            &JsValue::from_f64(*value),
+        )
+        .unwrap();
+    }
+
+    js_parameters
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub duration: Ticks,
+    pub velocity: Velocity,
+    pub note_number: NoteNumber,
+    pub track_id: Id,
+    #[serde(default)]
+    pub parameters: NoteParameters,
 }
 
 impl Note {
-    pub(crate) fn clone_with_updater(&self, updater: NoteUpdater) -> Self {
+    pub fn clone_with_updater(&self, updater: NoteUpdater) -> Self {
         Note {
             id: self.id,
             ticks: updater.ticks.unwrap_or(self.ticks),
@@ -70,150 +99,113 @@ impl Note {
             velocity: updater.velocity.unwrap_or(self.velocity),
             note_number: updater.note_number.unwrap_or(self.note_number),
             track_id: updater.track_id.unwrap_or(self.track_id),
+            parameters: updater
+                .parameters
+                .unwrap_or_else(|| self.parameters.clone()),
         }
     }
+}
 
-    pub(crate) fn from_js_object(obj: js_sys::Object) -> Self {
-        let id = js_sys::Reflect::get(&obj, &JsValue::from_str("id"))
-            .unwrap()
-            .as_string()
-            .unwrap();
-
-        let ticks = js_sys::Reflect::get(&obj, &JsValue::from_str("ticks"))
-            .unwrap()
-            .as_f64()
-            .unwrap();
-
-        let duration = js_sys::Reflect::get(&obj, &JsValue::from_str("duration"))
-            .unwrap()
-            .as_f64()
-            .unwrap();
-
-        let velocity = js_sys::Reflect::get(&obj, &JsValue::from_str("velocity"))
-            .unwrap()
-            .as_f64()
-            .unwrap();
-
-        let note_number = js_sys::Reflect::get(&obj, &JsValue::from_str("noteNumber"))
-            .unwrap()
-            .as_f64()
-            .unwrap();
-
-        let track_id = js_sys::Reflect::get(&obj, &JsValue::from_str("trackId"))
-            .unwrap()
-            .as_string()
-            .unwrap();
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub duration: Option<Ticks>,
+    pub velocity: Option<Velocity>,
+    pub note_number: Option<NoteNumber>,
+    pub track_id: Option<Id>,
+    pub parameters: Option<NoteParameters>,
+}
 
-        Note {
-            id: Id::try_from(id.as_str()).unwrap(),
-            ticks: Ticks::new(ticks as u32),
-            duration: Ticks::new(duration as u32),
-            velocity: Velocity::new(velocity as u8),
-            note_number: NoteNumber::new(note_number as u8),
-            track_id: Id::try_from(track_id.as_str()).unwrap(),
+impl From<&Note> for NoteUpdater {
+    /// An updater that sets every field to the note's current value.
+    fn from(note: &Note) -> Self {
+        NoteUpdater {
+            id: note.id,
+            ticks: Some(note.ticks),
+            duration: Some(note.duration),
+            velocity: Some(note.velocity),
+            note_number: Some(note.note_number),
+            track_id: Some(note.track_id),
+            parameters: Some(note.parameters.clone()),
         }
     }
+}
 
-    pub(crate) fn to_js_object(&self) -> js_sys::Object {
-        let js_event = js_sys::Object::new();
+impl NoteUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
 
         js_sys::Reflect::set(
-            &js_event,
+            &js_updater,
             &JsValue::from_str("id"),
             &JsValue::from_str(self.id.to_string().as_str()),
         )
         .unwrap();
 
         js_sys::Reflect::set(
-            &js_event,
+            &js_updater,
             &JsValue::from_str("kind"),
             &JsValue::from_str("Note"),
         )
         .unwrap();
 
-        js_sys::Reflect::set(
-            &js_event,
-            &JsValue::from_str("ticks"),
-            &JsValue::from_f64(self.ticks.as_u32() as f64),
-        )
-        .unwrap();
-
-        js_sys::Reflect::set(
-            &js_event,
-            &JsValue::from_str("duration"),
-            &JsValue::from_f64(self.duration.as_u32() as f64),
-        )
-        .unwrap();
-
-        js_sys::Reflect::set(
-            &js_event,
-            &JsValue::from_str("velocity"),
-            &JsValue::from_f64(self.velocity.as_u8() as f64),
-        )
-        .unwrap();
-
-        js_sys::Reflect::set(
-            &js_event,
-            &JsValue::from_str("noteNumber"),
-            &JsValue::from_f64(self.note_number.as_u8() as f64),
-        )
-        .unwrap();
-
-        js_sys::Reflect::set(
-            &js_event,
-            &JsValue::from_str("trackId"),
-            &JsValue::from_str(self.track_id.to_string().as_str()),
-        )
-        .unwrap();
-
-        js_event
-    }
-}
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub(crate) struct NoteUpdater {
-    pub(crate) id: Id,
-    pub(crate) ticks: Option<Ticks>,
-    pub(crate) duration: Option<Ticks>,
-    pub(crate) velocity: Option<Velocity>,
-    pub(crate) note_number: Option<NoteNumber>,
-    pub(crate) track_id: Option<Id>,
-}
-
-impl NoteUpdater {
-    pub(crate) fn from_js_object(obj: js_sys::Object) -> Self {
-        let id = js_sys::Reflect::get(&obj, &JsValue::from_str("id"))
-            .unwrap()
-            .as_string()
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
             .unwrap();
+        }
 
-        let ticks = js_sys::Reflect::get(&obj, &JsValue::from_str("ticks"))
-            .unwrap()
-            .as_f64();
-
-        let duration = js_sys::Reflect::get(&obj, &JsValue::from_str("duration"))
-            .unwrap()
-            .as_f64();
+        if let Some(duration) = self.duration {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("duration"),
+                &JsValue::from_f64(duration.as_f64()),
+            )
+            .unwrap();
+        }
 
-        let velocity = js_sys::Reflect::get(&obj, &JsValue::from_str("velocity"))
-            .unwrap()
-            .as_f64();
+        if let Some(velocity) = self.velocity {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("velocity"),
+                &JsValue::from_f64(velocity.as_u8() as f64),
+            )
+            .unwrap();
+        }
 
-        let note_number = js_sys::Reflect::get(&obj, &JsValue::from_str("noteNumber"))
-            .unwrap()
-            .as_f64();
+        if let Some(note_number) = self.note_number {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("noteNumber"),
+                &JsValue::from_f64(note_number.as_u8() as f64),
+            )
+            .unwrap();
+        }
 
-        let track_id = js_sys::Reflect::get(&obj, &JsValue::from_str("trackId"))
-            .unwrap()
-            .as_string();
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
 
-        NoteUpdater {
-            id: Id::try_from(id.as_str()).unwrap(),
-            ticks: ticks.map(|t| Ticks::new(t as u32)),
-            duration: duration.map(|d| Ticks::new(d as u32)),
-            velocity: velocity.map(|v| Velocity::new(v as u8)),
-            note_number: note_number.map(|n| NoteNumber::new(n as u8)),
-            track_id: track_id.map(|t| Id::try_from(t.as_str()).unwrap()),
+        if let Some(parameters) = &self.parameters {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("parameters"),
+                &parameters_to_js(parameters),
+            )
+            .unwrap();
         }
+
+        js_updater
     }
 }
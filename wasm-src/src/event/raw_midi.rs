@@ -0,0 +1,158 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RAW_MIDI_INTERFACES: &'static str = r#"
+/**
+ * A MIDI message the store has no kind for, such as pitch bend or SysEx, kept as bytes so it
+ * survives import and export and reaches the host's playback engine.
+ */
+export interface RawMidi {
+  id: string;
+  kind: "RawMidi";
+  ticks: number;
+  /** The status byte and its data. SysEx starts with 0xF0 and ends with 0xF7. */
+  data: number[];
+  trackId: string;
+}
+
+export interface RawMidiUpdater {
+  id: string;
+  kind: "RawMidi";
+  ticks?: number;
+  data?: number[];
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawMidi {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub data: Vec<u8>,
+    pub track_id: Id,
+}
+
+impl RawMidi {
+    pub fn clone_with_updater(&self, updater: RawMidiUpdater) -> Self {
+        RawMidi {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            data: updater.data.unwrap_or_else(|| self.data.clone()),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawMidiUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub data: Option<Vec<u8>>,
+    pub track_id: Option<Id>,
+}
+
+impl From<&RawMidi> for RawMidiUpdater {
+    /// An updater that sets every field to the message's current value.
+    fn from(raw: &RawMidi) -> Self {
+        RawMidiUpdater {
+            id: raw.id,
+            ticks: Some(raw.ticks),
+            data: Some(raw.data.clone()),
+            track_id: Some(raw.track_id),
+        }
+    }
+}
+
+impl RawMidiUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("RawMidi"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(data) = &self.data {
+            let js_data: js_sys::Array = data.iter().map(|byte| JsValue::from(*byte)).collect();
+            js_sys::Reflect::set(&js_updater, &JsValue::from_str("data"), &js_data).unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::error::StoreError;
+    use serde_json::json;
+
+    #[test]
+    fn test_raw_midi_data_is_bytes() {
+        let mut js = json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "RawMidi",
+            "ticks": 120,
+            "data": [0xE0, 0x00, 0x40],
+            "trackId": Id::from_u128(2).to_string(),
+        });
+        let decode =
+            |value| StoreError::decoding("Event", || serde_json::from_value::<RawMidi>(value));
+        let pitch_bend = decode(js.clone()).unwrap();
+        assert_eq!(pitch_bend.data, vec![0xE0, 0x00, 0x40]);
+
+        js["data"] = json!([0xF0, 0x100, 0xF7]);
+        assert!(matches!(
+            decode(js.clone()),
+            Err(StoreError::ValidationFailed(_))
+        ));
+        js["data"] = json!("F0 F7");
+        assert!(matches!(decode(js), Err(StoreError::ValidationFailed(_))));
+
+        // the updater replaces the whole message
+        let sysex = vec![0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7];
+        let updater: RawMidiUpdater = serde_json::from_value(json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "RawMidi",
+            "data": sysex,
+        }))
+        .unwrap();
+        let reset = pitch_bend.clone_with_updater(updater);
+        assert_eq!(reset.data, sysex);
+        assert_eq!(reset.ticks, Ticks::new(120));
+    }
+}
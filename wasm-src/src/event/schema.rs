@@ -0,0 +1,209 @@
+use super::{chord_symbol::ChordQuality, event::EventKind, key_signature::KeyMode};
+#[cfg(feature = "wasm")]
+use crate::shared::js::to_js_value;
+use crate::shared::unit::time::Ticks;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_EVENT_SCHEMA_INTERFACES: &'static str = r#"
+export type EventFieldType = "integer" | "number" | "string" | "id" | "enum" | "record" | "array";
+
+/** One field of an event kind, beyond `id` and `kind`, as an editor form would show it. */
+export interface EventFieldSchema {
+  name: string;
+  type: EventFieldType;
+  /** Smallest accepted value of an integer field. */
+  min?: number;
+  /** Largest accepted value of an integer field. */
+  max?: number;
+  /** The accepted values of an enum field. */
+  values?: string[];
+  /** Present when the field may be `null`. */
+  nullable?: true;
+}
+
+export interface EventKindSchema {
+  kind: EventKind;
+  fields: EventFieldSchema[];
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum FieldType {
+    Integer,
+    Number,
+    String,
+    Id,
+    Enum,
+    Record,
+    Array,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FieldSchema {
+    name: &'static str,
+    #[serde(rename = "type")]
+    field_type: FieldType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values: Option<Vec<&'static str>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    nullable: bool,
+}
+
+impl FieldSchema {
+    fn new(name: &'static str, field_type: FieldType) -> Self {
+        FieldSchema {
+            name,
+            field_type,
+            min: None,
+            max: None,
+            values: None,
+            nullable: false,
+        }
+    }
+
+    fn integer(name: &'static str, min: u64, max: u64) -> Self {
+        FieldSchema {
+            min: Some(min as f64),
+            max: Some(max as f64),
+            ..Self::new(name, FieldType::Integer)
+        }
+    }
+
+    fn choice(name: &'static str, values: impl IntoIterator<Item = &'static str>) -> Self {
+        FieldSchema {
+            values: Some(values.into_iter().collect()),
+            ..Self::new(name, FieldType::Enum)
+        }
+    }
+
+    fn nullable(self) -> Self {
+        FieldSchema {
+            nullable: true,
+            ..self
+        }
+    }
+}
+
+/// The fields of an event kind with their types and ranges, so generic editors can build forms
+/// without knowing the kinds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventKindSchema {
+    kind: String,
+    fields: Vec<FieldSchema>,
+}
+
+impl EventKindSchema {
+    pub fn new(kind: EventKind) -> Self {
+        let ticks = FieldSchema::integer("ticks", 0, Ticks::MAX.as_u64());
+        let duration = FieldSchema::integer("duration", 1, Ticks::MAX.as_u64());
+        let track_id = FieldSchema::new("trackId", FieldType::Id);
+        let midi_value = |name| FieldSchema::integer(name, 0, 127);
+        let pitch_class = |name| FieldSchema::integer(name, 0, 11);
+
+        let fields = match kind {
+            EventKind::Note => vec![
+                ticks,
+                duration,
+                midi_value("velocity"),
+                midi_value("noteNumber"),
+                track_id,
+                FieldSchema::new("parameters", FieldType::Record),
+            ],
+            EventKind::ControlChange => vec![
+                ticks,
+                midi_value("controller"),
+                midi_value("value"),
+                track_id,
+            ],
+            EventKind::Lyric => vec![ticks, FieldSchema::new("text", FieldType::String), track_id],
+            EventKind::AudioRegion => vec![
+                ticks,
+                duration,
+                FieldSchema::new("source", FieldType::String),
+                FieldSchema::new("startOffset", FieldType::Number),
+                FieldSchema::new("warpMarkers", FieldType::Array),
+                track_id,
+            ],
+            EventKind::TimeSignature => vec![
+                ticks,
+                FieldSchema::integer("numerator", 1, u8::MAX as u64),
+                FieldSchema::integer("denominator", 1, 128),
+                track_id,
+            ],
+            EventKind::Marker => vec![ticks, FieldSchema::new("name", FieldType::String)],
+            EventKind::KeySignature => vec![
+                ticks,
+                pitch_class("root"),
+                FieldSchema::choice("mode", KeyMode::ALL.iter().map(KeyMode::as_str)),
+                track_id,
+            ],
+            EventKind::RawMidi => vec![ticks, FieldSchema::new("data", FieldType::Array), track_id],
+            _ => vec![
+                ticks,
+                pitch_class("root"),
+                FieldSchema::choice(
+                    "quality",
+                    ChordQuality::ALL.iter().map(ChordQuality::as_str),
+                ),
+                pitch_class("bass").nullable(),
+                track_id,
+            ],
+        };
+
+        EventKindSchema {
+            kind: kind.to_string(),
+            fields,
+        }
+    }
+
+    /// Every kind of event the store supports.
+    pub fn all() -> Vec<Self> {
+        EventKind::ALL.into_iter().map(Self::new).collect()
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_array(schemas: &[Self]) -> js_sys::Array {
+        schemas.iter().map(to_js_value).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_kind_schemas() {
+        let schemas = EventKindSchema::all();
+        assert_eq!(schemas.len(), EventKind::ALL.len());
+
+        let note = &schemas[0];
+        assert_eq!(note.kind, "Note");
+        let names: Vec<_> = note.fields.iter().map(|field| field.name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "ticks",
+                "duration",
+                "velocity",
+                "noteNumber",
+                "trackId",
+                "parameters"
+            ]
+        );
+        assert_eq!(note.fields[2].max, Some(127.0));
+
+        let chord = EventKindSchema::new(EventKind::ChordSymbol);
+        assert_eq!(chord.fields[2].values.as_ref().unwrap().len(), 11);
+        assert!(chord.fields[3].nullable);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::shared::{id::Id, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TIME_SIGNATURE_INTERFACES: &'static str = r#"
+/**
+ * A meter change. Time signatures apply song-wide whichever track holds them, and each one
+ * starts a new bar.
+ */
+export interface TimeSignature {
+  id: string;
+  kind: "TimeSignature";
+  ticks: number;
+  numerator: number;
+  /** A power of two: 4 for quarter-note beats, 8 for eighth-note beats. */
+  denominator: number;
+  trackId: string;
+}
+
+export interface TimeSignatureUpdater {
+  id: string;
+  kind: "TimeSignature";
+  ticks?: number;
+  numerator?: number;
+  denominator?: number;
+  trackId?: string;
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSignature {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub numerator: u8,
+    pub denominator: u8,
+    pub track_id: Id,
+}
+
+impl TimeSignature {
+    pub fn clone_with_updater(&self, updater: TimeSignatureUpdater) -> Self {
+        TimeSignature {
+            id: self.id,
+            ticks: updater.ticks.unwrap_or(self.ticks),
+            numerator: updater.numerator.unwrap_or(self.numerator),
+            denominator: updater.denominator.unwrap_or(self.denominator),
+            track_id: updater.track_id.unwrap_or(self.track_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSignatureUpdater {
+    pub id: Id,
+    pub ticks: Option<Ticks>,
+    pub numerator: Option<u8>,
+    pub denominator: Option<u8>,
+    pub track_id: Option<Id>,
+}
+
+impl From<&TimeSignature> for TimeSignatureUpdater {
+    /// An updater that sets every field to the time signature's current value.
+    fn from(time_signature: &TimeSignature) -> Self {
+        TimeSignatureUpdater {
+            id: time_signature.id,
+            ticks: Some(time_signature.ticks),
+            numerator: Some(time_signature.numerator),
+            denominator: Some(time_signature.denominator),
+            track_id: Some(time_signature.track_id),
+        }
+    }
+}
+
+impl TimeSignatureUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_updater = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("id"),
+            &JsValue::from_str(self.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_updater,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str("TimeSignature"),
+        )
+        .unwrap();
+
+        if let Some(ticks) = self.ticks {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("ticks"),
+                &JsValue::from_f64(ticks.as_f64()),
+            )
+            .unwrap();
+        }
+
+        if let Some(numerator) = self.numerator {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("numerator"),
+                &JsValue::from_f64(numerator as f64),
+            )
+            .unwrap();
+        }
+
+        if let Some(denominator) = self.denominator {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("denominator"),
+                &JsValue::from_f64(denominator as f64),
+            )
+            .unwrap();
+        }
+
+        if let Some(track_id) = self.track_id {
+            js_sys::Reflect::set(
+                &js_updater,
+                &JsValue::from_str("trackId"),
+                &JsValue::from_str(track_id.to_string().as_str()),
+            )
+            .unwrap();
+        }
+
+        js_updater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::error::StoreError;
+    use serde_json::json;
+
+    #[test]
+    fn test_time_signature_meter_fields() {
+        let mut js = json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "TimeSignature",
+            "ticks": 1920,
+            "numerator": 3,
+            "denominator": 4,
+            "trackId": Id::from_u128(2).to_string(),
+        });
+        let decode = |value| {
+            StoreError::decoding("Event", || serde_json::from_value::<TimeSignature>(value))
+        };
+        let three_four = decode(js.clone()).unwrap();
+        assert_eq!((three_four.numerator, three_four.denominator), (3, 4));
+        js["numerator"] = json!(300);
+        assert!(matches!(decode(js), Err(StoreError::ValidationFailed(_))));
+
+        // changing the meter keeps the time signature where it is
+        let updater: TimeSignatureUpdater = serde_json::from_value(json!({
+            "id": Id::from_u128(1).to_string(),
+            "kind": "TimeSignature",
+            "numerator": 6,
+            "denominator": 8,
+        }))
+        .unwrap();
+        let six_eight = three_four.clone_with_updater(updater);
+        assert_eq!((six_eight.numerator, six_eight.denominator), (6, 8));
+        assert_eq!(six_eight.ticks, Ticks::new(1920));
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// When an event was created and last modified, in whatever unit the host clock uses.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTimestamps {
+    /// `None` when the event existed before the clock was set.
+    pub created_at: Option<f64>,
+    pub modified_at: f64,
+}
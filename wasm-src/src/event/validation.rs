@@ -0,0 +1,193 @@
+use crate::shared::{js::string_enum, unit::time::Ticks};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+string_enum! {
+    /// What `addEvent` and `updateEvent` do with values out of range.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ValidationPolicy {
+        /// Throws and leaves the song unchanged.
+        Strict = "strict",
+        /// Moves each value to the nearest whole number in range.
+        Clamp = "clamp",
+    }
+}
+
+/// The values of an event or updater that are checked before it is decoded, which takes whole
+/// numbers only. Fields the object does not set are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EventValues {
+    pub ticks: Option<f64>,
+    pub duration: Option<f64>,
+    pub velocity: Option<f64>,
+    pub note_number: Option<f64>,
+}
+
+#[cfg(feature = "wasm")]
+const FIELDS: [&str; 4] = ["ticks", "duration", "velocity", "noteNumber"];
+
+impl EventValues {
+    fn fields_mut(&mut self) -> [&mut Option<f64>; 4] {
+        [
+            &mut self.ticks,
+            &mut self.duration,
+            &mut self.velocity,
+            &mut self.note_number,
+        ]
+    }
+
+    #[cfg(feature = "wasm")]
+    fn from_js_object(obj: &js_sys::Object) -> Self {
+        let mut values = EventValues::default();
+        for (key, value) in FIELDS.iter().zip(values.fields_mut()) {
+            *value = js_sys::Reflect::get(obj, &JsValue::from_str(key))
+                .unwrap()
+                .as_f64();
+        }
+        values
+    }
+
+    /// A shallow copy of `obj` with these values in place of its own.
+    #[cfg(feature = "wasm")]
+    fn to_js_object(mut self, obj: &js_sys::Object) -> js_sys::Object {
+        let copy = js_sys::Object::assign(&js_sys::Object::new(), obj);
+        for (key, value) in FIELDS.iter().zip(self.fields_mut()) {
+            if let Some(value) = value {
+                js_sys::Reflect::set(&copy, &JsValue::from_str(key), &JsValue::from_f64(*value))
+                    .unwrap();
+            }
+        }
+        copy
+    }
+}
+
+/// The store's checks on event values coming from JS.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Validation {
+    pub policy: ValidationPolicy,
+    pub max_ticks: Ticks,
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Validation {
+            policy: ValidationPolicy::Strict,
+            max_ticks: Ticks::MAX,
+        }
+    }
+}
+
+impl Validation {
+    pub fn apply(&self, values: &mut EventValues) -> Result<(), String> {
+        let ranges = [
+            ("ticks", 0.0, self.max_ticks.as_f64()),
+            ("duration", 1.0, Ticks::MAX.as_f64()),
+            ("velocity", 0.0, 127.0),
+            ("noteNumber", 0.0, 127.0),
+        ];
+        for ((field, min, max), value) in ranges.into_iter().zip(values.fields_mut()) {
+            let Some(value) = value else {
+                continue;
+            };
+            if (min..=max).contains(value) && value.fract() == 0.0 {
+                continue;
+            }
+            match self.policy {
+                ValidationPolicy::Strict if value.fract() == 0.0 => {
+                    return Err(format!("{field} {value} is not between {min} and {max}"))
+                }
+                ValidationPolicy::Strict => {
+                    return Err(format!("{field} {value} is not a whole number"))
+                }
+                ValidationPolicy::Clamp if value.is_nan() => *value = min,
+                ValidationPolicy::Clamp => *value = value.round().clamp(min, max),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the event or updater `obj`, returning it as is when its values are in range or a
+    /// clamped copy.
+    #[cfg(feature = "wasm")]
+    pub fn check_js_object(&self, obj: js_sys::Object) -> Result<js_sys::Object, String> {
+        if !obj.is_object() {
+            return Err("Event is not an object".to_string());
+        }
+        let values = EventValues::from_js_object(&obj);
+        let mut checked = values;
+        self.apply(&mut checked)?;
+        Ok(if checked == values {
+            obj
+        } else {
+            checked.to_js_object(&obj)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_values(ticks: f64, duration: f64, velocity: f64, note_number: f64) -> EventValues {
+        EventValues {
+            ticks: Some(ticks),
+            duration: Some(duration),
+            velocity: Some(velocity),
+            note_number: Some(note_number),
+        }
+    }
+
+    #[test]
+    fn test_strict_validation_rejects_out_of_range_values() {
+        let validation = Validation::default();
+        let mut values = note_values(0.0, 480.0, 100.0, 60.0);
+        assert_eq!(validation.apply(&mut values), Ok(()));
+        assert_eq!(values, note_values(0.0, 480.0, 100.0, 60.0));
+
+        for mut values in [
+            note_values(0.0, 480.0, 200.0, 60.0),
+            note_values(0.0, 480.0, 100.0, 128.0),
+            note_values(0.0, 0.0, 100.0, 60.0),
+            note_values(-1.0, 480.0, 100.0, 60.0),
+            note_values(0.5, 480.0, 100.0, 60.0),
+        ] {
+            assert!(validation.apply(&mut values).is_err());
+        }
+
+        let mut updater = EventValues {
+            velocity: Some(127.0),
+            ..EventValues::default()
+        };
+        assert_eq!(validation.apply(&mut updater), Ok(()));
+    }
+
+    #[test]
+    fn test_clamp_validation_moves_values_into_range() {
+        let validation = Validation {
+            policy: ValidationPolicy::Clamp,
+            max_ticks: Ticks::new(3840),
+        };
+        let mut values = note_values(5000.0, 0.0, 200.0, f64::NAN);
+        assert_eq!(validation.apply(&mut values), Ok(()));
+        assert_eq!(values, note_values(3840.0, 1.0, 127.0, 0.0));
+
+        let mut values = note_values(119.6, 480.0, 99.5, 60.2);
+        assert_eq!(validation.apply(&mut values), Ok(()));
+        assert_eq!(values, note_values(120.0, 480.0, 100.0, 60.0));
+
+        // an updater moving an event before the start only gets its ticks clamped
+        let mut updater = EventValues {
+            ticks: Some(-5.0),
+            ..EventValues::default()
+        };
+        assert!(Validation::default().apply(&mut updater).is_err());
+        assert_eq!(validation.apply(&mut updater), Ok(()));
+        assert_eq!(
+            updater,
+            EventValues {
+                ticks: Some(0.0),
+                ..EventValues::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Deterministic songs for tests, benchmarks and demos. Built with the `fixtures` feature, which
+//! also adds `Store.createDemoSong`.
+
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, NoteNumber, NoteParameters, Velocity},
+    },
+    shared::{id::Id, rng::Rng, unit::time::Ticks},
+    song::song::Song,
+    track::track::Track,
+};
+#[cfg(feature = "fixtures")]
+use serde::Deserialize;
+#[cfg(feature = "fixtures")]
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "fixtures")]
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FIXTURES_INTERFACES: &'static str = r#"
+export interface DemoSongOptions {
+  title?: string;
+  /** Defaults to 480. */
+  ppq?: number;
+  /** Defaults to 3. */
+  tracks?: number;
+  /** Notes spread over all tracks. Defaults to 1000. */
+  notes?: number;
+  /** The same seed makes the same song, ids included. Defaults to 1. */
+  seed?: number;
+}
+
+export interface Store {
+  /** Replaces the song with generated notes, for demos and benchmarks. */
+  createDemoSong(options?: DemoSongOptions): void;
+}
+"#;
+
+#[cfg(feature = "fixtures")]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DemoSongOptions {
+    pub title: String,
+    pub ppq: u32,
+    pub tracks: usize,
+    pub notes: usize,
+    pub seed: u64,
+}
+
+#[cfg(feature = "fixtures")]
+impl Default for DemoSongOptions {
+    fn default() -> Self {
+        DemoSongOptions {
+            title: "Demo".to_string(),
+            ppq: 480,
+            tracks: 3,
+            notes: 1000,
+            seed: 1,
+        }
+    }
+}
+
+#[cfg(feature = "fixtures")]
+impl DemoSongOptions {
+    pub fn build(&self) -> Song {
+        SongBuilder::new(self.title.clone(), self.ppq)
+            .with_tracks(self.tracks)
+            .with_random_notes(self.notes, self.seed)
+            .build()
+    }
+}
+
+fn random_id(rng: &mut Rng) -> Id {
+    Id::from_u128(((rng.next() as u128) << 64) | rng.next() as u128)
+}
+
+/// Builds songs like `SongBuilder::new("Demo".into(), 480).with_tracks(3).with_random_notes(1000,
+/// seed).build()`. Notes average four per beat across the tracks, so the song grows with them.
+pub struct SongBuilder {
+    title: String,
+    ppq: u32,
+    tracks: usize,
+    notes: usize,
+    seed: u64,
+}
+
+impl SongBuilder {
+    pub fn new(title: String, ppq: u32) -> Self {
+        SongBuilder {
+            title,
+            ppq,
+            tracks: 1,
+            notes: 0,
+            seed: 0,
+        }
+    }
+
+    pub fn with_tracks(self, tracks: usize) -> Self {
+        SongBuilder {
+            tracks: tracks.max(1),
+            ..self
+        }
+    }
+
+    pub fn with_random_notes(self, notes: usize, seed: u64) -> Self {
+        SongBuilder {
+            notes,
+            seed,
+            ..self
+        }
+    }
+
+    /// The song, with no pending changes.
+    pub fn build(self) -> Song {
+        let mut rng = Rng::new(self.seed);
+        let mut song = Song::new(self.title, self.ppq);
+
+        let track_ids: Vec<Id> = (0..self.tracks)
+            .map(|index| {
+                let mut track = Track::new(random_id(&mut rng));
+                track.display.name = Some(format!("Track {}", index + 1));
+                song.add_track(track).id
+            })
+            .collect();
+
+        let beat = self.ppq as u64;
+        let span = (self.notes as u64 * beat / 4).max(beat);
+        for _ in 0..self.notes {
+            let track_id = track_ids[rng.below(track_ids.len() as u64) as usize];
+            let duration = (beat / 4) << rng.below(4);
+            song.add_event(Event::Note(Note {
+                id: random_id(&mut rng),
+                ticks: Ticks::new(rng.below(span)),
+                duration: Ticks::new(duration.max(1)),
+                velocity: Velocity::new(40 + rng.below(88) as u8),
+                note_number: NoteNumber::new(36 + rng.below(61) as u8),
+                track_id,
+                parameters: NoteParameters::new(),
+            }))
+            .unwrap();
+        }
+
+        song.take_changes();
+        song
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_song_builder_is_deterministic() {
+        let build = |seed| {
+            SongBuilder::new("test".to_string(), 480)
+                .with_tracks(3)
+                .with_random_notes(1000, seed)
+                .build()
+        };
+        let song = build(7);
+
+        assert_eq!(song.get_tracks().len(), 3);
+        assert_eq!(song.get_events(None).len(), 1000);
+        assert!(song.get_changes().is_empty());
+        assert!(song
+            .get_tracks()
+            .iter()
+            .all(|track| track.event_count() > 0));
+        assert_eq!(
+            serde_json::to_string(&song).unwrap(),
+            serde_json::to_string(&build(7)).unwrap()
+        );
+        assert_ne!(
+            serde_json::to_string(&song).unwrap(),
+            serde_json::to_string(&build(8)).unwrap()
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    change::change::Change,
+    operation::operation::{apply_operations, Conflict, Operation},
+    shared::id::Id,
+    song::{snapshot::restore, song::Song},
+};
+
+enum HistoryEntry {
+    /// The changes of one mutating call, or of every call in a group.
+    Changes(Vec<Change>),
+    /// The song a new song replaced, `None` when there was none.
+    Song(Option<Box<Song>>),
+    /// The song as it was before, or after, an edit of more than its events and tracks, such as
+    /// a ppq change. Stepping edits the song back into it and keeps the other for the way back.
+    Restore(Box<Song>),
+}
+
+/// What an undo or redo did to the store's song.
+pub enum HistoryStep {
+    /// The song was edited; parts edited elsewhere since are left alone and reported.
+    Changes(Vec<Conflict>),
+    /// The song was swapped for the one it replaced or was replaced by.
+    Song,
+}
+
+/// Undo and redo stacks of local edits. Recording a new edit clears the redo stack.
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    group: Option<Vec<Change>>,
+    group_depth: u32,
+}
+
+impl History {
+    pub fn record(&mut self, changes: &[Change]) {
+        if changes.is_empty() {
+            return;
+        }
+        self.redo_stack.clear();
+        match &mut self.group {
+            Some(group) => group.extend_from_slice(changes),
+            None => self
+                .undo_stack
+                .push(HistoryEntry::Changes(changes.to_vec())),
+        }
+    }
+
+    /// Records that the song `previous` was replaced by a new one. Closes any open group, as its
+    /// changes belong to the replaced song.
+    pub fn record_song(&mut self, previous: Option<Song>) {
+        self.close_group();
+        self.redo_stack.clear();
+        self.undo_stack
+            .push(HistoryEntry::Song(previous.map(Box::new)));
+    }
+
+    /// Records an edit of the whole song that `previous` undoes, such as a ppq change, as one
+    /// entry. The edit's own changes are not recorded besides. Closes any open group.
+    pub fn record_restore(&mut self, previous: Song) {
+        self.close_group();
+        self.redo_stack.clear();
+        self.undo_stack
+            .push(HistoryEntry::Restore(Box::new(previous)));
+    }
+
+    /// Starts collecting the changes of the following calls into one entry. Groups nest; the
+    /// entry is recorded when the outermost group ends.
+    pub fn begin_group(&mut self) {
+        self.group_depth += 1;
+        self.group.get_or_insert_with(Vec::new);
+    }
+
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            self.close_group();
+        }
+    }
+
+    fn close_group(&mut self) {
+        self.group_depth = 0;
+        if let Some(changes) = self.group.take() {
+            if !changes.is_empty() {
+                self.undo_stack.push(HistoryEntry::Changes(changes));
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || self.group.as_ref().is_some_and(|group| !group.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Reverts the latest entry, closing any open group first. The changes this makes to the song
+    /// are left pending for the caller to commit without recording them.
+    pub fn undo(&mut self, song: &mut Option<Song>) -> Option<HistoryStep> {
+        self.close_group();
+        let entry = self.undo_stack.pop()?;
+        let (entry, step) = Self::step(entry, song, true);
+        self.redo_stack.push(entry);
+        Some(step)
+    }
+
+    /// Reverts the track's part of the latest entry that changed it, leaving the rest of that
+    /// entry and later edits of other tracks in place. Looks no further back than the latest song
+    /// replacement, as older entries belong to the replaced song. The reverted part is redone
+    /// like a whole entry.
+    pub fn undo_in_track(&mut self, track_id: &Id, song: &mut Option<Song>) -> Option<HistoryStep> {
+        self.close_group();
+        let index = self
+            .undo_stack
+            .iter()
+            .rev()
+            .take_while(|entry| matches!(entry, HistoryEntry::Changes(_)))
+            .position(|entry| match entry {
+                HistoryEntry::Changes(changes) => {
+                    changes.iter().any(|change| change.touches_track(track_id))
+                }
+                HistoryEntry::Song(_) | HistoryEntry::Restore(_) => false,
+            })
+            .map(|position| self.undo_stack.len() - 1 - position)?;
+        let HistoryEntry::Changes(changes) = &mut self.undo_stack[index] else {
+            return None;
+        };
+
+        let (track_changes, rest): (Vec<Change>, Vec<Change>) = std::mem::take(changes)
+            .into_iter()
+            .partition(|change| change.touches_track(track_id));
+        if rest.is_empty() {
+            self.undo_stack.remove(index);
+        } else {
+            *changes = rest;
+        }
+        let (entry, step) = Self::step(HistoryEntry::Changes(track_changes), song, true);
+        self.redo_stack.push(entry);
+        Some(step)
+    }
+
+    /// Replays the latest undone entry.
+    pub fn redo(&mut self, song: &mut Option<Song>) -> Option<HistoryStep> {
+        self.close_group();
+        let entry = self.redo_stack.pop()?;
+        let (entry, step) = Self::step(entry, song, false);
+        self.undo_stack.push(entry);
+        Some(step)
+    }
+
+    fn step(
+        entry: HistoryEntry,
+        song: &mut Option<Song>,
+        revert: bool,
+    ) -> (HistoryEntry, HistoryStep) {
+        match entry {
+            HistoryEntry::Changes(changes) => {
+                let operations = if revert {
+                    changes.iter().rev().map(Operation::inverse_of).collect()
+                } else {
+                    changes.iter().map(Operation::from_change).collect()
+                };
+                let conflicts = match song.as_mut() {
+                    Some(song) => apply_operations(song, operations),
+                    None => Vec::new(),
+                };
+                (
+                    HistoryEntry::Changes(changes),
+                    HistoryStep::Changes(conflicts),
+                )
+            }
+            HistoryEntry::Song(other) => {
+                let current = std::mem::replace(song, other.map(|song| *song));
+                (HistoryEntry::Song(current.map(Box::new)), HistoryStep::Song)
+            }
+            HistoryEntry::Restore(other) => match song.as_mut() {
+                Some(song) => {
+                    let current = song.snapshot();
+                    restore(song, &other);
+                    (
+                        HistoryEntry::Restore(Box::new(current)),
+                        HistoryStep::Changes(Vec::new()),
+                    )
+                }
+                None => (
+                    HistoryEntry::Restore(other),
+                    HistoryStep::Changes(Vec::new()),
+                ),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::id::Id,
+        song::ppq::rescale_ppq,
+        track::track::Track,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_undo_redo() {
+        let (mut song, track_id) = song_with_track();
+        // the song comes with its track, which the first undo must not take away
+        song.take_changes();
+        let mut history = History::default();
+        history.record_song(None);
+        let mut song = Some(song);
+
+        let record = |song: &mut Option<Song>, history: &mut History| {
+            let changes = song.as_mut().unwrap().take_changes();
+            history.record(&changes);
+        };
+
+        let first = add_note(song.as_mut().unwrap(), track_id, 0, 480, 60, 100);
+        record(&mut song, &mut history);
+
+        history.begin_group();
+        let second = add_note(song.as_mut().unwrap(), track_id, 480, 480, 62, 100);
+        record(&mut song, &mut history);
+        history.begin_group();
+        let third = add_note(song.as_mut().unwrap(), track_id, 960, 480, 64, 100);
+        record(&mut song, &mut history);
+        history.end_group();
+        history.end_group();
+
+        // The group is undone as a unit.
+        assert!(matches!(
+            history.undo(&mut song),
+            Some(HistoryStep::Changes(conflicts)) if conflicts.is_empty()
+        ));
+        let current = song.as_mut().unwrap();
+        current.take_changes();
+        assert!(current.get_event(&first).is_some());
+        assert!(current.get_event(&second).is_none());
+        assert!(current.get_event(&third).is_none());
+
+        history.redo(&mut song).unwrap();
+        assert!(song.as_ref().unwrap().get_event(&third).is_some());
+        assert!(!history.can_redo());
+
+        history.undo(&mut song).unwrap();
+        history.undo(&mut song).unwrap();
+        assert!(song.as_ref().unwrap().get_event(&first).is_none());
+
+        // Undoing the song's creation leaves no song; redoing brings it back.
+        assert!(matches!(history.undo(&mut song), Some(HistoryStep::Song)));
+        assert!(song.is_none());
+        assert!(!history.can_undo());
+        history.redo(&mut song).unwrap();
+        assert!(song.is_some());
+
+        // A new edit drops what was undone.
+        add_note(song.as_mut().unwrap(), track_id, 0, 240, 67, 100);
+        record(&mut song, &mut history);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_undo_in_track() {
+        let (mut song, drums) = song_with_track();
+        let bass = song.add_track(Track::new(Id::new())).id;
+        song.take_changes();
+        let mut history = History::default();
+
+        let kick = add_note(&mut song, drums, 0, 120, 36, 100);
+        let root = add_note(&mut song, bass, 0, 480, 40, 100);
+        history.record(&song.take_changes());
+        let fifth = add_note(&mut song, bass, 480, 480, 47, 100);
+        history.record(&song.take_changes());
+        let mut song = Some(song);
+
+        // The drum note is reverted from under the later bass edit, which stays.
+        assert!(history.undo_in_track(&drums, &mut song).is_some());
+        let current = song.as_mut().unwrap();
+        current.take_changes();
+        assert!(current.get_event(&kick).is_none());
+        assert!(current.get_event(&root).is_some());
+        assert!(current.get_event(&fifth).is_some());
+        assert!(history.undo_in_track(&drums, &mut song).is_none());
+
+        history.undo(&mut song).unwrap();
+        history.undo(&mut song).unwrap();
+        let current = song.as_mut().unwrap();
+        current.take_changes();
+        assert!(current.get_event(&root).is_none());
+        assert!(!history.can_undo());
+
+        history.redo(&mut song).unwrap();
+        history.redo(&mut song).unwrap();
+        history.redo(&mut song).unwrap();
+        assert!(song.as_ref().unwrap().get_event(&kick).is_some());
+    }
+
+    #[test]
+    fn test_undo_redo_track_order() {
+        let (mut song, first) = song_with_track();
+        let second = song.add_track(Track::new(Id::new())).id;
+        song.take_changes();
+        let mut history = History::default();
+        let order = |song: &Option<Song>| -> Vec<Id> {
+            song.as_ref()
+                .unwrap()
+                .get_tracks()
+                .iter()
+                .map(|track| track.id)
+                .collect()
+        };
+
+        let inserted = song
+            .insert_track_at(0, Track::new(Id::new()), Vec::new())
+            .unwrap()
+            .id;
+        song.move_track(&first, 5);
+        history.record(&song.take_changes());
+        let mut song = Some(song);
+        assert_eq!(order(&song), vec![inserted, second, first]);
+
+        history.undo(&mut song).unwrap();
+        song.as_mut().unwrap().take_changes();
+        assert_eq!(order(&song), vec![first, second]);
+
+        history.redo(&mut song).unwrap();
+        assert_eq!(order(&song), vec![inserted, second, first]);
+    }
+
+    #[test]
+    fn test_undo_redo_restore() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 480, 240, 60, 100);
+        song.take_changes();
+        let mut history = History::default();
+
+        let previous = song.snapshot();
+        rescale_ppq(&mut song, 960);
+        history.record_restore(previous);
+        song.take_changes();
+        let mut song = Some(song);
+        let ticks = |song: &Option<Song>| {
+            let song = song.as_ref().unwrap();
+            (
+                song.ppq,
+                song.get_event(&note_id).unwrap().get_ticks().as_u64(),
+            )
+        };
+        assert_eq!(ticks(&song), (960, 960));
+
+        // the whole song goes back, its moved events as changes to publish
+        assert!(matches!(
+            history.undo(&mut song),
+            Some(HistoryStep::Changes(conflicts)) if conflicts.is_empty()
+        ));
+        assert_eq!(ticks(&song), (480, 480));
+        assert_eq!(song.as_mut().unwrap().take_changes().len(), 1);
+
+        history.redo(&mut song).unwrap();
+        assert_eq!(ticks(&song), (960, 960));
+    }
+}
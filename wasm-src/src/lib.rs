@@ -1,5 +1,27 @@
-mod event;
-mod shared;
-mod song;
+//! The song model of the store: songs, tracks and events, with the queries and transforms on
+//! them. The `wasm` feature, on by default, adds the `Store` class and the rest of the JS
+//! bindings; without it the crate is a plain Rust library.
+#![allow(clippy::module_inception)]
+
+pub mod analysis;
+pub mod change;
+pub mod event;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+pub mod history;
+pub mod midi;
+pub mod notation;
+pub mod operation;
+pub mod persistence;
+pub mod query;
+pub mod shared;
+pub mod song;
+#[cfg(feature = "wasm")]
 mod store;
-mod track;
+pub mod track;
+pub mod transform;
+
+pub use event::event::Event;
+pub use shared::unit::time::Ticks;
+pub use song::song::Song;
+pub use track::track::Track;
@@ -0,0 +1,107 @@
+use crate::{
+    midi::{
+        export::{export_midi, MidiExportOptions},
+        import::{import_midi, MidiImportOptions},
+        smf::MidiError,
+    },
+    shared::id::IdStrategy,
+    song::song::Song,
+    track::track::Track,
+    transform::clipboard::Clipboard,
+};
+
+/// Title written to the first track of clipboard SMFs, telling them apart from whole songs.
+const CLIPBOARD_TITLE: &str = "Clipboard";
+
+/// Writes `clipboard` as a format 1 SMF at the song's ppq and tempo, with one track per track
+/// the events were copied from, on that track's channel and program. The first track only holds
+/// the `Clipboard` title and tempo, so DAWs pasting the file see the selection from tick 0.
+pub fn clipboard_to_midi(song: &Song, clipboard: &Clipboard) -> Vec<u8> {
+    let mut selection = Song::new(CLIPBOARD_TITLE.to_string(), song.ppq);
+    for event in clipboard.get_events() {
+        if let Some(track_id) = event
+            .get_track_id()
+            .filter(|track_id| selection.get_track(track_id).is_none())
+        {
+            let track = match song.get_track(&track_id) {
+                Some(track) => track.without_events(),
+                None => Track::new(track_id),
+            };
+            selection.add_track(track);
+        }
+        selection
+            .add_event(event.clone())
+            .expect("The event's track was just added");
+    }
+
+    let options = MidiExportOptions {
+        bpm: song.bpm,
+        ..Default::default()
+    };
+    export_midi(&selection, &options)
+}
+
+/// Reads the notes and control changes of every track of an SMF, such as one copied
+/// from a DAW, rescaled to `ppq`. Tempos, time signatures and markers are left out.
+pub fn clipboard_from_midi(bytes: &[u8], ppq: u32) -> Result<Clipboard, MidiError> {
+    let mut selection = Song::new(CLIPBOARD_TITLE.to_string(), ppq);
+    // the events get the store's ids when they are pasted
+    import_midi(
+        &mut selection,
+        &mut IdStrategy::default(),
+        bytes,
+        MidiImportOptions::default(),
+    )?;
+
+    let events = selection.get_events(None).into_iter().cloned().collect();
+    Ok(Clipboard::from_events(events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        midi::test_helpers::test_smf_bytes,
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_clipboard_midi_round_trip() {
+        let (mut song, track_id) = song_with_track();
+        let note = add_note(&mut song, track_id, 960, 240, 62, 90);
+        let cc = add_control_change(&mut song, track_id, 1200, 1, 64);
+        song.get_track_mut(&track_id).unwrap().channel = Some(3);
+
+        let clipboard = Clipboard::copy(&song, &[note, cc]).unwrap();
+        let bytes = clipboard_to_midi(&song, &clipboard);
+        let (exported, _) =
+            Song::from_smf(&mut IdStrategy::default(), &bytes, Default::default()).unwrap();
+        assert_eq!(exported.title, CLIPBOARD_TITLE);
+        assert_eq!(exported.get_tracks()[0].channel, Some(3));
+
+        let pasted = clipboard_from_midi(&bytes, 480)
+            .unwrap()
+            .paste(
+                &mut song,
+                &mut IdStrategy::default(),
+                Ticks::new(0),
+                Some(track_id),
+            )
+            .unwrap();
+        let note = pasted[0].as_note().unwrap();
+        assert_eq!(
+            (note.ticks, note.duration, note.note_number.as_u8()),
+            (Ticks::new(0), Ticks::new(240), 62)
+        );
+        assert_eq!(pasted[1].get_ticks(), Ticks::new(240));
+
+        let clipboard = clipboard_from_midi(&test_smf_bytes(), 480).unwrap();
+        let ticks: Vec<Ticks> = clipboard
+            .get_events()
+            .iter()
+            .map(|event| event.get_ticks())
+            .collect();
+        assert_eq!(ticks, vec![Ticks::new(0), Ticks::new(480)]);
+    }
+}
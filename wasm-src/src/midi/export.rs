@@ -0,0 +1,394 @@
+use crate::{
+    event::{
+        event::{Event, EventKind, KindOrder},
+        key_signature::KeyMode,
+    },
+    song::{section::markers, song::Song},
+};
+use midly::{
+    live::LiveEvent,
+    num::{u15, u28, u4, u7},
+    Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MIDI_EXPORT_INTERFACES: &'static str = r#"
+export interface MidiExportOptions {
+  /** Fixed tempo written to the file and used to convert millisecond delays. Defaults to 120. */
+  bpm?: number;
+  /** Shift each track by its `delayTicks` and `delayMs` settings. */
+  applyTrackDelay?: boolean;
+  /**
+   * Order of kinds among events written on the same tick, e.g. control changes before notes.
+   * Kinds left out keep the default order of metas, note-offs, control changes and note-ons.
+   */
+  kindOrder?: EventKind[];
+  /** Write the keyswitch from the track's expression map before each articulated note. */
+  keyswitches?: boolean;
+}
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MidiExportOptions {
+    pub bpm: f64,
+    pub apply_track_delay: bool,
+    pub kind_order: KindOrder,
+    pub keyswitches: bool,
+}
+
+impl Default for MidiExportOptions {
+    fn default() -> Self {
+        MidiExportOptions {
+            bpm: 120.0,
+            apply_track_delay: false,
+            kind_order: KindOrder::default(),
+            keyswitches: false,
+        }
+    }
+}
+
+/// Events at the same tick are written metas first, then note-offs, control changes and
+/// note-ons, so a repeated note is released before it sounds again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Order {
+    Meta,
+    NoteOff,
+    ControlChange,
+    NoteOn,
+}
+
+/// An event to write at a tick, with the kind of song event it came from if any.
+type Pending<'a> = (u64, Option<EventKind>, Order, TrackEventKind<'a>);
+
+/// A delta time, shortened to the longest SMF can hold.
+pub fn delta_ticks(ticks: u64) -> u28 {
+    u28::new(ticks.min(u28::max_value().as_int() as u64) as u32)
+}
+
+/// The SMF event for the bytes of a raw message: SysEx, a channel message on the channel it
+/// names, or an escape for anything else.
+fn raw_track_event(data: &[u8]) -> TrackEventKind<'_> {
+    match data {
+        [0xF0, sysex @ ..] => TrackEventKind::SysEx(sysex),
+        _ => match LiveEvent::parse(data) {
+            Ok(LiveEvent::Midi { channel, message }) => TrackEventKind::Midi { channel, message },
+            _ => TrackEventKind::Escape(data),
+        },
+    }
+}
+
+/// Sorts `events` into a track. On the same tick, events that did not come from a song event
+/// such as the tempo go first, then the kinds in `kind_order`, then the rest by `Order`. The
+/// track ends at `end_ticks`, or at its last event if that is later.
+fn to_track<'a>(
+    mut events: Vec<Pending<'a>>,
+    kind_order: &KindOrder,
+    end_ticks: u64,
+) -> Vec<TrackEvent<'a>> {
+    events.sort_by_key(|(ticks, kind, order, _)| {
+        let rank = kind.map_or(0, |kind| kind_order.rank(kind) + 1);
+        (*ticks, rank, *order)
+    });
+
+    let mut ticks = 0;
+    let mut track: Vec<TrackEvent> = events
+        .into_iter()
+        .map(|(event_ticks, _, _, kind)| {
+            let delta = event_ticks - ticks;
+            ticks = event_ticks;
+            TrackEvent {
+                delta: delta_ticks(delta),
+                kind,
+            }
+        })
+        .collect();
+    track.push(TrackEvent {
+        delta: delta_ticks(end_ticks.saturating_sub(ticks)),
+        kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+    });
+    track
+}
+
+/// Writes the song as a format 1 SMF at its ppq.
+///
+/// The first track holds the song title, the tempo, and every time signature and marker,
+/// whichever track they are on. Each song track follows in order with its program change, notes,
+/// control changes and lyrics on its channel, channel 1 if unset. Audio regions are left out.
+/// With `options.keyswitches`, articulated notes are preceded by the keyswitches of their
+/// track's expression map. Every track lasts until the end of the song.
+pub fn export_midi(song: &Song, options: &MidiExportOptions) -> Vec<u8> {
+    let microseconds_per_quarter = (60_000_000.0 / options.bpm).round() as u32;
+    let end_ticks = song.end_of_song().as_u64();
+    let mut conductor = vec![
+        (
+            0,
+            None,
+            Order::Meta,
+            TrackEventKind::Meta(MetaMessage::TrackName(song.title.as_bytes())),
+        ),
+        (
+            0,
+            None,
+            Order::Meta,
+            TrackEventKind::Meta(MetaMessage::Tempo(microseconds_per_quarter.into())),
+        ),
+    ];
+
+    conductor.extend(markers(song).into_iter().map(|marker| {
+        (
+            marker.ticks.as_u64(),
+            Some(EventKind::Marker),
+            Order::Meta,
+            TrackEventKind::Meta(MetaMessage::Marker(marker.name.as_bytes())),
+        )
+    }));
+
+    let keyswitched: Vec<Option<Vec<Event>>> = song
+        .get_tracks()
+        .iter()
+        .map(|track| {
+            options.keyswitches.then(|| {
+                let events = song.track_events(track).iter().cloned().collect();
+                track.expression_map.with_keyswitches(events)
+            })
+        })
+        .collect();
+    let mut tracks = Vec::new();
+
+    for (track, keyswitched) in song.get_tracks().iter().zip(&keyswitched) {
+        let delay = if options.apply_track_delay {
+            track.settings.delay(song.ppq, options.bpm)
+        } else {
+            0
+        };
+        let shift = |ticks: u64| (ticks as i64 + delay).max(0) as u64;
+        let channel = u4::new(track.channel.unwrap_or(0));
+        let mut events: Vec<Pending> = track
+            .program
+            .map(|program| {
+                (
+                    0,
+                    None,
+                    Order::Meta,
+                    TrackEventKind::Midi {
+                        channel,
+                        message: MidiMessage::ProgramChange {
+                            program: u7::new(program),
+                        },
+                    },
+                )
+            })
+            .into_iter()
+            .collect();
+
+        let track_events: Vec<&Event> = match keyswitched {
+            Some(events) => events.iter().collect(),
+            None => song.track_events(track).iter().collect(),
+        };
+
+        for event in track_events {
+            match event {
+                Event::Note(note) => {
+                    let key = u7::new(note.note_number.as_u8());
+                    let start = shift(note.ticks.as_u64());
+                    let end = shift((note.ticks + note.duration).as_u64());
+                    events.push((
+                        start,
+                        Some(EventKind::Note),
+                        Order::NoteOn,
+                        TrackEventKind::Midi {
+                            channel,
+                            message: MidiMessage::NoteOn {
+                                key,
+                                vel: u7::new(note.velocity.as_u8()),
+                            },
+                        },
+                    ));
+                    events.push((
+                        end,
+                        Some(EventKind::Note),
+                        Order::NoteOff,
+                        TrackEventKind::Midi {
+                            channel,
+                            message: MidiMessage::NoteOff {
+                                key,
+                                vel: u7::new(0),
+                            },
+                        },
+                    ));
+                }
+                Event::ControlChange(cc) => events.push((
+                    shift(cc.ticks.as_u64()),
+                    Some(EventKind::ControlChange),
+                    Order::ControlChange,
+                    TrackEventKind::Midi {
+                        channel,
+                        message: MidiMessage::Controller {
+                            controller: u7::new(cc.controller.as_u8()),
+                            value: u7::new(cc.value.as_u8()),
+                        },
+                    },
+                )),
+                Event::Lyric(lyric) => events.push((
+                    shift(lyric.ticks.as_u64()),
+                    Some(EventKind::Lyric),
+                    Order::Meta,
+                    TrackEventKind::Meta(MetaMessage::Lyric(lyric.text.as_bytes())),
+                )),
+                Event::TimeSignature(time_signature) => conductor.push((
+                    time_signature.ticks.as_u64(),
+                    Some(EventKind::TimeSignature),
+                    Order::Meta,
+                    TrackEventKind::Meta(MetaMessage::TimeSignature(
+                        time_signature.numerator,
+                        time_signature.denominator.max(1).trailing_zeros() as u8,
+                        24,
+                        8,
+                    )),
+                )),
+                Event::KeySignature(key) => {
+                    let major_root = match key.mode {
+                        KeyMode::Major => key.root,
+                        KeyMode::Minor => (key.root + 3) % 12,
+                    };
+                    let fifths = (major_root as i8 * 7) % 12;
+                    conductor.push((
+                        key.ticks.as_u64(),
+                        Some(EventKind::KeySignature),
+                        Order::Meta,
+                        TrackEventKind::Meta(MetaMessage::KeySignature(
+                            if fifths > 6 { fifths - 12 } else { fifths },
+                            key.mode == KeyMode::Minor,
+                        )),
+                    ))
+                }
+                Event::RawMidi(raw) => events.push((
+                    shift(raw.ticks.as_u64()),
+                    Some(EventKind::RawMidi),
+                    Order::ControlChange,
+                    raw_track_event(&raw.data),
+                )),
+                // SMF has no chord symbols; markers belong to the song, not to its tracks.
+                Event::AudioRegion(_) | Event::ChordSymbol(_) | Event::Marker(_) => {}
+            }
+        }
+
+        tracks.push(to_track(events, &options.kind_order, end_ticks));
+    }
+
+    tracks.insert(0, to_track(conductor, &options.kind_order, end_ticks));
+
+    let smf = Smf {
+        header: Header::new(
+            Format::Parallel,
+            Timing::Metrical(u15::new(song.ppq as u16)),
+        ),
+        tracks,
+    };
+    let mut bytes = Vec::new();
+    smf.write(&mut bytes).unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::raw_midi::RawMidi,
+        midi::test_helpers::test_smf_bytes,
+        shared::id::{Id, IdStrategy},
+        shared::unit::time::Ticks,
+        song::{meter::MeterMap, section::sections},
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_export_midi_round_trip() {
+        let (mut song, _) = Song::from_smf(
+            &mut IdStrategy::default(),
+            &test_smf_bytes(),
+            Default::default(),
+        )
+        .unwrap();
+        let track_id = song.get_tracks()[0].id;
+        // Released and struck again on the same tick.
+        add_note(&mut song, track_id, 192, 96, 64, 80);
+        let track = song.get_track_mut(&track_id).unwrap();
+        track.settings.delay_ticks = 12;
+        track.channel = Some(2);
+        track.program = Some(33);
+
+        let options = MidiExportOptions {
+            apply_track_delay: true,
+            ..Default::default()
+        };
+        let (exported, _) = Song::from_smf(
+            &mut IdStrategy::default(),
+            &export_midi(&song, &options),
+            Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(exported.title, song.title);
+        assert_eq!(exported.ppq, 96);
+        assert_eq!(exported.get_tracks().len(), 1);
+        assert_eq!(exported.get_tracks()[0].channel, Some(2));
+
+        let notes: Vec<(u64, u64, u8)> = exported
+            .get_events(None)
+            .into_iter()
+            .filter_map(|event| event.as_note())
+            .map(|note| {
+                (
+                    note.ticks.as_u64(),
+                    note.duration.as_u64(),
+                    note.note_number.as_u8(),
+                )
+            })
+            .collect();
+        assert_eq!(notes, vec![(12, 96, 60), (108, 96, 64), (204, 96, 64)]);
+
+        let meters = MeterMap::new(&exported);
+        assert_eq!(meters.segment_at(Ticks::new(0)).numerator, 3);
+        assert_eq!(sections(&exported)[0].marker.name, "Intro");
+    }
+
+    #[test]
+    fn test_export_raw_midi_round_trip() {
+        let (mut song, track_id) = song_with_track();
+        let pitch_bend = vec![0xE3, 0x00, 0x50];
+        let sysex = vec![0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7];
+        for (ticks, data) in [(0, sysex.clone()), (240, pitch_bend.clone())] {
+            song.add_event(Event::RawMidi(RawMidi {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                data,
+                track_id,
+            }))
+            .unwrap();
+        }
+
+        let (exported, _) = Song::from_smf(
+            &mut IdStrategy::default(),
+            &export_midi(&song, &Default::default()),
+            Default::default(),
+        )
+        .unwrap();
+        let raw: Vec<(u64, Vec<u8>)> = exported
+            .get_events(None)
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::RawMidi(raw) => Some((raw.ticks.as_u64(), raw.data.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(raw, vec![(0, sysex), (240, pitch_bend)]);
+        let pitch_bend_id = exported.get_events(None)[1].get_id();
+        assert_eq!(exported.get_channel(&pitch_bend_id), Some(3));
+    }
+}
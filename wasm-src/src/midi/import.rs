@@ -0,0 +1,593 @@
+use super::smf::{self, MidiError, SmfTrack};
+use crate::{
+    event::{
+        control_change::{ControlChange, ControllerNumber, ControllerValue},
+        event::Event,
+        marker::Marker,
+        note::{Note, NoteNumber, NoteParameters, Velocity},
+        raw_midi::RawMidi,
+        time_signature::TimeSignature,
+    },
+    shared::unit::grid::Grid,
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        unit::time::Ticks,
+    },
+    song::{import_snap::ImportSnap, song::Song},
+    track::track::Track,
+    transform::split::channel_track_name,
+};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MIDI_IMPORT_INTERFACES: &'static str = r#"
+/**
+ * Imports SMF track `source` into the existing track `target`, or into a new track named
+ * after the SMF track when `target` is omitted.
+ */
+export interface MidiTrackMapping {
+  source: number;
+  target?: string;
+}
+
+/** How rescaled ticks are rounded; `nearest` rounds halves up. */
+export type MidiRounding = "nearest" | "floor" | "ceil";
+
+export interface MidiImportOptions {
+  mapping?: MidiTrackMapping[];
+  /**
+   * Resolution to rescale the file's ticks to. Without a song, one is created at this ppq
+   * (default: the file's); otherwise it defaults to, and must match, the song's ppq.
+   */
+  ppq?: number;
+  rounding?: MidiRounding;
+  /**
+   * Also import the file's time signatures, onto the first imported track. Defaults to true
+   * when the import creates the song and false otherwise.
+   */
+  timeSignatures?: boolean;
+  /** Also import the file's markers, onto the song. Defaults like `timeSignatures`. */
+  markers?: boolean;
+  /**
+   * Give each MIDI channel of an SMF track imported without a `target` a track of its own, as
+   * format-0 files put every channel on one track. Defaults to false.
+   */
+  splitChannels?: boolean;
+  /**
+   * Snaps rescaled positions within this many song ticks of a `snapGrid` line onto it, cleaning
+   * up files exported from loose sources; positions further off keep their offset. Off by
+   * default.
+   */
+  snapTolerance?: number;
+  /** Grid to snap to. Defaults to sixteenths. */
+  snapGrid?: Grid;
+}
+
+/** Rounding error of the rescaled note starts, note ends and control changes, in song ticks. */
+export interface MidiImportDrift {
+  positions: number;
+  rounded: number;
+  max: number;
+  mean: number;
+}
+
+export interface MidiImportResult {
+  tracks: Track[];
+  drift: MidiImportDrift;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct MidiTrackMapping {
+    pub source: usize,
+    pub target: Option<Id>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MidiRounding {
+    #[default]
+    Nearest,
+    Floor,
+    Ceil,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MidiImportOptions {
+    pub mapping: Option<Vec<MidiTrackMapping>>,
+    pub ppq: Option<u32>,
+    pub rounding: MidiRounding,
+    pub time_signatures: Option<bool>,
+    pub markers: Option<bool>,
+    pub split_channels: bool,
+    pub snap_tolerance: Option<u64>,
+    pub snap_grid: Option<Grid>,
+}
+
+/// How far rescaled positions moved from their exact values, in target ticks.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MidiImportDrift {
+    pub positions: usize,
+    pub rounded: usize,
+    pub max: f64,
+    pub total: f64,
+}
+
+impl MidiImportDrift {
+    pub fn mean(&self) -> f64 {
+        if self.positions == 0 {
+            0.0
+        } else {
+            self.total / self.positions as f64
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(self) -> js_sys::Object {
+        let js_drift = js_sys::Object::new();
+        let set = |key: &str, value: f64| {
+            js_sys::Reflect::set(
+                &js_drift,
+                &JsValue::from_str(key),
+                &JsValue::from_f64(value),
+            )
+            .unwrap();
+        };
+
+        set("positions", self.positions as f64);
+        set("rounded", self.rounded as f64);
+        set("max", self.max);
+        set("mean", self.mean());
+
+        js_drift
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiImport {
+    pub track_ids: Vec<Id>,
+    pub drift: MidiImportDrift,
+}
+
+impl MidiImport {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, song: &Song) -> js_sys::Object {
+        let js_result = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &js_result,
+            &JsValue::from_str("tracks"),
+            &self
+                .track_ids
+                .iter()
+                .map(|track_id| song.track_to_js_object(song.get_track(track_id).unwrap()))
+                .collect::<js_sys::Array>(),
+        )
+        .unwrap();
+        js_sys::Reflect::set(
+            &js_result,
+            &JsValue::from_str("drift"),
+            &self.drift.to_js_object(),
+        )
+        .unwrap();
+        js_result
+    }
+}
+
+/// An import whose tracks are in the song and whose events are added a chunk at a time, so a
+/// large file does not block the host in one long call.
+pub struct MidiImportJob {
+    /// Events still to add, with the MIDI channel of those that came from one.
+    pending: std::vec::IntoIter<(Event, Option<u8>)>,
+    total: usize,
+    pub import: MidiImport,
+    /// Whether the import creates the song, which then starts with no pending changes.
+    pub new_song: bool,
+}
+
+impl MidiImportJob {
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// How many events have been added so far.
+    pub fn done(&self) -> usize {
+        self.total - self.pending.len()
+    }
+
+    /// Adds up to `max_events` more events to `song`, returning how many have been added in all.
+    pub fn step(&mut self, song: &mut Song, max_events: usize) -> usize {
+        for (event, channel) in self.pending.by_ref().take(max_events) {
+            // the track may have been removed while the import was under way
+            let Ok(event) = song.add_event(event) else {
+                continue;
+            };
+            let event_id = event.get_id();
+            if let Some(channel) = channel {
+                song.set_channel(event_id, channel);
+            }
+        }
+        self.done()
+    }
+}
+
+/// Converts file ticks to target ticks and accumulates the drift that rounding introduces.
+struct Rescaler {
+    from_ppq: u64,
+    to_ppq: u64,
+    rounding: MidiRounding,
+    drift: MidiImportDrift,
+    snap: Option<ImportSnap>,
+}
+
+impl Rescaler {
+    fn rescale(&mut self, ticks: u32) -> Ticks {
+        let exact = ticks as u64 * self.to_ppq;
+        let scaled = match self.rounding {
+            MidiRounding::Nearest => (exact + self.from_ppq / 2) / self.from_ppq,
+            MidiRounding::Floor => exact / self.from_ppq,
+            MidiRounding::Ceil => exact.div_ceil(self.from_ppq),
+        };
+
+        let drift = (scaled * self.from_ppq).abs_diff(exact) as f64 / self.from_ppq as f64;
+        self.drift.positions += 1;
+        if drift > 0.0 {
+            self.drift.rounded += 1;
+            self.drift.total += drift;
+            self.drift.max = self.drift.max.max(drift);
+        }
+
+        let scaled = Ticks::new(scaled);
+        match self.snap {
+            Some(snap) => snap.snap(self.to_ppq as u32, scaled),
+            None => scaled,
+        }
+    }
+}
+
+/// The notes, control changes and raw messages of `track`, or of its `channel` only, with their
+/// channels. Raw messages without a channel, such as SysEx, go with the track's lowest channel.
+fn track_events(
+    track: &SmfTrack,
+    channel: Option<u8>,
+    track_id: Id,
+    ids: &mut IdStrategy,
+    rescaler: &mut Rescaler,
+) -> Result<Vec<(Event, Option<u8>)>, StoreError> {
+    let in_channel = |event_channel: u8| channel.is_none_or(|channel| channel == event_channel);
+    let mut events = Vec::new();
+
+    for note in track.notes.iter().filter(|note| in_channel(note.channel)) {
+        let ticks = rescaler.rescale(note.ticks);
+        let end = rescaler.rescale(note.ticks + note.duration);
+        let event = Event::Note(Note {
+            id: ids.next_id()?,
+            ticks,
+            duration: Ticks::new(end.as_u64().saturating_sub(ticks.as_u64()).max(1)),
+            velocity: Velocity::new(note.velocity),
+            note_number: NoteNumber::new(note.key),
+            track_id,
+            parameters: NoteParameters::new(),
+        });
+        events.push((event, Some(note.channel)));
+    }
+
+    for cc in track
+        .control_changes
+        .iter()
+        .filter(|cc| in_channel(cc.channel))
+    {
+        let event = Event::ControlChange(ControlChange {
+            id: ids.next_id()?,
+            ticks: rescaler.rescale(cc.ticks),
+            controller: ControllerNumber::new(cc.controller),
+            value: ControllerValue::new(cc.value),
+            track_id,
+        });
+        events.push((event, Some(cc.channel)));
+    }
+
+    let lowest_channel = channel.is_none_or(|channel| track.channels.first() == Some(&channel));
+    for raw in track.raw.iter().filter(|raw| match raw.channel {
+        Some(raw_channel) => in_channel(raw_channel),
+        None => lowest_channel,
+    }) {
+        let event = Event::RawMidi(RawMidi {
+            id: ids.next_id()?,
+            ticks: rescaler.rescale(raw.ticks),
+            data: raw.data.clone(),
+            track_id,
+        });
+        events.push((event, raw.channel));
+    }
+
+    Ok(events)
+}
+
+/// Imports an SMF into `song`, rescaling its ticks to the song's ppq. Starts and ends are
+/// rounded independently with `options.rounding` (nearest by default, halves rounding up) so
+/// that notes which abut in the file still abut after import; a duration that would round to
+/// zero becomes one tick. Without a mapping every track holding notes, control changes or raw
+/// messages becomes a new track, or one track per channel with `options.split_channels`. Each
+/// imported event remembers its channel, and new tracks whose events share one channel play on
+/// it. With `options.time_signatures`, the first of several time signatures on the same tick
+/// wins and they go on the first imported track; markers go on the song.
+pub fn import_midi(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    bytes: &[u8],
+    options: MidiImportOptions,
+) -> Result<MidiImport, MidiError> {
+    let mut job = start_midi_import(song, ids, bytes, options)?;
+    job.step(song, usize::MAX);
+    Ok(job.import)
+}
+
+/// Starts an `import_midi`: adds the new tracks to `song` and leaves the events to the job.
+pub fn start_midi_import(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    bytes: &[u8],
+    options: MidiImportOptions,
+) -> Result<MidiImportJob, MidiError> {
+    let (smf, file_ppq) = smf::parse(bytes)?;
+    if let Some(ppq) = options.ppq.filter(|ppq| *ppq != song.ppq) {
+        return Err(MidiError::PpqMismatch {
+            target: ppq,
+            song: song.ppq,
+        });
+    }
+
+    let tracks: Vec<SmfTrack> = smf
+        .tracks
+        .iter()
+        .map(|events| SmfTrack::read(events))
+        .collect();
+
+    let mapping = options.mapping.unwrap_or_else(|| {
+        tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| {
+                !track.notes.is_empty()
+                    || !track.control_changes.is_empty()
+                    || !track.raw.is_empty()
+            })
+            .map(|(source, _)| MidiTrackMapping {
+                source,
+                target: None,
+            })
+            .collect()
+    });
+    if let Some(track_id) = mapping
+        .iter()
+        .filter_map(|mapping| mapping.target)
+        .find(|track_id| song.get_track(track_id).is_none())
+    {
+        return Err(MidiError::TrackNotFound(track_id));
+    }
+
+    let mut rescaler = Rescaler {
+        from_ppq: file_ppq as u64,
+        to_ppq: song.ppq as u64,
+        rounding: options.rounding,
+        drift: MidiImportDrift::default(),
+        snap: ImportSnap::from_options(options.snap_tolerance, options.snap_grid),
+    };
+    let mut track_ids = Vec::new();
+    let mut pending = Vec::new();
+
+    for MidiTrackMapping { source, target } in mapping {
+        let Some(track) = tracks.get(source) else {
+            continue;
+        };
+
+        let channels: BTreeSet<u8> = track
+            .notes
+            .iter()
+            .map(|note| note.channel)
+            .chain(track.control_changes.iter().map(|cc| cc.channel))
+            .chain(track.raw.iter().filter_map(|raw| raw.channel))
+            .collect();
+        let only_channel = match channels.len() {
+            1 => channels.first().copied(),
+            _ => None,
+        };
+        let parts: Vec<Option<u8>> = if options.split_channels && target.is_none() {
+            channels.into_iter().map(Some).collect()
+        } else {
+            vec![None]
+        };
+
+        for channel in parts {
+            let track_id = match target {
+                Some(track_id) => track_id,
+                None => {
+                    let mut new_track = Track::new(ids.next_id()?);
+                    new_track.display.name = match channel {
+                        Some(channel) => Some(channel_track_name(track.name.as_deref(), channel)),
+                        None => track.name.clone(),
+                    };
+                    new_track.channel = channel.or(only_channel);
+                    song.add_track(new_track).id
+                }
+            };
+
+            pending.extend(track_events(track, channel, track_id, ids, &mut rescaler)?);
+
+            if !track_ids.contains(&track_id) {
+                track_ids.push(track_id);
+            }
+        }
+    }
+
+    if let (Some(true), Some(track_id)) = (options.time_signatures, track_ids.first()) {
+        let mut time_signatures: Vec<_> = tracks
+            .iter()
+            .flat_map(|track| &track.time_signatures)
+            .collect();
+        time_signatures.sort_by_key(|time_signature| time_signature.ticks);
+        time_signatures.dedup_by_key(|time_signature| time_signature.ticks);
+
+        for time_signature in time_signatures {
+            let event = Event::TimeSignature(TimeSignature {
+                id: ids.next_id()?,
+                ticks: rescaler.rescale(time_signature.ticks),
+                numerator: time_signature.numerator,
+                denominator: time_signature.denominator,
+                track_id: *track_id,
+            });
+            pending.push((event, None));
+        }
+    }
+
+    if options.markers == Some(true) {
+        for marker in tracks.iter().flat_map(|track| &track.markers) {
+            let event = Event::Marker(Marker {
+                id: ids.next_id()?,
+                ticks: rescaler.rescale(marker.ticks),
+                name: marker.text.clone(),
+            });
+            pending.push((event, None));
+        }
+    }
+
+    Ok(MidiImportJob {
+        total: pending.len(),
+        pending: pending.into_iter(),
+        import: MidiImport {
+            track_ids,
+            drift: rescaler.drift,
+        },
+        new_song: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::test_helpers::test_smf_bytes;
+
+    #[test]
+    fn test_import_midi_rescales_to_song_ppq() {
+        let mut song = Song::new("test".to_string(), 480);
+
+        let import = import_midi(
+            &mut song,
+            &mut IdStrategy::default(),
+            &test_smf_bytes(),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(import.track_ids.len(), 1);
+        assert_eq!(import.drift.rounded, 0);
+
+        let events = song.get_events(None);
+        assert_eq!(events.len(), 2);
+        let second = events[1].as_note().unwrap();
+        assert_eq!(second.ticks, Ticks::new(480));
+        assert_eq!(second.duration, Ticks::new(480));
+        assert_eq!(second.note_number, NoteNumber::new(64));
+        assert_eq!(second.velocity, Velocity::new(90));
+    }
+
+    #[test]
+    fn test_midi_import_job_adds_events_in_chunks() {
+        let mut song = Song::new("test".to_string(), 480);
+        let options = MidiImportOptions {
+            markers: Some(true),
+            ..Default::default()
+        };
+        let mut job = start_midi_import(
+            &mut song,
+            &mut IdStrategy::default(),
+            &test_smf_bytes(),
+            options,
+        )
+        .unwrap();
+        assert_eq!(job.total(), 3);
+        assert_eq!(song.get_tracks().len(), 1);
+        assert!(song.get_events(None).is_empty());
+
+        assert_eq!(job.step(&mut song, 2), 2);
+        assert_eq!(song.get_events(None).len(), 2);
+        assert_eq!(job.step(&mut song, 2), 3);
+        assert_eq!(job.step(&mut song, 2), 3);
+        assert_eq!(song.get_events(None).len(), 3);
+        assert_eq!(job.import.track_ids, vec![song.get_tracks()[0].id]);
+    }
+
+    #[test]
+    fn test_import_midi_into_mapped_track() {
+        let mut song = Song::new("test".to_string(), 96);
+        let track_id = Id::new();
+        song.add_track(Track::new(track_id));
+
+        let mapping = vec![MidiTrackMapping {
+            source: 1,
+            target: Some(track_id),
+        }];
+        let options = MidiImportOptions {
+            mapping: Some(mapping),
+            ..Default::default()
+        };
+        let import = import_midi(
+            &mut song,
+            &mut IdStrategy::default(),
+            &test_smf_bytes(),
+            options,
+        )
+        .unwrap();
+
+        assert_eq!(import.track_ids, vec![track_id]);
+        assert_eq!(song.get_tracks().len(), 1);
+        assert_eq!(song.get_track_events(&track_id).len(), 2);
+    }
+
+    #[test]
+    fn test_rescale_reports_rounding_drift() {
+        let mut rescaler = Rescaler {
+            from_ppq: 96,
+            to_ppq: 100,
+            rounding: MidiRounding::Nearest,
+            drift: MidiImportDrift::default(),
+            snap: None,
+        };
+        assert_eq!(rescaler.rescale(96), Ticks::new(100));
+        assert_eq!(rescaler.rescale(1), Ticks::new(1));
+        assert_eq!(rescaler.rescale(47), Ticks::new(49));
+
+        assert_eq!(rescaler.drift.positions, 3);
+        assert_eq!(rescaler.drift.rounded, 2);
+        assert!((rescaler.drift.max - 4.0 / 96.0).abs() < 1e-9);
+
+        rescaler.rounding = MidiRounding::Ceil;
+        assert_eq!(rescaler.rescale(1), Ticks::new(2));
+        rescaler.rounding = MidiRounding::Floor;
+        assert_eq!(rescaler.rescale(47), Ticks::new(48));
+    }
+
+    #[test]
+    fn test_import_midi_rejects_mismatched_ppq() {
+        let mut song = Song::new("test".to_string(), 480);
+        let options = MidiImportOptions {
+            ppq: Some(960),
+            ..Default::default()
+        };
+        assert!(matches!(
+            import_midi(
+                &mut song,
+                &mut IdStrategy::default(),
+                &test_smf_bytes(),
+                options
+            ),
+            Err(MidiError::PpqMismatch { .. })
+        ));
+        assert!(song.get_tracks().is_empty());
+    }
+}
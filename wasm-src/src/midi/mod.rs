@@ -0,0 +1,68 @@
+pub mod clipboard;
+pub mod export;
+pub mod import;
+pub mod preview;
+pub mod smf;
+
+#[cfg(test)]
+pub mod test_helpers {
+    use midly::{
+        num::{u15, u28, u4, u7},
+        Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind,
+    };
+
+    fn note_event(delta: u32, key: u8, vel: u8) -> TrackEvent<'static> {
+        TrackEvent {
+            delta: u28::new(delta),
+            kind: TrackEventKind::Midi {
+                channel: u4::new(0),
+                message: MidiMessage::NoteOn {
+                    key: u7::new(key),
+                    vel: u7::new(vel),
+                },
+            },
+        }
+    }
+
+    /// A format 1 file at 96 ppq: a tempo track in 3/4 with an "Intro" marker and one track with two quarter notes.
+    pub fn test_smf_bytes() -> Vec<u8> {
+        let end = TrackEvent {
+            delta: u28::new(0),
+            kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+        };
+        let smf = Smf {
+            header: Header::new(Format::Parallel, Timing::Metrical(u15::new(96))),
+            tracks: vec![
+                vec![
+                    TrackEvent {
+                        delta: u28::new(0),
+                        kind: TrackEventKind::Meta(MetaMessage::Tempo(500_000.into())),
+                    },
+                    TrackEvent {
+                        delta: u28::new(0),
+                        kind: TrackEventKind::Meta(MetaMessage::TimeSignature(3, 2, 24, 8)),
+                    },
+                    TrackEvent {
+                        delta: u28::new(0),
+                        kind: TrackEventKind::Meta(MetaMessage::Marker(b"Intro")),
+                    },
+                    end,
+                ],
+                vec![
+                    TrackEvent {
+                        delta: u28::new(0),
+                        kind: TrackEventKind::Meta(MetaMessage::TrackName(b"Piano")),
+                    },
+                    note_event(0, 60, 100),
+                    note_event(96, 60, 0),
+                    note_event(0, 64, 90),
+                    note_event(96, 64, 0),
+                    end,
+                ],
+            ],
+        };
+        let mut bytes = Vec::new();
+        smf.write(&mut bytes).unwrap();
+        bytes
+    }
+}
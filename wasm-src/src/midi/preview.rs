@@ -0,0 +1,176 @@
+use super::smf::{self, MidiError, SmfTrack};
+use midly::Format;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MIDI_PREVIEW_INTERFACES: &'static str = r#"
+export interface MidiTrackPreview {
+  index: number;
+  name?: string;
+  channels: number[];
+  eventCount: number;
+  noteCount: number;
+  controlChangeCount: number;
+}
+
+export interface MidiTempoChange {
+  ticks: number;
+  bpm: number;
+}
+
+export interface MidiPreview {
+  format: 0 | 1 | 2;
+  ppq: number;
+  tracks: MidiTrackPreview[];
+  tempoChanges: MidiTempoChange[];
+}
+"#;
+
+/// What an SMF contains, for an import dialog to show before anything is committed.
+#[derive(Debug, Clone)]
+pub struct MidiPreview {
+    pub format: u8,
+    pub ppq: u32,
+    pub tracks: Vec<SmfTrack>,
+}
+
+impl MidiPreview {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MidiError> {
+        let (smf, ppq) = smf::parse(bytes)?;
+        let format = match smf.header.format {
+            Format::SingleTrack => 0,
+            Format::Parallel => 1,
+            Format::Sequential => 2,
+        };
+
+        Ok(MidiPreview {
+            format,
+            ppq,
+            tracks: smf
+                .tracks
+                .iter()
+                .map(|events| SmfTrack::read(events))
+                .collect(),
+        })
+    }
+
+    /// Tempo changes from every track as `(ticks, bpm)`, in file ticks.
+    pub fn tempo_changes(&self) -> Vec<(u32, f64)> {
+        let mut tempos: Vec<_> = self
+            .tracks
+            .iter()
+            .flat_map(|track| track.tempos.iter())
+            .map(|tempo| {
+                (
+                    tempo.ticks,
+                    60_000_000.0 / tempo.microseconds_per_quarter as f64,
+                )
+            })
+            .collect();
+        tempos.sort_by_key(|(ticks, _)| *ticks);
+        tempos
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_preview = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_preview,
+            &JsValue::from_str("format"),
+            &JsValue::from_f64(self.format as f64),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_preview,
+            &JsValue::from_str("ppq"),
+            &JsValue::from_f64(self.ppq as f64),
+        )
+        .unwrap();
+
+        let tracks: js_sys::Array = self
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let js_track = js_sys::Object::new();
+                let set = |key: &str, value: &JsValue| {
+                    js_sys::Reflect::set(&js_track, &JsValue::from_str(key), value).unwrap();
+                };
+
+                set("index", &JsValue::from_f64(index as f64));
+                if let Some(name) = &track.name {
+                    set("name", &JsValue::from_str(name));
+                }
+                set(
+                    "channels",
+                    &track
+                        .channels
+                        .iter()
+                        .map(|channel| JsValue::from_f64(*channel as f64))
+                        .collect::<js_sys::Array>(),
+                );
+                set("eventCount", &JsValue::from_f64(track.event_count as f64));
+                set("noteCount", &JsValue::from_f64(track.notes.len() as f64));
+                set(
+                    "controlChangeCount",
+                    &JsValue::from_f64(track.control_changes.len() as f64),
+                );
+
+                js_track
+            })
+            .collect();
+        js_sys::Reflect::set(&js_preview, &JsValue::from_str("tracks"), &tracks).unwrap();
+
+        let tempo_changes: js_sys::Array = self
+            .tempo_changes()
+            .iter()
+            .map(|(ticks, bpm)| {
+                let js_tempo = js_sys::Object::new();
+                js_sys::Reflect::set(
+                    &js_tempo,
+                    &JsValue::from_str("ticks"),
+                    &JsValue::from_f64(*ticks as f64),
+                )
+                .unwrap();
+                js_sys::Reflect::set(
+                    &js_tempo,
+                    &JsValue::from_str("bpm"),
+                    &JsValue::from_f64(*bpm),
+                )
+                .unwrap();
+                js_tempo
+            })
+            .collect();
+        js_sys::Reflect::set(
+            &js_preview,
+            &JsValue::from_str("tempoChanges"),
+            &tempo_changes,
+        )
+        .unwrap();
+
+        js_preview
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::test_helpers::test_smf_bytes;
+
+    #[test]
+    fn test_preview_midi() {
+        let preview = MidiPreview::from_bytes(&test_smf_bytes()).unwrap();
+
+        assert_eq!(preview.format, 1);
+        assert_eq!(preview.ppq, 96);
+        assert_eq!(preview.tracks.len(), 2);
+        assert_eq!(preview.tracks[1].name.as_deref(), Some("Piano"));
+        assert_eq!(preview.tracks[1].notes.len(), 2);
+        assert!(preview.tracks[0].notes.is_empty());
+        assert_eq!(preview.tempo_changes(), vec![(0, 120.0)]);
+    }
+}
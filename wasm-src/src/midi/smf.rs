@@ -0,0 +1,251 @@
+use crate::shared::{error::StoreError, id::Id};
+use midly::{live::LiveEvent, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fmt::{Display, Formatter},
+};
+
+#[derive(Debug)]
+pub enum MidiError {
+    Parse(midly::Error),
+    /// SMPTE timecode divisions have no ppq to map onto the song's ticks.
+    UnsupportedTiming,
+    /// An import asked for a ppq other than that of the song it imports into.
+    PpqMismatch {
+        target: u32,
+        song: u32,
+    },
+    /// A track mapping targets a track the song does not have.
+    TrackNotFound(Id),
+    /// The store's id strategy could not give the new tracks and events their ids.
+    Id(StoreError),
+}
+
+impl Display for MidiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MidiError::Parse(error) => write!(f, "MIDI file is not valid: {}", error),
+            MidiError::PpqMismatch { target, song } => write!(
+                f,
+                "Target ppq {} does not match the song's ppq {}",
+                target, song
+            ),
+            MidiError::UnsupportedTiming => {
+                write!(f, "MIDI files with timecode timing are not supported")
+            }
+            MidiError::TrackNotFound(id) => write!(f, "Track {} not found", id),
+            MidiError::Id(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<MidiError> for StoreError {
+    fn from(error: MidiError) -> Self {
+        match error {
+            MidiError::TrackNotFound(id) => StoreError::TrackNotFound(id),
+            MidiError::Id(error) => error,
+            error => StoreError::invalid(error.to_string()),
+        }
+    }
+}
+
+impl From<StoreError> for MidiError {
+    fn from(error: StoreError) -> Self {
+        MidiError::Id(error)
+    }
+}
+
+impl From<midly::Error> for MidiError {
+    fn from(error: midly::Error) -> Self {
+        MidiError::Parse(error)
+    }
+}
+
+pub fn parse(bytes: &[u8]) -> Result<(Smf<'_>, u32), MidiError> {
+    let smf = Smf::parse(bytes)?;
+    let ppq = match smf.header.timing {
+        Timing::Metrical(ppq) => ppq.as_int() as u32,
+        Timing::Timecode(..) => return Err(MidiError::UnsupportedTiming),
+    };
+    Ok((smf, ppq))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmfNote {
+    pub ticks: u32,
+    pub duration: u32,
+    pub key: u8,
+    pub velocity: u8,
+    pub channel: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmfControlChange {
+    pub ticks: u32,
+    pub controller: u8,
+    pub value: u8,
+    pub channel: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmfTempo {
+    pub ticks: u32,
+    pub microseconds_per_quarter: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmfTimeSignature {
+    pub ticks: u32,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+/// A message the store has no kind for, as its status and data bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmfRaw {
+    pub ticks: u32,
+    pub data: Vec<u8>,
+    /// The channel of channel messages; SysEx and escapes have none.
+    pub channel: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmfMarker {
+    pub ticks: u32,
+    pub text: String,
+}
+
+/// The contents of one SMF track with note-on/note-off pairs resolved into notes.
+#[derive(Debug, Clone, Default)]
+pub struct SmfTrack {
+    pub name: Option<String>,
+    pub channels: BTreeSet<u8>,
+    pub event_count: usize,
+    pub notes: Vec<SmfNote>,
+    pub control_changes: Vec<SmfControlChange>,
+    pub tempos: Vec<SmfTempo>,
+    pub time_signatures: Vec<SmfTimeSignature>,
+    pub markers: Vec<SmfMarker>,
+    pub raw: Vec<SmfRaw>,
+}
+
+impl SmfTrack {
+    /// Note-offs close the earliest open note of the same key and channel; notes still open at
+    /// the end of the track end there.
+    pub fn read(events: &[TrackEvent]) -> Self {
+        let mut track = SmfTrack {
+            event_count: events.len(),
+            ..Default::default()
+        };
+        let mut open_notes: HashMap<(u8, u8), VecDeque<(u32, u8)>> = HashMap::new();
+        let mut ticks = 0u32;
+
+        for event in events {
+            ticks += event.delta.as_int();
+
+            match event.kind {
+                TrackEventKind::Midi { channel, message } => {
+                    let channel = channel.as_int();
+                    track.channels.insert(channel);
+
+                    match message {
+                        MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                            open_notes
+                                .entry((channel, key.as_int()))
+                                .or_default()
+                                .push_back((ticks, vel.as_int()));
+                        }
+                        MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                            let Some((start, velocity)) = open_notes
+                                .get_mut(&(channel, key.as_int()))
+                                .and_then(|starts| starts.pop_front())
+                            else {
+                                continue;
+                            };
+                            track.notes.push(SmfNote {
+                                ticks: start,
+                                duration: ticks - start,
+                                key: key.as_int(),
+                                velocity,
+                                channel,
+                            });
+                        }
+                        MidiMessage::Controller { controller, value } => {
+                            track.control_changes.push(SmfControlChange {
+                                ticks,
+                                controller: controller.as_int(),
+                                value: value.as_int(),
+                                channel,
+                            });
+                        }
+                        message => {
+                            let mut data = Vec::new();
+                            let event = LiveEvent::Midi {
+                                channel: channel.into(),
+                                message,
+                            };
+                            event.write(&mut data).unwrap();
+                            track.raw.push(SmfRaw {
+                                ticks,
+                                data,
+                                channel: Some(channel),
+                            });
+                        }
+                    }
+                }
+                TrackEventKind::SysEx(data) => {
+                    track.raw.push(SmfRaw {
+                        ticks,
+                        data: [&[0xF0], data].concat(),
+                        channel: None,
+                    });
+                }
+                TrackEventKind::Escape(data) => {
+                    track.raw.push(SmfRaw {
+                        ticks,
+                        data: data.to_vec(),
+                        channel: None,
+                    });
+                }
+                TrackEventKind::Meta(MetaMessage::TrackName(name)) => {
+                    track.name = Some(String::from_utf8_lossy(name).into_owned());
+                }
+                TrackEventKind::Meta(MetaMessage::Tempo(tempo)) => {
+                    track.tempos.push(SmfTempo {
+                        ticks,
+                        microseconds_per_quarter: tempo.as_int(),
+                    });
+                }
+                TrackEventKind::Meta(MetaMessage::TimeSignature(numerator, denominator, ..)) => {
+                    track.time_signatures.push(SmfTimeSignature {
+                        ticks,
+                        numerator,
+                        denominator: 1u8.checked_shl(denominator as u32).unwrap_or(4),
+                    });
+                }
+                TrackEventKind::Meta(MetaMessage::Marker(text)) => {
+                    track.markers.push(SmfMarker {
+                        ticks,
+                        text: String::from_utf8_lossy(text).into_owned(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        for ((channel, key), starts) in open_notes {
+            for (start, velocity) in starts {
+                track.notes.push(SmfNote {
+                    ticks: start,
+                    duration: ticks - start,
+                    key,
+                    velocity,
+                    channel,
+                });
+            }
+        }
+        track.notes.sort_by_key(|note| (note.ticks, note.key));
+
+        track
+    }
+}
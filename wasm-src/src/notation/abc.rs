@@ -0,0 +1,310 @@
+use super::quantize::{quantize_notes, QuantizedNote};
+use crate::{event::note::Note, song::song::Song, track::track::Track};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_NOTATION_INTERFACES: &'static str = r#"
+export interface NotationExportOptions {
+  /** Rounds starts and durations to the nearest notatable value, detecting triplets. */
+  quantize?: boolean;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct NotationExportOptions {
+    pub quantize: bool,
+}
+
+const BEATS_PER_BAR: u64 = 4;
+const BARS_PER_LINE: u64 = 4;
+
+const PITCH_CLASSES: [(char, bool); 12] = [
+    ('C', false),
+    ('C', true),
+    ('D', false),
+    ('D', true),
+    ('E', false),
+    ('F', false),
+    ('F', true),
+    ('G', false),
+    ('G', true),
+    ('A', false),
+    ('A', true),
+    ('B', false),
+];
+
+/// A chord, single note or rest (no notes) between two ticks.
+struct Element {
+    start: u64,
+    end: u64,
+    notes: Vec<u8>,
+    tied: bool,
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Lengths are written relative to `L:1/4`.
+fn write_length(output: &mut String, numerator: u64, denominator: u64) {
+    let divisor = gcd(numerator, denominator).max(1);
+    let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+
+    match (numerator, denominator) {
+        (n, d) if n == d => {}
+        (n, 1) => output.push_str(&n.to_string()),
+        (1, d) => output.push_str(&format!("/{}", d)),
+        (n, d) => output.push_str(&format!("{}/{}", n, d)),
+    }
+}
+
+/// Writes `note_number` with the accidental it needs given those already set in the bar.
+fn write_pitch(output: &mut String, note_number: u8, accidentals: &mut HashMap<(char, i32), bool>) {
+    let (letter, sharp) = PITCH_CLASSES[(note_number % 12) as usize];
+    let octave = (note_number / 12) as i32 - 1;
+
+    let current = accidentals.get(&(letter, octave)).copied().unwrap_or(false);
+    if sharp != current {
+        output.push(if sharp { '^' } else { '=' });
+        accidentals.insert((letter, octave), sharp);
+    }
+
+    if octave >= 5 {
+        output.push(letter.to_ascii_lowercase());
+        output.push_str(&"'".repeat((octave - 5) as usize));
+    } else {
+        output.push(letter);
+        output.push_str(&",".repeat((4 - octave).max(0) as usize));
+    }
+}
+
+/// Lays notes out as one voice: notes starting together form a chord, a chord is cut short by
+/// the next one, and gaps become rests. The last bar is filled up with a rest.
+fn elements(notes: &[QuantizedNote], ppq: u64) -> Vec<Element> {
+    let mut chords: Vec<(u64, u64, Vec<u8>)> = Vec::new();
+    for note in notes {
+        let (start, end) = (note.ticks.as_u64(), (note.ticks + note.duration).as_u64());
+        match chords.last_mut() {
+            Some((chord_start, chord_end, pitches)) if *chord_start == start => {
+                *chord_end = (*chord_end).max(end);
+                pitches.push(note.note_number.as_u8());
+            }
+            _ => chords.push((start, end, vec![note.note_number.as_u8()])),
+        }
+    }
+
+    let mut elements = Vec::new();
+    let mut cursor = 0;
+
+    for (index, (start, end, mut pitches)) in chords.iter().cloned().enumerate() {
+        let end = chords
+            .get(index + 1)
+            .map_or(end, |(next_start, _, _)| end.min(*next_start));
+        if start > cursor {
+            elements.push(Element {
+                start: cursor,
+                end: start,
+                notes: Vec::new(),
+                tied: false,
+            });
+        }
+        pitches.sort();
+        pitches.dedup();
+        elements.push(Element {
+            start,
+            end,
+            notes: pitches,
+            tied: false,
+        });
+        cursor = end;
+    }
+
+    let bar = ppq * BEATS_PER_BAR;
+    let song_end = cursor.div_ceil(bar).max(1) * bar;
+    if song_end > cursor {
+        elements.push(Element {
+            start: cursor,
+            end: song_end,
+            notes: Vec::new(),
+            tied: false,
+        });
+    }
+
+    elements
+}
+
+/// Beats whose element boundaries all lie on the eighth-triplet grid, at least one of them off
+/// the sixteenth grid. Those are written as a `(3:2:n` group.
+fn triplet_beats(elements: &[Element], ppq: u64) -> BTreeSet<u64> {
+    let mut boundaries: HashMap<u64, Vec<u64>> = HashMap::new();
+    for element in elements {
+        for position in [element.start, element.end] {
+            if position % ppq != 0 {
+                boundaries
+                    .entry(position / ppq)
+                    .or_default()
+                    .push(position % ppq);
+            }
+        }
+    }
+
+    boundaries
+        .into_iter()
+        .filter(|(_, offsets)| {
+            offsets.iter().all(|offset| offset * 3 % ppq == 0)
+                && offsets.iter().any(|offset| offset * 4 % ppq != 0)
+        })
+        .map(|(beat, _)| beat)
+        .collect()
+}
+
+/// Splits elements at bar lines and around triplet beats, tying split notes together.
+fn split(elements: Vec<Element>, ppq: u64, triplets: &BTreeSet<u64>) -> Vec<Element> {
+    let bar = ppq * BEATS_PER_BAR;
+    let mut pieces = Vec::new();
+
+    for element in elements {
+        let mut start = element.start;
+
+        while start < element.end {
+            let beat = start / ppq;
+            let mut end = element.end.min((start / bar + 1) * bar);
+            if triplets.contains(&beat) {
+                end = end.min((beat + 1) * ppq);
+            } else if let Some(next) = triplets.range(beat + 1..).next() {
+                end = end.min(*next * ppq);
+            }
+
+            pieces.push(Element {
+                start,
+                end,
+                notes: element.notes.clone(),
+                tied: end < element.end && !element.notes.is_empty(),
+            });
+            start = end;
+        }
+    }
+
+    pieces
+}
+
+/// Renders `track` as a single ABC voice of the song, titled after it, in 4/4 at `L:1/4`. With
+/// `quantize`, starts and durations are first rounded to notatable values (see
+/// `quantize_notes`); otherwise raw timing is written as exact fractions of a quarter.
+pub fn export_abc(song: &Song, track: &Track, quantize: bool) -> String {
+    let (title, ppq) = (song.title.as_str(), song.ppq);
+    let notes: Vec<&Note> = song
+        .track_events(track)
+        .iter()
+        .filter_map(|event| event.as_note())
+        .collect();
+    let notes: Vec<QuantizedNote> = if quantize {
+        quantize_notes(&notes, ppq)
+    } else {
+        notes.into_iter().map(QuantizedNote::from).collect()
+    };
+    let ppq = ppq as u64;
+
+    let elements = elements(&notes, ppq);
+    let triplets = triplet_beats(&elements, ppq);
+    let pieces = split(elements, ppq, &triplets);
+
+    let mut output = format!("X:1\nT:{}\nM:4/4\nL:1/4\nK:C\n", title);
+    let bar = ppq * BEATS_PER_BAR;
+    let mut accidentals = HashMap::new();
+
+    for (index, piece) in pieces.iter().enumerate() {
+        let beat = piece.start / ppq;
+        let in_triplet = triplets.contains(&beat);
+
+        if in_triplet && (index == 0 || pieces[index - 1].start / ppq != beat) {
+            let count = pieces[index..]
+                .iter()
+                .take_while(|other| other.start / ppq == beat)
+                .count();
+            output.push_str(&format!("(3:2:{}", count));
+        }
+
+        match piece.notes.as_slice() {
+            [] => output.push('z'),
+            [note_number] => write_pitch(&mut output, *note_number, &mut accidentals),
+            note_numbers => {
+                output.push('[');
+                for note_number in note_numbers {
+                    write_pitch(&mut output, *note_number, &mut accidentals);
+                }
+                output.push(']');
+            }
+        }
+
+        let length = piece.end - piece.start;
+        if in_triplet {
+            write_length(&mut output, length * 3, ppq * 2);
+        } else {
+            write_length(&mut output, length, ppq);
+        }
+        if piece.tied {
+            output.push('-');
+        }
+
+        if piece.end % bar == 0 {
+            accidentals.clear();
+            if index + 1 == pieces.len() {
+                output.push_str("|]\n");
+            } else if (piece.end / bar).is_multiple_of(BARS_PER_LINE) {
+                output.push_str("|\n");
+            } else {
+                output.push_str(" | ");
+            }
+        } else if piece.end % ppq == 0 {
+            output.push(' ');
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_export_abc() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 0, 480, 64, 100);
+        add_note(&mut song, track_id, 480, 160, 61, 100);
+        add_note(&mut song, track_id, 640, 160, 62, 100);
+        add_note(&mut song, track_id, 800, 160, 61, 100);
+        add_note(&mut song, track_id, 1440, 960, 72, 100);
+
+        let abc = export_abc(&song, song.get_track(&track_id).unwrap(), false);
+        assert_eq!(
+            abc,
+            "X:1\nT:test\nM:4/4\nL:1/4\nK:C\n[CE] (3:2:3^C/2D/2C/2 z c- | c z3|]\n"
+        );
+    }
+
+    #[test]
+    fn test_export_abc_quantized() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 5, 470, 60, 100);
+        add_note(&mut song, track_id, 478, 250, 67, 100);
+
+        let raw = export_abc(&song, song.get_track(&track_id).unwrap(), false);
+        assert!(raw.contains("C47/48"));
+
+        let quantized = export_abc(&song, song.get_track(&track_id).unwrap(), true);
+        assert!(quantized.ends_with("C G/2z5/2|]\n"));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    event::{event::Event, lyric::Lyric},
+    midi::export::delta_ticks,
+    shared::id::Id,
+    song::song::Song,
+};
+use midly::{
+    num::{u15, u28},
+    Format, Header, MetaMessage, Smf, Timing, TrackEvent, TrackEventKind,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LYRIC_EXPORT_INTERFACES: &'static str = r#"
+export interface LyricExportOptions {
+  /** Tracks to take lyrics from; all tracks when omitted. */
+  trackIds?: string[];
+  /** Fixed tempo used to place lyrics in time. Defaults to 120. */
+  bpm?: number;
+}
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LyricExportOptions {
+    pub track_ids: Option<Vec<Id>>,
+    pub bpm: f64,
+}
+
+impl Default for LyricExportOptions {
+    fn default() -> Self {
+        LyricExportOptions {
+            track_ids: None,
+            bpm: 120.0,
+        }
+    }
+}
+
+fn lyrics<'a>(song: &'a Song, track_ids: &Option<Vec<Id>>) -> Vec<&'a Lyric> {
+    let mut lyrics: Vec<&Lyric> = song
+        .get_events(None)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Lyric(lyric) => Some(lyric),
+            _ => None,
+        })
+        .filter(|lyric| {
+            track_ids
+                .as_ref()
+                .is_none_or(|track_ids| track_ids.contains(&lyric.track_id))
+        })
+        .collect();
+    lyrics.sort_by_key(|lyric| (lyric.ticks, lyric.id));
+    lyrics
+}
+
+/// `[mm:ss.xx]`, rounded to the nearest hundredth of a second.
+fn lrc_timestamp(seconds: f64) -> String {
+    let centiseconds = (seconds * 100.0).round() as u64;
+    format!(
+        "[{:02}:{:02}.{:02}]",
+        centiseconds / 6000,
+        centiseconds / 100 % 60,
+        centiseconds % 100
+    )
+}
+
+/// Renders lyrics as LRC, one timestamped line per lyric line. Syllables are joined as written
+/// and a line starts at its first syllable.
+pub fn export_lrc(song: &Song, options: &LyricExportOptions) -> String {
+    let seconds_per_tick = 60.0 / options.bpm / song.ppq as f64;
+    let mut output = format!("[ti:{}]\n", song.title);
+    let mut line: Option<(f64, String)> = None;
+
+    for lyric in lyrics(song, &options.track_ids) {
+        let (_, text) =
+            line.get_or_insert_with(|| (lyric.ticks.as_f64() * seconds_per_tick, String::new()));
+        text.push_str(lyric.text.trim_end_matches(['\n', '\r']));
+
+        if lyric.ends_line() {
+            let (seconds, text) = line.take().unwrap();
+            output.push_str(&format!("{}{}\n", lrc_timestamp(seconds), text.trim()));
+        }
+    }
+
+    if let Some((seconds, text)) = line {
+        output.push_str(&format!("{}{}\n", lrc_timestamp(seconds), text.trim()));
+    }
+
+    output
+}
+
+/// Writes lyrics as a format 0 SMF holding a tempo and one lyric meta event per syllable, at the
+/// song's ppq. Line ends are written as a carriage return, as karaoke players expect.
+pub fn export_lyrics_midi(song: &Song, options: &LyricExportOptions) -> Vec<u8> {
+    let lyrics = lyrics(song, &options.track_ids);
+    let texts: Vec<Vec<u8>> = lyrics
+        .iter()
+        .map(|lyric| {
+            let mut text = lyric.text.trim_end_matches(['\n', '\r']).to_string();
+            if lyric.ends_line() {
+                text.push('\r');
+            }
+            text.into_bytes()
+        })
+        .collect();
+
+    let microseconds_per_quarter = (60_000_000.0 / options.bpm).round() as u32;
+    let mut events = vec![TrackEvent {
+        delta: u28::new(0),
+        kind: TrackEventKind::Meta(MetaMessage::Tempo(microseconds_per_quarter.into())),
+    }];
+
+    let mut ticks = 0;
+    for (lyric, text) in lyrics.iter().zip(&texts) {
+        events.push(TrackEvent {
+            delta: delta_ticks(lyric.ticks.as_u64() - ticks),
+            kind: TrackEventKind::Meta(MetaMessage::Lyric(text)),
+        });
+        ticks = lyric.ticks.as_u64();
+    }
+
+    events.push(TrackEvent {
+        delta: u28::new(0),
+        kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+    });
+
+    let smf = Smf {
+        header: Header::new(
+            Format::SingleTrack,
+            Timing::Metrical(u15::new(song.ppq as u16)),
+        ),
+        tracks: vec![events],
+    };
+    let mut bytes = Vec::new();
+    smf.write(&mut bytes).unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{shared::unit::time::Ticks, transform::test_helpers::song_with_track};
+
+    fn add_lyric(song: &mut Song, track_id: Id, ticks: u64, text: &str) {
+        song.add_event(Event::Lyric(Lyric {
+            id: Id::new(),
+            ticks: Ticks::new(ticks),
+            text: text.to_string(),
+            track_id,
+        }))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_export_lyrics() {
+        let (mut song, track_id) = song_with_track();
+        add_lyric(&mut song, track_id, 0, "Twin");
+        add_lyric(&mut song, track_id, 480, "kle ");
+        add_lyric(&mut song, track_id, 960, "star\n");
+        add_lyric(&mut song, track_id, 28_800, "How ");
+        add_lyric(&mut song, track_id, 29_280, "I");
+
+        let lrc = export_lrc(&song, &LyricExportOptions::default());
+        assert_eq!(lrc, "[ti:test]\n[00:00.00]Twinkle star\n[00:30.00]How I\n");
+
+        let bytes = export_lyrics_midi(&song, &LyricExportOptions::default());
+        let smf = Smf::parse(&bytes).unwrap();
+        let texts: Vec<&[u8]> = smf.tracks[0]
+            .iter()
+            .filter_map(|event| match event.kind {
+                TrackEventKind::Meta(MetaMessage::Lyric(text)) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec![&b"Twin"[..], b"kle ", b"star\r", b"How ", b"I"]);
+    }
+}
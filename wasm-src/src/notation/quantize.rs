@@ -0,0 +1,147 @@
+use crate::{
+    event::note::{Note, NoteNumber},
+    shared::unit::{
+        grid::{Grid, Tuplet},
+        time::Ticks,
+    },
+};
+use std::collections::BTreeMap;
+
+const STRAIGHT: Grid = Grid {
+    division: 16,
+    tuplet: None,
+};
+
+const TRIPLET: Grid = Grid {
+    division: 8,
+    tuplet: Some(Tuplet {
+        actual: 3,
+        normal: 2,
+    }),
+};
+
+/// Straight note values from a sixteenth to a whole note, dotted ones included, in sixteenths.
+const STRAIGHT_VALUES: [u64; 8] = [1, 2, 3, 4, 6, 8, 12, 16];
+
+/// Triplet note values from a sixteenth triplet to a half triplet, in sixteenth triplets.
+const TRIPLET_VALUES: [u64; 4] = [1, 2, 4, 8];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuantizedNote {
+    pub ticks: Ticks,
+    pub duration: Ticks,
+    pub note_number: NoteNumber,
+}
+
+impl From<&Note> for QuantizedNote {
+    fn from(note: &Note) -> Self {
+        QuantizedNote {
+            ticks: note.ticks,
+            duration: note.duration,
+            note_number: note.note_number,
+        }
+    }
+}
+
+fn snap_error(grid: &Grid, ppq: u32, notes: &[&Note]) -> u64 {
+    notes
+        .iter()
+        .map(|note| {
+            grid.snap_from(ppq, Ticks::new(0), note.ticks)
+                .unwrap_or(note.ticks)
+                .as_u64()
+                .abs_diff(note.ticks.as_u64())
+        })
+        .sum()
+}
+
+/// The legal value closest to `duration`; values longer than a whole note snap to quarters.
+fn nearest_value(duration: Ticks, ppq: u32, triplet: bool) -> Ticks {
+    let ppq = ppq as u64;
+    let (values, unit): (&[u64], (u64, u64)) = if triplet {
+        (&TRIPLET_VALUES, (ppq, 6))
+    } else {
+        (&STRAIGHT_VALUES, (ppq, 4))
+    };
+    let to_ticks = |value: u64| (value * unit.0 + unit.1 / 2) / unit.1;
+
+    let whole = ppq * 4;
+    if !triplet && duration.as_u64() > whole {
+        let quarters = (duration.as_u64() + ppq / 2) / ppq;
+        return Ticks::new(quarters * ppq);
+    }
+
+    let ticks = values
+        .iter()
+        .map(|value| to_ticks(*value))
+        .min_by_key(|ticks| ticks.abs_diff(duration.as_u64()))
+        .unwrap();
+    Ticks::new(ticks)
+}
+
+/// Rounds note starts and durations to notatable values.
+///
+/// Each beat is quantized either to sixteenths or to eighth triplets, whichever moves the notes
+/// starting in it the least (sixteenths on a tie). Durations then snap to the nearest legal
+/// value of the same family as their beat.
+pub fn quantize_notes(notes: &[&Note], ppq: u32) -> Vec<QuantizedNote> {
+    let mut beats: BTreeMap<u64, Vec<&Note>> = BTreeMap::new();
+    for note in notes {
+        beats
+            .entry(note.ticks.as_u64() / ppq as u64)
+            .or_default()
+            .push(note);
+    }
+
+    let mut quantized = Vec::new();
+
+    for notes in beats.into_values() {
+        let triplet = snap_error(&TRIPLET, ppq, &notes) < snap_error(&STRAIGHT, ppq, &notes);
+        let grid = if triplet { TRIPLET } else { STRAIGHT };
+
+        for note in notes {
+            quantized.push(QuantizedNote {
+                ticks: grid
+                    .snap_from(ppq, Ticks::new(0), note.ticks)
+                    .unwrap_or(note.ticks),
+                duration: nearest_value(note.duration, ppq, triplet),
+                note_number: note.note_number,
+            });
+        }
+    }
+
+    quantized.sort_by_key(|note| (note.ticks, note.note_number));
+    quantized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_quantize_notes() {
+        let (mut song, track_id) = song_with_track();
+        // A loosely played quarter, then an eighth triplet in the second beat.
+        add_note(&mut song, track_id, 7, 455, 60, 100);
+        add_note(&mut song, track_id, 482, 150, 62, 100);
+        add_note(&mut song, track_id, 645, 170, 64, 100);
+        add_note(&mut song, track_id, 798, 160, 65, 100);
+
+        let notes: Vec<&Note> = song
+            .get_track_events(&track_id)
+            .into_iter()
+            .filter_map(|event| event.as_note())
+            .collect();
+
+        let quantized = quantize_notes(&notes, 480);
+        let positions: Vec<(u64, u64)> = quantized
+            .iter()
+            .map(|note| (note.ticks.as_u64(), note.duration.as_u64()))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![(0, 480), (480, 160), (640, 160), (800, 160)]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod operation;
+pub mod optimistic;
+pub mod queue;
+pub mod transaction;
@@ -0,0 +1,705 @@
+use crate::{
+    change::{change::Change, journal::JournalEntry},
+    event::event::{Event, EventUpdater},
+    shared::id::Id,
+    song::song::Song,
+    track::track::Track,
+};
+#[cfg(feature = "wasm")]
+use crate::{
+    event::event::JsEvent,
+    shared::{
+        error::{parse_id, StoreError},
+        js::to_js_value,
+    },
+};
+use std::collections::HashSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_OPERATION_INTERFACES: &'static str = r#"
+export type Operation = (
+  | { type: "addEvent"; event: Event }
+  | { type: "updateEvent"; event: EventUpdater; expected?: Event }
+  | { type: "removeEvent"; eventId: string }
+  | { type: "addTrack"; track: Track }
+  /** Sets the track's own properties; its events are left alone. */
+  | { type: "updateTrack"; track: Track }
+  /** Moves the track to `index` of the track list, or last when `index` is past the end. */
+  | { type: "moveTrack"; trackId: string; index: number }
+  | { type: "removeTrack"; trackId: string }
+) & {
+  /**
+   * Set on operations from `getPendingOps`. `applyOps` and `receiveOps` skip operations whose
+   * id they have seen before, so a redelivered operation is applied once.
+   */
+  opId?: string;
+};
+
+export type ConflictReason =
+  | "eventNotFound"
+  | "trackNotFound"
+  | "duplicateId"
+  | "kindMismatch"
+  | "concurrentEdit";
+
+/** One operation of the journal, as `getOperationsSince` lists it. */
+export interface OperationRecord {
+  /** Journal seq of the call that made the operation; several operations can share one. */
+  seq: number;
+  op: Operation["type"];
+  payload: Operation;
+}
+
+export interface Conflict {
+  index: number;
+  reason: ConflictReason;
+  current?: Event;
+}
+"#;
+
+#[derive(Clone)]
+pub enum Operation {
+    AddEvent(Event),
+    UpdateEvent {
+        updater: EventUpdater,
+        /// The event as the sender saw it before editing, used to detect concurrent edits.
+        expected: Option<Event>,
+    },
+    RemoveEvent(Id),
+    /// A new track with the events given with it.
+    AddTrack {
+        track: Track,
+        events: Vec<Event>,
+    },
+    UpdateTrack(Track),
+    MoveTrack {
+        track_id: Id,
+        index: usize,
+    },
+    RemoveTrack(Id),
+}
+
+impl Operation {
+    #[cfg(feature = "wasm")]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<Self, StoreError> {
+        if !obj.is_object() {
+            return Err(StoreError::invalid("Operation is not an object"));
+        }
+        let get = |key: &str| js_sys::Reflect::get(&obj, &JsValue::from_str(key)).unwrap();
+        let get_id = |key: &str| {
+            let id = get(key)
+                .as_string()
+                .ok_or_else(|| StoreError::invalid(format!("Operation {} is not set", key)))?;
+            parse_id(&id)
+        };
+        let op_type = get("type")
+            .as_string()
+            .ok_or_else(|| StoreError::invalid("Operation type is not set"))?;
+
+        Ok(match op_type.as_str() {
+            "addEvent" => Operation::AddEvent(Event::from_js_object(get("event").into())?),
+            "updateEvent" => Operation::UpdateEvent {
+                updater: EventUpdater::from_js_object(get("event").into())?,
+                expected: if get("expected").is_undefined() {
+                    None
+                } else {
+                    Some(Event::from_js_object(get("expected").into())?)
+                },
+            },
+            "removeEvent" => Operation::RemoveEvent(get_id("eventId")?),
+            "addTrack" => {
+                let (track, events) = Track::from_js_object(get("track").into())?;
+                Operation::AddTrack { track, events }
+            }
+            "updateTrack" => Operation::UpdateTrack(Track::from_js_object(get("track").into())?.0),
+            "moveTrack" => Operation::MoveTrack {
+                track_id: get_id("trackId")?,
+                index: get("index")
+                    .as_f64()
+                    .ok_or_else(|| StoreError::invalid("Operation index is not set"))?
+                    .max(0.0) as usize,
+            },
+            "removeTrack" => Operation::RemoveTrack(get_id("trackId")?),
+            _ => {
+                return Err(StoreError::invalid(format!(
+                    "Operation type \"{}\" is not valid",
+                    op_type
+                )))
+            }
+        })
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Operation::AddEvent(_) => "addEvent",
+            Operation::UpdateEvent { .. } => "updateEvent",
+            Operation::RemoveEvent(_) => "removeEvent",
+            Operation::AddTrack { .. } => "addTrack",
+            Operation::UpdateTrack(_) => "updateTrack",
+            Operation::MoveTrack { .. } => "moveTrack",
+            Operation::RemoveTrack(_) => "removeTrack",
+        }
+    }
+
+    /// The `opId` of an operation passed in from JS, if it has one.
+    #[cfg(feature = "wasm")]
+    pub fn id_from_js_object(obj: &js_sys::Object) -> Result<Option<Id>, StoreError> {
+        js_sys::Reflect::get(obj, &JsValue::from_str("opId"))
+            .unwrap()
+            .as_string()
+            .map(|id| parse_id(&id))
+            .transpose()
+    }
+
+    /// The operation that replays `change`.
+    pub fn from_change(change: &Change) -> Self {
+        match change {
+            Change::EventAdded(event) => Operation::AddEvent(event.clone()),
+            Change::EventUpdated { before, after } => Operation::UpdateEvent {
+                updater: after.into(),
+                expected: Some(before.clone()),
+            },
+            Change::EventRemoved(event) => Operation::RemoveEvent(event.get_id()),
+            Change::TrackAdded(track) => Operation::AddTrack {
+                track: track.clone(),
+                events: Vec::new(),
+            },
+            Change::TrackUpdated { after, .. } => Operation::UpdateTrack((**after).clone()),
+            Change::TrackRemoved(track) => Operation::RemoveTrack(track.id),
+            Change::TrackMoved { track_id, to, .. } => Operation::MoveTrack {
+                track_id: *track_id,
+                index: *to,
+            },
+        }
+    }
+
+    /// The operation that reverts `change`.
+    pub fn inverse_of(change: &Change) -> Self {
+        match change {
+            Change::EventAdded(event) => Operation::RemoveEvent(event.get_id()),
+            Change::EventUpdated { before, after } => Operation::UpdateEvent {
+                updater: before.into(),
+                expected: Some(after.clone()),
+            },
+            Change::EventRemoved(event) => Operation::AddEvent(event.clone()),
+            Change::TrackAdded(track) => Operation::RemoveTrack(track.id),
+            Change::TrackUpdated { before, .. } => Operation::UpdateTrack((**before).clone()),
+            Change::TrackRemoved(track) => Operation::AddTrack {
+                track: track.clone(),
+                events: Vec::new(),
+            },
+            Change::TrackMoved { track_id, from, .. } => Operation::MoveTrack {
+                track_id: *track_id,
+                index: *from,
+            },
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_op = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_op, &JsValue::from_str(key), value).unwrap();
+        };
+
+        match self {
+            Operation::AddEvent(event) => {
+                set("type", &JsValue::from_str("addEvent"));
+                set("event", &event.to_js_object());
+            }
+            Operation::UpdateEvent { updater, expected } => {
+                set("type", &JsValue::from_str("updateEvent"));
+                set("event", &updater.to_js_object());
+                if let Some(expected) = expected {
+                    set("expected", &expected.to_js_object());
+                }
+            }
+            Operation::RemoveEvent(event_id) => {
+                set("type", &JsValue::from_str("removeEvent"));
+                set("eventId", &JsValue::from_str(&event_id.to_string()));
+            }
+            Operation::AddTrack { track, events } => {
+                set("type", &JsValue::from_str("addTrack"));
+                let events: Vec<JsEvent> = events.iter().map(JsEvent::from).collect();
+                set("track", &to_js_value(&track.to_js(events)));
+            }
+            Operation::UpdateTrack(track) => {
+                set("type", &JsValue::from_str("updateTrack"));
+                set("track", &to_js_value(&track.to_js(Vec::<JsEvent>::new())));
+            }
+            Operation::MoveTrack { track_id, index } => {
+                set("type", &JsValue::from_str("moveTrack"));
+                set("trackId", &JsValue::from_str(&track_id.to_string()));
+                set("index", &JsValue::from_f64(*index as f64));
+            }
+            Operation::RemoveTrack(track_id) => {
+                set("type", &JsValue::from_str("removeTrack"));
+                set("trackId", &JsValue::from_str(&track_id.to_string()));
+            }
+        }
+
+        js_op
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictReason {
+    EventNotFound,
+    TrackNotFound,
+    DuplicateId,
+    KindMismatch,
+    ConcurrentEdit,
+}
+
+impl ConflictReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictReason::EventNotFound => "eventNotFound",
+            ConflictReason::TrackNotFound => "trackNotFound",
+            ConflictReason::DuplicateId => "duplicateId",
+            ConflictReason::KindMismatch => "kindMismatch",
+            ConflictReason::ConcurrentEdit => "concurrentEdit",
+        }
+    }
+}
+
+/// An operation that was skipped, with the event's current state when there is one.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub index: usize,
+    pub reason: ConflictReason,
+    pub current: Option<Event>,
+}
+
+impl Conflict {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_conflict = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_conflict,
+            &JsValue::from_str("index"),
+            &JsValue::from_f64(self.index as f64),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_conflict,
+            &JsValue::from_str("reason"),
+            &JsValue::from_str(self.reason.as_str()),
+        )
+        .unwrap();
+
+        if let Some(current) = &self.current {
+            js_sys::Reflect::set(
+                &js_conflict,
+                &JsValue::from_str("current"),
+                &current.to_js_object(),
+            )
+            .unwrap();
+        }
+
+        js_conflict
+    }
+}
+
+fn check(song: &Song, operation: &Operation) -> Result<(), (ConflictReason, Option<Event>)> {
+    let track_exists = |track_id: &Id| song.get_track(track_id).is_some();
+
+    match operation {
+        Operation::AddEvent(event) => {
+            if let Some(current) = song.get_event(&event.get_id()) {
+                return Err((ConflictReason::DuplicateId, Some(current.clone())));
+            }
+            if event.get_track_id().is_some_and(|id| !track_exists(&id)) {
+                return Err((ConflictReason::TrackNotFound, None));
+            }
+        }
+        Operation::UpdateEvent { updater, expected } => {
+            let Some(current) = song.get_event(&updater.get_id()) else {
+                return Err((ConflictReason::EventNotFound, None));
+            };
+            if !current.accepts(updater) {
+                return Err((ConflictReason::KindMismatch, Some(current.clone())));
+            }
+            if expected
+                .as_ref()
+                .is_some_and(|expected| expected != current)
+            {
+                return Err((ConflictReason::ConcurrentEdit, Some(current.clone())));
+            }
+            if updater.get_track_id().is_some_and(|id| !track_exists(&id)) {
+                return Err((ConflictReason::TrackNotFound, Some(current.clone())));
+            }
+        }
+        Operation::RemoveEvent(event_id) => {
+            if song.get_event(event_id).is_none() {
+                return Err((ConflictReason::EventNotFound, None));
+            }
+        }
+        Operation::AddTrack { track, events } => {
+            if track_exists(&track.id) {
+                return Err((ConflictReason::DuplicateId, None));
+            }
+            let known_event = events
+                .iter()
+                .find_map(|event| song.get_event(&event.get_id()));
+            if let Some(current) = known_event {
+                return Err((ConflictReason::DuplicateId, Some(current.clone())));
+            }
+        }
+        Operation::UpdateTrack(Track { id: track_id, .. })
+        | Operation::MoveTrack { track_id, .. } => {
+            if !track_exists(track_id) {
+                return Err((ConflictReason::TrackNotFound, None));
+            }
+        }
+        Operation::RemoveTrack(track_id) => {
+            if !track_exists(track_id) {
+                return Err((ConflictReason::TrackNotFound, None));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An operation with the journal seq of the call that made it.
+pub struct OperationRecord {
+    pub seq: u64,
+    pub operation: Operation,
+}
+
+impl OperationRecord {
+    /// The operations replaying `entries`, in order.
+    pub fn from_entries(entries: &[JournalEntry]) -> Vec<Self> {
+        entries
+            .iter()
+            .flat_map(|entry| {
+                entry.changes.iter().map(|change| OperationRecord {
+                    seq: entry.seq,
+                    operation: Operation::from_change(change),
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<Self, StoreError> {
+        if !obj.is_object() {
+            return Err(StoreError::invalid("Operation record is not an object"));
+        }
+        let get = |key: &str| js_sys::Reflect::get(&obj, &JsValue::from_str(key)).unwrap();
+        let seq = get("seq")
+            .as_f64()
+            .ok_or_else(|| StoreError::invalid("Operation seq is not set"))?;
+        Ok(OperationRecord {
+            seq: seq as u64,
+            operation: Operation::from_js_object(get("payload").into())?,
+        })
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_record = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_record, &JsValue::from_str(key), value).unwrap();
+        };
+        set("seq", &JsValue::from_f64(self.seq as f64));
+        set("op", &JsValue::from_str(self.operation.type_name()));
+        set("payload", &self.operation.to_js_object());
+        js_record
+    }
+}
+
+/// Applies `records` in seq order, letting each operation overwrite what it touches: adding an
+/// existing event or track replaces it, updates ignore their `expected` event, and removing
+/// what is already gone does nothing. Operations still skipped, such as updates of missing
+/// events, are reported under their index in `records`.
+pub fn apply_last_writer_wins(song: &mut Song, records: Vec<OperationRecord>) -> Vec<Conflict> {
+    let mut records: Vec<(usize, OperationRecord)> = records.into_iter().enumerate().collect();
+    records.sort_by_key(|(_, record)| record.seq);
+
+    let mut conflicts = Vec::new();
+    for (index, record) in records {
+        let operations = match record.operation {
+            Operation::AddEvent(event) => match song.get_event(&event.get_id()) {
+                Some(current) if current.get_kind() == event.get_kind() => {
+                    vec![Operation::UpdateEvent {
+                        updater: (&event).into(),
+                        expected: None,
+                    }]
+                }
+                Some(_) => vec![
+                    Operation::RemoveEvent(event.get_id()),
+                    Operation::AddEvent(event),
+                ],
+                None => vec![Operation::AddEvent(event)],
+            },
+            Operation::UpdateEvent { updater, .. } => vec![Operation::UpdateEvent {
+                updater,
+                expected: None,
+            }],
+            Operation::RemoveEvent(event_id) if song.get_event(&event_id).is_none() => vec![],
+            Operation::AddTrack { track, .. } if song.get_track(&track.id).is_some() => {
+                vec![Operation::UpdateTrack(track)]
+            }
+            Operation::RemoveTrack(track_id) if song.get_track(&track_id).is_none() => vec![],
+            operation => vec![operation],
+        };
+        conflicts.extend(
+            apply_operations(song, operations)
+                .into_iter()
+                .map(|conflict| Conflict { index, ..conflict }),
+        );
+    }
+    conflicts
+}
+
+/// Ids of the operations received from elsewhere, so operations delivered more than once are
+/// applied once.
+#[derive(Debug, Default)]
+pub struct ReceivedOperations(HashSet<Id>);
+
+impl ReceivedOperations {
+    /// Leaves out the operations whose id was received before, remembering the ids of the rest.
+    /// Returns the new operations with their indexes in `operations`.
+    pub fn filter_new(
+        &mut self,
+        operations: Vec<(Option<Id>, Operation)>,
+    ) -> (Vec<usize>, Vec<Operation>) {
+        operations
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (op_id, _))| op_id.is_none_or(|op_id| self.0.insert(op_id)))
+            .map(|(index, (_, operation))| (index, operation))
+            .unzip()
+    }
+}
+
+/// Points conflicts from applying the operations `filter_new` kept back at the operations as
+/// they were received.
+pub fn restore_conflict_indexes(conflicts: &mut [Conflict], indexes: &[usize]) {
+    for conflict in conflicts {
+        conflict.index = indexes[conflict.index];
+    }
+}
+
+/// Reverts `changes`, last first. Parts that were edited again since are left alone and
+/// reported as conflicts.
+pub fn revert_changes(song: &mut Song, changes: &[Change]) -> Vec<Conflict> {
+    let reverts = changes.iter().rev().map(Operation::inverse_of).collect();
+    apply_operations(song, reverts)
+}
+
+/// Applies `operations` in order, skipping the ones that no longer fit the song instead of
+/// failing the whole batch. Returns one conflict per skipped operation.
+pub fn apply_operations(song: &mut Song, operations: Vec<Operation>) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+
+    for (index, operation) in operations.into_iter().enumerate() {
+        if let Err((reason, current)) = check(song, &operation) {
+            conflicts.push(Conflict {
+                index,
+                reason,
+                current,
+            });
+            continue;
+        }
+
+        match operation {
+            Operation::AddEvent(event) => {
+                song.add_event(event).expect("Checked above");
+            }
+            Operation::UpdateEvent { updater, .. } => {
+                song.update_event(updater).expect("Checked above");
+            }
+            Operation::RemoveEvent(event_id) => song.remove_event(&event_id),
+            Operation::AddTrack { track, events } => {
+                song.add_track_with_events(track, events)
+                    .expect("Checked above");
+            }
+            Operation::UpdateTrack(track) => {
+                song.update_track(&track.id, |current| current.set_properties(&track));
+            }
+            Operation::MoveTrack { track_id, index } => {
+                song.move_track(&track_id, index);
+            }
+            Operation::RemoveTrack(track_id) => song.remove_track(&track_id),
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::note::NoteUpdater,
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    fn move_note(id: Id, ticks: u64) -> EventUpdater {
+        EventUpdater::Note(NoteUpdater {
+            id,
+            ticks: Some(Ticks::new(ticks)),
+            duration: None,
+            velocity: None,
+            note_number: None,
+            track_id: None,
+            parameters: None,
+        })
+    }
+
+    #[test]
+    fn test_apply_operations_reports_conflicts() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let stale = song.get_event(&note_id).unwrap().clone();
+        song.update_event(move_note(note_id, 240)).unwrap();
+
+        let removed_id = add_note(&mut song, track_id, 960, 480, 60, 100);
+        song.remove_event(&removed_id);
+
+        let conflicts = apply_operations(
+            &mut song,
+            vec![
+                Operation::UpdateEvent {
+                    updater: move_note(note_id, 480),
+                    expected: Some(stale),
+                },
+                Operation::RemoveEvent(removed_id),
+                Operation::RemoveTrack(Id::new()),
+                Operation::UpdateEvent {
+                    updater: move_note(note_id, 720),
+                    expected: None,
+                },
+            ],
+        );
+
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts[0].index, 0);
+        assert_eq!(conflicts[0].reason, ConflictReason::ConcurrentEdit);
+        assert_eq!(
+            conflicts[0].current.as_ref().unwrap().get_ticks(),
+            Ticks::new(240)
+        );
+        assert_eq!(conflicts[1].reason, ConflictReason::EventNotFound);
+        assert_eq!(conflicts[2].reason, ConflictReason::TrackNotFound);
+
+        // the valid operation at the end of the batch is still applied
+        assert_eq!(
+            song.get_event(&note_id).unwrap().get_ticks(),
+            Ticks::new(720)
+        );
+    }
+
+    #[test]
+    fn test_apply_operations_rejects_duplicate_ids() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let existing = song.get_event(&note_id).unwrap().clone();
+
+        let conflicts = apply_operations(
+            &mut song,
+            vec![
+                Operation::AddEvent(existing.clone().with_ticks(Ticks::new(960))),
+                Operation::AddTrack {
+                    track: Track::new(track_id),
+                    events: Vec::new(),
+                },
+            ],
+        );
+
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].reason, ConflictReason::DuplicateId);
+        assert_eq!(conflicts[0].current, Some(existing));
+        assert_eq!(conflicts[1].reason, ConflictReason::DuplicateId);
+        assert_eq!(song.get_events(None).len(), 1);
+    }
+
+    #[test]
+    fn test_received_operations_skip_redeliveries() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let (op_id, other_op_id) = (Id::new(), Id::new());
+        let mut received = ReceivedOperations::default();
+
+        let (indexes, operations) =
+            received.filter_new(vec![(Some(op_id), Operation::RemoveEvent(note_id))]);
+        assert_eq!(indexes, vec![0]);
+        assert!(apply_operations(&mut song, operations).is_empty());
+
+        // the first operation arrives again, along with a new one and one without an id
+        let (indexes, operations) = received.filter_new(vec![
+            (Some(op_id), Operation::RemoveEvent(note_id)),
+            (Some(other_op_id), Operation::RemoveTrack(Id::new())),
+            (None, Operation::RemoveEvent(note_id)),
+            (Some(other_op_id), Operation::RemoveTrack(Id::new())),
+        ]);
+        assert_eq!(indexes, vec![1, 2]);
+
+        let mut conflicts = apply_operations(&mut song, operations);
+        restore_conflict_indexes(&mut conflicts, &indexes);
+        let conflicts: Vec<_> = conflicts
+            .iter()
+            .map(|conflict| (conflict.index, conflict.reason))
+            .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                (1, ConflictReason::TrackNotFound),
+                (2, ConflictReason::EventNotFound)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_last_writer_wins() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let stale = song.get_event(&note_id).unwrap().clone();
+        song.update_event(move_note(note_id, 240)).unwrap();
+        song.commit(None);
+        let records = OperationRecord::from_entries(song.get_journal().entries_since(0));
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].operation.type_name(), "addTrack");
+
+        let record = |seq, operation| OperationRecord { seq, operation };
+        let conflicts = apply_last_writer_wins(
+            &mut song,
+            vec![
+                record(
+                    2,
+                    Operation::UpdateEvent {
+                        updater: move_note(note_id, 960),
+                        expected: Some(stale.clone()),
+                    },
+                ),
+                record(1, Operation::AddEvent(stale.with_ticks(Ticks::new(480)))),
+                record(1, Operation::RemoveTrack(Id::new())),
+                record(
+                    3,
+                    Operation::UpdateEvent {
+                        updater: move_note(Id::new(), 0),
+                        expected: None,
+                    },
+                ),
+            ],
+        );
+
+        // the update with the highest seq is applied last and wins despite its stale `expected`
+        assert_eq!(
+            song.get_event(&note_id).unwrap().get_ticks(),
+            Ticks::new(960)
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].index, 3);
+        assert_eq!(conflicts[0].reason, ConflictReason::EventNotFound);
+    }
+}
@@ -0,0 +1,128 @@
+use super::operation::{apply_operations, Conflict, Operation};
+use crate::{change::change::Change, song::song::Song};
+
+struct OptimisticEntry {
+    token: u32,
+    seq: Option<u64>,
+    changes: Vec<Change>,
+}
+
+/// Provisionally applied batches, kept until they are confirmed or rolled back.
+#[derive(Default)]
+pub struct OptimisticUpdates {
+    entries: Vec<OptimisticEntry>,
+    next_token: u32,
+}
+
+impl OptimisticUpdates {
+    /// Remembers `changes`, recorded under journal entry `seq`, and returns a token for them.
+    pub fn insert(&mut self, seq: Option<u64>, changes: Vec<Change>) -> u32 {
+        self.next_token += 1;
+        self.entries.push(OptimisticEntry {
+            token: self.next_token,
+            seq,
+            changes,
+        });
+        self.next_token
+    }
+
+    pub fn confirm(&mut self, token: u32) {
+        self.entries.retain(|entry| entry.token != token);
+    }
+
+    /// Reverts the batch behind `token`. Parts that were edited again since are left alone and
+    /// reported as conflicts. Returns the batch's journal seq along with the conflicts, or `None`
+    /// for an unknown token.
+    pub fn rollback(
+        &mut self,
+        song: &mut Song,
+        token: u32,
+    ) -> Option<(Option<u64>, Vec<Conflict>)> {
+        let position = self.entries.iter().position(|entry| entry.token == token)?;
+        let entry = self.entries.remove(position);
+
+        let reverts = entry
+            .changes
+            .iter()
+            .rev()
+            .map(Operation::inverse_of)
+            .collect();
+
+        Some((entry.seq, apply_operations(song, reverts)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{
+            event::EventUpdater,
+            note::{NoteNumber, NoteUpdater},
+        },
+        operation::operation::ConflictReason,
+        shared::{id::Id, unit::time::Ticks},
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    fn set_note_number(id: Id, note_number: u8) -> EventUpdater {
+        EventUpdater::Note(NoteUpdater {
+            id,
+            ticks: None,
+            duration: None,
+            velocity: None,
+            note_number: Some(NoteNumber::new(note_number)),
+            track_id: None,
+            parameters: None,
+        })
+    }
+
+    #[test]
+    fn test_rollback_reverts_optimistic_batch() {
+        let (mut song, track_id) = song_with_track();
+        let kept_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let edited_id = add_note(&mut song, track_id, 480, 480, 60, 100);
+        song.take_changes();
+
+        let mut optimistic = OptimisticUpdates::default();
+
+        let added = song
+            .get_event(&kept_id)
+            .unwrap()
+            .clone()
+            .with_id(Id::new())
+            .with_ticks(Ticks::new(960));
+        let added_id = added.get_id();
+        apply_operations(
+            &mut song,
+            vec![
+                Operation::AddEvent(added),
+                Operation::UpdateEvent {
+                    updater: set_note_number(kept_id, 62),
+                    expected: None,
+                },
+                Operation::UpdateEvent {
+                    updater: set_note_number(edited_id, 62),
+                    expected: None,
+                },
+            ],
+        );
+        let token = optimistic.insert(Some(1), song.take_changes());
+
+        // edited again before the server answered
+        song.update_event(set_note_number(edited_id, 64)).unwrap();
+
+        let (seq, conflicts) = optimistic.rollback(&mut song, token).unwrap();
+        assert_eq!(seq, Some(1));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].reason, ConflictReason::ConcurrentEdit);
+
+        assert!(song.get_event(&added_id).is_none());
+        let kept = song.get_event(&kept_id).unwrap().as_note().unwrap();
+        assert_eq!(kept.note_number, NoteNumber::new(60));
+        let edited = song.get_event(&edited_id).unwrap().as_note().unwrap();
+        assert_eq!(edited.note_number, NoteNumber::new(64));
+
+        assert!(optimistic.rollback(&mut song, token).is_none());
+    }
+}
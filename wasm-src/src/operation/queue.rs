@@ -0,0 +1,289 @@
+use super::operation::{apply_operations, Conflict, ConflictReason, Operation};
+use crate::{
+    change::{change::Change, journal::JournalEntry},
+    shared::id::{Id, IdStrategy},
+    song::song::Song,
+};
+use std::collections::HashSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_QUEUE_INTERFACES: &'static str = r#"
+export interface PendingOps {
+  seq: number;
+  ops: Operation[];
+}
+
+export interface DroppedOp {
+  seq: number;
+  op: Operation;
+  reason: ConflictReason;
+}
+
+export interface RebaseReport {
+  conflicts: Conflict[];
+  dropped: DroppedOp[];
+}
+"#;
+
+/// Local changes from one journal entry that the server has not acknowledged yet.
+#[derive(Debug, Clone)]
+pub struct PendingEntry {
+    pub seq: u64,
+    changes: Vec<Change>,
+    /// One id per change, sent along with its operation so receivers can skip redeliveries.
+    op_ids: Vec<Id>,
+}
+
+impl PendingEntry {
+    fn new(seq: u64, changes: Vec<Change>, ids: &mut IdStrategy) -> Self {
+        let op_ids = changes.iter().map(|_| ids.next_operation_id()).collect();
+        PendingEntry {
+            seq,
+            changes,
+            op_ids,
+        }
+    }
+
+    pub fn operations(&self) -> Vec<Operation> {
+        self.changes.iter().map(Operation::from_change).collect()
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_entry = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_entry,
+            &JsValue::from_str("seq"),
+            &JsValue::from_f64(self.seq as f64),
+        )
+        .unwrap();
+
+        let ops: js_sys::Array = self
+            .operations()
+            .iter()
+            .zip(&self.op_ids)
+            .map(|(op, op_id)| {
+                let js_op = op.to_js_object();
+                js_sys::Reflect::set(
+                    &js_op,
+                    &JsValue::from_str("opId"),
+                    &JsValue::from_str(&op_id.to_string()),
+                )
+                .unwrap();
+                js_op
+            })
+            .collect();
+        js_sys::Reflect::set(&js_entry, &JsValue::from_str("ops"), &ops).unwrap();
+
+        js_entry
+    }
+}
+
+/// A pending operation that no longer applied after rebasing over server operations.
+#[derive(Clone)]
+pub struct DroppedOperation {
+    pub seq: u64,
+    pub operation: Operation,
+    pub reason: ConflictReason,
+}
+
+#[derive(Clone, Default)]
+pub struct RebaseReport {
+    pub conflicts: Vec<Conflict>,
+    pub dropped: Vec<DroppedOperation>,
+}
+
+impl RebaseReport {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_report = js_sys::Object::new();
+
+        let conflicts: js_sys::Array = self
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.to_js_object())
+            .collect();
+        js_sys::Reflect::set(&js_report, &JsValue::from_str("conflicts"), &conflicts).unwrap();
+
+        let dropped: js_sys::Array = self
+            .dropped
+            .iter()
+            .map(|dropped| {
+                let js_dropped = js_sys::Object::new();
+                js_sys::Reflect::set(
+                    &js_dropped,
+                    &JsValue::from_str("seq"),
+                    &JsValue::from_f64(dropped.seq as f64),
+                )
+                .unwrap();
+                js_sys::Reflect::set(
+                    &js_dropped,
+                    &JsValue::from_str("op"),
+                    &dropped.operation.to_js_object(),
+                )
+                .unwrap();
+                js_sys::Reflect::set(
+                    &js_dropped,
+                    &JsValue::from_str("reason"),
+                    &JsValue::from_str(dropped.reason.as_str()),
+                )
+                .unwrap();
+                js_dropped
+            })
+            .collect();
+        js_sys::Reflect::set(&js_report, &JsValue::from_str("dropped"), &dropped).unwrap();
+
+        js_report
+    }
+}
+
+/// Outbound queue of local changes awaiting server acknowledgement.
+#[derive(Debug, Clone, Default)]
+pub struct SyncQueue {
+    pending: Vec<PendingEntry>,
+}
+
+impl SyncQueue {
+    pub fn push(&mut self, entry: &JournalEntry, ids: &mut IdStrategy) {
+        self.pending
+            .push(PendingEntry::new(entry.seq, entry.changes.clone(), ids));
+    }
+
+    pub fn get_pending(&self) -> &[PendingEntry] {
+        &self.pending
+    }
+
+    /// Drops the entry recorded under `seq`, e.g. after the server rejected it.
+    pub fn discard(&mut self, seq: u64) {
+        self.pending.retain(|entry| entry.seq != seq);
+    }
+
+    /// Forgets every pending entry up to and including `up_to`.
+    pub fn mark_synced(&mut self, up_to: u64) {
+        self.pending.retain(|entry| entry.seq > up_to);
+    }
+
+    /// Applies server `operations` underneath the pending local changes: the pending changes are
+    /// reverted, the server operations applied, and the pending changes replayed on top. Pending
+    /// operations that no longer apply are dropped from the queue and reported; the others keep
+    /// their ids.
+    ///
+    /// The resulting changes are left on the song for the caller to commit.
+    pub fn rebase(
+        &mut self,
+        song: &mut Song,
+        ids: &mut IdStrategy,
+        operations: Vec<Operation>,
+    ) -> RebaseReport {
+        let reverts = self
+            .pending
+            .iter()
+            .rev()
+            .flat_map(|entry| entry.changes.iter().rev().map(Operation::inverse_of))
+            .collect();
+        apply_operations(song, reverts);
+
+        let mut report = RebaseReport {
+            conflicts: apply_operations(song, operations),
+            ..Default::default()
+        };
+
+        for entry in &mut self.pending {
+            let operations = entry.operations();
+            let start = song.get_changes().len();
+            let mut dropped = HashSet::new();
+
+            for conflict in apply_operations(song, operations.clone()) {
+                dropped.insert(conflict.index);
+                report.dropped.push(DroppedOperation {
+                    seq: entry.seq,
+                    operation: operations[conflict.index].clone(),
+                    reason: conflict.reason,
+                });
+            }
+
+            let op_ids: Vec<Id> = (0..operations.len())
+                .filter(|index| !dropped.contains(index))
+                .map(|index| entry.op_ids[index])
+                .collect();
+            *entry = PendingEntry::new(entry.seq, song.get_changes()[start..].to_vec(), ids);
+            if op_ids.len() == entry.changes.len() {
+                entry.op_ids = op_ids;
+            }
+        }
+
+        self.pending.retain(|entry| !entry.changes.is_empty());
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{
+            event::{Event, EventUpdater},
+            note::{NoteNumber, NoteUpdater},
+        },
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    fn note_updater(event: &Event) -> NoteUpdater {
+        match EventUpdater::from(event) {
+            EventUpdater::Note(updater) => updater,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_rebase_replays_pending_over_server_operations() {
+        let (mut song, track_id) = song_with_track();
+        let shared_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.commit(None);
+
+        let mut queue = SyncQueue::default();
+
+        // local, unsynced edits: add a note and move the shared one
+        let local_id = add_note(&mut song, track_id, 480, 480, 64, 100);
+        let shared = song.get_event(&shared_id).unwrap().clone();
+        song.update_event(EventUpdater::Note(NoteUpdater {
+            ticks: Some(Ticks::new(240)),
+            ..note_updater(&shared)
+        }))
+        .unwrap();
+        queue.push(song.commit(None).unwrap(), &mut IdStrategy::default());
+        let local_op_id = queue.get_pending()[0].op_ids[0];
+
+        // the server meanwhile changed the pitch of the shared note
+        let server_op = Operation::UpdateEvent {
+            updater: EventUpdater::Note(NoteUpdater {
+                note_number: Some(NoteNumber::new(62)),
+                ..note_updater(&shared)
+            }),
+            expected: Some(shared),
+        };
+        let report = queue.rebase(&mut song, &mut IdStrategy::default(), vec![server_op]);
+
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.dropped.len(), 1);
+        assert_eq!(report.dropped[0].reason, ConflictReason::ConcurrentEdit);
+
+        let shared = song.get_event(&shared_id).unwrap().as_note().unwrap();
+        assert_eq!(shared.note_number, NoteNumber::new(62));
+        assert_eq!(shared.ticks, Ticks::new(0));
+        assert!(song.get_event(&local_id).is_some());
+
+        assert_eq!(queue.get_pending().len(), 1);
+        assert_eq!(queue.get_pending()[0].operations().len(), 1);
+        assert_eq!(queue.get_pending()[0].op_ids, vec![local_op_id]);
+
+        queue.mark_synced(queue.get_pending()[0].seq);
+        assert!(queue.get_pending().is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+use super::operation::{apply_last_writer_wins, Conflict, OperationRecord};
+use crate::{change::change::Change, song::song::Song};
+
+/// Local changes held back from the journal until the transaction is committed, so observers
+/// and the sync queue see them as one batch or not at all.
+pub struct Transaction {
+    changes: Vec<Change>,
+    /// The song as the transaction found it, put back on rollback.
+    snapshot: Song,
+    /// The last journal seq when the transaction began; entries after it came from elsewhere.
+    seq: u64,
+}
+
+impl Transaction {
+    pub fn begin(song: &Song) -> Self {
+        Transaction {
+            changes: Vec::new(),
+            snapshot: song.snapshot(),
+            seq: song.get_journal().last().map_or(0, |entry| entry.seq),
+        }
+    }
+
+    /// Holds back the song's pending changes.
+    pub fn defer(&mut self, song: &mut Song) {
+        self.changes.extend(song.take_changes());
+    }
+
+    /// Hands the held back changes to the song for it to commit.
+    pub fn commit(mut self, song: &mut Song) {
+        self.defer(song);
+        song.restore_changes(self.changes);
+    }
+
+    /// Puts the song back as the transaction found it, dropping the held back changes along
+    /// with any still pending, then replays the changes recorded from elsewhere since, letting
+    /// them win. Those that no longer apply are reported as conflicts. None of it is recorded.
+    pub fn rollback(self, song: &mut Song) -> Vec<Conflict> {
+        let records = OperationRecord::from_entries(song.get_journal().entries_since(self.seq));
+        song.restore_snapshot(self.snapshot);
+        let conflicts = apply_last_writer_wins(song, records);
+        song.take_changes();
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_transaction() {
+        let (mut song, track_id) = song_with_track();
+        let moved = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.commit(None);
+
+        let mut transaction = Transaction::begin(&song);
+        let note = song.get_event(&moved).unwrap().clone();
+        song.replace_event(note.with_ticks(Ticks::new(240)))
+            .unwrap();
+        transaction.defer(&mut song);
+        let added = add_note(&mut song, track_id, 960, 480, 62, 100);
+        song.bpm = 90.0;
+        song.muted.insert(moved);
+
+        let conflicts = transaction.rollback(&mut song);
+        assert!(conflicts.is_empty());
+        assert!(song.get_changes().is_empty());
+        assert!(song.get_event(&added).is_none());
+        assert_eq!(song.get_event(&moved).unwrap().get_ticks(), Ticks::new(0));
+        assert_eq!(song.bpm, 120.0);
+        assert!(song.muted.is_empty());
+
+        let mut transaction = Transaction::begin(&song);
+        add_note(&mut song, track_id, 960, 480, 62, 100);
+        transaction.defer(&mut song);
+        let note = song.get_event(&moved).unwrap().clone();
+        song.replace_event(note.with_ticks(Ticks::new(120)))
+            .unwrap();
+        transaction.commit(&mut song);
+        let entry = song.commit(None).unwrap();
+        assert_eq!(entry.changes.len(), 2);
+        assert_eq!(song.get_journal().entries_since(0).len(), 2);
+    }
+
+    #[test]
+    fn test_rollback_keeps_changes_from_elsewhere() {
+        let (mut song, track_id) = song_with_track();
+        let shared = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.commit(None);
+
+        let mut transaction = Transaction::begin(&song);
+        let note = song.get_event(&shared).unwrap().clone();
+        song.replace_event(note.with_ticks(Ticks::new(240)))
+            .unwrap();
+        transaction.defer(&mut song);
+
+        // meanwhile a collaborator moves the same note and adds one of their own
+        let note = song.get_event(&shared).unwrap().clone();
+        song.replace_event(note.with_ticks(Ticks::new(960)))
+            .unwrap();
+        let remote = add_note(&mut song, track_id, 1920, 480, 64, 100);
+        song.commit(Some("remote".to_string()));
+
+        let conflicts = transaction.rollback(&mut song);
+        assert!(conflicts.is_empty());
+        assert!(song.get_changes().is_empty());
+        assert_eq!(
+            song.get_event(&shared).unwrap().get_ticks(),
+            Ticks::new(960)
+        );
+        assert!(song.get_event(&remote).is_some());
+        assert_eq!(song.get_journal().entries_since(0).len(), 2);
+    }
+}
@@ -0,0 +1,580 @@
+//! Saving songs to IndexedDB, with the `persistence` feature, which adds `Store.saveTo` and
+//! `Store.loadFrom`. Songs are stored in the binary format of `Store.toBinary`; songs saved in
+//! an older version of the format are migrated on load, here and in `Store.fromBinary`.
+
+use crate::{
+    event::{
+        audio_region::AudioRegion, chord_symbol::ChordSymbol, control_change::ControlChange,
+        event::Event, key_signature::KeySignature, lyric::Lyric, marker::Marker, note::Note,
+        raw_midi::RawMidi, time_signature::TimeSignature, timestamp::EventTimestamps,
+    },
+    shared::{error::StoreError, id::Id, meta::Meta, unit::time::Ticks},
+    song::{
+        end_of_song::EndOfSongPolicy,
+        groove::{GroovePool, TrackGroove},
+        group::EventGroups,
+        loop_region::LoopRegion,
+        performance::PerformanceProfiles,
+        settings::SongSettings,
+        song::SONG_FORMAT_VERSION,
+    },
+    track::{
+        clip::Clip,
+        expression::ExpressionMap,
+        track::{PitchRange, TrackDisplay, TrackMix, TrackSettings},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "persistence")]
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "persistence")]
+use wasm_bindgen_futures::JsFuture;
+#[cfg(feature = "persistence")]
+use web_sys::{IdbDatabase, IdbFactory, IdbRequest, IdbTransactionMode};
+
+#[cfg(feature = "persistence")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PERSISTENCE_INTERFACES: &'static str = r#"
+export interface Store {
+  /**
+   * Saves the song in the binary format of `toBinary` under `key` in the IndexedDB database
+   * `dbName`, creating the database on first use.
+   */
+  saveTo(dbName: string, key: string): Promise<void>;
+
+  /**
+   * Replaces the song with the one saved under `key`, migrating songs saved by older versions.
+   * Like `fromBinary`, this can be undone. Other store calls throw until the promise settles.
+   */
+  loadFrom(dbName: string, key: string): Promise<void>;
+}
+"#;
+
+/// Upgrades a song of one binary format version to the next, keyed by the version it reads.
+type Migration = (u32, fn(&[u8]) -> Result<Vec<u8>, String>);
+
+/// Add an entry here whenever `SONG_FORMAT_VERSION` changes, so songs saved before still load.
+const MIGRATIONS: &[Migration] = &[
+    (1, add_groups),
+    (2, add_track_clips),
+    (3, add_muted),
+    (4, add_performance_profiles),
+    (5, add_meta),
+    (6, add_record_filters),
+    (7, move_markers_to_song),
+];
+
+/// `bytes` as `version`, with an empty collection appended to the song.
+fn append_empty(bytes: &[u8], version: u32) -> Result<Vec<u8>, String> {
+    let (_, rest) = postcard::take_from_bytes::<u32>(bytes).map_err(|error| error.to_string())?;
+    let mut migrated = postcard::to_allocvec(&version).unwrap();
+    migrated.extend_from_slice(rest);
+    migrated.push(0);
+    Ok(migrated)
+}
+
+/// Version 2 appends the song's event groups, which older songs have none of.
+fn add_groups(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    append_empty(bytes, 2)
+}
+
+/// Version 4 appends the ids of muted events and groups, which older songs have none of.
+fn add_muted(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    append_empty(bytes, 4)
+}
+
+/// Version 5 appends the song's performance profiles and the tracks they are assigned to, both
+/// empty in older songs.
+fn add_performance_profiles(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut migrated = append_empty(bytes, 5)?;
+    migrated.push(0);
+    Ok(migrated)
+}
+
+/// The binary layout of a song in versions 2 and 3, with `T` as the layout of its tracks. Later
+/// versions start the same way.
+#[derive(Serialize, Deserialize)]
+struct SongLayout<T> {
+    version: u32,
+    title: String,
+    ppq: u32,
+    bpm: f64,
+    end_of_song_policy: EndOfSongPolicy,
+    grooves: GroovePool,
+    settings: SongSettings,
+    loop_region: Option<LoopRegion>,
+    tracks: Vec<T>,
+    channels: HashMap<Id, u8>,
+    timestamps: HashMap<Id, EventTimestamps>,
+    groups: EventGroups,
+}
+
+impl<T> SongLayout<T> {
+    fn map_tracks<U>(self, version: u32, f: impl FnMut(T) -> U) -> SongLayout<U> {
+        SongLayout {
+            version,
+            title: self.title,
+            ppq: self.ppq,
+            bpm: self.bpm,
+            end_of_song_policy: self.end_of_song_policy,
+            grooves: self.grooves,
+            settings: self.settings,
+            loop_region: self.loop_region,
+            tracks: self.tracks.into_iter().map(f).collect(),
+            channels: self.channels,
+            timestamps: self.timestamps,
+            groups: self.groups,
+        }
+    }
+}
+
+/// The binary layout of track settings before version 7.
+#[derive(Serialize, Deserialize)]
+struct TrackSettingsV1 {
+    transpose: i32,
+    velocity_offset: i32,
+    swing: f64,
+    delay_ticks: i32,
+    delay_ms: f64,
+    groove: Option<TrackGroove>,
+}
+
+/// The binary layout of a marker before version 8, when a track held it.
+#[derive(Serialize, Deserialize)]
+struct MarkerV1 {
+    id: Id,
+    ticks: Ticks,
+    name: String,
+    track_id: Id,
+}
+
+/// The binary layout of an event before version 8, in the variant order of `Event`.
+#[derive(Serialize, Deserialize)]
+enum EventV1 {
+    Note(Note),
+    ControlChange(ControlChange),
+    Lyric(Lyric),
+    AudioRegion(AudioRegion),
+    TimeSignature(TimeSignature),
+    Marker(MarkerV1),
+    KeySignature(KeySignature),
+    ChordSymbol(ChordSymbol),
+    RawMidi(RawMidi),
+}
+
+impl From<EventV1> for Event {
+    fn from(event: EventV1) -> Self {
+        match event {
+            EventV1::Note(note) => Event::Note(note),
+            EventV1::ControlChange(cc) => Event::ControlChange(cc),
+            EventV1::Lyric(lyric) => Event::Lyric(lyric),
+            EventV1::AudioRegion(region) => Event::AudioRegion(region),
+            EventV1::TimeSignature(meter) => Event::TimeSignature(meter),
+            EventV1::Marker(marker) => Event::Marker(Marker {
+                id: marker.id,
+                ticks: marker.ticks,
+                name: marker.name,
+            }),
+            EventV1::KeySignature(key) => Event::KeySignature(key),
+            EventV1::ChordSymbol(chord) => Event::ChordSymbol(chord),
+            EventV1::RawMidi(raw) => Event::RawMidi(raw),
+        }
+    }
+}
+
+/// The binary layout of a track in version 2, with `S` as the layout of its settings and `E`
+/// as the layout of its events.
+#[derive(Serialize, Deserialize)]
+struct TrackLayoutV2<S, E = EventV1> {
+    id: Id,
+    order: usize,
+    settings: S,
+    pitch_range: Option<PitchRange>,
+    pitch_bend_range: u8,
+    expression_map: ExpressionMap,
+    mix: TrackMix,
+    display: TrackDisplay,
+    output: Option<String>,
+    channel: Option<u8>,
+    program: Option<u8>,
+    events: Vec<E>,
+}
+
+impl<S, E> TrackLayoutV2<S, E> {
+    fn with_settings<T>(self, settings: T) -> TrackLayoutV2<T, E> {
+        TrackLayoutV2 {
+            id: self.id,
+            order: self.order,
+            settings,
+            pitch_range: self.pitch_range,
+            pitch_bend_range: self.pitch_bend_range,
+            expression_map: self.expression_map,
+            mix: self.mix,
+            display: self.display,
+            output: self.output,
+            channel: self.channel,
+            program: self.program,
+            events: self.events,
+        }
+    }
+
+    fn with_events<F>(self, events: Vec<F>) -> TrackLayoutV2<S, F> {
+        TrackLayoutV2 {
+            id: self.id,
+            order: self.order,
+            settings: self.settings,
+            pitch_range: self.pitch_range,
+            pitch_bend_range: self.pitch_bend_range,
+            expression_map: self.expression_map,
+            mix: self.mix,
+            display: self.display,
+            output: self.output,
+            channel: self.channel,
+            program: self.program,
+            events,
+        }
+    }
+}
+
+/// The binary layout of a track since version 3; nested structs add no framing in postcard.
+#[derive(Serialize, Deserialize)]
+struct TrackLayoutV3<S, E = EventV1> {
+    track: TrackLayoutV2<S, E>,
+    clips: Vec<Clip>,
+}
+
+/// Version 3 appends each track's clips, which older songs have none of.
+fn add_track_clips(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let song: SongLayout<TrackLayoutV2<TrackSettingsV1>> =
+        postcard::from_bytes(bytes).map_err(|error| error.to_string())?;
+    let song = song.map_tracks(3, |track| TrackLayoutV3 {
+        track,
+        clips: Vec::new(),
+    });
+    Ok(postcard::to_allocvec(&song).unwrap())
+}
+
+/// The binary layout of a song since version 5, up to its event meta.
+#[derive(Serialize, Deserialize)]
+struct SongLayoutV5<T> {
+    song: SongLayout<T>,
+    muted: HashSet<Id>,
+    profiles: PerformanceProfiles,
+}
+
+/// The binary layout of a track since version 6.
+#[derive(Serialize, Deserialize)]
+struct TrackLayoutV6<S, E = EventV1> {
+    track: TrackLayoutV3<S, E>,
+    meta: Meta,
+}
+
+/// Version 6 appends each track's meta and then the song's event meta, which older songs have
+/// none of.
+fn add_meta(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let song: SongLayoutV5<TrackLayoutV3<TrackSettingsV1>> =
+        postcard::from_bytes(bytes).map_err(|error| error.to_string())?;
+    let song = SongLayoutV5 {
+        song: song.song.map_tracks(6, |track| TrackLayoutV6 {
+            track,
+            meta: Meta::default(),
+        }),
+        muted: song.muted,
+        profiles: song.profiles,
+    };
+    let mut migrated = postcard::to_allocvec(&song).unwrap();
+    migrated.push(0);
+    Ok(migrated)
+}
+
+/// Version 7 adds a record filter to each track's settings, which older tracks have none of.
+fn add_record_filters(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let (song, event_meta): (SongLayoutV5<TrackLayoutV6<TrackSettingsV1>>, _) =
+        postcard::take_from_bytes(bytes).map_err(|error| error.to_string())?;
+    let song = SongLayoutV5 {
+        song: song.song.map_tracks(7, |track| {
+            let TrackLayoutV6 {
+                track: TrackLayoutV3 { track, clips },
+                meta,
+            } = track;
+            let settings = TrackSettings {
+                transpose: track.settings.transpose,
+                velocity_offset: track.settings.velocity_offset,
+                swing: track.settings.swing,
+                delay_ticks: track.settings.delay_ticks,
+                delay_ms: track.settings.delay_ms,
+                groove: track.settings.groove,
+                record_filter: None,
+            };
+            TrackLayoutV6 {
+                track: TrackLayoutV3 {
+                    track: track.with_settings(settings),
+                    clips,
+                },
+                meta,
+            }
+        }),
+        muted: song.muted,
+        profiles: song.profiles,
+    };
+    let mut migrated = postcard::to_allocvec(&song).unwrap();
+    migrated.extend_from_slice(event_meta);
+    Ok(migrated)
+}
+
+/// Version 8 moves markers from the tracks' events to a list of the song's own, appended last.
+fn move_markers_to_song(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let (song, event_meta): (SongLayoutV5<TrackLayoutV6<TrackSettings>>, _) =
+        postcard::take_from_bytes(bytes).map_err(|error| error.to_string())?;
+    let mut markers = Vec::new();
+    let song = SongLayoutV5 {
+        song: song.song.map_tracks(8, |track| {
+            let TrackLayoutV6 {
+                track: TrackLayoutV3 { mut track, clips },
+                meta,
+            } = track;
+            let mut events = Vec::new();
+            for event in std::mem::take(&mut track.events)
+                .into_iter()
+                .map(Event::from)
+            {
+                match event {
+                    Event::Marker(marker) => markers.push(marker),
+                    event => events.push(event),
+                }
+            }
+            TrackLayoutV6 {
+                track: TrackLayoutV3 {
+                    track: track.with_events(events),
+                    clips,
+                },
+                meta,
+            }
+        }),
+        muted: song.muted,
+        profiles: song.profiles,
+    };
+    let mut migrated = postcard::to_allocvec(&song).unwrap();
+    migrated.extend_from_slice(event_meta);
+    migrated.extend(postcard::to_allocvec(&markers).unwrap());
+    Ok(migrated)
+}
+
+#[cfg(feature = "persistence")]
+const OBJECT_STORE: &str = "songs";
+
+/// The binary format version a saved song starts with.
+fn format_version(bytes: &[u8]) -> Result<u32, StoreError> {
+    postcard::take_from_bytes::<u32>(bytes)
+        .map(|(version, _)| version)
+        .map_err(|error| StoreError::invalid(format!("Song binary is not valid: {}", error)))
+}
+
+/// Runs `migrations` on `bytes` until they are in the current binary format.
+fn migrate_with(mut bytes: Vec<u8>, migrations: &[Migration]) -> Result<Vec<u8>, StoreError> {
+    loop {
+        let version = format_version(&bytes)?;
+        if version == SONG_FORMAT_VERSION {
+            return Ok(bytes);
+        }
+        let (_, migrate) = migrations
+            .iter()
+            .find(|(from, _)| *from == version)
+            .ok_or_else(|| {
+                StoreError::invalid(format!("Song format version {} is not supported", version))
+            })?;
+        bytes = migrate(&bytes).map_err(StoreError::invalid)?;
+    }
+}
+
+pub fn migrate(bytes: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+    migrate_with(bytes, MIGRATIONS)
+}
+
+/// Waits for an IndexedDB request, resolving to its result or rejecting with its error event.
+#[cfg(feature = "persistence")]
+async fn settle(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let on_success = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let result = request.result().unwrap_or(JsValue::UNDEFINED);
+                resolve.call1(&JsValue::NULL, &result).unwrap();
+            })
+        };
+        let on_error = Closure::once_into_js(move |event: JsValue| {
+            reject.call1(&JsValue::NULL, &event).unwrap();
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+#[cfg(feature = "persistence")]
+async fn open(db_name: &str) -> Result<IdbDatabase, JsValue> {
+    let factory: IdbFactory =
+        js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("indexedDB"))?.dyn_into()?;
+    let request = factory.open_with_u32(db_name, 1)?;
+    let on_upgrade_needed = {
+        let request = request.clone();
+        Closure::once_into_js(move || {
+            let db: IdbDatabase = request.result().unwrap().unchecked_into();
+            db.create_object_store(OBJECT_STORE).unwrap();
+        })
+    };
+    request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+    Ok(settle(&request).await?.unchecked_into())
+}
+
+/// Stores `bytes` under `key` in the database `db_name`.
+#[cfg(feature = "persistence")]
+pub async fn save(db_name: &str, key: &str, bytes: &[u8]) -> Result<(), JsValue> {
+    let db = open(db_name).await?;
+    let transaction =
+        db.transaction_with_str_and_mode(OBJECT_STORE, IdbTransactionMode::Readwrite)?;
+    let value = js_sys::Uint8Array::from(bytes);
+    let request = transaction
+        .object_store(OBJECT_STORE)?
+        .put_with_key(&value, &JsValue::from_str(key))?;
+    settle(&request).await?;
+    db.close();
+    Ok(())
+}
+
+/// The song bytes saved under `key` in the database `db_name`, in the format they were saved in.
+#[cfg(feature = "persistence")]
+pub async fn load(db_name: &str, key: &str) -> Result<Vec<u8>, JsValue> {
+    let db = open(db_name).await?;
+    let transaction = db.transaction_with_str(OBJECT_STORE)?;
+    let request = transaction
+        .object_store(OBJECT_STORE)?
+        .get(&JsValue::from_str(key))?;
+    let value = settle(&request).await?;
+    db.close();
+    if value.is_undefined() {
+        return Err(StoreError::invalid(format!("No song is saved under {:?}", key)).into());
+    }
+    Ok(value.unchecked_into::<js_sys::Uint8Array>().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        song::song::Song,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_migrate() {
+        let bytes = postcard::to_allocvec(&Song::new("test".to_string(), 480)).unwrap();
+        assert_eq!(migrate(bytes.clone()), Ok(bytes.clone()));
+
+        // version 1 had no groups, muted events, performance profiles, event meta or song markers
+        let mut old = bytes[..bytes.len() - 6].to_vec();
+        old[0] = 1;
+        assert!(postcard::from_bytes::<Song>(&old).is_err());
+        assert_eq!(migrate(old.clone()), Ok(bytes.clone()));
+
+        old[0] = 0;
+        assert_eq!(
+            migrate(old.clone()).unwrap_err().to_string(),
+            "Song format version 0 is not supported"
+        );
+        let bump: Migration = (0, |bytes| {
+            let mut bytes = bytes.to_vec();
+            bytes[0] = 1;
+            Ok(bytes)
+        });
+        let migrations = [&[bump], MIGRATIONS].concat();
+        let migrated = migrate_with(old, &migrations).unwrap();
+        assert!(postcard::from_bytes::<Song>(&migrated).is_ok());
+    }
+
+    #[test]
+    fn test_migrate_track_clips() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 480, 240, 60, 100);
+        song.update_track(&track_id, |track| track.settings.transpose = 3);
+        let bytes = postcard::to_allocvec(&song).unwrap();
+
+        let current: SongLayoutV5<TrackLayoutV6<TrackSettings>> =
+            postcard::from_bytes(&bytes[..bytes.len() - 2]).unwrap();
+        let old = current.song.map_tracks(2, |track| {
+            let track = track.track.track;
+            let settings = TrackSettingsV1 {
+                transpose: track.settings.transpose,
+                velocity_offset: track.settings.velocity_offset,
+                swing: track.settings.swing,
+                delay_ticks: track.settings.delay_ticks,
+                delay_ms: track.settings.delay_ms,
+                groove: track.settings.groove,
+            };
+            track.with_settings(settings)
+        });
+        let old = postcard::to_allocvec(&old).unwrap();
+        assert!(postcard::from_bytes::<Song>(&old).is_err());
+
+        let migrated: Song = postcard::from_bytes(&migrate(old).unwrap()).unwrap();
+        assert!(migrated.get_event(&note_id).is_some());
+        let track = migrated.get_track(&track_id).unwrap();
+        assert!(track.clips.is_empty() && track.meta.is_empty());
+        assert_eq!(track.settings.transpose, 3);
+        assert_eq!(track.settings.record_filter, None);
+    }
+
+    #[test]
+    fn test_migrate_markers_to_song() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let bytes = postcard::to_allocvec(&song).unwrap();
+
+        // version 7 kept markers with a track's events and had none of the song's own
+        let marker_id = Id::from_u128(1);
+        let current: SongLayoutV5<TrackLayoutV6<TrackSettings, Event>> =
+            postcard::from_bytes(&bytes).unwrap();
+        let old = SongLayoutV5 {
+            song: current.song.map_tracks(7, |track| {
+                let TrackLayoutV6 {
+                    track: TrackLayoutV3 { mut track, clips },
+                    meta,
+                } = track;
+                let mut events: Vec<EventV1> = std::mem::take(&mut track.events)
+                    .into_iter()
+                    .map(|event| match event {
+                        Event::Note(note) => EventV1::Note(note),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                events.push(EventV1::Marker(MarkerV1 {
+                    id: marker_id,
+                    ticks: Ticks::new(960),
+                    name: "Chorus".to_string(),
+                    track_id: track.id,
+                }));
+                TrackLayoutV6 {
+                    track: TrackLayoutV3 {
+                        track: track.with_events(events),
+                        clips,
+                    },
+                    meta,
+                }
+            }),
+            muted: current.muted,
+            profiles: current.profiles,
+        };
+        let mut old = postcard::to_allocvec(&old).unwrap();
+        old.push(0);
+        assert!(postcard::from_bytes::<Song>(&old).is_err());
+
+        let migrated: Song = postcard::from_bytes(&migrate(old).unwrap()).unwrap();
+        assert_eq!(migrated.get_track_events(&track_id).len(), 1);
+        assert!(migrated.get_event(&note_id).is_some());
+        let markers = migrated.get_marker_events();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].get_id(), marker_id);
+        assert_eq!(markers[0].get_track_id(), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    event::{control_change::ControllerNumber, event::Event},
+    shared::unit::time::Ticks,
+    song::song::Song,
+    track::track::Track,
+    transform::ramp::ValuePoint,
+};
+
+/// The values one controller of a track takes over time, read as an automation lane: a
+/// straight line between consecutive control changes, holding the first value before the
+/// first one and the last value after the last.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControllerLane {
+    /// One point per tick with a control change, the last one on the tick when there are
+    /// several, in ticks order.
+    points: Vec<ValuePoint>,
+}
+
+impl ControllerLane {
+    pub fn new(song: &Song, track: &Track, controller: ControllerNumber) -> Self {
+        let mut points: Vec<ValuePoint> = Vec::new();
+        for event in song.track_events(track).iter() {
+            let Event::ControlChange(cc) = event else {
+                continue;
+            };
+            if cc.controller != controller {
+                continue;
+            }
+            let point = ValuePoint {
+                ticks: cc.ticks,
+                value: cc.value.as_u8() as f64,
+            };
+            match points.last_mut() {
+                Some(last) if last.ticks == point.ticks => *last = point,
+                _ => points.push(point),
+            }
+        }
+        ControllerLane { points }
+    }
+
+    /// The value at `ticks`, or `None` when the track has no control changes for the
+    /// controller.
+    pub fn value_at(&self, ticks: Ticks) -> Option<f64> {
+        let next = self.points.partition_point(|point| point.ticks <= ticks);
+        let Some(before) = next.checked_sub(1).map(|index| self.points[index]) else {
+            return self.points.first().map(|first| first.value);
+        };
+        let Some(after) = self.points.get(next) else {
+            return Some(before.value);
+        };
+
+        let position = (ticks - before.ticks).as_f64() / (after.ticks - before.ticks).as_f64();
+        Some(before.value + (after.value - before.value) * position)
+    }
+
+    /// The value every `resolution` ticks from `start_ticks` up to `end_ticks`, which is
+    /// included when a step lands on it. Empty without control changes for the controller.
+    pub fn sample(
+        &self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+        resolution: Ticks,
+    ) -> Vec<ValuePoint> {
+        if self.points.is_empty() || resolution == Ticks::new(0) {
+            return Vec::new();
+        }
+        (start_ticks.as_u64()..=end_ticks.as_u64())
+            .step_by(resolution.as_u64() as usize)
+            .map(Ticks::new)
+            .filter_map(|ticks| {
+                let value = self.value_at(ticks)?;
+                Some(ValuePoint { ticks, value })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, song_with_track};
+
+    #[test]
+    fn test_controller_lane() {
+        let (mut song, track_id) = song_with_track();
+        let lane = |song: &Song| {
+            ControllerLane::new(
+                song,
+                song.get_track(&track_id).unwrap(),
+                ControllerNumber::new(11),
+            )
+        };
+        assert_eq!(lane(&song).value_at(Ticks::new(0)), None);
+
+        add_control_change(&mut song, track_id, 480, 11, 0);
+        add_control_change(&mut song, track_id, 960, 11, 100);
+        add_control_change(&mut song, track_id, 1440, 11, 50);
+        add_control_change(&mut song, track_id, 720, 7, 127);
+
+        let lane = lane(&song);
+        assert_eq!(lane.value_at(Ticks::new(0)), Some(0.0));
+        assert_eq!(lane.value_at(Ticks::new(720)), Some(50.0));
+        assert_eq!(lane.value_at(Ticks::new(960)), Some(100.0));
+        assert_eq!(lane.value_at(Ticks::new(1200)), Some(75.0));
+        assert_eq!(lane.value_at(Ticks::new(1920)), Some(50.0));
+
+        let values: Vec<f64> = lane
+            .sample(Ticks::new(480), Ticks::new(1440), Ticks::new(240))
+            .iter()
+            .map(|point| point.value)
+            .collect();
+        assert_eq!(values, vec![0.0, 50.0, 100.0, 75.0, 50.0]);
+    }
+}
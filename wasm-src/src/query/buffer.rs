@@ -0,0 +1,166 @@
+use crate::{event::event::Event, shared::unit::time::Ticks, song::song::Song};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Kinds of the records `fillPlaybackBuffer` writes. Each record is four words: ticks, duration,
+/// track index, then `kind << 16 | data1 << 8 | data2`, where the data bytes are the note number
+/// and velocity of notes and the controller and value of control changes. Ticks and durations
+/// past the largest word are written as that word.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackRecordKind {
+    ControlChange = 1,
+    Note = 2,
+}
+
+/// Words per record in the buffers handed over from JS.
+pub const RECORD_WORDS: usize = 4;
+
+/// A note or control change as the audio scheduler needs it, in a fixed size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlaybackRecord {
+    pub ticks: u32,
+    /// 0 for control changes.
+    pub duration: u32,
+    pub track_index: u32,
+    pub kind: u8,
+    pub data1: u8,
+    pub data2: u8,
+}
+
+/// `ticks` as a buffer word.
+fn word(ticks: Ticks) -> u32 {
+    ticks.as_u64().min(u32::MAX as u64) as u32
+}
+
+impl PlaybackRecord {
+    pub fn to_words(&self) -> [u32; RECORD_WORDS] {
+        [
+            self.ticks,
+            self.duration,
+            self.track_index,
+            (self.kind as u32) << 16 | (self.data1 as u32) << 8 | self.data2 as u32,
+        ]
+    }
+
+    pub fn from_words(words: &[u32; RECORD_WORDS]) -> Self {
+        PlaybackRecord {
+            ticks: words[0],
+            duration: words[1],
+            track_index: words[2],
+            kind: (words[3] >> 16) as u8,
+            data1: (words[3] >> 8) as u8,
+            data2: words[3] as u8,
+        }
+    }
+}
+
+/// Writes the audible notes and control changes starting in `[start_ticks,
+/// end_ticks)` into `buf` as records of words, with the tracks' transpose and velocity offset
+/// applied, and returns how many there are. Only the first `buf.len()` are written, so a larger
+/// result means the buffer was too small. The events are read straight from the song's index
+/// and sorted in place, so nothing is allocated, which keeps it fit for a real-time thread.
+///
+/// Records are ordered by ticks, control changes first, then by track and data. Swing, grooves,
+/// delays and performance profiles are left out; `playback_events` applies them.
+pub fn fill_playback_buffer(
+    song: &Song,
+    buf: &mut [[u32; RECORD_WORDS]],
+    start_ticks: Ticks,
+    end_ticks: Ticks,
+) -> usize {
+    if start_ticks >= end_ticks {
+        return 0;
+    }
+
+    let mut count = 0;
+    for event in song.iter_events_in_ticks_range(start_ticks, end_ticks) {
+        let Some(track) = event.get_track_id().and_then(|id| song.get_track(&id)) else {
+            continue;
+        };
+        if !song.is_event_audible(event) {
+            continue;
+        }
+        let settings = &track.settings;
+
+        let record = match event {
+            Event::Note(note) => PlaybackRecord {
+                ticks: word(note.ticks),
+                duration: word(note.duration),
+                track_index: track.order() as u32,
+                kind: PlaybackRecordKind::Note as u8,
+                data1: (note.note_number.as_u8() as i32 + settings.transpose).clamp(0, 127) as u8,
+                data2: (note.velocity.as_u8() as i32 + settings.velocity_offset).clamp(1, 127)
+                    as u8,
+            },
+            Event::ControlChange(cc) => PlaybackRecord {
+                ticks: word(cc.ticks),
+                duration: 0,
+                track_index: track.order() as u32,
+                kind: PlaybackRecordKind::ControlChange as u8,
+                data1: cc.controller.as_u8(),
+                data2: cc.value.as_u8(),
+            },
+            _ => continue,
+        };
+        if let Some(slot) = buf.get_mut(count) {
+            *slot = record.to_words();
+        }
+        count += 1;
+    }
+
+    let written = count.min(buf.len());
+    // ticks, kind, track index, then the data bytes
+    buf[..written].sort_unstable_by_key(|words| (words[0], words[3] >> 16, words[2], words[3]));
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::id::Id,
+        track::track::Track,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_fill_playback_buffer() {
+        let (mut song, first_track_id) = song_with_track();
+        let muted_track_id = Id::new();
+        song.add_track(Track::new(muted_track_id));
+        song.update_track(&muted_track_id, |track| track.mix.mute = true);
+        song.update_track(&first_track_id, |track| track.settings.transpose = 12);
+        add_note(&mut song, first_track_id, 480, 240, 60, 100);
+        add_control_change(&mut song, first_track_id, 480, 7, 90);
+        add_note(&mut song, first_track_id, 0, 480, 64, 80);
+        add_note(&mut song, muted_track_id, 0, 480, 48, 80);
+        add_note(&mut song, first_track_id, 960, 480, 67, 100);
+
+        let mut buf = [[0; RECORD_WORDS]; 4];
+        let count = fill_playback_buffer(&song, &mut buf, Ticks::new(0), Ticks::new(960));
+        assert_eq!(count, 3);
+        let records: Vec<(u32, u8, u8, u8)> = buf[..count]
+            .iter()
+            .map(PlaybackRecord::from_words)
+            .map(|record| (record.ticks, record.kind, record.data1, record.data2))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (0, PlaybackRecordKind::Note as u8, 76, 80),
+                (480, PlaybackRecordKind::ControlChange as u8, 7, 90),
+                (480, PlaybackRecordKind::Note as u8, 72, 100),
+            ]
+        );
+
+        let mut small = [[0; RECORD_WORDS]; 1];
+        assert_eq!(
+            fill_playback_buffer(&song, &mut small, Ticks::new(0), Ticks::new(960)),
+            3
+        );
+        assert_eq!(small[0][0], 0);
+
+        assert_eq!(buf[1], [480, 0, 0, 1 << 16 | 7 << 8 | 90]);
+    }
+}
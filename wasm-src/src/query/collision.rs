@@ -0,0 +1,66 @@
+use crate::{
+    event::{event::Event, note::NoteNumber},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::Song,
+    track::track::Track,
+};
+use std::collections::HashSet;
+
+/// Notes of `track` with `note_number` that overlap `[ticks, ticks + duration)`, ordered by
+/// ticks. Notes that merely touch the span do not collide; a zero duration counts as one tick.
+pub fn find_collisions(
+    song: &Song,
+    track: &Track,
+    ticks: Ticks,
+    duration: Ticks,
+    note_number: NoteNumber,
+    ignore_ids: &HashSet<Id>,
+) -> Vec<Id> {
+    let end_ticks = ticks + Ticks::new(duration.as_u64().max(1));
+
+    song.track_events(track)
+        .in_ticks_range(ticks, end_ticks, true)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Note(note) => Some(note),
+            _ => None,
+        })
+        .filter(|note| {
+            note.note_number == note_number
+                && note.ticks < end_ticks
+                && note.ticks + note.duration > ticks
+                && !ignore_ids.contains(&note.id)
+        })
+        .map(|note| note.id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_find_collisions() {
+        let (mut song, track_id) = song_with_track();
+        let long = add_note(&mut song, track_id, 0, 960, 60, 100);
+        let short = add_note(&mut song, track_id, 600, 120, 60, 100);
+        add_note(&mut song, track_id, 480, 480, 62, 100);
+        add_note(&mut song, track_id, 960, 480, 60, 100);
+
+        let track = song.get_track(&track_id).unwrap();
+        let collisions = |ignore_ids: &HashSet<Id>| {
+            find_collisions(
+                &song,
+                track,
+                Ticks::new(480),
+                Ticks::new(480),
+                NoteNumber::new(60),
+                ignore_ids,
+            )
+        };
+
+        assert_eq!(collisions(&HashSet::new()), vec![long, short]);
+        assert_eq!(collisions(&HashSet::from([long])), vec![short]);
+    }
+}
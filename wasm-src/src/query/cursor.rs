@@ -0,0 +1,63 @@
+use crate::{event::event::Event, shared::unit::time::Ticks, song::song::Song};
+
+/// A scheduler's read position, so consecutive advances return each event once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackCursor {
+    position: Ticks,
+    started: bool,
+}
+
+impl PlaybackCursor {
+    pub fn new(start_ticks: Ticks) -> Self {
+        PlaybackCursor {
+            position: start_ticks,
+            started: false,
+        }
+    }
+
+    /// The events starting from the cursor up to `ticks`, exclusive, in ticks order, moving the
+    /// cursor there. The first advance also returns the events still sounding at the start.
+    /// Ticks at or before the cursor return nothing and leave it in place.
+    pub fn advance_to<'a>(&mut self, song: &'a Song, ticks: Ticks) -> Vec<&'a Event> {
+        if ticks <= self.position {
+            return Vec::new();
+        }
+
+        let events = song.get_events_in_ticks_range(self.position, ticks, !self.started, None);
+        self.position = ticks;
+        self.started = true;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::id::Id,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_cursor_returns_each_event_once() {
+        let (mut song, track_id) = song_with_track();
+        let held = add_note(&mut song, track_id, 0, 960, 60, 100);
+        let first = add_note(&mut song, track_id, 480, 240, 62, 100);
+        let second = add_note(&mut song, track_id, 720, 480, 64, 100);
+        let third = add_note(&mut song, track_id, 960, 240, 65, 100);
+
+        let mut cursor = PlaybackCursor::new(Ticks::new(240));
+        let mut advance = |ticks| -> Vec<Id> {
+            cursor
+                .advance_to(&song, Ticks::new(ticks))
+                .iter()
+                .map(|event| event.get_id())
+                .collect()
+        };
+        assert!(advance(240).is_empty());
+        assert_eq!(advance(600), vec![held, first]);
+        assert_eq!(advance(960), vec![second]);
+        assert!(advance(900).is_empty());
+        assert_eq!(advance(1200), vec![third]);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{
+    event::event::{Event, EventKind},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::{GetEventsFilter, Song, TicksRange},
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_EVENT_FILTER_INTERFACES: &'static str = r#"
+/** Inclusive bounds; a missing bound is open. */
+export interface ValueRange {
+  min?: number;
+  max?: number;
+}
+
+/** Criteria an event must all meet. Omitted criteria match every event. */
+export interface EventFilter {
+  kinds?: EventKind[];
+  trackIds?: string[];
+  range?: TicksRange;
+  /** Matches notes only. */
+  noteNumber?: ValueRange;
+  /** Matches notes only. */
+  velocity?: ValueRange;
+  /** Matches notes and audio regions only. */
+  duration?: ValueRange;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ValueRange {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl ValueRange {
+    fn contains(&self, value: u64) -> bool {
+        self.min.is_none_or(|min| min <= value) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// The kinds an `EventFilter` matches, parsed as the filter is read so an unknown kind fails
+/// there rather than in the query.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct FilterKinds(Vec<EventKind>);
+
+impl FilterKinds {
+    pub fn contains(&self, kind: EventKind) -> bool {
+        self.0.contains(&kind)
+    }
+}
+
+impl TryFrom<Vec<String>> for FilterKinds {
+    type Error = String;
+
+    fn try_from(kinds: Vec<String>) -> Result<Self, Self::Error> {
+        kinds
+            .iter()
+            .map(|kind| {
+                kind.parse::<EventKind>()
+                    .map_err(|_| format!("Unknown event kind: {kind}"))
+            })
+            .collect::<Result<_, _>>()
+            .map(FilterKinds)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventFilter {
+    pub kinds: Option<FilterKinds>,
+    pub track_ids: Option<Vec<Id>>,
+    pub range: Option<TicksRange>,
+    pub note_number: Option<ValueRange>,
+    pub velocity: Option<ValueRange>,
+    pub duration: Option<ValueRange>,
+}
+
+impl EventFilter {
+    /// Whether the event meets every criterion, the tracks and range included, for callers
+    /// that do not go through the indexes like `query_events`.
+    pub fn accepts(&self, event: &Event) -> bool {
+        if let Some(track_ids) = &self.track_ids {
+            if !event
+                .get_track_id()
+                .is_some_and(|track_id| track_ids.contains(&track_id))
+            {
+                return false;
+            }
+        }
+        if let Some(range) = self.range {
+            let start = event.get_ticks();
+            let end = start + event.get_duration().unwrap_or(Ticks::new(0));
+            let in_range = (range.start_ticks..range.end_ticks).contains(&start)
+                || (range.within_duration && start < range.start_ticks && range.start_ticks < end);
+            if !in_range {
+                return false;
+            }
+        }
+        self.matches(event)
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        if self
+            .kinds
+            .as_ref()
+            .is_some_and(|kinds| !kinds.contains(event.get_kind()))
+        {
+            return false;
+        }
+
+        if let Some(duration) = self.duration {
+            match event.get_duration() {
+                Some(ticks) if duration.contains(ticks.as_u64()) => {}
+                _ => return false,
+            }
+        }
+
+        if self.note_number.is_none() && self.velocity.is_none() {
+            return true;
+        }
+        let Event::Note(note) = event else {
+            return false;
+        };
+        self.note_number
+            .is_none_or(|range| range.contains(note.note_number.as_u8() as u64))
+            && self
+                .velocity
+                .is_none_or(|range| range.contains(note.velocity.as_u8() as u64))
+    }
+}
+
+/// Events meeting every criterion of `filter`, in ticks order. The tracks and range narrow the
+/// candidates through the song's indexes before the remaining criteria are checked.
+pub fn query_events<'a>(song: &'a Song, filter: &EventFilter) -> Vec<&'a Event> {
+    let tracks = filter.track_ids.clone().map(|track_ids| GetEventsFilter {
+        track_ids: Some(track_ids),
+    });
+
+    let candidates = match filter.range {
+        Some(range) => song.get_events_in_ticks_range(
+            range.start_ticks,
+            range.end_ticks,
+            range.within_duration,
+            tracks,
+        ),
+        None => song.get_events(tracks),
+    };
+
+    candidates
+        .into_iter()
+        .filter(|event| filter.matches(event))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_query_events() {
+        let (mut song, track_id) = song_with_track();
+        let low = add_note(&mut song, track_id, 0, 480, 35, 100);
+        add_note(&mut song, track_id, 480, 480, 60, 100);
+        let quiet_low = add_note(&mut song, track_id, 960, 240, 33, 40);
+        add_note(&mut song, track_id, 3840, 480, 30, 100);
+        add_control_change(&mut song, track_id, 0, 1, 64);
+
+        let ids = |filter: &EventFilter| -> Vec<Id> {
+            query_events(&song, filter)
+                .iter()
+                .map(|event| event.get_id())
+                .collect()
+        };
+
+        let below_c2 = EventFilter {
+            track_ids: Some(vec![track_id]),
+            range: Some(TicksRange {
+                start_ticks: Ticks::new(0),
+                end_ticks: Ticks::new(1920),
+                within_duration: false,
+            }),
+            note_number: Some(ValueRange {
+                min: None,
+                max: Some(35),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(ids(&below_c2), vec![low, quiet_low]);
+
+        let quiet = EventFilter {
+            velocity: Some(ValueRange {
+                min: None,
+                max: Some(64),
+            }),
+            duration: Some(ValueRange {
+                min: Some(240),
+                max: Some(240),
+            }),
+            ..below_c2.clone()
+        };
+        assert_eq!(ids(&quiet), vec![quiet_low]);
+
+        let control_changes = EventFilter {
+            kinds: Some(FilterKinds(vec![EventKind::ControlChange])),
+            ..Default::default()
+        };
+        assert_eq!(query_events(&song, &control_changes).len(), 1);
+
+        let filter: Result<EventFilter, _> =
+            serde_json::from_str(r#"{"kinds":["ControlChange","Chord"]}"#);
+        assert!(filter.is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    event::event::Event,
+    shared::{id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FLAT_NOTES_INTERFACE: &'static str = r#"
+/** Notes as parallel arrays: the note at index `i` is described by element `i` of each. */
+export interface FlatNotes {
+  ticks: Float64Array;
+  duration: Float64Array;
+  noteNumber: Uint8Array;
+  velocity: Uint8Array;
+  /** Position of the note's track in `Song.tracks`. */
+  trackIndex: Uint32Array;
+}
+"#;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FlatNotes {
+    pub ticks: Vec<f64>,
+    pub duration: Vec<f64>,
+    pub note_number: Vec<u8>,
+    pub velocity: Vec<u8>,
+    pub track_index: Vec<u32>,
+}
+
+impl FlatNotes {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_notes = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_notes, &JsValue::from_str(key), value).unwrap();
+        };
+
+        set("ticks", &js_sys::Float64Array::from(self.ticks.as_slice()));
+        set(
+            "duration",
+            &js_sys::Float64Array::from(self.duration.as_slice()),
+        );
+        set(
+            "noteNumber",
+            &js_sys::Uint8Array::from(self.note_number.as_slice()),
+        );
+        set(
+            "velocity",
+            &js_sys::Uint8Array::from(self.velocity.as_slice()),
+        );
+        set(
+            "trackIndex",
+            &js_sys::Uint32Array::from(self.track_index.as_slice()),
+        );
+
+        js_notes
+    }
+}
+
+/// The notes starting in `[start_ticks, end_ticks)`, in ticks order.
+pub fn flat_notes_in_ticks_range(song: &Song, start_ticks: Ticks, end_ticks: Ticks) -> FlatNotes {
+    let track_indexes: HashMap<Id, u32> = song
+        .get_tracks()
+        .iter()
+        .enumerate()
+        .map(|(index, track)| (track.id, index as u32))
+        .collect();
+    let mut flat = FlatNotes::default();
+
+    for event in song.get_events_in_ticks_range(start_ticks, end_ticks, false, None) {
+        let Event::Note(note) = event else {
+            continue;
+        };
+        flat.ticks.push(note.ticks.as_f64());
+        flat.duration.push(note.duration.as_f64());
+        flat.note_number.push(note.note_number.as_u8());
+        flat.velocity.push(note.velocity.as_u8());
+        flat.track_index.push(track_indexes[&note.track_id]);
+    }
+
+    flat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        track::track::Track,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_flat_notes_in_ticks_range() {
+        let (mut song, first_track_id) = song_with_track();
+        let second_track_id = Id::new();
+        song.add_track(Track::new(second_track_id));
+        add_note(&mut song, second_track_id, 480, 240, 64, 90);
+        add_note(&mut song, first_track_id, 0, 480, 60, 100);
+        add_note(&mut song, first_track_id, 960, 480, 67, 100);
+        add_control_change(&mut song, first_track_id, 0, 1, 64);
+
+        let flat = flat_notes_in_ticks_range(&song, Ticks::new(0), Ticks::new(960));
+        assert_eq!(
+            flat,
+            FlatNotes {
+                ticks: vec![0.0, 480.0],
+                duration: vec![480.0, 240.0],
+                note_number: vec![60, 64],
+                velocity: vec![100, 90],
+                track_index: vec![0, 1],
+            }
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    event::{event::Event, note::Note},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::{Song, TicksRange},
+};
+use std::collections::BTreeSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LINE_INTERFACES: &'static str = r#"
+/** A stretch of an extracted line, during which one note is the highest or lowest sounding. */
+export interface LineNote {
+  eventId: string;
+  ticks: number;
+  duration: number;
+  noteNumber: number;
+  velocity: number;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineVoice {
+    Top,
+    Bass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineNote {
+    pub event_id: Id,
+    pub ticks: Ticks,
+    pub duration: Ticks,
+    pub note_number: u8,
+    pub velocity: u8,
+}
+
+impl LineNote {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(self) -> js_sys::Object {
+        let js_note = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_note, &JsValue::from_str(key), value).unwrap();
+        };
+
+        set("eventId", &JsValue::from_str(&self.event_id.to_string()));
+        set("ticks", &JsValue::from_f64(self.ticks.as_f64()));
+        set("duration", &JsValue::from_f64(self.duration.as_f64()));
+        set("noteNumber", &JsValue::from_f64(self.note_number as f64));
+        set("velocity", &JsValue::from_f64(self.velocity as f64));
+
+        js_note
+    }
+}
+
+/// The highest (`Top`) or lowest (`Bass`) note sounding at each point of `range` across the
+/// tracks `track_ids`, as a monophonic line clipped to the range.
+///
+/// A note covered by a higher one for the top line is cut there and picks up again once that
+/// note ends, if it is still sounding. Between pitches, the later start wins. Gaps where nothing
+/// sounds are left out.
+pub fn extract_line(
+    song: &Song,
+    track_ids: &[Id],
+    range: TicksRange,
+    voice: LineVoice,
+) -> Vec<LineNote> {
+    let (start, end) = (range.start_ticks, range.end_ticks);
+    let notes: Vec<&Note> = track_ids
+        .iter()
+        .filter_map(|track_id| song.get_track(track_id))
+        .flat_map(|track| song.track_events(track).in_ticks_range(start, end, true))
+        .filter_map(Event::as_note)
+        .filter(|note| note.duration > Ticks::new(0))
+        .collect();
+
+    let mut boundaries = BTreeSet::from([start, end]);
+    for note in &notes {
+        for ticks in [note.ticks, note.ticks + note.duration] {
+            if start < ticks && ticks < end {
+                boundaries.insert(ticks);
+            }
+        }
+    }
+    let boundaries: Vec<Ticks> = boundaries.into_iter().collect();
+
+    let mut line: Vec<LineNote> = Vec::new();
+
+    for window in boundaries.windows(2) {
+        let (from, to) = (window[0], window[1]);
+        let sounding = notes
+            .iter()
+            .filter(|note| note.ticks <= from && from < note.ticks + note.duration);
+        let chosen = match voice {
+            LineVoice::Top => sounding.max_by_key(|note| (note.note_number, note.ticks, note.id)),
+            LineVoice::Bass => sounding.min_by_key(|note| {
+                (
+                    note.note_number,
+                    std::cmp::Reverse(note.ticks),
+                    std::cmp::Reverse(note.id),
+                )
+            }),
+        };
+        let Some(note) = chosen else {
+            continue;
+        };
+
+        match line.last_mut() {
+            Some(last) if last.event_id == note.id && last.ticks + last.duration == from => {
+                last.duration = to - last.ticks;
+            }
+            _ => line.push(LineNote {
+                event_id: note.id,
+                ticks: from,
+                duration: to - from,
+                note_number: note.note_number.as_u8(),
+                velocity: note.velocity.as_u8(),
+            }),
+        }
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    fn pitches(line: &[LineNote]) -> Vec<(u64, u64, u8)> {
+        line.iter()
+            .map(|note| {
+                (
+                    note.ticks.as_u64(),
+                    note.duration.as_u64(),
+                    note.note_number,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_line() {
+        let (mut song, track_id) = song_with_track();
+        // Held C and E with a melody note above them, then a gap and a low note.
+        add_note(&mut song, track_id, 0, 1920, 48, 100);
+        add_note(&mut song, track_id, 0, 1920, 64, 100);
+        add_note(&mut song, track_id, 480, 480, 72, 100);
+        add_note(&mut song, track_id, 2400, 480, 40, 100);
+
+        let range = TicksRange {
+            start_ticks: Ticks::new(240),
+            end_ticks: Ticks::new(2640),
+            within_duration: true,
+        };
+
+        let top = extract_line(&song, &[track_id], range, LineVoice::Top);
+        assert_eq!(
+            pitches(&top),
+            vec![
+                (240, 240, 64),
+                (480, 480, 72),
+                (960, 960, 64),
+                (2400, 240, 40)
+            ]
+        );
+
+        let bass = extract_line(&song, &[track_id], range, LineVoice::Bass);
+        assert_eq!(pitches(&bass), vec![(240, 1680, 48), (2400, 240, 40)]);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{shared::unit::time::Ticks, song::meter::MeterMap};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_METRONOME_INTERFACES: &'static str = r#"
+export interface MetronomeClick {
+  ticks: number;
+  /** One-based bar number. */
+  bar: number;
+  /** One-based beat within the bar. */
+  beat: number;
+  /** `"downbeat"` on the first beat of a bar, `"beat"` elsewhere. */
+  accent: "downbeat" | "beat";
+}
+
+export interface CountInOptions {
+  /** Fixed tempo used to time the count-in. Defaults to 120. */
+  bpm?: number;
+}
+
+export interface CountIn {
+  /** Length of the count-in; recording starts this many ticks after its first click. */
+  offsetTicks: number;
+  /** Length of the count-in at the given tempo. */
+  seconds: number;
+  /** Clicks with ticks counted from the start of the count-in and bars numbered from 1. */
+  clicks: MetronomeClick[];
+}
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CountInOptions {
+    pub bpm: f64,
+}
+
+impl Default for CountInOptions {
+    fn default() -> Self {
+        CountInOptions { bpm: 120.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickAccent {
+    Downbeat,
+    Beat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Click {
+    pub ticks: Ticks,
+    pub bar: u64,
+    pub beat: u64,
+    pub accent: ClickAccent,
+}
+
+impl Click {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_click = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_click,
+            &JsValue::from_str("ticks"),
+            &JsValue::from_f64(self.ticks.as_f64()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_click,
+            &JsValue::from_str("bar"),
+            &JsValue::from_f64(self.bar as f64),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_click,
+            &JsValue::from_str("beat"),
+            &JsValue::from_f64(self.beat as f64),
+        )
+        .unwrap();
+
+        let accent = match self.accent {
+            ClickAccent::Downbeat => "downbeat",
+            ClickAccent::Beat => "beat",
+        };
+        js_sys::Reflect::set(
+            &js_click,
+            &JsValue::from_str("accent"),
+            &JsValue::from_str(accent),
+        )
+        .unwrap();
+
+        js_click
+    }
+}
+
+/// One click per beat in `[start_ticks, end_ticks)`, following the song's meter changes.
+pub fn clicks_in_range(meters: &MeterMap, start_ticks: Ticks, end_ticks: Ticks) -> Vec<Click> {
+    let ppq = meters.ppq();
+    let mut clicks = Vec::new();
+
+    for (index, segment) in meters.segments().iter().enumerate() {
+        let segment_start = segment.ticks.as_u64();
+        let segment_end = meters
+            .segment_end(index)
+            .map_or(u64::MAX, |ticks| ticks.as_u64());
+        let end = segment_end.min(end_ticks.as_u64());
+        if end <= start_ticks.as_u64() || segment_start >= end {
+            continue;
+        }
+
+        let beat_ticks = segment.beat_ticks(ppq);
+        let beats_per_bar = segment.beats_per_bar();
+        let mut beat = start_ticks
+            .as_u64()
+            .saturating_sub(segment_start)
+            .div_ceil(beat_ticks);
+
+        loop {
+            let ticks = segment_start + beat * beat_ticks;
+            if ticks >= end {
+                break;
+            }
+            clicks.push(Click {
+                ticks: Ticks::new(ticks),
+                bar: segment.bar + beat / beats_per_bar + 1,
+                beat: beat % beats_per_bar + 1,
+                accent: if beat.is_multiple_of(beats_per_bar) {
+                    ClickAccent::Downbeat
+                } else {
+                    ClickAccent::Beat
+                },
+            });
+            beat += 1;
+        }
+    }
+
+    clicks
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountIn {
+    pub offset_ticks: Ticks,
+    pub seconds: f64,
+    pub clicks: Vec<Click>,
+}
+
+impl CountIn {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_count_in = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_count_in,
+            &JsValue::from_str("offsetTicks"),
+            &JsValue::from_f64(self.offset_ticks.as_f64()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_count_in,
+            &JsValue::from_str("seconds"),
+            &JsValue::from_f64(self.seconds),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_count_in,
+            &JsValue::from_str("clicks"),
+            &self
+                .clicks
+                .iter()
+                .map(|click| click.to_js_object())
+                .collect::<js_sys::Array>(),
+        )
+        .unwrap();
+
+        js_count_in
+    }
+}
+
+/// `bars` bars of clicks to play before `playhead_ticks`, in the meter in effect there.
+pub fn count_in(
+    meters: &MeterMap,
+    bars: u32,
+    playhead_ticks: Ticks,
+    options: &CountInOptions,
+) -> CountIn {
+    let ppq = meters.ppq();
+    let segment = meters.segment_at(playhead_ticks);
+    let beat_ticks = segment.beat_ticks(ppq);
+    let beats_per_bar = segment.beats_per_bar();
+
+    let clicks = (0..bars as u64 * beats_per_bar)
+        .map(|beat| Click {
+            ticks: Ticks::new(beat * beat_ticks),
+            bar: beat / beats_per_bar + 1,
+            beat: beat % beats_per_bar + 1,
+            accent: if beat.is_multiple_of(beats_per_bar) {
+                ClickAccent::Downbeat
+            } else {
+                ClickAccent::Beat
+            },
+        })
+        .collect();
+
+    let offset_ticks = bars as u64 * segment.bar_ticks(ppq);
+
+    CountIn {
+        offset_ticks: Ticks::new(offset_ticks),
+        seconds: offset_ticks as f64 / ppq as f64 * 60.0 / options.bpm,
+        clicks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{event::Event, time_signature::TimeSignature},
+        shared::id::Id,
+        transform::test_helpers::song_with_track,
+    };
+
+    #[test]
+    fn test_clicks_follow_meter_changes() {
+        let (mut song, track_id) = song_with_track();
+        for (ticks, numerator, denominator) in [(1920, 3, 4), (3360, 6, 8)] {
+            song.add_event(Event::TimeSignature(TimeSignature {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                numerator,
+                denominator,
+                track_id,
+            }))
+            .unwrap();
+        }
+
+        let meters = MeterMap::new(&song);
+        let clicks: Vec<(u64, u64, u64, ClickAccent)> =
+            clicks_in_range(&meters, Ticks::new(1440), Ticks::new(3840))
+                .iter()
+                .map(|click| (click.ticks.as_u64(), click.bar, click.beat, click.accent))
+                .collect();
+
+        assert_eq!(
+            clicks,
+            vec![
+                (1440, 1, 4, ClickAccent::Beat),
+                (1920, 2, 1, ClickAccent::Downbeat),
+                (2400, 2, 2, ClickAccent::Beat),
+                (2880, 2, 3, ClickAccent::Beat),
+                (3360, 3, 1, ClickAccent::Downbeat),
+                (3600, 3, 2, ClickAccent::Beat),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_in_uses_meter_at_playhead() {
+        let (mut song, track_id) = song_with_track();
+        song.add_event(Event::TimeSignature(TimeSignature {
+            id: Id::new(),
+            ticks: Ticks::new(1920),
+            numerator: 3,
+            denominator: 4,
+            track_id,
+        }))
+        .unwrap();
+
+        let meters = MeterMap::new(&song);
+        let count_in = count_in(&meters, 2, Ticks::new(2400), &CountInOptions::default());
+
+        assert_eq!(count_in.offset_ticks, Ticks::new(2880));
+        assert_eq!(count_in.seconds, 3.0);
+        assert_eq!(count_in.clicks.len(), 6);
+        assert_eq!(count_in.clicks[3].ticks, Ticks::new(1440));
+        assert_eq!(count_in.clicks[3].bar, 2);
+        assert_eq!(count_in.clicks[3].accent, ClickAccent::Downbeat);
+    }
+}
@@ -0,0 +1,20 @@
+pub mod automation;
+pub mod buffer;
+pub mod collision;
+pub mod cursor;
+pub mod filter;
+pub mod flat;
+pub mod line;
+pub mod metronome;
+pub mod navigation;
+pub mod note_off;
+pub mod phrase;
+pub mod pitch_class;
+pub mod playback;
+pub mod position;
+pub mod preview;
+pub mod render;
+pub mod summary;
+pub mod sustain;
+pub mod table;
+pub mod window;
@@ -0,0 +1,97 @@
+use super::filter::EventFilter;
+use crate::{
+    event::event::Event,
+    shared::{error::StoreError, id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+
+/// Where to look for the next or previous event from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationAnchor {
+    /// An event: events on its tick come before or after it by kind priority and id, as the
+    /// song orders them.
+    Event(Id),
+    /// A caret before the events on `ticks`, so the next event may start on it and the
+    /// previous one starts earlier.
+    Ticks(Ticks),
+}
+
+/// The first event after `anchor`, or before it when `reverse`, in the song's
+/// `Event::order_key` order that meets `filter`. Walks the tick index from the anchor, so only the events passed
+/// over are looked at.
+pub fn adjacent_event<'a>(
+    song: &'a Song,
+    anchor: NavigationAnchor,
+    reverse: bool,
+    filter: &EventFilter,
+) -> Result<Option<&'a Event>, StoreError> {
+    let (ticks, after) = match anchor {
+        NavigationAnchor::Event(event_id) => {
+            let event = song
+                .get_event(&event_id)
+                .ok_or(StoreError::EventNotFound(event_id))?;
+            (event.get_ticks(), Some(event.order_key()))
+        }
+        NavigationAnchor::Ticks(ticks) => (ticks, None),
+    };
+
+    Ok(song
+        .events_around(ticks, reverse)
+        .filter(|event| {
+            let key = event.order_key();
+            match (after, reverse) {
+                (Some(anchor), false) => key > anchor,
+                (Some(anchor), true) => key < anchor,
+                (None, false) => true,
+                (None, true) => key.0 < ticks,
+            }
+        })
+        .find(|event| filter.accepts(event)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_adjacent_event() {
+        let (mut song, track_id) = song_with_track();
+        let mut chord = [
+            add_note(&mut song, track_id, 0, 480, 60, 100),
+            add_note(&mut song, track_id, 0, 480, 64, 100),
+        ];
+        chord.sort();
+        add_control_change(&mut song, track_id, 240, 1, 64);
+        let next = add_note(&mut song, track_id, 480, 480, 67, 100);
+
+        let notes = EventFilter {
+            kinds: Some(vec!["Note".to_string()].try_into().unwrap()),
+            ..Default::default()
+        };
+        let id = |event: Option<&Event>| event.map(Event::get_id);
+        let step = |anchor, reverse| id(adjacent_event(&song, anchor, reverse, &notes).unwrap());
+
+        assert_eq!(
+            step(NavigationAnchor::Event(chord[0]), false),
+            Some(chord[1])
+        );
+        assert_eq!(step(NavigationAnchor::Event(chord[1]), false), Some(next));
+        assert_eq!(step(NavigationAnchor::Event(next), true), Some(chord[1]));
+        assert_eq!(step(NavigationAnchor::Event(chord[0]), true), None);
+        assert_eq!(
+            step(NavigationAnchor::Ticks(Ticks::new(480)), false),
+            Some(next)
+        );
+        assert_eq!(
+            step(NavigationAnchor::Ticks(Ticks::new(480)), true),
+            Some(chord[1])
+        );
+
+        let any = EventFilter::default();
+        let after_chord = adjacent_event(&song, NavigationAnchor::Event(chord[1]), false, &any);
+        assert_eq!(after_chord.unwrap().unwrap().get_ticks(), Ticks::new(240));
+        let missing = adjacent_event(&song, NavigationAnchor::Event(Id::new()), false, &any);
+        assert!(matches!(missing, Err(StoreError::EventNotFound(_))));
+    }
+}
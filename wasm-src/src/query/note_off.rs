@@ -0,0 +1,158 @@
+#[cfg(feature = "wasm")]
+use crate::shared::js::to_js_value;
+use crate::{
+    event::{
+        event::{Event, TickPriority},
+        note::NoteNumber,
+    },
+    shared::{id::Id, unit::time::Ticks},
+    song::song::{GetEventsFilter, Song},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_NOTE_OFF_INTERFACES: &'static str = r#"
+/** Where a note ends, interleaved with the events by `getEventsInTicksRange` with `noteOffs`. */
+export interface NoteOff {
+  kind: "NoteOff";
+  /** The id of the note that ends. */
+  id: string;
+  trackId: string;
+  ticks: number;
+  noteNumber: number;
+}
+
+export interface TicksRangeOptions {
+  /**
+   * Adds a `NoteOff` for each note ending in the range. On the same tick, note offs come after
+   * time signatures, markers and the other metas and before controller changes and new notes,
+   * so a scheduler can send the stream as it comes.
+   */
+  noteOffs?: boolean;
+}
+"#;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TicksRangeOptions {
+    pub note_offs: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub struct NoteOff {
+    pub id: Id,
+    pub track_id: Id,
+    pub ticks: Ticks,
+    pub note_number: NoteNumber,
+}
+
+/// An event, or the end of a note, in a range query's stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeItem<'a> {
+    Event(&'a Event),
+    NoteOff(NoteOff),
+}
+
+impl RangeItem<'_> {
+    fn order_key(&self) -> (Ticks, TickPriority) {
+        match self {
+            RangeItem::Event(event) => (event.get_ticks(), event.get_kind().priority()),
+            RangeItem::NoteOff(note_off) => (note_off.ticks, TickPriority::NoteOff),
+        }
+    }
+
+    /// The items as JS objects, events the way `Song::event_to_js_object` gives them.
+    #[cfg(feature = "wasm")]
+    pub fn to_js_array(song: &Song, items: &[RangeItem]) -> js_sys::Array {
+        items
+            .iter()
+            .map(|item| -> JsValue {
+                match item {
+                    RangeItem::Event(event) => song.event_to_js_object(event).into(),
+                    RangeItem::NoteOff(note_off) => to_js_value(note_off),
+                }
+            })
+            .collect()
+    }
+}
+
+/// `events`, as a range query over `[start_ticks, end_ticks)` returns them, with a `NoteOff`
+/// for every note on the tracks in `filter`, or all tracks, that ends in the range. The
+/// events' order is kept and each note off goes before the events of lower priority on its
+/// tick, note offs on the same tick in note id order.
+pub fn with_note_offs<'a>(
+    song: &Song,
+    events: Vec<&'a Event>,
+    start_ticks: Ticks,
+    end_ticks: Ticks,
+    filter: Option<GetEventsFilter>,
+) -> Vec<RangeItem<'a>> {
+    let track_ids = filter.and_then(|filter| filter.track_ids);
+    let mut note_offs: Vec<NoteOff> = song
+        .iter_events_ending_in_ticks_range(start_ticks, end_ticks)
+        .filter(|event| {
+            track_ids.as_ref().is_none_or(|track_ids| {
+                event
+                    .get_track_id()
+                    .is_some_and(|track_id| track_ids.contains(&track_id))
+            })
+        })
+        .filter_map(|event| event.as_note())
+        .map(|note| NoteOff {
+            id: note.id,
+            track_id: note.track_id,
+            ticks: note.ticks + note.duration,
+            note_number: note.note_number,
+        })
+        .collect();
+    note_offs.sort_by_key(|note_off| (note_off.ticks, note_off.id));
+
+    let mut items: Vec<RangeItem> = events.into_iter().map(RangeItem::Event).collect();
+    items.extend(note_offs.into_iter().map(RangeItem::NoteOff));
+    // stable, so the events and the note offs each keep their order
+    items.sort_by_key(RangeItem::order_key);
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_with_note_offs() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let second = add_note(&mut song, track_id, 480, 480, 62, 100);
+        let sustain = add_control_change(&mut song, track_id, 480, 64, 127);
+        add_note(&mut song, track_id, 960, 960, 64, 100);
+
+        let events = song.get_events_in_ticks_range(Ticks::new(0), Ticks::new(960), false, None);
+        let items = with_note_offs(&song, events, Ticks::new(0), Ticks::new(960), None);
+        let kinds: Vec<(u64, String, Id)> = items
+            .iter()
+            .map(|item| match item {
+                RangeItem::Event(event) => {
+                    let kind = event.get_kind().to_string();
+                    (event.get_ticks().as_u64(), kind, event.get_id())
+                }
+                RangeItem::NoteOff(note_off) => {
+                    (note_off.ticks.as_u64(), "NoteOff".to_string(), note_off.id)
+                }
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (0, "Note".to_string(), first),
+                (480, "NoteOff".to_string(), first),
+                (480, "ControlChange".to_string(), sustain),
+                (480, "Note".to_string(), second),
+            ]
+        );
+    }
+}
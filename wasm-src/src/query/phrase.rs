@@ -0,0 +1,181 @@
+use crate::{
+    event::{event::Event, note::Note},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+use std::collections::HashSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PHRASE_INTERFACES: &'static str = r#"
+export interface PhraseMatch {
+  trackId: string;
+  startTicks: number;
+  /** End of the latest matched note. */
+  endTicks: number;
+  /** Semitones from the selected phrase to the match. */
+  transpose: number;
+  /** Matched notes, in the order of the phrase's notes. */
+  eventIds: string[];
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhraseMatch {
+    pub track_id: Id,
+    pub start_ticks: Ticks,
+    pub end_ticks: Ticks,
+    pub transpose: i32,
+    pub event_ids: Vec<Id>,
+}
+
+impl PhraseMatch {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_match = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_match, &JsValue::from_str(key), value).unwrap();
+        };
+
+        set("trackId", &JsValue::from_str(&self.track_id.to_string()));
+        set("startTicks", &JsValue::from_f64(self.start_ticks.as_f64()));
+        set("endTicks", &JsValue::from_f64(self.end_ticks.as_f64()));
+        set("transpose", &JsValue::from_f64(self.transpose as f64));
+        set(
+            "eventIds",
+            &self
+                .event_ids
+                .iter()
+                .map(|id| JsValue::from_str(&id.to_string()))
+                .collect::<js_sys::Array>(),
+        );
+
+        js_match
+    }
+}
+
+/// Other occurrences of the phrase made of the notes `event_ids`, at any pitch, on any track.
+///
+/// A match has, for every note of the phrase, a note on one track at the same interval from its
+/// first note and starting within `tolerance` ticks of the same distance from it. Each phrase
+/// note takes the closest such note not already taken. The selection itself is not reported.
+/// Matches are ordered by track and ticks and may overlap.
+pub fn find_similar_phrases(song: &Song, event_ids: &[Id], tolerance: Ticks) -> Vec<PhraseMatch> {
+    let mut phrase: Vec<&Note> = event_ids
+        .iter()
+        .filter_map(|id| song.get_event(id).and_then(|event| event.as_note()))
+        .collect();
+    phrase.sort_by_key(|note| (note.ticks, note.note_number, note.id));
+    let Some(first) = phrase.first() else {
+        return Vec::new();
+    };
+
+    let selected: HashSet<Id> = phrase.iter().map(|note| note.id).collect();
+    let pitch = |note: &Note| note.note_number.as_u8() as i32;
+    let tolerance = tolerance.as_u64() as i64;
+    let mut matches = Vec::new();
+
+    for track in song.get_tracks().iter() {
+        let events = song.track_events(track);
+        let notes: Vec<&Note> = events.iter().filter_map(|event| event.as_note()).collect();
+
+        for anchor in &notes {
+            let offset = anchor.ticks.as_u64() as i64 - first.ticks.as_u64() as i64;
+            let transpose = pitch(anchor) - pitch(first);
+            let mut event_ids = vec![anchor.id];
+
+            for note in &phrase[1..] {
+                let target = note.ticks.as_u64() as i64 + offset;
+                let start = (target - tolerance).max(0) as u64;
+                let end = (target + tolerance + 1).max(0) as u64;
+
+                let found = events
+                    .iter_range(Ticks::new(start), Ticks::new(end))
+                    .filter_map(|event| match event {
+                        Event::Note(candidate)
+                            if pitch(candidate) == pitch(note) + transpose
+                                && !event_ids.contains(&candidate.id) =>
+                        {
+                            Some(candidate)
+                        }
+                        _ => None,
+                    })
+                    .min_by_key(|candidate| {
+                        (
+                            (candidate.ticks.as_u64() as i64 - target).abs(),
+                            candidate.id,
+                        )
+                    });
+
+                match found {
+                    Some(candidate) => event_ids.push(candidate.id),
+                    None => break,
+                }
+            }
+
+            if event_ids.len() < phrase.len() || event_ids.iter().all(|id| selected.contains(id)) {
+                continue;
+            }
+
+            let matched: Vec<&Note> = event_ids
+                .iter()
+                .filter_map(|id| song.get_event(id).and_then(|event| event.as_note()))
+                .collect();
+            matches.push(PhraseMatch {
+                track_id: track.id,
+                start_ticks: matched.iter().map(|note| note.ticks).min().unwrap(),
+                end_ticks: matched
+                    .iter()
+                    .map(|note| note.ticks + note.duration)
+                    .max()
+                    .unwrap(),
+                transpose,
+                event_ids,
+            });
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_find_similar_phrases() {
+        let (mut song, track_id) = song_with_track();
+        let phrase: Vec<Id> = [(0, 60), (480, 62), (960, 64)]
+            .into_iter()
+            .map(|(ticks, nn)| add_note(&mut song, track_id, ticks, 240, nn, 100))
+            .collect();
+        // A fourth higher, slightly late on the second note.
+        let higher: Vec<Id> = [(1920, 65), (2405, 67), (2880, 69)]
+            .into_iter()
+            .map(|(ticks, nn)| add_note(&mut song, track_id, ticks, 240, nn, 100))
+            .collect();
+        // Different intervals.
+        for (ticks, nn) in [(3840, 60), (4320, 63), (4800, 64)] {
+            add_note(&mut song, track_id, ticks, 240, nn, 100);
+        }
+        // Too late on the third note.
+        for (ticks, nn) in [(5760, 60), (6240, 62), (6760, 64)] {
+            add_note(&mut song, track_id, ticks, 240, nn, 100);
+        }
+
+        let matches = find_similar_phrases(&song, &phrase, Ticks::new(10));
+        assert_eq!(
+            matches,
+            vec![PhraseMatch {
+                track_id,
+                start_ticks: Ticks::new(1920),
+                end_ticks: Ticks::new(3120),
+                transpose: 5,
+                event_ids: higher,
+            }]
+        );
+    }
+}
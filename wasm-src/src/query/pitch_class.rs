@@ -0,0 +1,150 @@
+use crate::{event::event::Event, shared::unit::time::Ticks, song::song::Song};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PITCH_CLASS_INTERFACES: &'static str = r#"
+/** The harmony in effect at a tick, for coloring piano roll rows. */
+export interface PitchClassInfo {
+  ticks: number;
+  /** The latest key signature at or before `ticks`. */
+  key: KeySignature | null;
+  /** The latest chord symbol at or before `ticks`. */
+  chord: ChordSymbol | null;
+  /** Twelve flags from C: whether each pitch class belongs to the key's scale. */
+  inKey: boolean[];
+  /** Twelve flags from C: whether each pitch class is a chord tone or the chord's bass. */
+  inChord: boolean[];
+}
+"#;
+
+pub struct PitchClassInfo<'a> {
+    pub ticks: Ticks,
+    pub key: Option<&'a Event>,
+    pub chord: Option<&'a Event>,
+}
+
+impl PitchClassInfo<'_> {
+    /// All false without a key signature.
+    pub fn in_key(&self) -> [bool; 12] {
+        match self.key {
+            Some(Event::KeySignature(key)) => key.pitch_classes(),
+            _ => [false; 12],
+        }
+    }
+
+    /// All false without a chord symbol.
+    pub fn in_chord(&self) -> [bool; 12] {
+        match self.chord {
+            Some(Event::ChordSymbol(chord)) => chord.pitch_classes(),
+            _ => [false; 12],
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, song: &Song) -> js_sys::Object {
+        let js_info = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_info, &JsValue::from_str(key), value).unwrap();
+        };
+        let flags = |flags: [bool; 12]| {
+            flags
+                .into_iter()
+                .map(JsValue::from_bool)
+                .collect::<js_sys::Array>()
+        };
+
+        set("ticks", &JsValue::from_f64(self.ticks.as_f64()));
+        set(
+            "key",
+            &self
+                .key
+                .map_or(JsValue::NULL, |event| song.event_to_js_object(event).into()),
+        );
+        set(
+            "chord",
+            &self
+                .chord
+                .map_or(JsValue::NULL, |event| song.event_to_js_object(event).into()),
+        );
+        set("inKey", &flags(self.in_key()));
+        set("inChord", &flags(self.in_chord()));
+
+        js_info
+    }
+}
+
+/// The key signature and chord symbol in effect at `ticks`, from any track. Of several on the
+/// same tick, the one with the greatest id wins.
+pub fn pitch_class_info(song: &Song, ticks: Ticks) -> PitchClassInfo<'_> {
+    let key = song
+        .get_key_at(ticks)
+        .and_then(|key| song.get_event(&key.id));
+    let mut chord = None;
+
+    for event in song.get_events(None) {
+        if event.get_ticks() > ticks || !matches!(event, Event::ChordSymbol(_)) {
+            continue;
+        }
+        let position = |event: &Event| (event.get_ticks(), event.get_id());
+        if chord.is_none_or(|chord: &Event| position(chord) < position(event)) {
+            chord = Some(event);
+        }
+    }
+
+    PitchClassInfo { ticks, key, chord }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{
+            chord_symbol::{ChordQuality, ChordSymbol},
+            key_signature::{KeyMode, KeySignature},
+        },
+        shared::id::Id,
+        transform::test_helpers::song_with_track,
+    };
+
+    fn pitch_classes(flags: [bool; 12]) -> Vec<usize> {
+        (0..12).filter(|pitch_class| flags[*pitch_class]).collect()
+    }
+
+    #[test]
+    fn test_pitch_class_info() {
+        let (mut song, track_id) = song_with_track();
+        assert_eq!(pitch_class_info(&song, Ticks::new(0)).in_key(), [false; 12]);
+
+        song.add_event(Event::KeySignature(KeySignature {
+            id: Id::new(),
+            ticks: Ticks::new(0),
+            root: 2,
+            mode: KeyMode::Major,
+            track_id,
+        }))
+        .unwrap();
+        for (ticks, root, quality, bass) in [
+            (0, 7, ChordQuality::Major, None),
+            (960, 9, ChordQuality::Dominant7, Some(1)),
+        ] {
+            song.add_event(Event::ChordSymbol(ChordSymbol {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                root,
+                quality,
+                bass,
+                track_id,
+            }))
+            .unwrap();
+        }
+
+        let info = pitch_class_info(&song, Ticks::new(480));
+        assert_eq!(pitch_classes(info.in_key()), vec![1, 2, 4, 6, 7, 9, 11]);
+        assert_eq!(pitch_classes(info.in_chord()), vec![2, 7, 11]);
+
+        let info = pitch_class_info(&song, Ticks::new(960));
+        assert_eq!(pitch_classes(info.in_chord()), vec![1, 4, 7, 9]);
+    }
+}
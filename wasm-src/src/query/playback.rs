@@ -0,0 +1,241 @@
+use super::sustain::effective_note_ends;
+use crate::{
+    event::event::{Event, KindOrder},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PLAYBACK_INTERFACES: &'static str = r#"
+export interface PlaybackOptions {
+  /** Tracks to play; all tracks when omitted. */
+  trackIds?: string[];
+  /** Fixed tempo used to convert millisecond delays to ticks. Defaults to 120. */
+  bpm?: number;
+  /**
+   * Order of kinds among events on the same tick, e.g. control changes before notes. Kinds left
+   * out follow the listed ones in track order.
+   */
+  kindOrder?: EventKind[];
+  /** Lengthen notes held by the sustain pedal (CC64) to where the pedal lets them go. */
+  sustainPedal?: boolean;
+  /** Add the keyswitch from the track's expression map before each articulated note. */
+  keyswitches?: boolean;
+  /**
+   * Seed for the variations of tracks with a performance profile. The same seed plays each
+   * event the same way. Defaults to 1.
+   */
+  seed?: number;
+}
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlaybackOptions {
+    pub track_ids: Option<Vec<Id>>,
+    pub bpm: f64,
+    pub kind_order: KindOrder,
+    pub sustain_pedal: bool,
+    pub keyswitches: bool,
+    pub seed: u64,
+}
+
+impl Default for PlaybackOptions {
+    fn default() -> Self {
+        PlaybackOptions {
+            track_ids: None,
+            bpm: 120.0,
+            kind_order: KindOrder::default(),
+            sustain_pedal: false,
+            keyswitches: false,
+            seed: 1,
+        }
+    }
+}
+
+/// Events as they should sound in `[start_ticks, end_ticks)`, with their track's settings and
+/// delay applied, then varied by its performance profile with `options.seed`. Muted events and
+/// groups, muted tracks and tracks silenced by a solo are left out.
+///
+/// Events are picked by their delayed position before swing, so consecutive ranges return each
+/// event exactly once. Events delayed before the song start play at tick 0. Events on the same
+/// tick are ordered by `options.kind_order`, then by track. With `options.sustain_pedal`, notes
+/// last until their effective ends, see `effective_note_ends`. With `options.keyswitches`, the
+/// keyswitches of articulated notes are added, untransposed, on the notes' played ticks.
+pub fn playback_events(
+    song: &Song,
+    start_ticks: Ticks,
+    end_ticks: Ticks,
+    options: &PlaybackOptions,
+) -> Vec<Event> {
+    let mut events = Vec::new();
+
+    for track in song.get_tracks().iter() {
+        if let Some(track_ids) = &options.track_ids {
+            if !track_ids.contains(&track.id) {
+                continue;
+            }
+        }
+        if song.is_track_audible(&track.id) != Some(true) {
+            continue;
+        }
+
+        let delay = track.settings.delay(song.ppq, options.bpm);
+        let profile = song.profiles.of_track(&track.id);
+        let note_ends = options
+            .sustain_pedal
+            .then(|| effective_note_ends(song, track));
+        let stored_start = if start_ticks.as_u64() == 0 {
+            0
+        } else {
+            (start_ticks.as_u64() as i64 - delay).max(0)
+        };
+        let stored_end = end_ticks.as_u64() as i64 - delay;
+        if stored_end <= stored_start {
+            continue;
+        }
+
+        let mut track_events = Vec::new();
+        for event in song.track_events(track).iter_range(
+            Ticks::new(stored_start as u64),
+            Ticks::new(stored_end.min(Ticks::MAX.as_u64() as i64) as u64),
+        ) {
+            if song.is_event_muted(&event.get_id()) {
+                continue;
+            }
+            let event = match (&note_ends, event) {
+                (Some(note_ends), Event::Note(note)) => event
+                    .clone()
+                    .with_duration(note_ends[&note.id] - note.ticks),
+                _ => event.clone(),
+            };
+            let event = track.settings.apply(event, song.ppq, &song.grooves);
+            let event = match profile {
+                Some(profile) => profile.apply(event, options.seed),
+                None => event,
+            };
+            let ticks = (event.get_ticks().as_u64() as i64 + delay).max(0);
+            track_events.push(event.with_ticks(Ticks::new(ticks as u64)));
+        }
+        if options.keyswitches {
+            track_events = track.expression_map.with_keyswitches(track_events);
+        }
+        events.extend(track_events);
+    }
+
+    events.sort_by_key(|event| (event.get_ticks(), options.kind_order.rank(event.get_kind())));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::event::EventKind,
+        track::track::Track,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_playback_events_apply_track_delay() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 0, 240, 60, 100);
+        add_note(&mut song, track_id, 480, 240, 62, 100);
+        add_note(&mut song, track_id, 980, 240, 64, 100);
+        let settings = &mut song.get_track_mut(&track_id).unwrap().settings;
+        settings.delay_ticks = -40;
+        settings.delay_ms = -10.0;
+
+        let options = PlaybackOptions::default();
+        let ticks = |events: Vec<Event>| -> Vec<u64> {
+            events
+                .iter()
+                .map(|event| event.get_ticks().as_u64())
+                .collect()
+        };
+
+        // -10 ms at 120 BPM rounds to -10 ticks at 480 ppq.
+        assert_eq!(
+            ticks(playback_events(
+                &song,
+                Ticks::new(0),
+                Ticks::new(480),
+                &options
+            )),
+            vec![0, 430]
+        );
+        assert_eq!(
+            ticks(playback_events(
+                &song,
+                Ticks::new(480),
+                Ticks::new(960),
+                &options
+            )),
+            vec![930]
+        );
+        assert!(playback_events(&song, Ticks::new(960), Ticks::new(1440), &options).is_empty());
+    }
+
+    #[test]
+    fn test_playback_events_kind_order() {
+        let (mut song, track_id) = song_with_track();
+        let control_track_id = Id::new();
+        song.add_track(Track::new(control_track_id));
+        add_note(&mut song, track_id, 0, 240, 60, 100);
+        add_control_change(&mut song, control_track_id, 0, 7, 90);
+
+        let kinds = |options: &PlaybackOptions| -> Vec<EventKind> {
+            playback_events(&song, Ticks::new(0), Ticks::new(480), options)
+                .iter()
+                .map(|event| event.get_kind())
+                .collect()
+        };
+
+        let options = PlaybackOptions::default();
+        assert_eq!(
+            kinds(&options),
+            vec![EventKind::Note, EventKind::ControlChange]
+        );
+
+        let options = PlaybackOptions {
+            kind_order: KindOrder::try_from(vec!["ControlChange".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            kinds(&options),
+            vec![EventKind::ControlChange, EventKind::Note]
+        );
+        assert!(KindOrder::try_from(vec!["Tempo".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_playback_events_leave_out_silenced_tracks() {
+        let (mut song, muted) = song_with_track();
+        let [soloed, plain] = [Id::new(), Id::new()];
+        for track_id in [soloed, plain] {
+            song.add_track(Track::new(track_id));
+        }
+        for (note_number, track_id) in [(60, muted), (62, soloed), (64, plain)] {
+            add_note(&mut song, track_id, 0, 240, note_number, 100);
+        }
+        song.get_track_mut(&muted).unwrap().mix.mute = true;
+        let mix = &mut song.get_track_mut(&soloed).unwrap().mix;
+        mix.mute = true;
+        mix.solo = true;
+
+        let track_ids = |song: &Song| -> Vec<Id> {
+            playback_events(song, Ticks::new(0), Ticks::new(480), &Default::default())
+                .iter()
+                .filter_map(|event| event.get_track_id())
+                .collect()
+        };
+        assert_eq!(track_ids(&song), vec![soloed]);
+
+        song.get_track_mut(&soloed).unwrap().mix.solo = false;
+        assert_eq!(track_ids(&song), vec![plain]);
+    }
+}
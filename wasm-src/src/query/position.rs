@@ -0,0 +1,70 @@
+use crate::{
+    shared::{js::string_enum, unit::time::Ticks},
+    song::{meter::MeterMap, song::Song},
+};
+
+string_enum! {
+    /// How `formatPosition` writes a position.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum PositionFormat {
+        /// One-based bar and beat, then ticks within the beat, like `5.2.240`.
+        Bars = "bars",
+        /// Hours, minutes, seconds and milliseconds at the song's tempo, like `00:01:23.456`.
+        Time = "time",
+    }
+}
+
+/// `ticks` written in `format`, following the song's meter changes and tempo.
+pub fn format_position(song: &Song, ticks: Ticks, format: PositionFormat) -> String {
+    match format {
+        PositionFormat::Time => {
+            let milliseconds = (song.ticks_to_seconds(ticks) * 1000.0).round() as u64;
+            format!(
+                "{:02}:{:02}:{:02}.{:03}",
+                milliseconds / 3_600_000,
+                milliseconds / 60_000 % 60,
+                milliseconds / 1000 % 60,
+                milliseconds % 1000
+            )
+        }
+        PositionFormat::Bars => {
+            let (bar, beat, ticks) = MeterMap::new(song).bar_beat_at(ticks);
+            format!("{}.{}.{}", bar + 1, beat + 1, ticks)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{event::Event, time_signature::TimeSignature},
+        shared::id::Id,
+        transform::test_helpers::song_with_track,
+    };
+
+    #[test]
+    fn test_format_position() {
+        let (mut song, track_id) = song_with_track();
+        song.bpm = 60.0;
+        song.add_event(Event::TimeSignature(TimeSignature {
+            id: Id::new(),
+            ticks: Ticks::new(1920 * 2),
+            numerator: 6,
+            denominator: 8,
+            track_id,
+        }))
+        .unwrap();
+
+        let format =
+            |ticks: u64, format: PositionFormat| format_position(&song, Ticks::new(ticks), format);
+        assert_eq!(format(0, PositionFormat::Bars), "1.1.0");
+        assert_eq!(format(480 + 240, PositionFormat::Bars), "1.2.240");
+        assert_eq!(
+            format(1920 * 2 + 1440 + 250, PositionFormat::Bars),
+            "4.2.10"
+        );
+        assert_eq!(format(480 * 83 + 219, PositionFormat::Time), "00:01:23.456");
+        assert_eq!(format(480 * 3600, PositionFormat::Time), "01:00:00.000");
+    }
+}
@@ -0,0 +1,220 @@
+use super::collision::find_collisions;
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, NoteNumber},
+    },
+    shared::{
+        error::StoreError,
+        id::Id,
+        unit::{
+            grid::{too_fine_error, Grid},
+            time::Ticks,
+        },
+    },
+    song::{meter::MeterMap, song::Song},
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PREVIEW_INTERFACES: &'static str = r#"
+export interface TransformDelta {
+  deltaTicks?: number;
+  deltaNotes?: number;
+  /** Snaps the earliest event to this grid; the others keep their distance to it. */
+  grid?: Grid;
+}
+
+export interface TransformedEvent {
+  id: string;
+  ticks: number;
+  noteNumber?: number;
+  /** Notes the moved note would overlap, not counting the other moved events. */
+  collisions: string[];
+}
+
+export interface TransformPreview {
+  /** The tick offset actually applied, after snapping and clamping at zero. */
+  deltaTicks: number;
+  events: TransformedEvent[];
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransformDelta {
+    pub delta_ticks: i64,
+    pub delta_notes: i32,
+    pub grid: Option<Grid>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformedEvent {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub note_number: Option<NoteNumber>,
+    pub collisions: Vec<Id>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformPreview {
+    pub delta_ticks: i64,
+    pub events: Vec<TransformedEvent>,
+}
+
+/// Where `event_ids` would end up if moved by `delta`, without touching the song.
+///
+/// The events move as a block: the offset is chosen so the earliest one lands on the grid, and
+/// is clamped so none moves before tick zero. Note numbers are clamped to 0..=127.
+pub fn preview_transform(
+    song: &Song,
+    event_ids: &[Id],
+    delta: TransformDelta,
+) -> Result<TransformPreview, StoreError> {
+    let events = song.try_get_events(event_ids)?;
+    let moved_ids: HashSet<Id> = event_ids.iter().copied().collect();
+
+    let Some(earliest) = events.iter().map(|event| event.get_ticks()).min() else {
+        return Ok(TransformPreview {
+            delta_ticks: 0,
+            events: Vec::new(),
+        });
+    };
+
+    let target = (earliest.as_u64() as i64 + delta.delta_ticks).max(0);
+    let target = match delta.grid {
+        Some(grid) => grid
+            .snap(&MeterMap::new(song), Ticks::new(target as u64))
+            .ok_or_else(too_fine_error)?
+            .as_u64() as i64,
+        None => target,
+    };
+    let delta_ticks = target - earliest.as_u64() as i64;
+
+    let events = events
+        .into_iter()
+        .map(|event| {
+            let ticks = Ticks::new((event.get_ticks().as_u64() as i64 + delta_ticks) as u64);
+
+            match event {
+                Event::Note(Note {
+                    id,
+                    duration,
+                    note_number,
+                    track_id,
+                    ..
+                }) => {
+                    let note_number = NoteNumber::new(
+                        (note_number.as_u8() as i32 + delta.delta_notes).clamp(0, 127) as u8,
+                    );
+                    let collisions = song
+                        .get_track(track_id)
+                        .map(|track| {
+                            find_collisions(song, track, ticks, *duration, note_number, &moved_ids)
+                        })
+                        .unwrap_or_default();
+
+                    TransformedEvent {
+                        id: *id,
+                        ticks,
+                        note_number: Some(note_number),
+                        collisions,
+                    }
+                }
+                event => TransformedEvent {
+                    id: event.get_id(),
+                    ticks,
+                    note_number: None,
+                    collisions: Vec::new(),
+                },
+            }
+        })
+        .collect();
+
+    Ok(TransformPreview {
+        delta_ticks,
+        events,
+    })
+}
+
+impl TransformPreview {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_preview = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_preview,
+            &JsValue::from_str("deltaTicks"),
+            &JsValue::from_f64(self.delta_ticks as f64),
+        )
+        .unwrap();
+
+        let events: js_sys::Array = self
+            .events
+            .iter()
+            .map(|event| {
+                let js_event = js_sys::Object::new();
+                let set = |key: &str, value: &JsValue| {
+                    js_sys::Reflect::set(&js_event, &JsValue::from_str(key), value).unwrap();
+                };
+
+                set("id", &JsValue::from_str(event.id.to_string().as_str()));
+                set("ticks", &JsValue::from_f64(event.ticks.as_f64()));
+                if let Some(note_number) = event.note_number {
+                    set("noteNumber", &JsValue::from_f64(note_number.as_u8() as f64));
+                }
+                set(
+                    "collisions",
+                    &event
+                        .collisions
+                        .iter()
+                        .map(|id| JsValue::from_str(id.to_string().as_str()))
+                        .collect::<js_sys::Array>(),
+                );
+
+                js_event
+            })
+            .collect();
+        js_sys::Reflect::set(&js_preview, &JsValue::from_str("events"), &events).unwrap();
+
+        js_preview
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_preview_transform() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 10, 480, 60, 100);
+        let second = add_note(&mut song, track_id, 490, 480, 62, 100);
+        let obstacle = add_note(&mut song, track_id, 960, 480, 61, 100);
+
+        let delta = TransformDelta {
+            delta_ticks: 500,
+            delta_notes: -1,
+            grid: Some(Grid {
+                division: 4,
+                tuplet: None,
+            }),
+        };
+        let preview = preview_transform(&song, &[first, second], delta).unwrap();
+
+        assert_eq!(preview.delta_ticks, 470);
+        assert_eq!(preview.events[0].ticks, Ticks::new(480));
+        assert_eq!(preview.events[0].note_number, Some(NoteNumber::new(59)));
+        assert!(preview.events[0].collisions.is_empty());
+        assert_eq!(preview.events[1].ticks, Ticks::new(960));
+        assert_eq!(preview.events[1].collisions, vec![obstacle]);
+
+        // Nothing was moved.
+        assert_eq!(song.get_event(&first).unwrap().get_ticks(), Ticks::new(10));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    event::event::Event, shared::unit::time::Ticks, song::song::Song, track::track::Track,
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RENDER_INTERFACES: &'static str = r#"
+export interface RenderTrack {
+  id: string;
+  name: string | null;
+  /** The track's display color, or a palette color picked by its position. */
+  color: string;
+  mute: boolean;
+  solo: boolean;
+  /** Whether the track sounds, given the solos of all tracks. */
+  audible: boolean;
+  /** Events starting in the range or still sounding in it. */
+  events: Event[];
+}
+
+export interface RenderModel {
+  ppq: number;
+  startTicks: number;
+  endTicks: number;
+  tracks: RenderTrack[];
+}
+"#;
+
+/// Colors for tracks without one of their own, cycled by track position.
+const PALETTE: [&str; 8] = [
+    "#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#42d4f4", "#f032e6", "#9a6324",
+];
+
+/// One track as a renderer draws it.
+pub struct RenderTrack<'a> {
+    pub track: &'a Track,
+    pub color: &'a str,
+    pub audible: bool,
+    pub events: Vec<&'a Event>,
+}
+
+impl RenderTrack<'_> {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, song: &Song) -> js_sys::Object {
+        let js_track = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&js_track, &JsValue::from_str(key), value).unwrap();
+        };
+
+        set("id", &JsValue::from_str(&self.track.id.to_string()));
+        set(
+            "name",
+            &self
+                .track
+                .display
+                .name
+                .as_deref()
+                .map_or(JsValue::NULL, JsValue::from_str),
+        );
+        set("color", &JsValue::from_str(self.color));
+        set("mute", &JsValue::from_bool(self.track.mix.mute));
+        set("solo", &JsValue::from_bool(self.track.mix.solo));
+        set("audible", &JsValue::from_bool(self.audible));
+        set(
+            "events",
+            &song.events_to_js_array(self.events.iter().copied()),
+        );
+
+        js_track
+    }
+}
+
+/// Every track in order with its display metadata and the events visible in
+/// `[start_ticks, end_ticks)`.
+pub fn render_tracks(song: &Song, start_ticks: Ticks, end_ticks: Ticks) -> Vec<RenderTrack<'_>> {
+    song.get_tracks()
+        .iter()
+        .enumerate()
+        .map(|(index, track)| RenderTrack {
+            track,
+            color: track
+                .display
+                .color
+                .as_deref()
+                .unwrap_or(PALETTE[index % PALETTE.len()]),
+            audible: song.is_track_audible(&track.id).unwrap_or(false),
+            events: song
+                .track_events(track)
+                .in_ticks_range(start_ticks, end_ticks, true),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::id::Id,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_render_tracks() {
+        let (mut song, first_id) = song_with_track();
+        let second_id = Id::new();
+        let mut second = Track::new(second_id);
+        second.display.name = Some("Bass".to_string());
+        second.display.color = Some("#123456".to_string());
+        second.mix.solo = true;
+        song.add_track(second);
+
+        let sounding = add_note(&mut song, first_id, 0, 960, 60, 100);
+        add_note(&mut song, first_id, 1920, 480, 62, 100);
+        let bass = add_note(&mut song, second_id, 480, 480, 36, 100);
+
+        let tracks = render_tracks(&song, Ticks::new(480), Ticks::new(1920));
+        assert_eq!(tracks.len(), 2);
+
+        assert_eq!(tracks[0].color, PALETTE[0]);
+        assert!(!tracks[0].audible);
+        let ids: Vec<Id> = tracks[0]
+            .events
+            .iter()
+            .map(|event| event.get_id())
+            .collect();
+        assert_eq!(ids, vec![sounding]);
+
+        assert_eq!(tracks[1].track.display.name.as_deref(), Some("Bass"));
+        assert_eq!(tracks[1].color, "#123456");
+        assert!(tracks[1].audible);
+        assert_eq!(tracks[1].events[0].get_id(), bass);
+    }
+}
@@ -0,0 +1,370 @@
+#[cfg(feature = "wasm")]
+use crate::shared::js::to_js_value;
+use crate::{
+    event::{event::Event, note::NoteNumber},
+    shared::{id::Id, unit::time::Ticks},
+    song::{
+        meter::MeterMap,
+        song::{GetEventsFilter, Song},
+    },
+    track::track::{PitchRange, Track},
+};
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SONG_SUMMARY_INTERFACE: &'static str = r#"
+/** Song statistics for project browsers and share cards. */
+export interface SongSummary {
+  trackCount: number;
+  noteCount: number;
+  /** Where the song ends, see `setEndOfSongPolicy`, or the last event if that is later. */
+  durationTicks: number;
+  durationSeconds: number;
+  /** The song plays at one fixed tempo for now, so both ends are its `bpm`. */
+  tempoRange: { min: number; max: number };
+  /** Distinct meters in order of first use, including the default 4/4 when nothing sets tick 0. */
+  meters: { numerator: number; denominator: number }[];
+  /** The lowest and highest notes, or `null` without notes. */
+  pitchRange: PitchRange | null;
+}
+
+/** Aggregates for overviews and minimaps, see `Store.getStats`. */
+export interface SongStats {
+  eventCount: number;
+  noteCount: number;
+  /** The lowest and highest notes, or `null` without notes. */
+  pitchRange: PitchRange | null;
+  /** 128 note counts, one per velocity from 0 to 127. */
+  velocityHistogram: number[];
+  /** How many events start in each bar, from the first bar to the last one with events. */
+  eventsPerBar: number[];
+  /** Where the last event ends. */
+  lengthTicks: number;
+}
+
+/** Statistics of one track, for track headers. */
+export interface TrackStats {
+  eventCount: number;
+  noteCount: number;
+  /** Where the first event starts, or `null` without events. */
+  startTicks: number | null;
+  /** Where the last event ends, or `null` without events. */
+  endTicks: number | null;
+  pitchRange: PitchRange | null;
+}
+"#;
+
+/// `range` widened to take in `note_number`.
+fn widen(range: Option<PitchRange>, note_number: NoteNumber) -> PitchRange {
+    match range {
+        Some(range) => PitchRange {
+            low: range.low.min(note_number),
+            high: range.high.max(note_number),
+        },
+        None => PitchRange {
+            low: note_number,
+            high: note_number,
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SongSummary {
+    pub track_count: usize,
+    pub note_count: usize,
+    pub duration_ticks: Ticks,
+    pub duration_seconds: f64,
+    pub tempo_range: (f64, f64),
+    pub meters: Vec<(u8, u8)>,
+    pub pitch_range: Option<PitchRange>,
+}
+
+impl SongSummary {
+    pub fn new(song: &Song) -> Self {
+        let mut note_count = 0;
+        let mut duration_ticks = song.end_of_song();
+        let mut pitch_range: Option<PitchRange> = None;
+
+        for event in song.get_events(None) {
+            let end = event.get_ticks() + event.get_duration().unwrap_or(Ticks::new(0));
+            duration_ticks = duration_ticks.max(end);
+
+            if let Event::Note(note) = event {
+                note_count += 1;
+                pitch_range = Some(widen(pitch_range, note.note_number));
+            }
+        }
+
+        let mut meters = Vec::new();
+        for segment in MeterMap::new(song).segments() {
+            let meter = (segment.numerator, segment.denominator);
+            if !meters.contains(&meter) {
+                meters.push(meter);
+            }
+        }
+
+        SongSummary {
+            track_count: song.get_tracks().len(),
+            note_count,
+            duration_ticks,
+            duration_seconds: song.ticks_to_seconds(duration_ticks),
+            tempo_range: (song.bpm, song.bpm),
+            meters,
+            pitch_range,
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_summary = js_sys::Object::new();
+        let set = |object: &js_sys::Object, key: &str, value: &JsValue| {
+            js_sys::Reflect::set(object, &JsValue::from_str(key), value).unwrap();
+        };
+
+        set(
+            &js_summary,
+            "trackCount",
+            &JsValue::from_f64(self.track_count as f64),
+        );
+        set(
+            &js_summary,
+            "noteCount",
+            &JsValue::from_f64(self.note_count as f64),
+        );
+        set(
+            &js_summary,
+            "durationTicks",
+            &JsValue::from_f64(self.duration_ticks.as_f64()),
+        );
+        set(
+            &js_summary,
+            "durationSeconds",
+            &JsValue::from_f64(self.duration_seconds),
+        );
+
+        let tempo_range = js_sys::Object::new();
+        set(&tempo_range, "min", &JsValue::from_f64(self.tempo_range.0));
+        set(&tempo_range, "max", &JsValue::from_f64(self.tempo_range.1));
+        set(&js_summary, "tempoRange", &tempo_range);
+
+        let meters: js_sys::Array = self
+            .meters
+            .iter()
+            .map(|(numerator, denominator)| {
+                let meter = js_sys::Object::new();
+                set(&meter, "numerator", &JsValue::from_f64(*numerator as f64));
+                set(
+                    &meter,
+                    "denominator",
+                    &JsValue::from_f64(*denominator as f64),
+                );
+                meter
+            })
+            .collect();
+        set(&js_summary, "meters", &meters);
+
+        set(&js_summary, "pitchRange", &to_js_value(&self.pitch_range));
+
+        js_summary
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SongStats {
+    pub event_count: usize,
+    pub note_count: usize,
+    pub pitch_range: Option<PitchRange>,
+    pub velocity_histogram: Vec<usize>,
+    pub events_per_bar: Vec<usize>,
+    pub length_ticks: Ticks,
+}
+
+impl SongStats {
+    /// Computes the statistics of the events of `track_ids`, or of every track, in one pass.
+    pub fn new(song: &Song, track_ids: Option<Vec<Id>>) -> Self {
+        let meters = MeterMap::new(song);
+        let mut stats = SongStats {
+            event_count: 0,
+            note_count: 0,
+            pitch_range: None,
+            velocity_histogram: vec![0; 128],
+            events_per_bar: Vec::new(),
+            length_ticks: Ticks::new(0),
+        };
+
+        let filter = track_ids.map(|track_ids| GetEventsFilter {
+            track_ids: Some(track_ids),
+        });
+        for event in song.get_events(filter) {
+            let end = event.get_ticks() + event.get_duration().unwrap_or(Ticks::new(0));
+            stats.event_count += 1;
+            stats.length_ticks = stats.length_ticks.max(end);
+
+            let (bar, _, _) = meters.bar_beat_at(event.get_ticks());
+            let bar = bar as usize;
+            if stats.events_per_bar.len() <= bar {
+                stats.events_per_bar.resize(bar + 1, 0);
+            }
+            stats.events_per_bar[bar] += 1;
+
+            if let Event::Note(note) = event {
+                stats.note_count += 1;
+                stats.pitch_range = Some(widen(stats.pitch_range, note.note_number));
+                stats.velocity_histogram[note.velocity.as_u8() as usize] += 1;
+            }
+        }
+
+        stats
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        to_js_value(self).unchecked_into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackStats {
+    pub event_count: usize,
+    pub note_count: usize,
+    pub start_ticks: Option<Ticks>,
+    pub end_ticks: Option<Ticks>,
+    pub pitch_range: Option<PitchRange>,
+}
+
+impl TrackStats {
+    pub fn new(song: &Song, track: &Track) -> Self {
+        let mut stats = TrackStats {
+            event_count: 0,
+            note_count: 0,
+            start_ticks: None,
+            end_ticks: None,
+            pitch_range: None,
+        };
+
+        for event in song.track_events(track).iter() {
+            let end = event.get_ticks() + event.get_duration().unwrap_or(Ticks::new(0));
+            stats.event_count += 1;
+            stats.start_ticks = Some(
+                stats
+                    .start_ticks
+                    .map_or(event.get_ticks(), |start| start.min(event.get_ticks())),
+            );
+            stats.end_ticks = stats.end_ticks.max(Some(end));
+
+            if let Event::Note(note) = event {
+                stats.note_count += 1;
+                stats.pitch_range = Some(widen(stats.pitch_range, note.note_number));
+            }
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::time_signature::TimeSignature,
+        shared::id::Id,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_song_summary() {
+        let (mut song, track_id) = song_with_track();
+        assert_eq!(SongSummary::new(&song).pitch_range, None);
+
+        add_note(&mut song, track_id, 0, 480, 64, 100);
+        add_note(&mut song, track_id, 1920, 960, 40, 100);
+        add_note(&mut song, track_id, 960, 480, 72, 100);
+        add_control_change(&mut song, track_id, 3840, 7, 100);
+        for (ticks, numerator) in [(1920, 3), (3840, 4)] {
+            song.add_event(Event::TimeSignature(TimeSignature {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                numerator,
+                denominator: 4,
+                track_id,
+            }))
+            .unwrap();
+        }
+
+        let summary = SongSummary::new(&song);
+        assert_eq!(summary.track_count, 1);
+        assert_eq!(summary.note_count, 3);
+        assert_eq!(summary.duration_ticks, Ticks::new(3840));
+        assert_eq!(summary.duration_seconds, 4.0);
+        assert_eq!(summary.tempo_range, (120.0, 120.0));
+        assert_eq!(summary.meters, vec![(4, 4), (3, 4)]);
+        assert_eq!(
+            summary.pitch_range,
+            Some(PitchRange {
+                low: NoteNumber::new(40),
+                high: NoteNumber::new(72),
+            })
+        );
+    }
+
+    #[test]
+    fn test_song_stats() {
+        let (mut song, track_id) = song_with_track();
+        let other_id = Id::new();
+        song.add_track(Track::new(other_id));
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 240, 480, 67, 100);
+        add_note(&mut song, track_id, 3840, 960, 48, 80);
+        add_control_change(&mut song, other_id, 5760, 7, 100);
+
+        let stats = SongStats::new(&song, None);
+        assert_eq!(stats.event_count, 4);
+        assert_eq!(stats.note_count, 3);
+        assert_eq!(stats.velocity_histogram[100], 2);
+        assert_eq!(stats.velocity_histogram[80], 1);
+        assert_eq!(stats.events_per_bar, vec![2, 0, 1, 1]);
+        assert_eq!(stats.length_ticks, Ticks::new(5760));
+
+        let stats = SongStats::new(&song, Some(vec![track_id]));
+        assert_eq!(stats.events_per_bar, vec![2, 0, 1]);
+        assert_eq!(stats.length_ticks, Ticks::new(4800));
+        assert_eq!(
+            stats.pitch_range,
+            Some(PitchRange {
+                low: NoteNumber::new(48),
+                high: NoteNumber::new(67),
+            })
+        );
+    }
+
+    #[test]
+    fn test_track_stats() {
+        let (mut song, track_id) = song_with_track();
+        let stats = TrackStats::new(&song, song.get_track(&track_id).unwrap());
+        assert_eq!(stats.event_count, 0);
+        assert_eq!(stats.start_ticks, None);
+        assert_eq!(stats.pitch_range, None);
+
+        add_control_change(&mut song, track_id, 240, 7, 100);
+        add_note(&mut song, track_id, 480, 960, 64, 100);
+        add_note(&mut song, track_id, 960, 240, 52, 100);
+
+        let stats = TrackStats::new(&song, song.get_track(&track_id).unwrap());
+        assert_eq!(stats.event_count, 3);
+        assert_eq!(stats.note_count, 2);
+        assert_eq!(stats.start_ticks, Some(Ticks::new(240)));
+        assert_eq!(stats.end_ticks, Some(Ticks::new(1440)));
+        assert_eq!(
+            stats.pitch_range,
+            Some(PitchRange {
+                low: NoteNumber::new(52),
+                high: NoteNumber::new(64),
+            })
+        );
+    }
+}
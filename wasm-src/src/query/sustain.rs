@@ -0,0 +1,175 @@
+use crate::{
+    event::{event::Event, note::NoteNumber},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::{Song, TicksRange},
+    track::track::Track,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SUSTAIN_INTERFACES: &'static str = r#"
+export interface EffectiveNoteEnd {
+  id: string;
+  ticks: number;
+  /** Where the note stops sounding, after the sustain pedal. */
+  endTicks: number;
+  /** Whether the pedal held the note past its own end. */
+  sustained: boolean;
+}
+"#;
+
+/// The sustain (damper) pedal controller.
+const SUSTAIN_PEDAL: u8 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveNoteEnd {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub end_ticks: Ticks,
+    pub sustained: bool,
+}
+
+/// Where each note of `track` stops sounding once its sustain pedal (CC64) is taken into
+/// account, by note id.
+///
+/// A note released while the pedal is down, values 64 and up, sounds until the pedal comes
+/// up, or until the same pitch is struck again, whichever comes first. A pedal that never
+/// comes up holds the note until the last event of the track ends.
+pub fn effective_note_ends(song: &Song, track: &Track) -> HashMap<Id, Ticks> {
+    let events: Vec<&Event> = song.track_events(track).iter().collect();
+    let track_end = events
+        .iter()
+        .map(|event| event.get_ticks() + event.get_duration().unwrap_or(Ticks::new(0)))
+        .max()
+        .unwrap_or(Ticks::new(0));
+
+    // (pedal down, pedal up) spans in ticks order.
+    let mut pedal: Vec<(Ticks, Ticks)> = Vec::new();
+    let mut down_since: Option<Ticks> = None;
+    let mut pedal_events: Vec<(Ticks, bool)> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::ControlChange(cc) if cc.controller.as_u8() == SUSTAIN_PEDAL => {
+                Some((cc.ticks, cc.value.as_u8() >= 64))
+            }
+            _ => None,
+        })
+        .collect();
+    pedal_events.sort_by_key(|(ticks, _)| *ticks);
+    for (ticks, down) in pedal_events {
+        match (down, down_since) {
+            (true, None) => down_since = Some(ticks),
+            (false, Some(since)) => {
+                pedal.push((since, ticks));
+                down_since = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(since) = down_since {
+        pedal.push((since, track_end.max(since)));
+    }
+
+    let mut onsets: HashMap<NoteNumber, Vec<Ticks>> = HashMap::new();
+    for event in &events {
+        if let Event::Note(note) = event {
+            onsets.entry(note.note_number).or_default().push(note.ticks);
+        }
+    }
+    for ticks in onsets.values_mut() {
+        ticks.sort();
+    }
+
+    events
+        .iter()
+        .filter_map(|event| event.as_note())
+        .map(|note| {
+            let end = note.ticks + note.duration;
+            let span = pedal.iter().find(|(down, up)| *down <= end && end < *up);
+            let Some((_, up)) = span else {
+                return (note.id, end);
+            };
+            let next_onset = onsets[&note.note_number]
+                .iter()
+                .find(|ticks| **ticks > note.ticks)
+                .copied();
+            let sustained_end = match next_onset {
+                Some(next) if next < *up => next.max(end),
+                _ => *up,
+            };
+            (note.id, sustained_end)
+        })
+        .collect()
+}
+
+/// The effective ends of the notes of `track` in `range`, in ticks order.
+pub fn effective_note_ends_in_range(
+    song: &Song,
+    track: &Track,
+    range: TicksRange,
+) -> Vec<EffectiveNoteEnd> {
+    let ends = effective_note_ends(song, track);
+    let mut notes: Vec<EffectiveNoteEnd> = song
+        .track_events(track)
+        .in_ticks_range(range.start_ticks, range.end_ticks, range.within_duration)
+        .into_iter()
+        .filter_map(|event| event.as_note())
+        .map(|note| {
+            let end_ticks = ends[&note.id];
+            EffectiveNoteEnd {
+                id: note.id,
+                ticks: note.ticks,
+                end_ticks,
+                sustained: end_ticks > note.ticks + note.duration,
+            }
+        })
+        .collect();
+    notes.sort_by_key(|note| (note.ticks, note.id));
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_effective_note_ends() {
+        let (mut song, track_id) = song_with_track();
+        let before = add_note(&mut song, track_id, 0, 240, 60, 100);
+        add_control_change(&mut song, track_id, 480, 64, 127);
+        let held = add_note(&mut song, track_id, 480, 240, 64, 100);
+        let restruck = add_note(&mut song, track_id, 600, 120, 67, 100);
+        add_note(&mut song, track_id, 1200, 240, 67, 100);
+        add_control_change(&mut song, track_id, 1920, 64, 0);
+        let after = add_note(&mut song, track_id, 1920, 240, 72, 100);
+
+        let ends = effective_note_ends(&song, song.get_track(&track_id).unwrap());
+        assert_eq!(ends[&before], Ticks::new(240));
+        assert_eq!(ends[&held], Ticks::new(1920));
+        assert_eq!(ends[&restruck], Ticks::new(1200));
+        assert_eq!(ends[&after], Ticks::new(2160));
+
+        let notes = effective_note_ends_in_range(
+            &song,
+            song.get_track(&track_id).unwrap(),
+            TicksRange {
+                start_ticks: Ticks::new(480),
+                end_ticks: Ticks::new(960),
+                within_duration: false,
+            },
+        );
+        assert_eq!(
+            notes
+                .iter()
+                .map(|note| (note.id, note.sustained))
+                .collect::<Vec<_>>(),
+            vec![(held, true), (restruck, true)]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use super::filter::{query_events, EventFilter};
+use crate::{event::event::Event, song::song::Song};
+use serde::Deserialize;
+use std::cmp::Reverse;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TABLE_INTERFACES: &'static str = r#"
+export type EventTableColumn = "ticks" | "pitch" | "velocity" | "duration";
+
+export interface EventTableOptions {
+  /** Defaults to `"ticks"`. Events without the field, such as control changes by pitch, go last. */
+  sortBy?: EventTableColumn;
+  descending?: boolean;
+  filter?: EventFilter;
+  /** Rows to skip, for paging. Defaults to 0. */
+  offset?: number;
+  /** Most rows to return. Defaults to all. */
+  limit?: number;
+}
+
+export interface EventTable {
+  /** Rows matching the filter, before `offset` and `limit`. */
+  total: number;
+  rows: Event[];
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventTableColumn {
+    #[default]
+    Ticks,
+    Pitch,
+    Velocity,
+    Duration,
+}
+
+impl EventTableColumn {
+    fn value(&self, event: &Event) -> Option<u64> {
+        match self {
+            EventTableColumn::Ticks => Some(event.get_ticks().as_u64()),
+            EventTableColumn::Pitch => event.as_note().map(|note| note.note_number.as_u8() as u64),
+            EventTableColumn::Velocity => event.as_note().map(|note| note.velocity.as_u8() as u64),
+            EventTableColumn::Duration => event.get_duration().map(|ticks| ticks.as_u64()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventTableOptions {
+    pub sort_by: EventTableColumn,
+    pub descending: bool,
+    pub filter: EventFilter,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+pub struct EventTable<'a> {
+    pub total: usize,
+    pub rows: Vec<&'a Event>,
+}
+
+impl EventTable<'_> {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, song: &Song) -> js_sys::Object {
+        let js_table = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_table,
+            &JsValue::from_str("total"),
+            &JsValue::from_f64(self.total as f64),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_table,
+            &JsValue::from_str("rows"),
+            &song.events_to_js_array(self.rows.iter().copied()),
+        )
+        .unwrap();
+
+        js_table
+    }
+}
+
+/// One page of the events matching `options.filter`, sorted by `options.sort_by`. Ties keep
+/// ticks order, then id order, whichever the direction, so pages are stable.
+pub fn event_table<'a>(song: &'a Song, options: &EventTableOptions) -> EventTable<'a> {
+    let mut events = query_events(song, &options.filter);
+    let column = options.sort_by;
+    let tie = |event: &Event| (event.get_ticks(), event.get_id());
+    if options.descending {
+        events.sort_by_key(|event| {
+            let value = column.value(event);
+            (value.is_none(), Reverse(value), tie(event))
+        });
+    } else {
+        events.sort_by_key(|event| {
+            let value = column.value(event);
+            (value.is_none(), value, tie(event))
+        });
+    }
+
+    let total = events.len();
+    let rows = events
+        .into_iter()
+        .skip(options.offset)
+        .take(options.limit.unwrap_or(usize::MAX))
+        .collect();
+    EventTable { total, rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::id::Id,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_event_table() {
+        let (mut song, track_id) = song_with_track();
+        let low = add_note(&mut song, track_id, 0, 480, 48, 100);
+        let high = add_note(&mut song, track_id, 480, 240, 72, 80);
+        let middle = add_note(&mut song, track_id, 960, 480, 60, 90);
+        let cc = add_control_change(&mut song, track_id, 240, 7, 100);
+
+        let ids = |options: &EventTableOptions| -> Vec<Id> {
+            event_table(&song, options)
+                .rows
+                .iter()
+                .map(|event| event.get_id())
+                .collect()
+        };
+
+        let by_pitch = EventTableOptions {
+            sort_by: EventTableColumn::Pitch,
+            descending: true,
+            ..Default::default()
+        };
+        assert_eq!(ids(&by_pitch), vec![high, middle, low, cc]);
+
+        let page = EventTableOptions {
+            sort_by: EventTableColumn::Duration,
+            offset: 1,
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(ids(&page), vec![low, middle]);
+        assert_eq!(event_table(&song, &page).total, 4);
+
+        assert_eq!(ids(&Default::default()), vec![low, cc, high, middle]);
+    }
+}
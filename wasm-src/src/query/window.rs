@@ -0,0 +1,130 @@
+#[cfg(feature = "wasm")]
+use crate::song::song::Song;
+use crate::{event::event::Event, shared::id::Id};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_WINDOW_INTERFACES: &'static str = r#"
+export interface WindowDelta {
+  entered: Event[];
+  /** Events no longer in the window, as they were last returned. */
+  left: Event[];
+  changed: Event[];
+}
+"#;
+
+/// The events a window query returned last time, to diff the next query against.
+#[derive(Debug, Clone, Default)]
+pub struct QueryWindow {
+    events: HashMap<Id, Event>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WindowDelta {
+    pub entered: Vec<Event>,
+    pub left: Vec<Event>,
+    pub changed: Vec<Event>,
+}
+
+impl QueryWindow {
+    /// Replaces the remembered result with `events` and returns how it differs from the last one.
+    pub fn update(&mut self, events: Vec<&Event>) -> WindowDelta {
+        let mut delta = WindowDelta::default();
+        let mut previous = std::mem::take(&mut self.events);
+
+        for event in events {
+            match previous.remove(&event.get_id()) {
+                None => delta.entered.push(event.clone()),
+                Some(before) if &before != event => delta.changed.push(event.clone()),
+                _ => {}
+            }
+            self.events.insert(event.get_id(), event.clone());
+        }
+
+        delta.left = previous.into_values().collect();
+        delta
+            .left
+            .sort_by_key(|event| (event.get_ticks(), event.get_id()));
+
+        delta
+    }
+}
+
+impl WindowDelta {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, song: &Song) -> js_sys::Object {
+        let js_delta = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_delta,
+            &JsValue::from_str("entered"),
+            &song.events_to_js_array(&self.entered),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_delta,
+            &JsValue::from_str("left"),
+            &self
+                .left
+                .iter()
+                .map(|event| event.to_js_object())
+                .collect::<js_sys::Array>(),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_delta,
+            &JsValue::from_str("changed"),
+            &song.events_to_js_array(&self.changed),
+        )
+        .unwrap();
+
+        js_delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_window_delta() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let second = add_note(&mut song, track_id, 960, 480, 62, 100);
+        let third = add_note(&mut song, track_id, 1920, 480, 64, 100);
+
+        let mut window = QueryWindow::default();
+        let delta = window.update(song.get_events_in_ticks_range(
+            Ticks::new(0),
+            Ticks::new(1440),
+            true,
+            None,
+        ));
+        assert_eq!(delta.entered.len(), 2);
+        assert!(delta.left.is_empty() && delta.changed.is_empty());
+
+        let event = song.get_event(&second).unwrap().clone();
+        song.replace_event(event.with_ticks(Ticks::new(1200)))
+            .unwrap();
+
+        let delta = window.update(song.get_events_in_ticks_range(
+            Ticks::new(480),
+            Ticks::new(1920 + 480),
+            true,
+            None,
+        ));
+        let ids = |events: &[Event]| events.iter().map(Event::get_id).collect::<Vec<_>>();
+        assert_eq!(ids(&delta.entered), vec![third]);
+        assert_eq!(ids(&delta.left), vec![first]);
+        assert_eq!(ids(&delta.changed), vec![second]);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    shared::{id::Id, unit::time::Ticks},
+    song::quota::QuotaResource,
+};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_STORE_ERROR: &'static str = r#"
+export type StoreErrorCode =
+  | "songNotSet"
+  | "trackNotFound"
+  | "eventNotFound"
+  | "invalidId"
+  | "validationFailed"
+  | "quotaExceeded";
+
+/** Thrown by `Store` methods; `code` tells the cases apart without parsing the message. */
+export interface StoreError extends Error {
+  name: "StoreError";
+  code: StoreErrorCode;
+}
+"#;
+
+/// A recoverable failure of a store call. It reaches JS as a thrown `Error` named `StoreError`
+/// with a `code` field, leaving the store usable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreError {
+    SongNotSet,
+    TrackNotFound(Id),
+    EventNotFound(Id),
+    /// The string that failed to parse as an id.
+    InvalidId(String),
+    /// An argument was malformed or the song rejected it; the message says which.
+    ValidationFailed(String),
+    /// The edit would take the song past a limit set with `setQuota`.
+    QuotaExceeded {
+        resource: QuotaResource,
+        limit: usize,
+    },
+}
+
+thread_local! {
+    /// The last error raised with `StoreError::into_de_error`.
+    static DECODE_ERROR: RefCell<Option<StoreError>> = const { RefCell::new(None) };
+}
+
+impl StoreError {
+    pub fn invalid(message: impl Into<String>) -> Self {
+        StoreError::ValidationFailed(message.into())
+    }
+
+    /// The deserializer error for `self`. Serde only passes errors on as text, so `self` is
+    /// also kept for `decoding` to return in its place.
+    pub fn into_de_error<E: serde::de::Error>(self) -> E {
+        let error = E::custom(&self);
+        DECODE_ERROR.with(|decode_error| *decode_error.borrow_mut() = Some(self));
+        error
+    }
+
+    /// Runs `decode`, failing with the error a deserializer raised through `into_de_error`,
+    /// such as `invalidId` for an id that did not parse, or else with `validationFailed`.
+    pub fn decoding<T, E: Display>(
+        what: &str,
+        decode: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, StoreError> {
+        DECODE_ERROR.with(|decode_error| decode_error.borrow_mut().take());
+        decode().map_err(|error| {
+            DECODE_ERROR
+                .with(|decode_error| decode_error.borrow_mut().take())
+                .unwrap_or_else(|| StoreError::invalid(format!("{} is not valid: {}", what, error)))
+        })
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            StoreError::SongNotSet => "songNotSet",
+            StoreError::TrackNotFound(_) => "trackNotFound",
+            StoreError::EventNotFound(_) => "eventNotFound",
+            StoreError::InvalidId(_) => "invalidId",
+            StoreError::ValidationFailed(_) => "validationFailed",
+            StoreError::QuotaExceeded { .. } => "quotaExceeded",
+        }
+    }
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::SongNotSet => write!(f, "Song is not set"),
+            StoreError::TrackNotFound(id) => write!(f, "Track {} not found", id),
+            StoreError::EventNotFound(id) => write!(f, "Event {} not found", id),
+            StoreError::InvalidId(id) => write!(f, "Id {:?} is not valid", id),
+            StoreError::ValidationFailed(message) => write!(f, "{}", message),
+            StoreError::QuotaExceeded { resource, limit } => {
+                write!(f, "The song is limited to {} {}", limit, resource.as_str())
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+#[cfg(feature = "wasm")]
+impl From<StoreError> for JsValue {
+    fn from(error: StoreError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("StoreError");
+        js_sys::Reflect::set(
+            &js_error,
+            &JsValue::from_str("code"),
+            &JsValue::from_str(error.code()),
+        )
+        .unwrap();
+        js_error.into()
+    }
+}
+
+/// Parses an id passed in from JS.
+pub fn parse_id(id: &str) -> Result<Id, StoreError> {
+    Id::try_from(id).map_err(|_| StoreError::InvalidId(id.to_string()))
+}
+
+/// Parses a tick position or length passed in from JS.
+pub fn parse_ticks(ticks: f64) -> Result<Ticks, StoreError> {
+    Ticks::from_f64(ticks).ok_or_else(|| {
+        StoreError::invalid(format!(
+            "Ticks {ticks} is not a whole number from 0 to Number.MAX_SAFE_INTEGER"
+        ))
+    })
+}
+
+/// Parses the bounds of a ticks range passed in from JS, which must not end before it starts.
+pub fn parse_ticks_range(start_ticks: f64, end_ticks: f64) -> Result<(Ticks, Ticks), StoreError> {
+    let (start_ticks, end_ticks) = (parse_ticks(start_ticks)?, parse_ticks(end_ticks)?);
+    if end_ticks < start_ticks {
+        return Err(StoreError::invalid(
+            "End ticks must not be before the start ticks",
+        ));
+    }
+    Ok((start_ticks, end_ticks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_error_codes_and_messages() {
+        let id = Id::new();
+        assert_eq!(StoreError::TrackNotFound(id).code(), "trackNotFound");
+        assert_eq!(
+            StoreError::EventNotFound(id).to_string(),
+            format!("Event {} not found", id)
+        );
+        assert_eq!(parse_id(&id.to_string()), Ok(id));
+        assert_eq!(
+            parse_id("nope").unwrap_err().to_string(),
+            "Id \"nope\" is not valid"
+        );
+        assert_eq!(
+            StoreError::invalid("Grid is not valid").code(),
+            "validationFailed"
+        );
+    }
+
+    #[test]
+    fn test_parse_ticks_range() {
+        assert_eq!(
+            parse_ticks_range(0.0, 480.0),
+            Ok((Ticks::new(0), Ticks::new(480)))
+        );
+        assert_eq!(
+            parse_ticks_range(480.0, 480.0),
+            Ok((Ticks::new(480), Ticks::new(480)))
+        );
+        assert_eq!(
+            parse_ticks_range(960.0, 480.0),
+            Err(StoreError::invalid(
+                "End ticks must not be before the start ticks"
+            ))
+        );
+        assert!(parse_ticks_range(-1.0, 480.0).is_err());
+    }
+
+    #[test]
+    fn test_decoding_returns_the_raised_error() {
+        assert_eq!(
+            StoreError::decoding("Id", || serde_json::from_str::<Id>(r#""not-an-id""#)),
+            Err(StoreError::InvalidId("not-an-id".to_string()))
+        );
+        // a message that only reads like an id error stays a validation error
+        assert_eq!(
+            StoreError::decoding("Title", || Err::<(), _>(r#"Id "x" is not valid"#)),
+            Err(StoreError::invalid(
+                r#"Title is not valid: Id "x" is not valid"#
+            ))
+        );
+    }
+}
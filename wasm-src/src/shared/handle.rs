@@ -0,0 +1,60 @@
+use super::id::Id;
+use crate::song::compact::CompactionReport;
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_HANDLE_INTERFACE: &'static str = r#"
+/**
+ * Present on query results. `handle` stands for the event's id in the `...ByHandle` methods,
+ * which skip id parsing, and stays the same until the event is removed.
+ */
+export interface EventHandle {
+  handle?: number;
+}
+"#;
+
+/// Numbers standing for event ids, so hot paths in JS can refer to events without passing
+/// UUID strings across the boundary.
+#[derive(Debug, Clone, Default)]
+pub struct Handles {
+    handles: HashMap<Id, u32>,
+    ids: HashMap<u32, Id>,
+    next_handle: u32,
+}
+
+impl Handles {
+    /// The handle of `id`, assigning one if it has none yet.
+    pub fn assign(&mut self, id: Id) -> u32 {
+        if let Some(handle) = self.handles.get(&id) {
+            return *handle;
+        }
+
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.handles.insert(id, handle);
+        self.ids.insert(handle, id);
+        handle
+    }
+
+    pub fn release(&mut self, id: &Id) {
+        if let Some(handle) = self.handles.remove(id) {
+            self.ids.remove(&handle);
+        }
+    }
+
+    pub fn get(&self, id: &Id) -> Option<u32> {
+        self.handles.get(id).copied()
+    }
+
+    pub fn compact(&mut self, report: &mut CompactionReport) {
+        report.map(&mut self.handles);
+        report.map(&mut self.ids);
+    }
+
+    pub fn resolve(&self, handle: u32) -> Option<Id> {
+        self.ids.get(&handle).copied()
+    }
+}
@@ -1,19 +1,142 @@
+#[cfg(feature = "wasm")]
+use super::error::parse_id;
+use super::{error::StoreError, rng::Rng};
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Formatter},
+};
 use uuid::Uuid;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ID_INTERFACES: &'static str = r#"
+/**
+ * How the store mints ids for new tracks, events and songs, see `StoreOptions`.
+ *
+ * - `"uuid-v4"`: random UUIDs, the default.
+ * - `"sequential"`: `00000000-0000-0000-0000-000000000001`, then `...002` and so on, for
+ *   snapshot tests.
+ * - `{ prefix }`: sequential ids with `prefix` in their first half, so clients given different
+ *   prefixes never mint the same id.
+ * - A function returning each new id, which must be a UUID.
+ */
+export type IdStrategy = "uuid-v4" | "sequential" | { prefix: number } | (() => string);
+"#;
+
+/// How the store mints ids. The store holds one and hands it to every edit that creates tracks,
+/// events or songs.
+#[derive(Debug, Clone, Default)]
+pub enum IdStrategy {
+    #[default]
+    UuidV4,
+    /// `prefix` in the upper 64 bits and a count from 1 in the lower ones.
+    Sequential { prefix: u64, count: u64 },
+    /// Ids from the host, parsed from what the function returns.
+    #[cfg(feature = "wasm")]
+    Callback(js_sys::Function),
+}
+
+impl IdStrategy {
+    /// Reads an `IdStrategy` as JS gives it; undefined is the default.
+    #[cfg(feature = "wasm")]
+    pub fn from_js(value: &JsValue) -> Result<IdStrategy, StoreError> {
+        if value.is_undefined() {
+            return Ok(IdStrategy::default());
+        }
+        if let Some(callback) = value.dyn_ref::<js_sys::Function>() {
+            return Ok(IdStrategy::Callback(callback.clone()));
+        }
+        let sequential = |prefix| IdStrategy::Sequential { prefix, count: 0 };
+        match value.as_string().as_deref() {
+            Some("uuid-v4") => return Ok(IdStrategy::UuidV4),
+            Some("sequential") => return Ok(sequential(0)),
+            _ => {}
+        }
+        js_sys::Reflect::get(value, &JsValue::from_str("prefix"))
+            .ok()
+            .and_then(|prefix| prefix.as_f64())
+            .filter(|prefix| *prefix >= 0.0 && prefix.fract() == 0.0)
+            .map(|prefix| sequential(prefix as u64))
+            .ok_or_else(|| StoreError::invalid("Id strategy is not valid"))
+    }
+
+    /// A new id. Fails with `invalidId` when the host's function returns something other than a
+    /// UUID string, and with `validationFailed` when it throws.
+    pub fn next_id(&mut self) -> Result<Id, StoreError> {
+        #[cfg(feature = "wasm")]
+        if let IdStrategy::Callback(callback) = self {
+            let id = callback
+                .call0(&JsValue::NULL)
+                .map_err(|_| StoreError::invalid("Id strategy threw"))?;
+            return match id.as_string() {
+                Some(id) => parse_id(&id),
+                None => Err(StoreError::InvalidId(
+                    js_sys::JSON::stringify(&id)
+                        .ok()
+                        .and_then(|json| json.as_string())
+                        .unwrap_or_else(|| "undefined".to_string()),
+                )),
+            };
+        }
+        Ok(self.next_own_id())
+    }
+
+    /// A new id for an operation queued for sync. These only let receivers skip redeliveries,
+    /// so they are random rather than asked of the host's function.
+    pub fn next_operation_id(&mut self) -> Id {
+        self.next_own_id()
+    }
+
+    /// A new id the strategy mints itself, random when the host's function would be asked.
+    fn next_own_id(&mut self) -> Id {
+        match self {
+            IdStrategy::Sequential { prefix, count } => {
+                *count += 1;
+                Id(Uuid::from_u128((*prefix as u128) << 64 | *count as u128))
+            }
+            _ => Id(Uuid::new_v4()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Id(Uuid);
 
 impl Id {
-    pub fn new() -> Self {
+    /// A random id, for tests; the store mints ids through its `IdStrategy`.
+    #[cfg(test)]
+    pub(crate) fn new() -> Self {
         Id(Uuid::new_v4())
     }
+
+    /// The id folded into 64 bits, to seed random draws that stay the same for the same id.
+    pub fn seed(self) -> u64 {
+        let value = self.0.as_u128();
+        (value >> 64) as u64 ^ value as u64
+    }
+
+    /// An id made from this one, the same every time, for events made on the fly from a stored
+    /// one rather than stored themselves.
+    pub fn derived(self) -> Id {
+        let mut rng = Rng::new(self.seed());
+        Id(Uuid::from_u128(
+            (rng.next() as u128) << 64 | rng.next() as u128,
+        ))
+    }
+
+    /// A fixed id, for generated songs that must come out the same every time.
+    #[cfg(any(test, feature = "fixtures"))]
+    pub fn from_u128(value: u128) -> Self {
+        Id(Uuid::from_u128(value))
+    }
 }
 
-impl ToString for Id {
-    fn to_string(&self) -> String {
-        self.0.to_string()
+impl Display for Id {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -25,20 +148,16 @@ impl TryFrom<&str> for Id {
     }
 }
 
-impl Clone for Id {
-    fn clone(&self) -> Self {
-        Id(self.0)
-    }
-}
-
-impl Copy for Id {}
-
 impl Serialize for Id {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        self.0.to_string().serialize(serializer)
+        if serializer.is_human_readable() {
+            self.0.to_string().serialize(serializer)
+        } else {
+            self.0.as_bytes().serialize(serializer)
+        }
     }
 }
 
@@ -47,8 +166,13 @@ impl<'de> Deserialize<'de> for Id {
     where
         D: serde::de::Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        Ok(Id::try_from(s.as_str()).unwrap())
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            Id::try_from(s.as_str()).map_err(|_| StoreError::InvalidId(s.clone()).into_de_error())
+        } else {
+            let bytes = <[u8; 16]>::deserialize(deserializer)?;
+            Ok(Id(Uuid::from_bytes(bytes)))
+        }
     }
 }
 
@@ -61,4 +185,37 @@ mod tests {
         let id = Id::new();
         assert!(Uuid::parse_str(&id.to_string()).is_ok());
     }
+
+    #[test]
+    fn test_sequential_ids() {
+        let mut ids = IdStrategy::Sequential {
+            prefix: 2,
+            count: 0,
+        };
+        let mut other_ids = IdStrategy::Sequential {
+            prefix: 3,
+            count: 0,
+        };
+
+        assert_eq!(
+            ids.next_id().unwrap().to_string(),
+            "00000000-0000-0002-0000-000000000001"
+        );
+        assert_eq!(
+            other_ids.next_id().unwrap().to_string(),
+            "00000000-0000-0003-0000-000000000001"
+        );
+        assert_eq!(
+            ids.next_id().unwrap().to_string(),
+            "00000000-0000-0002-0000-000000000002"
+        );
+    }
+
+    #[test]
+    fn test_derived_ids() {
+        let id = Id::new();
+        assert_eq!(id.derived(), id.derived());
+        assert_ne!(id.derived(), id);
+        assert_ne!(id.derived(), Id::new().derived());
+    }
 }
@@ -0,0 +1,83 @@
+#[cfg(feature = "wasm")]
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+/// Serializes `value` into plain JS objects and arrays in one pass, with maps as objects and
+/// `None` as `null`. Much cheaper than building large results key by key with `Reflect::set`.
+#[cfg(feature = "wasm")]
+pub(crate) fn to_js_value<T: Serialize + ?Sized>(value: &T) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap()
+}
+
+/// Declares an enum that JS passes as a string union. The enum parses from those strings with
+/// `FromStr`, failing with `validationFailed` on any other string, and `as_str` gives them back.
+/// Under the `wasm` feature the union is added to the TypeScript definitions as well.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        impl $name {
+            /// The string JS passes for the value.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $value,)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::shared::error::StoreError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    $($value => Ok($name::$variant),)*
+                    _ => Err($crate::shared::error::StoreError::invalid(format!(
+                        concat!("Unknown ", stringify!($name), ": {}"),
+                        value
+                    ))),
+                }
+            }
+        }
+
+        #[cfg(feature = "wasm")]
+        const _: () = {
+            #[wasm_bindgen::prelude::wasm_bindgen(typescript_custom_section)]
+            const TS_STRING_ENUM: &'static str = concat!(
+                "export type ",
+                stringify!($name),
+                " =",
+                $(" | \"", $value, "\"",)*
+                ";"
+            );
+        };
+    };
+}
+
+pub(crate) use string_enum;
+
+#[cfg(test)]
+mod tests {
+    use crate::{event::event::EventKind, shared::error::StoreError};
+
+    #[test]
+    fn test_string_enum_round_trip() {
+        for kind in EventKind::ALL {
+            assert_eq!(kind.as_str().parse::<EventKind>(), Ok(kind));
+        }
+        assert!(matches!(
+            "note".parse::<EventKind>(),
+            Err(StoreError::ValidationFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(feature = "wasm")]
+use super::error::StoreError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_META_INTERFACES: &'static str = r#"
+/**
+ * The app's own data on an event or track, such as tags or ids in another system. The store
+ * keeps it with the event or track and saves it with the song, but never reads it.
+ */
+export type Meta = Record<string, unknown>;
+"#;
+
+/// Custom data the app attaches to an event or track.
+///
+/// Like `SongSettings`, values are kept as JSON text: human-readable formats write them as
+/// plain JSON values and binary formats write the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Meta(BTreeMap<String, String>);
+
+impl Meta {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The JSON text of the value under `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Whether the value under `key` is the JSON value `json`, whatever the key order of the
+    /// objects in it.
+    pub fn matches(&self, key: &str, json: &serde_json::Value) -> bool {
+        self.get(key)
+            .and_then(|value| serde_json::from_str::<serde_json::Value>(value).ok())
+            .is_some_and(|value| value == *json)
+    }
+
+    /// Reads a `meta` property: `None` when undefined, and no data when null.
+    #[cfg(feature = "wasm")]
+    pub fn from_js(value: &JsValue) -> Result<Option<Meta>, StoreError> {
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        if value.is_null() {
+            return Ok(Some(Meta::default()));
+        }
+        if !value.is_object() || js_sys::Array::is_array(value) {
+            return Err(StoreError::invalid("Meta is not valid"));
+        }
+
+        let mut meta = Meta::default();
+        for entry in js_sys::Object::entries(value.unchecked_ref()).iter() {
+            let entry: js_sys::Array = entry.unchecked_into();
+            let key = entry.get(0).as_string().unwrap();
+            let json = js_sys::JSON::stringify(&entry.get(1))
+                .ok()
+                .and_then(|json| json.as_string())
+                .ok_or_else(|| StoreError::invalid(format!("Meta value {} is not valid", key)))?;
+            meta.0.insert(key, json);
+        }
+        Ok(Some(meta))
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_meta = js_sys::Object::new();
+        for (key, json) in &self.0 {
+            js_sys::Reflect::set(
+                &js_meta,
+                &JsValue::from_str(key),
+                &js_sys::JSON::parse(json).unwrap(),
+            )
+            .unwrap();
+        }
+        js_meta
+    }
+}
+
+impl Serialize for Meta {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return self.0.serialize(serializer);
+        }
+
+        let values = self
+            .0
+            .iter()
+            .map(|(key, json)| Ok((key, serde_json::from_str(json)?)))
+            .collect::<Result<BTreeMap<&String, serde_json::Value>, serde_json::Error>>()
+            .map_err(serde::ser::Error::custom)?;
+        values.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Meta {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return BTreeMap::deserialize(deserializer).map(Meta);
+        }
+
+        let values = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        Ok(Meta(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, value.to_string()))
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+impl<const N: usize> From<[(&str, serde_json::Value); N]> for Meta {
+    fn from(values: [(&str, serde_json::Value); N]) -> Self {
+        Meta(
+            values
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_meta_matches_json_values() {
+        let meta = Meta::from([
+            ("layer", json!({"id": 3, "name": "pads"})),
+            ("tag", json!("a")),
+        ]);
+        assert!(meta.matches("layer", &json!({"name": "pads", "id": 3})));
+        assert!(meta.matches("tag", &json!("a")));
+        assert!(!meta.matches("tag", &json!("b")));
+        assert!(!meta.matches("layer", &json!(3)));
+        assert!(!meta.matches("other", &json!("a")));
+    }
+
+    #[test]
+    fn test_meta_round_trip() {
+        let meta = Meta::from([("layer", json!({"id": 3})), ("muted", json!(true))]);
+
+        let json = serde_json::to_string(&meta).unwrap();
+        assert_eq!(json, r#"{"layer":{"id":3},"muted":true}"#);
+        assert_eq!(serde_json::from_str::<Meta>(&json).unwrap(), meta);
+
+        let bytes = postcard::to_allocvec(&meta).unwrap();
+        assert_eq!(postcard::from_bytes::<Meta>(&bytes).unwrap(), meta);
+    }
+}
@@ -1,2 +1,7 @@
+pub mod error;
+pub mod handle;
 pub mod id;
+pub(crate) mod js;
+pub mod meta;
+pub mod rng;
 pub mod unit;
@@ -0,0 +1,324 @@
+use super::time::Ticks;
+use crate::{shared::error::StoreError, song::meter::MeterMap};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_GRID_INTERFACES: &'static str = r#"
+export interface Tuplet {
+  actual: number;
+  normal: number;
+}
+
+export interface Grid {
+  division: number;
+  tuplet?: Tuplet;
+}
+"#;
+
+/// `actual` notes played in the time of `normal` notes, e.g. 3:2 for triplets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tuplet {
+    pub actual: u32,
+    pub normal: u32,
+}
+
+/// A note-value grid such as 1/16 or 1/8 triplets.
+///
+/// `division` is the note value denominator (4 = quarter notes, 16 = sixteenths).
+/// Grid lines start over at every meter change, so each meter begins on a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "GridFields")]
+pub struct Grid {
+    pub division: u32,
+    pub tuplet: Option<Tuplet>,
+}
+
+/// A `Grid` as JS passes it, before it is checked.
+#[derive(Deserialize)]
+struct GridFields {
+    division: u32,
+    tuplet: Option<Tuplet>,
+}
+
+impl TryFrom<GridFields> for Grid {
+    type Error = String;
+
+    fn try_from(fields: GridFields) -> Result<Self, Self::Error> {
+        if fields.division == 0 {
+            return Err("Grid division must be at least 1".to_string());
+        }
+        if fields
+            .tuplet
+            .is_some_and(|tuplet| tuplet.actual == 0 || tuplet.normal == 0)
+        {
+            return Err("Tuplet notes must be at least 1".to_string());
+        }
+        Ok(Grid {
+            division: fields.division,
+            tuplet: fields.tuplet,
+        })
+    }
+}
+
+/// The error for a grid too fine to count its lines up to the ticks asked for.
+pub fn too_fine_error() -> StoreError {
+    StoreError::invalid("Grid is too fine for these ticks")
+}
+
+impl Grid {
+    /// Step length as an exact fraction of ticks, so tuplet grids don't drift. `None` for an
+    /// empty step or one too long to count in ticks.
+    fn step_fraction(&self, ppq: u32) -> Option<(u64, u64)> {
+        let (actual, normal) = self
+            .tuplet
+            .map(|tuplet| (tuplet.actual as u64, tuplet.normal as u64))
+            .unwrap_or((1, 1));
+
+        let numerator = (ppq as u64 * 4).checked_mul(normal)?;
+        let denominator = self.division as u64 * actual;
+        (numerator > 0 && denominator > 0).then_some((numerator, denominator))
+    }
+
+    /// Ticks of the `index`-th grid line from `origin`, rounded to the nearest tick. `None` past
+    /// the last tick.
+    pub fn line_at(&self, ppq: u32, origin: Ticks, index: u64) -> Option<Ticks> {
+        let (numerator, denominator) = self.step_fraction(ppq)?;
+        let offset = index.checked_mul(numerator)?.checked_add(denominator / 2)? / denominator;
+        (offset <= Ticks::MAX.as_u64())
+            .then(|| Ticks::new(offset))
+            .and_then(|offset| origin.checked_add(offset))
+    }
+
+    fn index_before(&self, ppq: u32, origin: Ticks, ticks: Ticks) -> Option<u64> {
+        let (numerator, denominator) = self.step_fraction(ppq)?;
+        Some((ticks - origin).as_u64().checked_mul(denominator)? / numerator)
+    }
+
+    /// Index of the grid line from `origin` closest to `ticks`; ties snap forward. `None` when
+    /// the grid is too fine to count up to `ticks`.
+    pub fn nearest_index(&self, ppq: u32, origin: Ticks, ticks: Ticks) -> Option<u64> {
+        let index = self.index_before(ppq, origin, ticks)?;
+        let before = self.line_at(ppq, origin, index)?;
+        match self.line_at(ppq, origin, index + 1) {
+            Some(after) if ticks - before >= after - ticks => Some(index + 1),
+            _ => Some(index),
+        }
+    }
+
+    /// The grid line from `origin` closest to `ticks`; ties snap forward.
+    pub fn snap_from(&self, ppq: u32, origin: Ticks, ticks: Ticks) -> Option<Ticks> {
+        self.line_at(ppq, origin, self.nearest_index(ppq, origin, ticks)?)
+    }
+
+    /// The grid line closest to `ticks` within its meter, or the start of the next meter when
+    /// that is as close; ties snap forward. `None` when the grid is too fine to count up to
+    /// `ticks`.
+    pub fn snap(&self, meters: &MeterMap, ticks: Ticks) -> Option<Ticks> {
+        let index = meters.segment_index_at(ticks);
+        let line = self.snap_from(meters.ppq(), meters.segments()[index].ticks, ticks)?;
+        Some(match meters.segment_end(index) {
+            Some(next)
+                if line >= next
+                    || next.as_u64() - ticks.as_u64() <= ticks.as_u64().abs_diff(line.as_u64()) =>
+            {
+                next
+            }
+            _ => line,
+        })
+    }
+
+    /// Grid lines in `[start_ticks, end_ticks)`, starting over at every meter change. `None`
+    /// when the grid is too fine to count up to `end_ticks`.
+    pub fn lines_in_range(
+        &self,
+        meters: &MeterMap,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    ) -> Option<Vec<Ticks>> {
+        let ppq = meters.ppq();
+        let mut lines = Vec::new();
+
+        for (index, segment) in meters.segments().iter().enumerate() {
+            let from = start_ticks.max(segment.ticks);
+            let until = meters
+                .segment_end(index)
+                .map_or(end_ticks, |next| next.min(end_ticks));
+            if from >= until {
+                continue;
+            }
+
+            let mut line_index = self.index_before(ppq, segment.ticks, from)?;
+            // past the last tick, which is past `until` too
+            while let Some(line) = self.line_at(ppq, segment.ticks, line_index) {
+                if line >= until {
+                    break;
+                }
+                // lines closer than a tick round onto the same tick
+                if line >= from && lines.last() != Some(&line) {
+                    lines.push(line);
+                }
+                // so skip to the first line that could round onto the next tick
+                let next = self.index_before(ppq, segment.ticks, line + Ticks::new(1))?;
+                line_index = next.max(line_index + 1);
+            }
+        }
+
+        Some(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{event::Event, time_signature::TimeSignature},
+        shared::id::Id,
+        song::song::Song,
+        transform::test_helpers::song_with_track,
+    };
+
+    const TRIPLET: Option<Tuplet> = Some(Tuplet {
+        actual: 3,
+        normal: 2,
+    });
+
+    fn meters(ppq: u32) -> MeterMap {
+        MeterMap::new(&Song::new("test".to_string(), ppq))
+    }
+
+    #[test]
+    fn test_straight_grid() {
+        let grid = Grid {
+            division: 16,
+            tuplet: None,
+        };
+
+        let lines = grid.lines_in_range(&meters(480), Ticks::new(100), Ticks::new(480));
+        assert_eq!(
+            lines,
+            Some(vec![Ticks::new(120), Ticks::new(240), Ticks::new(360)])
+        );
+    }
+
+    #[test]
+    fn test_tuplet_grid() {
+        let origin = Ticks::new(0);
+        let triplets = Grid {
+            division: 8,
+            tuplet: TRIPLET,
+        };
+        let lines = triplets.lines_in_range(&meters(480), Ticks::new(0), Ticks::new(480));
+        assert_eq!(
+            lines,
+            Some(vec![Ticks::new(0), Ticks::new(160), Ticks::new(320)])
+        );
+
+        let quintuplets = Grid {
+            division: 16,
+            tuplet: Some(Tuplet {
+                actual: 5,
+                normal: 4,
+            }),
+        };
+        assert_eq!(quintuplets.line_at(480, origin, 1), Some(Ticks::new(96)));
+        assert_eq!(quintuplets.line_at(480, origin, 5), Some(Ticks::new(480)));
+
+        // 1/16 triplets at 100 ppq don't land on whole ticks, but every sixth line must
+        // still fall exactly on a beat.
+        let uneven = Grid {
+            division: 16,
+            tuplet: TRIPLET,
+        };
+        assert_eq!(uneven.line_at(100, origin, 1), Some(Ticks::new(17)));
+        assert_eq!(uneven.line_at(100, origin, 6), Some(Ticks::new(100)));
+        assert_eq!(uneven.line_at(100, origin, 600), Some(Ticks::new(10000)));
+    }
+
+    #[test]
+    fn test_snap() {
+        let triplets = Grid {
+            division: 8,
+            tuplet: TRIPLET,
+        };
+        let meters = meters(480);
+        assert_eq!(
+            triplets.snap(&meters, Ticks::new(150)),
+            Some(Ticks::new(160))
+        );
+        assert_eq!(triplets.snap(&meters, Ticks::new(70)), Some(Ticks::new(0)));
+        assert_eq!(
+            triplets.snap(&meters, Ticks::new(80)),
+            Some(Ticks::new(160))
+        );
+    }
+
+    #[test]
+    fn test_lines_restart_at_meter_changes() {
+        let (mut song, track_id) = song_with_track();
+        // a bar of 7/8, then 4/4
+        for (ticks, numerator, denominator) in [(0, 7, 8), (1680, 4, 4)] {
+            song.add_event(Event::TimeSignature(TimeSignature {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                numerator,
+                denominator,
+                track_id,
+            }))
+            .unwrap();
+        }
+        let meters = MeterMap::new(&song);
+        let halves = Grid {
+            division: 2,
+            tuplet: None,
+        };
+
+        let lines = halves.lines_in_range(&meters, Ticks::new(0), Ticks::new(3600));
+        assert_eq!(
+            lines,
+            Some(vec![
+                Ticks::new(0),
+                Ticks::new(960),
+                Ticks::new(1680),
+                Ticks::new(2640),
+            ])
+        );
+        assert_eq!(
+            halves.snap(&meters, Ticks::new(1400)),
+            Some(Ticks::new(1680))
+        );
+        assert_eq!(
+            halves.snap(&meters, Ticks::new(1200)),
+            Some(Ticks::new(960))
+        );
+        assert_eq!(
+            halves.snap(&meters, Ticks::new(2000)),
+            Some(Ticks::new(1680))
+        );
+    }
+
+    #[test]
+    fn test_grid_rejects_empty_steps() {
+        let parse = |json| serde_json::from_str::<Grid>(json);
+        assert!(parse(r#"{"division":16}"#).is_ok());
+        assert!(parse(r#"{"division":0}"#).is_err());
+        assert!(parse(r#"{"division":8,"tuplet":{"actual":3,"normal":0}}"#).is_err());
+        assert!(parse(r#"{"division":8,"tuplet":{"actual":0,"normal":2}}"#).is_err());
+    }
+
+    #[test]
+    fn test_grid_overflow() {
+        let fine = Grid {
+            division: u32::MAX,
+            tuplet: None,
+        };
+        assert_eq!(fine.line_at(480, Ticks::new(0), u64::MAX), None);
+        assert_eq!(fine.snap(&meters(480), Ticks::MAX), None);
+        assert_eq!(
+            fine.lines_in_range(&meters(480), Ticks::MAX - Ticks::new(1), Ticks::MAX),
+            None
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use super::time::Ticks;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LENGTH_INTERFACES: &'static str = r#"
+/** A note length for length menus, named like "1/8", "1/8." (dotted) or "1/8T" (triplet). */
+export interface LengthPreset {
+  name: string;
+  ticks: number;
+}
+"#;
+
+/// Divisions offered as presets, from whole notes down.
+const PRESET_DIVISIONS: [u32; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthModifier {
+    Straight,
+    Dotted,
+    Triplet,
+}
+
+/// A note value such as an eighth, a dotted quarter or a sixteenth triplet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteLength {
+    /// The note value denominator (4 = quarter notes, 16 = sixteenths).
+    pub division: u32,
+    pub modifier: LengthModifier,
+}
+
+impl NoteLength {
+    /// Parses names like "1/4", "1/4." and "1/8T".
+    pub fn parse(name: &str) -> Option<Self> {
+        let (name, modifier) = if let Some(name) = name.strip_suffix('.') {
+            (name, LengthModifier::Dotted)
+        } else if let Some(name) = name.strip_suffix(['T', 't']) {
+            (name, LengthModifier::Triplet)
+        } else {
+            (name, LengthModifier::Straight)
+        };
+        let division: u32 = name.strip_prefix("1/")?.parse().ok()?;
+        (division > 0).then_some(NoteLength { division, modifier })
+    }
+
+    pub fn name(&self) -> String {
+        let suffix = match self.modifier {
+            LengthModifier::Straight => "",
+            LengthModifier::Dotted => ".",
+            LengthModifier::Triplet => "T",
+        };
+        format!("1/{}{}", self.division, suffix)
+    }
+
+    /// The length at `ppq`, rounded to the nearest tick.
+    pub fn ticks(&self, ppq: u32) -> Ticks {
+        let (numerator, denominator) = match self.modifier {
+            LengthModifier::Straight => (1, 1),
+            LengthModifier::Dotted => (3, 2),
+            LengthModifier::Triplet => (2, 3),
+        };
+        let numerator = ppq as u64 * 4 * numerator;
+        let denominator = self.division as u64 * denominator;
+        Ticks::new((numerator + denominator / 2) / denominator)
+    }
+
+    /// The length as a `LengthPreset`.
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(self, ppq: u32) -> js_sys::Object {
+        let js_preset = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_preset,
+            &JsValue::from_str("name"),
+            &JsValue::from_str(&self.name()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_preset,
+            &JsValue::from_str("ticks"),
+            &JsValue::from_f64(self.ticks(ppq).as_f64()),
+        )
+        .unwrap();
+
+        js_preset
+    }
+}
+
+/// Straight, dotted and triplet lengths from whole notes down, leaving out those shorter than a
+/// tick at `ppq`.
+pub fn length_presets(ppq: u32) -> Vec<NoteLength> {
+    PRESET_DIVISIONS
+        .iter()
+        .flat_map(|division| {
+            [
+                LengthModifier::Straight,
+                LengthModifier::Dotted,
+                LengthModifier::Triplet,
+            ]
+            .map(|modifier| NoteLength {
+                division: *division,
+                modifier,
+            })
+        })
+        .filter(|length| length.ticks(ppq).as_u64() > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_length() {
+        let ticks = |name: &str| NoteLength::parse(name).unwrap().ticks(480).as_u64();
+        assert_eq!(ticks("1/4"), 480);
+        assert_eq!(ticks("1/4."), 720);
+        assert_eq!(ticks("1/8T"), 160);
+        assert_eq!(ticks("1/1"), 1920);
+        assert_eq!(NoteLength::parse("1/0"), None);
+        assert_eq!(NoteLength::parse("quarter"), None);
+
+        let presets = length_presets(24);
+        assert_eq!(presets.first().unwrap().name(), "1/1");
+        assert_eq!(presets.last().unwrap().name(), "1/64T");
+        assert!(presets.iter().all(|length| length.ticks(24).as_u64() > 0));
+        assert_eq!(length_presets(480).len(), 21);
+    }
+}
@@ -1 +1,3 @@
+pub mod grid;
+pub mod length;
 pub mod time;
@@ -1,24 +1,73 @@
 use serde::{Deserialize, Serialize};
 use std::ops::{Add, Sub};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Ticks(u32);
+/// Largest integer a JS number holds exactly, `Number.MAX_SAFE_INTEGER`.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// A position or length in ticks.
+///
+/// Ticks count up to `Number.MAX_SAFE_INTEGER`, so they cross the JS boundary as plain numbers.
+/// `+` and `-` saturate at both ends instead of wrapping or panicking; use `checked_add` where
+/// running past the last tick should be noticed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u64")]
+pub struct Ticks(u64);
 
 impl Ticks {
-    pub fn new(ticks: u32) -> Self {
-        Ticks(ticks)
+    pub const MAX: Ticks = Ticks(MAX_SAFE_INTEGER);
+
+    pub fn new(ticks: u64) -> Self {
+        Ticks(ticks.min(MAX_SAFE_INTEGER))
+    }
+
+    /// Ticks from a JS number, or `None` if it is negative, fractional or too large.
+    pub fn from_f64(ticks: f64) -> Option<Self> {
+        (ticks >= 0.0 && ticks.fract() == 0.0 && ticks <= MAX_SAFE_INTEGER as f64)
+            .then_some(Ticks(ticks as u64))
     }
 
-    pub fn as_u32(&self) -> u32 {
+    pub fn as_u64(&self) -> u64 {
         self.0
     }
+
+    pub fn as_f64(&self) -> f64 {
+        self.0 as f64
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0
+            .checked_add(other.0)
+            .filter(|ticks| *ticks <= MAX_SAFE_INTEGER)
+            .map(Ticks)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Ticks::new(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Ticks(self.0.saturating_sub(other.0))
+    }
+}
+
+/// Deserialized ticks past `Ticks::MAX` are rejected rather than clamped, since a song holding
+/// them was not written by the store.
+impl TryFrom<u64> for Ticks {
+    type Error = String;
+
+    fn try_from(ticks: u64) -> Result<Self, Self::Error> {
+        if ticks > MAX_SAFE_INTEGER {
+            return Err(format!("Ticks {ticks} are past Number.MAX_SAFE_INTEGER"));
+        }
+        Ok(Ticks(ticks))
+    }
 }
 
 impl Add for Ticks {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        Ticks(self.0 + other.0)
+        self.saturating_add(other)
     }
 }
 
@@ -26,6 +75,39 @@ impl Sub for Ticks {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
-        Ticks(self.0 - other.0)
+        self.saturating_sub(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_arithmetic_stays_in_range() {
+        let early = Ticks::new(480);
+        let late = Ticks::new(960);
+        assert_eq!(early - late, Ticks::new(0));
+        assert_eq!(late - early, early);
+
+        assert_eq!(Ticks::MAX + late, Ticks::MAX);
+        assert_eq!(Ticks::MAX.checked_add(late), None);
+        assert_eq!(Ticks::new(u64::MAX), Ticks::MAX);
+        let past_u32 = Ticks::new(u32::MAX as u64) + late;
+        assert_eq!(past_u32.as_u64(), u32::MAX as u64 + 960);
+
+        assert_eq!(Ticks::from_f64(960.0), Some(late));
+        assert_eq!(Ticks::from_f64(-1.0), None);
+        assert_eq!(Ticks::from_f64(0.5), None);
+        assert_eq!(Ticks::from_f64(f64::NAN), None);
+        assert_eq!(Ticks::from_f64(9.1e15), None);
+    }
+
+    #[test]
+    fn test_ticks_deserialize_up_to_max() {
+        let max = MAX_SAFE_INTEGER.to_string();
+        assert_eq!(serde_json::from_str::<Ticks>(&max).unwrap(), Ticks::MAX);
+        assert!(serde_json::from_str::<Ticks>(&(MAX_SAFE_INTEGER + 1).to_string()).is_err());
+        assert!(serde_json::from_str::<Ticks>("-1").is_err());
     }
 }
@@ -0,0 +1,97 @@
+use crate::shared::unit::time::Ticks;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hash,
+    mem::size_of,
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_COMPACT_INTERFACES: &'static str = r#"
+/** What `Store.compact` freed. */
+export interface CompactionReport {
+  /** Tick index entries left without events by removals and moves. */
+  emptyBuckets: number;
+  /**
+   * Roughly how many bytes the freed entries and spare capacity took. The wasm memory itself
+   * does not shrink, but later edits reuse the space.
+   */
+  bytesReclaimed: number;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    pub empty_buckets: usize,
+    pub bytes_reclaimed: usize,
+}
+
+impl CompactionReport {
+    /// Drops the empty buckets of a tick index and shrinks the others.
+    pub fn index<T>(&mut self, index: &mut BTreeMap<Ticks, Vec<T>>) {
+        let before = index.len();
+        index.retain(|_, bucket| {
+            if bucket.is_empty() {
+                self.bytes_reclaimed += bucket.capacity() * size_of::<T>();
+            }
+            !bucket.is_empty()
+        });
+        let removed = before - index.len();
+        self.empty_buckets += removed;
+        self.bytes_reclaimed += removed * (size_of::<Ticks>() + size_of::<Vec<T>>());
+        for bucket in index.values_mut() {
+            self.vec(bucket);
+        }
+    }
+
+    pub fn vec<T>(&mut self, vec: &mut Vec<T>) {
+        let capacity = vec.capacity();
+        vec.shrink_to_fit();
+        self.bytes_reclaimed += (capacity - vec.capacity()) * size_of::<T>();
+    }
+
+    pub fn set<T: Eq + Hash>(&mut self, set: &mut HashSet<T>) {
+        let capacity = set.capacity();
+        set.shrink_to_fit();
+        self.bytes_reclaimed += (capacity - set.capacity()) * size_of::<T>();
+    }
+
+    pub fn map<K: Eq + Hash, V>(&mut self, map: &mut HashMap<K, V>) {
+        let capacity = map.capacity();
+        map.shrink_to_fit();
+        self.bytes_reclaimed += (capacity - map.capacity()) * (size_of::<K>() + size_of::<V>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_compact() {
+        let (mut song, track_id) = song_with_track();
+        let note_ids: Vec<_> = (0..100)
+            .map(|index| add_note(&mut song, track_id, index * 120, 60, 60, 100))
+            .collect();
+        for note_id in &note_ids[1..] {
+            song.remove_event(note_id);
+        }
+
+        let report = song.compact();
+        // a tick, an end tick and a duration class bucket per removed note, in the song's index
+        // and in its track's
+        assert_eq!(report.empty_buckets, 99 * 3 * 2);
+        assert!(report.bytes_reclaimed > 0);
+        assert!(song.get_event(&note_ids[0]).is_some());
+        let in_range =
+            song.get_events_in_ticks_range(Ticks::new(0), Ticks::new(12_000), true, None);
+        assert_eq!(in_range.len(), 1);
+
+        assert_eq!(song.compact().empty_buckets, 0);
+    }
+}
@@ -0,0 +1,111 @@
+use super::{meter::MeterMap, song::Song};
+use crate::shared::unit::time::Ticks;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_END_OF_SONG_INTERFACES: &'static str = r#"
+/** `"auto"` ends the song where its last event ends; `"bar"` rounds that up to a bar line. */
+export type EndOfSongMode = "auto" | "bar";
+
+export interface EndOfSongPolicy {
+  mode: EndOfSongMode;
+  /** The song lasts at least this long, however early its events end. */
+  minTicks?: number | null;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EndOfSongMode {
+    #[default]
+    Auto,
+    Bar,
+}
+
+/// How the end of the song follows its events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndOfSongPolicy {
+    pub mode: EndOfSongMode,
+    #[serde(default)]
+    pub min_ticks: Option<Ticks>,
+}
+
+impl EndOfSongPolicy {
+    /// The end of `song` whose last event ends at `last_end`.
+    pub fn resolve(&self, song: &Song, last_end: Ticks) -> Ticks {
+        let end = match self.mode {
+            EndOfSongMode::Auto => last_end,
+            EndOfSongMode::Bar => next_bar_line(&MeterMap::new(song), last_end),
+        };
+        end.max(self.min_ticks.unwrap_or(Ticks::new(0)))
+    }
+}
+
+/// The first bar line at or after `ticks`.
+fn next_bar_line(meters: &MeterMap, ticks: Ticks) -> Ticks {
+    let index = meters
+        .segments()
+        .partition_point(|segment| segment.ticks <= ticks)
+        - 1;
+    let segment = &meters.segments()[index];
+    let bar_ticks = segment.bar_ticks(meters.ppq());
+    let elapsed = ticks.as_u64() - segment.ticks.as_u64();
+    let bar_line = Ticks::new(segment.ticks.as_u64() + elapsed.div_ceil(bar_ticks) * bar_ticks);
+
+    // a time signature mid-bar starts a new bar
+    match meters.segment_end(index) {
+        Some(next) => bar_line.min(next),
+        None => bar_line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{event::Event, time_signature::TimeSignature},
+        midi::export::{export_midi, MidiExportOptions},
+        shared::id::Id,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_end_of_song_policy() {
+        let (mut song, track_id) = song_with_track();
+        assert_eq!(song.end_of_song(), Ticks::new(0));
+        add_note(&mut song, track_id, 240, 260, 60, 100);
+        assert_eq!(song.end_of_song(), Ticks::new(500));
+
+        song.end_of_song_policy = EndOfSongPolicy {
+            mode: EndOfSongMode::Bar,
+            min_ticks: None,
+        };
+        assert_eq!(song.end_of_song(), Ticks::new(1920));
+
+        // 3/4 from the second bar
+        song.add_event(Event::TimeSignature(TimeSignature {
+            id: Id::new(),
+            ticks: Ticks::new(1920),
+            numerator: 3,
+            denominator: 4,
+            track_id,
+        }))
+        .unwrap();
+        add_note(&mut song, track_id, 1920, 480, 60, 100);
+        assert_eq!(song.end_of_song(), Ticks::new(3360));
+
+        song.end_of_song_policy.min_ticks = Some(Ticks::new(7680));
+        assert_eq!(song.end_of_song(), Ticks::new(7680));
+
+        let bytes = export_midi(&song, &MidiExportOptions::default());
+        let smf = midly::Smf::parse(&bytes).unwrap();
+        for track in &smf.tracks {
+            let length: u32 = track.iter().map(|event| event.delta.as_int()).sum();
+            assert_eq!(length, 7680);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, Velocity},
+    },
+    shared::{id::Id, unit::time::Ticks},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_GROOVE_INTERFACES: &'static str = r#"
+/** Offsets for the events nearest to one step of a groove. */
+export interface GrooveStep {
+  /** Ticks added to the event's position; negative values play early. */
+  timing?: number;
+  /** Added to note velocities. */
+  velocity?: number;
+}
+
+/** A named timing and velocity template, stored with the song and shared by its tracks. */
+export interface Groove {
+  id: string;
+  name: string;
+  /** Length of one step; the steps repeat from tick 0. */
+  stepTicks: number;
+  steps: GrooveStep[];
+}
+
+/** A track's reference to a groove of the song's groove pool. */
+export interface TrackGroove {
+  grooveId: string;
+  /** How much of the groove's offsets to apply, from 0 to 1. Defaults to 1. */
+  amount?: number;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrooveStep {
+    pub timing: i32,
+    pub velocity: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Groove {
+    pub id: Id,
+    pub name: String,
+    pub step_ticks: Ticks,
+    pub steps: Vec<GrooveStep>,
+}
+
+impl Groove {
+    /// Returns `event` moved and, for notes, re-voiced by `amount` of the offsets of the step
+    /// nearest to it. Positions before the song start clamp to tick 0.
+    pub fn apply(&self, event: Event, amount: f64) -> Event {
+        let step_ticks = self.step_ticks.as_u64();
+        if step_ticks == 0 || self.steps.is_empty() {
+            return event;
+        }
+
+        let ticks = event.get_ticks().as_u64();
+        let index = ((ticks + step_ticks / 2) / step_ticks) as usize % self.steps.len();
+        let step = self.steps[index];
+
+        let timing = (step.timing as f64 * amount).round() as i64;
+        let ticks = (ticks as i64 + timing).clamp(0, Ticks::MAX.as_u64() as i64);
+
+        match event.with_ticks(Ticks::new(ticks as u64)) {
+            Event::Note(note) => {
+                let offset = (step.velocity as f64 * amount).round() as i32;
+                let velocity = (note.velocity.as_u8() as i32 + offset).clamp(1, 127);
+                Event::Note(Note {
+                    velocity: Velocity::new(velocity as u8),
+                    ..note
+                })
+            }
+            event => event,
+        }
+    }
+}
+
+/// The song's groove templates, in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GroovePool(Vec<Groove>);
+
+impl GroovePool {
+    pub fn get(&self, groove_id: &Id) -> Option<&Groove> {
+        self.0.iter().find(|groove| groove.id == *groove_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Groove> {
+        self.0.iter()
+    }
+
+    /// Adds `groove`, or replaces the groove with the same id in place.
+    pub fn put(&mut self, groove: Groove) {
+        match self.0.iter_mut().find(|existing| existing.id == groove.id) {
+            Some(existing) => *existing = groove,
+            None => self.0.push(groove),
+        }
+    }
+
+    pub fn remove(&mut self, groove_id: &Id) -> Option<Groove> {
+        let index = self.0.iter().position(|groove| groove.id == *groove_id)?;
+        Some(self.0.remove(index))
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_array(&self) -> js_sys::Array {
+        self.0
+            .iter()
+            .map(|groove| serde_wasm_bindgen::to_value(groove).unwrap())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackGroove {
+    pub groove_id: Id,
+    #[serde(default = "TrackGroove::default_amount")]
+    pub amount: f64,
+}
+
+impl TrackGroove {
+    fn default_amount() -> f64 {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::note::{NoteNumber, NoteParameters};
+
+    #[test]
+    fn test_groove_apply() {
+        let groove = Groove {
+            id: Id::new(),
+            name: "Push".to_string(),
+            step_ticks: Ticks::new(240),
+            steps: vec![
+                GrooveStep {
+                    timing: 0,
+                    velocity: 10,
+                },
+                GrooveStep {
+                    timing: 20,
+                    velocity: -30,
+                },
+            ],
+        };
+        let note = |ticks: u64| {
+            Event::Note(Note {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                duration: Ticks::new(120),
+                velocity: Velocity::new(100),
+                note_number: NoteNumber::new(60),
+                track_id: Id::new(),
+                parameters: NoteParameters::new(),
+            })
+        };
+
+        let on_beat = groove.apply(note(480), 1.0);
+        let on_beat = on_beat.as_note().unwrap();
+        assert_eq!(on_beat.ticks, Ticks::new(480));
+        assert_eq!(on_beat.velocity, Velocity::new(110));
+
+        // Nearest to the second step, at half strength.
+        let off_beat = groove.apply(note(730), 0.5);
+        let off_beat = off_beat.as_note().unwrap();
+        assert_eq!(off_beat.ticks, Ticks::new(740));
+        assert_eq!(off_beat.velocity, Velocity::new(85));
+
+        let mut pool = GroovePool::default();
+        pool.put(groove.clone());
+        pool.put(Groove {
+            name: "Renamed".to_string(),
+            ..groove.clone()
+        });
+        assert_eq!(pool.iter().count(), 1);
+        assert_eq!(pool.get(&groove.id).unwrap().name, "Renamed");
+        assert!(pool.remove(&groove.id).is_some());
+        assert!(pool.get(&groove.id).is_none());
+    }
+}
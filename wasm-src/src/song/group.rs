@@ -0,0 +1,146 @@
+use super::song::Song;
+use crate::shared::id::Id;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_GROUP_INTERFACES: &'static str = r#"
+/**
+ * Named events that belong together, such as a drum fill. Moving, copying or removing a member
+ * can apply to the whole group; see `Store.createGroup`.
+ */
+export interface EventGroup {
+  id: string;
+  name: string;
+  /** The members that are in the song, in the order they were grouped. */
+  eventIds: string[];
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventGroup {
+    pub id: Id,
+    pub name: String,
+    pub event_ids: Vec<Id>,
+}
+
+impl EventGroup {
+    /// The group without members that are not in `song`, e.g. removed ones that an undo may
+    /// bring back.
+    pub fn in_song(&self, song: &Song) -> EventGroup {
+        EventGroup {
+            event_ids: self
+                .event_ids
+                .iter()
+                .copied()
+                .filter(|event_id| song.get_event(event_id).is_some())
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// The group as JS, leaving out members that are not in `song`.
+    #[cfg(feature = "wasm")]
+    pub fn to_js_value(&self, song: &Song) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.in_song(song)).unwrap()
+    }
+}
+
+/// The song's event groups, in the order they were created. An event is in one group at most.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventGroups(Vec<EventGroup>);
+
+impl EventGroups {
+    pub fn iter(&self) -> impl Iterator<Item = &EventGroup> {
+        self.0.iter()
+    }
+
+    /// The group holding `event_id`.
+    pub fn of_event(&self, event_id: &Id) -> Option<&EventGroup> {
+        self.0
+            .iter()
+            .find(|group| group.event_ids.contains(event_id))
+    }
+
+    /// Adds the group `id` of `event_ids`, taking them out of the groups they were in. Groups
+    /// left empty are removed.
+    pub fn create(&mut self, id: Id, name: String, event_ids: Vec<Id>) -> &EventGroup {
+        for group in &mut self.0 {
+            group
+                .event_ids
+                .retain(|event_id| !event_ids.contains(event_id));
+        }
+        self.0.retain(|group| !group.event_ids.is_empty());
+        self.0.push(EventGroup {
+            id,
+            name,
+            event_ids,
+        });
+        self.0.last().unwrap()
+    }
+
+    /// Removes the group, leaving its events in the song.
+    pub fn remove(&mut self, group_id: &Id) -> Option<EventGroup> {
+        let index = self.0.iter().position(|group| group.id == *group_id)?;
+        Some(self.0.remove(index))
+    }
+
+    /// `event_ids` followed by the other members of their groups that are in `song`.
+    pub fn expand(&self, song: &Song, event_ids: &[Id]) -> Vec<Id> {
+        let mut expanded = event_ids.to_vec();
+        for event_id in event_ids {
+            let Some(group) = self.of_event(event_id) else {
+                continue;
+            };
+            for member in &group.event_ids {
+                if !expanded.contains(member) && song.get_event(member).is_some() {
+                    expanded.push(*member);
+                }
+            }
+        }
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_event_groups() {
+        let (mut song, track_id) = song_with_track();
+        let kick = add_note(&mut song, track_id, 0, 120, 36, 100);
+        let snare = add_note(&mut song, track_id, 120, 120, 38, 100);
+        let tom = add_note(&mut song, track_id, 240, 120, 45, 100);
+        let crash = add_note(&mut song, track_id, 480, 120, 49, 100);
+
+        let mut groups = EventGroups::default();
+        let fill = groups
+            .create(Id::new(), "Fill".to_string(), vec![kick, snare, tom])
+            .id;
+        assert_eq!(groups.of_event(&snare).unwrap().id, fill);
+        assert_eq!(
+            groups.expand(&song, &[tom, crash]),
+            vec![tom, crash, kick, snare]
+        );
+
+        song.remove_event(&kick);
+        assert_eq!(groups.expand(&song, &[snare]), vec![snare, tom]);
+
+        let ending = groups
+            .create(Id::new(), "Ending".to_string(), vec![tom, crash])
+            .id;
+        assert_eq!(groups.of_event(&tom).unwrap().id, ending);
+        assert_eq!(groups.of_event(&snare).unwrap().id, fill);
+
+        groups.create(Id::new(), "Hit".to_string(), vec![kick, snare]);
+        assert!(groups.iter().all(|group| group.id != fill));
+        assert!(groups.remove(&ending).is_some());
+        assert!(groups.of_event(&crash).is_none());
+    }
+}
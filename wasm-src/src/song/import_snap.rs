@@ -0,0 +1,120 @@
+use super::{meter::MeterMap, song::Song};
+use crate::{
+    event::event::Event,
+    shared::unit::{grid::Grid, time::Ticks},
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_IMPORT_SNAP_INTERFACES: &'static str = r#"
+export interface JsonImportOptions {
+  /** Snaps positions this close to the grid onto it, like `MidiImportOptions.snapTolerance`. */
+  snapTolerance?: number;
+  /** Grid to snap to. Defaults to sixteenths. */
+  snapGrid?: Grid;
+}
+"#;
+
+const DEFAULT_SNAP_GRID: Grid = Grid {
+    division: 16,
+    tuplet: None,
+};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JsonImportOptions {
+    pub snap_tolerance: Option<u64>,
+    pub snap_grid: Option<Grid>,
+}
+
+/// Moves positions within `tolerance` ticks of a `grid` line onto it, cleaning up the timing of
+/// loosely exported files while positions further off keep their intended offset.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportSnap {
+    grid: Grid,
+    tolerance: u64,
+}
+
+impl ImportSnap {
+    /// The snap that import options ask for; none without a tolerance.
+    pub fn from_options(tolerance: Option<u64>, grid: Option<Grid>) -> Option<ImportSnap> {
+        tolerance.map(|tolerance| ImportSnap {
+            grid: grid.unwrap_or(DEFAULT_SNAP_GRID),
+            tolerance,
+        })
+    }
+
+    /// Snaps `ticks` to a grid counted from tick zero, for positions read before the song's
+    /// meters are known.
+    pub fn snap(&self, ppq: u32, ticks: Ticks) -> Ticks {
+        self.within_tolerance(self.grid.snap_from(ppq, Ticks::new(0), ticks), ticks)
+    }
+
+    /// Snaps `ticks` to a grid that starts over at every meter change.
+    pub fn snap_in(&self, meters: &MeterMap, ticks: Ticks) -> Ticks {
+        self.within_tolerance(self.grid.snap(meters, ticks), ticks)
+    }
+
+    fn within_tolerance(&self, line: Option<Ticks>, ticks: Ticks) -> Ticks {
+        match line {
+            Some(line) if line.as_u64().abs_diff(ticks.as_u64()) <= self.tolerance => line,
+            _ => ticks,
+        }
+    }
+}
+
+/// Snaps the starts and ends of the song's events independently, so events that touched still
+/// touch; lengths stay at least one tick. The grid starts over at each of the song's meters as
+/// they were before snapping. The edits are recorded as changes of the song.
+pub fn snap_events(song: &mut Song, snap: ImportSnap) {
+    let meters = MeterMap::new(song);
+    let events: Vec<Event> = song.get_events(None).into_iter().cloned().collect();
+    for event in events {
+        let ticks = snap.snap_in(&meters, event.get_ticks());
+        let snapped = match event.get_duration() {
+            Some(duration) => {
+                let end = snap.snap_in(&meters, event.get_ticks() + duration);
+                event
+                    .clone()
+                    .with_duration((end - ticks).max(Ticks::new(1)))
+            }
+            None => event.clone(),
+        }
+        .with_ticks(ticks);
+        if snapped != event {
+            song.replace_event(snapped)
+                .expect("Snapped events are in the song");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_snap_events() {
+        let (mut song, track_id) = song_with_track();
+        let near = add_note(&mut song, track_id, 483, 236, 60, 100);
+        let offset = add_note(&mut song, track_id, 540, 60, 62, 100);
+        let cc = add_control_change(&mut song, track_id, 118, 7, 100);
+
+        let snap = ImportSnap::from_options(Some(5), None).unwrap();
+        snap_events(&mut song, snap);
+
+        let span = |id| {
+            let event = song.get_event(&id).unwrap();
+            (
+                event.get_ticks().as_u64(),
+                event.get_duration().map(|duration| duration.as_u64()),
+            )
+        };
+        assert_eq!(span(near), (480, Some(240)));
+        assert_eq!(span(offset), (540, Some(60)));
+        assert_eq!(span(cc), (120, None));
+    }
+}
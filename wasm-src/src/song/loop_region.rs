@@ -0,0 +1,104 @@
+use super::song::Song;
+use crate::{
+    event::event::Event,
+    query::playback::{playback_events, PlaybackOptions},
+    shared::unit::time::Ticks,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LOOP_REGION_INTERFACES: &'static str = r#"
+/** The stretch `[startTicks, endTicks)` the transport repeats while `enabled`. */
+export interface LoopRegion {
+  startTicks: number;
+  endTicks: number;
+  enabled: boolean;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopRegion {
+    pub start_ticks: Ticks,
+    pub end_ticks: Ticks,
+    pub enabled: bool,
+}
+
+impl LoopRegion {
+    pub fn length(&self) -> Ticks {
+        self.end_ticks - self.start_ticks
+    }
+}
+
+/// The playback events of the loop's `iteration`-th pass, from 0, as `playback_events` returns
+/// them for the loop window. Their ticks count from the start of the first pass, so pass `n`
+/// starts at `n` loop lengths, and notes sounding past the loop end are cut off there.
+pub fn loop_iteration_events(
+    song: &Song,
+    loop_region: &LoopRegion,
+    iteration: u32,
+    options: &PlaybackOptions,
+) -> Vec<Event> {
+    let length = loop_region.length().as_u64();
+    let pass_start = (iteration as u64).saturating_mul(length);
+
+    playback_events(
+        song,
+        loop_region.start_ticks,
+        loop_region.end_ticks,
+        options,
+    )
+    .into_iter()
+    .map(|event| {
+        let offset = event
+            .get_ticks()
+            .as_u64()
+            .saturating_sub(loop_region.start_ticks.as_u64());
+        let event = match event.get_duration() {
+            Some(duration) if offset + duration.as_u64() > length => {
+                event.with_duration(Ticks::new(length - offset))
+            }
+            _ => event,
+        };
+        event.with_ticks(Ticks::new(pass_start.saturating_add(offset)))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_loop_iteration_events() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 0, 960, 60, 100);
+        let inside = add_note(&mut song, track_id, 600, 240, 62, 100);
+        let crossing = add_note(&mut song, track_id, 1200, 480, 64, 100);
+        add_note(&mut song, track_id, 1440, 240, 65, 100);
+        let loop_region = LoopRegion {
+            start_ticks: Ticks::new(480),
+            end_ticks: Ticks::new(1440),
+            enabled: true,
+        };
+
+        let placed = |iteration| {
+            loop_iteration_events(&song, &loop_region, iteration, &PlaybackOptions::default())
+                .iter()
+                .map(|event| {
+                    (
+                        event.get_id(),
+                        event.get_ticks().as_u64(),
+                        event.get_duration().unwrap().as_u64(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(placed(0), vec![(inside, 120, 240), (crossing, 720, 240)]);
+        assert_eq!(placed(2), vec![(inside, 2040, 240), (crossing, 2640, 240)]);
+    }
+}
@@ -0,0 +1,190 @@
+use super::song::Song;
+use crate::shared::unit::time::Ticks;
+
+/// The meter of songs without a time signature at tick 0.
+const DEFAULT_METER: (u8, u8) = (4, 4);
+
+/// A stretch of the song in one meter, from its time signature to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeterSegment {
+    pub ticks: Ticks,
+    /// Zero-based index of the bar the segment starts with.
+    pub bar: u64,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+impl MeterSegment {
+    /// Length of one beat, the note value of the denominator.
+    pub fn beat_ticks(&self, ppq: u32) -> u64 {
+        (ppq as u64 * 4 / self.denominator.max(1) as u64).max(1)
+    }
+
+    pub fn beats_per_bar(&self) -> u64 {
+        self.numerator.max(1) as u64
+    }
+
+    pub fn bar_ticks(&self, ppq: u32) -> u64 {
+        self.beat_ticks(ppq) * self.beats_per_bar()
+    }
+}
+
+/// The song's time signatures resolved into bars.
+///
+/// Every time signature starts a new bar, so one placed mid-bar cuts the bar before it short.
+/// When several share a tick, the last in id order wins.
+#[derive(Debug, Clone)]
+pub struct MeterMap {
+    ppq: u32,
+    segments: Vec<MeterSegment>,
+}
+
+impl MeterMap {
+    pub fn new(song: &Song) -> Self {
+        let mut segments = vec![MeterSegment {
+            ticks: Ticks::new(0),
+            bar: 0,
+            numerator: DEFAULT_METER.0,
+            denominator: DEFAULT_METER.1,
+        }];
+
+        for time_signature in song.get_time_signatures() {
+            let last = segments.last_mut().unwrap();
+            if last.ticks == time_signature.ticks {
+                last.numerator = time_signature.numerator;
+                last.denominator = time_signature.denominator;
+                continue;
+            }
+
+            let bar_ticks = last.bar_ticks(song.ppq);
+            let elapsed = time_signature.ticks.as_u64() - last.ticks.as_u64();
+            let bar = last.bar + elapsed.div_ceil(bar_ticks);
+            segments.push(MeterSegment {
+                ticks: time_signature.ticks,
+                bar,
+                numerator: time_signature.numerator,
+                denominator: time_signature.denominator,
+            });
+        }
+
+        MeterMap {
+            ppq: song.ppq,
+            segments,
+        }
+    }
+
+    pub fn ppq(&self) -> u32 {
+        self.ppq
+    }
+
+    pub fn segments(&self) -> &[MeterSegment] {
+        &self.segments
+    }
+
+    /// The segment in effect at `ticks`.
+    pub fn segment_at(&self, ticks: Ticks) -> &MeterSegment {
+        &self.segments[self.segment_index_at(ticks)]
+    }
+
+    /// Index of the segment in effect at `ticks`.
+    pub fn segment_index_at(&self, ticks: Ticks) -> usize {
+        self.segments
+            .partition_point(|segment| segment.ticks <= ticks)
+            - 1
+    }
+
+    /// Zero-based bar, beat within the bar and ticks within the beat at `ticks`.
+    pub fn bar_beat_at(&self, ticks: Ticks) -> (u64, u64, u64) {
+        let segment = self.segment_at(ticks);
+        let elapsed = ticks.as_u64() - segment.ticks.as_u64();
+        let bar_ticks = segment.bar_ticks(self.ppq);
+        let beat_ticks = segment.beat_ticks(self.ppq);
+        let in_bar = elapsed % bar_ticks;
+
+        (
+            segment.bar + elapsed / bar_ticks,
+            in_bar / beat_ticks,
+            in_bar % beat_ticks,
+        )
+    }
+
+    /// Where the zero-based `bar` starts.
+    pub fn bar_start(&self, bar: u64) -> Ticks {
+        let index = self.segments.partition_point(|segment| segment.bar <= bar);
+        let segment = &self.segments[index - 1];
+        segment.ticks + Ticks::new((bar - segment.bar) * segment.bar_ticks(self.ppq))
+    }
+
+    /// Where the segment at `index` ends, if another follows it.
+    pub fn segment_end(&self, index: usize) -> Option<Ticks> {
+        self.segments.get(index + 1).map(|segment| segment.ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{event::Event, time_signature::TimeSignature},
+        shared::id::Id,
+        transform::test_helpers::song_with_track,
+    };
+
+    fn add_time_signature(song: &mut Song, id: u128, ticks: u64, numerator: u8, denominator: u8) {
+        let track_id = song.get_tracks()[0].id;
+        song.add_event(Event::TimeSignature(TimeSignature {
+            id: Id::from_u128(id),
+            ticks: Ticks::new(ticks),
+            numerator,
+            denominator,
+            track_id,
+        }))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_meter_map_defaults_to_four_four() {
+        let (song, _) = song_with_track();
+        let meters = MeterMap::new(&song);
+
+        assert_eq!(meters.segments().len(), 1);
+        assert_eq!(meters.segment_at(Ticks::MAX).numerator, 4);
+        assert_eq!(meters.bar_beat_at(Ticks::new(1920 + 960 + 10)), (1, 2, 10));
+        assert_eq!(meters.bar_start(3), Ticks::new(5760));
+        assert_eq!(meters.segment_end(0), None);
+    }
+
+    #[test]
+    fn test_meter_lookup() {
+        let (mut song, _) = song_with_track();
+        // two bars of 3/4, then 6/8 placed mid-bar, cutting the third bar short
+        add_time_signature(&mut song, 1, 0, 3, 4);
+        add_time_signature(&mut song, 2, 3600, 6, 8);
+        // of two on one tick, the last in id order wins
+        add_time_signature(&mut song, 4, 7200, 5, 4);
+        add_time_signature(&mut song, 3, 7200, 2, 4);
+        let meters = MeterMap::new(&song);
+
+        let starts: Vec<(u64, u64, u8)> = meters
+            .segments()
+            .iter()
+            .map(|segment| (segment.ticks.as_u64(), segment.bar, segment.numerator))
+            .collect();
+        assert_eq!(starts, vec![(0, 0, 3), (3600, 3, 6), (7200, 6, 5)]);
+
+        assert_eq!(meters.segment_index_at(Ticks::new(3599)), 0);
+        assert_eq!(meters.segment_index_at(Ticks::new(3600)), 1);
+        assert_eq!(meters.segment_end(1), Some(Ticks::new(7200)));
+        assert_eq!(meters.segment_at(Ticks::new(3600)).denominator, 8);
+
+        assert_eq!(meters.bar_beat_at(Ticks::new(3000)), (2, 0, 120));
+        assert_eq!(
+            meters.bar_beat_at(Ticks::new(3600 + 1440 + 250)),
+            (4, 1, 10)
+        );
+        assert_eq!(meters.bar_start(2), Ticks::new(2880));
+        assert_eq!(meters.bar_start(4), Ticks::new(5040));
+        assert_eq!(meters.bar_start(6), Ticks::new(7200));
+        assert_eq!(meters.bar_start(7), Ticks::new(9600));
+    }
+}
@@ -1 +1,16 @@
+pub mod compact;
+pub mod end_of_song;
+pub mod groove;
+pub mod group;
+pub mod import_snap;
+pub mod loop_region;
+pub mod meter;
+pub mod mute;
+pub mod performance;
+pub mod ppq;
+pub mod quota;
+pub mod recording;
+pub mod section;
+pub mod settings;
+pub mod snapshot;
 pub mod song;
@@ -0,0 +1,106 @@
+use super::song::Song;
+use crate::event::event::Event;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_MUTE_INTERFACES: &'static str = r#"
+/** Why an event sounds or not, see `Store.getEffectiveEventState`. */
+export interface EffectiveEventState {
+  /** Whether playback includes the event: its track is audible and nothing mutes it. */
+  audible: boolean;
+  eventMuted: boolean;
+  /** Whether the event's group is muted; false for events in no group. */
+  groupMuted: boolean;
+  /** Whether the event's track sounds given the mutes and solos, see `Store.isTrackAudible`. */
+  trackAudible: boolean;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveEventState {
+    pub audible: bool,
+    pub event_muted: bool,
+    pub group_muted: bool,
+    pub track_audible: bool,
+}
+
+impl EffectiveEventState {
+    pub fn of(song: &Song, event: &Event) -> Self {
+        let event_muted = song.is_muted(&event.get_id());
+        let group_muted = song
+            .groups
+            .of_event(&event.get_id())
+            .is_some_and(|group| song.is_muted(&group.id));
+        let track_audible = event
+            .get_track_id()
+            .is_some_and(|track_id| song.is_track_audible(&track_id) == Some(true));
+        EffectiveEventState {
+            audible: track_audible && !event_muted && !group_muted,
+            event_muted,
+            group_muted,
+            track_audible,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        query::playback::{playback_events, PlaybackOptions},
+        shared::{id::Id, unit::time::Ticks},
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_effective_event_state() {
+        let (mut song, track_id) = song_with_track();
+        let kick = add_note(&mut song, track_id, 0, 120, 36, 100);
+        let snare = add_note(&mut song, track_id, 120, 120, 38, 100);
+        let tom = add_note(&mut song, track_id, 240, 120, 45, 100);
+        let fill = song
+            .groups
+            .create(Id::new(), "Fill".to_string(), vec![snare, tom])
+            .id;
+        let state = |song: &Song, event_id| {
+            EffectiveEventState::of(song, song.get_event(&event_id).unwrap())
+        };
+        let audible = |song: &Song| {
+            playback_events(
+                song,
+                Ticks::new(0),
+                Ticks::new(480),
+                &PlaybackOptions::default(),
+            )
+            .iter()
+            .map(Event::get_id)
+            .collect::<Vec<_>>()
+        };
+        assert!(state(&song, kick).audible);
+
+        song.set_muted(kick, true);
+        song.set_muted(fill, true);
+        assert_eq!(
+            state(&song, kick),
+            EffectiveEventState {
+                audible: false,
+                event_muted: true,
+                group_muted: false,
+                track_audible: true,
+            }
+        );
+        assert!(state(&song, tom).group_muted);
+        assert!(audible(&song).is_empty());
+
+        song.set_muted(fill, false);
+        assert_eq!(audible(&song), vec![snare, tom]);
+
+        song.update_track(&track_id, |track| track.mix.mute = true);
+        let state = state(&song, snare);
+        assert!(!state.audible && !state.track_audible && !state.group_muted);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, Velocity},
+    },
+    shared::{id::Id, rng::Rng, unit::time::Ticks},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PERFORMANCE_INTERFACES: &'static str = r#"
+/**
+ * How a track's events vary when played, stored with the song and shared by its tracks.
+ * Offsets are drawn around the bias, more often near it than far from it, and never further
+ * than the spread. The stored events are left as they are.
+ */
+export interface PerformanceProfile {
+  id: string;
+  name: string;
+  /** Ticks events move at most from `timingBias`. Defaults to 0. */
+  timingSpread?: number;
+  /** Ticks added to every event, e.g. positive to play behind the beat. Defaults to 0. */
+  timingBias?: number;
+  /** How much note velocities change at most from `velocityBias`. Defaults to 0. */
+  velocitySpread?: number;
+  /** Added to every note velocity. Defaults to 0. */
+  velocityBias?: number;
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceProfile {
+    pub id: Id,
+    pub name: String,
+    #[serde(default)]
+    pub timing_spread: u32,
+    #[serde(default)]
+    pub timing_bias: i32,
+    #[serde(default)]
+    pub velocity_spread: u8,
+    #[serde(default)]
+    pub velocity_bias: i32,
+}
+
+/// An offset from `-spread` to `spread`, the sum of two uniform draws, so small offsets are
+/// likelier than large ones.
+fn weighted_offset(rng: &mut Rng, spread: u64) -> i64 {
+    let sum = rng.within(spread) + rng.within(spread);
+    sum / 2
+}
+
+impl PerformanceProfile {
+    /// Returns `event` moved and, for notes, re-voiced by offsets drawn for it alone: the same
+    /// `seed` varies the same event the same way whatever range it is played in. Positions
+    /// before the song start clamp to tick 0 and velocities stay from 1 to 127.
+    pub fn apply(&self, event: Event, seed: u64) -> Event {
+        let mut rng = Rng::new(seed ^ event.get_id().seed());
+        let timing = self.timing_bias as i64 + weighted_offset(&mut rng, self.timing_spread as u64);
+        let velocity =
+            self.velocity_bias as i64 + weighted_offset(&mut rng, self.velocity_spread as u64);
+
+        let ticks = (event.get_ticks().as_u64() as i64 + timing).max(0);
+        match event.with_ticks(Ticks::new(ticks as u64)) {
+            Event::Note(note) => {
+                let velocity = (note.velocity.as_u8() as i64 + velocity).clamp(1, 127);
+                Event::Note(Note {
+                    velocity: Velocity::new(velocity as u8),
+                    ..note
+                })
+            }
+            event => event,
+        }
+    }
+}
+
+/// The song's performance profiles, in the order they were added, and the profile each track
+/// plays with.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceProfiles {
+    profiles: Vec<PerformanceProfile>,
+    tracks: HashMap<Id, Id>,
+}
+
+impl PerformanceProfiles {
+    pub fn get(&self, profile_id: &Id) -> Option<&PerformanceProfile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.id == *profile_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PerformanceProfile> {
+        self.profiles.iter()
+    }
+
+    /// Adds `profile`, or replaces the profile with the same id in place.
+    pub fn put(&mut self, profile: PerformanceProfile) {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|existing| existing.id == profile.id)
+        {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    /// Removes the profile, and takes it off the tracks playing with it.
+    pub fn remove(&mut self, profile_id: &Id) -> Option<PerformanceProfile> {
+        let index = self
+            .profiles
+            .iter()
+            .position(|profile| profile.id == *profile_id)?;
+        self.tracks.retain(|_, assigned| assigned != profile_id);
+        Some(self.profiles.remove(index))
+    }
+
+    /// The profile the track plays with.
+    pub fn of_track(&self, track_id: &Id) -> Option<&PerformanceProfile> {
+        self.get(self.tracks.get(track_id)?)
+    }
+
+    pub fn assign(&mut self, track_id: Id, profile_id: Option<Id>) {
+        match profile_id {
+            Some(profile_id) => self.tracks.insert(track_id, profile_id),
+            None => self.tracks.remove(&track_id),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{
+        control_change::{ControlChange, ControllerNumber, ControllerValue},
+        note::{NoteNumber, NoteParameters},
+    };
+
+    #[test]
+    fn test_performance_profile_apply() {
+        let profile = PerformanceProfile {
+            id: Id::new(),
+            name: "Loose".to_string(),
+            timing_spread: 10,
+            timing_bias: 5,
+            velocity_spread: 8,
+            velocity_bias: -4,
+        };
+        let track_id = Id::new();
+        let note = Event::Note(Note {
+            id: Id::new(),
+            ticks: Ticks::new(480),
+            duration: Ticks::new(240),
+            velocity: Velocity::new(100),
+            note_number: NoteNumber::new(60),
+            track_id,
+            parameters: NoteParameters::new(),
+        });
+
+        let played = profile.apply(note.clone(), 1);
+        assert_eq!(played, profile.apply(note.clone(), 1));
+        assert_eq!(played.get_duration(), note.get_duration());
+        let ticks = played.get_ticks().as_u64();
+        assert!((475..=495).contains(&ticks));
+        let velocity = played.as_note().unwrap().velocity.as_u8();
+        assert!((88..=104).contains(&velocity));
+
+        let seeds_differ = (2..20).any(|seed| profile.apply(note.clone(), seed) != played);
+        assert!(seeds_differ);
+
+        let cc = Event::ControlChange(ControlChange {
+            id: Id::new(),
+            ticks: Ticks::new(0),
+            controller: ControllerNumber::new(7),
+            value: ControllerValue::new(100),
+            track_id,
+        });
+        assert!(profile.apply(cc, 1).get_ticks().as_u64() <= 15);
+
+        let mut profiles = PerformanceProfiles::default();
+        profiles.put(profile.clone());
+        profiles.assign(track_id, Some(profile.id));
+        assert_eq!(profiles.of_track(&track_id), Some(&profile));
+        profiles.remove(&profile.id);
+        assert!(profiles.of_track(&track_id).is_none());
+        assert!(profiles.tracks.is_empty());
+    }
+}
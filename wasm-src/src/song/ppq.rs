@@ -0,0 +1,134 @@
+use super::song::Song;
+use crate::{event::event::Event, shared::unit::time::Ticks};
+
+/// Converts ticks from one resolution to another, rounding to the nearest tick.
+struct PpqScale {
+    from: u64,
+    to: u64,
+}
+
+impl PpqScale {
+    /// `ticks` at the new resolution, capped at `Ticks::MAX`. The product is taken in 128 bits
+    /// as late ticks times a large ppq pass `u64::MAX`.
+    fn ticks(&self, ticks: Ticks) -> Ticks {
+        let (from, to) = (self.from as u128, self.to as u128);
+        let scaled = (ticks.as_u64() as u128 * to + from / 2) / from;
+        Ticks::new(u64::try_from(scaled).unwrap_or(u64::MAX))
+    }
+
+    fn offset(&self, ticks: i32) -> i32 {
+        (ticks as f64 * self.to as f64 / self.from as f64).round() as i32
+    }
+}
+
+/// Moves the song to `ppq`, converting every position and length measured in ticks so the music
+/// keeps its rhythm: events, track delays, groove steps, the loop and the minimum song length.
+///
+/// Ends are converted rather than lengths, so events that touched still touch; lengths stay at
+/// least one tick. The edits are recorded as changes of the song.
+pub fn rescale_ppq(song: &mut Song, ppq: u32) {
+    if song.ppq == ppq {
+        return;
+    }
+    let scale = PpqScale {
+        from: song.ppq as u64,
+        to: ppq as u64,
+    };
+
+    let events: Vec<Event> = song.get_events(None).into_iter().cloned().collect();
+    for event in events {
+        let ticks = scale.ticks(event.get_ticks());
+        let event = match event.get_duration() {
+            Some(duration) => {
+                let end = scale.ticks(event.get_ticks() + duration);
+                let duration = (end - ticks).max(Ticks::new(1));
+                event.with_duration(duration)
+            }
+            None => event,
+        };
+        song.replace_event(event.with_ticks(ticks))
+            .expect("Rescaled events are in the song");
+    }
+
+    let track_ids: Vec<_> = song.get_tracks().iter().map(|track| track.id).collect();
+    for track_id in track_ids {
+        song.update_track(&track_id, |track| {
+            track.settings.delay_ticks = scale.offset(track.settings.delay_ticks);
+        });
+    }
+
+    let grooves: Vec<_> = song.grooves.iter().cloned().collect();
+    for mut groove in grooves {
+        groove.step_ticks = scale.ticks(groove.step_ticks).max(Ticks::new(1));
+        for step in &mut groove.steps {
+            step.timing = scale.offset(step.timing);
+        }
+        song.grooves.put(groove);
+    }
+
+    if let Some(loop_region) = &mut song.loop_region {
+        loop_region.start_ticks = scale.ticks(loop_region.start_ticks);
+        loop_region.end_ticks = scale.ticks(loop_region.end_ticks);
+    }
+    if let Some(min_ticks) = &mut song.end_of_song_policy.min_ticks {
+        *min_ticks = scale.ticks(*min_ticks);
+    }
+
+    song.ppq = ppq;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        song::loop_region::LoopRegion,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_rescale_ppq() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 480, 240, 60, 100);
+        let second = add_note(&mut song, track_id, 725, 5, 62, 100);
+        song.update_track(&track_id, |track| track.settings.delay_ticks = -10);
+        song.loop_region = Some(LoopRegion {
+            start_ticks: Ticks::new(0),
+            end_ticks: Ticks::new(1920),
+            enabled: true,
+        });
+
+        rescale_ppq(&mut song, 96);
+
+        assert_eq!(song.ppq, 96);
+        let span = |id| {
+            let event = song.get_event(&id).unwrap();
+            (
+                event.get_ticks().as_u64(),
+                event.get_duration().unwrap().as_u64(),
+            )
+        };
+        assert_eq!(span(first), (96, 48));
+        assert_eq!(span(second), (145, 1));
+        assert_eq!(song.get_track(&track_id).unwrap().settings.delay_ticks, -2);
+        assert_eq!(song.loop_region.unwrap().end_ticks, Ticks::new(384));
+        assert_eq!(
+            song.get_events_in_ticks_range(Ticks::new(96), Ticks::new(97), false, None)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_ppq_scale_caps_ticks() {
+        let up = PpqScale {
+            from: 1,
+            to: u32::MAX as u64,
+        };
+        assert_eq!(up.ticks(Ticks::MAX), Ticks::MAX);
+        let down = PpqScale { from: 960, to: 480 };
+        assert_eq!(
+            down.ticks(Ticks::MAX),
+            Ticks::new(Ticks::MAX.as_u64() / 2 + 1)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use super::song::Song;
+#[cfg(feature = "wasm")]
+use crate::shared::js::to_js_value;
+use crate::{change::change::Change, shared::error::StoreError};
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_QUOTA_USAGE: &'static str = r#"
+export interface QuotaUsage {
+  events: number;
+  tracks: number;
+  /** Absent when events are not limited. */
+  maxEvents?: number;
+  /** Absent when tracks are not limited. */
+  maxTracks?: number;
+}
+"#;
+
+/// What a song limit counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaResource {
+    Events,
+    Tracks,
+}
+
+impl QuotaResource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaResource::Events => "events",
+            QuotaResource::Tracks => "tracks",
+        }
+    }
+}
+
+/// Limits on the size of the song, set by the host. Edits that would grow the song past a limit
+/// fail; a song already past a lowered limit can still shrink.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quota {
+    pub max_events: Option<usize>,
+    pub max_tracks: Option<usize>,
+}
+
+impl Quota {
+    /// Fails when the song's pending `changes` add events or tracks and leave more than allowed.
+    pub fn check_changes(&self, song: &Song, changes: &[Change]) -> Result<(), StoreError> {
+        let mut added_events = 0;
+        let mut added_tracks = 0;
+        for change in changes {
+            match change {
+                Change::EventAdded(_) => added_events += 1,
+                Change::EventRemoved(_) => added_events -= 1,
+                Change::TrackAdded(_) => added_tracks += 1,
+                Change::TrackRemoved(_) => added_tracks -= 1,
+                _ => {}
+            }
+        }
+        if added_events > 0 {
+            Self::check(QuotaResource::Events, song.event_count(), self.max_events)?;
+        }
+        if added_tracks > 0 {
+            Self::check(
+                QuotaResource::Tracks,
+                song.get_tracks().len(),
+                self.max_tracks,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Fails when a song about to be loaded is past a limit.
+    pub fn check_song(&self, song: &Song) -> Result<(), StoreError> {
+        Self::check(QuotaResource::Events, song.event_count(), self.max_events)?;
+        Self::check(
+            QuotaResource::Tracks,
+            song.get_tracks().len(),
+            self.max_tracks,
+        )
+    }
+
+    fn check(
+        resource: QuotaResource,
+        count: usize,
+        limit: Option<usize>,
+    ) -> Result<(), StoreError> {
+        match limit {
+            Some(limit) if count > limit => Err(StoreError::QuotaExceeded { resource, limit }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaUsage {
+    events: usize,
+    tracks: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_events: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tracks: Option<usize>,
+}
+
+impl QuotaUsage {
+    pub fn new(song: Option<&Song>, quota: &Quota) -> Self {
+        QuotaUsage {
+            events: song.map_or(0, Song::event_count),
+            tracks: song.map_or(0, |song| song.get_tracks().len()),
+            max_events: quota.max_events,
+            max_tracks: quota.max_tracks,
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        to_js_value(self).unchecked_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_quota() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 480, 480, 62, 100);
+        song.take_changes();
+        let quota = Quota {
+            max_events: Some(1),
+            max_tracks: Some(1),
+        };
+
+        assert_eq!(
+            quota.check_song(&song),
+            Err(StoreError::QuotaExceeded {
+                resource: QuotaResource::Events,
+                limit: 1
+            })
+        );
+        song.remove_event(&first);
+        assert_eq!(quota.check_changes(&song, song.get_changes()), Ok(()));
+        song.take_changes();
+
+        add_note(&mut song, track_id, 960, 480, 64, 100);
+        assert_eq!(
+            quota
+                .check_changes(&song, song.get_changes())
+                .unwrap_err()
+                .code(),
+            "quotaExceeded"
+        );
+    }
+}
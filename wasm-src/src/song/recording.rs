@@ -0,0 +1,160 @@
+use super::song::Song;
+use crate::shared::{error::StoreError, unit::time::Ticks};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RECORDING_INTERFACES: &'static str = r#"
+/** A kind of MIDI input a track can record, see `RecordFilter`. */
+export type RecordInputKind =
+  | "note"
+  | "controlChange"
+  | "aftertouch"
+  | "pitchBend"
+  | "programChange";
+
+/** What a track accepts while recording; input left out is dropped. */
+export interface RecordFilter {
+  /** 0-based MIDI channels to accept. All by default; input without a channel always passes. */
+  channels?: number[];
+  /** Kinds of input to accept. All by default. */
+  kinds?: RecordInputKind[];
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordInputKind {
+    Note,
+    ControlChange,
+    Aftertouch,
+    PitchBend,
+    ProgramChange,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordFilter {
+    pub channels: Option<Vec<u8>>,
+    pub kinds: Option<Vec<RecordInputKind>>,
+}
+
+impl RecordFilter {
+    /// Fails when a channel is out of the MIDI range.
+    pub fn check(&self) -> Result<(), StoreError> {
+        if self.channels.iter().flatten().any(|channel| *channel > 15) {
+            return Err(StoreError::invalid(
+                "Record filter channels must be from 0 to 15",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn accepts(&self, kind: RecordInputKind, channel: Option<u8>) -> bool {
+        let kind_accepted = self
+            .kinds
+            .as_ref()
+            .is_none_or(|kinds| kinds.contains(&kind));
+        let channel_accepted = match (&self.channels, channel) {
+            (Some(channels), Some(channel)) => channels.contains(&channel),
+            _ => true,
+        };
+        kind_accepted && channel_accepted
+    }
+}
+
+/// Where recording started: the host time, in milliseconds, heard at `ticks`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RecordingStart {
+    host_time: f64,
+    ticks: Ticks,
+    bpm: f64,
+}
+
+/// Converts host timestamps of recorded input, such as MIDI message times, to song ticks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingClock {
+    /// How long input takes to reach the host, in milliseconds; timestamps move back by it.
+    pub latency_ms: f64,
+    /// Follow tempo changes made while recording rather than the tempo recording started at.
+    pub tempo_aware: bool,
+    start: Option<RecordingStart>,
+}
+
+impl Default for RecordingClock {
+    fn default() -> Self {
+        RecordingClock {
+            latency_ms: 0.0,
+            tempo_aware: true,
+            start: None,
+        }
+    }
+}
+
+impl RecordingClock {
+    /// Anchors the clock: `host_time` is heard at `ticks`.
+    pub fn start(&mut self, song: &Song, host_time: f64, ticks: Ticks) {
+        self.start = Some(RecordingStart {
+            host_time,
+            ticks,
+            bpm: song.bpm,
+        });
+    }
+
+    /// The tick nearest to where input stamped `host_time` was heard, never before the song
+    /// start.
+    pub fn ticks_at(&self, song: &Song, host_time: f64) -> Result<Ticks, StoreError> {
+        let start = self
+            .start
+            .ok_or_else(|| StoreError::invalid("Recording has not started"))?;
+        let bpm = if self.tempo_aware {
+            song.bpm
+        } else {
+            start.bpm
+        };
+        let seconds = (host_time - self.latency_ms - start.host_time) / 1000.0;
+        let ticks = start.ticks.as_f64() + seconds * bpm / 60.0 * song.ppq as f64;
+        Ok(Ticks::new(
+            ticks.round().clamp(0.0, Ticks::MAX.as_f64()) as u64
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_clock() {
+        let mut song = Song::new("test".to_string(), 480);
+        let mut clock = RecordingClock::default();
+        assert!(clock.ticks_at(&song, 0.0).is_err());
+
+        // at 120 bpm a beat of 480 ticks lasts 500 ms
+        clock.start(&song, 10_000.0, Ticks::new(1920));
+        clock.latency_ms = 20.0;
+        assert_eq!(clock.ticks_at(&song, 10_520.0), Ok(Ticks::new(2400)));
+        assert_eq!(clock.ticks_at(&song, 0.0), Ok(Ticks::new(0)));
+
+        song.bpm = 60.0;
+        assert_eq!(clock.ticks_at(&song, 11_020.0), Ok(Ticks::new(2400)));
+        clock.tempo_aware = false;
+        assert_eq!(clock.ticks_at(&song, 11_020.0), Ok(Ticks::new(2880)));
+    }
+
+    #[test]
+    fn test_record_filter() {
+        let filter = RecordFilter {
+            channels: Some(vec![0, 9]),
+            kinds: Some(vec![RecordInputKind::Note, RecordInputKind::PitchBend]),
+        };
+        assert!(filter.accepts(RecordInputKind::Note, Some(9)));
+        assert!(filter.accepts(RecordInputKind::Note, None));
+        assert!(!filter.accepts(RecordInputKind::Note, Some(1)));
+        assert!(!filter.accepts(RecordInputKind::ControlChange, Some(0)));
+        assert!(!filter.accepts(RecordInputKind::Aftertouch, None));
+        assert!(RecordFilter::default().accepts(RecordInputKind::Aftertouch, Some(15)));
+    }
+}
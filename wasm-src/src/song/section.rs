@@ -0,0 +1,196 @@
+use super::song::Song;
+use crate::{
+    event::{event::Event, marker::Marker},
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        unit::time::Ticks,
+    },
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SECTION_INTERFACES: &'static str = r#"
+/** The stretch from a marker to the next one; the last section has no end. */
+export interface Section {
+  markerId: string;
+  name: string;
+  startTicks: number;
+  endTicks: number | null;
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section<'a> {
+    pub marker: &'a Marker,
+    pub end_ticks: Option<Ticks>,
+}
+
+impl Section<'_> {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_section = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_section,
+            &JsValue::from_str("markerId"),
+            &JsValue::from_str(self.marker.id.to_string().as_str()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_section,
+            &JsValue::from_str("name"),
+            &JsValue::from_str(&self.marker.name),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_section,
+            &JsValue::from_str("startTicks"),
+            &JsValue::from_f64(self.marker.ticks.as_f64()),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_section,
+            &JsValue::from_str("endTicks"),
+            &self
+                .end_ticks
+                .map_or(JsValue::NULL, |ticks| JsValue::from_f64(ticks.as_f64())),
+        )
+        .unwrap();
+
+        js_section
+    }
+}
+
+/// Markers in ticks order.
+pub fn markers(song: &Song) -> Vec<&Marker> {
+    song.get_marker_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Marker(marker) => Some(marker),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Adds a marker named `name` at `ticks` to the song.
+pub fn add_marker(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    ticks: Ticks,
+    name: String,
+) -> Result<Event, StoreError> {
+    let marker = Event::Marker(Marker {
+        id: ids.next_id()?,
+        ticks,
+        name,
+    });
+    song.add_event(marker).cloned()
+}
+
+/// Removes the marker `marker_id`, failing when no marker has that id.
+pub fn remove_marker(song: &mut Song, marker_id: &Id) -> Result<(), StoreError> {
+    match song.get_event(marker_id) {
+        Some(Event::Marker(_)) => {
+            song.remove_event(marker_id);
+            Ok(())
+        }
+        _ => Err(StoreError::EventNotFound(*marker_id)),
+    }
+}
+
+/// The song's arrangement, one section per marker. Markers sharing a tick make empty sections.
+pub fn sections(song: &Song) -> Vec<Section<'_>> {
+    let markers = markers(song);
+    markers
+        .iter()
+        .enumerate()
+        .map(|(index, marker)| Section {
+            marker,
+            end_ticks: markers.get(index + 1).map(|next| next.ticks),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_sections() {
+        let mut song = Song::new("Song".to_string(), 480);
+        for (ticks, name) in [(7680, "Chorus"), (0, "Verse")] {
+            song.add_event(Event::Marker(Marker {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                name: name.to_string(),
+            }))
+            .unwrap();
+        }
+
+        let sections: Vec<(&str, u64, Option<u64>)> = sections(&song)
+            .iter()
+            .map(|section| {
+                (
+                    section.marker.name.as_str(),
+                    section.marker.ticks.as_u64(),
+                    section.end_ticks.map(|ticks| ticks.as_u64()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![("Verse", 0, Some(7680)), ("Chorus", 7680, None)]
+        );
+    }
+
+    #[test]
+    fn test_add_and_query_markers() {
+        let mut song = Song::new("Song".to_string(), 480);
+        let mut ids = IdStrategy::default();
+        let chorus =
+            add_marker(&mut song, &mut ids, Ticks::new(3840), "Chorus".to_string()).unwrap();
+        let verse = add_marker(&mut song, &mut ids, Ticks::new(0), "Verse".to_string()).unwrap();
+        assert_eq!(chorus.get_track_id(), None);
+
+        let ids: Vec<Id> = song
+            .get_marker_events()
+            .iter()
+            .map(|event| event.get_id())
+            .collect();
+        assert_eq!(ids, vec![verse.get_id(), chorus.get_id()]);
+        let in_range = song.get_marker_events_in_ticks_range(Ticks::new(1), Ticks::new(7680));
+        assert_eq!(in_range, vec![&chorus]);
+
+        remove_marker(&mut song, &chorus.get_id()).unwrap();
+        assert_eq!(markers(&song).len(), 1);
+    }
+
+    #[test]
+    fn test_markers_outlive_tracks() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let verse = add_marker(
+            &mut song,
+            &mut IdStrategy::default(),
+            Ticks::new(0),
+            "Verse".to_string(),
+        )
+        .unwrap();
+        assert!(remove_marker(&mut song, &note_id).is_err());
+        assert!(song
+            .get_track_events(&track_id)
+            .iter()
+            .all(|event| *event != &verse));
+
+        song.remove_track(&track_id);
+        assert_eq!(song.get_marker_events(), vec![&verse]);
+        assert_eq!(song.get_events(None), vec![&verse]);
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+/// Per-song preferences of the app, such as its default grid or last view, saved with the song.
+///
+/// Values are kept as JSON text so any JSON value fits. Human-readable formats write them as
+/// plain JSON values; binary formats, which cannot hold arbitrary values, write the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SongSettings(BTreeMap<String, String>);
+
+impl SongSettings {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Sets `key` to the JSON value `json`, or removes it when `None`.
+    pub fn set(&mut self, key: &str, json: Option<String>) {
+        match json {
+            Some(json) => self.0.insert(key.to_string(), json),
+            None => self.0.remove(key),
+        };
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_settings = js_sys::Object::new();
+        for (key, json) in &self.0 {
+            js_sys::Reflect::set(
+                &js_settings,
+                &JsValue::from_str(key),
+                &js_sys::JSON::parse(json).unwrap(),
+            )
+            .unwrap();
+        }
+        js_settings
+    }
+}
+
+impl Serialize for SongSettings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return self.0.serialize(serializer);
+        }
+
+        let values = self
+            .0
+            .iter()
+            .map(|(key, json)| Ok((key, serde_json::from_str(json)?)))
+            .collect::<Result<BTreeMap<&String, serde_json::Value>, serde_json::Error>>()
+            .map_err(serde::ser::Error::custom)?;
+        values.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SongSettings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return BTreeMap::deserialize(deserializer).map(SongSettings);
+        }
+
+        let values = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        Ok(SongSettings(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, value.to_string()))
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut settings = SongSettings::default();
+        settings.set("grid", Some(r#"{"division":16}"#.to_string()));
+        settings.set("velocity", Some("96".to_string()));
+        settings.set("velocity", None);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(json, r#"{"grid":{"division":16}}"#);
+        assert_eq!(
+            serde_json::from_str::<SongSettings>(&json).unwrap(),
+            settings
+        );
+
+        let bytes = postcard::to_allocvec(&settings).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<SongSettings>(&bytes).unwrap(),
+            settings
+        );
+        assert_eq!(settings.get("grid"), Some(r#"{"division":16}"#));
+    }
+}
@@ -0,0 +1,206 @@
+use super::song::Song;
+use crate::{
+    change::change::Changeset,
+    event::event::Event,
+    operation::operation::{apply_operations, Operation},
+    shared::error::StoreError,
+};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SNAPSHOT_INTERFACES: &'static str = r#"
+export type SnapshotId = number;
+
+/** Events and tracks added, updated or removed between two snapshots. */
+export type SnapshotDiff = Omit<Changes<Event>, "seq" | "origin">;
+"#;
+
+/// Copies of the song kept by `Store.takeSnapshot`, by snapshot id.
+#[derive(Default)]
+pub struct Snapshots {
+    songs: HashMap<u32, Song>,
+    next_id: u32,
+}
+
+impl Snapshots {
+    pub fn take(&mut self, song: &Song) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.songs.insert(id, song.snapshot());
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Result<&Song, StoreError> {
+        self.songs
+            .get(&id)
+            .ok_or_else(|| StoreError::invalid(format!("Snapshot {} not found", id)))
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        self.songs.remove(&id).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.songs.clear();
+    }
+}
+
+/// What changed from `from` to `to`: events and tracks added, updated or removed. Updated tracks
+/// are those whose own properties or place in the track list differ.
+pub fn diff(from: &Song, to: &Song) -> Changeset {
+    let mut changeset = Changeset::default();
+
+    for event in to.get_events(None) {
+        match from.get_event(&event.get_id()) {
+            None => changeset.added.push(event.clone()),
+            Some(before) if before != event => changeset.updated.push(event.clone()),
+            _ => {}
+        }
+    }
+    changeset.removed = from
+        .get_events(None)
+        .into_iter()
+        .filter(|event| to.get_event(&event.get_id()).is_none())
+        .cloned()
+        .collect();
+
+    for track in to.get_tracks().iter() {
+        match from.get_track(&track.id) {
+            None => changeset.added_tracks.push(track.id),
+            Some(before) if before.without_events() != track.without_events() => {
+                changeset.updated_tracks.push(track.id)
+            }
+            _ => {}
+        }
+    }
+    changeset.removed_tracks = from
+        .get_tracks()
+        .iter()
+        .filter(|track| to.get_track(&track.id).is_none())
+        .map(|track| track.id)
+        .collect();
+
+    changeset
+}
+
+/// Edits `song` until it holds what `snapshot` holds. Events and tracks change through
+/// operations, so the restore is recorded like any other edit; the song's settings are copied.
+pub fn restore(song: &mut Song, snapshot: &Song) {
+    let mut operations = Vec::new();
+
+    for track in snapshot.get_tracks().iter() {
+        if song.get_track(&track.id).is_none() {
+            operations.push(Operation::AddTrack {
+                track: track.without_events(),
+                events: Vec::new(),
+            });
+        }
+    }
+
+    for event in song.get_events(None) {
+        match snapshot.get_event(&event.get_id()) {
+            None => operations.push(Operation::RemoveEvent(event.get_id())),
+            Some(target) if target.get_kind() != event.get_kind() => {
+                operations.push(Operation::RemoveEvent(event.get_id()));
+                operations.push(Operation::AddEvent(target.clone()));
+            }
+            Some(target) if target != event => operations.push(Operation::UpdateEvent {
+                updater: target.into(),
+                expected: None,
+            }),
+            _ => {}
+        }
+    }
+    let added: Vec<Event> = snapshot
+        .get_events(None)
+        .into_iter()
+        .filter(|event| song.get_event(&event.get_id()).is_none())
+        .cloned()
+        .collect();
+    operations.extend(added.into_iter().map(Operation::AddEvent));
+
+    for track in song.get_tracks().iter() {
+        if snapshot.get_track(&track.id).is_none() {
+            operations.push(Operation::RemoveTrack(track.id));
+        }
+    }
+    for (index, track) in snapshot.get_tracks().iter().enumerate() {
+        let current = song.get_track(&track.id);
+        if current.is_some_and(|current| !current.has_properties_of(track)) {
+            operations.push(Operation::UpdateTrack(track.without_events()));
+        }
+        operations.push(Operation::MoveTrack {
+            track_id: track.id,
+            index,
+        });
+    }
+
+    apply_operations(song, operations);
+
+    song.title = snapshot.title.clone();
+    song.ppq = snapshot.ppq;
+    song.bpm = snapshot.bpm;
+    song.end_of_song_policy = snapshot.end_of_song_policy;
+    song.grooves = snapshot.grooves.clone();
+    song.groups = snapshot.groups.clone();
+    song.muted = snapshot.muted.clone();
+    song.event_meta = snapshot.event_meta.clone();
+    song.profiles = snapshot.profiles.clone();
+    song.settings = snapshot.settings.clone();
+    song.loop_region = snapshot.loop_region;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::{id::Id, unit::time::Ticks},
+        track::track::Track,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_snapshot_diff_and_restore() {
+        let (mut song, track_id) = song_with_track();
+        let kept = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let moved = add_note(&mut song, track_id, 480, 480, 62, 100);
+        let removed = add_note(&mut song, track_id, 960, 480, 64, 100);
+        song.commit(None);
+
+        let mut snapshots = Snapshots::default();
+        let before = snapshots.take(&song);
+
+        let other_track_id = song.add_track(Track::new(Id::new())).id;
+        let note = song.get_event(&moved).unwrap().clone();
+        song.replace_event(note.with_ticks(Ticks::new(720)))
+            .unwrap();
+        song.remove_event(&removed);
+        let added = add_note(&mut song, other_track_id, 0, 240, 67, 100);
+        song.bpm = 90.0;
+        song.commit(None);
+        let after = snapshots.take(&song);
+
+        let changeset = diff(
+            snapshots.get(before).unwrap(),
+            snapshots.get(after).unwrap(),
+        );
+        let ids = |events: &[Event]| events.iter().map(Event::get_id).collect::<Vec<_>>();
+        assert_eq!(ids(&changeset.added), vec![added]);
+        assert_eq!(ids(&changeset.updated), vec![moved]);
+        assert_eq!(ids(&changeset.removed), vec![removed]);
+        assert_eq!(changeset.added_tracks, vec![other_track_id]);
+        assert!(changeset.updated_tracks.is_empty());
+
+        restore(&mut song, snapshots.get(before).unwrap());
+        assert!(!song.get_changes().is_empty());
+        assert!(diff(snapshots.get(before).unwrap(), &song).is_empty());
+        assert!(song.get_event(&kept).is_some());
+        assert_eq!(song.bpm, 120.0);
+
+        assert!(snapshots.remove(before));
+        assert!(snapshots.get(before).is_err());
+    }
+}
@@ -1,39 +1,358 @@
+use super::{
+    compact::CompactionReport,
+    end_of_song::EndOfSongPolicy,
+    groove::{Groove, GroovePool},
+    group::EventGroups,
+    loop_region::LoopRegion,
+    performance::PerformanceProfiles,
+    section::markers,
+    settings::SongSettings,
+};
 use crate::{
-    event::event::{Event, EventUpdater},
-    shared::{id::Id, unit::time::Ticks},
-    track::track::{Track, TrackVec},
+    change::{
+        change::{Change, Changeset},
+        journal::{Journal, JournalEntry},
+        revision::Revisions,
+    },
+    event::{
+        event::{Event, EventUpdater},
+        index::{EventArena, EventIndex, IndexedEvents},
+        key_signature::KeySignature,
+        marker::Marker,
+        time_signature::TimeSignature,
+        timestamp::EventTimestamps,
+    },
+    midi::{
+        import::{start_midi_import, MidiImport, MidiImportJob, MidiImportOptions},
+        preview::MidiPreview,
+        smf::MidiError,
+    },
+    shared::{
+        error::StoreError,
+        handle::Handles,
+        id::{Id, IdStrategy},
+        meta::Meta,
+        unit::time::Ticks,
+    },
+    track::track::{check_events, Track, TrackRecord, TrackVec, TrackWithEvents},
 };
-use std::{
-    cell::RefCell,
-    collections::{BTreeMap, HashMap, HashSet},
-    vec,
+#[cfg(feature = "wasm")]
+use crate::{
+    event::event::JsEvent, shared::js::to_js_value, song::group::EventGroup, track::track::JsTrack,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(typescript_custom_section)]
 const TS_SONG_INTERFACE: &'static str = r#"
 export interface Song {
   title: string;
   ppq: number;
+  /** Where the song ends under its `endOfSongPolicy`. */
   endOfSong: number;
+  endOfSongPolicy: EndOfSongPolicy;
+  /** Fixed tempo used to convert between ticks and seconds. */
+  bpm: number;
   tracks: Track[];
+  /** The song's markers in ticks order; they belong to no track. */
+  markers: Marker[];
+  grooves: Groove[];
+  groups: EventGroup[];
+  /** The app's preferences for this song, see `Store.setSetting`. */
+  settings: Record<string, unknown>;
+  loop: LoopRegion | null;
+}
+
+/** A song open in the store, see `Store.listSongs`. */
+export interface SongEntry {
+  id: string;
+  title: string;
+  /** Whether the store's methods without a song id act on this song. */
+  active: boolean;
+}
+
+export interface EventsFilter {
+  trackIds?: string[];
+}
+
+export interface TicksRange {
+  startTicks: number;
+  endTicks: number;
+  /** Also include events that start before the range but are still sounding in it. */
+  withinDuration?: boolean;
 }
 "#;
 
-#[derive(Clone)]
-pub(crate) struct GetEventsFilter {
-    track_ids: Option<Vec<Id>>,
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventsFilter {
+    pub track_ids: Option<Vec<Id>>,
+}
+
+impl GetEventsFilter {
+    /// Whether the filter lets `event` through.
+    pub fn matches(&self, event: &Event) -> bool {
+        self.track_ids.as_ref().is_none_or(|track_ids| {
+            event
+                .get_track_id()
+                .is_some_and(|track_id| track_ids.contains(&track_id))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "TicksRangeFields")]
+pub struct TicksRange {
+    pub start_ticks: Ticks,
+    pub end_ticks: Ticks,
+    pub within_duration: bool,
 }
 
+/// A `TicksRange` as JS passes it, before the bounds are checked.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TicksRangeFields {
+    start_ticks: Ticks,
+    end_ticks: Ticks,
+    #[serde(default)]
+    within_duration: bool,
+}
+
+impl TryFrom<TicksRangeFields> for TicksRange {
+    type Error = String;
+
+    fn try_from(fields: TicksRangeFields) -> Result<Self, Self::Error> {
+        if fields.end_ticks < fields.start_ticks {
+            return Err("End ticks must not be before the start ticks".to_string());
+        }
+        Ok(TicksRange {
+            start_ticks: fields.start_ticks,
+            end_ticks: fields.end_ticks,
+            within_duration: fields.within_duration,
+        })
+    }
+}
+
+/// An event as query results return it, see `Event` in TypeScript.
+#[cfg(feature = "wasm")]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JsSongEvent<'a> {
+    #[serde(flatten)]
+    event: JsEvent<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified_at: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    handle: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<&'a Meta>,
+}
+
+/// A song as `getSong` returns it, see `Song` in TypeScript.
+#[cfg(feature = "wasm")]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsSong<'a> {
+    title: &'a str,
+    ppq: u32,
+    end_of_song: Ticks,
+    end_of_song_policy: &'a EndOfSongPolicy,
+    bpm: f64,
+    tracks: Vec<JsTrack<'a, JsSongEvent<'a>>>,
+    markers: Vec<JsSongEvent<'a>>,
+    grooves: &'a GroovePool,
+    groups: Vec<EventGroup>,
+    settings: &'a SongSettings,
+    #[serde(rename = "loop")]
+    loop_region: Option<LoopRegion>,
+}
+
+/// Version of the layout songs are serialized to. JSON of older versions loads as the fields
+/// added since have defaults; binaries are migrated first, see `persistence::migrate`. Loading
+/// refuses newer versions.
+pub const SONG_FORMAT_VERSION: u32 = 8;
+
+/// A song serializes to what it holds; the journal, undo history, handles and takes are left
+/// out, and the indexes are rebuilt on load.
 #[derive(Clone)]
 pub struct Song {
-    pub(crate) title: String,
-    pub(crate) ppq: u32,
-    pub(crate) end_of_song: Ticks,
+    pub title: String,
+    pub ppq: u32,
+    pub end_of_song_policy: EndOfSongPolicy,
+    /// The song's tempo. There are no tempo events yet, so it holds for the whole song.
+    pub bpm: f64,
     tracks: TrackVec,
-    events: HashMap<Id, Event>,
-    ticks_index: BTreeMap<Ticks, HashSet<Id>>,
-    end_ticks_index: BTreeMap<Ticks, HashSet<Id>>,
+    /// The only copy of each event. The song's index and its tracks' hold slots into it.
+    events: EventArena,
+    /// The slots of all events, for song-wide queries.
+    event_index: EventIndex,
+    time_signature_index: BTreeSet<(Ticks, Id)>,
+    key_signature_index: BTreeSet<(Ticks, Id)>,
+    marker_index: BTreeSet<(Ticks, Id)>,
+    pub grooves: GroovePool,
+    pub groups: EventGroups,
+    pub profiles: PerformanceProfiles,
+    pub settings: SongSettings,
+    pub loop_region: Option<LoopRegion>,
+    changes: Vec<Change>,
+    journal: Journal,
+    revisions: Revisions,
+    timestamps: HashMap<Id, EventTimestamps>,
+    handles: Handles,
+    /// Insertion order of the events, kept across updates.
+    sequences: HashMap<Id, u64>,
+    next_sequence: u64,
+    /// MIDI channels of events imported from SMF, kept across updates.
+    channels: HashMap<Id, u8>,
+    /// Ids of the muted events and event groups, kept across updates.
+    pub muted: HashSet<Id>,
+    /// The app's data on events, kept across updates and removals so undoing brings it back.
+    pub event_meta: HashMap<Id, Meta>,
+    /// Per track, the sequence of the first event of each take being cycle-recorded on it.
+    take_starts: HashMap<Id, Vec<u64>>,
+}
+
+impl Serialize for Song {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut song = serializer.serialize_struct("Song", 16)?;
+        song.serialize_field("version", &SONG_FORMAT_VERSION)?;
+        song.serialize_field("title", &self.title)?;
+        song.serialize_field("ppq", &self.ppq)?;
+        song.serialize_field("bpm", &self.bpm)?;
+        song.serialize_field("endOfSongPolicy", &self.end_of_song_policy)?;
+        song.serialize_field("grooves", &self.grooves)?;
+        song.serialize_field("settings", &self.settings)?;
+        song.serialize_field("loop", &self.loop_region)?;
+        let tracks: Vec<TrackWithEvents> = self
+            .tracks
+            .iter()
+            .map(|track| TrackWithEvents {
+                track,
+                events: self.track_events(track).iter().collect(),
+            })
+            .collect();
+        song.serialize_field("tracks", &tracks)?;
+        song.serialize_field("channels", &self.channels)?;
+        song.serialize_field("timestamps", &self.timestamps)?;
+        song.serialize_field("groups", &self.groups)?;
+        song.serialize_field("muted", &self.muted)?;
+        song.serialize_field("profiles", &self.profiles)?;
+        let event_meta: BTreeMap<&Id, &Meta> = self
+            .event_meta
+            .iter()
+            .filter(|(event_id, _)| self.events.contains(event_id))
+            .collect();
+        song.serialize_field("eventMeta", &event_meta)?;
+        song.serialize_field("markers", &markers(self))?;
+        song.end()
+    }
+}
+
+/// A serialized song, before its tracks are added back.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SongRecord {
+    version: u32,
+    title: String,
+    ppq: u32,
+    bpm: f64,
+    end_of_song_policy: EndOfSongPolicy,
+    grooves: GroovePool,
+    settings: SongSettings,
+    #[serde(rename = "loop")]
+    loop_region: Option<LoopRegion>,
+    tracks: Vec<TrackRecord>,
+    channels: HashMap<Id, u8>,
+    timestamps: HashMap<Id, EventTimestamps>,
+    #[serde(default)]
+    groups: EventGroups,
+    #[serde(default)]
+    muted: HashSet<Id>,
+    #[serde(default)]
+    profiles: PerformanceProfiles,
+    #[serde(default)]
+    event_meta: HashMap<Id, Meta>,
+    #[serde(default)]
+    markers: Vec<Marker>,
+}
+
+impl<'de> Deserialize<'de> for Song {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Song::try_from(SongRecord::deserialize(deserializer)?).map_err(StoreError::into_de_error)
+    }
+}
+
+impl TryFrom<SongRecord> for Song {
+    type Error = StoreError;
+
+    fn try_from(record: SongRecord) -> Result<Self, Self::Error> {
+        if record.version == 0 || record.version > SONG_FORMAT_VERSION {
+            return Err(StoreError::invalid(format!(
+                "Song format version {} is not supported",
+                record.version
+            )));
+        }
+        if record.ppq == 0 {
+            return Err(StoreError::invalid("Song ppq must be positive"));
+        }
+
+        let mut song = Song::new(record.title, record.ppq);
+        song.bpm = record.bpm;
+        song.end_of_song_policy = record.end_of_song_policy;
+        song.grooves = record.grooves;
+        song.groups = record.groups;
+        song.profiles = record.profiles;
+        song.settings = record.settings;
+        song.loop_region = record.loop_region;
+        let mut markers = record.markers;
+        let mut tracks = record
+            .tracks
+            .into_iter()
+            .map(|mut track| {
+                if record.version < 8 {
+                    markers.extend(track.take_markers());
+                }
+                track.into_parts().map_err(StoreError::invalid)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        tracks.sort_by_key(|(track, _)| track.order());
+        for (track, events) in tracks {
+            if song.get_track(&track.id).is_some() {
+                return Err(StoreError::invalid(format!(
+                    "Track {} appears twice",
+                    track.id
+                )));
+            }
+            if let Some(event) = events
+                .iter()
+                .find(|event| song.get_event(&event.get_id()).is_some())
+            {
+                return Err(StoreError::invalid(format!(
+                    "Event {} appears twice",
+                    event.get_id()
+                )));
+            }
+            song.add_track_with_events(track, events)?;
+        }
+        for marker in markers {
+            song.add_event(Event::Marker(marker))?;
+        }
+        song.take_changes();
+        song.channels = record.channels;
+        song.timestamps = record.timestamps;
+        song.muted = record.muted;
+        song.event_meta = record.event_meta;
+        Ok(song)
+    }
 }
 
 impl Song {
@@ -41,316 +360,1257 @@ impl Song {
         Song {
             title,
             ppq,
-            end_of_song: Ticks::new(0),
+            end_of_song_policy: EndOfSongPolicy::default(),
+            bpm: 120.0,
             tracks: TrackVec::new(),
-            events: HashMap::new(),
-            ticks_index: BTreeMap::new(),
-            end_ticks_index: BTreeMap::new(),
+            events: EventArena::default(),
+            event_index: EventIndex::default(),
+            time_signature_index: BTreeSet::new(),
+            key_signature_index: BTreeSet::new(),
+            marker_index: BTreeSet::new(),
+            grooves: GroovePool::default(),
+            groups: EventGroups::default(),
+            profiles: PerformanceProfiles::default(),
+            settings: SongSettings::default(),
+            loop_region: None,
+            changes: Vec::new(),
+            journal: Journal::default(),
+            revisions: Revisions::default(),
+            timestamps: HashMap::new(),
+            handles: Handles::default(),
+            sequences: HashMap::new(),
+            next_sequence: 0,
+            channels: HashMap::new(),
+            muted: HashSet::new(),
+            event_meta: HashMap::new(),
+            take_starts: HashMap::new(),
         }
     }
 
-    pub(crate) fn get_track(&self, track_id: &Id) -> Option<&Track> {
-        self.tracks.iter().find(|track| track.id == *track_id)
+    /// A copy of what the song holds, without its pending changes, journal or handles.
+    pub fn snapshot(&self) -> Song {
+        Song {
+            title: self.title.clone(),
+            ppq: self.ppq,
+            end_of_song_policy: self.end_of_song_policy,
+            bpm: self.bpm,
+            tracks: self.tracks.clone(),
+            events: self.events.clone(),
+            event_index: self.event_index.clone(),
+            time_signature_index: self.time_signature_index.clone(),
+            key_signature_index: self.key_signature_index.clone(),
+            marker_index: self.marker_index.clone(),
+            grooves: self.grooves.clone(),
+            groups: self.groups.clone(),
+            profiles: self.profiles.clone(),
+            settings: self.settings.clone(),
+            loop_region: self.loop_region,
+            changes: Vec::new(),
+            journal: Journal::default(),
+            revisions: Revisions::default(),
+            timestamps: self.timestamps.clone(),
+            handles: Handles::default(),
+            sequences: self.sequences.clone(),
+            next_sequence: self.next_sequence,
+            channels: self.channels.clone(),
+            muted: self.muted.clone(),
+            event_meta: self.event_meta.clone(),
+            take_starts: self.take_starts.clone(),
+        }
     }
 
-    fn get_track_mut(&mut self, track_id: &Id) -> Option<&mut Track> {
-        self.tracks.iter_mut().find(|track| track.id == *track_id)
+    /// Loads a Standard MIDI File into a new song titled after its first named track. The song
+    /// takes the file's ppq unless `options.ppq` is set, and its time signatures and markers are
+    /// imported unless the options say otherwise, and it plays at the file's first tempo. The
+    /// song starts with no pending changes.
+    pub fn from_smf(
+        ids: &mut IdStrategy,
+        bytes: &[u8],
+        options: MidiImportOptions,
+    ) -> Result<(Song, MidiImport), MidiError> {
+        let (mut song, mut job) = Song::start_from_smf(ids, bytes, options)?;
+        job.step(&mut song, usize::MAX);
+        song.take_changes();
+        Ok((song, job.import))
     }
 
-    pub(crate) fn get_tracks(&self) -> &TrackVec {
-        &self.tracks
+    /// Starts a `from_smf` whose events are left to the returned job. The song holds its tracks
+    /// as pending changes until the caller takes them.
+    pub fn start_from_smf(
+        ids: &mut IdStrategy,
+        bytes: &[u8],
+        mut options: MidiImportOptions,
+    ) -> Result<(Song, MidiImportJob), MidiError> {
+        options.time_signatures.get_or_insert(true);
+        options.markers.get_or_insert(true);
+        let preview = MidiPreview::from_bytes(bytes)?;
+        let title = preview
+            .tracks
+            .iter()
+            .find_map(|track| track.name.clone())
+            .unwrap_or_else(|| "Untitled".to_string());
+
+        let mut song = Song::new(title, options.ppq.unwrap_or(preview.ppq));
+        if let Some((_, bpm)) = preview.tempo_changes().first() {
+            song.bpm = *bpm;
+        }
+        let mut job = start_midi_import(&mut song, ids, bytes, options)?;
+        job.new_song = true;
+        Ok((song, job))
     }
 
-    pub(crate) fn add_track(&mut self, track: Track) -> &Track {
-        let current_track_count = self.tracks.len();
-        self.tracks.push(track);
-        self.tracks.get(current_track_count).unwrap()
+    pub fn ticks_to_seconds(&self, ticks: Ticks) -> f64 {
+        ticks.as_f64() / self.ppq as f64 * 60.0 / self.bpm
     }
 
-    pub(crate) fn remove_track(&mut self, track_id: &Id) {
-        if let Some(index) = self.tracks.iter().position(|track| track.id == *track_id) {
-            if let Some(track) = self.get_track(track_id) {
-                let event_ids_to_remove: Vec<_> = track
-                    .get_events()
-                    .iter()
-                    .map(|event| event.get_id())
-                    .collect();
-
-                for event_id in event_ids_to_remove {
-                    self.remove_event(&event_id);
+    /// The tick nearest to `seconds`, clamped to the song start.
+    pub fn seconds_to_ticks(&self, seconds: f64) -> Ticks {
+        let ticks = seconds * self.bpm / 60.0 * self.ppq as f64;
+        Ticks::new(ticks.round().clamp(0.0, Ticks::MAX.as_f64()) as u64)
+    }
+
+    /// Changes recorded since the last `take_changes` or `commit`.
+    pub fn get_changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Drains the changes recorded since the last call.
+    pub fn take_changes(&mut self) -> Vec<Change> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Puts back what `snapshot` holds without recording it, keeping the song's journal and
+    /// handles. Pending changes are dropped.
+    pub fn restore_snapshot(&mut self, snapshot: Song) {
+        *self = Song {
+            journal: std::mem::take(&mut self.journal),
+            revisions: std::mem::take(&mut self.revisions),
+            handles: std::mem::take(&mut self.handles),
+            ..snapshot
+        };
+    }
+
+    /// Puts `changes` back ahead of the pending changes, for changes that were held back.
+    pub fn restore_changes(&mut self, mut changes: Vec<Change>) {
+        changes.append(&mut self.changes);
+        self.changes = changes;
+    }
+
+    /// Moves the pending changes into the journal as one entry, if there are any.
+    pub fn commit(&mut self, origin: Option<String>) -> Option<&JournalEntry> {
+        let changes = self.take_changes();
+        if changes.is_empty() {
+            return None;
+        }
+        self.journal.record(origin, changes);
+        self.revisions.record(&self.journal);
+        self.journal.last()
+    }
+
+    pub fn get_journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Numbers the song's revisions on from `revision`, which every track starts at as well.
+    pub fn rebase_revisions(&mut self, revision: u64) {
+        self.revisions.rebase(revision, &self.journal);
+    }
+
+    pub fn get_revision(&self) -> u64 {
+        self.revisions.current(&self.journal)
+    }
+
+    pub fn get_track_revision(&self, track_id: &Id) -> u64 {
+        self.revisions.of_track(track_id)
+    }
+
+    /// The net changes committed after `revision`, or `None` when it is from before the song's
+    /// revisions were last rebased.
+    pub fn get_changes_since_revision(&self, revision: u64) -> Option<Changeset> {
+        self.revisions.changes_since(revision, &self.journal)
+    }
+
+    /// Stamps the events touched by the pending changes with `now`.
+    pub fn stamp_changes(&mut self, now: f64) {
+        for change in &self.changes {
+            match change {
+                Change::EventAdded(event) => {
+                    self.timestamps.insert(
+                        event.get_id(),
+                        EventTimestamps {
+                            created_at: Some(now),
+                            modified_at: now,
+                        },
+                    );
+                }
+                Change::EventUpdated { after, .. } => {
+                    self.timestamps
+                        .entry(after.get_id())
+                        .and_modify(|timestamps| timestamps.modified_at = now)
+                        .or_insert(EventTimestamps {
+                            created_at: None,
+                            modified_at: now,
+                        });
+                }
+                Change::EventRemoved(event) => {
+                    self.timestamps.remove(&event.get_id());
                 }
+                _ => {}
             }
+        }
+    }
+
+    pub fn get_timestamps(&self, event_id: &Id) -> Option<&EventTimestamps> {
+        self.timestamps.get(event_id)
+    }
+
+    /// The number the event got when it was added, greater than that of every event added
+    /// before it. Updates keep it; an event that is removed and added back gets a new one.
+    pub fn get_sequence(&self, event_id: &Id) -> Option<u64> {
+        self.sequences.get(event_id).copied()
+    }
+
+    /// The MIDI channel the event was imported from, if it came from a MIDI file.
+    pub fn get_channel(&self, event_id: &Id) -> Option<u8> {
+        self.channels.get(event_id).copied()
+    }
 
-            self.tracks.remove(index);
+    pub fn set_channel(&mut self, event_id: Id, channel: u8) {
+        self.channels.insert(event_id, channel);
+    }
+
+    pub fn get_event_meta(&self, event_id: &Id) -> Option<&Meta> {
+        self.event_meta.get(event_id)
+    }
+
+    /// Replaces the event's meta; an empty one clears it.
+    pub fn set_event_meta(&mut self, event_id: Id, meta: Meta) {
+        if meta.is_empty() {
+            self.event_meta.remove(&event_id);
+        } else {
+            self.event_meta.insert(event_id, meta);
         }
     }
 
-    pub(crate) fn get_event(&self, event_id: &Id) -> Option<&Event> {
-        self.events.get(event_id)
+    /// The events whose meta holds the JSON value `json` under `key`, in ticks order.
+    pub fn get_events_by_meta(&self, key: &str, json: &serde_json::Value) -> Vec<&Event> {
+        let mut events: Vec<&Event> = self
+            .event_meta
+            .iter()
+            .filter(|(_, meta)| meta.matches(key, json))
+            .filter_map(|(event_id, _)| self.get_event(event_id))
+            .collect();
+        events.sort_by_key(|event| (event.get_ticks(), event.get_id()));
+        events
+    }
+
+    /// Whether the event or event group with `id` is muted.
+    pub fn is_muted(&self, id: &Id) -> bool {
+        self.muted.contains(id)
+    }
+
+    pub fn set_muted(&mut self, id: Id, muted: bool) {
+        if muted {
+            self.muted.insert(id);
+        } else {
+            self.muted.remove(&id);
+        }
+    }
+
+    /// Starts a new take on the track: events added from now on belong to it until the next
+    /// take starts. Returns the number of takes started on the track since the last merge.
+    pub fn start_take(&mut self, track_id: Id) -> usize {
+        let starts = self.take_starts.entry(track_id).or_default();
+        starts.push(self.next_sequence + 1);
+        starts.len()
+    }
+
+    /// Forgets the track's takes, returning where each of them began.
+    pub fn clear_takes(&mut self, track_id: &Id) -> Vec<u64> {
+        self.take_starts.remove(track_id).unwrap_or_default()
+    }
+
+    /// The event with its timestamps, handle and sequence, as query results return it.
+    #[cfg(feature = "wasm")]
+    fn js_song_event<'a>(&'a self, event: &'a Event) -> JsSongEvent<'a> {
+        let id = event.get_id();
+        let timestamps = self.get_timestamps(&id);
+        JsSongEvent {
+            event: JsEvent::from(event),
+            created_at: timestamps.and_then(|timestamps| timestamps.created_at),
+            modified_at: timestamps.map(|timestamps| timestamps.modified_at),
+            handle: self.handles.get(&id),
+            sequence: self.get_sequence(&id),
+            meta: self.get_event_meta(&id),
+        }
     }
 
-    fn merge_events_each_track<'a, F>(
+    #[cfg(feature = "wasm")]
+    pub fn event_to_js_object(&self, event: &Event) -> js_sys::Object {
+        to_js_value(&self.js_song_event(event)).unchecked_into()
+    }
+
+    /// Like `event_to_js_object` for each event, in a single serialization pass.
+    #[cfg(feature = "wasm")]
+    pub fn events_to_js_array<'a>(
         &self,
-        track_ids: Vec<Id>,
-        get_events_from_track_id_fn: F,
-    ) -> Vec<&'a Event>
-    where
-        F: Fn(&Id) -> Vec<&'a Event>,
-    {
-        let mut events = Vec::new();
-
-        let mut events_each_track: Vec<_> = track_ids
+        events: impl IntoIterator<Item = &'a Event>,
+    ) -> js_sys::Array {
+        let events: Vec<JsSongEvent> = events
+            .into_iter()
+            .map(|event| self.js_song_event(event))
+            .collect();
+        to_js_value(&events).unchecked_into()
+    }
+
+    /// The track in its TypeScript shape, with its events as query results return them.
+    #[cfg(feature = "wasm")]
+    pub(crate) fn js_track<'a>(&'a self, track: &'a Track) -> JsTrack<'a, JsSongEvent<'a>> {
+        let events = self
+            .track_events(track)
             .iter()
-            .map(|track_id| get_events_from_track_id_fn(track_id).into_iter())
+            .map(|event| self.js_song_event(event))
             .collect();
+        track.to_js(events)
+    }
 
-        let mut current_event_caches: Vec<Option<&Event>> = vec![None; events_each_track.len()];
+    #[cfg(feature = "wasm")]
+    pub fn track_to_js_object(&self, track: &Track) -> js_sys::Object {
+        to_js_value(&self.js_track(track)).unchecked_into()
+    }
 
-        loop {
-            let mut min_ticks = u32::MAX;
-            let mut min_event = None;
-            let mut min_track_index = None;
+    #[cfg(feature = "wasm")]
+    pub fn tracks_to_js_array(&self) -> js_sys::Array {
+        let tracks: Vec<_> = self
+            .tracks
+            .iter()
+            .map(|track| self.js_track(track))
+            .collect();
+        to_js_value(&tracks).unchecked_into()
+    }
 
-            for (track_index, events) in events_each_track.iter_mut().enumerate() {
-                let event = current_event_caches[track_index].or_else(|| events.next());
+    /// The id of the event `handle` stands for, if that event still exists.
+    pub fn resolve_handle(&self, handle: u32) -> Option<Id> {
+        self.handles.resolve(handle)
+    }
 
-                if let Some(event) = event {
-                    if event.get_ticks().as_u32() < min_ticks {
-                        min_ticks = event.get_ticks().as_u32();
-                        min_event = Some(event);
-                        min_track_index = Some(track_index);
-                    }
+    pub fn get_track(&self, track_id: &Id) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.id == *track_id)
+    }
 
-                    current_event_caches[track_index] = Some(event);
-                }
-            }
+    /// Whether the track sounds given its mute and every track's solo, or `None` for an unknown
+    /// track. A soloed track always sounds, even when muted, and silences the tracks that are
+    /// not soloed: only those of its group when it has one, all of them otherwise.
+    pub fn is_track_audible(&self, track_id: &Id) -> Option<bool> {
+        let track = self.get_track(track_id)?;
+        if track.mix.solo {
+            return Some(true);
+        }
+        if track.mix.mute {
+            return Some(false);
+        }
 
-            match (min_track_index, min_event) {
-                (Some(min_track_index), Some(min_event)) => {
-                    events.push(min_event);
-                    current_event_caches[min_track_index] = None;
-                }
-                (None, None) => break,
-                _ => panic!("unexpected"),
+        let silenced = self.tracks.iter().any(|other| {
+            other.mix.solo && (other.mix.group.is_none() || other.mix.group == track.mix.group)
+        });
+        Some(!silenced)
+    }
+
+    /// Whether the event sounds: its track is audible and neither it nor its group is muted.
+    pub fn is_event_audible(&self, event: &Event) -> bool {
+        event
+            .get_track_id()
+            .is_some_and(|track_id| self.is_track_audible(&track_id) == Some(true))
+            && !self.is_event_muted(&event.get_id())
+    }
+
+    /// Whether the event is muted, itself or through its group.
+    pub fn is_event_muted(&self, event_id: &Id) -> bool {
+        self.is_muted(event_id)
+            || self
+                .groups
+                .of_event(event_id)
+                .is_some_and(|group| self.is_muted(&group.id))
+    }
+
+    pub fn get_track_mut(&mut self, track_id: &Id) -> Option<&mut Track> {
+        self.tracks.iter_mut().find(|track| track.id == *track_id)
+    }
+
+    /// Removes the groove from the pool, along with the track settings referring to it.
+    pub fn remove_groove(&mut self, groove_id: &Id) -> Option<Groove> {
+        let groove = self.grooves.remove(groove_id)?;
+        for track in self.tracks.iter_mut() {
+            if track
+                .settings
+                .groove
+                .is_some_and(|track_groove| track_groove.groove_id == *groove_id)
+            {
+                track.settings.groove = None;
             }
         }
+        Some(groove)
+    }
 
-        events
+    pub fn get_tracks(&self) -> &TrackVec {
+        &self.tracks
     }
 
-    pub(crate) fn get_events(&self, filter: Option<GetEventsFilter>) -> Vec<&Event> {
-        if let Some(track_ids) = filter.and_then(|f| f.track_ids) {
-            return self.merge_events_each_track(track_ids, |track_id| {
-                self.get_track(track_id)
-                    .map(|track| track.get_events())
-                    .unwrap_or_default()
+    /// Adds a track without events. A track taken from another song leaves its events there;
+    /// see `add_track_with_events`.
+    pub fn add_track(&mut self, track: Track) -> &Track {
+        let track = track.without_events();
+        self.changes.push(Change::TrackAdded(track.clone()));
+        self.tracks.push(track);
+        self.tracks.last().unwrap()
+    }
+
+    /// Adds a track along with its events, which must belong to it and have ids the song does
+    /// not hold yet.
+    pub fn add_track_with_events(
+        &mut self,
+        track: Track,
+        events: Vec<Event>,
+    ) -> Result<&Track, StoreError> {
+        self.check_new_track(&track, &events)?;
+        let track_id = track.id;
+        self.add_track(track);
+        for event in events {
+            self.add_event(event)?;
+        }
+        Ok(self.get_track(&track_id).unwrap())
+    }
+
+    /// Changes the own properties of a track through `update`, recording the change. Events
+    /// must not be touched here; they have changes of their own.
+    pub fn update_track(
+        &mut self,
+        track_id: &Id,
+        update: impl FnOnce(&mut Track),
+    ) -> Option<&Track> {
+        let track = self.tracks.iter_mut().find(|track| track.id == *track_id)?;
+        let before = track.without_events();
+        update(track);
+        let after = track.without_events();
+        if before != after {
+            self.changes.push(Change::TrackUpdated {
+                before: Box::new(before),
+                after: Box::new(after),
             });
         }
+        self.get_track(track_id)
+    }
+
+    /// Like `add_track_with_events`, adding the track at `index` in the track list, or last when
+    /// `index` is past the end.
+    pub fn insert_track_at(
+        &mut self,
+        index: usize,
+        track: Track,
+        events: Vec<Event>,
+    ) -> Result<&Track, StoreError> {
+        let track_id = track.id;
+        self.add_track_with_events(track, events)?;
+        self.move_track(&track_id, index);
+        Ok(self.get_track(&track_id).unwrap())
+    }
+
+    /// Moves the track to `index` in the track list, or last when `index` is past the end,
+    /// recording the move. Returns the index it ended up at, or `None` for an unknown track.
+    pub fn move_track(&mut self, track_id: &Id, index: usize) -> Option<usize> {
+        let from = self.tracks.position(track_id)?;
+        let to = index.min(self.tracks.len() - 1);
+        if from != to {
+            let track = self.tracks.remove(from);
+            self.tracks.insert(to, track);
+            self.changes.push(Change::TrackMoved {
+                track_id: *track_id,
+                from,
+                to,
+            });
+        }
+        Some(to)
+    }
+
+    pub fn remove_track(&mut self, track_id: &Id) {
+        if let Some(index) = self.tracks.position(track_id) {
+            let event_ids_to_remove: Vec<_> = self
+                .get_track_events(track_id)
+                .iter()
+                .map(|event| event.get_id())
+                .collect();
+            for event_id in event_ids_to_remove {
+                self.remove_event(&event_id);
+            }
 
-        self.ticks_index
+            let track = self.tracks.remove(index);
+            self.changes
+                .push(Change::TrackRemoved(track.without_events()));
+        }
+    }
+
+    /// Drops the empty buckets removals leave in its tick indexes and its tracks', and shrinks
+    /// the event arena and the maps kept per event to what they hold.
+    pub fn compact(&mut self) -> CompactionReport {
+        let mut report = CompactionReport::default();
+        self.events.compact(&mut report);
+        self.event_index.compact(&mut report);
+        report.map(&mut self.timestamps);
+        report.map(&mut self.sequences);
+        report.map(&mut self.channels);
+        report.set(&mut self.muted);
+        report.map(&mut self.event_meta);
+        self.handles.compact(&mut report);
+        for track in self.tracks.iter_mut() {
+            track.compact(&mut report);
+        }
+        report
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn get_event(&self, event_id: &Id) -> Option<&Event> {
+        self.events.get(event_id)
+    }
+
+    /// The events with the given ids, in the same order; fails on the first id that is missing.
+    pub fn try_get_events(&self, event_ids: &[Id]) -> Result<Vec<&Event>, StoreError> {
+        event_ids
             .iter()
-            .map(|(_, ids)| ids.iter().filter_map(|id| self.events.get(id)))
-            .flatten()
+            .map(|event_id| {
+                self.get_event(event_id)
+                    .ok_or(StoreError::EventNotFound(*event_id))
+            })
             .collect()
     }
 
-    pub(crate) fn get_events_in_ticks_range(
+    /// The track's index over the song's events, for queries that collect nothing.
+    pub(crate) fn track_events<'a>(&'a self, track: &'a Track) -> IndexedEvents<'a> {
+        track.event_index().view(&self.events)
+    }
+
+    /// The song's index over its events.
+    fn song_events(&self) -> IndexedEvents<'_> {
+        self.event_index.view(&self.events)
+    }
+
+    /// The index to answer a query under `filter` from, with what of the filter is left to
+    /// apply: a filter naming one track is answered by that track's index alone.
+    fn filtered_events(
         &self,
+        filter: Option<GetEventsFilter>,
+    ) -> (IndexedEvents<'_>, GetEventsFilter) {
+        let filter = filter.unwrap_or_default();
+        if let Some([track_id]) = filter.track_ids.as_deref() {
+            if let Some(track) = self.get_track(track_id) {
+                return (self.track_events(track), GetEventsFilter::default());
+            }
+        }
+        (self.song_events(), filter)
+    }
+
+    /// The events of the track in `Event::order_key` order, none for an unknown track.
+    pub fn get_track_events(&self, track_id: &Id) -> Vec<&Event> {
+        self.get_track(track_id)
+            .map(|track| self.track_events(track).iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Like `get_events_in_ticks_range` for the events of one track, none for an unknown track.
+    pub fn get_track_events_in_ticks_range(
+        &self,
+        track_id: &Id,
         start_ticks: Ticks,
         end_ticks: Ticks,
         within_duration: bool,
-        filter: Option<GetEventsFilter>,
     ) -> Vec<&Event> {
-        if let Some(track_ids) = filter.clone().and_then(|f| f.track_ids) {
-            return self.merge_events_each_track(track_ids, |track_id| {
-                self.get_track(track_id)
-                    .map(|track| {
-                        track.get_events_in_ticks_range(start_ticks, end_ticks, within_duration)
-                    })
-                    .unwrap_or_default()
-            });
-        }
-
-        let got_event_ids: RefCell<HashSet<Id>> = RefCell::new(HashSet::new());
-
-        // TODO: refactor
-        let events: Vec<&Event> = self
-            .ticks_index
-            .range(start_ticks..end_ticks)
-            .map(|(_, ids)| {
-                ids.iter()
-                    .filter_map(|id| self.events.get(id))
-                    .map(|event| {
-                        if within_duration {
-                            got_event_ids.borrow_mut().insert(event.get_id());
-                        }
-                        event
-                    })
+        self.get_track(track_id)
+            .map(|track| {
+                self.track_events(track)
+                    .in_ticks_range(start_ticks, end_ticks, within_duration)
             })
-            .flatten()
-            .collect();
+            .unwrap_or_default()
+    }
 
-        if !within_duration {
-            return events;
+    /// The notes of the track outside its pitch range, in `Event::order_key` order.
+    pub fn get_out_of_range_notes(&self, track_id: &Id) -> Vec<&Event> {
+        match self.get_track(track_id) {
+            Some(track) => self
+                .track_events(track)
+                .iter()
+                .filter(|event| !track.is_in_pitch_range(event))
+                .collect(),
+            None => Vec::new(),
         }
+    }
 
-        // TODO: refactor
-        let tick = Ticks::new(1);
-        let mut has_duration_events: Vec<&Event> = self
-            .end_ticks_index
-            .range((start_ticks + tick)..)
-            .map(|(_, ids)| {
-                ids.iter()
-                    .filter_map(|id| self.events.get(id))
-                    .filter(|event| {
-                        event.get_ticks() < start_ticks
-                            && !got_event_ids.borrow().contains(&event.get_id())
-                    })
-            })
-            .flatten()
-            .collect();
+    /// The events in `Event::order_key` order.
+    pub fn get_events(&self, filter: Option<GetEventsFilter>) -> Vec<&Event> {
+        let (events, filter) = self.filtered_events(filter);
+        events
+            .iter()
+            .filter(|event| filter.matches(event))
+            .collect()
+    }
 
-        // MEMO: can it be implemented so that it does not need to be sorted?
-        has_duration_events.sort_by(|a, b| a.get_ticks().cmp(&b.get_ticks()));
+    /// The events from `ticks` on in `Event::order_key` order, or from `ticks` back in the
+    /// reverse order when `reverse`.
+    pub fn events_around(
+        &self,
+        ticks: Ticks,
+        reverse: bool,
+    ) -> Box<dyn Iterator<Item = &Event> + '_> {
+        self.song_events().around(ticks, reverse)
+    }
 
-        let mut merged_events = Vec::with_capacity(events.len() + has_duration_events.len());
+    /// Where the last event ends, or 0 without events.
+    pub fn last_event_end(&self) -> Ticks {
+        self.event_index.last_end().unwrap_or(Ticks::new(0))
+    }
 
-        let (mut i, mut j) = (0, 0);
-        while i < events.len() && j < has_duration_events.len() {
-            if events[i].get_ticks() <= has_duration_events[j].get_ticks() {
-                merged_events.push(events[i]);
-                i += 1;
-            } else {
-                merged_events.push(has_duration_events[j]);
-                j += 1;
-            }
-        }
+    /// Where the song ends under its end of song policy.
+    pub fn end_of_song(&self) -> Ticks {
+        self.end_of_song_policy.resolve(self, self.last_event_end())
+    }
 
-        merged_events.extend_from_slice(&events[i..]);
-        merged_events.extend_from_slice(&has_duration_events[j..]);
+    /// Events with a duration whose `[ticks, ticks + duration)` contains `ticks`, found through
+    /// the end ticks index. Notes come first from the lowest pitch, then other events in ticks
+    /// order.
+    pub fn get_active_events_at(
+        &self,
+        ticks: Ticks,
+        filter: Option<GetEventsFilter>,
+    ) -> Vec<&Event> {
+        let (events, filter) = self.filtered_events(filter);
+        let mut events: Vec<&Event> = events
+            .ending_after(ticks)
+            .filter(|event| event.get_ticks() <= ticks && filter.matches(event))
+            .collect();
+        events.sort_by_key(|event| {
+            let pitch = event
+                .as_note()
+                .map_or(u8::MAX, |note| note.note_number.as_u8());
+            (pitch, event.get_ticks(), event.get_id())
+        });
+        events
+    }
 
-        merged_events
+    /// Events with a duration ending in `[start_ticks, end_ticks)`, in end order.
+    pub fn iter_events_ending_in_ticks_range(
+        &self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    ) -> impl Iterator<Item = &Event> + '_ {
+        self.song_events().ending_in(start_ticks, end_ticks)
     }
 
-    fn _add_event(&mut self, event: Event) {
-        let id = event.get_id();
-        let ticks = event.get_ticks();
+    /// Events starting in `[start_ticks, end_ticks)` in `Event::order_key` order, without
+    /// collecting them. `start_ticks` must not be after `end_ticks`.
+    pub fn iter_events_in_ticks_range(
+        &self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    ) -> impl Iterator<Item = &Event> + '_ {
+        self.song_events().iter_range(start_ticks, end_ticks)
+    }
+
+    pub fn get_events_in_ticks_range(
+        &self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+        within_duration: bool,
+        filter: Option<GetEventsFilter>,
+    ) -> Vec<&Event> {
+        let (events, filter) = self.filtered_events(filter);
+        let mut events = events.in_ticks_range(start_ticks, end_ticks, within_duration);
+        events.retain(|event| filter.matches(event));
+        events
+    }
 
-        self.events.insert(id, event);
+    /// One result per range, in the order of `ranges`.
+    pub fn get_events_in_ranges(
+        &self,
+        ranges: &[TicksRange],
+        filter: Option<GetEventsFilter>,
+    ) -> Vec<Vec<&Event>> {
+        ranges
+            .iter()
+            .map(|range| {
+                self.get_events_in_ticks_range(
+                    range.start_ticks,
+                    range.end_ticks,
+                    range.within_duration,
+                    filter.clone(),
+                )
+            })
+            .collect()
+    }
 
-        self.ticks_index
-            .entry(ticks)
-            .or_insert_with(HashSet::new)
-            .insert(id);
+    /// Notes whose onsets the playhead crossed moving from `prev_ticks` to `new_ticks`, in the
+    /// order it crossed them. Moving forward covers `(prev_ticks, new_ticks]` and moving back
+    /// `[new_ticks, prev_ticks)`, so an onset under the playhead is reported only once.
+    pub fn get_scrub_events(
+        &self,
+        prev_ticks: Ticks,
+        new_ticks: Ticks,
+        filter: Option<GetEventsFilter>,
+    ) -> Vec<&Event> {
+        let tick = Ticks::new(1);
+        let mut events: Vec<&Event> = if prev_ticks <= new_ticks {
+            self.get_events_in_ticks_range(prev_ticks + tick, new_ticks + tick, false, filter)
+        } else {
+            self.get_events_in_ticks_range(new_ticks, prev_ticks, false, filter)
+        };
+
+        events.retain(|event| matches!(event, Event::Note(_)));
+        if new_ticks < prev_ticks {
+            events.reverse();
+        }
+        events
+    }
 
-        if let Some(duration) = event.get_duration() {
-            let end_ticks = ticks + duration;
+    /// Time signatures of all tracks in ticks order.
+    pub fn get_time_signatures(&self) -> Vec<&TimeSignature> {
+        self.time_signature_index
+            .iter()
+            .filter_map(|(_, id)| match self.get_event(id) {
+                Some(Event::TimeSignature(time_signature)) => Some(time_signature),
+                _ => None,
+            })
+            .collect()
+    }
 
-            self.end_ticks_index
-                .entry(end_ticks)
-                .or_insert_with(HashSet::new)
-                .insert(id);
+    /// The time signature in effect at `ticks`: the latest one at or before it, the last in id
+    /// order when several share a tick. `None` before the first one, where the song is in 4/4.
+    pub fn get_time_signature_at(&self, ticks: Ticks) -> Option<&TimeSignature> {
+        let (_, id) = self
+            .time_signature_index
+            .iter()
+            .take_while(|(time_signature_ticks, _)| *time_signature_ticks <= ticks)
+            .last()?;
+        match self.get_event(id) {
+            Some(Event::TimeSignature(time_signature)) => Some(time_signature),
+            _ => None,
         }
     }
 
-    pub(crate) fn add_event(&mut self, event: Event) -> &Event {
-        let track_id = event.get_track_id();
-        self._add_event(event);
-        self.get_track_mut(&track_id)
-            .map(|track| track.add_event(event));
-        self.get_event(&event.get_id()).unwrap()
+    /// Marker events in ticks order, then in id order when several share a tick. Markers
+    /// belong to the song, so they are in no track's events.
+    pub fn get_marker_events(&self) -> Vec<&Event> {
+        self.marker_index
+            .iter()
+            .filter_map(|(_, id)| self.get_event(id))
+            .collect()
     }
 
-    pub(crate) fn update_event(&mut self, updater: EventUpdater) -> &Event {
-        let id = updater.get_id();
-        let event = self.events.get(&id).expect_throw("Event not found");
-        let event = event.clone_with_updater(updater);
-        self.remove_event(&id);
-        self._add_event(event);
-        self.get_event(&event.get_id()).unwrap()
+    /// Marker events starting in `[start_ticks, end_ticks)`, in ticks order.
+    pub fn get_marker_events_in_ticks_range(
+        &self,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    ) -> Vec<&Event> {
+        self.marker_index
+            .iter()
+            .skip_while(|(ticks, _)| *ticks < start_ticks)
+            .take_while(|(ticks, _)| *ticks < end_ticks)
+            .filter_map(|(_, id)| self.get_event(id))
+            .collect()
     }
 
-    pub(crate) fn remove_event(&mut self, event_id: &Id) {
-        let event = self.events.get(&event_id).expect_throw("Event not found");
+    /// The key signature in effect at `ticks`, whichever track holds it: the latest one at or
+    /// before it, the last in id order when several share a tick. `None` before the first one.
+    pub fn get_key_at(&self, ticks: Ticks) -> Option<&KeySignature> {
+        let (_, id) = self
+            .key_signature_index
+            .iter()
+            .take_while(|(key_ticks, _)| *key_ticks <= ticks)
+            .last()?;
+        match self.get_event(id) {
+            Some(Event::KeySignature(key)) => Some(key),
+            _ => None,
+        }
+    }
 
-        let ticks = self
-            .get_event(event_id)
-            .expect_throw(format!("Event with id {} does not exist", event_id.to_string()).as_str())
-            .get_ticks();
+    /// Indexes a new event on the song's side: its track, handle, sequence, signatures and
+    /// markers.
+    fn register_event(&mut self, event: &Event) {
+        let id = event.get_id();
+        self.handles.assign(id);
+        self.sequences.entry(id).or_insert_with(|| {
+            self.next_sequence += 1;
+            self.next_sequence
+        });
+        let ticks = event.get_ticks();
 
-        if let Some(ids) = self.ticks_index.get_mut(&ticks) {
-            ids.remove(&event_id);
+        match event {
+            Event::TimeSignature(_) => {
+                self.time_signature_index.insert((ticks, id));
+            }
+            Event::KeySignature(_) => {
+                self.key_signature_index.insert((ticks, id));
+            }
+            Event::Marker(_) => {
+                self.marker_index.insert((ticks, id));
+            }
+            _ => {}
         }
+    }
 
-        if let Some(duration) = event.get_duration() {
-            let end_ticks = ticks + duration;
+    /// Whether `event` can go into the song as a new event: its id must be new and its track,
+    /// if it has one, must exist.
+    pub fn check_new_event(&self, event: &Event) -> Result<(), StoreError> {
+        let id = event.get_id();
+        if self.events.contains(&id) {
+            return Err(StoreError::invalid(format!("Event {} already exists", id)));
+        }
+        self.check_event_track(event)
+    }
 
-            if let Some(ids) = self.end_ticks_index.get_mut(&end_ticks) {
-                ids.remove(&event_id);
+    /// Fails when `event` is held by a track the song does not have.
+    fn check_event_track(&self, event: &Event) -> Result<(), StoreError> {
+        match event.get_track_id() {
+            Some(track_id) if self.get_track(&track_id).is_none() => {
+                Err(StoreError::TrackNotFound(track_id))
             }
+            _ => Ok(()),
         }
+    }
 
-        let track_id = event.get_track_id();
-        if let Some(track) = self.get_track_mut(&track_id) {
-            track.remove_event(&event_id);
+    /// Whether `track` can go into the song as a new track with `events`: its id and the ids of
+    /// its events must all be new, and the events must belong to it and appear once each.
+    pub fn check_new_track(&self, track: &Track, events: &[Event]) -> Result<(), StoreError> {
+        if self.get_track(&track.id).is_some() {
+            return Err(StoreError::invalid(format!(
+                "Track {} already exists",
+                track.id
+            )));
+        }
+        check_events(track.id, events).map_err(StoreError::invalid)?;
+        match events
+            .iter()
+            .find(|event| self.events.contains(&event.get_id()))
+        {
+            Some(event) => Err(StoreError::invalid(format!(
+                "Event {} already exists",
+                event.get_id()
+            ))),
+            None => Ok(()),
         }
+    }
 
-        self.events.remove(&event_id);
+    /// Adds an event to the arena, the song's index and its track's, and to the song's own
+    /// indexes.
+    fn insert_event(&mut self, event: Event) -> Result<(), StoreError> {
+        self.check_new_event(&event)?;
+        self.register_event(&event);
+        let track_id = event.get_track_id();
+        let slot = self.events.insert(event)?;
+        self.event_index.insert(&self.events, slot);
+        if let Some(track_id) = track_id {
+            let track = self.tracks.iter_mut().find(|track| track.id == track_id);
+            track
+                .expect("Checked above")
+                .event_index_mut()
+                .insert(&self.events, slot);
+        }
+        Ok(())
     }
 
-    pub(crate) fn to_js_object(&self) -> js_sys::Object {
-        let js_song = js_sys::Object::new();
+    fn delete_event(&mut self, event_id: &Id) -> Event {
+        let (slot, event) = self.events.remove(event_id).expect("Event not found");
+        self.event_index.remove(slot, &event);
+        if let Some(track_id) = event.get_track_id() {
+            let track = self.tracks.iter_mut().find(|track| track.id == track_id);
+            track
+                .expect("Track not found")
+                .event_index_mut()
+                .remove(slot, &event);
+        }
+        let ticks = event.get_ticks();
+        self.time_signature_index.remove(&(ticks, *event_id));
+        self.key_signature_index.remove(&(ticks, *event_id));
+        self.marker_index.remove(&(ticks, *event_id));
+        event
+    }
 
-        js_sys::Reflect::set(
-            &js_song,
-            &JsValue::from_str("title"),
-            &JsValue::from_str(self.title.as_str()),
-        )
-        .unwrap();
+    /// Adds an event with an id the song does not hold yet, to one of its tracks or, for a
+    /// marker, to the song itself.
+    pub fn add_event(&mut self, event: Event) -> Result<&Event, StoreError> {
+        let id = event.get_id();
+        self.insert_event(event.clone())?;
+        self.changes.push(Change::EventAdded(event));
+        Ok(self.get_event(&id).unwrap())
+    }
 
-        js_sys::Reflect::set(
-            &js_song,
-            &JsValue::from_str("ppq"),
-            &JsValue::from_f64(self.ppq as f64),
-        )
-        .unwrap();
+    pub fn update_event(&mut self, updater: EventUpdater) -> Result<&Event, StoreError> {
+        let id = updater.get_id();
+        let event = self.get_event(&id).ok_or(StoreError::EventNotFound(id))?;
+        let event = event.clone_with_updater(updater)?;
+        self.replace_event(event)
+    }
 
-        js_sys::Reflect::set(
-            &js_song,
-            &JsValue::from_str("endOfSong"),
-            &JsValue::from_f64(self.end_of_song.as_u32() as f64),
-        )
-        .unwrap();
+    /// Swaps in a new version of an existing event, which may move it to another track.
+    pub fn replace_event(&mut self, event: Event) -> Result<&Event, StoreError> {
+        let id = event.get_id();
+        if !self.events.contains(&id) {
+            return Err(StoreError::EventNotFound(id));
+        }
+        self.check_event_track(&event)?;
+
+        let before = self.delete_event(&id);
+        self.changes.push(Change::EventUpdated {
+            before,
+            after: event.clone(),
+        });
+        self.insert_event(event)?;
+        Ok(self.get_event(&id).unwrap())
+    }
 
-        js_sys::Reflect::set(
-            &js_song,
-            &JsValue::from_str("tracks"),
-            &self.get_tracks().to_js_array(),
-        )
-        .unwrap();
+    pub fn remove_event(&mut self, event_id: &Id) {
+        let event = self.delete_event(event_id);
+        self.handles.release(event_id);
+        self.sequences.remove(event_id);
+        self.channels.remove(event_id);
+        self.changes.push(Change::EventRemoved(event));
+    }
 
-        js_song
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let song = JsSong {
+            title: &self.title,
+            ppq: self.ppq,
+            end_of_song: self.end_of_song(),
+            end_of_song_policy: &self.end_of_song_policy,
+            bpm: self.bpm,
+            tracks: self
+                .tracks
+                .iter()
+                .map(|track| self.js_track(track))
+                .collect(),
+            markers: self
+                .get_marker_events()
+                .into_iter()
+                .map(|event| self.js_song_event(event))
+                .collect(),
+            grooves: &self.grooves,
+            groups: self
+                .groups
+                .iter()
+                .map(|group| group.in_song(self))
+                .collect(),
+            settings: &self.settings,
+            loop_region: self.loop_region,
+        };
+        to_js_value(&song).unchecked_into()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::event::note::{Note, NoteNumber, Velocity};
+    use crate::{
+        event::{
+            control_change::ControlChangeUpdater,
+            key_signature::KeyMode,
+            note::{Note, NoteNumber, NoteParameters, Velocity},
+        },
+        midi::test_helpers::test_smf_bytes,
+        song::{
+            recording::RecordFilter,
+            section::{add_marker, sections},
+        },
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_song_json_round_trip() {
+        let (mut song, track_id) = song_with_track();
+        song.bpm = 96.0;
+        let note_id = add_note(&mut song, track_id, 480, 240, 64, 90);
+        let cc_id = add_control_change(&mut song, track_id, 0, 7, 100);
+        song.set_channel(note_id, 9);
+        song.set_event_meta(cc_id, Meta::from([("lane", serde_json::json!({"row": 2}))]));
+        song.settings.set("velocity", Some("96".to_string()));
+        song.loop_region = Some(LoopRegion {
+            start_ticks: Ticks::new(480),
+            end_ticks: Ticks::new(1920),
+            enabled: true,
+        });
+        song.commit(None);
+
+        let json = serde_json::to_string(&song).unwrap();
+        let loaded: Song = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.title, song.title);
+        assert_eq!(loaded.bpm, 96.0);
+        assert_eq!(loaded.get_event(&note_id), song.get_event(&note_id));
+        assert_eq!(loaded.get_channel(&note_id), Some(9));
+        let in_range = loaded.get_events_in_ticks_range(Ticks::new(0), Ticks::new(600), true, None);
+        assert_eq!(in_range.len(), 2);
+        assert!(loaded.get_changes().is_empty());
+        assert!(loaded.get_journal().entries_since(0).is_empty());
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        assert!(loaded.get_event(&cc_id).is_some());
+        assert_eq!(loaded.settings.get("velocity"), Some("96"));
+        assert_eq!(loaded.loop_region, song.loop_region);
+        assert_eq!(loaded.get_event_meta(&cc_id), song.get_event_meta(&cc_id));
+        assert_eq!(
+            loaded.get_events_by_meta("lane", &serde_json::json!({"row": 2})),
+            vec![loaded.get_event(&cc_id).unwrap()]
+        );
+
+        let bad = json.replace("\"version\":8", "\"version\":9");
+        assert!(serde_json::from_str::<Song>(&bad).is_err());
+    }
+
+    #[test]
+    fn test_song_json_keeps_markers_on_the_song() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_marker(
+            &mut song,
+            &mut IdStrategy::default(),
+            Ticks::new(960),
+            "Chorus".to_string(),
+        )
+        .unwrap();
+        let json = serde_json::to_string(&song).unwrap();
+        let loaded: Song = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.get_marker_events(), song.get_marker_events());
+
+        // before version 8 a track held each marker among its events
+        let mut old: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let mut marker = old["markers"][0].take();
+        marker["trackId"] = serde_json::json!(track_id.to_string());
+        old["markers"] = serde_json::json!([]);
+        old["tracks"][0]["events"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({ "Marker": marker }));
+        old["version"] = serde_json::json!(7);
+        let loaded: Song = serde_json::from_value(old.clone()).unwrap();
+        assert_eq!(loaded.get_marker_events(), song.get_marker_events());
+        assert_eq!(loaded.get_track_events(&track_id).len(), 1);
+
+        old["version"] = serde_json::json!(8);
+        assert!(serde_json::from_value::<Song>(old).is_err());
+    }
+
+    #[test]
+    fn test_song_json_rejects_bad_ids() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 240, 64, 90);
+        let json = serde_json::to_string(&song).unwrap();
+
+        let bad = json.replace(&note_id.to_string(), "not-an-id");
+        assert_eq!(
+            StoreError::decoding("Song JSON", || serde_json::from_str::<Song>(&bad)).err(),
+            Some(StoreError::InvalidId("not-an-id".to_string()))
+        );
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let events = value["tracks"][0]["events"].as_array_mut().unwrap();
+        events.push(events[0].clone());
+        let Err(error) = serde_json::from_value::<Song>(value) else {
+            panic!("a repeated event should not load");
+        };
+        assert!(error.to_string().contains("appears twice"));
+    }
+
+    #[test]
+    fn test_song_json_rejects_ticks_past_max() {
+        let (mut song, track_id) = song_with_track();
+        add_note(&mut song, track_id, 0, 240, 64, 90);
+        let mut value = serde_json::to_value(&song).unwrap();
+        value["tracks"][0]["events"][0]["ticks"] = serde_json::json!(1u64 << 60);
+
+        assert!(matches!(
+            StoreError::decoding("Song JSON", || serde_json::from_value::<Song>(value)),
+            Err(StoreError::ValidationFailed(_))
+        ));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_js_track_events_match_query_results() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 240, 64, 90);
+        song.set_event_meta(note_id, Meta::from([("lane", serde_json::json!(1))]));
+        song.commit(None);
+
+        let track = song.get_track(&track_id).unwrap();
+        let js_track = serde_json::to_value(song.js_track(track)).unwrap();
+        let js_event = &js_track["events"][0];
+        let event = song.get_event(&note_id).unwrap();
+        assert_eq!(
+            *js_event,
+            serde_json::to_value(song.js_song_event(event)).unwrap()
+        );
+        assert!(js_event["sequence"].is_u64());
+        assert_eq!(js_event["meta"], serde_json::json!({ "lane": 1 }));
+    }
+
+    #[test]
+    fn test_update_event_rejects_other_kinds() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 240, 64, 90);
+        song.take_changes();
+
+        let updater = EventUpdater::ControlChange(ControlChangeUpdater {
+            id: note_id,
+            ticks: Some(Ticks::new(480)),
+            controller: None,
+            value: None,
+            track_id: None,
+        });
+        assert!(matches!(
+            song.update_event(updater),
+            Err(StoreError::ValidationFailed(_))
+        ));
+        assert_eq!(song.get_event(&note_id).unwrap().get_ticks(), Ticks::new(0));
+        assert!(song.get_changes().is_empty());
+    }
+
+    #[test]
+    fn test_check_new_track() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 0, 240, 64, 90);
+        let note = song.get_event(&note_id).unwrap().clone();
+
+        assert!(song.check_new_track(&Track::new(track_id), &[]).is_err());
+        let other_id = Id::new();
+        let copy = vec![note.clone().with_track_id(other_id)];
+        assert_eq!(
+            song.check_new_track(&Track::new(other_id), &copy),
+            Err(StoreError::invalid(format!(
+                "Event {} already exists",
+                note_id
+            )))
+        );
+        let fresh = note.with_id(Id::new()).with_track_id(other_id);
+        assert_eq!(
+            song.check_new_track(&Track::new(other_id), &[fresh]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_track_order_round_trip() {
+        let mut song = Song::new("test".to_string(), 480);
+        let ids: Vec<Id> = (0..3).map(|_| Id::new()).collect();
+        for id in &ids {
+            song.add_track(Track::new(*id));
+        }
+        song.move_track(&ids[2], 0);
+        let orders: Vec<(Id, usize)> = song
+            .get_tracks()
+            .iter()
+            .map(|track| (track.id, track.order()))
+            .collect();
+        assert_eq!(orders, vec![(ids[2], 0), (ids[0], 1), (ids[1], 2)]);
+
+        let json = serde_json::to_string(&song).unwrap();
+        let loaded: Song = serde_json::from_str(&json).unwrap();
+        let loaded_ids: Vec<Id> = loaded.get_tracks().iter().map(|track| track.id).collect();
+        assert_eq!(loaded_ids, vec![ids[2], ids[0], ids[1]]);
+
+        // an explicit order wins over the position in the tracks array
+        let swapped = json.replacen("\"order\":0", "\"order\":9", 1).replacen(
+            "\"order\":2",
+            "\"order\":0",
+            1,
+        );
+        let loaded: Song = serde_json::from_str(&swapped).unwrap();
+        let loaded_ids: Vec<Id> = loaded.get_tracks().iter().map(|track| track.id).collect();
+        assert_eq!(loaded_ids, vec![ids[1], ids[0], ids[2]]);
+        assert_eq!(loaded.get_tracks()[2].order(), 2);
+    }
+
+    #[test]
+    fn test_song_binary_round_trip() {
+        let (mut song, track_id) = song_with_track();
+        let note_id = add_note(&mut song, track_id, 480, 240, 64, 90);
+        add_control_change(&mut song, track_id, 0, 7, 100);
+        let filter = RecordFilter {
+            channels: Some(vec![9]),
+            kinds: None,
+        };
+        song.update_track(&track_id, |track| {
+            track.settings.record_filter = Some(filter.clone())
+        });
+
+        let bytes = postcard::to_allocvec(&song).unwrap();
+        let loaded: Song = postcard::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.get_event(&note_id), song.get_event(&note_id));
+        let track = loaded.get_track(&track_id).unwrap();
+        assert_eq!(loaded.get_track_events(&track_id).len(), 2);
+        assert_eq!(track.settings.record_filter, Some(filter));
+        assert!(bytes.len() < serde_json::to_string(&song).unwrap().len());
+        assert!(postcard::from_bytes::<Song>(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_ticks_range_bounds() {
+        let range: TicksRange =
+            serde_json::from_str(r#"{"startTicks":480,"endTicks":960}"#).unwrap();
+        assert_eq!(range.end_ticks, Ticks::new(960));
+        assert!(!range.within_duration);
+        assert!(
+            serde_json::from_str::<TicksRange>(r#"{"startTicks":960,"endTicks":480}"#).is_err()
+        );
+    }
 
     #[test]
     fn test_song() {
         let song = Song::new("test".to_string(), 480);
         assert_eq!(song.title, "test");
         assert_eq!(song.ppq, 480);
-        assert_eq!(song.end_of_song, Ticks::new(0));
+        assert_eq!(song.end_of_song(), Ticks::new(0));
+    }
+
+    #[test]
+    fn test_stamp_changes() {
+        let mut song = Song::new("test".to_string(), 480);
+        let track_id = Id::new();
+        song.add_track(Track::new(track_id));
+
+        let note = Note {
+            id: Id::new(),
+            ticks: Ticks::new(0),
+            duration: Ticks::new(480),
+            velocity: Velocity::new(100),
+            note_number: NoteNumber::new(60),
+            track_id,
+            parameters: NoteParameters::new(),
+        };
+        let event_id = note.id;
+        song.add_event(Event::Note(note)).unwrap();
+        song.stamp_changes(1000.0);
+        song.commit(None);
+
+        let event = song.get_event(&event_id).unwrap().clone();
+        song.replace_event(event.with_ticks(Ticks::new(240)))
+            .unwrap();
+        song.stamp_changes(2000.0);
+        song.commit(None);
+
+        let timestamps = song.get_timestamps(&event_id).unwrap();
+        assert_eq!(timestamps.created_at, Some(1000.0));
+        assert_eq!(timestamps.modified_at, 2000.0);
+
+        song.remove_event(&event_id);
+        song.stamp_changes(3000.0);
+        assert!(song.get_timestamps(&event_id).is_none());
     }
 
     #[test]
     fn test_tracks_scenario() {
         let mut song = Song::new("test".to_string(), 480);
-        song.add_track(Track::new(Id::new(), None));
-        song.add_track(Track::new(Id::new(), None));
+        song.add_track(Track::new(Id::new()));
+        song.add_track(Track::new(Id::new()));
 
         let tracks = song.get_tracks();
         assert_eq!(tracks.len(), 2);
@@ -372,10 +1632,10 @@ mod tests {
         let mut song = Song::new("test".to_string(), 480);
 
         let track_id1 = Id::new();
-        song.add_track(Track::new(track_id1, None));
+        song.add_track(Track::new(track_id1));
 
         let track_id2 = Id::new();
-        song.add_track(Track::new(track_id2, None));
+        song.add_track(Track::new(track_id2));
 
         let event_id1 = Id::new();
         song.add_event(Event::Note(Note {
@@ -385,7 +1645,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
         song.add_event(Event::Note(Note {
             id: Id::new(),
             ticks: Ticks::new(480),
@@ -393,20 +1655,22 @@ mod tests {
             velocity: Velocity::new(90),
             note_number: NoteNumber::new(72),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         let event = song.get_event(&event_id1).unwrap();
-        assert_eq!(event.get_ticks().as_u32(), 240);
+        assert_eq!(event.get_ticks().as_u64(), 240);
 
         let events = song.get_events(None);
         assert_eq!(events.len(), 2);
-        assert_eq!(events[0].get_ticks().as_u32(), 240);
+        assert_eq!(events[0].get_ticks().as_u64(), 240);
 
         let events = song.get_events(Some(GetEventsFilter {
             track_ids: Some(vec![track_id1]),
         }));
         assert_eq!(events.len(), 1);
-        assert_eq!(events[0].get_ticks().as_u32(), 240);
+        assert_eq!(events[0].get_ticks().as_u64(), 240);
 
         song.remove_event(&event.get_id());
         let events = song.get_events(None);
@@ -418,13 +1682,13 @@ mod tests {
         let mut song = Song::new("test".to_string(), 480);
 
         let track_id1 = Id::new();
-        song.add_track(Track::new(track_id1, None));
+        song.add_track(Track::new(track_id1));
 
         let track_id2 = Id::new();
-        song.add_track(Track::new(track_id2, None));
+        song.add_track(Track::new(track_id2));
 
         let track_id3 = Id::new();
-        song.add_track(Track::new(track_id3, None));
+        song.add_track(Track::new(track_id3));
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -433,7 +1697,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -442,7 +1708,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -451,7 +1719,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -460,7 +1730,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -469,32 +1741,34 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id3,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         let events = song.get_events(None);
 
         assert_eq!(events.len(), 5);
-        assert_eq!(events[0].get_ticks().as_u32(), 0);
-        assert_eq!(events[1].get_ticks().as_u32(), 240);
-        assert_eq!(events[2].get_ticks().as_u32(), 480);
-        assert_eq!(events[3].get_ticks().as_u32(), 720);
-        assert_eq!(events[4].get_ticks().as_u32(), 960);
+        assert_eq!(events[0].get_ticks().as_u64(), 0);
+        assert_eq!(events[1].get_ticks().as_u64(), 240);
+        assert_eq!(events[2].get_ticks().as_u64(), 480);
+        assert_eq!(events[3].get_ticks().as_u64(), 720);
+        assert_eq!(events[4].get_ticks().as_u64(), 960);
 
         let events = song.get_events(Some(GetEventsFilter {
             track_ids: Some(vec![track_id1]),
         }));
 
         assert_eq!(events.len(), 2);
-        assert_eq!(events[0].get_ticks().as_u32(), 240);
-        assert_eq!(events[1].get_ticks().as_u32(), 480);
+        assert_eq!(events[0].get_ticks().as_u64(), 240);
+        assert_eq!(events[1].get_ticks().as_u64(), 480);
     }
 
     fn create_tracks_and_events(song: &mut Song) -> [Id; 2] {
         let track_id1 = Id::new();
-        song.add_track(Track::new(track_id1, None));
+        song.add_track(Track::new(track_id1));
 
         let track_id2 = Id::new();
-        song.add_track(Track::new(track_id2, None));
+        song.add_track(Track::new(track_id2));
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -503,7 +1777,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -512,7 +1788,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -521,7 +1799,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -530,7 +1810,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -539,7 +1821,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -548,7 +1832,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -557,7 +1843,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -566,7 +1854,9 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id2,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         song.add_event(Event::Note(Note {
             id: Id::new(),
@@ -575,11 +1865,41 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id: track_id1,
-        }));
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
 
         [track_id1, track_id2]
     }
 
+    #[test]
+    fn test_get_active_events_at() {
+        let (mut song, track_id) = song_with_track();
+        let other_track_id = Id::new();
+        song.add_track(Track::new(other_track_id));
+        let high = add_note(&mut song, track_id, 0, 960, 67, 100);
+        let low = add_note(&mut song, other_track_id, 480, 480, 48, 100);
+        let middle = add_note(&mut song, track_id, 240, 480, 60, 100);
+        add_note(&mut song, track_id, 0, 480, 72, 100);
+        add_control_change(&mut song, track_id, 480, 64, 127);
+
+        let ids = |events: Vec<&Event>| -> Vec<Id> {
+            events.iter().map(|event| event.get_id()).collect()
+        };
+        assert_eq!(
+            ids(song.get_active_events_at(Ticks::new(480), None)),
+            vec![low, middle, high]
+        );
+        let filter = GetEventsFilter {
+            track_ids: Some(vec![track_id]),
+        };
+        assert_eq!(
+            ids(song.get_active_events_at(Ticks::new(480), Some(filter))),
+            vec![middle, high]
+        );
+        assert!(song.get_active_events_at(Ticks::new(960), None).is_empty());
+    }
+
     #[test]
     fn test_get_events_in_ticks_range_within_duration() {
         let mut song = Song::new("test".to_string(), 480);
@@ -588,11 +1908,11 @@ mod tests {
 
         let events = song.get_events_in_ticks_range(Ticks::new(480), Ticks::new(960), true, None);
         assert_eq!(events.len(), 5);
-        assert_eq!(events[0].get_ticks().as_u32(), 0);
-        assert_eq!(events[1].get_ticks().as_u32(), 120);
-        assert_eq!(events[2].get_ticks().as_u32(), 240);
-        assert_eq!(events[3].get_ticks().as_u32(), 480);
-        assert_eq!(events[4].get_ticks().as_u32(), 959);
+        assert_eq!(events[0].get_ticks().as_u64(), 0);
+        assert_eq!(events[1].get_ticks().as_u64(), 120);
+        assert_eq!(events[2].get_ticks().as_u64(), 240);
+        assert_eq!(events[3].get_ticks().as_u64(), 480);
+        assert_eq!(events[4].get_ticks().as_u64(), 959);
 
         let events = song.get_events_in_ticks_range(
             Ticks::new(480),
@@ -603,8 +1923,8 @@ mod tests {
             }),
         );
         assert_eq!(events.len(), 2);
-        assert_eq!(events[0].get_ticks().as_u32(), 120);
-        assert_eq!(events[1].get_ticks().as_u32(), 480);
+        assert_eq!(events[0].get_ticks().as_u64(), 120);
+        assert_eq!(events[1].get_ticks().as_u64(), 480);
 
         let events = song.get_events_in_ticks_range(
             Ticks::new(480),
@@ -615,9 +1935,290 @@ mod tests {
             }),
         );
         assert_eq!(events.len(), 3);
-        assert_eq!(events[0].get_ticks().as_u32(), 0);
-        assert_eq!(events[1].get_ticks().as_u32(), 240);
-        assert_eq!(events[2].get_ticks().as_u32(), 959);
+        assert_eq!(events[0].get_ticks().as_u64(), 0);
+        assert_eq!(events[1].get_ticks().as_u64(), 240);
+        assert_eq!(events[2].get_ticks().as_u64(), 959);
+    }
+
+    #[test]
+    fn test_get_events_track_filter() {
+        let mut song = Song::new("test".to_string(), 480);
+        let [track_id1, track_id2] = self::create_tracks_and_events(&mut song);
+        let filter = |track_ids: Vec<Id>| {
+            Some(GetEventsFilter {
+                track_ids: Some(track_ids),
+            })
+        };
+
+        let all = song.get_events(None);
+        assert_eq!(song.get_events(filter(vec![track_id2, track_id1])), all);
+        assert_eq!(song.get_events(Some(GetEventsFilter::default())), all);
+        assert!(song.get_events(filter(vec![])).is_empty());
+
+        let events = song.get_events(filter(vec![track_id2, Id::new()]));
+        assert_eq!(events, song.get_events(filter(vec![track_id2])));
+        assert!(!events.is_empty());
+        assert!(events
+            .iter()
+            .all(|event| event.get_track_id() == Some(track_id2)));
+
+        let events = song.get_events_in_ticks_range(
+            Ticks::new(0),
+            Ticks::new(1920),
+            false,
+            filter(vec![Id::new()]),
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_get_events_by_meta() {
+        let (mut song, track_id) = song_with_track();
+        let later = add_note(&mut song, track_id, 960, 240, 60, 100);
+        let earlier = add_note(&mut song, track_id, 0, 240, 62, 100);
+        let removed = add_note(&mut song, track_id, 480, 240, 64, 100);
+        let untagged = add_note(&mut song, track_id, 240, 240, 65, 100);
+        let layer = |id: i64| Meta::from([("layer", serde_json::json!(id))]);
+        for event_id in [later, earlier, removed] {
+            song.set_event_meta(event_id, layer(1));
+        }
+        song.set_event_meta(untagged, layer(2));
+        song.remove_event(&removed);
+
+        let ids = |song: &Song, id: i64| -> Vec<Id> {
+            song.get_events_by_meta("layer", &serde_json::json!(id))
+                .iter()
+                .map(|event| event.get_id())
+                .collect()
+        };
+        assert_eq!(ids(&song, 1), vec![earlier, later]);
+        assert_eq!(ids(&song, 2), vec![untagged]);
+
+        song.set_event_meta(untagged, Meta::default());
+        assert!(ids(&song, 2).is_empty());
+        assert_eq!(song.get_event_meta(&untagged), None);
+    }
+
+    #[test]
+    fn test_ticks_to_seconds() {
+        let mut song = Song::new("test".to_string(), 480);
+        assert_eq!(song.ticks_to_seconds(Ticks::new(960)), 1.0);
+        assert_eq!(song.seconds_to_ticks(1.0), Ticks::new(960));
+
+        song.bpm = 90.0;
+        assert_eq!(song.ticks_to_seconds(Ticks::new(720)), 1.0);
+        assert_eq!(song.seconds_to_ticks(0.501), Ticks::new(361));
+        assert_eq!(song.seconds_to_ticks(-1.0), Ticks::new(0));
+    }
+
+    #[test]
+    fn test_get_time_signature_at() {
+        let mut song = Song::new("test".to_string(), 480);
+        let track_id = Id::new();
+        song.add_track(Track::new(track_id));
+        for (ticks, numerator) in [(1920, 3), (3360, 6)] {
+            song.add_event(Event::TimeSignature(TimeSignature {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                numerator,
+                denominator: 4,
+                track_id,
+            }))
+            .unwrap();
+        }
+
+        assert!(song.get_time_signature_at(Ticks::new(1919)).is_none());
+        let numerator_at = |ticks: u64| {
+            song.get_time_signature_at(Ticks::new(ticks))
+                .unwrap()
+                .numerator
+        };
+        assert_eq!(numerator_at(1920), 3);
+        assert_eq!(numerator_at(3359), 3);
+        assert_eq!(numerator_at(10_000), 6);
+    }
+
+    #[test]
+    fn test_get_key_at() {
+        let (mut song, track_id) = song_with_track();
+        let mut key_ids = Vec::new();
+        for (ticks, root, mode) in [(960, 2, KeyMode::Major), (1920, 9, KeyMode::Minor)] {
+            let id = Id::new();
+            song.add_event(Event::KeySignature(KeySignature {
+                id,
+                ticks: Ticks::new(ticks),
+                root,
+                mode,
+                track_id,
+            }))
+            .unwrap();
+            key_ids.push(id);
+        }
+
+        assert!(song.get_key_at(Ticks::new(959)).is_none());
+        assert_eq!(song.get_key_at(Ticks::new(960)).unwrap().root, 2);
+        assert_eq!(
+            song.get_key_at(Ticks::new(5000)).unwrap().mode,
+            KeyMode::Minor
+        );
+
+        song.remove_event(&key_ids[1]);
+        assert_eq!(song.get_key_at(Ticks::new(5000)).unwrap().id, key_ids[0]);
+    }
+
+    #[test]
+    fn test_from_smf() {
+        let (song, import) = Song::from_smf(
+            &mut IdStrategy::default(),
+            &test_smf_bytes(),
+            MidiImportOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.title, "Piano");
+        assert_eq!(song.ppq, 96);
+        assert_eq!(import.track_ids.len(), 1);
+        assert!(song.get_changes().is_empty());
+
+        assert_eq!(song.get_events(None).len(), 4);
+        let sections = sections(&song);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].marker.name, "Intro");
+        let time_signature = song.get_time_signatures()[0];
+        assert_eq!(
+            (time_signature.numerator, time_signature.denominator),
+            (3, 4)
+        );
+        assert_eq!(time_signature.track_id, import.track_ids[0]);
+    }
+
+    #[test]
+    fn test_is_track_audible() {
+        let mut song = Song::new("test".to_string(), 480);
+        let ids: Vec<Id> = (0..4).map(|_| Id::new()).collect();
+        for (index, id) in ids.iter().enumerate() {
+            let mut track = Track::new(*id);
+            if index < 2 {
+                track.mix.group = Some("drums".to_string());
+            }
+            song.add_track(track);
+        }
+        let audible = |song: &Song| -> Vec<bool> {
+            ids.iter()
+                .map(|id| song.is_track_audible(id).unwrap())
+                .collect()
+        };
+
+        song.get_track_mut(&ids[2]).unwrap().mix.mute = true;
+        assert_eq!(audible(&song), vec![true, true, false, true]);
+
+        song.get_track_mut(&ids[0]).unwrap().mix.solo = true;
+        assert_eq!(audible(&song), vec![true, false, false, true]);
+
+        song.get_track_mut(&ids[3]).unwrap().mix.solo = true;
+        assert_eq!(audible(&song), vec![true, false, false, true]);
+
+        song.get_track_mut(&ids[0]).unwrap().mix.solo = false;
+        assert_eq!(audible(&song), vec![false, false, false, true]);
+
+        song.get_track_mut(&ids[2]).unwrap().mix.solo = true;
+        assert_eq!(audible(&song), vec![false, false, true, true]);
+        assert_eq!(song.is_track_audible(&Id::new()), None);
+    }
+
+    #[test]
+    fn test_handles_follow_event_lifetime() {
+        let mut song = Song::new("test".to_string(), 480);
+        self::create_tracks_and_events(&mut song);
+        let event = song.get_events(None)[0].clone();
+        let handle = song.handles.get(&event.get_id()).unwrap();
+
+        song.replace_event(event.clone().with_ticks(Ticks::new(1)))
+            .unwrap();
+        assert_eq!(song.resolve_handle(handle), Some(event.get_id()));
+
+        song.remove_event(&event.get_id());
+        assert_eq!(song.resolve_handle(handle), None);
+    }
+
+    #[test]
+    fn test_sequences_follow_insertion_order() {
+        let mut song = Song::new("test".to_string(), 480);
+        self::create_tracks_and_events(&mut song);
+        let mut events: Vec<Event> = song.get_events(None).into_iter().cloned().collect();
+        events.sort_by_key(|event| song.get_sequence(&event.get_id()));
+        let last = song.get_sequence(&events.last().unwrap().get_id()).unwrap();
+
+        let first = events[0].clone();
+        let sequence = song.get_sequence(&first.get_id()).unwrap();
+        song.replace_event(first.clone().with_ticks(Ticks::new(1)))
+            .unwrap();
+        assert_eq!(song.get_sequence(&first.get_id()), Some(sequence));
+
+        song.remove_event(&first.get_id());
+        assert_eq!(song.get_sequence(&first.get_id()), None);
+        song.add_event(first.clone()).unwrap();
+        assert_eq!(song.get_sequence(&first.get_id()), Some(last + 1));
+    }
+
+    #[test]
+    fn test_get_scrub_events() {
+        let mut song = Song::new("test".to_string(), 480);
+        self::create_tracks_and_events(&mut song);
+
+        let ticks = |events: Vec<&Event>| {
+            events
+                .iter()
+                .map(|event| event.get_ticks().as_u64())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ticks(song.get_scrub_events(Ticks::new(120), Ticks::new(480), None)),
+            vec![240, 480]
+        );
+        assert_eq!(
+            ticks(song.get_scrub_events(Ticks::new(480), Ticks::new(120), None)),
+            vec![240, 120]
+        );
+        assert!(song
+            .get_scrub_events(Ticks::new(480), Ticks::new(480), None)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_events_in_ranges() {
+        let mut song = Song::new("test".to_string(), 480);
+
+        let [track_id1, _] = self::create_tracks_and_events(&mut song);
+
+        let ranges = [
+            TicksRange {
+                start_ticks: Ticks::new(480),
+                end_ticks: Ticks::new(960),
+                within_duration: true,
+            },
+            TicksRange {
+                start_ticks: Ticks::new(0),
+                end_ticks: Ticks::new(480),
+                within_duration: false,
+            },
+        ];
+        let filter = GetEventsFilter {
+            track_ids: Some(vec![track_id1]),
+        };
+
+        let results = song.get_events_in_ranges(&ranges, Some(filter.clone()));
+        assert_eq!(results.len(), 2);
+        for (range, events) in ranges.iter().zip(&results) {
+            let expected = song.get_events_in_ticks_range(
+                range.start_ticks,
+                range.end_ticks,
+                range.within_duration,
+                Some(filter.clone()),
+            );
+            assert_eq!(events, &expected);
+        }
     }
 
     #[test]
@@ -628,8 +2229,8 @@ mod tests {
 
         let events = song.get_events_in_ticks_range(Ticks::new(480), Ticks::new(960), false, None);
         assert_eq!(events.len(), 2);
-        assert_eq!(events[0].get_ticks().as_u32(), 480);
-        assert_eq!(events[1].get_ticks().as_u32(), 959);
+        assert_eq!(events[0].get_ticks().as_u64(), 480);
+        assert_eq!(events[1].get_ticks().as_u64(), 959);
 
         let events = song.get_events_in_ticks_range(
             Ticks::new(480),
@@ -640,7 +2241,7 @@ mod tests {
             }),
         );
         assert_eq!(events.len(), 1);
-        assert_eq!(events[0].get_ticks().as_u32(), 480);
+        assert_eq!(events[0].get_ticks().as_u64(), 480);
 
         let events = song.get_events_in_ticks_range(
             Ticks::new(480),
@@ -651,6 +2252,6 @@ mod tests {
             }),
         );
         assert_eq!(events.len(), 1);
-        assert_eq!(events[0].get_ticks().as_u32(), 959);
+        assert_eq!(events[0].get_ticks().as_u64(), 959);
     }
 }
@@ -1,158 +1,5298 @@
 use crate::{
-    event::event::{Event, EventUpdater},
-    shared::{id::Id, unit::time::Ticks},
-    song::song::Song,
-    track::track::Track,
+    analysis::{
+        key::{detect_key, pitch_class_durations},
+        tempo::{estimate_tempo, note_onsets, TempoEstimateOptions},
+    },
+    change::{change::ChangeDetail, mirror::MirrorFrame, revision::RevisionChanges},
+    event::{
+        control_change::{ControlChange, ControllerNumber, ControllerValue},
+        event::{Event, EventUpdater},
+        note::{Note, NoteNumber, NoteParameters, Velocity},
+        schema::EventKindSchema,
+        validation::{Validation, ValidationPolicy},
+    },
+    history::history::{History, HistoryStep},
+    midi::{
+        clipboard::{clipboard_from_midi, clipboard_to_midi},
+        export::{export_midi, MidiExportOptions},
+        import::{import_midi, start_midi_import, MidiImportJob, MidiImportOptions},
+        preview::MidiPreview,
+    },
+    notation::{
+        abc::{export_abc, NotationExportOptions},
+        lyric::{export_lrc, export_lyrics_midi, LyricExportOptions},
+    },
+    operation::{
+        operation::{
+            apply_last_writer_wins, apply_operations, restore_conflict_indexes, revert_changes,
+            Conflict, Operation, OperationRecord, ReceivedOperations,
+        },
+        optimistic::OptimisticUpdates,
+        queue::SyncQueue,
+        transaction::Transaction,
+    },
+    query::{
+        automation::ControllerLane,
+        buffer::{fill_playback_buffer, RECORD_WORDS},
+        collision::find_collisions,
+        cursor::PlaybackCursor,
+        filter::{query_events, EventFilter},
+        flat::flat_notes_in_ticks_range,
+        line::{extract_line, LineVoice},
+        metronome::{clicks_in_range, count_in, CountInOptions},
+        navigation::{adjacent_event, NavigationAnchor},
+        note_off::{with_note_offs, RangeItem, TicksRangeOptions},
+        phrase::find_similar_phrases,
+        pitch_class::pitch_class_info,
+        playback::{playback_events, PlaybackOptions},
+        position::{format_position, PositionFormat},
+        preview::{preview_transform, TransformDelta},
+        render::render_tracks,
+        summary::{SongStats, SongSummary, TrackStats},
+        sustain::effective_note_ends_in_range,
+        table::{event_table, EventTableOptions},
+        window::QueryWindow,
+    },
+    shared::{
+        error::{parse_id, parse_ticks, parse_ticks_range, StoreError},
+        id::{Id, IdStrategy},
+        js::to_js_value,
+        meta::Meta,
+        unit::{
+            grid::{too_fine_error, Grid},
+            length::{length_presets, NoteLength},
+            time::Ticks,
+        },
+    },
+    song::{
+        groove::Groove,
+        import_snap::{snap_events, ImportSnap, JsonImportOptions},
+        loop_region::{loop_iteration_events, LoopRegion},
+        meter::MeterMap,
+        mute::EffectiveEventState,
+        performance::PerformanceProfile,
+        ppq::rescale_ppq,
+        quota::{Quota, QuotaUsage},
+        recording::{RecordFilter, RecordInputKind, RecordingClock},
+        section::{add_marker, remove_marker, sections},
+        snapshot::{diff as diff_snapshots, restore as restore_snapshot, Snapshots},
+        song::{GetEventsFilter, Song, TicksRange},
+    },
+    track::{
+        clip::{add_clip, duplicate_clip, move_clip, remove_clip, resize_clip},
+        duplicate::duplicate_track,
+        expression::ExpressionMap,
+        freeze::FrozenTrack,
+        take::{merge_takes, TakeMerge},
+        track::{PitchRange, RangeCheck, Track, TrackDisplay, TrackSettings, TrackUpdater},
+        trim::{trim_track_to_content, TrackExtent},
+    },
+    transform::{
+        align::{align_to_grid, GridAlignOptions},
+        bulk::{
+            add_events, remove_events, transform_events, update_events, update_events_relative,
+            EventTransform, RelativeUpdater,
+        },
+        clipboard::Clipboard,
+        curve::{insert_curve, CurveLane, CurvePoint, CurveShape},
+        dedupe::{dedupe_notes, DedupeScope},
+        drag::{move_events, MoveConstraints},
+        duration::{set_durations, set_overlap_ratio, DurationChange},
+        flatten::flatten_tracks,
+        groove::apply_groove,
+        humanize::{humanize_events, HumanizeOptions},
+        parameter::set_note_parameter,
+        quantize::{quantize_events, QuantizeOptions},
+        ramp::{apply_value_line, ramp_values, RampCurve, RampTarget, ValueLineField, ValuePoint},
+        ratchet::ratchet_notes,
+        region::{duplicate_region, repeat_range},
+        repeat::repeat_events_to_fill,
+        split::{
+            merge_tracks, split_track_by_channel, split_track_by_pitch, split_track_by_velocity,
+            VelocitySplit,
+        },
+        strum::{strum_chords, StrumDirection},
+        thin::thin_controller_events,
+        transpose::{transpose_song, TransposeOptions},
+    },
 };
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
+#[wasm_bindgen(typescript_custom_section)]
+const TS_STORE_OPTIONS: &'static str = r#"
+export interface StoreOptions {
+  idStrategy?: IdStrategy;
+}
+
+/** Something a call did that the app may want to tell the user about, see `subscribeToWarnings`. */
+export type StoreWarning =
+  /** Notes added outside their track's pitch range with the `"warn"` range check. */
+  | { kind: "outOfPitchRange"; eventIds: string[] }
+  /** Parts of an undo or redo left alone because they were edited elsewhere since. */
+  | { kind: "conflicts"; conflicts: Conflict[] };
+"#;
+
 #[wasm_bindgen(typescript_custom_section)]
 const TS_STORE_CLASS: &'static str = r#"
 export class Store {
   free(): void;
 
-  constructor();
+  /**
+   * With `idStrategy`, the ids the store mints for new songs, tracks and events, including
+   * pasted, duplicated and imported ones, follow that strategy. Each store keeps its own, so
+   * stores with different strategies can live side by side. Edits that mint ids throw
+   * `invalidId` when a function strategy returns something other than a UUID.
+   */
+  constructor(options?: StoreOptions);
+
+  getSong(): Song | undefined;
+
+  /**
+   * Opens a new song and makes it the active one, which the methods without a song id act on.
+   * Songs open before stay open with their own undo history; see `setActiveSong`. Returns the
+   * new song's id.
+   */
+  createSong(title: string, ppq: number): string;
+
+  /** The songs open in the store, in the order they were opened. */
+  listSongs(): SongEntry[];
+
+  /** The id of the active song, if there is one. */
+  getActiveSongId(): string | undefined;
+
+  /**
+   * Makes the song the active one. The previously active song keeps its undo history, frozen
+   * tracks and open transaction for when it is active again; its windows, cursors and MIDI
+   * import are dropped. Mirror subscribers get the new song's snapshot, as when a song is loaded.
+   */
+  setActiveSong(songId: string): void;
+
+  /** Closes the song. Closing the active one leaves no song active until `setActiveSong`. */
+  removeSong(songId: string): void;
+
+  /**
+   * Like `getEvent` and the methods below without `Song`, acting on the open song `songId`
+   * without making it active. Its edits are recorded in its own undo history and sync queue,
+   * or held in its own open transaction, but not notified to subscribers, which follow the
+   * active song.
+   */
+  getSongEvent(songId: string, eventId: string): Event | undefined;
+
+  getSongEvents(songId: string, trackIds?: string[]): Event[];
+
+  addSongEvent(songId: string, event: Event, rangeCheck?: RangeCheck): Event;
+
+  updateSongEvent(songId: string, event: EventUpdater): Event;
+
+  removeSongEvent(songId: string, eventId: string): void;
+
+  getSongTrack(songId: string, trackId: string): Track | undefined;
+
+  getSongTracks(songId: string, includeStats?: boolean): Track[];
+
+  addSongTrack(songId: string, track: Track): Track;
+
+  updateSongTrack(songId: string, trackUpdater: TrackUpdater): Track;
+
+  removeSongTrack(songId: string, trackId: string): void;
+
+  /**
+   * The song as JSON, with its tracks, events, ids and grooves. The journal, undo history and
+   * event handles are not included.
+   */
+  toJSON(): string;
+
+  /**
+   * Replaces the song with one saved by `toJSON`, or written by another tool with `snapTolerance`
+   * to tidy its timing. Without an active song, the loaded one is opened. This can be undone.
+   */
+  loadJSON(json: string, options?: JsonImportOptions): void;
+
+  /**
+   * The song in the same shape as `toJSON`, in a compact binary encoding that is cheaper to
+   * produce and parse. Suited to posting to a worker or storing in IndexedDB.
+   */
+  toBinary(): Uint8Array;
+
+  /** Replaces the song with one saved by `toBinary`. Like `loadJSON`, this can be undone. */
+  fromBinary(bytes: Uint8Array): void;
+
+  /**
+   * Keeps a copy of the song's current state and returns its id. Snapshots are dropped when the
+   * song is replaced, or with `dropSnapshot`.
+   */
+  takeSnapshot(): SnapshotId;
+
+  /**
+   * Brings the song back to the snapshot. The events and tracks that differ are edited like any
+   * other change, so the restore is notified, synced and can be undone.
+   */
+  restoreSnapshot(id: SnapshotId): void;
+
+  /** What changed from snapshot `a` to snapshot `b`. */
+  diffSnapshots(a: SnapshotId, b: SnapshotId): SnapshotDiff;
+
+  /** Frees the snapshot. Returns whether it existed. */
+  dropSnapshot(id: SnapshotId): boolean;
+
+  /**
+   * The app's preference saved under `key` with the song, such as its default grid or last view,
+   * or `undefined` when unset.
+   */
+  getSetting(key: string): unknown;
+
+  /**
+   * Saves a JSON value under `key` with the song, or removes it when `value` is `undefined`.
+   * Settings are not song changes, so they are neither notified nor undone.
+   */
+  setSetting(key: string, value: unknown): void;
+
+  getSettings(): Record<string, unknown>;
+
+  /**
+   * Sets the loop region the transport repeats, saved with the song. Like settings, the loop is
+   * not a song change, so it is neither notified nor undone.
+   */
+  setLoop(startTicks: number, endTicks: number, enabled: boolean): LoopRegion;
+
+  /**
+   * Sets how the end of the song follows its events, for bounces and loops that need to end on a
+   * bar line. Saved with the song but not undone, like the loop. Returns the new end in ticks.
+   */
+  setEndOfSongPolicy(policy: EndOfSongPolicy): number;
+
+  getLoop(): LoopRegion | undefined;
+
+  /**
+   * The playback events of the loop's `iteration`-th pass, from 0, as `getPlaybackEvents`
+   * returns them for the loop region. Ticks count from the start of the first pass, so pass `n`
+   * starts at `n` loop lengths; notes sounding past the loop end are cut off there.
+   */
+  getEventsForLoopIteration(iteration: number, options?: PlaybackOptions): Event[];
+
+  /** Track, note and meter statistics of the song in one call. */
+  getSongSummary(): SongSummary;
+
+  /**
+   * Note, velocity and density aggregates of the events of `trackIds`, or of every track, in
+   * one call, so overviews need not fetch every event.
+   */
+  getStats(trackIds?: string[]): SongStats;
+
+  /** Sets the song's fixed tempo, used by `ticksToSeconds` and `secondsToTicks`. */
+  setBpm(bpm: number): void;
+
+  /**
+   * Changes the song's resolution. With `rescaleEvents`, every position and length in ticks is
+   * converted so the music keeps its rhythm; without it, the ticks are kept as they are and play
+   * at the new resolution. Either way the change is one undo step, and moved events are
+   * published and synced like any edit. Throws while a transaction is open.
+   */
+  setPpq(ppq: number, rescaleEvents: boolean): void;
+
+  ticksToSeconds(ticks: number): number;
+
+  /** The tick nearest to `seconds`, never before the song start. */
+  secondsToTicks(seconds: number): number;
+
+  /**
+   * `ticks` as `"5.2.240"` (bar, beat, ticks) following the song's meter changes, or as
+   * `"00:01:23.456"` at the song's tempo, so rulers, tooltips and lists agree.
+   */
+  formatPosition(ticks: number, format: PositionFormat): string;
+
+  /** Closes the active song, like `removeSong`. */
+  clearSong(): void;
+
+  /**
+   * Reverts the latest local edit: one mutating call, a group of them, or a new song replacing
+   * the previous one. Parts edited by others since are left alone. Returns false when there is
+   * nothing to undo. Throws while a transaction is open, like `redo` and `undoInTrack`.
+   */
+  undo(): boolean;
+
+  /** Replays the latest undone edit. Returns false when there is nothing to redo. */
+  redo(): boolean;
+
+  /**
+   * Reverts the track's part of the latest local edit that changed it, leaving later edits of
+   * other tracks, and the other tracks' part of that edit, in place. Looks no further back than
+   * the latest new song. `redo` replays it. Returns false when there is nothing to undo.
+   */
+  undoInTrack(trackId: string): boolean;
+
+  canUndo(): boolean;
+
+  canRedo(): boolean;
+
+  /**
+   * Holds back the changes of the following mutating calls until `commitTransaction` publishes
+   * them as one journal entry and undo step, or `rollbackTransaction` reverts them. Changes
+   * applied from elsewhere meanwhile are published as usual.
+   */
+  beginTransaction(): void;
+
+  /** Returns the journal seq of the transaction's changes, if it made any. */
+  commitTransaction(): number | undefined;
+
+  /**
+   * Puts the song back as it was at `beginTransaction`, then replays the changes applied from
+   * elsewhere since, which win over the transaction's. Those that no longer apply are reported,
+   * indexed in the order they were applied.
+   */
+  rollbackTransaction(): Conflict[];
+
+  /** Collects the edits of the following calls into one undo step until `endHistoryGroup`. */
+  beginHistoryGroup(): void;
+
+  endHistoryGroup(): void;
+
+  /**
+   * Registers `callback` to receive the net changes of every mutating call, delivered in a
+   * microtask. `detail` picks the payload: event ids, summaries, or full events (the default).
+   * The calls of an open transaction are delivered together when it commits.
+   */
+  subscribe(callback: (changes: Changes<string>) => void, detail: "ids"): number;
+  subscribe(callback: (changes: Changes<EventSummary>) => void, detail: "summary"): number;
+  subscribe(callback: (changes: Changes<Event>) => void, detail?: "full"): number;
+
+  /**
+   * Calls `callback` with the events each commit adds to the track, such as notes as they are
+   * recorded, so an input monitor can draw them without polling. Stop with `unsubscribe`.
+   */
+  subscribeToRecordedEvents(trackId: string, callback: (events: Event[]) => void): number;
+
+  /**
+   * Calls `callback` in a microtask with each warning of a call that went through anyway, such
+   * as a note added out of its track's pitch range. Stop with `unsubscribe`.
+   */
+  subscribeToWarnings(callback: (warning: StoreWarning) => void): number;
+
+  unsubscribe(subscriptionId: number): void;
+
+  /**
+   * Forwards a snapshot and then every committed changeset to `callback` in a compact binary
+   * form, for a replica store to consume with `applyMirror`. Stop with `unsubscribe`.
+   */
+  mirrorTo(callback: (frame: Uint8Array) => void): number;
+
+  applyMirror(frame: Uint8Array): void;
+
+  /** Tags subsequent mutations with `origin` (a user id, device, "import", ...) in the journal. */
+  setOrigin(origin: string | null): void;
+
+  /**
+   * Sets the clock used to stamp events with `createdAt`/`modifiedAt` when they change, or
+   * stops stamping with `null`.
+   */
+  setClock(clock: (() => number) | null): void;
+
+  /**
+   * Sets what `addEvent(s)` and `updateEvent(s)` do with a velocity or note number outside 0 to
+   * 127, a duration of 0, ticks past `maxTicks` or any of these that is not a whole number.
+   * `Strict`, the default, throws; `Clamp` rounds each value and moves it into range.
+   */
+  setValidationPolicy(policy: ValidationPolicy, maxTicks?: number): void;
+
+  /**
+   * Limits how many events and tracks the song may hold; omitted limits are lifted. Edits and
+   * loads that would go past a limit throw a `quotaExceeded` error and change nothing. A song
+   * already past a lowered limit can still shrink.
+   */
+  setQuota(maxEvents?: number, maxTracks?: number): void;
+
+  /** The song's event and track counts next to the limits set with `setQuota`. */
+  getQuotaUsage(): QuotaUsage;
+
+  /**
+   * Frees what removals leave behind in the song's indexes, e.g. after a long editing session.
+   * Queries and edits work the same before and after.
+   */
+  compact(): CompactionReport;
+
+  getJournal(sinceSeq?: number): Changes<Event>[];
+
+  /**
+   * A number that grows with every change to the song's events and tracks committed to the
+   * journal. Loading, switching or replacing the song moves it on as well, so a revision is
+   * never reused within the store. Other settings do not move it.
+   */
+  getRevision(): number;
+
+  /** The revision of the latest change to the track or its events. */
+  getTrackRevision(trackId: string): number;
+
+  /**
+   * The net changes to events after `revision`: events added or updated since as they are now,
+   * and the ids of the ones removed. Null when the revision is from before the song was loaded,
+   * switched to or replaced, in which case everything should be read again.
+   */
+  getEventsChangedSince(revision: number): RevisionChanges | null;
+
+  /**
+   * Local changes not yet acknowledged by the server, grouped by journal seq. Each op carries an
+   * `opId` that stays the same across calls.
+   */
+  getPendingOps(): PendingOps[];
+
+  markOpsSynced(upToSeq: number): void;
+
+  /**
+   * Applies server ops underneath the pending local ops, which are replayed on top. Pending ops
+   * that no longer apply are dropped from the queue. Ops with an `opId` received before are
+   * skipped.
+   */
+  receiveOps(ops: Operation[], origin?: string): RebaseReport;
+
+  getTrack(trackId: string): Track | undefined;
+
+  /**
+   * The tracks in their `order`, which `toJSON`, `loadJSON` and MIDI export follow as well. With
+   * `includeStats`, each track also carries its `stats`.
+   */
+  getTracks(includeStats?: boolean): Track[];
+
+  /** Where the track's events start and end, or `null` for a track without events. */
+  getTrackExtent(trackId: string): TrackExtent | null;
+
+  /**
+   * Removes the controller changes and raw MIDI messages left at or after the end of the
+   * track's other events, such as lingering resets, in one commit, e.g. before exporting the
+   * track on its own. Returns the ids of the removed events.
+   */
+  trimTrackToContent(trackId: string): string[];
+
+  addTrack(track: Track): Track;
+
+  removeTrack(trackId: string): void;
+
+  /**
+   * Adds `track`, or a new empty track, at `index` of the track list, or last when `index` is
+   * past the end.
+   */
+  insertTrackAt(index: number, track?: Track): Track;
+
+  /**
+   * Moves the track to `newIndex` of the track list, or last when `newIndex` is past the end.
+   * Recorded as a change, so subscribers get a `trackUpdated` notification and it can be undone.
+   */
+  moveTrack(trackId: string, newIndex: number): void;
+
+  /**
+   * Adds a copy of the track and all its events right after it, with new ids for the track and
+   * every event. Returns the new track.
+   */
+  duplicateTrack(trackId: string): Track;
+
+  /** The clips of `trackId`, or of every track in track order, each track's in creation order. */
+  getClips(trackId?: string): Clip[];
+
+  /**
+   * Adds a clip of `length` at `startTicks` to the track, holding `eventIds` or else the track's
+   * events starting within it. An event is in one clip at most, so they leave other clips.
+   * Clips are track properties: editing them is notified as a track update and can be undone.
+   */
+  addClip(trackId: string, startTicks: number, length: number, eventIds?: string[]): Clip;
+
+  /** Moves the clip to `startTicks`, moving its events by as much, in one commit. */
+  moveClip(clipId: string, startTicks: number): Clip;
+
+  /**
+   * Sets the clip's bounds without moving its events. Events no longer starting within it
+   * leave the clip but stay on the track.
+   */
+  resizeClip(clipId: string, startTicks: number, length: number): Clip;
+
+  /** Copies the clip and its events to `startTicks`, by default right after the clip. */
+  duplicateClip(clipId: string, startTicks?: number): Clip;
+
+  /** Removes the clip, and with `removeEvents` the events it holds. */
+  removeClip(clipId: string, removeEvents?: boolean): void;
+
+  setTrackSettings(trackId: string, settings: TrackSettings): Track;
+
+  setTrackPitchRange(trackId: string, pitchRange: PitchRange | null): Track;
+
+  /** Sets the semitones a full pitch bend reaches on the track, from 1 to 127. */
+  setTrackPitchBendRange(trackId: string, semitones: number): Track;
+
+  /**
+   * Sets the keyswitches for the track's articulations, used by `getPlaybackEvents` and
+   * `exportMidi` with `keyswitches`.
+   */
+  setTrackExpressionMap(trackId: string, expressionMap: ExpressionMap): Track;
+
+  /** The 14-bit pitch bend value, 8192 at rest, that bends the track's instrument by `cents`. */
+  bendValueForSemitones(trackId: string, cents: number): number;
+
+  /** The bend in cents that a 14-bit pitch bend value gives the track's instrument. */
+  centsForBendValue(trackId: string, value: number): number;
+
+  setTrackMute(trackId: string, mute: boolean): Track;
+
+  setTrackSolo(trackId: string, solo: boolean): Track;
+
+  setTrackGroup(trackId: string, group: string | null): Track;
+
+  /** Sets the track's gain, from 0 to 1. */
+  setTrackVolume(trackId: string, volume: number): Track;
+
+  /** Sets the track's stereo position, from -1 (left) to 1 (right). */
+  setTrackPan(trackId: string, pan: number): Track;
+
+  setTrackDisplay(trackId: string, display: TrackDisplay): Track;
+
+  /**
+   * Sets where the track plays, such as a MIDI port or plugin instance id. Unlike the other
+   * track setters this is recorded as a change: subscribers get a `trackUpdated` notification
+   * and it can be undone.
+   */
+  setTrackOutput(trackId: string, output: string | null): Track;
+
+  /**
+   * Sets the track's name, color, MIDI channel, program and meta. Like `setTrackOutput` this is
+   * recorded as a change, so it is notified and can be undone.
+   */
+  updateTrack(trackUpdater: TrackUpdater): Track;
+
+  getGrooves(): Groove[];
+
+  /** Adds the groove to the song's groove pool, or replaces the one with the same id. */
+  setGroove(groove: Groove): Groove;
+
+  /** Removes the groove from the pool and from the settings of the tracks using it. */
+  removeGroove(grooveId: string): void;
+
+  getPerformanceProfiles(): PerformanceProfile[];
+
+  /**
+   * Adds the profile to the song, or replaces the one with the same id. Like grooves, profiles
+   * and their assignments are not part of the undo history.
+   */
+  setPerformanceProfile(profile: PerformanceProfile): PerformanceProfile;
+
+  /** Removes the profile from the song and from the tracks playing with it. */
+  removePerformanceProfile(profileId: string): void;
+
+  /** The profile `getPlaybackEvents` varies the track's events with. */
+  getTrackPerformanceProfile(trackId: string): PerformanceProfile | undefined;
+
+  /** Plays the track with the profile, or with none when `profileId` is null. */
+  setTrackPerformanceProfile(trackId: string, profileId: string | null): void;
+
+  /** The song's event groups, in the order they were created. */
+  getGroups(): EventGroup[];
+
+  /**
+   * Groups the events under `name`, taking them out of the groups they were in; groups left
+   * empty are removed. Like grooves, groups are not part of the undo history.
+   */
+  createGroup(name: string, eventIds: string[]): EventGroup;
+
+  /** The group holding the event, e.g. to outline its members in the editor. */
+  getGroup(eventId: string): EventGroup | undefined;
+
+  /** Ungroups the group's events, leaving them in the song. */
+  removeGroup(groupId: string): void;
+
+  /**
+   * Mutes or unmutes the events, leaving them out of playback. Like groups, mutes are saved with
+   * the song but are not part of the undo history.
+   */
+  setEventsMuted(eventIds: string[], muted: boolean): void;
+
+  /** Mutes or unmutes the group, which silences all of its events. */
+  setGroupMuted(groupId: string, muted: boolean): void;
+
+  /**
+   * Whether the event sounds, with what decides it: its own mute, its group's mute and its
+   * track's mute and solo. Playback leaves out the events that are not `audible`.
+   */
+  getEffectiveEventState(eventId: string): EffectiveEventState;
+
+  /**
+   * Moves the events by `amount` (0 to 1, default 1) of the offsets of the groove's nearest
+   * step, and changes note velocities likewise. With `stepTicks` of `ppq / 4` each step is a
+   * sixteenth note. Unlike a track's groove setting this edits the events. Returns the updated
+   * events.
+   */
+  applyGroove(eventIds: string[], grooveId: string, amount?: number): Event[];
+
+  /**
+   * Whether the track sounds, given its mute and the solos of all tracks and their groups.
+   * Solo overrides mute.
+   */
+  isTrackAudible(trackId: string): boolean;
+
+  getOutOfRangeNotes(trackId: string): Event[];
+
+  /** Captures the track's events as a baseline for `diffAgainstFrozen`, replacing any earlier one. */
+  freezeTrackState(trackId: string, label?: string): void;
+
+  diffAgainstFrozen(trackId: string): TrackDiff;
+
+  /** Summarizes an SMF without importing it, so a host can offer a track mapping. */
+  previewMidi(bytes: Uint8Array): MidiPreview;
+
+  /**
+   * Imports an SMF, rescaled to the song's ppq. When no song is set, the file is loaded as a new
+   * song titled after its first named track, with its time signatures and no pending changes.
+   * Without `mapping` every track holding notes or control changes becomes a new track. Returns
+   * the tracks that received events and the rounding drift of the rescale.
+   */
+  importMidi(bytes: Uint8Array, options?: MidiImportOptions): MidiImportResult;
+
+  /**
+   * Starts an `importMidi` that adds its events in chunks through `continueMidiImport`, so large
+   * files can show progress, see `importMidiAsync`. The new tracks are added at once. Returns
+   * how many events the file holds. Leave the song alone until `finishMidiImport`.
+   */
+  startMidiImport(bytes: Uint8Array, options?: MidiImportOptions): number;
+
+  /** Adds up to `maxEvents` more events of the running import; returns how many are in so far. */
+  continueMidiImport(maxEvents: number): number;
+
+  /** Adds what is left of the running import and commits it as one change. */
+  finishMidiImport(): MidiImportResult;
+
+  /**
+   * Writes the song as a format 1 SMF at its ppq, with a first track holding the tempo, time
+   * signatures and markers, followed by one track per song track in order. Every track lasts
+   * until the end of the song, see `setEndOfSongPolicy`.
+   */
+  exportMidi(options?: MidiExportOptions): Uint8Array;
+
+  /**
+   * Renders the track's notes as a single-voice ABC tune in 4/4. With `quantize`, starts and
+   * durations are rounded to notatable values and eighth-triplet beats are written as tuplets.
+   */
+  exportAbc(trackId: string, options?: NotationExportOptions): string;
+
+  /** Renders lyric events as LRC, one timestamped line per lyric line. */
+  exportLrc(options?: LyricExportOptions): string;
+
+  /** Writes lyric events as lyric meta events of a format 0 SMF. */
+  exportLyricsMidi(options?: LyricExportOptions): Uint8Array;
+
+  flattenTracks(trackIds: string[], applyNonDestructiveSettings: boolean): Track;
+
+  /**
+   * Moves the track's events onto one new track per MIDI channel they were imported on, as
+   * format-0 files put every channel on one track. Returns the new tracks by channel.
+   */
+  splitTrackByChannel(trackId: string): Track[];
+
+  /**
+   * Moves the notes below `splitNote` onto a new track right after the track, such as a piano's
+   * left hand. Other events stay. Returns the new track.
+   */
+  splitTrackByPitch(trackId: string, splitNote: number): Track;
+
+  /**
+   * Moves the notes at or above (`"above"`) or below (`"below"`) the velocity `threshold` onto a
+   * new track right after the track, such as accents for another instrument. Other events stay.
+   * Returns the new track.
+   */
+  splitByVelocity(trackId: string, threshold: number, split: VelocitySplit): Track;
+
+  /** Moves every event of the tracks onto `targetTrackId` and removes the emptied tracks. */
+  mergeTracks(trackIds: string[], targetTrackId: string): Track;
+
+  /**
+   * Starts a new take of a cycle recording on the track: events added from now on belong to it.
+   * Returns the number of takes started on the track since the last `mergeTakes`.
+   */
+  startTake(trackId: string): number;
+
+  /**
+   * Resolves the takes started on the track, leaving events added before the first take alone.
+   * Returns the track followed by the tracks created for earlier takes.
+   */
+  mergeTakes(trackId: string, merge: TakeMerge): Track[];
+
+  /**
+   * Input latency in milliseconds, taken off host timestamps by `hostTimeToTicks` and
+   * `recordNote` so recorded notes land where they were heard. Defaults to 0.
+   */
+  setInputLatency(latencyMs: number): void;
+
+  /**
+   * With `tempoAware`, the default, host timestamps convert at the song's current tempo,
+   * following `setBpm` calls made while recording; otherwise at the tempo recording started at.
+   */
+  setRecordingClock(tempoAware: boolean): void;
+
+  /**
+   * Starts converting host timestamps, in milliseconds such as `performance.now()`, to ticks:
+   * `hostTime` is heard at `ticks`.
+   */
+  startRecording(hostTime: number, ticks: number): void;
+
+  /** The tick where input stamped `hostTime` was heard. Throws before `startRecording`. */
+  hostTimeToTicks(hostTime: number): number;
+
+  /**
+   * Adds a note played from host time `startTime` to `endTime`, placed by `hostTimeToTicks`.
+   * Returns `undefined` without adding it when the track's `RecordFilter` drops it; the event
+   * remembers `channel` like imported ones.
+   */
+  recordNote(
+    trackId: string,
+    noteNumber: number,
+    velocity: number,
+    startTime: number,
+    endTime: number,
+    channel?: number,
+  ): Event | undefined;
+
+  /** Adds a control change received at host time `hostTime`, like `recordNote`. */
+  recordControlChange(
+    trackId: string,
+    controller: number,
+    value: number,
+    hostTime: number,
+    channel?: number,
+  ): Event | undefined;
+
+  /**
+   * Sets what the track accepts while recording, or accepts everything again with `null`. The
+   * filter is kept in the track's settings, so it is saved and undone like the rest of them.
+   */
+  setRecordFilter(trackId: string, filter: RecordFilter | null): void;
+
+  getRecordFilter(trackId: string): RecordFilter | undefined;
+
+  /**
+   * Whether the track's filter lets input of `kind` on `channel` through, for input such as
+   * aftertouch that hosts handle themselves rather than with a `record` method.
+   */
+  acceptsRecordInput(trackId: string, kind: RecordInputKind, channel?: number): boolean;
+
+  /**
+   * The kinds of event the store supports, each with its fields, types and value ranges, so
+   * editors can build a form for any kind.
+   */
+  getSupportedEventKinds(): EventKindSchema[];
+
+  getEvent(eventId: string): Event | undefined;
+
+  /** Events of the tracks `trackIds`, or of all tracks when omitted. */
+  getEvents(trackIds?: string[]): Event[];
+
+  /**
+   * Events starting in `[startTicks, endTicks)`, in ticks order and, on the same tick, with
+   * time signatures, markers and the other metas first, then controller changes and raw MIDI,
+   * then notes and audio regions. With `withinDuration`, events that started earlier and still
+   * sound at `startTicks` come first.
+   */
+  getEventsInTicksRange(
+    startTicks: number,
+    endTicks: number,
+    withinDuration: boolean,
+    trackIds?: string[],
+  ): Event[];
+  getEventsInTicksRange(
+    startTicks: number,
+    endTicks: number,
+    withinDuration: boolean,
+    trackIds: string[] | undefined,
+    options: TicksRangeOptions,
+  ): (Event | NoteOff)[];
+
+  /** The track's events in ticks order, for editors showing a single lane. */
+  getTrackEvents(trackId: string): Event[];
+
+  /** Like `getEventsInTicksRange` on one track, reading only that track's index. */
+  getTrackEventsInTicksRange(
+    trackId: string,
+    startTicks: number,
+    endTicks: number,
+    withinDuration: boolean,
+  ): Event[];
+
+  /**
+   * Events sounding at `ticks`, those whose `[ticks, ticks + duration)` contains it, such as the
+   * chord under the playhead. Notes come first from the lowest pitch.
+   */
+  getActiveEventsAt(ticks: number, trackIds?: string[]): Event[];
+
+  /** Events meeting every criterion of `filter`, in ticks order. */
+  queryEvents(filter: EventFilter): Event[];
+
+  /**
+   * The first event after `from` meeting `filter`, e.g. to select the next note from the
+   * keyboard. `from` is an event id, with events on its tick ordered by id, or a caret tick
+   * the next event may start on. Only the events passed over are looked at.
+   */
+  getNextEvent(from: string | number, filter?: EventFilter): Event | undefined;
+
+  /** Like `getNextEvent` going back; from a tick, the previous event starts before it. */
+  getPreviousEvent(from: string | number, filter?: EventFilter): Event | undefined;
+
+  /** A sorted page of the events matching a filter, for event list editors. */
+  getEventTable(options?: EventTableOptions): EventTable;
+
+  /** Queries several ranges in one call, returning one list of events per range. */
+  getEventsInRanges(ranges: TicksRange[], filter?: EventsFilter): Event[][];
+
+  /**
+   * Notes whose onsets the playhead crossed moving from `prevTicks` to `newTicks`, in either
+   * direction, ordered as they were crossed.
+   */
+  getScrubEvents(prevTicks: number, newTicks: number, filter?: EventsFilter): Event[];
+
+  /**
+   * Events as they should sound in `[startTicks, endTicks)`, with their track's transpose,
+   * velocity offset, swing and delay applied. Tracks silenced by mute or solo are left out,
+   * see `isTrackAudible`.
+   */
+  getPlaybackEvents(startTicks: number, endTicks: number, options?: PlaybackOptions): Event[];
+
+  /**
+   * The notes starting in `[startTicks, endTicks)` as typed arrays, for schedulers that must not
+   * allocate an object per note.
+   */
+  getNotesInTicksRangeFlat(startTicks: number, endTicks: number): FlatNotes;
+
+  /**
+   * Writes the notes and control changes of audible tracks starting in `[startTicks, endTicks)`
+   * into `buffer` as records of four words, see `PlaybackRecordKind`, with the tracks' transpose
+   * and velocity offset applied. Returns how many records there are; a count above
+   * `buffer.length / 4` means only that many fit. Swing, grooves and delays are left to
+   * `getPlaybackEvents`. Nothing is allocated while the records are written, apart from the
+   * copy of `buffer` the bindings pass in and back, for the scheduler's real-time loop.
+   */
+  fillPlaybackBuffer(buffer: Uint32Array, startTicks: number, endTicks: number): number;
+
+  /** The arrangement: one section per marker, running until the next marker. */
+  getSections(): Section[];
+
+  /** Adds a marker such as "Verse" at `ticks`. Markers belong to the song, not to a track. */
+  addMarker(ticks: number, name: string): Marker;
+
+  /** Removes a marker, failing when `markerId` is not a marker. */
+  removeMarker(markerId: string): void;
+
+  /** The song's markers in ticks order. */
+  getMarkers(): Marker[];
+
+  /** The markers starting in `[startTicks, endTicks)` in ticks order. */
+  getMarkersInTicksRange(startTicks: number, endTicks: number): Marker[];
+
+  /**
+   * Everything a canvas renderer needs for `[startTicks, endTicks)` in one payload: every track
+   * in order with its name, color and mute state, and the events visible in the range.
+   */
+  getRenderModel(startTicks: number, endTicks: number): RenderModel;
+
+  /**
+   * The time signature in effect at `ticks`, whichever track holds it. Undefined before the
+   * first one, where the song is in 4/4.
+   */
+  getTimeSignatureAt(ticks: number): TimeSignature | undefined;
+
+  /**
+   * The key signature in effect at `ticks`, whichever track holds it, e.g. to spell notes or
+   * highlight the scale. Undefined before the first one.
+   */
+  getKeyAt(ticks: number): KeySignature | undefined;
+
+  /** Metronome clicks on every beat in `[startTicks, endTicks)`, following meter changes. */
+  getClicksInRange(startTicks: number, endTicks: number): MetronomeClick[];
+
+  /** `bars` bars of clicks to play before recording at `ticks`, in the meter in effect there. */
+  getCountIn(bars: number, ticks: number, options?: CountInOptions): CountIn;
+
+  /**
+   * Proposes the tempo and beat offset implied by the notes of a track, or of the given
+   * events, or `undefined` when there are fewer than two onsets.
+   */
+  estimateTempo(
+    source: string | string[],
+    options?: TempoEstimateOptions,
+  ): TempoEstimate | undefined;
+
+  /**
+   * Ranks the 24 major and minor keys by how well the notes' pitch classes, weighted by how
+   * long they sound, fit each key's Krumhansl-Kessler profile. Best first; empty without notes.
+   */
+  detectKey(range?: TicksRange | null, trackIds?: string[]): KeyCandidate[];
+
+  /**
+   * Queries `[startTicks, endTicks)`, including events still sounding in it, and returns only
+   * what differs from the previous query under the same `windowId`.
+   */
+  getWindowDelta(
+    windowId: string,
+    startTicks: number,
+    endTicks: number,
+    filter?: EventsFilter,
+  ): WindowDelta;
+
+  /** Forgets the window's last result; its next query reports every event as entered. */
+  clearWindow(windowId: string): void;
+
+  /**
+   * Starts a playback cursor at `startTicks` for a scheduler to read the song ahead with
+   * `advanceCursor`, without deduplicating overlapping ranges. Cursors end with the song or
+   * with `closeCursor`.
+   */
+  createCursor(startTicks: number): number;
+
+  /**
+   * Moves the cursor to `ticks` and returns the events starting on the way, in ticks order. The
+   * first advance also returns the notes still sounding at the start. Moving back returns
+   * nothing and keeps the cursor in place; create a new cursor to seek.
+   */
+  advanceCursor(cursorId: number, ticks: number): Event[];
+
+  closeCursor(cursorId: number): void;
+
+  /**
+   * Adds the event, with its `meta` when set. A note out of its track's pitch range throws with
+   * `"reject"`, and is added and reported to `subscribeToWarnings` with `"warn"`.
+   */
+  addEvent(event: Event, rangeCheck?: RangeCheck): Event;
+
+  updateEvent(event: EventUpdater): Event;
+
+  removeEvent(eventId: string): void;
+
+  /** Adds all of `events` in one call and one commit, checking each like `addEvent`. */
+  addEvents(events: Event[], rangeCheck?: RangeCheck): Event[];
+
+  /** Applies all of `updaters` in one commit. Throws before changing anything if one fails. */
+  updateEvents(updaters: EventUpdater[]): Event[];
+
+  /**
+   * The events whose meta holds `value` under `key`, in ticks order. Values compare as JSON,
+   * so objects match whatever their key order.
+   */
+  queryEventsByMeta(key: string, value: unknown): Event[];
+
+  /**
+   * Adds the deltas of `updater` to each of the events' own values in one commit, e.g.
+   * `{ deltaTicks: 120, deltaVelocity: -10 }`. Returns the events in `eventIds` order.
+   */
+  updateEventsRelative(eventIds: string[], updater: RelativeUpdater): Event[];
+
+  /**
+   * Removes all of `eventIds` in one commit. Throws before removing anything if one is unknown.
+   * With `wholeGroups`, the other members of their groups are removed as well.
+   */
+  removeEvents(eventIds: string[], wholeGroups?: boolean): void;
+
+  getEventByHandle(handle: number): Event | undefined;
+
+  /** Like `updateEvent`, addressing the event by its handle; `id` and `kind` are not needed. */
+  updateEventByHandle(handle: number, fields: EventUpdaterFields): Event;
+
+  removeEventByHandle(handle: number): void;
+
+  /** Computes where the events would land if moved by `delta`, without changing the song. */
+  previewTransform(eventIds: string[], delta: TransformDelta): TransformPreview;
+
+  /**
+   * Moves the events as `previewTransform` shows, with the pitch or time axis locked and an
+   * optional snap grid. Returns the moved events in `eventIds` order. With `wholeGroups`, the
+   * other members of their groups move along and follow in the result.
+   */
+  moveEvents(
+    eventIds: string[],
+    delta: TransformDelta,
+    constraints?: MoveConstraints,
+    wholeGroups?: boolean,
+  ): Event[];
+
+  /**
+   * Ids of the track's notes with `noteNumber` that would overlap a note placed at `ticks` for
+   * `duration`, skipping `ignoreIds`.
+   */
+  checkCollision(
+    trackId: string,
+    ticks: number,
+    duration: number,
+    noteNumber: number,
+    ignoreIds?: string[],
+  ): string[];
+
+  /**
+   * Other occurrences of the phrase made of the notes `eventIds`, at any pitch and on any track,
+   * with note starts up to `toleranceTicks` off. For "select all occurrences".
+   */
+  findSimilarPhrases(eventIds: string[], toleranceTicks: number): PhraseMatch[];
+
+  /** The key and chord in effect at `ticks`, with the pitch classes they contain. */
+  getPitchClassInfo(ticks: number): PitchClassInfo;
+
+  /** The highest note sounding at each point of `range` across the tracks, as one line. */
+  extractTopLine(trackIds: string[], range: TicksRange): LineNote[];
+
+  /** The lowest note sounding at each point of `range` across the tracks, as one line. */
+  extractBassLine(trackIds: string[], range: TicksRange): LineNote[];
+
+  /**
+   * Where the notes of the track in `range` stop sounding once the sustain pedal (CC64) is
+   * taken into account.
+   */
+  getEffectiveNoteEnds(trackId: string, range: TicksRange): EffectiveNoteEnd[];
+
+  /** Applies ops from elsewhere, skipping ops with an `opId` received before. */
+  applyOps(ops: Operation[], origin?: string): Conflict[];
+
+  /**
+   * Every operation recorded after journal seq `sinceSeq`, or since the song was created, so a
+   * replica can be brought up to date with `applyRemoteOperations`.
+   */
+  getOperationsSince(sinceSeq?: number): OperationRecord[];
+
+  /**
+   * Applies operations of another replica in seq order. Unlike `applyOps` the last writer wins:
+   * an operation overwrites the event or track it targets instead of conflicting with local
+   * edits. Only operations that still cannot apply, such as updates of removed events, are
+   * returned.
+   */
+  applyRemoteOperations(records: OperationRecord[], origin?: string): Conflict[];
+
+  /** Applies `ops` provisionally and returns a token to confirm or roll them back with. */
+  applyOptimistic(ops: Operation[]): number;
+
+  confirmOptimistic(token: number): void;
+
+  /** Reverts an optimistic batch; parts edited again since are left alone and reported. */
+  rollbackOptimistic(token: number): Conflict[];
+
+  /** Grid lines start over at every time signature; the ticks must not overflow the grid. */
+  getGridTicksInRange(startTicks: number, endTicks: number, grid: Grid): number[];
+
+  /** The closest grid line, or the next time signature when that is as close. */
+  snapToGrid(ticks: number, grid: Grid): number;
+
+  /** Ticks at the song's ppq of a length named like "1/8", "1/4." (dotted) or "1/8T" (triplet). */
+  noteLengthTicks(name: string): number;
+
+  /** Straight, dotted and triplet lengths from whole notes down to the shortest the ppq allows. */
+  getLengthPresets(): LengthPreset[];
+
+  setDurations(eventIds: string[], duration: number | { scale: number }): Event[];
+
+  /**
+   * Sets each note's duration to `ratio` times the gap to the next note of the same pitch on its
+   * track: 1 makes them touch, 0.5 plays them half as long and 1.1 overlaps them by a tenth.
+   * Notes with no later note of their pitch keep their duration.
+   */
+  setOverlapRatio(eventIds: string[], ratio: number): Event[];
+
+  /** Moves the events towards their nearest grid lines and returns them in `eventIds` order. */
+  quantizeEvents(eventIds: string[], options: QuantizeOptions): Event[];
+
+  /**
+   * Transposes, scales velocities and shifts many events in one pass. Velocities stay within
+   * 1 to 127. Returns the events in `eventIds` order.
+   */
+  transformEvents(eventIds: string[], transform: EventTransform): Event[];
+
+  /**
+   * Changes the song's key: moves every note by `semitones` in one commit, with chord symbols
+   * and, unless turned off, key signatures following. Returns how many events changed.
+   */
+  transposeSong(semitones: number, options?: TransposeOptions): number;
+
+  /**
+   * Removes notes that repeat the pitch of a kept note on the same track, starting at most
+   * `toleranceTicks` after it, in one commit. `target` is a track id, for all of its notes, or
+   * event ids. The earliest note of a pitch is kept, or on a shared tick the first in `target`
+   * or in the track's order; later notes are measured from the kept one, not from each other.
+   * Returns the ids of the removed notes.
+   */
+  dedupeNotes(target: string | string[], toleranceTicks: number): string[];
+
+  /**
+   * Thins a recorded controller lane, keeping the events needed to follow its shape within
+   * `tolerance` values. Returns the ids of the removed events.
+   */
+  thinControllerEvents(trackId: string, controller: number, tolerance: number): string[];
+
+  /**
+   * The controller's value on the track at `ticks`, along a straight line between its control
+   * changes. Before the first one it holds the first value and after the last one the last
+   * value; undefined when the track has none for the controller.
+   */
+  getControllerValueAt(trackId: string, controller: number, ticks: number): number | undefined;
+
+  /**
+   * Samples `getControllerValueAt` every `resolution` ticks from `startTicks` up to `endTicks`,
+   * for drawing an automation lane or driving playback. Empty when the track has no control
+   * changes for the controller.
+   */
+  getControllerCurve(
+    trackId: string,
+    controller: number,
+    startTicks: number,
+    endTicks: number,
+    resolution: number,
+  ): ValuePoint[];
+
+  /**
+   * Tiles the events back to back until `untilTicks` in one commit, each copy with a new id.
+   * A tile spans the events from the earliest start to the latest end, and copies starting at
+   * or after `untilTicks` are dropped. Returns the copies.
+   */
+  repeatEventsToFill(eventIds: string[], untilTicks: number): Event[];
+
+  /**
+   * Repeats the bars `[startBar, endBar)`, counted from 0, `count` times right after themselves
+   * in one commit. The bars' events on `trackIds`, or all tracks, are copied and everything
+   * after the bars moves later to make room. Returns the copies.
+   */
+  duplicateRegion(startBar: number, endBar: number, count: number, trackIds?: string[]): Event[];
+
+  /**
+   * Pastes `count` copies of the events starting in `[startTicks, endTicks)` on `trackIds`, or
+   * all tracks, back-to-back right after the range in one commit. Unlike `duplicateRegion`,
+   * nothing moves to make room. Returns the copies.
+   */
+  repeatRange(startTicks: number, endTicks: number, count: number, trackIds?: string[]): Event[];
+
+  /**
+   * Copies the events to the store's clipboard, replacing what it held. With `wholeGroups`, the
+   * other members of their groups are copied as well.
+   */
+  copyEvents(eventIds: string[], wholeGroups?: boolean): void;
+
+  /**
+   * Copies the events to the store's clipboard like `copyEvents` and removes them in one
+   * commit.
+   */
+  cutEvents(eventIds: string[], wholeGroups?: boolean): void;
+
+  /**
+   * Adds copies of the clipboard's events with new ids, keeping their spacing, the earliest
+   * at `atTicks`. They go to `targetTrackId` when given, otherwise to the tracks they were
+   * copied from. Returns the new events.
+   */
+  pasteEvents(atTicks: number, targetTrackId?: string | null): Event[];
+
+  /**
+   * Copies the events to the store's clipboard like `copyEvents`, and returns them as a MIDI
+   * file starting at tick 0, with a first track titled "Clipboard", for the system clipboard.
+   */
+  copyEventsAsMidi(eventIds: string[]): Uint8Array;
+
+  /**
+   * Adds the notes and control changes of a MIDI file, such as one copied from a DAW, to
+   * `targetTrackId`, the earliest at `atTicks`. Returns the new events.
+   */
+  pasteMidi(bytes: Uint8Array, atTicks: number, targetTrackId: string): Event[];
+
+  /**
+   * Spreads the notes of `eventIds` that start on the same tick of the same track over
+   * `spreadTicks` in one commit. `up` keeps the lowest note in place and `down` the highest;
+   * ends stay put, so a note shorter than its share of the spread moves only to the tick before
+   * its end. The last note of each chord gets `velocityRamp` added to its velocity and the notes
+   * between a share of it. Returns the changed notes.
+   */
+  strumChords(
+    eventIds: string[],
+    spreadTicks: number,
+    direction: StrumDirection,
+    velocityRamp: number,
+  ): Event[];
+
+  /**
+   * Splits each note into `divisions` equally spaced repeats filling its duration, each one
+   * `velocityDecay` softer than the last. Returns every repeat; the first keeps the note's id.
+   */
+  ratchetNotes(eventIds: string[], divisions: number, velocityDecay: number): Event[];
+
+  /**
+   * Moves the notes by small random amounts in time and velocity. The same `seed` gives the same
+   * result, so a humanized take can be reproduced. Returns the updated notes.
+   */
+  humanizeEvents(eventIds: string[], options?: HumanizeOptions): Event[];
+
+  /**
+   * Detects the beat of a freely played recording and maps it onto the song's grid, returning
+   * the corrected position of each note. Notes move only with `apply`. Returns `undefined` when
+   * the notes have fewer than two onsets.
+   */
+  alignToGrid(
+    eventIds: string[],
+    strength: number,
+    options?: GridAlignOptions,
+  ): GridAlignment | undefined;
+
+  /**
+   * Sets the per-note parameter `name` on each note of `eventIds` in one commit, or clears it
+   * when `value` is null. Returns the events in `eventIds` order; other kinds come back
+   * unchanged.
+   */
+  setNoteParameter(eventIds: string[], name: string, value: number | null): Event[];
+
+  /**
+   * Spreads values from `startValue` to `endValue` over the target's span in one commit:
+   * velocities of notes and values of control changes, by ticks along `curve`. A `RampLane`
+   * targets the track's notes starting in `[startTicks, endTicks)`, or the control changes of
+   * `controller` when it is set. Velocities stay at least 1. Returns the target's events.
+   */
+  rampValues(
+    target: string[] | RampLane,
+    startValue: number,
+    endValue: number,
+    curve: RampCurve,
+  ): Event[];
+
+  /**
+   * Sets the velocity of notes and value of control changes, or with `field` another field, to
+   * the line through `points` at each event's ticks, like a pencil drawn over a lane. Events
+   * outside the line's span are left alone. Returns the changed events.
+   */
+  applyValueLine(eventIds: string[], points: ValuePoint[], field?: ValueLineField): Event[];
+
+  /**
+   * Draws control changes from `start` to `end` every `resolutionTicks`, replacing the lane's
+   * events in that span, and returns the added events. Sines and saws swing between the two
+   * values once per `periodTicks`, or once across the span when it is not given.
+   */
+  insertCurve(
+    lane: CurveLane,
+    start: CurvePoint,
+    end: CurvePoint,
+    shape: CurveShape,
+    resolutionTicks: number,
+    periodTicks?: number,
+  ): Event[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = queueMicrotask)]
+    fn queue_microtask(callback: &JsValue);
+}
+
+/// Reverts the song's pending changes and drops them along with the changes the reverts made.
+fn discard_changes(song: &mut Song) {
+    let changes = song.take_changes();
+    revert_changes(song, &changes);
+    song.take_changes();
+}
+
+/// Runs `edit` on the song. An edit that fails partway has its changes discarded, so the next
+/// commit does not pick up half of it.
+fn edit_song<T>(
+    song: &mut Song,
+    edit: impl FnOnce(&mut Song) -> Result<T, StoreError>,
+) -> Result<T, StoreError> {
+    let result = edit(song);
+    if result.is_err() {
+        discard_changes(song);
+    }
+    result
+}
+
+fn ids_from_js_array(ids: js_sys::Array) -> Result<Vec<Id>, StoreError> {
+    ids.iter()
+        .map(|id| {
+            let id = id
+                .as_string()
+                .ok_or_else(|| StoreError::invalid("Id is not a string"))?;
+            parse_id(&id)
+        })
+        .collect()
+}
+
+/// `event_ids`, followed by the other members of their groups when `whole_groups` is set.
+fn with_groups(song: &Song, event_ids: Vec<Id>, whole_groups: Option<bool>) -> Vec<Id> {
+    if whole_groups.unwrap_or(false) {
+        song.groups.expand(song, &event_ids)
+    } else {
+        event_ids
+    }
+}
+
+/// The `meta` of an event or updater from JS, see `Meta::from_js`.
+fn meta_of(obj: &js_sys::Object) -> Result<Option<Meta>, StoreError> {
+    Meta::from_js(&js_sys::Reflect::get(obj, &JsValue::from_str("meta")).unwrap())
+}
+
+/// The ids of the notes of `events` outside their track's pitch range, to warn about, or an
+/// error when `range_check` rejects them.
+fn check_pitch_range(
+    song: &Song,
+    events: &[Event],
+    range_check: Option<RangeCheck>,
+) -> Result<Vec<Id>, StoreError> {
+    let Some(range_check) = range_check else {
+        return Ok(Vec::new());
+    };
+    let out_of_range: Vec<Id> = events
+        .iter()
+        .filter(|event| {
+            event
+                .get_track_id()
+                .and_then(|track_id| song.get_track(&track_id))
+                .is_some_and(|track| !track.is_in_pitch_range(event))
+        })
+        .map(|event| event.get_id())
+        .collect();
+
+    if !out_of_range.is_empty() && range_check == RangeCheck::Reject {
+        return Err(StoreError::invalid(
+            "Note is out of the track's pitch range",
+        ));
+    }
+    Ok(out_of_range)
+}
+
+/// Operations passed in from JS, with their ids.
+fn parse_ops(ops: js_sys::Array) -> Result<Vec<(Option<Id>, Operation)>, StoreError> {
+    ops.iter()
+        .map(|op| {
+            let op: js_sys::Object = op.into();
+            let operation = Operation::from_js_object(op.clone())?;
+            Ok((Operation::id_from_js_object(&op)?, operation))
+        })
+        .collect()
+}
+
+fn events_filter(track_ids: Option<js_sys::Array>) -> Result<Option<GetEventsFilter>, StoreError> {
+    let Some(track_ids) = track_ids else {
+        return Ok(None);
+    };
+    Ok(Some(GetEventsFilter {
+        track_ids: Some(ids_from_js_array(track_ids)?),
+    }))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Delivery {
+    Changes(ChangeDetail),
+    Mirror,
+    /// The events added to a track.
+    Recorded(Id),
+    Warnings,
+}
+
+/// Something a call did that the app may want to tell the user about, for warning subscribers.
+enum StoreWarning {
+    /// Notes added outside their track's pitch range.
+    OutOfPitchRange(Vec<Id>),
+    /// Parts of an undo or redo left alone because they were edited elsewhere since.
+    Conflicts(Vec<Conflict>),
+}
+
+impl StoreWarning {
+    fn to_js_object(&self) -> js_sys::Object {
+        let js_warning = js_sys::Object::new();
+        let (kind, key, value): (_, _, js_sys::Array) = match self {
+            StoreWarning::OutOfPitchRange(event_ids) => (
+                "outOfPitchRange",
+                "eventIds",
+                event_ids
+                    .iter()
+                    .map(|id| JsValue::from_str(&id.to_string()))
+                    .collect(),
+            ),
+            StoreWarning::Conflicts(conflicts) => (
+                "conflicts",
+                "conflicts",
+                conflicts
+                    .iter()
+                    .map(|conflict| conflict.to_js_object())
+                    .collect(),
+            ),
+        };
+        js_sys::Reflect::set(
+            &js_warning,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str(kind),
+        )
+        .unwrap();
+        js_sys::Reflect::set(&js_warning, &JsValue::from_str(key), &value).unwrap();
+        js_warning
+    }
+}
+
+struct Subscription {
+    id: u32,
+    callback: js_sys::Function,
+    delivery: Delivery,
+}
+
+/// A song open in the store besides the active one, with the state kept for it while inactive.
+/// `song` is only `None` while `Store::with_song` has swapped it in.
+struct OpenSong {
+    id: Id,
+    song: Option<Song>,
+    frozen_tracks: HashMap<Id, FrozenTrack>,
+    history: History,
+    transaction: Option<Transaction>,
+    snapshots: Snapshots,
+    outbox: SyncQueue,
+    received_ops: ReceivedOperations,
+}
+
+/// A song as `listSongs` returns it, see `SongEntry` in TypeScript.
+#[derive(Serialize)]
+struct SongEntry<'a> {
+    id: Id,
+    title: &'a str,
+    active: bool,
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub struct Store {
+    song: Option<Song>,
+    /// Id of the active song, `song`; set while the store has one.
+    song_id: Option<Id>,
+    /// Ids of the open songs in the order they were opened, the active one included.
+    song_order: Vec<Id>,
+    open_songs: Vec<OpenSong>,
+    subscriptions: Vec<Subscription>,
+    next_subscription_id: u32,
+    origin: Option<String>,
+    outbox: SyncQueue,
+    received_ops: ReceivedOperations,
+    optimistic: OptimisticUpdates,
+    frozen_tracks: HashMap<Id, FrozenTrack>,
+    clock: Option<js_sys::Function>,
+    windows: HashMap<String, QueryWindow>,
+    cursors: HashMap<u32, PlaybackCursor>,
+    next_cursor_id: u32,
+    history: History,
+    transaction: Option<Transaction>,
+    midi_import: Option<MidiImportJob>,
+    clipboard: Clipboard,
+    id_strategy: IdStrategy,
+    validation: Validation,
+    quota: Quota,
+    snapshots: Snapshots,
+    recording_clock: RecordingClock,
+    /// The latest revision handed out, across every song the store has held.
+    revision: u64,
+}
+
+impl Store {
+    /// Records the song's pending changes in the journal under the store's origin and in the
+    /// undo history, queues them for sync and hands them to subscribers. While a transaction is
+    /// open the changes are held back instead.
+    ///
+    /// Changes that take the song past its quota are reverted unrecorded and fail the call.
+    fn commit(&mut self) -> Result<Option<u64>, StoreError> {
+        if let Some(song) = self.song.as_mut() {
+            if let Err(error) = self.quota.check_changes(song, song.get_changes()) {
+                discard_changes(song);
+                return Err(error);
+            }
+        }
+        if let (Some(transaction), Some(song)) = (self.transaction.as_mut(), self.song.as_mut()) {
+            transaction.defer(song);
+            return Ok(None);
+        }
+        let origin = self.origin.clone();
+        let Some(seq) = self.commit_with(origin, true) else {
+            return Ok(None);
+        };
+        let song = self.song.as_ref().unwrap();
+        self.history
+            .record(&song.get_journal().entries_since(seq - 1)[0].changes);
+        Ok(Some(seq))
+    }
+
+    /// Records changes that came from elsewhere; they are not queued for sync.
+    fn commit_remote(&mut self, origin: Option<String>) -> Option<u64> {
+        self.commit_with(origin, false)
+    }
+
+    /// Hands `warning` to the warning subscribers in a microtask.
+    fn warn(&self, warning: StoreWarning) {
+        let mut subscriptions = self
+            .subscriptions
+            .iter()
+            .filter(|subscription| subscription.delivery == Delivery::Warnings)
+            .peekable();
+        if subscriptions.peek().is_none() {
+            return;
+        }
+        let payload: JsValue = warning.to_js_object().into();
+        for subscription in subscriptions {
+            let callback = subscription.callback.bind1(&JsValue::NULL, &payload);
+            queue_microtask(callback.as_ref());
+        }
+    }
+
+    fn add_subscription(&mut self, callback: js_sys::Function, delivery: Delivery) -> u32 {
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.push(Subscription {
+            id,
+            callback,
+            delivery,
+        });
+        id
+    }
+
+    /// Returns the journal seq of the recorded entry. Delivery to subscribers is deferred so
+    /// callbacks can read from the store without hitting the borrow held by the mutating call.
+    fn commit_with(&mut self, origin: Option<String>, local: bool) -> Option<u64> {
+        let song = self.song.as_mut()?;
+        if let Some(clock) = &self.clock {
+            if let Some(now) = clock
+                .call0(&JsValue::NULL)
+                .ok()
+                .and_then(|now| now.as_f64())
+            {
+                song.stamp_changes(now);
+            }
+        }
+        song.commit(origin)?;
+        self.revision = self.revision.max(song.get_revision());
+        let entry = song.get_journal().last().unwrap();
+        if local {
+            self.outbox.push(entry, &mut self.id_strategy);
+        }
+        if self.subscriptions.is_empty() {
+            return Some(entry.seq);
+        }
+        let changeset = entry.to_changeset();
+        if changeset.is_empty() {
+            return Some(entry.seq);
+        }
+
+        let mut payloads: HashMap<Delivery, Option<JsValue>> = HashMap::new();
+        for subscription in &self.subscriptions {
+            let payload =
+                payloads.entry(subscription.delivery).or_insert_with(|| {
+                    match subscription.delivery {
+                        Delivery::Changes(detail) => Some(entry.to_js_object(detail).into()),
+                        Delivery::Mirror => {
+                            let bytes = MirrorFrame::from_entry(entry).to_bytes();
+                            Some(js_sys::Uint8Array::from(bytes.as_slice()).into())
+                        }
+                        Delivery::Recorded(track_id) => {
+                            let events = changeset.added_to_track(track_id);
+                            (!events.is_empty()).then(|| Event::to_js_array(events).into())
+                        }
+                        Delivery::Warnings => None,
+                    }
+                });
+            let Some(payload) = payload else {
+                continue;
+            };
+            let callback = subscription.callback.bind1(&JsValue::NULL, payload);
+            queue_microtask(callback.as_ref());
+        }
+
+        Some(entry.seq)
+    }
+}
+
+#[wasm_bindgen]
+impl Store {
+    pub(crate) fn new() -> Self {
+        Store {
+            song: None,
+            song_id: None,
+            song_order: Vec::new(),
+            open_songs: Vec::new(),
+            subscriptions: Vec::new(),
+            next_subscription_id: 0,
+            origin: None,
+            outbox: SyncQueue::default(),
+            received_ops: ReceivedOperations::default(),
+            optimistic: OptimisticUpdates::default(),
+            frozen_tracks: HashMap::new(),
+            clock: None,
+            windows: HashMap::new(),
+            cursors: HashMap::new(),
+            next_cursor_id: 0,
+            history: History::default(),
+            transaction: None,
+            midi_import: None,
+            clipboard: Clipboard::default(),
+            id_strategy: IdStrategy::default(),
+            validation: Validation::default(),
+            quota: Quota::default(),
+            snapshots: Snapshots::default(),
+            recording_clock: RecordingClock::default(),
+            revision: 0,
+        }
+    }
+
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(options: JsValue) -> Result<Store, StoreError> {
+        let mut store = Self::new();
+        if !options.is_undefined() {
+            let id_strategy = js_sys::Reflect::get(&options, &JsValue::from_str("idStrategy"))
+                .map_err(|_| StoreError::invalid("Store options are not valid"))?;
+            store.id_strategy = IdStrategy::from_js(&id_strategy)?;
+        }
+        Ok(store)
+    }
+
+    #[wasm_bindgen(js_name = getSong)]
+    pub fn get_song_js(&self) -> Option<js_sys::Object> {
+        self.song.as_ref().map(|song| song.to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = createSong)]
+    pub fn create_song_js(&mut self, title: String, ppq: u32) -> Result<String, StoreError> {
+        if ppq == 0 {
+            return Err(StoreError::invalid("Song ppq must be positive"));
+        }
+        let song_id = self.id_strategy.next_id()?;
+        self.park_song();
+        self.song = Some(Song::new(title, ppq));
+        self.song_id = Some(song_id);
+        self.song_order.push(song_id);
+        self.history = History::default();
+        self.song_replaced();
+        Ok(song_id.to_string())
+    }
+
+    #[wasm_bindgen(js_name = listSongs)]
+    pub fn list_songs_js(&self) -> js_sys::Array {
+        self.song_order
+            .iter()
+            .filter_map(|song_id| {
+                let song = if self.song_id == Some(*song_id) {
+                    self.song.as_ref()?
+                } else {
+                    let open = self.open_songs.iter().find(|open| open.id == *song_id)?;
+                    open.song.as_ref()?
+                };
+                let entry = SongEntry {
+                    id: *song_id,
+                    title: &song.title,
+                    active: self.song_id == Some(*song_id),
+                };
+                Some(to_js_value(&entry))
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = getActiveSongId)]
+    pub fn get_active_song_id_js(&self) -> Option<String> {
+        self.song_id
+            .filter(|_| self.song.is_some())
+            .map(|song_id| song_id.to_string())
+    }
+
+    #[wasm_bindgen(js_name = setActiveSong)]
+    pub fn set_active_song_js(&mut self, song_id: &str) -> Result<(), StoreError> {
+        let song_id = parse_id(song_id)?;
+        if self.song_id == Some(song_id) {
+            return Ok(());
+        }
+        let index = self.open_song_index(&song_id)?;
+        self.park_song();
+        let open = self.open_songs.remove(index);
+        self.song = open.song;
+        self.song_id = Some(open.id);
+        self.history = open.history;
+        self.song_replaced();
+        self.frozen_tracks = open.frozen_tracks;
+        self.transaction = open.transaction;
+        self.snapshots = open.snapshots;
+        self.outbox = open.outbox;
+        self.received_ops = open.received_ops;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = removeSong)]
+    pub fn remove_song_js(&mut self, song_id: &str) -> Result<(), StoreError> {
+        let song_id = parse_id(song_id)?;
+        if self.song_id == Some(song_id) {
+            self.clear_song_js();
+            return Ok(());
+        }
+        let index = self.open_song_index(&song_id)?;
+        self.open_songs.remove(index);
+        self.song_order.retain(|id| *id != song_id);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getSongEvent)]
+    pub fn get_song_event_js(
+        &mut self,
+        song_id: &str,
+        event_id: &str,
+    ) -> Result<Option<js_sys::Object>, StoreError> {
+        self.with_song(song_id, |store| store.get_event_js(event_id))
+    }
+
+    #[wasm_bindgen(js_name = getSongEvents)]
+    pub fn get_song_events_js(
+        &mut self,
+        song_id: &str,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        self.with_song(song_id, |store| store.get_events_js(track_ids))
+    }
+
+    #[wasm_bindgen(js_name = addSongEvent)]
+    pub fn add_song_event_js(
+        &mut self,
+        song_id: &str,
+        event: js_sys::Object,
+        range_check: Option<String>,
+    ) -> Result<js_sys::Object, StoreError> {
+        self.with_song(song_id, |store| store.add_event_js(event, range_check))
+    }
+
+    #[wasm_bindgen(js_name = updateSongEvent)]
+    pub fn update_song_event_js(
+        &mut self,
+        song_id: &str,
+        event: js_sys::Object,
+    ) -> Result<js_sys::Object, StoreError> {
+        self.with_song(song_id, |store| store.update_event_js(event))
+    }
+
+    #[wasm_bindgen(js_name = removeSongEvent)]
+    pub fn remove_song_event_js(
+        &mut self,
+        song_id: &str,
+        event_id: &str,
+    ) -> Result<(), StoreError> {
+        self.with_song(song_id, |store| store.remove_event_js(event_id))
+    }
+
+    #[wasm_bindgen(js_name = getSongTrack)]
+    pub fn get_song_track_js(
+        &mut self,
+        song_id: &str,
+        track_id: &str,
+    ) -> Result<Option<js_sys::Object>, StoreError> {
+        self.with_song(song_id, |store| store.get_track_js(track_id))
+    }
+
+    #[wasm_bindgen(js_name = getSongTracks)]
+    pub fn get_song_tracks_js(
+        &mut self,
+        song_id: &str,
+        include_stats: Option<bool>,
+    ) -> Result<js_sys::Array, StoreError> {
+        self.with_song(song_id, |store| store.get_tracks_js(include_stats))
+    }
+
+    #[wasm_bindgen(js_name = addSongTrack)]
+    pub fn add_song_track_js(
+        &mut self,
+        song_id: &str,
+        track: js_sys::Object,
+    ) -> Result<js_sys::Object, StoreError> {
+        self.with_song(song_id, |store| store.add_track_js(track))
+    }
+
+    #[wasm_bindgen(js_name = updateSongTrack)]
+    pub fn update_song_track_js(
+        &mut self,
+        song_id: &str,
+        track_updater: js_sys::Object,
+    ) -> Result<js_sys::Object, StoreError> {
+        self.with_song(song_id, |store| store.update_track_js(track_updater))
+    }
+
+    #[wasm_bindgen(js_name = removeSongTrack)]
+    pub fn remove_song_track_js(
+        &mut self,
+        song_id: &str,
+        track_id: &str,
+    ) -> Result<(), StoreError> {
+        self.with_song(song_id, |store| store.remove_track_js(track_id))
+    }
+
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json_js(&self) -> Result<String, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(serde_json::to_string(song).unwrap())
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[wasm_bindgen(js_name = createDemoSong)]
+    pub fn create_demo_song_js(&mut self, options: JsValue) -> Result<(), StoreError> {
+        let options: crate::fixtures::DemoSongOptions = if options.is_undefined() {
+            Default::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Demo song options are not valid"))?
+        };
+        if options.ppq == 0 {
+            return Err(StoreError::invalid("Song ppq must be positive"));
+        }
+        self.set_song(options.build())?;
+        Ok(())
+    }
+
+    #[cfg(feature = "persistence")]
+    #[wasm_bindgen(js_name = saveTo)]
+    pub fn save_to_js(&self, db_name: String, key: String) -> Result<js_sys::Promise, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let bytes = postcard::to_allocvec(song).unwrap();
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            crate::persistence::save(&db_name, &key, &bytes).await?;
+            Ok(JsValue::UNDEFINED)
+        }))
+    }
+
+    #[cfg(feature = "persistence")]
+    #[wasm_bindgen(js_name = loadFrom)]
+    pub async fn load_from_js(&mut self, db_name: String, key: String) -> Result<(), JsValue> {
+        let bytes = crate::persistence::load(&db_name, &key).await?;
+        self.load_binary_js(&bytes)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = loadJSON)]
+    pub fn load_json_js(&mut self, json: &str, options: JsValue) -> Result<(), StoreError> {
+        let options: JsonImportOptions = if options.is_undefined() {
+            JsonImportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("JSON import options are not valid"))?
+        };
+
+        let mut song: Song = StoreError::decoding("Song JSON", || serde_json::from_str(json))?;
+        if let Some(snap) = ImportSnap::from_options(options.snap_tolerance, options.snap_grid) {
+            snap_events(&mut song, snap);
+            song.take_changes();
+        }
+        self.quota.check_song(&song)?;
+        self.set_song(song)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = toBinary)]
+    pub fn to_binary_js(&self) -> Result<Vec<u8>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(postcard::to_allocvec(song).unwrap())
+    }
+
+    #[wasm_bindgen(js_name = fromBinary)]
+    pub fn load_binary_js(&mut self, bytes: &[u8]) -> Result<(), StoreError> {
+        let bytes = crate::persistence::migrate(bytes.to_vec())?;
+        let song: Song = StoreError::decoding("Song binary", || postcard::from_bytes(&bytes))?;
+        self.quota.check_song(&song)?;
+        self.set_song(song)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = takeSnapshot)]
+    pub fn take_snapshot_js(&mut self) -> Result<u32, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(self.snapshots.take(song))
+    }
+
+    #[wasm_bindgen(js_name = restoreSnapshot)]
+    pub fn restore_snapshot_js(&mut self, id: u32) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        restore_snapshot(song, self.snapshots.get(id)?);
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = diffSnapshots)]
+    pub fn diff_snapshots_js(&self, a: u32, b: u32) -> Result<js_sys::Object, StoreError> {
+        let changeset = diff_snapshots(self.snapshots.get(a)?, self.snapshots.get(b)?);
+        Ok(changeset.to_js_object(ChangeDetail::Full))
+    }
+
+    #[wasm_bindgen(js_name = dropSnapshot)]
+    pub fn drop_snapshot_js(&mut self, id: u32) -> bool {
+        self.snapshots.remove(id)
+    }
+
+    #[wasm_bindgen(js_name = getSetting)]
+    pub fn get_setting_js(&self, key: &str) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.settings.get(key).map_or(JsValue::UNDEFINED, |json| {
+            js_sys::JSON::parse(json).unwrap()
+        }))
+    }
+
+    #[wasm_bindgen(js_name = setSetting)]
+    pub fn set_setting_js(&mut self, key: &str, value: JsValue) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let json = match value.is_undefined() {
+            true => None,
+            false => Some(
+                js_sys::JSON::stringify(&value)
+                    .ok()
+                    .and_then(|json| json.as_string())
+                    .ok_or_else(|| StoreError::invalid("Setting value is not valid"))?,
+            ),
+        };
+        song.settings.set(key, json);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getSettings)]
+    pub fn get_settings_js(&self) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.settings.to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = setLoop)]
+    pub fn set_loop_js(
+        &mut self,
+        start_ticks: f64,
+        end_ticks: f64,
+        enabled: bool,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        if start_ticks >= end_ticks {
+            return Err(StoreError::invalid("Loop must end after it starts"));
+        }
+        let loop_region = LoopRegion {
+            start_ticks: parse_ticks(start_ticks)?,
+            end_ticks: parse_ticks(end_ticks)?,
+            enabled,
+        };
+        song.loop_region = Some(loop_region);
+        Ok(serde_wasm_bindgen::to_value(&loop_region).unwrap())
+    }
+
+    #[wasm_bindgen(js_name = setEndOfSongPolicy)]
+    pub fn set_end_of_song_policy_js(&mut self, policy: JsValue) -> Result<f64, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.end_of_song_policy = serde_wasm_bindgen::from_value(policy)
+            .map_err(|_| StoreError::invalid("End of song policy is not valid"))?;
+        Ok(song.end_of_song().as_f64())
+    }
+
+    #[wasm_bindgen(js_name = getLoop)]
+    pub fn get_loop_js(&self) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.loop_region.map_or(JsValue::UNDEFINED, |loop_region| {
+            serde_wasm_bindgen::to_value(&loop_region).unwrap()
+        }))
+    }
+
+    #[wasm_bindgen(js_name = getEventsForLoopIteration)]
+    pub fn get_events_for_loop_iteration_js(
+        &self,
+        iteration: u32,
+        options: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let loop_region = song
+            .loop_region
+            .ok_or_else(|| StoreError::invalid("Song has no loop"))?;
+        let options: PlaybackOptions = if options.is_undefined() {
+            PlaybackOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Playback options are not valid"))?
+        };
+        let events = loop_iteration_events(song, &loop_region, iteration, &options);
+        Ok(song.events_to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = getStats)]
+    pub fn get_stats_js(
+        &self,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_ids = track_ids.map(ids_from_js_array).transpose()?;
+        for track_id in track_ids.iter().flatten() {
+            song.get_track(track_id)
+                .ok_or(StoreError::TrackNotFound(*track_id))?;
+        }
+        Ok(SongStats::new(song, track_ids).to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = getSongSummary)]
+    pub fn get_song_summary_js(&self) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(SongSummary::new(song).to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = setBpm)]
+    pub fn set_bpm_js(&mut self, bpm: f64) -> Result<(), StoreError> {
+        if !(bpm.is_finite() && bpm > 0.0) {
+            return Err(StoreError::invalid("Tempo must be positive"));
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.bpm = bpm;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setPpq)]
+    pub fn set_ppq_js(&mut self, ppq: u32, rescale_events: bool) -> Result<(), StoreError> {
+        if ppq == 0 {
+            return Err(StoreError::invalid("PPQ must be positive"));
+        }
+        if self.transaction.is_some() {
+            return Err(StoreError::invalid(
+                "The ppq cannot change while a transaction is open",
+            ));
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        if song.ppq == ppq {
+            return Ok(());
+        }
+
+        // the ppq is not journaled, so undo puts the whole song back rather than reverting the
+        // moved events
+        let previous = song.snapshot();
+        if rescale_events {
+            rescale_ppq(song, ppq);
+        } else {
+            song.ppq = ppq;
+        }
+        self.history.record_restore(previous);
+        let origin = self.origin.clone();
+        self.commit_with(origin, true);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = ticksToSeconds)]
+    pub fn ticks_to_seconds_js(&self, ticks: f64) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.ticks_to_seconds(parse_ticks(ticks)?))
+    }
+
+    #[wasm_bindgen(js_name = secondsToTicks)]
+    pub fn seconds_to_ticks_js(&self, seconds: f64) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.seconds_to_ticks(seconds).as_f64())
+    }
+
+    #[wasm_bindgen(js_name = formatPosition)]
+    pub fn format_position_js(&self, ticks: f64, format: &str) -> Result<String, StoreError> {
+        let format: PositionFormat = format.parse()?;
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(format_position(song, parse_ticks(ticks)?, format))
+    }
+
+    /// Replaces the song, recording the previous one in the undo history.
+    fn set_song(&mut self, song: Song) -> Result<(), StoreError> {
+        if self.song_id.is_none() {
+            let song_id = self.id_strategy.next_id()?;
+            self.song_id = Some(song_id);
+            self.song_order.push(song_id);
+        }
+        let previous = self.song.replace(song);
+        self.history.record_song(previous);
+        self.song_replaced();
+        Ok(())
+    }
+
+    /// Whether the track's record filter lets the input through; fails for unknown tracks.
+    fn accepts_record_input(
+        &self,
+        track_id: Id,
+        kind: RecordInputKind,
+        channel: Option<u8>,
+    ) -> Result<bool, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        if channel.is_some_and(|channel| channel > 15) {
+            return Err(StoreError::invalid("Channel must be from 0 to 15"));
+        }
+        Ok(track
+            .settings
+            .record_filter
+            .as_ref()
+            .is_none_or(|filter| filter.accepts(kind, channel)))
+    }
+
+    /// Adds a recorded event, remembering the channel it came in on, and commits it.
+    fn add_recorded_event(
+        &mut self,
+        event: Event,
+        channel: Option<u8>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_id = song.add_event(event)?.get_id();
+        if let Some(channel) = channel {
+            song.set_channel(event_id, channel);
+        }
+        let js_event = song.event_to_js_object(song.get_event(&event_id).unwrap());
+        self.commit()?;
+        Ok(js_event)
+    }
+
+    fn open_song_index(&self, song_id: &Id) -> Result<usize, StoreError> {
+        self.open_songs
+            .iter()
+            .position(|open| open.id == *song_id)
+            .ok_or_else(|| StoreError::invalid("Song not found"))
+    }
+
+    /// Moves the active song and the state kept for it into `open_songs`, leaving none active.
+    fn park_song(&mut self) {
+        let Some(song_id) = self.song_id.take() else {
+            return;
+        };
+        let Some(song) = self.song.take() else {
+            self.song_order.retain(|id| *id != song_id);
+            return;
+        };
+        self.open_songs.push(OpenSong {
+            id: song_id,
+            song: Some(song),
+            frozen_tracks: std::mem::take(&mut self.frozen_tracks),
+            history: std::mem::take(&mut self.history),
+            transaction: self.transaction.take(),
+            snapshots: std::mem::take(&mut self.snapshots),
+            outbox: std::mem::take(&mut self.outbox),
+            received_ops: std::mem::take(&mut self.received_ops),
+        });
+    }
+
+    /// Runs `f` on the open song `song_id` as if it were active, with the active song's change
+    /// subscribers and views set aside so they see none of it.
+    fn with_song<R>(
+        &mut self,
+        song_id: &str,
+        f: impl FnOnce(&mut Store) -> Result<R, StoreError>,
+    ) -> Result<R, StoreError> {
+        let song_id = parse_id(song_id)?;
+        if self.song_id == Some(song_id) {
+            return f(self);
+        }
+        let index = self.open_song_index(&song_id)?;
+        self.swap_open_song(index);
+        // warnings are about the call, whichever song it acts on
+        let (warnings, subscriptions): (Vec<_>, Vec<_>) = std::mem::take(&mut self.subscriptions)
+            .into_iter()
+            .partition(|subscription| subscription.delivery == Delivery::Warnings);
+        self.subscriptions = warnings;
+        let optimistic = std::mem::take(&mut self.optimistic);
+        let windows = std::mem::take(&mut self.windows);
+        let cursors = std::mem::take(&mut self.cursors);
+        let midi_import = self.midi_import.take();
+
+        let result = f(self);
+
+        self.subscriptions.extend(subscriptions);
+        self.subscriptions
+            .sort_by_key(|subscription| subscription.id);
+        self.optimistic = optimistic;
+        self.windows = windows;
+        self.cursors = cursors;
+        self.midi_import = midi_import;
+        self.swap_open_song(index);
+        result
+    }
+
+    fn swap_open_song(&mut self, index: usize) {
+        let open = &mut self.open_songs[index];
+        std::mem::swap(&mut self.song, &mut open.song);
+        std::mem::swap(&mut self.frozen_tracks, &mut open.frozen_tracks);
+        std::mem::swap(&mut self.history, &mut open.history);
+        std::mem::swap(&mut self.transaction, &mut open.transaction);
+        std::mem::swap(&mut self.snapshots, &mut open.snapshots);
+        std::mem::swap(&mut self.outbox, &mut open.outbox);
+        std::mem::swap(&mut self.received_ops, &mut open.received_ops);
+    }
+
+    /// Sends the new song's snapshot to mirror subscribers and drops all state tied to the
+    /// previous one.
+    fn song_replaced(&mut self) {
+        self.revision += 1;
+        if let Some(song) = self.song.as_mut() {
+            song.rebase_revisions(self.revision);
+        }
+        if let Some(song) = &self.song {
+            let snapshot = MirrorFrame::snapshot(song).to_bytes();
+            for subscription in &self.subscriptions {
+                if subscription.delivery == Delivery::Mirror {
+                    let payload = js_sys::Uint8Array::from(snapshot.as_slice());
+                    let callback = subscription.callback.bind1(&JsValue::NULL, &payload);
+                    queue_microtask(callback.as_ref());
+                }
+            }
+        }
+        self.outbox = SyncQueue::default();
+        self.received_ops = ReceivedOperations::default();
+        self.optimistic = OptimisticUpdates::default();
+        self.frozen_tracks.clear();
+        self.windows.clear();
+        self.cursors.clear();
+        self.transaction = None;
+        self.midi_import = None;
+        self.snapshots.clear();
+    }
+
+    #[wasm_bindgen(js_name = clearSong)]
+    pub fn clear_song_js(&mut self) {
+        if let Some(song_id) = self.song_id.take() {
+            self.song_order.retain(|id| *id != song_id);
+        }
+        self.song = None;
+        self.history = History::default();
+        self.song_replaced();
+    }
+
+    #[wasm_bindgen(js_name = undo)]
+    pub fn undo_js(&mut self) -> Result<bool, StoreError> {
+        self.check_no_transaction("undo")?;
+        let step = self.history.undo(&mut self.song);
+        Ok(self.history_stepped(step))
+    }
+
+    #[wasm_bindgen(js_name = redo)]
+    pub fn redo_js(&mut self) -> Result<bool, StoreError> {
+        self.check_no_transaction("redo")?;
+        let step = self.history.redo(&mut self.song);
+        Ok(self.history_stepped(step))
+    }
+
+    #[wasm_bindgen(js_name = undoInTrack)]
+    pub fn undo_in_track_js(&mut self, track_id: &str) -> Result<bool, StoreError> {
+        let track_id = parse_id(track_id)?;
+        self.check_no_transaction("undo")?;
+        let step = self.history.undo_in_track(&track_id, &mut self.song);
+        Ok(self.history_stepped(step))
+    }
+
+    /// Undo and redo publish their edits at once, so they would break up an open transaction.
+    fn check_no_transaction(&self, action: &str) -> Result<(), StoreError> {
+        match self.transaction {
+            Some(_) => Err(StoreError::invalid(format!(
+                "Cannot {action} while a transaction is open"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Publishes what an undo or redo did. Its edits are synced like any local edit but are not
+    /// recorded in the history again.
+    fn history_stepped(&mut self, step: Option<HistoryStep>) -> bool {
+        match step {
+            Some(HistoryStep::Changes(conflicts)) => {
+                let origin = self.origin.clone();
+                self.commit_with(origin, true);
+                if !conflicts.is_empty() {
+                    self.warn(StoreWarning::Conflicts(conflicts));
+                }
+                true
+            }
+            Some(HistoryStep::Song) => {
+                self.song_replaced();
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[wasm_bindgen(js_name = beginTransaction)]
+    pub fn begin_transaction_js(&mut self) -> Result<(), StoreError> {
+        if self.transaction.is_some() {
+            return Err(StoreError::invalid("A transaction is already open"));
+        }
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        self.transaction = Some(Transaction::begin(song));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = commitTransaction)]
+    pub fn commit_transaction_js(&mut self) -> Result<Option<f64>, StoreError> {
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or_else(|| StoreError::invalid("No transaction is open"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        transaction.commit(song);
+        Ok(self.commit()?.map(|seq| seq as f64))
+    }
+
+    #[wasm_bindgen(js_name = rollbackTransaction)]
+    pub fn rollback_transaction_js(&mut self) -> Result<js_sys::Array, StoreError> {
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or_else(|| StoreError::invalid("No transaction is open"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        Ok(transaction
+            .rollback(song)
+            .iter()
+            .map(|conflict| conflict.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = canUndo)]
+    pub fn can_undo_js(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    #[wasm_bindgen(js_name = canRedo)]
+    pub fn can_redo_js(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    #[wasm_bindgen(js_name = beginHistoryGroup)]
+    pub fn begin_history_group_js(&mut self) {
+        self.history.begin_group();
+    }
+
+    #[wasm_bindgen(js_name = endHistoryGroup)]
+    pub fn end_history_group_js(&mut self) {
+        self.history.end_group();
+    }
+
+    #[wasm_bindgen(js_name = subscribe)]
+    pub fn subscribe_js(
+        &mut self,
+        callback: js_sys::Function,
+        detail: Option<String>,
+    ) -> Result<u32, StoreError> {
+        let detail = detail
+            .map(|detail| detail.parse::<ChangeDetail>())
+            .transpose()?;
+        Ok(self.add_subscription(
+            callback,
+            Delivery::Changes(detail.unwrap_or(ChangeDetail::Full)),
+        ))
+    }
+
+    #[wasm_bindgen(js_name = mirrorTo)]
+    pub fn mirror_to_js(&mut self, callback: js_sys::Function) -> u32 {
+        if let Some(song) = &self.song {
+            let bytes = MirrorFrame::snapshot(song).to_bytes();
+            let payload = js_sys::Uint8Array::from(bytes.as_slice());
+            queue_microtask(callback.bind1(&JsValue::NULL, &payload).as_ref());
+        }
+        self.add_subscription(callback, Delivery::Mirror)
+    }
+
+    #[wasm_bindgen(js_name = applyMirror)]
+    pub fn apply_mirror_js(&mut self, bytes: &[u8]) -> Result<(), StoreError> {
+        let frame = MirrorFrame::from_bytes(bytes)
+            .map_err(|_| StoreError::invalid("Mirror frame is not valid"))?;
+        frame.apply(&mut self.song);
+        self.commit_remote(frame.origin);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = subscribeToRecordedEvents)]
+    pub fn subscribe_to_recorded_events_js(
+        &mut self,
+        track_id: &str,
+        callback: js_sys::Function,
+    ) -> Result<u32, StoreError> {
+        let track_id = parse_id(track_id)?;
+        Ok(self.add_subscription(callback, Delivery::Recorded(track_id)))
+    }
+
+    #[wasm_bindgen(js_name = subscribeToWarnings)]
+    pub fn subscribe_to_warnings_js(&mut self, callback: js_sys::Function) -> u32 {
+        self.add_subscription(callback, Delivery::Warnings)
+    }
+
+    #[wasm_bindgen(js_name = unsubscribe)]
+    pub fn unsubscribe_js(&mut self, subscription_id: u32) {
+        self.subscriptions
+            .retain(|subscription| subscription.id != subscription_id);
+    }
+
+    #[wasm_bindgen(js_name = setOrigin)]
+    pub fn set_origin_js(&mut self, origin: Option<String>) {
+        self.origin = origin;
+    }
+
+    #[wasm_bindgen(js_name = setClock)]
+    pub fn set_clock_js(&mut self, clock: Option<js_sys::Function>) {
+        self.clock = clock;
+    }
+
+    #[wasm_bindgen(js_name = setValidationPolicy)]
+    pub fn set_validation_policy_js(
+        &mut self,
+        policy: &str,
+        max_ticks: Option<f64>,
+    ) -> Result<(), StoreError> {
+        let policy: ValidationPolicy = policy.parse()?;
+        self.validation = Validation {
+            policy,
+            max_ticks: max_ticks
+                .map(parse_ticks)
+                .transpose()?
+                .unwrap_or(Ticks::MAX),
+        };
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setQuota)]
+    pub fn set_quota_js(&mut self, max_events: Option<usize>, max_tracks: Option<usize>) {
+        self.quota = Quota {
+            max_events,
+            max_tracks,
+        };
+    }
+
+    #[wasm_bindgen(js_name = getQuotaUsage)]
+    pub fn get_quota_usage_js(&self) -> js_sys::Object {
+        QuotaUsage::new(self.song.as_ref(), &self.quota).to_js_object()
+    }
+
+    #[wasm_bindgen(js_name = compact)]
+    pub fn compact_js(&mut self) -> Result<JsValue, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        Ok(to_js_value(&song.compact()))
+    }
+
+    #[wasm_bindgen(js_name = getJournal)]
+    pub fn get_journal_js(&self, since_seq: Option<f64>) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song
+            .get_journal()
+            .entries_since(since_seq.unwrap_or(0.0) as u64)
+            .iter()
+            .map(|entry| entry.to_js_object(ChangeDetail::Full))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getRevision)]
+    pub fn get_revision_js(&self) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.get_revision() as f64)
+    }
+
+    #[wasm_bindgen(js_name = getTrackRevision)]
+    pub fn get_track_revision_js(&self, track_id: &str) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        song.get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(song.get_track_revision(&track_id) as f64)
+    }
+
+    #[wasm_bindgen(js_name = getEventsChangedSince)]
+    pub fn get_events_changed_since_js(&self, revision: f64) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        if revision < 0.0 || revision.fract() != 0.0 {
+            return Err(StoreError::invalid("Revision is not valid"));
+        }
+        Ok(match song.get_changes_since_revision(revision as u64) {
+            Some(changeset) => to_js_value(&RevisionChanges::new(song, &changeset)),
+            None => JsValue::NULL,
+        })
+    }
+
+    #[wasm_bindgen(js_name = getPendingOps)]
+    pub fn get_pending_ops_js(&self) -> js_sys::Array {
+        self.outbox
+            .get_pending()
+            .iter()
+            .map(|entry| entry.to_js_object())
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = markOpsSynced)]
+    pub fn mark_ops_synced_js(&mut self, up_to_seq: f64) {
+        self.outbox.mark_synced(up_to_seq as u64);
+    }
+
+    #[wasm_bindgen(js_name = receiveOps)]
+    pub fn receive_ops_js(
+        &mut self,
+        ops: js_sys::Array,
+        origin: Option<String>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (indexes, operations) = self.received_ops.filter_new(parse_ops(ops)?);
+        let mut report = self.outbox.rebase(song, &mut self.id_strategy, operations);
+        restore_conflict_indexes(&mut report.conflicts, &indexes);
+        self.commit_remote(origin);
+        Ok(report.to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = getTrack)]
+    pub fn get_track_js(&self, track_id: &str) -> Result<Option<js_sys::Object>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song.get_track(&track_id);
+        Ok(track.map(|track| song.track_to_js_object(track)))
+    }
+
+    #[wasm_bindgen(js_name = getTracks)]
+    pub fn get_tracks_js(&self, include_stats: Option<bool>) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let tracks = song.get_tracks();
+        if !include_stats.unwrap_or(false) {
+            return Ok(song.tracks_to_js_array());
+        }
+
+        let tracks: Vec<_> = tracks
+            .iter()
+            .map(|track| {
+                let mut js_track = song.js_track(track);
+                js_track.stats = Some(TrackStats::new(song, track));
+                js_track
+            })
+            .collect();
+        Ok(to_js_value(&tracks).unchecked_into())
+    }
+
+    #[wasm_bindgen(js_name = getTrackExtent)]
+    pub fn get_track_extent_js(&self, track_id: &str) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(to_js_value(&TrackExtent::of(song, track)))
+    }
+
+    #[wasm_bindgen(js_name = trimTrackToContent)]
+    pub fn trim_track_to_content_js(
+        &mut self,
+        track_id: &str,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let removed_ids = edit_song(song, |song| {
+            trim_track_to_content(song, parse_id(track_id)?)
+        })?;
+        self.commit()?;
+        Ok(removed_ids
+            .iter()
+            .map(|id| JsValue::from_str(id.to_string().as_str()))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = addTrack)]
+    pub fn add_track_js(&mut self, track: js_sys::Object) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (track, events) = Track::from_js_object(track)?;
+        let track_id = song.add_track_with_events(track, events)?.id;
+        let track = song.track_to_js_object(song.get_track(&track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = removeTrack)]
+    pub fn remove_track_js(&mut self, track_id: &str) -> Result<(), StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.remove_track(&track_id);
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = insertTrackAt)]
+    pub fn insert_track_at_js(
+        &mut self,
+        index: usize,
+        track: Option<js_sys::Object>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (track, events) = match track {
+            Some(track) => Track::from_js_object(track)?,
+            None => (Track::new(self.id_strategy.next_id()?), Vec::new()),
+        };
+        let track_id = song.insert_track_at(index, track, events)?.id;
+        let track = song.track_to_js_object(song.get_track(&track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = moveTrack)]
+    pub fn move_track_js(&mut self, track_id: &str, new_index: usize) -> Result<(), StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.move_track(&track_id, new_index)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = duplicateTrack)]
+    pub fn duplicate_track_js(&mut self, track_id: &str) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let new_track_id = edit_song(song, |song| {
+            duplicate_track(song, &mut self.id_strategy, track_id)
+        })?;
+        let track = song.track_to_js_object(song.get_track(&new_track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = getClips)]
+    pub fn get_clips_js(&self, track_id: Option<String>) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = track_id.as_deref().map(parse_id).transpose()?;
+        if let Some(track_id) = track_id {
+            song.get_track(&track_id)
+                .ok_or(StoreError::TrackNotFound(track_id))?;
+        }
+        Ok(song
+            .get_tracks()
+            .iter()
+            .filter(|track| track_id.is_none_or(|track_id| track.id == track_id))
+            .flat_map(|track| {
+                track
+                    .clips
+                    .iter()
+                    .map(|clip| clip.to_js_value(song, track.id))
+            })
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = addClip)]
+    pub fn add_clip_js(
+        &mut self,
+        track_id: &str,
+        start_ticks: f64,
+        length: f64,
+        event_ids: Option<js_sys::Array>,
+    ) -> Result<JsValue, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let event_ids = event_ids.map(ids_from_js_array).transpose()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let clip = edit_song(song, |song| {
+            add_clip(
+                song,
+                &mut self.id_strategy,
+                track_id,
+                parse_ticks(start_ticks)?,
+                parse_ticks(length)?,
+                event_ids,
+            )
+        })?;
+        let js_clip = clip.to_js_value(song, track_id);
+        self.commit()?;
+        Ok(js_clip)
+    }
+
+    #[wasm_bindgen(js_name = moveClip)]
+    pub fn move_clip_js(&mut self, clip_id: &str, start_ticks: f64) -> Result<JsValue, StoreError> {
+        let clip_id = parse_id(clip_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (track_id, clip) = edit_song(song, |song| {
+            move_clip(song, &clip_id, parse_ticks(start_ticks)?)
+        })?;
+        let js_clip = clip.to_js_value(song, track_id);
+        self.commit()?;
+        Ok(js_clip)
+    }
+
+    #[wasm_bindgen(js_name = resizeClip)]
+    pub fn resize_clip_js(
+        &mut self,
+        clip_id: &str,
+        start_ticks: f64,
+        length: f64,
+    ) -> Result<JsValue, StoreError> {
+        let clip_id = parse_id(clip_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (track_id, clip) = edit_song(song, |song| {
+            resize_clip(
+                song,
+                &clip_id,
+                parse_ticks(start_ticks)?,
+                parse_ticks(length)?,
+            )
+        })?;
+        let js_clip = clip.to_js_value(song, track_id);
+        self.commit()?;
+        Ok(js_clip)
+    }
+
+    #[wasm_bindgen(js_name = duplicateClip)]
+    pub fn duplicate_clip_js(
+        &mut self,
+        clip_id: &str,
+        start_ticks: Option<f64>,
+    ) -> Result<JsValue, StoreError> {
+        let clip_id = parse_id(clip_id)?;
+        let start_ticks = start_ticks.map(parse_ticks).transpose()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (track_id, clip) = edit_song(song, |song| {
+            duplicate_clip(song, &mut self.id_strategy, &clip_id, start_ticks)
+        })?;
+        let js_clip = clip.to_js_value(song, track_id);
+        self.commit()?;
+        Ok(js_clip)
+    }
+
+    #[wasm_bindgen(js_name = removeClip)]
+    pub fn remove_clip_js(
+        &mut self,
+        clip_id: &str,
+        remove_events: Option<bool>,
+    ) -> Result<(), StoreError> {
+        let clip_id = parse_id(clip_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        edit_song(song, |song| {
+            remove_clip(song, &clip_id, remove_events.unwrap_or(false))
+        })?;
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setTrackSettings)]
+    pub fn set_track_settings_js(
+        &mut self,
+        track_id: &str,
+        settings: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let settings: TrackSettings = serde_wasm_bindgen::from_value(settings)
+            .map_err(|_| StoreError::invalid("Track settings are not valid"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.settings = settings;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackPitchRange)]
+    pub fn set_track_pitch_range_js(
+        &mut self,
+        track_id: &str,
+        pitch_range: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let pitch_range: Option<PitchRange> = serde_wasm_bindgen::from_value(pitch_range)
+            .map_err(|_| StoreError::invalid("Track pitch range is not valid"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.pitch_range = pitch_range;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackPitchBendRange)]
+    pub fn set_track_pitch_bend_range_js(
+        &mut self,
+        track_id: &str,
+        semitones: u8,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        if !(1..=127).contains(&semitones) {
+            return Err(StoreError::invalid("Pitch bend range is not valid"));
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.pitch_bend_range = semitones;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackExpressionMap)]
+    pub fn set_track_expression_map_js(
+        &mut self,
+        track_id: &str,
+        expression_map: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let expression_map: ExpressionMap = serde_wasm_bindgen::from_value(expression_map)
+            .map_err(|_| StoreError::invalid("Track expression map is not valid"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.expression_map = expression_map;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = bendValueForSemitones)]
+    pub fn bend_value_for_semitones_js(
+        &self,
+        track_id: &str,
+        cents: f64,
+    ) -> Result<u16, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(track.bend_value_for_cents(cents))
+    }
+
+    #[wasm_bindgen(js_name = centsForBendValue)]
+    pub fn cents_for_bend_value_js(&self, track_id: &str, value: u16) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(track.cents_for_bend_value(value))
+    }
+
+    #[wasm_bindgen(js_name = setTrackMute)]
+    pub fn set_track_mute_js(
+        &mut self,
+        track_id: &str,
+        mute: bool,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.mix.mute = mute;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackSolo)]
+    pub fn set_track_solo_js(
+        &mut self,
+        track_id: &str,
+        solo: bool,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.mix.solo = solo;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackGroup)]
+    pub fn set_track_group_js(
+        &mut self,
+        track_id: &str,
+        group: Option<String>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.mix.group = group;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackVolume)]
+    pub fn set_track_volume_js(
+        &mut self,
+        track_id: &str,
+        volume: f64,
+    ) -> Result<js_sys::Object, StoreError> {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(StoreError::invalid("Volume must be from 0 to 1"));
+        }
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.mix.volume = volume;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackPan)]
+    pub fn set_track_pan_js(
+        &mut self,
+        track_id: &str,
+        pan: f64,
+    ) -> Result<js_sys::Object, StoreError> {
+        if !(-1.0..=1.0).contains(&pan) {
+            return Err(StoreError::invalid("Pan must be from -1 to 1"));
+        }
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.mix.pan = pan;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackDisplay)]
+    pub fn set_track_display_js(
+        &mut self,
+        track_id: &str,
+        display: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let display: TrackDisplay = serde_wasm_bindgen::from_value(display)
+            .map_err(|_| StoreError::invalid("Track display is not valid"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track = song
+            .get_track_mut(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        track.display = display;
+        let track = song.get_track(&track_id).unwrap();
+        Ok(song.track_to_js_object(track))
+    }
+
+    #[wasm_bindgen(js_name = setTrackOutput)]
+    pub fn set_track_output_js(
+        &mut self,
+        track_id: &str,
+        output: Option<String>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.update_track(&track_id, |track| track.output = output)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let track = song.track_to_js_object(song.get_track(&track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = updateTrack)]
+    pub fn update_track_js(
+        &mut self,
+        track_updater: js_sys::Object,
+    ) -> Result<js_sys::Object, StoreError> {
+        let updater = TrackUpdater::from_js_object(track_updater)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.update_track(&updater.id, |track| updater.apply(track))
+            .ok_or(StoreError::TrackNotFound(updater.id))?;
+        let track = song.track_to_js_object(song.get_track(&updater.id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = getGrooves)]
+    pub fn get_grooves_js(&self) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.grooves.to_js_array())
+    }
+
+    #[wasm_bindgen(js_name = setGroove)]
+    pub fn set_groove_js(&mut self, groove: JsValue) -> Result<JsValue, StoreError> {
+        let groove: Groove = serde_wasm_bindgen::from_value(groove)
+            .map_err(|_| StoreError::invalid("Groove is not valid"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let js_groove = serde_wasm_bindgen::to_value(&groove).unwrap();
+        song.grooves.put(groove);
+        Ok(js_groove)
+    }
+
+    #[wasm_bindgen(js_name = applyGroove)]
+    pub fn apply_groove_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        groove_id: &str,
+        amount: Option<f64>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let amount = amount.unwrap_or(1.0);
+        if !(0.0..=1.0).contains(&amount) {
+            return Err(StoreError::invalid("Groove amount must be from 0 to 1"));
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let groove_id = parse_id(groove_id)?;
+        let events = edit_song(song, |song| {
+            apply_groove(song, &event_ids, &groove_id, amount)
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = removeGroove)]
+    pub fn remove_groove_js(&mut self, groove_id: &str) -> Result<(), StoreError> {
+        let groove_id = parse_id(groove_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.remove_groove(&groove_id)
+            .ok_or_else(|| StoreError::invalid("Groove not found"))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getPerformanceProfiles)]
+    pub fn get_performance_profiles_js(&self) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song
+            .profiles
+            .iter()
+            .map(|profile| serde_wasm_bindgen::to_value(profile).unwrap())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = setPerformanceProfile)]
+    pub fn set_performance_profile_js(&mut self, profile: JsValue) -> Result<JsValue, StoreError> {
+        let profile: PerformanceProfile = serde_wasm_bindgen::from_value(profile)
+            .map_err(|_| StoreError::invalid("Performance profile is not valid"))?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let js_profile = serde_wasm_bindgen::to_value(&profile).unwrap();
+        song.profiles.put(profile);
+        Ok(js_profile)
+    }
+
+    #[wasm_bindgen(js_name = removePerformanceProfile)]
+    pub fn remove_performance_profile_js(&mut self, profile_id: &str) -> Result<(), StoreError> {
+        let profile_id = parse_id(profile_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.profiles
+            .remove(&profile_id)
+            .ok_or_else(|| StoreError::invalid("Performance profile not found"))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getTrackPerformanceProfile)]
+    pub fn get_track_performance_profile_js(&self, track_id: &str) -> Result<JsValue, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        song.get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(song
+            .profiles
+            .of_track(&track_id)
+            .map_or(JsValue::UNDEFINED, |profile| {
+                serde_wasm_bindgen::to_value(profile).unwrap()
+            }))
+    }
+
+    #[wasm_bindgen(js_name = setTrackPerformanceProfile)]
+    pub fn set_track_performance_profile_js(
+        &mut self,
+        track_id: &str,
+        profile_id: Option<String>,
+    ) -> Result<(), StoreError> {
+        let track_id = parse_id(track_id)?;
+        let profile_id = profile_id.as_deref().map(parse_id).transpose()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        if let Some(profile_id) = &profile_id {
+            song.profiles
+                .get(profile_id)
+                .ok_or_else(|| StoreError::invalid("Performance profile not found"))?;
+        }
+        song.profiles.assign(track_id, profile_id);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getGroups)]
+    pub fn get_groups_js(&self) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song
+            .groups
+            .iter()
+            .map(|group| group.to_js_value(song))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = createGroup)]
+    pub fn create_group_js(
+        &mut self,
+        name: String,
+        event_ids: js_sys::Array,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        if event_ids.is_empty() {
+            return Err(StoreError::invalid("A group needs at least one event"));
+        }
+        song.try_get_events(&event_ids)?;
+        let group = song
+            .groups
+            .create(self.id_strategy.next_id()?, name, event_ids)
+            .clone();
+        Ok(group.to_js_value(song))
+    }
+
+    #[wasm_bindgen(js_name = getGroup)]
+    pub fn get_group_js(&self, event_id: &str) -> Result<JsValue, StoreError> {
+        let event_id = parse_id(event_id)?;
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song
+            .groups
+            .of_event(&event_id)
+            .map_or(JsValue::UNDEFINED, |group| group.to_js_value(song)))
+    }
+
+    #[wasm_bindgen(js_name = removeGroup)]
+    pub fn remove_group_js(&mut self, group_id: &str) -> Result<(), StoreError> {
+        let group_id = parse_id(group_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        song.groups
+            .remove(&group_id)
+            .ok_or_else(|| StoreError::invalid("Group not found"))?;
+        song.set_muted(group_id, false);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setEventsMuted)]
+    pub fn set_events_muted_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        muted: bool,
+    ) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        song.try_get_events(&event_ids)?;
+        for event_id in event_ids {
+            song.set_muted(event_id, muted);
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setGroupMuted)]
+    pub fn set_group_muted_js(&mut self, group_id: &str, muted: bool) -> Result<(), StoreError> {
+        let group_id = parse_id(group_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        if !song.groups.iter().any(|group| group.id == group_id) {
+            return Err(StoreError::invalid("Group not found"));
+        }
+        song.set_muted(group_id, muted);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getEffectiveEventState)]
+    pub fn get_effective_event_state_js(&self, event_id: &str) -> Result<JsValue, StoreError> {
+        let event_id = parse_id(event_id)?;
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let event = song
+            .get_event(&event_id)
+            .ok_or(StoreError::EventNotFound(event_id))?;
+        Ok(to_js_value(&EffectiveEventState::of(song, event)))
+    }
+
+    #[wasm_bindgen(js_name = isTrackAudible)]
+    pub fn is_track_audible_js(&self, track_id: &str) -> Result<bool, StoreError> {
+        let track_id = parse_id(track_id)?;
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        song.is_track_audible(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))
+    }
+
+    #[wasm_bindgen(js_name = getOutOfRangeNotes)]
+    pub fn get_out_of_range_notes_js(&self, track_id: &str) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        if song.get_track(&track_id).is_none() {
+            return Err(StoreError::TrackNotFound(track_id));
+        }
+        Ok(song.events_to_js_array(song.get_out_of_range_notes(&track_id)))
+    }
+
+    #[wasm_bindgen(js_name = freezeTrackState)]
+    pub fn freeze_track_state_js(
+        &mut self,
+        track_id: &str,
+        label: Option<String>,
+    ) -> Result<(), StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        self.frozen_tracks
+            .insert(track_id, FrozenTrack::capture(song, track, label));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = diffAgainstFrozen)]
+    pub fn diff_against_frozen_js(&self, track_id: &str) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let frozen = self
+            .frozen_tracks
+            .get(&track_id)
+            .ok_or_else(|| StoreError::invalid("Track has not been frozen"))?;
+        Ok(frozen.diff(song, track).to_js_object(song))
+    }
+
+    #[wasm_bindgen(js_name = previewMidi)]
+    pub fn preview_midi_js(&self, bytes: &[u8]) -> Result<js_sys::Object, StoreError> {
+        Ok(MidiPreview::from_bytes(bytes)?.to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = importMidi)]
+    pub fn import_midi_js(
+        &mut self,
+        bytes: &[u8],
+        options: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let options: MidiImportOptions = if options.is_undefined() {
+            MidiImportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("MIDI import options are not valid"))?
+        };
+
+        let import = match self.song.as_mut() {
+            Some(song) => edit_song(song, |song| {
+                Ok(import_midi(song, &mut self.id_strategy, bytes, options)?)
+            })?,
+            None => {
+                let (song, import) = Song::from_smf(&mut self.id_strategy, bytes, options)?;
+                self.quota.check_song(&song)?;
+                self.set_song(song)?;
+                import
+            }
+        };
+        let js_result = import.to_js_object(self.song.as_ref().unwrap());
+
+        self.commit()?;
+        Ok(js_result)
+    }
+
+    #[wasm_bindgen(js_name = startMidiImport)]
+    pub fn start_midi_import_js(
+        &mut self,
+        bytes: &[u8],
+        options: JsValue,
+    ) -> Result<usize, StoreError> {
+        let options: MidiImportOptions = if options.is_undefined() {
+            MidiImportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("MIDI import options are not valid"))?
+        };
+
+        if self.midi_import.is_some() {
+            return Err(StoreError::invalid("A MIDI import is already running"));
+        }
+
+        let job = match self.song.as_mut() {
+            Some(song) => start_midi_import(song, &mut self.id_strategy, bytes, options)?,
+            None => {
+                let (song, job) = Song::start_from_smf(&mut self.id_strategy, bytes, options)?;
+                self.quota.check_song(&song)?;
+                self.set_song(song)?;
+                job
+            }
+        };
+        let total = job.total();
+        self.midi_import = Some(job);
+        Ok(total)
+    }
+
+    #[wasm_bindgen(js_name = continueMidiImport)]
+    pub fn continue_midi_import_js(&mut self, max_events: usize) -> Result<usize, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let job = self
+            .midi_import
+            .as_mut()
+            .ok_or_else(|| StoreError::invalid("No MIDI import is running"))?;
+        Ok(job.step(song, max_events))
+    }
+
+    #[wasm_bindgen(js_name = finishMidiImport)]
+    pub fn finish_midi_import_js(&mut self) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let mut job = self
+            .midi_import
+            .take()
+            .ok_or_else(|| StoreError::invalid("No MIDI import is running"))?;
+        job.step(song, usize::MAX);
+        let js_result = job.import.to_js_object(song);
+
+        if job.new_song {
+            song.take_changes();
+        } else {
+            self.commit()?;
+        }
+        Ok(js_result)
+    }
+
+    #[wasm_bindgen(js_name = exportMidi)]
+    pub fn export_midi_js(&self, options: JsValue) -> Result<Vec<u8>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: MidiExportOptions = if options.is_undefined() {
+            MidiExportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("MIDI export options are not valid"))?
+        };
+        Ok(export_midi(song, &options))
+    }
+
+    #[wasm_bindgen(js_name = exportAbc)]
+    pub fn export_abc_js(&self, track_id: &str, options: JsValue) -> Result<String, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let options: NotationExportOptions = if options.is_undefined() {
+            NotationExportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Export options are not valid"))?
+        };
+        Ok(export_abc(song, track, options.quantize))
+    }
+
+    #[wasm_bindgen(js_name = exportLrc)]
+    pub fn export_lrc_js(&self, options: JsValue) -> Result<String, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: LyricExportOptions = if options.is_undefined() {
+            LyricExportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Export options are not valid"))?
+        };
+        Ok(export_lrc(song, &options))
+    }
+
+    #[wasm_bindgen(js_name = exportLyricsMidi)]
+    pub fn export_lyrics_midi_js(&self, options: JsValue) -> Result<Vec<u8>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: LyricExportOptions = if options.is_undefined() {
+            LyricExportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Export options are not valid"))?
+        };
+        Ok(export_lyrics_midi(song, &options))
+    }
+
+    #[wasm_bindgen(js_name = flattenTracks)]
+    pub fn flatten_tracks_js(
+        &mut self,
+        track_ids: js_sys::Array,
+        apply_non_destructive_settings: bool,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_ids = ids_from_js_array(track_ids)?;
+        let track_id = edit_song(song, |song| {
+            flatten_tracks(
+                song,
+                &mut self.id_strategy,
+                &track_ids,
+                apply_non_destructive_settings,
+            )
+            .map(|track| track.id)
+        })?;
+        let track = song.track_to_js_object(song.get_track(&track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = splitTrackByChannel)]
+    pub fn split_track_by_channel_js(
+        &mut self,
+        track_id: &str,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let new_track_ids = edit_song(song, |song| {
+            split_track_by_channel(song, &mut self.id_strategy, track_id)
+        })?;
+        let tracks = new_track_ids
+            .iter()
+            .map(|track_id| song.track_to_js_object(song.get_track(track_id).unwrap()))
+            .collect();
+        self.commit()?;
+        Ok(tracks)
+    }
+
+    #[wasm_bindgen(js_name = splitTrackByPitch)]
+    pub fn split_track_by_pitch_js(
+        &mut self,
+        track_id: &str,
+        split_note: u8,
+    ) -> Result<js_sys::Object, StoreError> {
+        if split_note > 127 {
+            return Err(StoreError::invalid("Split note must be from 0 to 127"));
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let low_track_id = edit_song(song, |song| {
+            split_track_by_pitch(
+                song,
+                &mut self.id_strategy,
+                track_id,
+                NoteNumber::new(split_note),
+            )
+        })?;
+        let track = song.track_to_js_object(song.get_track(&low_track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = splitByVelocity)]
+    pub fn split_by_velocity_js(
+        &mut self,
+        track_id: &str,
+        threshold: u8,
+        split: &str,
+    ) -> Result<js_sys::Object, StoreError> {
+        let split: VelocitySplit = split.parse()?;
+        if threshold > 127 {
+            return Err(StoreError::invalid(
+                "Velocity threshold must be from 0 to 127",
+            ));
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let new_track_id = edit_song(song, |song| {
+            split_track_by_velocity(
+                song,
+                &mut self.id_strategy,
+                track_id,
+                Velocity::new(threshold),
+                split,
+            )
+        })?;
+        let track = song.track_to_js_object(song.get_track(&new_track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = mergeTracks)]
+    pub fn merge_tracks_js(
+        &mut self,
+        track_ids: js_sys::Array,
+        target_track_id: &str,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_ids = ids_from_js_array(track_ids)?;
+        let target_track_id = parse_id(target_track_id)?;
+        edit_song(song, |song| merge_tracks(song, &track_ids, target_track_id))?;
+        let track = song.track_to_js_object(song.get_track(&target_track_id).unwrap());
+        self.commit()?;
+        Ok(track)
+    }
+
+    #[wasm_bindgen(js_name = startTake)]
+    pub fn start_take_js(&mut self, track_id: &str) -> Result<usize, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        song.get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(song.start_take(track_id))
+    }
+
+    #[wasm_bindgen(js_name = mergeTakes)]
+    pub fn merge_takes_js(
+        &mut self,
+        track_id: &str,
+        merge: &str,
+    ) -> Result<js_sys::Array, StoreError> {
+        let merge: TakeMerge = merge.parse()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track_ids = edit_song(song, |song| {
+            merge_takes(song, &mut self.id_strategy, track_id, merge)
+        })?;
+        let tracks = track_ids
+            .iter()
+            .map(|track_id| song.track_to_js_object(song.get_track(track_id).unwrap()))
+            .collect();
+        self.commit()?;
+        Ok(tracks)
+    }
+
+    #[wasm_bindgen(js_name = setInputLatency)]
+    pub fn set_input_latency_js(&mut self, latency_ms: f64) -> Result<(), StoreError> {
+        if !latency_ms.is_finite() || latency_ms < 0.0 {
+            return Err(StoreError::invalid("Input latency must be zero or more"));
+        }
+        self.recording_clock.latency_ms = latency_ms;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setRecordingClock)]
+    pub fn set_recording_clock_js(&mut self, tempo_aware: bool) {
+        self.recording_clock.tempo_aware = tempo_aware;
+    }
+
+    #[wasm_bindgen(js_name = startRecording)]
+    pub fn start_recording_js(&mut self, host_time: f64, ticks: f64) -> Result<(), StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        self.recording_clock
+            .start(song, host_time, parse_ticks(ticks)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = hostTimeToTicks)]
+    pub fn host_time_to_ticks_js(&self, host_time: f64) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(self.recording_clock.ticks_at(song, host_time)?.as_f64())
+    }
+
+    #[wasm_bindgen(js_name = recordNote)]
+    pub fn record_note_js(
+        &mut self,
+        track_id: &str,
+        note_number: u8,
+        velocity: u8,
+        start_time: f64,
+        end_time: f64,
+        channel: Option<u8>,
+    ) -> Result<Option<js_sys::Object>, StoreError> {
+        if note_number > 127 || velocity > 127 {
+            return Err(StoreError::invalid(
+                "Note number and velocity must be from 0 to 127",
+            ));
+        }
+        let track_id = parse_id(track_id)?;
+        if !self.accepts_record_input(track_id, RecordInputKind::Note, channel)? {
+            return Ok(None);
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let ticks = self.recording_clock.ticks_at(song, start_time)?;
+        let end = self.recording_clock.ticks_at(song, end_time)?;
+        let note = Event::Note(Note {
+            id: self.id_strategy.next_id()?,
+            ticks,
+            duration: (end - ticks).max(Ticks::new(1)),
+            velocity: Velocity::new(velocity),
+            note_number: NoteNumber::new(note_number),
+            track_id,
+            parameters: NoteParameters::new(),
+        });
+        self.add_recorded_event(note, channel).map(Some)
+    }
 
-  getSong(): Song | undefined;
+    #[wasm_bindgen(js_name = recordControlChange)]
+    pub fn record_control_change_js(
+        &mut self,
+        track_id: &str,
+        controller: u8,
+        value: u8,
+        host_time: f64,
+        channel: Option<u8>,
+    ) -> Result<Option<js_sys::Object>, StoreError> {
+        if controller > 127 || value > 127 {
+            return Err(StoreError::invalid(
+                "Controller and value must be from 0 to 127",
+            ));
+        }
+        let track_id = parse_id(track_id)?;
+        if !self.accepts_record_input(track_id, RecordInputKind::ControlChange, channel)? {
+            return Ok(None);
+        }
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let cc = Event::ControlChange(ControlChange {
+            id: self.id_strategy.next_id()?,
+            ticks: self.recording_clock.ticks_at(song, host_time)?,
+            controller: ControllerNumber::new(controller),
+            value: ControllerValue::new(value),
+            track_id,
+        });
+        self.add_recorded_event(cc, channel).map(Some)
+    }
 
-  createSong(title: string, ppq: number): void;
+    #[wasm_bindgen(js_name = setRecordFilter)]
+    pub fn set_record_filter_js(
+        &mut self,
+        track_id: &str,
+        filter: JsValue,
+    ) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let filter: Option<RecordFilter> = serde_wasm_bindgen::from_value(filter)
+            .map_err(|_| StoreError::invalid("Record filter is not valid"))?;
+        if let Some(filter) = &filter {
+            filter.check()?;
+        }
+        song.update_track(&track_id, |track| track.settings.record_filter = filter)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        self.commit()?;
+        Ok(())
+    }
 
-  clearSong(): void;
+    #[wasm_bindgen(js_name = getRecordFilter)]
+    pub fn get_record_filter_js(&self, track_id: &str) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(track
+            .settings
+            .record_filter
+            .as_ref()
+            .map_or(JsValue::UNDEFINED, to_js_value))
+    }
 
-  getTrack(trackId: string): Track | undefined;
+    #[wasm_bindgen(js_name = acceptsRecordInput)]
+    pub fn accepts_record_input_js(
+        &self,
+        track_id: &str,
+        kind: JsValue,
+        channel: Option<u8>,
+    ) -> Result<bool, StoreError> {
+        let kind: RecordInputKind = serde_wasm_bindgen::from_value(kind)
+            .map_err(|_| StoreError::invalid("Record input kind is not valid"))?;
+        self.accepts_record_input(parse_id(track_id)?, kind, channel)
+    }
 
-  getTracks(): Track[];
+    #[wasm_bindgen(js_name = getSupportedEventKinds)]
+    pub fn get_supported_event_kinds_js(&self) -> js_sys::Array {
+        EventKindSchema::to_js_array(&EventKindSchema::all())
+    }
 
-  addTrack(track: Track): Track;
+    #[wasm_bindgen(js_name = getEvent)]
+    pub fn get_event_js(&self, event_id: &str) -> Result<Option<js_sys::Object>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let event_id = parse_id(event_id)?;
+        let event = song.get_event(&event_id);
+        Ok(event.map(|event| song.event_to_js_object(event)))
+    }
 
-  removeTrack(trackId: string): void;
+    #[wasm_bindgen(js_name = getEvents)]
+    pub fn get_events_js(
+        &self,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let events = song.get_events(events_filter(track_ids)?);
+        Ok(song.events_to_js_array(events))
+    }
 
-  getEvent(eventId: string): Event | undefined;
+    #[wasm_bindgen(js_name = getEventsInTicksRange)]
+    pub fn get_events_in_ticks_range_js(
+        &self,
+        start_ticks: f64,
+        end_ticks: f64,
+        within_duration: bool,
+        track_ids: Option<js_sys::Array>,
+        options: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: TicksRangeOptions = if options.is_undefined() {
+            TicksRangeOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Ticks range options are not valid"))?
+        };
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let filter = events_filter(track_ids)?;
+        let events =
+            song.get_events_in_ticks_range(start_ticks, end_ticks, within_duration, filter.clone());
+        if !options.note_offs {
+            return Ok(song.events_to_js_array(events));
+        }
+        let items = with_note_offs(song, events, start_ticks, end_ticks, filter);
+        Ok(RangeItem::to_js_array(song, &items))
+    }
 
-  getEvents(): Event[];
+    #[wasm_bindgen(js_name = getTrackEvents)]
+    pub fn get_track_events_js(&self, track_id: &str) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        Ok(song.events_to_js_array(song.track_events(track).iter()))
+    }
 
-  getEventsInTicksRange(startTicks: number, endTicks: number, withinDuration: boolean): Event[];
+    #[wasm_bindgen(js_name = getTrackEventsInTicksRange)]
+    pub fn get_track_events_in_ticks_range_js(
+        &self,
+        track_id: &str,
+        start_ticks: f64,
+        end_ticks: f64,
+        within_duration: bool,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let events =
+            song.track_events(track)
+                .in_ticks_range(start_ticks, end_ticks, within_duration);
+        Ok(song.events_to_js_array(events))
+    }
 
-  addEvent(event: Event): Event;
+    #[wasm_bindgen(js_name = queryEvents)]
+    pub fn query_events_js(&self, filter: JsValue) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let filter: EventFilter = serde_wasm_bindgen::from_value(filter)
+            .map_err(|_| StoreError::invalid("Event filter is not valid"))?;
+        Ok(song.events_to_js_array(query_events(song, &filter)))
+    }
 
-  updateEvent(event: EventUpdater): Event;
+    #[wasm_bindgen(js_name = getNextEvent)]
+    pub fn get_next_event_js(&self, from: JsValue, filter: JsValue) -> Result<JsValue, StoreError> {
+        self.adjacent_event(from, filter, false)
+    }
 
-  removeEvent(eventId: string): void;
-}
-"#;
+    #[wasm_bindgen(js_name = getPreviousEvent)]
+    pub fn get_previous_event_js(
+        &self,
+        from: JsValue,
+        filter: JsValue,
+    ) -> Result<JsValue, StoreError> {
+        self.adjacent_event(from, filter, true)
+    }
 
-#[wasm_bindgen(skip_typescript)]
-pub struct Store {
-    song: Option<Song>,
-}
+    fn adjacent_event(
+        &self,
+        from: JsValue,
+        filter: JsValue,
+        reverse: bool,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let anchor = match (from.as_string(), from.as_f64()) {
+            (Some(event_id), _) => NavigationAnchor::Event(parse_id(&event_id)?),
+            (_, Some(ticks)) => NavigationAnchor::Ticks(parse_ticks(ticks)?),
+            _ => return Err(StoreError::invalid("Navigation start is not valid")),
+        };
+        let filter: EventFilter = if filter.is_undefined() {
+            EventFilter::default()
+        } else {
+            serde_wasm_bindgen::from_value(filter)
+                .map_err(|_| StoreError::invalid("Event filter is not valid"))?
+        };
+        Ok(
+            adjacent_event(song, anchor, reverse, &filter)?.map_or(JsValue::UNDEFINED, |event| {
+                song.event_to_js_object(event).into()
+            }),
+        )
+    }
 
-#[wasm_bindgen]
-impl Store {
-    pub(crate) fn new() -> Self {
-        Store { song: None }
+    #[wasm_bindgen(js_name = getEventTable)]
+    pub fn get_event_table_js(&self, options: JsValue) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: EventTableOptions = if options.is_undefined() {
+            EventTableOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Event table options are not valid"))?
+        };
+        Ok(event_table(song, &options).to_js_object(song))
     }
 
-    #[wasm_bindgen(constructor)]
-    pub fn new_js() -> Self {
-        Self::new()
+    #[wasm_bindgen(js_name = getEventsInRanges)]
+    pub fn get_events_in_ranges_js(
+        &self,
+        ranges: JsValue,
+        filter: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let ranges: Vec<TicksRange> = serde_wasm_bindgen::from_value(ranges)
+            .map_err(|_| StoreError::invalid("Ranges are not valid"))?;
+        let filter: Option<GetEventsFilter> = serde_wasm_bindgen::from_value(filter)
+            .map_err(|_| StoreError::invalid("Filter is not valid"))?;
+        Ok(song
+            .get_events_in_ranges(&ranges, filter)
+            .iter()
+            .map(|events| song.events_to_js_array(events.iter().copied()))
+            .collect())
     }
 
-    #[wasm_bindgen(js_name = getSong)]
-    pub fn get_song_js(&self) -> Option<js_sys::Object> {
-        self.song.as_ref().map(|song| song.to_js_object())
+    #[wasm_bindgen(js_name = getScrubEvents)]
+    pub fn get_scrub_events_js(
+        &self,
+        prev_ticks: f64,
+        new_ticks: f64,
+        filter: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let filter: Option<GetEventsFilter> = serde_wasm_bindgen::from_value(filter)
+            .map_err(|_| StoreError::invalid("Filter is not valid"))?;
+        let events =
+            song.get_scrub_events(parse_ticks(prev_ticks)?, parse_ticks(new_ticks)?, filter);
+        Ok(song.events_to_js_array(events))
     }
 
-    #[wasm_bindgen(js_name = createSong)]
-    pub fn create_song_js(&mut self, title: String, ppq: u32) {
-        self.song = Some(Song::new(title, ppq));
+    #[wasm_bindgen(js_name = getPlaybackEvents)]
+    pub fn get_playback_events_js(
+        &self,
+        start_ticks: f64,
+        end_ticks: f64,
+        options: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let options: PlaybackOptions = if options.is_undefined() {
+            PlaybackOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Playback options are not valid"))?
+        };
+        let events = playback_events(song, start_ticks, end_ticks, &options);
+        Ok(song.events_to_js_array(&events))
     }
 
-    #[wasm_bindgen(js_name = clearSong)]
-    pub fn clear_song_js(&mut self) {
-        self.song = None;
+    #[wasm_bindgen(js_name = getActiveEventsAt)]
+    pub fn get_active_events_at_js(
+        &self,
+        ticks: f64,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let events = song.get_active_events_at(parse_ticks(ticks)?, events_filter(track_ids)?);
+        Ok(song.events_to_js_array(events))
     }
 
-    #[wasm_bindgen(js_name = getTrack)]
-    pub fn get_track_js(&self, track_id: &str) -> Option<js_sys::Object> {
-        let song = self.song.as_ref().expect_throw("Song is not set");
-        let track_id = Id::try_from(track_id).expect_throw("Track id is not valid");
-        let track = song.get_track(&track_id);
-        track.map(|track| track.to_js_object())
+    #[wasm_bindgen(js_name = getNotesInTicksRangeFlat)]
+    pub fn get_notes_in_ticks_range_flat_js(
+        &self,
+        start_ticks: f64,
+        end_ticks: f64,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        Ok(flat_notes_in_ticks_range(song, start_ticks, end_ticks).to_js_object())
     }
 
-    #[wasm_bindgen(js_name = getTracks)]
-    pub fn get_tracks_js(&self) -> js_sys::Array {
-        let song = self.song.as_ref().expect_throw("Song is not set");
-        let tracks = song.get_tracks();
-        tracks.to_js_array()
+    #[wasm_bindgen(js_name = fillPlaybackBuffer)]
+    pub fn fill_playback_buffer_js(
+        &self,
+        buffer: &mut [u32],
+        start_ticks: f64,
+        end_ticks: f64,
+    ) -> Result<usize, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let (records, _) = buffer.as_chunks_mut::<RECORD_WORDS>();
+        Ok(fill_playback_buffer(song, records, start_ticks, end_ticks))
     }
 
-    #[wasm_bindgen(js_name = addTrack)]
-    pub fn add_track_js(&mut self, track: js_sys::Object) -> js_sys::Object {
-        let song = self.song.as_mut().expect_throw("Song is not set");
-        let track = Track::from_js_object(track);
-        song.add_track(track).to_js_object()
+    #[wasm_bindgen(js_name = getRenderModel)]
+    pub fn get_render_model_js(
+        &self,
+        start_ticks: f64,
+        end_ticks: f64,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let tracks: js_sys::Array = render_tracks(song, start_ticks, end_ticks)
+            .iter()
+            .map(|track| track.to_js_object(song))
+            .collect();
+
+        let js_model = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &js_model,
+            &JsValue::from_str("ppq"),
+            &JsValue::from_f64(song.ppq as f64),
+        )
+        .unwrap();
+        js_sys::Reflect::set(
+            &js_model,
+            &JsValue::from_str("startTicks"),
+            &JsValue::from_f64(start_ticks.as_f64()),
+        )
+        .unwrap();
+        js_sys::Reflect::set(
+            &js_model,
+            &JsValue::from_str("endTicks"),
+            &JsValue::from_f64(end_ticks.as_f64()),
+        )
+        .unwrap();
+        js_sys::Reflect::set(&js_model, &JsValue::from_str("tracks"), &tracks).unwrap();
+        Ok(js_model)
     }
 
-    #[wasm_bindgen(js_name = removeTrack)]
-    pub fn remove_track_js(&mut self, track_id: &str) {
-        let track_id = Id::try_from(track_id).expect_throw("Track id is not valid");
-        let song = self.song.as_mut().expect_throw("Song is not set");
-        song.remove_track(&track_id);
+    #[wasm_bindgen(js_name = getSections)]
+    pub fn get_sections_js(&self) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(sections(song)
+            .iter()
+            .map(|section| section.to_js_object())
+            .collect())
     }
 
-    #[wasm_bindgen(js_name = getEvent)]
-    pub fn get_event_js(&self, event_id: &str) -> Option<js_sys::Object> {
-        let song = self.song.as_ref().expect_throw("Song is not set");
-        let event_id = Id::try_from(event_id).expect_throw("Event id is not valid");
-        let event = song.get_event(&event_id);
-        event.map(|event| event.to_js_object())
+    #[wasm_bindgen(js_name = addMarker)]
+    pub fn add_marker_js(
+        &mut self,
+        ticks: f64,
+        name: String,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let marker = edit_song(song, |song| {
+            add_marker(song, &mut self.id_strategy, parse_ticks(ticks)?, name)
+        })?;
+        self.commit()?;
+        Ok(marker.to_js_object())
     }
 
-    #[wasm_bindgen(js_name = getEvents)]
-    pub fn get_events_js(&self) -> js_sys::Array {
-        let song = self.song.as_ref().expect_throw("Song is not set");
-        let events = song.get_events(None); // TODO: None
-        events.iter().map(|event| event.to_js_object()).collect()
+    #[wasm_bindgen(js_name = removeMarker)]
+    pub fn remove_marker_js(&mut self, marker_id: &str) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        edit_song(song, |song| remove_marker(song, &parse_id(marker_id)?))?;
+        self.commit()?;
+        Ok(())
     }
 
-    #[wasm_bindgen(js_name = getEventsInTicksRange)]
-    pub fn get_events_in_ticks_range_js(
+    #[wasm_bindgen(js_name = getMarkers)]
+    pub fn get_markers_js(&self) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song.events_to_js_array(song.get_marker_events()))
+    }
+
+    #[wasm_bindgen(js_name = getMarkersInTicksRange)]
+    pub fn get_markers_in_ticks_range_js(
         &self,
-        start_ticks: u32,
-        end_ticks: u32,
-        within_duration: bool,
-    ) -> js_sys::Array {
-        let song = self.song.as_ref().expect_throw("Song is not set");
-        let events = song.get_events_in_ticks_range(
-            Ticks::new(start_ticks),
-            Ticks::new(end_ticks),
-            within_duration,
-            None, // TODO: None
-        );
-        events.iter().map(|event| event.to_js_object()).collect()
+        start_ticks: f64,
+        end_ticks: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let markers = song.get_marker_events_in_ticks_range(start_ticks, end_ticks);
+        Ok(song.events_to_js_array(markers))
+    }
+
+    #[wasm_bindgen(js_name = getTimeSignatureAt)]
+    pub fn get_time_signature_at_js(
+        &self,
+        ticks: f64,
+    ) -> Result<Option<js_sys::Object>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song
+            .get_time_signature_at(parse_ticks(ticks)?)
+            .and_then(|time_signature| song.get_event(&time_signature.id))
+            .map(|event| song.event_to_js_object(event)))
+    }
+
+    #[wasm_bindgen(js_name = getKeyAt)]
+    pub fn get_key_at_js(&self, ticks: f64) -> Result<Option<js_sys::Object>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(song
+            .get_key_at(parse_ticks(ticks)?)
+            .and_then(|key| song.get_event(&key.id))
+            .map(|event| song.event_to_js_object(event)))
+    }
+
+    #[wasm_bindgen(js_name = getClicksInRange)]
+    pub fn get_clicks_in_range_js(
+        &self,
+        start_ticks: f64,
+        end_ticks: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let meters = MeterMap::new(song);
+        Ok(clicks_in_range(&meters, start_ticks, end_ticks)
+            .iter()
+            .map(|click| click.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getCountIn)]
+    pub fn get_count_in_js(
+        &self,
+        bars: u32,
+        ticks: f64,
+        options: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: CountInOptions = if options.is_undefined() {
+            CountInOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Count-in options are not valid"))?
+        };
+        let meters = MeterMap::new(song);
+        Ok(count_in(&meters, bars, parse_ticks(ticks)?, &options).to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = detectKey)]
+    pub fn detect_key_js(
+        &self,
+        range: JsValue,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let range: Option<TicksRange> = if range.is_undefined() || range.is_null() {
+            None
+        } else {
+            Some(
+                serde_wasm_bindgen::from_value(range)
+                    .map_err(|_| StoreError::invalid("Range is not valid"))?,
+            )
+        };
+        let track_ids = track_ids.map(ids_from_js_array).transpose()?;
+        let durations = pitch_class_durations(song, range, track_ids.as_deref());
+        Ok(detect_key(&durations)
+            .into_iter()
+            .map(|candidate| candidate.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = estimateTempo)]
+    pub fn estimate_tempo_js(
+        &self,
+        source: JsValue,
+        options: JsValue,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let options: TempoEstimateOptions = if options.is_undefined() {
+            TempoEstimateOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Tempo estimate options are not valid"))?
+        };
+
+        let onsets = if js_sys::Array::is_array(&source) {
+            let event_ids = ids_from_js_array(source.into())?;
+            note_onsets(event_ids.iter().filter_map(|id| song.get_event(id)))
+        } else {
+            let track_id = source
+                .as_string()
+                .ok_or_else(|| StoreError::invalid("Source is not valid"))?;
+            let track_id = parse_id(&track_id)?;
+            let track = song
+                .get_track(&track_id)
+                .ok_or(StoreError::TrackNotFound(track_id))?;
+            note_onsets(song.track_events(track).iter())
+        };
+
+        Ok(match estimate_tempo(&onsets, song.ppq, &options) {
+            Some(estimate) => estimate.to_js_object().into(),
+            None => JsValue::UNDEFINED,
+        })
+    }
+
+    #[wasm_bindgen(js_name = getWindowDelta)]
+    pub fn get_window_delta_js(
+        &mut self,
+        window_id: String,
+        start_ticks: f64,
+        end_ticks: f64,
+        filter: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let filter: Option<GetEventsFilter> = serde_wasm_bindgen::from_value(filter)
+            .map_err(|_| StoreError::invalid("Filter is not valid"))?;
+        let events = song.get_events_in_ticks_range(start_ticks, end_ticks, true, filter);
+        Ok(self
+            .windows
+            .entry(window_id)
+            .or_default()
+            .update(events)
+            .to_js_object(song))
+    }
+
+    #[wasm_bindgen(js_name = clearWindow)]
+    pub fn clear_window_js(&mut self, window_id: &str) {
+        self.windows.remove(window_id);
+    }
+
+    #[wasm_bindgen(js_name = createCursor)]
+    pub fn create_cursor_js(&mut self, start_ticks: f64) -> Result<u32, StoreError> {
+        self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let id = self.next_cursor_id;
+        self.next_cursor_id += 1;
+        self.cursors
+            .insert(id, PlaybackCursor::new(parse_ticks(start_ticks)?));
+        Ok(id)
+    }
+
+    #[wasm_bindgen(js_name = advanceCursor)]
+    pub fn advance_cursor_js(
+        &mut self,
+        cursor_id: u32,
+        ticks: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let cursor = self
+            .cursors
+            .get_mut(&cursor_id)
+            .ok_or_else(|| StoreError::invalid(format!("Cursor {} does not exist", cursor_id)))?;
+        let events = cursor.advance_to(song, parse_ticks(ticks)?);
+        Ok(song.events_to_js_array(events))
+    }
+
+    #[wasm_bindgen(js_name = closeCursor)]
+    pub fn close_cursor_js(&mut self, cursor_id: u32) {
+        self.cursors.remove(&cursor_id);
     }
 
     #[wasm_bindgen(js_name = addEvent)]
-    pub fn add_event_js(&mut self, event: js_sys::Object) -> js_sys::Object {
-        let song = self.song.as_mut().expect_throw("Song is not set");
-        let event = Event::from_js_object(event);
-        let event = song.add_event(event);
-        event.to_js_object()
+    pub fn add_event_js(
+        &mut self,
+        event: js_sys::Object,
+        range_check: Option<String>,
+    ) -> Result<js_sys::Object, StoreError> {
+        let range_check = range_check
+            .map(|range_check| range_check.parse::<RangeCheck>())
+            .transpose()?;
+        let event = self
+            .validation
+            .check_js_object(event)
+            .map_err(StoreError::invalid)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let meta = meta_of(&event)?;
+        let event = Event::from_js_object(event)?;
+        let out_of_range = check_pitch_range(song, std::slice::from_ref(&event), range_check)?;
+
+        let event_id = event.get_id();
+        song.add_event(event)?;
+        if let Some(meta) = meta {
+            song.set_event_meta(event_id, meta);
+        }
+        let event = song.event_to_js_object(song.get_event(&event_id).unwrap());
+        self.commit()?;
+        if !out_of_range.is_empty() {
+            self.warn(StoreWarning::OutOfPitchRange(out_of_range));
+        }
+        Ok(event)
+    }
+
+    #[wasm_bindgen(js_name = addEvents)]
+    pub fn add_events_js(
+        &mut self,
+        events: js_sys::Array,
+        range_check: Option<String>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let range_check = range_check
+            .map(|range_check| range_check.parse::<RangeCheck>())
+            .transpose()?;
+        let events = events
+            .iter()
+            .map(|event| self.validation.check_js_object(event.into()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(StoreError::invalid)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let metas = events.iter().map(meta_of).collect::<Result<Vec<_>, _>>()?;
+        let events = events
+            .into_iter()
+            .map(Event::from_js_object)
+            .collect::<Result<Vec<_>, _>>()?;
+        let out_of_range = check_pitch_range(song, &events, range_check)?;
+
+        let event_ids = edit_song(song, |song| {
+            add_events(song, events.into_iter().zip(metas).collect())
+        })?;
+        let events =
+            song.events_to_js_array(event_ids.iter().map(|id| song.get_event(id).unwrap()));
+        self.commit()?;
+        if !out_of_range.is_empty() {
+            self.warn(StoreWarning::OutOfPitchRange(out_of_range));
+        }
+        Ok(events)
+    }
+
+    #[wasm_bindgen(js_name = updateEvents)]
+    pub fn update_events_js(
+        &mut self,
+        updaters: js_sys::Array,
+    ) -> Result<js_sys::Array, StoreError> {
+        let updaters = updaters
+            .iter()
+            .map(|updater| self.validation.check_js_object(updater.into()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(StoreError::invalid)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let metas = updaters
+            .iter()
+            .map(meta_of)
+            .collect::<Result<Vec<_>, _>>()?;
+        let updaters = updaters
+            .into_iter()
+            .map(EventUpdater::from_js_object)
+            .collect::<Result<Vec<_>, _>>()?;
+        let event_ids = edit_song(song, |song| {
+            update_events(song, updaters.into_iter().zip(metas).collect())
+        })?;
+        let events =
+            song.events_to_js_array(event_ids.iter().map(|id| song.get_event(id).unwrap()));
+        self.commit()?;
+        Ok(events)
+    }
+
+    #[wasm_bindgen(js_name = queryEventsByMeta)]
+    pub fn query_events_by_meta_js(
+        &self,
+        key: &str,
+        value: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let json = js_sys::JSON::stringify(&value)
+            .ok()
+            .and_then(|json| json.as_string())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .ok_or_else(|| StoreError::invalid("Meta value is not valid"))?;
+        Ok(song.events_to_js_array(song.get_events_by_meta(key, &json)))
+    }
+
+    #[wasm_bindgen(js_name = updateEventsRelative)]
+    pub fn update_events_relative_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        updater: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let updater: RelativeUpdater = serde_wasm_bindgen::from_value(updater)
+            .map_err(|_| StoreError::invalid("Relative updater is not valid"))?;
+        let events = edit_song(song, |song| {
+            update_events_relative(song, &event_ids, &updater)
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = removeEvents)]
+    pub fn remove_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        whole_groups: Option<bool>,
+    ) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        edit_song(song, |song| {
+            remove_events(song, &event_ids, whole_groups.unwrap_or(false))
+        })?;
+        self.commit()?;
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = updateEvent)]
-    pub fn update_event_js(&mut self, event: js_sys::Object) -> js_sys::Object {
-        let song = self.song.as_mut().expect_throw("Song is not set");
-        let event = EventUpdater::from_js_object(event);
-        let event = song.update_event(event);
-        event.to_js_object()
+    pub fn update_event_js(&mut self, event: js_sys::Object) -> Result<js_sys::Object, StoreError> {
+        let event = self
+            .validation
+            .check_js_object(event)
+            .map_err(StoreError::invalid)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let meta = meta_of(&event)?;
+        let event = EventUpdater::from_js_object(event)?;
+        let event_id = event.get_id();
+        song.update_event(event)?;
+        if let Some(meta) = meta {
+            song.set_event_meta(event_id, meta);
+        }
+        let event = song.event_to_js_object(song.get_event(&event_id).unwrap());
+        self.commit()?;
+        Ok(event)
     }
 
     #[wasm_bindgen(js_name = removeEvent)]
-    pub fn remove_event_js(&mut self, event_id: &str) {
-        let song = self.song.as_mut().expect_throw("Song is not set");
-        let event_id = Id::try_from(event_id).expect_throw("Event id is not valid");
+    pub fn remove_event_js(&mut self, event_id: &str) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_id = parse_id(event_id)?;
+        song.get_event(&event_id)
+            .ok_or(StoreError::EventNotFound(event_id))?;
+        song.remove_event(&event_id);
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getEventByHandle)]
+    pub fn get_event_by_handle_js(
+        &self,
+        handle: u32,
+    ) -> Result<Option<js_sys::Object>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let Some(event_id) = song.resolve_handle(handle) else {
+            return Ok(None);
+        };
+        Ok(song
+            .get_event(&event_id)
+            .map(|event| song.event_to_js_object(event)))
+    }
+
+    #[wasm_bindgen(js_name = updateEventByHandle)]
+    pub fn update_event_by_handle_js(
+        &mut self,
+        handle: u32,
+        updater: js_sys::Object,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_id = song
+            .resolve_handle(handle)
+            .ok_or_else(|| StoreError::invalid("Event handle is not valid"))?;
+        let kind = song
+            .get_event(&event_id)
+            .ok_or(StoreError::EventNotFound(event_id))?
+            .get_kind();
+        let updater = EventUpdater::from_js_fields(kind, event_id, updater)?;
+        let event = song.update_event(updater)?.clone();
+        let event = song.event_to_js_object(&event);
+        self.commit()?;
+        Ok(event)
+    }
+
+    #[wasm_bindgen(js_name = removeEventByHandle)]
+    pub fn remove_event_by_handle_js(&mut self, handle: u32) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_id = song
+            .resolve_handle(handle)
+            .ok_or_else(|| StoreError::invalid("Event handle is not valid"))?;
         song.remove_event(&event_id);
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = previewTransform)]
+    pub fn preview_transform_js(
+        &self,
+        event_ids: js_sys::Array,
+        delta: JsValue,
+    ) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let delta: TransformDelta = serde_wasm_bindgen::from_value(delta)
+            .map_err(|_| StoreError::invalid("Transform delta is not valid"))?;
+        Ok(preview_transform(song, &event_ids, delta)?.to_js_object())
+    }
+
+    #[wasm_bindgen(js_name = moveEvents)]
+    pub fn move_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        delta: JsValue,
+        constraints: JsValue,
+        whole_groups: Option<bool>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = with_groups(song, ids_from_js_array(event_ids)?, whole_groups);
+        let delta: TransformDelta = serde_wasm_bindgen::from_value(delta)
+            .map_err(|_| StoreError::invalid("Transform delta is not valid"))?;
+        let constraints: MoveConstraints = if constraints.is_undefined() {
+            MoveConstraints::default()
+        } else {
+            serde_wasm_bindgen::from_value(constraints)
+                .map_err(|_| StoreError::invalid("Move constraints are not valid"))?
+        };
+        let events = edit_song(song, |song| {
+            move_events(song, &event_ids, delta, &constraints)
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = checkCollision)]
+    pub fn check_collision_js(
+        &self,
+        track_id: &str,
+        ticks: f64,
+        duration: f64,
+        note_number: u8,
+        ignore_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let ignore_ids: HashSet<Id> = ignore_ids
+            .map(ids_from_js_array)
+            .transpose()?
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        Ok(find_collisions(
+            song,
+            track,
+            parse_ticks(ticks)?,
+            parse_ticks(duration)?,
+            NoteNumber::new(note_number),
+            &ignore_ids,
+        )
+        .iter()
+        .map(|id| JsValue::from_str(id.to_string().as_str()))
+        .collect())
+    }
+
+    #[wasm_bindgen(js_name = findSimilarPhrases)]
+    pub fn find_similar_phrases_js(
+        &self,
+        event_ids: js_sys::Array,
+        tolerance_ticks: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        Ok(
+            find_similar_phrases(song, &event_ids, parse_ticks(tolerance_ticks)?)
+                .iter()
+                .map(|phrase_match| phrase_match.to_js_object())
+                .collect(),
+        )
+    }
+
+    #[wasm_bindgen(js_name = getPitchClassInfo)]
+    pub fn get_pitch_class_info_js(&self, ticks: f64) -> Result<js_sys::Object, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(pitch_class_info(song, parse_ticks(ticks)?).to_js_object(song))
+    }
+
+    #[wasm_bindgen(js_name = extractTopLine)]
+    pub fn extract_top_line_js(
+        &self,
+        track_ids: js_sys::Array,
+        range: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        self.extract_line(track_ids, range, LineVoice::Top)
+    }
+
+    #[wasm_bindgen(js_name = extractBassLine)]
+    pub fn extract_bass_line_js(
+        &self,
+        track_ids: js_sys::Array,
+        range: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        self.extract_line(track_ids, range, LineVoice::Bass)
+    }
+
+    fn extract_line(
+        &self,
+        track_ids: js_sys::Array,
+        range: JsValue,
+        voice: LineVoice,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_ids = ids_from_js_array(track_ids)?;
+        let range: TicksRange = serde_wasm_bindgen::from_value(range)
+            .map_err(|_| StoreError::invalid("Range is not valid"))?;
+        Ok(extract_line(song, &track_ids, range, voice)
+            .into_iter()
+            .map(|note| note.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getEffectiveNoteEnds)]
+    pub fn get_effective_note_ends_js(
+        &self,
+        track_id: &str,
+        range: JsValue,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let range: TicksRange = serde_wasm_bindgen::from_value(range)
+            .map_err(|_| StoreError::invalid("Range is not valid"))?;
+        Ok(to_js_value(&effective_note_ends_in_range(
+            song, track, range,
+        )))
+    }
+
+    #[wasm_bindgen(js_name = applyOps)]
+    pub fn apply_ops_js(
+        &mut self,
+        ops: js_sys::Array,
+        origin: Option<String>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (indexes, operations) = self.received_ops.filter_new(parse_ops(ops)?);
+        let mut conflicts = apply_operations(song, operations);
+        restore_conflict_indexes(&mut conflicts, &indexes);
+        self.commit_remote(origin.or_else(|| self.origin.clone()));
+        Ok(conflicts
+            .iter()
+            .map(|conflict| conflict.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getOperationsSince)]
+    pub fn get_operations_since_js(
+        &self,
+        since_seq: Option<f64>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let entries = song
+            .get_journal()
+            .entries_since(since_seq.unwrap_or(0.0) as u64);
+        Ok(OperationRecord::from_entries(entries)
+            .iter()
+            .map(|record| record.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = applyRemoteOperations)]
+    pub fn apply_remote_operations_js(
+        &mut self,
+        records: js_sys::Array,
+        origin: Option<String>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let records = records
+            .iter()
+            .map(|record| OperationRecord::from_js_object(record.into()))
+            .collect::<Result<_, _>>()?;
+        let conflicts = apply_last_writer_wins(song, records);
+        self.commit_remote(origin.or_else(|| self.origin.clone()));
+        Ok(conflicts
+            .iter()
+            .map(|conflict| conflict.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = applyOptimistic)]
+    pub fn apply_optimistic_js(&mut self, ops: js_sys::Array) -> Result<u32, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let operations = ops
+            .iter()
+            .map(|op| Operation::from_js_object(op.into()))
+            .collect::<Result<_, _>>()?;
+        apply_operations(song, operations);
+        let changes = song.get_changes().to_vec();
+        let seq = self.commit()?;
+        Ok(self.optimistic.insert(seq, changes))
+    }
+
+    #[wasm_bindgen(js_name = confirmOptimistic)]
+    pub fn confirm_optimistic_js(&mut self, token: u32) {
+        self.optimistic.confirm(token);
+    }
+
+    #[wasm_bindgen(js_name = rollbackOptimistic)]
+    pub fn rollback_optimistic_js(&mut self, token: u32) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (seq, conflicts) = self
+            .optimistic
+            .rollback(song, token)
+            .ok_or_else(|| StoreError::invalid("Optimistic token is not valid"))?;
+        if let Some(seq) = seq {
+            self.outbox.discard(seq);
+        }
+        self.commit_remote(self.origin.clone());
+        Ok(conflicts
+            .iter()
+            .map(|conflict| conflict.to_js_object())
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getGridTicksInRange)]
+    pub fn get_grid_ticks_in_range_js(
+        &self,
+        start_ticks: f64,
+        end_ticks: f64,
+        grid: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let grid: Grid = serde_wasm_bindgen::from_value(grid)
+            .map_err(|_| StoreError::invalid("Grid is not valid"))?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        Ok(grid
+            .lines_in_range(&MeterMap::new(song), start_ticks, end_ticks)
+            .ok_or_else(too_fine_error)?
+            .iter()
+            .map(|ticks| JsValue::from_f64(ticks.as_f64()))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = snapToGrid)]
+    pub fn snap_to_grid_js(&self, ticks: f64, grid: JsValue) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let grid: Grid = serde_wasm_bindgen::from_value(grid)
+            .map_err(|_| StoreError::invalid("Grid is not valid"))?;
+        Ok(grid
+            .snap(&MeterMap::new(song), parse_ticks(ticks)?)
+            .ok_or_else(too_fine_error)?
+            .as_f64())
+    }
+
+    #[wasm_bindgen(js_name = noteLengthTicks)]
+    pub fn note_length_ticks_js(&self, name: &str) -> Result<f64, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let length = NoteLength::parse(name)
+            .ok_or_else(|| StoreError::invalid("Note length is not valid"))?;
+        Ok(length.ticks(song.ppq).as_f64())
+    }
+
+    #[wasm_bindgen(js_name = getLengthPresets)]
+    pub fn get_length_presets_js(&self) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        Ok(length_presets(song.ppq)
+            .iter()
+            .map(|length| length.to_js_object(song.ppq))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = setDurations)]
+    pub fn set_durations_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        duration: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let change = match duration.as_f64() {
+            Some(ticks) => DurationChange::Fixed(Ticks::new(ticks as u64)),
+            None => {
+                let scale = js_sys::Reflect::get(&duration, &JsValue::from_str("scale"))
+                    .ok()
+                    .and_then(|scale| scale.as_f64())
+                    .ok_or_else(|| {
+                        StoreError::invalid("Duration must be a number or { scale: number }")
+                    })?;
+                DurationChange::Scale(scale)
+            }
+        };
+        let events = edit_song(song, |song| set_durations(song, &event_ids, change))?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = setOverlapRatio)]
+    pub fn set_overlap_ratio_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        ratio: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return Err(StoreError::invalid("Overlap ratio must be greater than 0"));
+        }
+        let events = edit_song(song, |song| set_overlap_ratio(song, &event_ids, ratio))?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = quantizeEvents)]
+    pub fn quantize_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        options: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let options: QuantizeOptions = serde_wasm_bindgen::from_value(options)
+            .map_err(|_| StoreError::invalid("Quantize options are not valid"))?;
+        let events = edit_song(song, |song| quantize_events(song, &event_ids, &options))?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = transformEvents)]
+    pub fn transform_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        transform: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let transform: EventTransform = serde_wasm_bindgen::from_value(transform)
+            .map_err(|_| StoreError::invalid("Event transform is not valid"))?;
+        let events = edit_song(song, |song| transform_events(song, &event_ids, &transform))?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = transposeSong)]
+    pub fn transpose_song_js(
+        &mut self,
+        semitones: i32,
+        options: JsValue,
+    ) -> Result<u32, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let options: TransposeOptions = if options.is_undefined() {
+            TransposeOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Transpose options are not valid"))?
+        };
+        let count = transpose_song(song, semitones, &options);
+        self.commit()?;
+        Ok(count as u32)
+    }
+
+    #[wasm_bindgen(js_name = dedupeNotes)]
+    pub fn dedupe_notes_js(
+        &mut self,
+        target: JsValue,
+        tolerance_ticks: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let scope = match target.as_string() {
+            Some(track_id) => DedupeScope::Track(parse_id(&track_id)?),
+            None => DedupeScope::Events(ids_from_js_array(js_sys::Array::from(&target))?),
+        };
+        let removed_ids = edit_song(song, |song| {
+            dedupe_notes(song, scope, parse_ticks(tolerance_ticks)?)
+        })?;
+        self.commit()?;
+        Ok(removed_ids
+            .iter()
+            .map(|id| JsValue::from_str(id.to_string().as_str()))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = getControllerValueAt)]
+    pub fn get_controller_value_at_js(
+        &self,
+        track_id: &str,
+        controller: u8,
+        ticks: f64,
+    ) -> Result<Option<f64>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let lane = ControllerLane::new(song, track, ControllerNumber::new(controller));
+        Ok(lane.value_at(parse_ticks(ticks)?))
+    }
+
+    #[wasm_bindgen(js_name = getControllerCurve)]
+    pub fn get_controller_curve_js(
+        &self,
+        track_id: &str,
+        controller: u8,
+        start_ticks: f64,
+        end_ticks: f64,
+        resolution: f64,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let track = song
+            .get_track(&track_id)
+            .ok_or(StoreError::TrackNotFound(track_id))?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        let resolution = parse_ticks(resolution)?;
+        if resolution == Ticks::new(0) {
+            return Err(StoreError::invalid("Resolution must be at least one tick"));
+        }
+        let lane = ControllerLane::new(song, track, ControllerNumber::new(controller));
+        Ok(to_js_value(&lane.sample(
+            start_ticks,
+            end_ticks,
+            resolution,
+        )))
+    }
+
+    #[wasm_bindgen(js_name = thinControllerEvents)]
+    pub fn thin_controller_events_js(
+        &mut self,
+        track_id: &str,
+        controller: u8,
+        tolerance: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let track_id = parse_id(track_id)?;
+        let removed_ids = edit_song(song, |song| {
+            thin_controller_events(song, track_id, ControllerNumber::new(controller), tolerance)
+        })?;
+        self.commit()?;
+        Ok(removed_ids
+            .iter()
+            .map(|id| JsValue::from_str(id.to_string().as_str()))
+            .collect())
+    }
+
+    #[wasm_bindgen(js_name = repeatEventsToFill)]
+    pub fn repeat_events_to_fill_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        until_ticks: f64,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let events = edit_song(song, |song| {
+            repeat_events_to_fill(
+                song,
+                &mut self.id_strategy,
+                &event_ids,
+                parse_ticks(until_ticks)?,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = duplicateRegion)]
+    pub fn duplicate_region_js(
+        &mut self,
+        start_bar: u32,
+        end_bar: u32,
+        count: u32,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let filter = events_filter(track_ids)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        if end_bar <= start_bar {
+            return Err(StoreError::invalid("End bar must be after the start bar"));
+        }
+        let events = edit_song(song, |song| {
+            duplicate_region(
+                song,
+                &mut self.id_strategy,
+                start_bar as u64,
+                end_bar as u64,
+                count as u64,
+                filter,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = repeatRange)]
+    pub fn repeat_range_js(
+        &mut self,
+        start_ticks: f64,
+        end_ticks: f64,
+        count: u32,
+        track_ids: Option<js_sys::Array>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let filter = events_filter(track_ids)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let (start_ticks, end_ticks) = parse_ticks_range(start_ticks, end_ticks)?;
+        if end_ticks <= start_ticks {
+            return Err(StoreError::invalid(
+                "End ticks must be after the start ticks",
+            ));
+        }
+        let events = edit_song(song, |song| {
+            repeat_range(
+                song,
+                &mut self.id_strategy,
+                start_ticks,
+                end_ticks,
+                count as u64,
+                filter,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = copyEvents)]
+    pub fn copy_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        whole_groups: Option<bool>,
+    ) -> Result<(), StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let event_ids = with_groups(song, ids_from_js_array(event_ids)?, whole_groups);
+        self.clipboard = Clipboard::copy(song, &event_ids)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = cutEvents)]
+    pub fn cut_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        whole_groups: Option<bool>,
+    ) -> Result<(), StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = with_groups(song, ids_from_js_array(event_ids)?, whole_groups);
+        self.clipboard = edit_song(song, |song| Clipboard::cut(song, &event_ids))?;
+        self.commit()?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = pasteEvents)]
+    pub fn paste_events_js(
+        &mut self,
+        at_ticks: f64,
+        target_track_id: Option<String>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let target_track_id = target_track_id.as_deref().map(parse_id).transpose()?;
+        let events = edit_song(song, |song| {
+            self.clipboard.paste(
+                song,
+                &mut self.id_strategy,
+                parse_ticks(at_ticks)?,
+                target_track_id,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = copyEventsAsMidi)]
+    pub fn copy_events_as_midi_js(
+        &mut self,
+        event_ids: js_sys::Array,
+    ) -> Result<Vec<u8>, StoreError> {
+        let song = self.song.as_ref().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        self.clipboard = Clipboard::copy(song, &event_ids)?;
+        Ok(clipboard_to_midi(song, &self.clipboard))
+    }
+
+    #[wasm_bindgen(js_name = pasteMidi)]
+    pub fn paste_midi_js(
+        &mut self,
+        bytes: &[u8],
+        at_ticks: f64,
+        target_track_id: &str,
+    ) -> Result<js_sys::Array, StoreError> {
+        let target_track_id = parse_id(target_track_id)?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let clipboard = clipboard_from_midi(bytes, song.ppq)?;
+        let at_ticks = parse_ticks(at_ticks)?;
+        let events = edit_song(song, |song| {
+            clipboard.paste(song, &mut self.id_strategy, at_ticks, Some(target_track_id))
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = strumChords)]
+    pub fn strum_chords_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        spread_ticks: f64,
+        direction: &str,
+        velocity_ramp: i32,
+    ) -> Result<js_sys::Array, StoreError> {
+        let direction: StrumDirection = direction.parse()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let events = edit_song(song, |song| {
+            strum_chords(
+                song,
+                &event_ids,
+                parse_ticks(spread_ticks)?,
+                direction,
+                velocity_ramp,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = ratchetNotes)]
+    pub fn ratchet_notes_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        divisions: u32,
+        velocity_decay: i32,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let events = edit_song(song, |song| {
+            ratchet_notes(
+                song,
+                &mut self.id_strategy,
+                &event_ids,
+                divisions,
+                velocity_decay,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = humanizeEvents)]
+    pub fn humanize_events_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        options: JsValue,
+    ) -> Result<js_sys::Array, StoreError> {
+        let options: HumanizeOptions = if options.is_undefined() {
+            HumanizeOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Humanize options are not valid"))?
+        };
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let events = edit_song(song, |song| humanize_events(song, &event_ids, &options))?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = alignToGrid)]
+    pub fn align_to_grid_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        strength: f64,
+        options: JsValue,
+    ) -> Result<JsValue, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let options: GridAlignOptions = if options.is_undefined() {
+            GridAlignOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|_| StoreError::invalid("Grid alignment options are not valid"))?
+        };
+        let alignment = align_to_grid(song, &event_ids, strength, &options);
+        self.commit()?;
+        Ok(match alignment {
+            Some(alignment) => alignment.to_js_object().into(),
+            None => JsValue::UNDEFINED,
+        })
+    }
+
+    #[wasm_bindgen(js_name = setNoteParameter)]
+    pub fn set_note_parameter_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        name: &str,
+        value: Option<f64>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let events = edit_song(song, |song| {
+            set_note_parameter(song, &event_ids, name, value)
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = rampValues)]
+    pub fn ramp_values_js(
+        &mut self,
+        target: JsValue,
+        start_value: u8,
+        end_value: u8,
+        curve: &str,
+    ) -> Result<js_sys::Array, StoreError> {
+        let curve: RampCurve = curve.parse()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let target = if js_sys::Array::is_array(&target) {
+            RampTarget::Events(ids_from_js_array(js_sys::Array::from(&target))?)
+        } else {
+            let get = |key: &str| js_sys::Reflect::get(&target, &JsValue::from_str(key)).unwrap();
+            let track_id = get("trackId")
+                .as_string()
+                .ok_or_else(|| StoreError::invalid("Track id is not set"))?;
+            RampTarget::Lane {
+                track_id: parse_id(&track_id)?,
+                controller: get("controller")
+                    .as_f64()
+                    .map(|controller| ControllerNumber::new(controller as u8)),
+                start_ticks: Ticks::new(get("startTicks").as_f64().unwrap_or(0.0) as u64),
+                end_ticks: Ticks::new(
+                    get("endTicks").as_f64().unwrap_or(Ticks::MAX.as_f64()) as u64
+                ),
+            }
+        };
+        let events = edit_song(song, |song| {
+            ramp_values(song, target, start_value, end_value, curve)
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = insertCurve)]
+    pub fn insert_curve_js(
+        &mut self,
+        lane: JsValue,
+        start: JsValue,
+        end: JsValue,
+        shape: &str,
+        resolution_ticks: f64,
+        period_ticks: Option<f64>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let shape: CurveShape = shape.parse()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let lane: CurveLane = serde_wasm_bindgen::from_value(lane)
+            .map_err(|_| StoreError::invalid("Curve lane is not valid"))?;
+        let start: CurvePoint = serde_wasm_bindgen::from_value(start)
+            .map_err(|_| StoreError::invalid("Curve start is not valid"))?;
+        let end: CurvePoint = serde_wasm_bindgen::from_value(end)
+            .map_err(|_| StoreError::invalid("Curve end is not valid"))?;
+        let events = edit_song(song, |song| {
+            insert_curve(
+                song,
+                &mut self.id_strategy,
+                lane,
+                (start, end),
+                shape,
+                parse_ticks(resolution_ticks)?,
+                period_ticks.map(parse_ticks).transpose()?,
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+
+    #[wasm_bindgen(js_name = applyValueLine)]
+    pub fn apply_value_line_js(
+        &mut self,
+        event_ids: js_sys::Array,
+        points: JsValue,
+        field: Option<String>,
+    ) -> Result<js_sys::Array, StoreError> {
+        let field = field
+            .map(|field| field.parse::<ValueLineField>())
+            .transpose()?;
+        let song = self.song.as_mut().ok_or(StoreError::SongNotSet)?;
+        let event_ids = ids_from_js_array(event_ids)?;
+        let points: Vec<ValuePoint> = serde_wasm_bindgen::from_value(points)
+            .map_err(|_| StoreError::invalid("Value line points are not valid"))?;
+        let events = edit_song(song, |song| {
+            apply_value_line(
+                song,
+                &event_ids,
+                &points,
+                field.unwrap_or(ValueLineField::Value),
+            )
+        })?;
+        self.commit()?;
+        Ok(Event::to_js_array(&events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_check_pitch_range() {
+        let (mut song, track_id) = song_with_track();
+        let low = add_note(&mut song, track_id, 0, 480, 40, 100);
+        let high = add_note(&mut song, track_id, 480, 480, 60, 100);
+        song.update_track(&track_id, |track| {
+            track.pitch_range = Some(PitchRange {
+                low: NoteNumber::new(55),
+                high: NoteNumber::new(100),
+            })
+        });
+        let events: Vec<Event> = [low, high]
+            .iter()
+            .map(|id| song.get_event(id).unwrap().clone())
+            .collect();
+
+        assert_eq!(check_pitch_range(&song, &events, None), Ok(Vec::new()));
+        assert_eq!(
+            check_pitch_range(&song, &events, Some(RangeCheck::Warn)),
+            Ok(vec![low])
+        );
+        assert!(check_pitch_range(&song, &events, Some(RangeCheck::Reject)).is_err());
+        assert_eq!(
+            check_pitch_range(&song, &events[1..], Some(RangeCheck::Reject)),
+            Ok(Vec::new())
+        );
+    }
+
+    /// Opens a song with a track and a note, added in one undoable commit.
+    fn open_song(store: &mut Store) -> (String, Id) {
+        let song_id = store.create_song_js("test".to_string(), 480).unwrap();
+        let track_id = Id::new();
+        let song = store.song.as_mut().unwrap();
+        song.add_track(Track::new(track_id));
+        add_note(song, track_id, 0, 480, 60, 100);
+        store.commit().unwrap();
+        (song_id, track_id)
+    }
+
+    #[test]
+    fn test_create_song_rejects_zero_ppq() {
+        let mut store = Store::new();
+        assert_eq!(
+            store.create_song_js("test".to_string(), 0),
+            Err(StoreError::invalid("Song ppq must be positive"))
+        );
+        assert!(store.song.is_none());
+        assert!(store.song_order.is_empty());
+    }
+
+    #[test]
+    fn test_open_songs_keep_their_state() {
+        let mut store = Store::new();
+        let (first, first_track) = open_song(&mut store);
+        let filter = RecordFilter {
+            channels: Some(vec![1]),
+            kinds: None,
+        };
+        store
+            .song
+            .as_mut()
+            .unwrap()
+            .update_track(&first_track, |track| {
+                track.settings.record_filter = Some(filter.clone())
+            });
+        store.commit().unwrap();
+        store
+            .freeze_track_state_js(&first_track.to_string(), None)
+            .unwrap();
+        store.begin_transaction_js().unwrap();
+
+        let (second, second_track) = open_song(&mut store);
+        assert!(store.transaction.is_none());
+        assert!(store.frozen_tracks.is_empty());
+        let track = store.song.as_ref().unwrap().get_track(&second_track);
+        assert_eq!(track.unwrap().settings.record_filter, None);
+        assert!(store.undo_js().unwrap());
+        assert!(!store.can_undo_js());
+
+        store.set_active_song_js(&first).unwrap();
+        assert!(store.transaction.is_some());
+        assert!(store.frozen_tracks.contains_key(&first_track));
+        let track = store.song.as_ref().unwrap().get_track(&first_track);
+        assert_eq!(track.unwrap().settings.record_filter, Some(filter));
+        assert!(store.can_undo_js());
+        store.commit_transaction_js().unwrap();
+
+        store.set_active_song_js(&second).unwrap();
+        assert!(store.transaction.is_none());
+        assert!(!store.can_undo_js());
+        assert!(store.can_redo_js());
+    }
+
+    #[test]
+    fn test_with_song_restores_active_song_on_error() {
+        let mut store = Store::new();
+        let (first, first_track) = open_song(&mut store);
+        let (second, _) = open_song(&mut store);
+        store.begin_transaction_js().unwrap();
+
+        let result: Result<(), StoreError> = store.with_song(&first, |store| {
+            assert!(store.transaction.is_none());
+            let song = store.song.as_mut().unwrap();
+            song.update_track(&first_track, |track| track.settings.transpose = 12)
+                .ok_or(StoreError::TrackNotFound(first_track))?;
+            store.commit()?;
+            Err(StoreError::invalid("Callback failed"))
+        });
+        assert_eq!(result, Err(StoreError::invalid("Callback failed")));
+        assert_eq!(store.get_active_song_id_js(), Some(second));
+        assert!(store.transaction.is_some());
+        assert!(store
+            .song
+            .as_ref()
+            .unwrap()
+            .get_track(&first_track)
+            .is_none());
+
+        store.set_active_song_js(&first).unwrap();
+        let track = store.song.as_ref().unwrap().get_track(&first_track);
+        assert_eq!(track.unwrap().settings.transpose, 12);
+        assert!(store.undo_js().unwrap());
+        assert!(store.can_undo_js());
+    }
+
+    #[test]
+    fn test_undo_waits_for_open_transaction() {
+        let mut store = Store::new();
+        let (_, track_id) = open_song(&mut store);
+        store.begin_transaction_js().unwrap();
+        add_note(store.song.as_mut().unwrap(), track_id, 480, 480, 62, 100);
+        store.commit().unwrap();
+
+        let error = StoreError::invalid("Cannot undo while a transaction is open");
+        assert_eq!(store.undo_js(), Err(error.clone()));
+        assert_eq!(store.undo_in_track_js(&track_id.to_string()), Err(error));
+        assert!(store.redo_js().is_err());
+        let song = store.song.as_ref().unwrap();
+        assert_eq!(song.get_track_events(&track_id).len(), 2);
+        assert_eq!(song.get_journal().entries_since(0).len(), 1);
+
+        store.commit_transaction_js().unwrap();
+        assert_eq!(store.undo_js(), Ok(true));
+        let song = store.song.as_ref().unwrap();
+        assert_eq!(song.get_track_events(&track_id).len(), 1);
+    }
+
+    #[test]
+    fn test_failed_edit_leaves_no_changes() {
+        let mut store = Store::new();
+        let (_, track_id) = open_song(&mut store);
+        let song = store.song.as_mut().unwrap();
+        let note_id = song.get_track_events(&track_id)[0].get_id();
+
+        let result: Result<(), StoreError> = edit_song(song, |song| {
+            add_note(song, track_id, 480, 480, 62, 100);
+            song.remove_event(&note_id);
+            Err(StoreError::invalid("Edit failed"))
+        });
+        assert_eq!(result, Err(StoreError::invalid("Edit failed")));
+        assert!(song.get_changes().is_empty());
+        let ticks: Vec<Ticks> = song
+            .get_track_events(&track_id)
+            .iter()
+            .map(|event| event.get_ticks())
+            .collect();
+        assert_eq!(ticks, vec![Ticks::new(0)]);
+
+        assert_eq!(store.commit(), Ok(None));
+        assert!(store.undo_js().unwrap());
+        assert!(!store.can_undo_js());
     }
 }
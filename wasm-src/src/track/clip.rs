@@ -0,0 +1,310 @@
+use crate::{
+    event::event::Event,
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        unit::time::Ticks,
+    },
+    song::song::Song,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CLIP_INTERFACES: &'static str = r#"
+/**
+ * A region of a track for arrangement editing. Moving the clip moves the events it holds; see
+ * `Store.addClip`.
+ */
+export interface Clip {
+  id: string;
+  trackId: string;
+  startTicks: number;
+  length: number;
+  /** The events the clip holds that are in the song. */
+  eventIds: string[];
+}
+"#;
+
+/// A clip of its track, which keeps it with its other properties so clip edits are recorded
+/// as track changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Clip {
+    pub id: Id,
+    pub start_ticks: Ticks,
+    pub length: Ticks,
+    pub event_ids: Vec<Id>,
+}
+
+/// A clip as JS, see `Clip` in TypeScript.
+#[cfg(feature = "wasm")]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsClip<'a> {
+    id: Id,
+    track_id: Id,
+    start_ticks: Ticks,
+    length: Ticks,
+    event_ids: &'a [Id],
+}
+
+impl Clip {
+    fn end_ticks(&self) -> Ticks {
+        self.start_ticks + self.length
+    }
+
+    /// The clip as JS, leaving out events that are not in `song`.
+    #[cfg(feature = "wasm")]
+    pub fn to_js_value(&self, song: &Song, track_id: Id) -> JsValue {
+        let event_ids = self.events_in(song);
+        let clip = JsClip {
+            id: self.id,
+            track_id,
+            start_ticks: self.start_ticks,
+            length: self.length,
+            event_ids: &event_ids,
+        };
+        serde_wasm_bindgen::to_value(&clip).unwrap()
+    }
+
+    /// Ids of the clip's events that are in `song`, e.g. not removed since.
+    fn events_in(&self, song: &Song) -> Vec<Id> {
+        self.event_ids
+            .iter()
+            .copied()
+            .filter(|event_id| song.get_event(event_id).is_some())
+            .collect()
+    }
+}
+
+/// The clip `clip_id` and the id of the track holding it.
+pub fn find_clip(song: &Song, clip_id: &Id) -> Result<(Id, Clip), StoreError> {
+    song.get_tracks()
+        .iter()
+        .find_map(|track| {
+            let clip = track.clips.iter().find(|clip| clip.id == *clip_id)?;
+            Some((track.id, clip.clone()))
+        })
+        .ok_or_else(|| StoreError::invalid("Clip not found"))
+}
+
+/// Replaces the clip with the same id on `track_id`, or adds it. Its events leave the track's
+/// other clips, as an event is in one clip at most.
+fn put_clip(song: &mut Song, track_id: &Id, clip: Clip) {
+    song.update_track(track_id, |track| {
+        for other in track.clips.iter_mut().filter(|other| other.id != clip.id) {
+            other
+                .event_ids
+                .retain(|event_id| !clip.event_ids.contains(event_id));
+        }
+        match track.clips.iter_mut().find(|other| other.id == clip.id) {
+            Some(existing) => *existing = clip,
+            None => track.clips.push(clip),
+        }
+    });
+}
+
+fn check_length(length: Ticks) -> Result<(), StoreError> {
+    if length == Ticks::new(0) {
+        return Err(StoreError::invalid("Clip length must be positive"));
+    }
+    Ok(())
+}
+
+/// Adds a clip of `length` at `start_ticks` to the track. It holds `event_ids`, which must be on
+/// the track, or else the track's events starting within it.
+pub fn add_clip(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+    start_ticks: Ticks,
+    length: Ticks,
+    event_ids: Option<Vec<Id>>,
+) -> Result<Clip, StoreError> {
+    check_length(length)?;
+    let track = song
+        .get_track(&track_id)
+        .ok_or(StoreError::TrackNotFound(track_id))?;
+    let event_ids = match event_ids {
+        Some(event_ids) => {
+            for event in song.try_get_events(&event_ids)? {
+                if event.get_track_id() != Some(track_id) {
+                    return Err(StoreError::invalid(format!(
+                        "Event {} is not on the clip's track",
+                        event.get_id()
+                    )));
+                }
+            }
+            event_ids
+        }
+        None => {
+            let mut events = song.get_track_events(&track.id);
+            events.retain(|event| {
+                event.get_ticks() >= start_ticks && event.get_ticks() < start_ticks + length
+            });
+            events.sort_by_key(|event| (event.get_ticks(), event.get_id()));
+            events.iter().map(|event| event.get_id()).collect()
+        }
+    };
+
+    let clip = Clip {
+        id: ids.next_id()?,
+        start_ticks,
+        length,
+        event_ids,
+    };
+    put_clip(song, &track_id, clip.clone());
+    Ok(clip)
+}
+
+/// Moves the clip to `start_ticks`, moving its events by as much. Events that would land before
+/// the song start stop at tick 0.
+pub fn move_clip(
+    song: &mut Song,
+    clip_id: &Id,
+    start_ticks: Ticks,
+) -> Result<(Id, Clip), StoreError> {
+    let (track_id, mut clip) = find_clip(song, clip_id)?;
+    let delta = start_ticks.as_u64() as i64 - clip.start_ticks.as_u64() as i64;
+    for event_id in clip.events_in(song) {
+        let event = song.get_event(&event_id).unwrap().clone();
+        let ticks = (event.get_ticks().as_u64() as i64 + delta).max(0);
+        song.replace_event(event.with_ticks(Ticks::new(ticks as u64)))?;
+    }
+
+    clip.start_ticks = start_ticks;
+    put_clip(song, &track_id, clip.clone());
+    Ok((track_id, clip))
+}
+
+/// Sets the clip's bounds without moving its events. Events that no longer start within it
+/// leave the clip and stay on the track.
+pub fn resize_clip(
+    song: &mut Song,
+    clip_id: &Id,
+    start_ticks: Ticks,
+    length: Ticks,
+) -> Result<(Id, Clip), StoreError> {
+    check_length(length)?;
+    let (track_id, mut clip) = find_clip(song, clip_id)?;
+    clip.start_ticks = start_ticks;
+    clip.length = length;
+    let end_ticks = clip.end_ticks();
+    clip.event_ids.retain(|event_id| {
+        song.get_event(event_id)
+            .is_none_or(|event| event.get_ticks() >= start_ticks && event.get_ticks() < end_ticks)
+    });
+    put_clip(song, &track_id, clip.clone());
+    Ok((track_id, clip))
+}
+
+/// Adds a copy of the clip and its events at `start_ticks`, right after the clip by default,
+/// with new ids. Events keep the MIDI channel they were imported from.
+pub fn duplicate_clip(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    clip_id: &Id,
+    start_ticks: Option<Ticks>,
+) -> Result<(Id, Clip), StoreError> {
+    let (track_id, clip) = find_clip(song, clip_id)?;
+    let start_ticks = start_ticks.unwrap_or(clip.end_ticks());
+    let delta = start_ticks.as_u64() as i64 - clip.start_ticks.as_u64() as i64;
+
+    let mut event_ids = Vec::new();
+    for event_id in clip.events_in(song) {
+        let event = song.get_event(&event_id).unwrap();
+        let ticks = (event.get_ticks().as_u64() as i64 + delta).max(0);
+        let copy: Event = event
+            .clone()
+            .with_id(ids.next_id()?)
+            .with_ticks(Ticks::new(ticks as u64));
+        let copy_id = song.add_event(copy)?.get_id();
+        if let Some(channel) = song.get_channel(&event_id) {
+            song.set_channel(copy_id, channel);
+        }
+        event_ids.push(copy_id);
+    }
+
+    let copy = Clip {
+        id: ids.next_id()?,
+        start_ticks,
+        length: clip.length,
+        event_ids,
+    };
+    put_clip(song, &track_id, copy.clone());
+    Ok((track_id, copy))
+}
+
+/// Removes the clip, and with `remove_events` the events it holds as well.
+pub fn remove_clip(song: &mut Song, clip_id: &Id, remove_events: bool) -> Result<(), StoreError> {
+    let (track_id, clip) = find_clip(song, clip_id)?;
+    if remove_events {
+        for event_id in clip.events_in(song) {
+            song.remove_event(&event_id);
+        }
+    }
+    song.update_track(&track_id, |track| {
+        track.clips.retain(|other| other.id != *clip_id);
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_clips() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 1920, 240, 36, 100);
+        let second = add_note(&mut song, track_id, 2400, 240, 38, 100);
+        add_note(&mut song, track_id, 3840, 240, 36, 100);
+
+        let clip = add_clip(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            Ticks::new(1920),
+            Ticks::new(1920),
+            None,
+        )
+        .unwrap();
+        assert_eq!(clip.event_ids, vec![first, second]);
+
+        move_clip(&mut song, &clip.id, Ticks::new(0)).unwrap();
+        let ticks = |song: &Song, id| song.get_event(&id).unwrap().get_ticks().as_u64();
+        assert_eq!((ticks(&song, first), ticks(&song, second)), (0, 480));
+        assert_eq!(
+            find_clip(&song, &clip.id).unwrap().1.start_ticks,
+            Ticks::new(0)
+        );
+
+        let (_, copy) =
+            duplicate_clip(&mut song, &mut IdStrategy::default(), &clip.id, None).unwrap();
+        assert_eq!(copy.start_ticks, Ticks::new(1920));
+        let copied: Vec<u64> = copy.event_ids.iter().map(|id| ticks(&song, *id)).collect();
+        assert_eq!(copied, vec![1920, 2400]);
+
+        let (_, resized) =
+            resize_clip(&mut song, &clip.id, Ticks::new(0), Ticks::new(480)).unwrap();
+        assert_eq!(resized.event_ids, vec![first]);
+        assert_eq!(ticks(&song, second), 480);
+
+        remove_clip(&mut song, &copy.id, true).unwrap();
+        assert_eq!(song.get_track_events(&track_id).len(), 3);
+        assert_eq!(song.get_track(&track_id).unwrap().clips.len(), 1);
+        assert!(add_clip(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            Ticks::new(0),
+            Ticks::new(0),
+            None
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+    },
+    song::song::Song,
+};
+use std::collections::HashMap;
+
+/// Adds a copy of `track_id` right after it, with a new id for the track and each of its events.
+/// The copy takes over every property of the track and is named after it, with new ids for its
+/// clips as well; events keep the MIDI channel they were imported from. Returns the new track's
+/// id.
+pub fn duplicate_track(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+) -> Result<Id, StoreError> {
+    let track = song
+        .get_track(&track_id)
+        .ok_or(StoreError::TrackNotFound(track_id))?;
+    let index = song.get_tracks().position(&track_id).unwrap();
+
+    let new_track_id = ids.next_id()?;
+    let mut copy = track.without_events();
+    copy.id = new_track_id;
+    copy.display.name = Some(match &track.display.name {
+        Some(name) => format!("{} (Copy)", name),
+        None => "Copy".to_string(),
+    });
+    let mut channels = Vec::new();
+    let mut copied_ids = HashMap::new();
+    let mut events = Vec::new();
+    for event in song.track_events(track).iter() {
+        let event_copy = event
+            .clone()
+            .with_id(ids.next_id()?)
+            .with_track_id(new_track_id);
+        if let Some(channel) = song.get_channel(&event.get_id()) {
+            channels.push((event_copy.get_id(), channel));
+        }
+        copied_ids.insert(event.get_id(), event_copy.get_id());
+        events.push(event_copy);
+    }
+    for clip in &mut copy.clips {
+        clip.id = ids.next_id()?;
+        clip.event_ids = clip
+            .event_ids
+            .iter()
+            .filter_map(|event_id| copied_ids.get(event_id).copied())
+            .collect();
+    }
+
+    song.insert_track_at(index + 1, copy, events)?;
+    for (event_id, channel) in channels {
+        song.set_channel(event_id, channel);
+    }
+    Ok(new_track_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        track::track::Track,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_duplicate_track() {
+        let (mut song, track_id) = song_with_track();
+        let last_track_id = song.add_track(Track::new(Id::new())).id;
+        let note = add_note(&mut song, track_id, 480, 240, 60, 100);
+        add_control_change(&mut song, track_id, 0, 7, 100);
+        song.set_channel(note, 9);
+        song.get_track_mut(&track_id).unwrap().program = Some(4);
+
+        let copy_id = duplicate_track(&mut song, &mut IdStrategy::default(), track_id).unwrap();
+        let order: Vec<Id> = song.get_tracks().iter().map(|track| track.id).collect();
+        assert_eq!(order, vec![track_id, copy_id, last_track_id]);
+
+        let copy = song.get_track(&copy_id).unwrap();
+        assert_eq!(copy.program, Some(4));
+        assert_eq!(copy.display.name.as_deref(), Some("Copy"));
+        let events = song.get_track_events(&copy_id);
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| event.get_track_id() == Some(copy_id) && event.get_id() != note));
+        let note_copy = events
+            .iter()
+            .find(|event| event.as_note().is_some())
+            .unwrap();
+        assert_eq!(song.get_channel(&note_copy.get_id()), Some(9));
+        assert_eq!(song.get_events(None).len(), 4);
+        let missing = Id::new();
+        assert_eq!(
+            duplicate_track(&mut song, &mut IdStrategy::default(), missing).unwrap_err(),
+            StoreError::TrackNotFound(missing)
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    event::{
+        control_change::{ControlChange, ControllerNumber, ControllerValue},
+        event::Event,
+        note::{Note, NoteNumber, Velocity},
+    },
+    shared::unit::time::Ticks,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_EXPRESSION_INTERFACES: &'static str = r#"
+/** What selects an articulation on the track's instrument. */
+export type Keyswitch =
+  | { note: { noteNumber: number; velocity?: number } }
+  | { controlChange: { controller: number; value: number } };
+
+/**
+ * Keyswitches by articulation name. A note takes the first articulation, in name order, that
+ * it has a nonzero parameter of the same name for, e.g. `{ parameters: { staccato: 1 } }`.
+ */
+export type ExpressionMap = Record<string, Keyswitch>;
+"#;
+
+/// Velocity of keyswitch notes that do not give one.
+const DEFAULT_KEYSWITCH_VELOCITY: u8 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Keyswitch {
+    #[serde(rename_all = "camelCase")]
+    Note {
+        note_number: NoteNumber,
+        velocity: Option<Velocity>,
+    },
+    ControlChange {
+        controller: ControllerNumber,
+        value: ControllerValue,
+    },
+}
+
+impl Keyswitch {
+    /// The event that selects the articulation for `note`, at its ticks on its track. Keyswitch
+    /// notes last one tick, and the event's id is derived from the note's.
+    fn to_event(self, note: &Note) -> Event {
+        let (id, ticks, track_id) = (note.id.derived(), note.ticks, note.track_id);
+        match self {
+            Keyswitch::Note {
+                note_number,
+                velocity,
+            } => Event::Note(Note {
+                id,
+                ticks,
+                duration: Ticks::new(1),
+                velocity: velocity.unwrap_or(Velocity::new(DEFAULT_KEYSWITCH_VELOCITY)),
+                note_number,
+                track_id,
+                parameters: Default::default(),
+            }),
+            Keyswitch::ControlChange { controller, value } => Event::ControlChange(ControlChange {
+                id,
+                ticks,
+                controller,
+                value,
+                track_id,
+            }),
+        }
+    }
+}
+
+/// A track's articulations, by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExpressionMap(BTreeMap<String, Keyswitch>);
+
+impl ExpressionMap {
+    /// The articulation of `note`: the first one it has a nonzero parameter for.
+    fn articulation(&self, note: &Note) -> Option<(&str, Keyswitch)> {
+        self.0
+            .iter()
+            .find(|(name, _)| {
+                note.parameters
+                    .get(*name)
+                    .is_some_and(|value| *value != 0.0)
+            })
+            .map(|(name, keyswitch)| (name.as_str(), *keyswitch))
+    }
+
+    /// `events` with a keyswitch event placed just before each articulated note, at the note's
+    /// ticks. Notes starting together with the same articulation share one keyswitch.
+    pub fn with_keyswitches(&self, events: Vec<Event>) -> Vec<Event> {
+        if self.0.is_empty() {
+            return events;
+        }
+
+        let mut switched: BTreeSet<(Ticks, &str)> = BTreeSet::new();
+        let mut result = Vec::with_capacity(events.len());
+        for event in events {
+            if let Event::Note(note) = &event {
+                if let Some((name, keyswitch)) = self.articulation(note) {
+                    if switched.insert((note.ticks, name)) {
+                        result.push(keyswitch.to_event(note));
+                    }
+                }
+            }
+            result.push(event);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_with_keyswitches() {
+        let (mut song, track_id) = song_with_track();
+        let plain = add_note(&mut song, track_id, 0, 240, 60, 100);
+        let first = add_note(&mut song, track_id, 480, 240, 60, 100);
+        let second = add_note(&mut song, track_id, 480, 240, 64, 100);
+        let legato = add_note(&mut song, track_id, 960, 240, 62, 100);
+        let mut events: Vec<Event> = song
+            .get_track_events(&track_id)
+            .into_iter()
+            .cloned()
+            .collect();
+        for (id, name) in [
+            (first, "staccato"),
+            (second, "staccato"),
+            (legato, "legato"),
+        ] {
+            let Some(Event::Note(note)) = events.iter_mut().find(|event| event.get_id() == id)
+            else {
+                unreachable!();
+            };
+            note.parameters.insert(name.to_string(), 1.0);
+        }
+
+        let map: ExpressionMap = serde_json::from_str(
+            r#"{
+                "staccato": { "note": { "noteNumber": 24 } },
+                "legato": { "controlChange": { "controller": 58, "value": 10 } }
+            }"#,
+        )
+        .unwrap();
+        let events = map.with_keyswitches(events);
+
+        let summary: Vec<(u64, Option<u8>)> = events
+            .iter()
+            .map(|event| {
+                (
+                    event.get_ticks().as_u64(),
+                    event.as_note().map(|note| note.note_number.as_u8()),
+                )
+            })
+            .collect();
+        assert_eq!(events.len(), 6);
+        assert_eq!(summary[0], (0, Some(60)));
+        assert_eq!(events[0].get_id(), plain);
+        assert_eq!(summary[1], (480, Some(24)));
+        assert_eq!(events[1].as_note().unwrap().velocity.as_u8(), 100);
+        assert_eq!(summary[4], (960, None));
+        assert_eq!(events[5].get_id(), legato);
+    }
+}
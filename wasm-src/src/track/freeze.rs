@@ -0,0 +1,179 @@
+use super::track::Track;
+use crate::{event::event::Event, shared::id::Id, song::song::Song};
+use std::collections::HashMap;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FREEZE_INTERFACES: &'static str = r#"
+export interface TrackDiff {
+  label?: string;
+  added: Event[];
+  removed: Event[];
+  modified: { before: Event; after: Event }[];
+}
+"#;
+
+/// A labeled copy of a track's events to compare later edits against.
+#[derive(Debug, Clone)]
+pub struct FrozenTrack {
+    label: Option<String>,
+    events: HashMap<Id, Event>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrackDiff {
+    pub label: Option<String>,
+    pub added: Vec<Event>,
+    pub removed: Vec<Event>,
+    pub modified: Vec<(Event, Event)>,
+}
+
+impl FrozenTrack {
+    pub fn capture(song: &Song, track: &Track, label: Option<String>) -> Self {
+        FrozenTrack {
+            label,
+            events: song
+                .track_events(track)
+                .iter()
+                .map(|event| (event.get_id(), event.clone()))
+                .collect(),
+        }
+    }
+
+    /// What changed in `track` since it was frozen, each list ordered by ticks.
+    pub fn diff(&self, song: &Song, track: &Track) -> TrackDiff {
+        let mut diff = TrackDiff {
+            label: self.label.clone(),
+            ..Default::default()
+        };
+
+        for event in song.track_events(track).iter() {
+            match self.events.get(&event.get_id()) {
+                None => diff.added.push(event.clone()),
+                Some(before) if before != event => {
+                    diff.modified.push((before.clone(), event.clone()))
+                }
+                _ => {}
+            }
+        }
+
+        diff.removed = self
+            .events
+            .values()
+            .filter(|event| {
+                song.get_event(&event.get_id())
+                    .is_none_or(|current| current.get_track_id() != Some(track.id))
+            })
+            .cloned()
+            .collect();
+        diff.removed
+            .sort_by_key(|event| (event.get_ticks(), event.get_id()));
+
+        diff
+    }
+}
+
+impl TrackDiff {
+    /// Current events carry their timestamps from `song`; removed and `before` events do not.
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self, song: &Song) -> js_sys::Object {
+        let js_diff = js_sys::Object::new();
+        let events_to_js = |events: &[Event]| -> js_sys::Array { song.events_to_js_array(events) };
+
+        if let Some(label) = &self.label {
+            js_sys::Reflect::set(
+                &js_diff,
+                &JsValue::from_str("label"),
+                &JsValue::from_str(label),
+            )
+            .unwrap();
+        }
+
+        js_sys::Reflect::set(
+            &js_diff,
+            &JsValue::from_str("added"),
+            &events_to_js(&self.added),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_diff,
+            &JsValue::from_str("removed"),
+            &self
+                .removed
+                .iter()
+                .map(|event| event.to_js_object())
+                .collect::<js_sys::Array>(),
+        )
+        .unwrap();
+
+        let modified: js_sys::Array = self
+            .modified
+            .iter()
+            .map(|(before, after)| {
+                let js_modified = js_sys::Object::new();
+                js_sys::Reflect::set(
+                    &js_modified,
+                    &JsValue::from_str("before"),
+                    &before.to_js_object(),
+                )
+                .unwrap();
+                js_sys::Reflect::set(
+                    &js_modified,
+                    &JsValue::from_str("after"),
+                    &song.event_to_js_object(after),
+                )
+                .unwrap();
+                js_modified
+            })
+            .collect();
+        js_sys::Reflect::set(&js_diff, &JsValue::from_str("modified"), &modified).unwrap();
+
+        js_diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_diff_against_frozen() {
+        let (mut song, track_id) = song_with_track();
+        let kept = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let moved = add_note(&mut song, track_id, 480, 480, 62, 100);
+        let removed = add_note(&mut song, track_id, 960, 480, 64, 100);
+
+        let frozen = FrozenTrack::capture(
+            &song,
+            song.get_track(&track_id).unwrap(),
+            Some("before mixdown".to_string()),
+        );
+
+        let event = song.get_event(&moved).unwrap().clone();
+        song.replace_event(event.with_ticks(Ticks::new(720)))
+            .unwrap();
+        song.remove_event(&removed);
+        let added = add_note(&mut song, track_id, 1440, 480, 65, 100);
+
+        let diff = frozen.diff(&song, song.get_track(&track_id).unwrap());
+        assert_eq!(diff.label.as_deref(), Some("before mixdown"));
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].get_id(), added);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].get_id(), removed);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].0.get_ticks(), Ticks::new(480));
+        assert_eq!(diff.modified[0].1.get_ticks(), Ticks::new(720));
+        assert!(diff
+            .modified
+            .iter()
+            .all(|(before, _)| before.get_id() != kept));
+    }
+}
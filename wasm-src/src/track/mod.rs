@@ -1 +1,7 @@
+pub mod clip;
+pub mod duplicate;
+pub mod expression;
+pub mod freeze;
+pub mod take;
 pub mod track;
+pub mod trim;
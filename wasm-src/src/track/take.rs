@@ -0,0 +1,175 @@
+use crate::{
+    event::event::Event,
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        js::string_enum,
+    },
+    song::song::Song,
+    track::track::{Track, TrackDisplay, TrackMix},
+};
+
+string_enum! {
+    /// How `mergeTakes` resolves the takes of a cycle recording.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TakeMerge {
+        /// Keeps the events of every take on the track.
+        Overdub = "overdub",
+        /// Keeps only the last take that recorded anything.
+        ReplaceLast = "replaceLast",
+        /// Keeps the last take on the track and moves each earlier one to a muted track of its own.
+        KeepTakes = "keepTakes",
+    }
+}
+
+/// Resolves the takes started on `track_id` with `Song::start_take` and forgets them. Events
+/// added before the first take are left alone. Returns the track followed by the tracks
+/// created for earlier takes.
+pub fn merge_takes(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+    merge: TakeMerge,
+) -> Result<Vec<Id>, StoreError> {
+    song.get_track(&track_id)
+        .ok_or(StoreError::TrackNotFound(track_id))?;
+    let starts = song.clear_takes(&track_id);
+    let track = song.get_track(&track_id).unwrap();
+
+    let mut takes: Vec<Vec<Event>> = vec![Vec::new(); starts.len()];
+    for event in song.track_events(track).iter() {
+        let sequence = song.get_sequence(&event.get_id()).unwrap_or(0);
+        let take = starts.partition_point(|start| *start <= sequence);
+        if take > 0 {
+            takes[take - 1].push(event.clone());
+        }
+    }
+    let Some(last) = takes.iter().rposition(|events| !events.is_empty()) else {
+        return Ok(vec![track_id]);
+    };
+    let base = track.without_events();
+
+    let mut track_ids = vec![track_id];
+    for (take, events) in takes.into_iter().enumerate().take(last) {
+        match merge {
+            TakeMerge::Overdub => {}
+            TakeMerge::ReplaceLast => {
+                for event in events {
+                    song.remove_event(&event.get_id());
+                }
+            }
+            TakeMerge::KeepTakes => {
+                if events.is_empty() {
+                    continue;
+                }
+                let mut take_track = Track::new(ids.next_id()?);
+                take_track.settings = base.settings.clone();
+                take_track.pitch_range = base.pitch_range;
+                take_track.pitch_bend_range = base.pitch_bend_range;
+                take_track.expression_map = base.expression_map.clone();
+                take_track.mix = TrackMix {
+                    mute: true,
+                    solo: false,
+                    ..base.mix.clone()
+                };
+                take_track.output = base.output.clone();
+                take_track.channel = base.channel;
+                take_track.program = base.program;
+                take_track.display = TrackDisplay {
+                    name: Some(match &base.display.name {
+                        Some(name) => format!("{} (Take {})", name, take + 1),
+                        None => format!("Take {}", take + 1),
+                    }),
+                    color: base.display.color.clone(),
+                };
+                let take_track_id = song.add_track(take_track).id;
+
+                for event in events {
+                    song.replace_event(event.with_track_id(take_track_id))?;
+                }
+                track_ids.push(take_track_id);
+            }
+        }
+    }
+
+    Ok(track_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    fn record_takes(song: &mut Song, track_id: Id) -> [Id; 4] {
+        let existing = add_note(song, track_id, 0, 480, 48, 100);
+        song.start_take(track_id);
+        let first = add_note(song, track_id, 0, 480, 60, 100);
+        song.start_take(track_id);
+        let second = add_note(song, track_id, 480, 480, 62, 100);
+        song.start_take(track_id);
+        let third = add_note(song, track_id, 960, 480, 64, 100);
+        song.start_take(track_id);
+        [existing, first, second, third]
+    }
+
+    #[test]
+    fn test_merge_takes() {
+        let (mut song, track_id) = song_with_track();
+        let ids = record_takes(&mut song, track_id);
+        assert_eq!(
+            merge_takes(
+                &mut song,
+                &mut IdStrategy::default(),
+                track_id,
+                TakeMerge::Overdub
+            )
+            .unwrap(),
+            vec![track_id]
+        );
+        assert!(ids.iter().all(|id| song.get_event(id).is_some()));
+
+        let (mut song, track_id) = song_with_track();
+        let [existing, first, second, third] = record_takes(&mut song, track_id);
+        merge_takes(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            TakeMerge::ReplaceLast,
+        )
+        .unwrap();
+        assert!(song.get_event(&existing).is_some());
+        assert!(song.get_event(&first).is_none());
+        assert!(song.get_event(&second).is_none());
+        assert!(song.get_event(&third).is_some());
+
+        let (mut song, track_id) = song_with_track();
+        let [existing, first, second, third] = record_takes(&mut song, track_id);
+        let track_ids = merge_takes(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            TakeMerge::KeepTakes,
+        )
+        .unwrap();
+        assert_eq!(track_ids.len(), 3);
+        let track_of = |event_id: Id| song.get_event(&event_id).unwrap().get_track_id().unwrap();
+        assert_eq!(track_of(existing), track_id);
+        assert_eq!(track_of(first), track_ids[1]);
+        assert_eq!(track_of(second), track_ids[2]);
+        assert_eq!(track_of(third), track_id);
+        let take = song.get_track(&track_ids[1]).unwrap();
+        assert!(take.mix.mute);
+        assert_eq!(take.display.name.as_deref(), Some("Take 1"));
+
+        assert_eq!(
+            merge_takes(
+                &mut song,
+                &mut IdStrategy::default(),
+                track_id,
+                TakeMerge::KeepTakes
+            )
+            .unwrap(),
+            vec![track_id]
+        );
+    }
+}
@@ -1,229 +1,773 @@
 use crate::{
-    event::event::Event,
-    shared::{id::Id, unit::time::Ticks},
+    event::{
+        event::Event,
+        index::EventIndex,
+        marker::Marker,
+        note::{Note, NoteNumber, Velocity},
+    },
+    shared::{id::Id, js::string_enum, meta::Meta, unit::time::Ticks},
+    song::{
+        compact::CompactionReport,
+        groove::{GroovePool, TrackGroove},
+        recording::RecordFilter,
+    },
+    track::{clip::Clip, expression::ExpressionMap},
 };
+#[cfg(feature = "wasm")]
+use crate::{
+    query::summary::TrackStats,
+    shared::error::{parse_id, StoreError},
+};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 use std::{
-    cell::RefCell,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::HashSet,
     ops::{Deref, DerefMut},
 };
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(typescript_custom_section)]
 const TS_TRACK_INTERFACE: &'static str = r#"
 export interface Track {
   id: string;
+  /**
+   * 0-based position in the song's track order, which every export follows. Set by the store;
+   * ignored when adding a track.
+   */
+  order?: number;
   events: Event[];
+  settings?: TrackSettings;
+  pitchRange?: PitchRange | null;
+  /** Semitones a full pitch bend reaches in either direction. Defaults to 2. */
+  pitchBendRange?: number;
+  /** Keyswitches for the articulations of the track's instrument. */
+  expressionMap?: ExpressionMap;
+  mix?: TrackMix;
+  display?: TrackDisplay;
+  /** Where the track plays, such as a MIDI port or plugin instance id. */
+  output?: string | null;
+  /** 0-based MIDI channel the track plays on. */
+  channel?: number | null;
+  /** General MIDI program of the track's instrument, from 0 to 127. */
+  program?: number | null;
+  /** The track's clips, see `Store.getClips`. */
+  clips?: Omit<Clip, "trackId">[];
+  meta?: Meta;
+  /** Only set by `getTracks(true)`. */
+  stats?: TrackStats;
+}
+
+/**
+ * Changes to a track's header. Fields left out are kept; `null` clears them. `name` and `color`
+ * are the track's `display` fields.
+ */
+export interface TrackUpdater {
+  id: string;
+  name?: string | null;
+  color?: string | null;
+  channel?: number | null;
+  program?: number | null;
+  /** Replaces the track's meta. */
+  meta?: Meta | null;
+}
+
+export interface TrackDisplay {
+  name?: string | null;
+  /** A CSS color. Renderers fall back to a palette color picked by track position. */
+  color?: string | null;
+}
+
+export interface TrackMix {
+  mute?: boolean;
+  /** Soloing a muted track makes it sound again. */
+  solo?: boolean;
+  /**
+   * Solo group: soloing a grouped track silences only the other tracks of its group, while
+   * soloing an ungrouped track silences every track that is not soloed.
+   */
+  group?: string | null;
+  /** Gain from 0 to 1 for the host to apply. Defaults to 1. */
+  volume?: number;
+  /** Stereo position from -1 (left) to 1 (right). Defaults to 0. */
+  pan?: number;
+}
+
+export interface PitchRange {
+  low: number;
+  high: number;
+}
+
+export interface TrackSettings {
+  transpose?: number;
+  velocityOffset?: number;
+  swing?: number;
+  /** Ticks added to the track's playback positions; negative values play early. */
+  delayTicks?: number;
+  /** Milliseconds added to the track's playback positions, on top of `delayTicks`. */
+  delayMs?: number;
+  /** Groove from the song's groove pool, applied before swing. */
+  groove?: TrackGroove | null;
+  /** What the track accepts while recording; everything when left out. */
+  recordFilter?: RecordFilter | null;
 }
 "#;
 
-#[derive(Clone)]
-pub struct Track {
-    pub(crate) id: Id,
-    events: HashMap<Id, Event>,
-    ticks_index: BTreeMap<Ticks, HashSet<Id>>,
-    end_ticks_index: BTreeMap<Ticks, HashSet<Id>>,
+/// Non-destructive playback adjustments applied on top of a track's stored events.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrackSettings {
+    /// Semitones added to every note number.
+    pub transpose: i32,
+    /// Added to every note velocity.
+    pub velocity_offset: i32,
+    /// 0 = straight, 1 = triplet feel. Off-beat eighths are pushed towards the last third of
+    /// the beat.
+    pub swing: f64,
+    /// Playback offset, usually negative to start slow-attack instruments early.
+    pub delay_ticks: i32,
+    /// Playback offset in milliseconds, converted to ticks at the playback tempo.
+    pub delay_ms: f64,
+    pub groove: Option<TrackGroove>,
+    /// What the track accepts while recording; everything when `None`.
+    pub record_filter: Option<RecordFilter>,
 }
 
-impl Track {
-    pub(crate) fn new(id: Id, events: Option<Vec<Event>>) -> Self {
-        let mut track = Track {
-            id,
-            events: HashMap::new(),
-            ticks_index: BTreeMap::new(),
-            end_ticks_index: BTreeMap::new(),
+impl TrackSettings {
+    fn swing_ticks(&self, ticks: Ticks, ppq: u32) -> Ticks {
+        if self.swing == 0.0 || ppq < 2 {
+            return ticks;
+        }
+
+        let beat = ppq as f64;
+        let half = beat / 2.0;
+        let swung_half = half + self.swing.clamp(0.0, 1.0) * beat / 6.0;
+
+        let beat_start = ticks.as_u64() - ticks.as_u64() % ppq as u64;
+        let position = (ticks.as_u64() % ppq as u64) as f64;
+        let swung = if position < half {
+            position * swung_half / half
+        } else {
+            swung_half + (position - half) * (beat - swung_half) / half
+        };
+
+        Ticks::new(beat_start + swung.round() as u64)
+    }
+
+    /// The total playback offset in ticks at `bpm`.
+    pub fn delay(&self, ppq: u32, bpm: f64) -> i64 {
+        let delay_ms_ticks = self.delay_ms / 1000.0 * bpm / 60.0 * ppq as f64;
+        self.delay_ticks as i64 + delay_ms_ticks.round() as i64
+    }
+
+    /// Returns `event` with these settings baked in, looking the groove up in `grooves`. The
+    /// delay is left out as it depends on the playback tempo.
+    pub fn apply(&self, event: Event, ppq: u32, grooves: &GroovePool) -> Event {
+        let event = match self
+            .groove
+            .and_then(|track_groove| Some((grooves.get(&track_groove.groove_id)?, track_groove)))
+        {
+            Some((groove, track_groove)) => groove.apply(event, track_groove.amount),
+            None => event,
         };
+        let ticks = self.swing_ticks(event.get_ticks(), ppq);
 
-        if let Some(events) = events {
-            for event in events {
-                track.add_event(event);
+        match event.with_ticks(ticks) {
+            Event::Note(note) => {
+                let note_number = (note.note_number.as_u8() as i32 + self.transpose).clamp(0, 127);
+                let velocity = (note.velocity.as_u8() as i32 + self.velocity_offset).clamp(1, 127);
+
+                Event::Note(Note {
+                    note_number: NoteNumber::new(note_number as u8),
+                    velocity: Velocity::new(velocity as u8),
+                    ..note
+                })
             }
+            event => event,
         }
+    }
+}
 
-        track
+/// Mute, solo, volume and pan. Whether a track ends up audible also depends on the other
+/// tracks' solos, see `Song::is_track_audible`. Volume and pan are left to the host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrackMix {
+    pub mute: bool,
+    pub solo: bool,
+    pub group: Option<String>,
+    pub volume: f64,
+    pub pan: f64,
+}
+
+impl Default for TrackMix {
+    fn default() -> Self {
+        TrackMix {
+            mute: false,
+            solo: false,
+            group: None,
+            volume: 1.0,
+            pan: 0.0,
+        }
     }
+}
+
+/// How the track is labelled in editors; it has no effect on playback.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrackDisplay {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+/// The playable pitches of a track's instrument, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PitchRange {
+    pub low: NoteNumber,
+    pub high: NoteNumber,
+}
 
-    pub(crate) fn get_event(&self, event_id: &Id) -> Option<&Event> {
-        self.events.get(event_id)
+impl PitchRange {
+    pub fn contains(&self, note_number: NoteNumber) -> bool {
+        self.low <= note_number && note_number <= self.high
     }
+}
 
-    pub(crate) fn get_events(&self) -> Vec<&Event> {
-        self.ticks_index
-            .iter()
-            .map(|(_, ids)| ids.iter().filter_map(|id| self.events.get(id)))
-            .flatten()
-            .collect()
+/// Semitones a full pitch bend reaches in either direction, the General MIDI default.
+pub const DEFAULT_PITCH_BEND_RANGE: u8 = 2;
+
+/// The 14-bit pitch bend value at rest.
+const PITCH_BEND_CENTER: f64 = 8192.0;
+
+string_enum! {
+    /// What `addEvent` does with a note outside its track's pitch range.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RangeCheck {
+        Warn = "warn",
+        Reject = "reject",
     }
+}
 
-    pub(crate) fn get_events_in_ticks_range(
-        &self,
-        start_ticks: Ticks,
-        end_ticks: Ticks,
-        within_duration: bool,
-    ) -> Vec<&Event> {
-        let got_event_ids: RefCell<HashSet<Id>> = RefCell::new(HashSet::new());
-
-        // TODO: refactor
-        let events: Vec<&Event> = self
-            .ticks_index
-            .range(start_ticks..end_ticks)
-            .map(|(_, ids)| {
-                ids.iter()
-                    .filter_map(|id| self.events.get(id))
-                    .map(|event| {
-                        if within_duration {
-                            got_event_ids.borrow_mut().insert(event.get_id());
-                        }
-                        event
-                    })
-            })
-            .flatten()
-            .collect();
+/// A track's own properties, and the slots of its events in its song's arena. The events
+/// themselves live in the song; see `Song::get_track_events`. Tracks serialize without them,
+/// songs write them out with each track through `TrackWithEvents`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Track {
+    pub id: Id,
+    pub settings: TrackSettings,
+    pub pitch_range: Option<PitchRange>,
+    pub pitch_bend_range: u8,
+    pub expression_map: ExpressionMap,
+    pub mix: TrackMix,
+    pub display: TrackDisplay,
+    /// Routing chosen by the host; the store only keeps it.
+    pub output: Option<String>,
+    /// 0-based MIDI channel, from 0 to 15.
+    pub channel: Option<u8>,
+    /// General MIDI program, from 0 to 127.
+    pub program: Option<u8>,
+    #[serde(default)]
+    pub clips: Vec<Clip>,
+    #[serde(default)]
+    pub meta: Meta,
+    /// Position in the song's track list, kept up to date by `TrackVec`.
+    #[serde(default)]
+    order: usize,
+    #[serde(skip)]
+    events: EventIndex,
+}
 
-        if !within_duration {
-            return events;
+impl Track {
+    pub fn new(id: Id) -> Self {
+        Track {
+            id,
+            settings: TrackSettings::default(),
+            pitch_range: None,
+            pitch_bend_range: DEFAULT_PITCH_BEND_RANGE,
+            expression_map: ExpressionMap::default(),
+            mix: TrackMix::default(),
+            display: TrackDisplay::default(),
+            output: None,
+            channel: None,
+            program: None,
+            clips: Vec::new(),
+            meta: Meta::default(),
+            order: 0,
+            events: EventIndex::default(),
         }
+    }
 
-        // TODO: refactor
-        let tick = Ticks::new(1);
-        let mut has_duration_events: Vec<&Event> = self
-            .end_ticks_index
-            .range((start_ticks + tick)..)
-            .map(|(_, ids)| {
-                ids.iter()
-                    .filter_map(|id| self.events.get(id))
-                    .filter(|event| {
-                        event.get_ticks() < start_ticks
-                            && !got_event_ids.borrow().contains(&event.get_id())
-                    })
-            })
-            .flatten()
-            .collect();
+    /// A copy of the track's own properties with no events.
+    pub fn without_events(&self) -> Self {
+        Track {
+            settings: self.settings.clone(),
+            pitch_range: self.pitch_range,
+            pitch_bend_range: self.pitch_bend_range,
+            expression_map: self.expression_map.clone(),
+            mix: self.mix.clone(),
+            display: self.display.clone(),
+            output: self.output.clone(),
+            channel: self.channel,
+            program: self.program,
+            clips: self.clips.clone(),
+            meta: self.meta.clone(),
+            order: self.order,
+            ..Track::new(self.id)
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
 
-        // MEMO: can it be implemented so that it does not need to be sorted?
-        has_duration_events.sort_by(|a, b| a.get_ticks().cmp(&b.get_ticks()));
+    /// Takes over `other`'s own properties, keeping this track's id, position and events.
+    /// Whether the track's own properties equal `other`'s, leaving aside events and position.
+    pub fn has_properties_of(&self, other: &Track) -> bool {
+        let properties = Track {
+            id: self.id,
+            order: self.order,
+            ..other.without_events()
+        };
+        properties == self.without_events()
+    }
 
-        let mut merged_events = Vec::with_capacity(events.len() + has_duration_events.len());
+    pub fn set_properties(&mut self, other: &Track) {
+        *self = Track {
+            id: self.id,
+            order: self.order,
+            events: std::mem::take(&mut self.events),
+            ..other.without_events()
+        };
+    }
 
-        let (mut i, mut j) = (0, 0);
-        while i < events.len() && j < has_duration_events.len() {
-            if events[i].get_ticks() <= has_duration_events[j].get_ticks() {
-                merged_events.push(events[i]);
-                i += 1;
-            } else {
-                merged_events.push(has_duration_events[j]);
-                j += 1;
-            }
+    /// The 14-bit pitch bend value that bends this track's instrument by `cents`, clamped to
+    /// the ends of the bend range.
+    pub fn bend_value_for_cents(&self, cents: f64) -> u16 {
+        let range_cents = self.pitch_bend_range as f64 * 100.0;
+        let value = PITCH_BEND_CENTER + cents / range_cents * PITCH_BEND_CENTER;
+        value.round().clamp(0.0, 16383.0) as u16
+    }
+
+    /// The bend in cents that the 14-bit pitch bend `value` gives this track's instrument.
+    pub fn cents_for_bend_value(&self, value: u16) -> f64 {
+        let range_cents = self.pitch_bend_range as f64 * 100.0;
+        (value.min(16383) as f64 - PITCH_BEND_CENTER) / PITCH_BEND_CENTER * range_cents
+    }
+
+    /// Whether `event` fits the track's pitch range. Events other than notes always fit.
+    pub fn is_in_pitch_range(&self, event: &Event) -> bool {
+        match (self.pitch_range, event) {
+            (Some(pitch_range), Event::Note(note)) => pitch_range.contains(note.note_number),
+            _ => true,
         }
+    }
+
+    /// How many events the track holds.
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
 
-        merged_events.extend_from_slice(&events[i..]);
-        merged_events.extend_from_slice(&has_duration_events[j..]);
+    /// The slots of the track's events in its song's arena.
+    pub(crate) fn event_index(&self) -> &EventIndex {
+        &self.events
+    }
 
-        merged_events
+    pub(crate) fn event_index_mut(&mut self) -> &mut EventIndex {
+        &mut self.events
     }
 
-    pub(crate) fn add_event(&mut self, event: Event) {
-        let id = event.get_id();
-        let ticks = event.get_ticks();
+    /// Drops the empty buckets of the track's indexes.
+    pub fn compact(&mut self, report: &mut CompactionReport) {
+        self.events.compact(report);
+    }
 
-        self.events.insert(id, event);
+    /// The track and the events given with it, which belong to it and appear once each. The
+    /// events are read one by one like `addEvent` reads them.
+    #[cfg(feature = "wasm")]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<(Self, Vec<Event>), StoreError> {
+        if !obj.is_object() {
+            return Err(StoreError::invalid("Track is not an object"));
+        }
+        let events = js_sys::Reflect::get(&obj, &JsValue::from_str("events")).unwrap();
+        let fields: JsTrackFields =
+            StoreError::decoding("Track", || serde_wasm_bindgen::from_value(obj.into()))?;
 
-        self.ticks_index
-            .entry(ticks)
-            .or_insert_with(HashSet::new)
-            .insert(id);
+        let events: Vec<Event> = if events.is_undefined() {
+            Vec::new()
+        } else if js_sys::Array::is_array(&events) {
+            js_sys::Array::from(&events)
+                .iter()
+                .map(|event| Event::from_js_object(event.into()))
+                .collect::<Result<_, _>>()?
+        } else {
+            return Err(StoreError::invalid("Track events are not an array"));
+        };
+        check_events(fields.id, &events).map_err(StoreError::invalid)?;
 
-        if let Some(duration) = event.get_duration() {
-            let end_ticks = ticks + duration;
+        Ok((fields.into_track()?, events))
+    }
 
-            self.end_ticks_index
-                .entry(end_ticks)
-                .or_insert_with(HashSet::new)
-                .insert(id);
+    /// The track in its TypeScript shape, holding `events`.
+    #[cfg(feature = "wasm")]
+    pub(crate) fn to_js<E: Serialize>(&self, events: Vec<E>) -> JsTrack<'_, E> {
+        JsTrack {
+            id: self.id,
+            order: self.order,
+            events,
+            settings: &self.settings,
+            pitch_range: self.pitch_range,
+            pitch_bend_range: self.pitch_bend_range,
+            expression_map: &self.expression_map,
+            mix: &self.mix,
+            display: &self.display,
+            output: self.output.as_deref(),
+            channel: self.channel,
+            program: self.program,
+            clips: &self.clips,
+            meta: (!self.meta.is_empty()).then_some(&self.meta),
+            stats: None,
         }
     }
+}
 
-    pub(crate) fn remove_event(&mut self, event_id: &Id) {
-        let event = self.events.get(&event_id).expect_throw("Event not found");
+/// A track as JS returns it, see `Track` in TypeScript.
+#[cfg(feature = "wasm")]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JsTrack<'a, E> {
+    id: Id,
+    order: usize,
+    events: Vec<E>,
+    settings: &'a TrackSettings,
+    pitch_range: Option<PitchRange>,
+    pitch_bend_range: u8,
+    expression_map: &'a ExpressionMap,
+    mix: &'a TrackMix,
+    display: &'a TrackDisplay,
+    output: Option<&'a str>,
+    channel: Option<u8>,
+    program: Option<u8>,
+    clips: &'a [Clip],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<&'a Meta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TrackStats>,
+}
 
-        let ticks = self
-            .get_event(event_id)
-            .expect_throw(format!("Event with id {} does not exist", event_id.to_string()).as_str())
-            .get_ticks();
+/// A track as JS passes it to `addTrack` and `insertTrackAt`, before its numbers are checked.
+/// Fields left out take the defaults of `Track::new`.
+#[cfg(feature = "wasm")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsTrackFields {
+    id: Id,
+    #[serde(default)]
+    settings: TrackSettings,
+    #[serde(default)]
+    pitch_range: Option<PitchRange>,
+    #[serde(default)]
+    pitch_bend_range: Option<f64>,
+    #[serde(default)]
+    expression_map: ExpressionMap,
+    #[serde(default)]
+    mix: TrackMix,
+    #[serde(default)]
+    display: TrackDisplay,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    channel: Option<f64>,
+    #[serde(default)]
+    program: Option<f64>,
+    #[serde(default)]
+    clips: Vec<Clip>,
+    #[serde(default)]
+    meta: Option<Meta>,
+}
 
-        if let Some(ids) = self.ticks_index.get_mut(&ticks) {
-            ids.remove(&event_id);
+#[cfg(feature = "wasm")]
+impl JsTrackFields {
+    fn into_track(self) -> Result<Track, StoreError> {
+        if let Some(filter) = &self.settings.record_filter {
+            filter.check()?;
         }
+        let pitch_bend_range = match self.pitch_bend_range {
+            Some(semitones) => midi_number(semitones, 127)
+                .filter(|semitones| *semitones >= 1)
+                .ok_or_else(|| StoreError::invalid("Track pitch bend range is not valid"))?,
+            None => DEFAULT_PITCH_BEND_RANGE,
+        };
+        let channel = self
+            .channel
+            .map(|channel| {
+                midi_number(channel, 15)
+                    .ok_or_else(|| StoreError::invalid("Track channel is not valid"))
+            })
+            .transpose()?;
+        let program = self
+            .program
+            .map(|program| {
+                midi_number(program, 127)
+                    .ok_or_else(|| StoreError::invalid("Track program is not valid"))
+            })
+            .transpose()?;
 
-        if let Some(duration) = event.get_duration() {
-            let end_ticks = ticks + duration;
+        Ok(Track {
+            settings: self.settings,
+            pitch_range: self.pitch_range,
+            pitch_bend_range,
+            expression_map: self.expression_map,
+            mix: self.mix,
+            display: self.display,
+            output: self.output,
+            channel,
+            program,
+            clips: self.clips,
+            meta: self.meta.unwrap_or_default(),
+            ..Track::new(self.id)
+        })
+    }
+}
 
-            if let Some(ids) = self.end_ticks_index.get_mut(&end_ticks) {
-                ids.remove(&event_id);
-            }
-        }
+/// A track with its events, as songs serialize their tracks.
+pub(crate) struct TrackWithEvents<'a> {
+    pub track: &'a Track,
+    pub events: Vec<&'a Event>,
+}
 
-        self.events.remove(&event_id);
+/// Events are written in ticks order, ties broken by id, so equal tracks serialize the same.
+impl Serialize for TrackWithEvents<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut events = self.events.clone();
+        events.sort_by_key(|event| (event.get_ticks(), event.get_id()));
+
+        let track = self.track;
+        let mut record = serializer.serialize_struct("Track", 14)?;
+        record.serialize_field("id", &track.id)?;
+        record.serialize_field("order", &track.order)?;
+        record.serialize_field("settings", &track.settings)?;
+        record.serialize_field("pitchRange", &track.pitch_range)?;
+        record.serialize_field("pitchBendRange", &track.pitch_bend_range)?;
+        record.serialize_field("expressionMap", &track.expression_map)?;
+        record.serialize_field("mix", &track.mix)?;
+        record.serialize_field("display", &track.display)?;
+        record.serialize_field("output", &track.output)?;
+        record.serialize_field("channel", &track.channel)?;
+        record.serialize_field("program", &track.program)?;
+        record.serialize_field("events", &events)?;
+        record.serialize_field("clips", &track.clips)?;
+        record.serialize_field("meta", &track.meta)?;
+        record.end()
     }
+}
 
-    pub(crate) fn from_js_object(obj: js_sys::Object) -> Self {
-        let id = js_sys::Reflect::get(&obj, &JsValue::from_str("id"))
-            .unwrap()
-            .as_string()
-            .unwrap();
-        let id = Id::try_from(id.as_str()).unwrap();
+/// A serialized `TrackWithEvents`, before the song takes in its events.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrackRecord {
+    id: Id,
+    #[serde(default)]
+    order: usize,
+    settings: TrackSettings,
+    pitch_range: Option<PitchRange>,
+    pitch_bend_range: u8,
+    expression_map: ExpressionMap,
+    mix: TrackMix,
+    display: TrackDisplay,
+    output: Option<String>,
+    channel: Option<u8>,
+    program: Option<u8>,
+    events: Vec<Event>,
+    #[serde(default)]
+    clips: Vec<Clip>,
+    #[serde(default)]
+    meta: Meta,
+}
 
-        let events: Vec<Event> =
-            js_sys::Array::from(&js_sys::Reflect::get(&obj, &JsValue::from_str("events")).unwrap())
-                .iter()
-                .map(|event| js_sys::Object::from(event))
-                .map(|event| Event::from_js_object(event))
-                .collect();
+/// Checks that the events given for the track `track_id` all belong to it and that none appears
+/// twice.
+pub(crate) fn check_events(track_id: Id, events: &[Event]) -> Result<(), String> {
+    if let Some(event) = events
+        .iter()
+        .find(|event| event.get_track_id() != Some(track_id))
+    {
+        return Err(format!(
+            "Event {} does not belong to track {}",
+            event.get_id(),
+            track_id
+        ));
+    }
+    let mut event_ids = HashSet::new();
+    if let Some(event) = events
+        .iter()
+        .find(|event| !event_ids.insert(event.get_id()))
+    {
+        return Err(format!("Event {} appears twice", event.get_id()));
+    }
+    Ok(())
+}
+
+impl TrackRecord {
+    /// Takes out the markers that songs before format version 8 kept with a track's events.
+    pub(crate) fn take_markers(&mut self) -> Vec<Marker> {
+        let (markers, events) = std::mem::take(&mut self.events)
+            .into_iter()
+            .partition(|event| matches!(event, Event::Marker(_)));
+        self.events = events;
+        markers
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Marker(marker) => Some(marker),
+                _ => None,
+            })
+            .collect()
+    }
 
-        Track::new(id, Some(events))
+    /// The track and its events, once they are checked to belong to it.
+    pub(crate) fn into_parts(self) -> Result<(Track, Vec<Event>), String> {
+        check_events(self.id, &self.events)?;
+        let track = Track {
+            settings: self.settings,
+            pitch_range: self.pitch_range,
+            pitch_bend_range: self.pitch_bend_range,
+            expression_map: self.expression_map,
+            mix: self.mix,
+            display: self.display,
+            output: self.output,
+            channel: self.channel,
+            program: self.program,
+            clips: self.clips,
+            meta: self.meta,
+            order: self.order,
+            ..Track::new(self.id)
+        };
+        Ok((track, self.events))
     }
+}
 
-    pub(crate) fn to_js_object(&self) -> js_sys::Object {
-        let js_track = js_sys::Object::new();
+/// Changes to a track's header. `None` keeps a field and `Some(None)` clears it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackUpdater {
+    pub id: Id,
+    pub name: Option<Option<String>>,
+    pub color: Option<Option<String>>,
+    pub channel: Option<Option<u8>>,
+    pub program: Option<Option<u8>>,
+    /// Replaces the track's meta; an empty one clears it.
+    pub meta: Option<Meta>,
+}
 
-        js_sys::Reflect::set(
-            &js_track,
-            &JsValue::from_str("id"),
-            &JsValue::from_str(self.id.to_string().as_str()),
-        )
-        .unwrap();
+/// Reads the field `name` of a track updater: `None` when undefined and `Some(None)` when null.
+#[cfg(feature = "wasm")]
+fn read_updater_field<T>(
+    obj: &js_sys::Object,
+    name: &str,
+    read: impl Fn(&JsValue) -> Option<T>,
+) -> Result<Option<Option<T>>, StoreError> {
+    let value = js_sys::Reflect::get(obj, &JsValue::from_str(name)).unwrap();
+    if value.is_undefined() {
+        return Ok(None);
+    }
+    if value.is_null() {
+        return Ok(Some(None));
+    }
+    match read(&value) {
+        Some(value) => Ok(Some(Some(value))),
+        None => Err(StoreError::invalid(format!("Track {} is not valid", name))),
+    }
+}
 
-        js_sys::Reflect::set(
-            &js_track,
-            &JsValue::from_str("events"),
-            &self
-                .get_events()
-                .iter()
-                .map(|event| event.to_js_object())
-                .collect::<js_sys::Array>(),
-        )
-        .unwrap();
+/// `value` as a whole number from 0 to `max`.
+#[cfg(feature = "wasm")]
+fn read_midi_number(value: &JsValue, max: u8) -> Option<u8> {
+    value.as_f64().and_then(|number| midi_number(number, max))
+}
+
+/// `number` if it is a whole number from 0 to `max`.
+#[cfg(feature = "wasm")]
+fn midi_number(number: f64, max: u8) -> Option<u8> {
+    (number.fract() == 0.0 && (0.0..=max as f64).contains(&number)).then_some(number as u8)
+}
+
+impl TrackUpdater {
+    #[cfg(feature = "wasm")]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<Self, StoreError> {
+        let id = js_sys::Reflect::get(&obj, &JsValue::from_str("id"))
+            .unwrap()
+            .as_string()
+            .ok_or_else(|| StoreError::invalid("Track id is not valid"))?;
 
-        js_track
+        Ok(TrackUpdater {
+            id: parse_id(&id)?,
+            name: read_updater_field(&obj, "name", JsValue::as_string)?,
+            color: read_updater_field(&obj, "color", JsValue::as_string)?,
+            channel: read_updater_field(&obj, "channel", |value| read_midi_number(value, 15))?,
+            program: read_updater_field(&obj, "program", |value| read_midi_number(value, 127))?,
+            meta: Meta::from_js(&js_sys::Reflect::get(&obj, &JsValue::from_str("meta")).unwrap())?,
+        })
+    }
+
+    pub fn apply(&self, track: &mut Track) {
+        if let Some(name) = &self.name {
+            track.display.name = name.clone();
+        }
+        if let Some(color) = &self.color {
+            track.display.color = color.clone();
+        }
+        if let Some(channel) = self.channel {
+            track.channel = channel;
+        }
+        if let Some(program) = self.program {
+            track.program = program;
+        }
+        if let Some(meta) = &self.meta {
+            track.meta = meta.clone();
+        }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TrackVec(Vec<Track>);
 
 impl TrackVec {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         TrackVec(Vec::new())
     }
 
-    pub(crate) fn to_js_array(&self) -> js_sys::Array {
-        self.0.iter().map(|track| track.to_js_object()).collect()
+    pub fn push(&mut self, track: Track) {
+        self.0.push(track);
+        self.renumber();
+    }
+
+    pub fn insert(&mut self, index: usize, track: Track) {
+        self.0.insert(index, track);
+        self.renumber();
+    }
+
+    pub fn remove(&mut self, index: usize) -> Track {
+        let track = self.0.remove(index);
+        self.renumber();
+        track
+    }
+
+    fn renumber(&mut self) {
+        for (order, track) in self.0.iter_mut().enumerate() {
+            track.order = order;
+        }
+    }
+
+    /// Index of the track in the song's track order.
+    pub fn position(&self, track_id: &Id) -> Option<usize> {
+        self.0.iter().position(|track| track.id == *track_id)
     }
 }
 
 impl FromIterator<Track> for TrackVec {
     fn from_iter<T: IntoIterator<Item = Track>>(iter: T) -> Self {
-        TrackVec(iter.into_iter().collect())
+        let mut tracks = TrackVec(iter.into_iter().collect());
+        tracks.renumber();
+        tracks
     }
 }
 
@@ -243,18 +787,86 @@ impl DerefMut for TrackVec {
 
 #[cfg(test)]
 mod tests {
-    use crate::event::note::{Note, NoteNumber, Velocity};
+    use crate::{event::note::NoteParameters, song::song::Song};
 
     use super::*;
 
     #[test]
     fn test_new_track() {
         let id = Id::new();
-        let track = Track::new(id, None);
+        let track = Track::new(id);
         assert_eq!(track.id, id);
-        assert_eq!(track.events.len(), 0);
-        assert_eq!(track.ticks_index.len(), 0);
-        assert_eq!(track.end_ticks_index.len(), 0);
+        assert_eq!(track.event_count(), 0);
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_js_track_fields() {
+        let id = Id::new();
+        let decode = |fields: serde_json::Value| {
+            let mut js = serde_json::json!({ "id": id.to_string() });
+            js.as_object_mut()
+                .unwrap()
+                .extend(fields.as_object().unwrap().clone());
+            StoreError::decoding("Track", || serde_json::from_value::<JsTrackFields>(js))?
+                .into_track()
+        };
+
+        assert_eq!(decode(serde_json::json!({})), Ok(Track::new(id)));
+        let track =
+            decode(serde_json::json!({ "channel": 9, "program": null, "pitchBendRange": 12 }))
+                .unwrap();
+        assert_eq!(
+            (track.channel, track.program, track.pitch_bend_range),
+            (Some(9), None, 12)
+        );
+        for fields in [
+            serde_json::json!({ "channel": 16 }),
+            serde_json::json!({ "channel": 1.5 }),
+            serde_json::json!({ "program": 128 }),
+            serde_json::json!({ "pitchBendRange": 0 }),
+            serde_json::json!({ "pitchBendRange": 500 }),
+        ] {
+            assert!(matches!(
+                decode(fields),
+                Err(StoreError::ValidationFailed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_track_mix_defaults() {
+        let mix: TrackMix = serde_json::from_str(r#"{"mute":true,"pan":-0.5}"#).unwrap();
+        assert!(mix.mute);
+        assert!(!mix.solo);
+        assert_eq!(mix.volume, 1.0);
+        assert_eq!(mix.pan, -0.5);
+        assert_eq!(Track::new(Id::new()).mix, TrackMix::default());
+    }
+
+    #[test]
+    fn test_track_updater_apply() {
+        let mut track = Track::new(Id::new());
+        track.display.color = Some("red".to_string());
+        track.program = Some(0);
+
+        let updater = TrackUpdater {
+            id: track.id,
+            name: Some(Some("Bass".to_string())),
+            color: None,
+            channel: Some(Some(1)),
+            program: Some(None),
+            meta: Some(Meta::from([("part", serde_json::json!("A"))])),
+        };
+        updater.apply(&mut track);
+
+        assert_eq!(track.display.name.as_deref(), Some("Bass"));
+        assert_eq!(track.display.color.as_deref(), Some("red"));
+        assert_eq!((track.channel, track.program), (Some(1), None));
+        assert_eq!(track.meta.get("part"), Some(r#""A""#));
+
+        let json = serde_json::to_string(&track).unwrap();
+        assert_eq!(serde_json::from_str::<Track>(&json).unwrap(), track);
     }
 
     #[test]
@@ -268,6 +880,7 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id,
+            parameters: NoteParameters::new(),
         });
 
         let event2 = Event::Note(Note {
@@ -277,10 +890,141 @@ mod tests {
             velocity: Velocity::new(100),
             note_number: NoteNumber::new(60),
             track_id,
+            parameters: NoteParameters::new(),
         });
 
-        let track = Track::new(track_id, Some(vec![event1, event2]));
+        let mut song = Song::new("test".to_string(), 480);
+        let track = song
+            .add_track_with_events(Track::new(track_id), vec![event1, event2])
+            .unwrap();
         assert_eq!(track.id, track_id);
-        assert_eq!(track.get_events().len(), 2);
+        assert_eq!(track.event_count(), 2);
+        assert_eq!(song.get_track_events(&track_id).len(), 2);
+    }
+
+    #[test]
+    fn test_get_events_in_ticks_range() {
+        let track_id = Id::new();
+        let note = |ticks: u64, duration: u64| {
+            Event::Note(Note {
+                id: Id::new(),
+                ticks: Ticks::new(ticks),
+                duration: Ticks::new(duration),
+                velocity: Velocity::new(100),
+                note_number: NoteNumber::new(60),
+                track_id,
+                parameters: NoteParameters::new(),
+            })
+        };
+        let mut song = Song::new("test".to_string(), 480);
+        song.add_track_with_events(
+            Track::new(track_id),
+            vec![note(960, 240), note(240, 480), note(480, 240)],
+        )
+        .unwrap();
+        let ticks = |start: u64, end: u64, within_duration: bool| -> Vec<u64> {
+            song.get_track_events_in_ticks_range(
+                &track_id,
+                Ticks::new(start),
+                Ticks::new(end),
+                within_duration,
+            )
+            .iter()
+            .map(|event| event.get_ticks().as_u64())
+            .collect()
+        };
+
+        assert_eq!(ticks(0, 1920, false), vec![240, 480, 960]);
+        assert_eq!(ticks(480, 960, false), vec![480]);
+        assert_eq!(ticks(480, 960, true), vec![240, 480]);
+        assert!(ticks(1200, 1920, true).is_empty());
+    }
+
+    #[test]
+    fn test_track_settings_apply() {
+        let settings = TrackSettings {
+            transpose: 2,
+            velocity_offset: 40,
+            swing: 1.0,
+            delay_ticks: -10,
+            delay_ms: 25.0,
+            groove: None,
+            record_filter: None,
+        };
+        assert_eq!(settings.delay(480, 120.0), 14);
+
+        let note = Event::Note(Note {
+            id: Id::new(),
+            ticks: Ticks::new(480 + 240),
+            duration: Ticks::new(240),
+            velocity: Velocity::new(100),
+            note_number: NoteNumber::new(126),
+            track_id: Id::new(),
+            parameters: NoteParameters::new(),
+        });
+
+        let note = settings.apply(note, 480, &GroovePool::default());
+        let note = note.as_note().unwrap();
+        assert_eq!(note.ticks, Ticks::new(480 + 320));
+        assert_eq!(note.note_number, NoteNumber::new(127));
+        assert_eq!(note.velocity, Velocity::new(127));
+
+        let straight = TrackSettings::default();
+        assert_eq!(straight.swing_ticks(Ticks::new(720), 480), Ticks::new(720));
+        assert_eq!(settings.swing_ticks(Ticks::new(480), 480), Ticks::new(480));
+        assert_eq!(settings.swing_ticks(Ticks::new(600), 480), Ticks::new(640));
+    }
+
+    #[test]
+    fn test_pitch_bend_conversion() {
+        let mut track = Track::new(Id::new());
+        assert_eq!(track.bend_value_for_cents(0.0), 8192);
+        assert_eq!(track.bend_value_for_cents(100.0), 12288);
+        assert_eq!(track.bend_value_for_cents(-200.0), 0);
+        assert_eq!(track.bend_value_for_cents(300.0), 16383);
+
+        track.pitch_bend_range = 12;
+        assert_eq!(track.bend_value_for_cents(-600.0), 4096);
+        assert_eq!(track.cents_for_bend_value(4096), -600.0);
+        assert_eq!(track.cents_for_bend_value(8192), 0.0);
+    }
+
+    #[test]
+    fn test_out_of_range_notes() {
+        let track_id = Id::new();
+        let note = |note_number: u8| {
+            Event::Note(Note {
+                id: Id::new(),
+                ticks: Ticks::new(0),
+                duration: Ticks::new(480),
+                velocity: Velocity::new(100),
+                note_number: NoteNumber::new(note_number),
+                track_id,
+                parameters: NoteParameters::new(),
+            })
+        };
+
+        let mut song = Song::new("test".to_string(), 480);
+        song.add_track_with_events(
+            Track::new(track_id),
+            vec![note(54), note(55), note(105), note(106)],
+        )
+        .unwrap();
+        assert!(song.get_out_of_range_notes(&track_id).is_empty());
+
+        // violin: G3 to A7
+        song.update_track(&track_id, |track| {
+            track.pitch_range = Some(PitchRange {
+                low: NoteNumber::new(55),
+                high: NoteNumber::new(105),
+            })
+        });
+        let mut out_of_range: Vec<u8> = song
+            .get_out_of_range_notes(&track_id)
+            .iter()
+            .map(|event| event.as_note().unwrap().note_number.as_u8())
+            .collect();
+        out_of_range.sort();
+        assert_eq!(out_of_range, vec![54, 106]);
     }
 }
@@ -0,0 +1,115 @@
+use super::track::Track;
+use crate::{
+    event::event::Event,
+    shared::{error::StoreError, id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TRIM_INTERFACES: &'static str = r#"
+/** Where a track's events start and end, see `Store.getTrackExtent`. */
+export interface TrackExtent {
+  /** Where the first event starts. */
+  startTicks: number;
+  /** Where the last event ends, taking durations into account. */
+  endTicks: number;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackExtent {
+    pub start_ticks: Ticks,
+    pub end_ticks: Ticks,
+}
+
+impl TrackExtent {
+    /// The extent of the track's events for which `include` holds, or `None` without any.
+    fn of_events(song: &Song, track: &Track, include: impl Fn(&Event) -> bool) -> Option<Self> {
+        song.track_events(track)
+            .iter()
+            .filter(|event| include(event))
+            .map(|event| {
+                let end = event.get_ticks() + event.get_duration().unwrap_or(Ticks::new(0));
+                (event.get_ticks(), end)
+            })
+            .reduce(|(start, end), (event_start, event_end)| {
+                (start.min(event_start), end.max(event_end))
+            })
+            .map(|(start_ticks, end_ticks)| TrackExtent {
+                start_ticks,
+                end_ticks,
+            })
+    }
+
+    pub fn of(song: &Song, track: &Track) -> Option<Self> {
+        Self::of_events(song, track, |_| true)
+    }
+}
+
+/// Whether the event only sets up or resets the instrument, so it adds nothing to a track once
+/// everything else has ended.
+fn is_trimmable(event: &Event) -> bool {
+    matches!(event, Event::ControlChange(_) | Event::RawMidi(_))
+}
+
+/// Removes the controller changes and raw MIDI messages at or after the end of the track's
+/// other events, such as the resets a recording leaves behind, and returns their ids. A track
+/// with nothing else is left alone.
+pub fn trim_track_to_content(song: &mut Song, track_id: Id) -> Result<Vec<Id>, StoreError> {
+    let track = song
+        .get_track(&track_id)
+        .ok_or(StoreError::TrackNotFound(track_id))?;
+    let Some(content) = TrackExtent::of_events(song, track, |event| !is_trimmable(event)) else {
+        return Ok(Vec::new());
+    };
+
+    let mut trailing: Vec<&Event> = song
+        .track_events(track)
+        .iter()
+        .filter(|event| is_trimmable(event) && event.get_ticks() >= content.end_ticks)
+        .collect();
+    trailing.sort_by_key(|event| (event.get_ticks(), event.get_id()));
+    let removed_ids: Vec<Id> = trailing.iter().map(|event| event.get_id()).collect();
+
+    for event_id in &removed_ids {
+        song.remove_event(event_id);
+    }
+    Ok(removed_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_trim_track_to_content() {
+        let (mut song, track_id) = song_with_track();
+        let extent = |song: &Song| TrackExtent::of(song, song.get_track(&track_id).unwrap());
+        let volume = add_control_change(&mut song, track_id, 0, 7, 100);
+        assert_eq!(trim_track_to_content(&mut song, track_id), Ok(Vec::new()));
+        assert!(song.get_event(&volume).is_some());
+
+        add_note(&mut song, track_id, 480, 480, 60, 100);
+        let sustain = add_control_change(&mut song, track_id, 720, 64, 127);
+        let release = add_control_change(&mut song, track_id, 960, 64, 0);
+        let reset = add_control_change(&mut song, track_id, 1920, 121, 0);
+        assert_eq!(
+            extent(&song),
+            Some(TrackExtent {
+                start_ticks: Ticks::new(0),
+                end_ticks: Ticks::new(1920),
+            })
+        );
+
+        let removed = trim_track_to_content(&mut song, track_id).unwrap();
+        assert_eq!(removed, vec![release, reset]);
+        assert!(song.get_event(&sustain).is_some());
+        assert_eq!(extent(&song).unwrap().end_ticks, Ticks::new(960));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    analysis::tempo::{distinct_onsets, estimate_tempo, note_onsets, TempoEstimateOptions},
+    event::{event::Event, note::Note},
+    shared::{id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::{prelude::*, JsValue};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ALIGN_INTERFACES: &'static str = r#"
+export interface GridAlignOptions extends TempoEstimateOptions {
+  /** Steps per detected beat that note starts snap to. Defaults to 4. */
+  subdivision?: number;
+  /** Apply the corrections instead of only proposing them. Defaults to false. */
+  apply?: boolean;
+}
+
+export interface NoteCorrection {
+  id: string;
+  ticks: number;
+  duration: number;
+}
+
+export interface GridAlignment {
+  /** Overall tempo of the recording, which the beat tracking starts from. */
+  bpm: number;
+  /** Beats detected in the recording, in ticks. */
+  beats: number[];
+  corrections: NoteCorrection[];
+}
+"#;
+
+/// How far towards an onset near a predicted beat the beat is pulled, and how much of that
+/// error carries into the next beat's length.
+const PHASE_CORRECTION: f64 = 0.5;
+const PERIOD_CORRECTION: f64 = 0.25;
+
+/// Onsets within this fraction of a beat from a predicted beat steer the tracking.
+const CAPTURE_WINDOW: f64 = 0.25;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GridAlignOptions {
+    #[serde(flatten)]
+    pub tempo: TempoEstimateOptions,
+    pub subdivision: u32,
+    pub apply: bool,
+}
+
+impl Default for GridAlignOptions {
+    fn default() -> Self {
+        GridAlignOptions {
+            tempo: TempoEstimateOptions::default(),
+            subdivision: 4,
+            apply: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteCorrection {
+    pub id: Id,
+    pub ticks: Ticks,
+    pub duration: Ticks,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridAlignment {
+    pub bpm: f64,
+    pub beats: Vec<f64>,
+    pub corrections: Vec<NoteCorrection>,
+}
+
+impl GridAlignment {
+    #[cfg(feature = "wasm")]
+    pub fn to_js_object(&self) -> js_sys::Object {
+        let js_alignment = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &js_alignment,
+            &JsValue::from_str("bpm"),
+            &JsValue::from_f64(self.bpm),
+        )
+        .unwrap();
+
+        js_sys::Reflect::set(
+            &js_alignment,
+            &JsValue::from_str("beats"),
+            &self
+                .beats
+                .iter()
+                .map(|beat| JsValue::from_f64(beat.round()))
+                .collect::<js_sys::Array>(),
+        )
+        .unwrap();
+
+        let corrections = self
+            .corrections
+            .iter()
+            .map(|correction| {
+                let js_correction = js_sys::Object::new();
+                js_sys::Reflect::set(
+                    &js_correction,
+                    &JsValue::from_str("id"),
+                    &JsValue::from_str(correction.id.to_string().as_str()),
+                )
+                .unwrap();
+                js_sys::Reflect::set(
+                    &js_correction,
+                    &JsValue::from_str("ticks"),
+                    &JsValue::from_f64(correction.ticks.as_f64()),
+                )
+                .unwrap();
+                js_sys::Reflect::set(
+                    &js_correction,
+                    &JsValue::from_str("duration"),
+                    &JsValue::from_f64(correction.duration.as_f64()),
+                )
+                .unwrap();
+                js_correction
+            })
+            .collect::<js_sys::Array>();
+        js_sys::Reflect::set(
+            &js_alignment,
+            &JsValue::from_str("corrections"),
+            &corrections,
+        )
+        .unwrap();
+
+        js_alignment
+    }
+}
+
+/// Follows the beat from `first_beat`, letting onsets near each predicted beat pull it and
+/// adjust the tempo, until one beat past the last onset.
+fn track_beats(onsets: &[f64], first_beat: f64, period: f64) -> Vec<f64> {
+    let last_onset = onsets.last().copied().unwrap_or(first_beat);
+    let mut beats = vec![first_beat];
+    let mut current_period = period;
+
+    while beats.len() < 2 || *beats.last().unwrap() <= last_onset {
+        let predicted = beats.last().unwrap() + current_period;
+        let window = current_period * CAPTURE_WINDOW;
+        let error = onsets
+            .iter()
+            .map(|onset| onset - predicted)
+            .filter(|error| error.abs() <= window)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+
+        let beat = match error {
+            Some(error) => {
+                current_period =
+                    (current_period + PERIOD_CORRECTION * error).clamp(period / 2.0, period * 2.0);
+                predicted + PHASE_CORRECTION * error
+            }
+            None => predicted,
+        };
+        beats.push(beat);
+    }
+
+    beats
+}
+
+/// Position of `ticks` in beats from the first detected beat, extrapolating past either end.
+fn beat_position(beats: &[f64], ticks: f64) -> f64 {
+    let index = beats
+        .partition_point(|beat| *beat <= ticks)
+        .clamp(1, beats.len() - 1);
+    let (start, end) = (beats[index - 1], beats[index]);
+    (index - 1) as f64 + (ticks - start) / (end - start)
+}
+
+/// Maps a rubato recording onto the song's beat grid.
+///
+/// The recording's beat is tracked from its estimated tempo, then each detected beat is moved
+/// onto a song beat, starting from the one nearest the first. Note starts snap to
+/// `subdivision` steps within their beat and note ends follow the beat without snapping.
+/// `strength`, from 0 to 1, sets how far notes move towards their aligned position. Returns
+/// `None` when the notes have fewer than two distinct onsets.
+pub fn align_to_grid(
+    song: &mut Song,
+    event_ids: &[Id],
+    strength: f64,
+    options: &GridAlignOptions,
+) -> Option<GridAlignment> {
+    let mut notes: Vec<Note> = event_ids
+        .iter()
+        .filter_map(|id| match song.get_event(id) {
+            Some(Event::Note(note)) => Some(note.clone()),
+            _ => None,
+        })
+        .collect();
+    notes.sort_by_key(|note| (note.ticks, note.id));
+
+    let ppq = song.ppq as f64;
+    let onsets = note_onsets(event_ids.iter().filter_map(|id| song.get_event(id)));
+    let estimate = estimate_tempo(&onsets, song.ppq, &options.tempo)?;
+    let onsets = distinct_onsets(&onsets, song.ppq, options.tempo.bpm);
+
+    let period = ppq * options.tempo.bpm / estimate.bpm;
+    let first_beat =
+        estimate.offset_ticks + ((onsets[0] - estimate.offset_ticks) / period).round() * period;
+    let beats = track_beats(&onsets, first_beat, period);
+
+    let anchor = (first_beat / ppq).round() * ppq;
+    let subdivision = options.subdivision.max(1) as f64;
+    let strength = strength.clamp(0.0, 1.0);
+    let mut corrections = Vec::new();
+
+    for note in &notes {
+        let start = note.ticks.as_f64();
+        let end = start + note.duration.as_f64();
+
+        let position = (beat_position(&beats, start) * subdivision).round() / subdivision;
+        let aligned_start = anchor + position * ppq;
+        let aligned_end = anchor + beat_position(&beats, end) * ppq;
+
+        let ticks = (start + strength * (aligned_start - start))
+            .round()
+            .max(0.0) as u64;
+        let end = (end + strength * (aligned_end - end)).round().max(0.0) as u64;
+
+        corrections.push(NoteCorrection {
+            id: note.id,
+            ticks: Ticks::new(ticks),
+            duration: Ticks::new(end.saturating_sub(ticks).max(1)),
+        });
+    }
+
+    if options.apply {
+        for (note, correction) in notes.into_iter().zip(&corrections) {
+            if note.ticks != correction.ticks || note.duration != correction.duration {
+                song.replace_event(Event::Note(Note {
+                    ticks: correction.ticks,
+                    duration: correction.duration,
+                    ..note
+                }))
+                .expect("The note is in the song");
+            }
+        }
+    }
+
+    Some(GridAlignment {
+        bpm: estimate.bpm,
+        beats,
+        corrections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_align_to_grid() {
+        let (mut song, track_id) = song_with_track();
+        // Quarters slowing down from about 100 BPM, with an eighth after the second beat.
+        let mut ticks = 20;
+        let mut ids = Vec::new();
+        for interval in [576, 582, 590, 600, 612, 626, 640, 656] {
+            ids.push(add_note(&mut song, track_id, ticks, interval / 2, 60, 100));
+            ticks += interval;
+        }
+        let eighth = add_note(&mut song, track_id, 20 + 576 + 291, 100, 62, 100);
+        ids.push(eighth);
+
+        let proposal = align_to_grid(&mut song, &ids, 1.0, &GridAlignOptions::default()).unwrap();
+        assert!((90.0..=100.0).contains(&proposal.bpm), "{}", proposal.bpm);
+
+        let starts: Vec<u64> = proposal
+            .corrections
+            .iter()
+            .map(|correction| correction.ticks.as_u64())
+            .collect();
+        assert_eq!(starts, vec![0, 480, 720, 960, 1440, 1920, 2400, 2880, 3360]);
+        // Proposing leaves the notes alone.
+        let note = song.get_event(&ids[1]).unwrap().as_note().unwrap();
+        assert_eq!(note.ticks, Ticks::new(596));
+
+        let options = GridAlignOptions {
+            apply: true,
+            ..GridAlignOptions::default()
+        };
+        align_to_grid(&mut song, &ids, 0.5, &options).unwrap();
+        let note = song.get_event(&ids[1]).unwrap().as_note().unwrap();
+        assert_eq!(note.ticks, Ticks::new(538));
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    event::{
+        control_change::{ControlChange, ControllerValue},
+        event::{Event, EventUpdater},
+        note::{Note, NoteNumber, Velocity},
+    },
+    shared::{error::StoreError, id::Id, meta::Meta, unit::time::Ticks},
+    song::song::Song,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_BULK_INTERFACES: &'static str = r#"
+export interface EventTransform {
+  /** Semitones added to note numbers. */
+  transpose?: number;
+  /** Multiplies note velocities, before `velocityOffset` is added. Defaults to 1. */
+  velocityScale?: number;
+  velocityOffset?: number;
+  /** Ticks added to event positions; negative values move events earlier. */
+  ticksOffset?: number;
+}
+
+/**
+ * Changes added to each event's own values, so events keep their differences. Fields that do
+ * not apply to an event's kind are ignored.
+ */
+export interface RelativeUpdater {
+  deltaTicks?: number;
+  deltaDuration?: number;
+  deltaVelocity?: number;
+  deltaNoteNumber?: number;
+  /** Added to control change values. */
+  deltaValue?: number;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventTransform {
+    pub transpose: i32,
+    pub velocity_scale: f64,
+    pub velocity_offset: i32,
+    pub ticks_offset: i64,
+}
+
+impl Default for EventTransform {
+    fn default() -> Self {
+        EventTransform {
+            transpose: 0,
+            velocity_scale: 1.0,
+            velocity_offset: 0,
+            ticks_offset: 0,
+        }
+    }
+}
+
+impl EventTransform {
+    fn apply(&self, event: Event) -> Event {
+        let ticks = (event.get_ticks().as_u64() as i64 + self.ticks_offset)
+            .clamp(0, Ticks::MAX.as_u64() as i64);
+
+        match event.with_ticks(Ticks::new(ticks as u64)) {
+            Event::Note(note) => {
+                let note_number = (note.note_number.as_u8() as i32 + self.transpose).clamp(0, 127);
+                let velocity = (note.velocity.as_u8() as f64 * self.velocity_scale).round() as i32
+                    + self.velocity_offset;
+
+                Event::Note(Note {
+                    note_number: NoteNumber::new(note_number as u8),
+                    velocity: Velocity::new(velocity.clamp(1, 127) as u8),
+                    ..note
+                })
+            }
+            event => event,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RelativeUpdater {
+    pub delta_ticks: i64,
+    pub delta_duration: i64,
+    pub delta_velocity: i32,
+    pub delta_note_number: i32,
+    pub delta_value: i32,
+}
+
+impl RelativeUpdater {
+    fn apply(&self, event: Event) -> Event {
+        let ticks = (event.get_ticks().as_u64() as i64 + self.delta_ticks)
+            .clamp(0, Ticks::MAX.as_u64() as i64);
+        let event = event.with_ticks(Ticks::new(ticks as u64));
+        let event = match event.get_duration() {
+            Some(duration) => {
+                let duration = (duration.as_u64() as i64 + self.delta_duration)
+                    .clamp(1, Ticks::MAX.as_u64() as i64);
+                event.with_duration(Ticks::new(duration as u64))
+            }
+            None => event,
+        };
+
+        match event {
+            Event::Note(note) => {
+                let note_number =
+                    (note.note_number.as_u8() as i32 + self.delta_note_number).clamp(0, 127);
+                let velocity = (note.velocity.as_u8() as i32 + self.delta_velocity).clamp(1, 127);
+
+                Event::Note(Note {
+                    note_number: NoteNumber::new(note_number as u8),
+                    velocity: Velocity::new(velocity as u8),
+                    ..note
+                })
+            }
+            Event::ControlChange(cc) => {
+                let value = (cc.value.as_u8() as i32 + self.delta_value).clamp(0, 127);
+
+                Event::ControlChange(ControlChange {
+                    value: ControllerValue::new(value as u8),
+                    ..cc
+                })
+            }
+            event => event,
+        }
+    }
+}
+
+/// Applies `transform` to every event in `event_ids`, replacing each event once.
+///
+/// Positions are kept at or after the song start, note numbers within 0 to 127 and velocities
+/// within 1 to 127, so no note turns into a note-off. Transposition and velocity only apply to
+/// notes. Returns the events in `event_ids` order.
+pub fn transform_events(
+    song: &mut Song,
+    event_ids: &[Id],
+    transform: &EventTransform,
+) -> Result<Vec<Event>, StoreError> {
+    song.try_get_events(event_ids)?;
+
+    event_ids
+        .iter()
+        .map(|event_id| {
+            let event = song.get_event(event_id).unwrap().clone();
+            song.replace_event(transform.apply(event)).cloned()
+        })
+        .collect()
+}
+
+/// Adds `updater`'s deltas to every event in `event_ids`, replacing each event once.
+///
+/// Results are clamped like `transform_events`; durations stay at least one tick and control
+/// change values within 0 to 127. Returns the events in `event_ids` order.
+pub fn update_events_relative(
+    song: &mut Song,
+    event_ids: &[Id],
+    updater: &RelativeUpdater,
+) -> Result<Vec<Event>, StoreError> {
+    song.try_get_events(event_ids)?;
+
+    event_ids
+        .iter()
+        .map(|event_id| {
+            let event = song.get_event(event_id).unwrap().clone();
+            song.replace_event(updater.apply(event)).cloned()
+        })
+        .collect()
+}
+
+/// Adds the events, setting the meta given with each, and returns their ids in order. Every
+/// event is checked first, so an id the song holds or given twice adds none of them.
+pub fn add_events(
+    song: &mut Song,
+    events: Vec<(Event, Option<Meta>)>,
+) -> Result<Vec<Id>, StoreError> {
+    let mut seen = HashSet::new();
+    for (event, _) in &events {
+        song.check_new_event(event)?;
+        if !seen.insert(event.get_id()) {
+            return Err(StoreError::invalid(format!(
+                "Event {} is given twice",
+                event.get_id()
+            )));
+        }
+    }
+
+    let mut event_ids = Vec::new();
+    for (event, meta) in events {
+        let event_id = event.get_id();
+        song.add_event(event)?;
+        if let Some(meta) = meta {
+            song.set_event_meta(event_id, meta);
+        }
+        event_ids.push(event_id);
+    }
+    Ok(event_ids)
+}
+
+/// Applies the updaters in order, setting the meta given with each, and returns the ids they
+/// updated. Every updater is checked first, so a missing event or an updater of the wrong kind
+/// fails the call before anything changes.
+pub fn update_events(
+    song: &mut Song,
+    updaters: Vec<(EventUpdater, Option<Meta>)>,
+) -> Result<Vec<Id>, StoreError> {
+    for (updater, _) in &updaters {
+        let event = song
+            .get_event(&updater.get_id())
+            .ok_or(StoreError::EventNotFound(updater.get_id()))?;
+        if !event.accepts(updater) {
+            return Err(StoreError::invalid("Updater kind does not match the event"));
+        }
+    }
+
+    let mut event_ids = Vec::new();
+    for (updater, meta) in updaters {
+        let event_id = updater.get_id();
+        song.update_event(updater)?;
+        if let Some(meta) = meta {
+            song.set_event_meta(event_id, meta);
+        }
+        event_ids.push(event_id);
+    }
+    Ok(event_ids)
+}
+
+/// Removes each event once, however often its id is given, along with the rest of its group
+/// with `whole_groups`. Fails before removing anything when an event does not exist.
+pub fn remove_events(
+    song: &mut Song,
+    event_ids: &[Id],
+    whole_groups: bool,
+) -> Result<Vec<Id>, StoreError> {
+    let mut seen = HashSet::new();
+    let mut event_ids: Vec<Id> = event_ids
+        .iter()
+        .copied()
+        .filter(|event_id| seen.insert(*event_id))
+        .collect();
+    song.try_get_events(&event_ids)?;
+    if whole_groups {
+        event_ids = song.groups.expand(song, &event_ids);
+    }
+
+    for event_id in &event_ids {
+        song.remove_event(event_id);
+    }
+    Ok(event_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::note::NoteUpdater,
+        history::history::History,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    fn velocity_updater(id: Id, velocity: u8) -> (EventUpdater, Option<Meta>) {
+        let updater = NoteUpdater {
+            id,
+            ticks: None,
+            duration: None,
+            velocity: Some(Velocity::new(velocity)),
+            note_number: None,
+            track_id: None,
+            parameters: None,
+        };
+        (EventUpdater::Note(updater), None)
+    }
+
+    #[test]
+    fn test_bulk_calls_apply_nothing_on_failure() {
+        let (mut song, track_id) = song_with_track();
+        let note = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let cc = add_control_change(&mut song, track_id, 0, 7, 100);
+        song.take_changes();
+
+        let missing = Id::new();
+        let result = update_events(
+            &mut song,
+            vec![velocity_updater(note, 50), velocity_updater(missing, 50)],
+        );
+        assert_eq!(result, Err(StoreError::EventNotFound(missing)));
+        let result = update_events(
+            &mut song,
+            vec![velocity_updater(note, 50), velocity_updater(cc, 50)],
+        );
+        assert!(matches!(result, Err(StoreError::ValidationFailed(_))));
+        assert_eq!(
+            song.get_event(&note).unwrap().as_note().unwrap().velocity,
+            Velocity::new(100)
+        );
+
+        let result = remove_events(&mut song, &[note, missing], false);
+        assert_eq!(result, Err(StoreError::EventNotFound(missing)));
+        assert!(song.get_event(&note).is_some());
+        assert!(song.take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_add_events_rejects_known_ids() {
+        let (mut song, track_id) = song_with_track();
+        let existing = add_note(&mut song, track_id, 0, 480, 60, 100);
+        song.take_changes();
+        let note = |ticks| {
+            song.get_event(&existing)
+                .unwrap()
+                .clone()
+                .with_ticks(Ticks::new(ticks))
+        };
+        let fresh = note(480).with_id(Id::new());
+        let taken = note(960);
+
+        let result = add_events(&mut song, vec![(fresh.clone(), None), (taken, None)]);
+        assert!(matches!(result, Err(StoreError::ValidationFailed(_))));
+        let result = add_events(
+            &mut song,
+            vec![(fresh.clone(), None), (fresh.clone(), None)],
+        );
+        assert!(matches!(result, Err(StoreError::ValidationFailed(_))));
+        assert!(song.take_changes().is_empty());
+
+        // the event the song holds keeps its one place in its track
+        assert_eq!(song.get_events(None).len(), 1);
+        assert_eq!(
+            song.get_event(&existing).unwrap().get_ticks(),
+            Ticks::new(0)
+        );
+        assert_eq!(song.get_track_events(&track_id).len(), 1);
+    }
+
+    #[test]
+    fn test_remove_events_with_repeated_ids() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let second = add_note(&mut song, track_id, 480, 480, 62, 100);
+        let kept = add_note(&mut song, track_id, 960, 480, 64, 100);
+
+        let removed = remove_events(&mut song, &[first, second, first], false).unwrap();
+        assert_eq!(removed, vec![first, second]);
+        assert_eq!(song.get_events(None).len(), 1);
+        assert!(song.get_event(&kept).is_some());
+    }
+
+    #[test]
+    fn test_bulk_call_undoes_as_one_step() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let second = add_note(&mut song, track_id, 480, 480, 62, 100);
+        song.take_changes();
+        let mut history = History::default();
+
+        update_events(
+            &mut song,
+            vec![velocity_updater(first, 20), velocity_updater(second, 30)],
+        )
+        .unwrap();
+        history.record(&song.take_changes());
+        remove_events(&mut song, &[first, second], false).unwrap();
+        history.record(&song.take_changes());
+        let mut song = Some(song);
+
+        history.undo(&mut song).unwrap();
+        let velocities = |song: &Option<Song>| {
+            [first, second].map(|id| {
+                let song = song.as_ref().unwrap();
+                song.get_event(&id).unwrap().as_note().unwrap().velocity
+            })
+        };
+        assert_eq!(velocities(&song), [Velocity::new(20), Velocity::new(30)]);
+        history.undo(&mut song).unwrap();
+        assert_eq!(velocities(&song), [Velocity::new(100), Velocity::new(100)]);
+    }
+
+    #[test]
+    fn test_transform_events() {
+        let (mut song, track_id) = song_with_track();
+        let note = add_note(&mut song, track_id, 480, 240, 60, 100);
+        let high = add_note(&mut song, track_id, 60, 240, 120, 10);
+        let cc = add_control_change(&mut song, track_id, 480, 7, 100);
+
+        let transform = EventTransform {
+            transpose: 12,
+            velocity_scale: 1.5,
+            velocity_offset: -20,
+            ticks_offset: -120,
+        };
+        let events = transform_events(&mut song, &[note, high, cc], &transform).unwrap();
+
+        let note = events[0].as_note().unwrap();
+        assert_eq!(note.ticks, Ticks::new(360));
+        assert_eq!(note.note_number, NoteNumber::new(72));
+        assert_eq!(note.velocity, Velocity::new(127));
+        let high = events[1].as_note().unwrap();
+        assert_eq!(high.ticks, Ticks::new(0));
+        assert_eq!(high.note_number, NoteNumber::new(127));
+        assert_eq!(high.velocity, Velocity::new(1));
+        assert_eq!(events[2].get_ticks(), Ticks::new(360));
+
+        assert_eq!(
+            song.get_track_events_in_ticks_range(&track_id, Ticks::new(0), Ticks::new(1), false)[0]
+                .get_id(),
+            high.id
+        );
+    }
+
+    #[test]
+    fn test_update_events_relative() {
+        let (mut song, track_id) = song_with_track();
+        let soft = add_note(&mut song, track_id, 0, 240, 60, 40);
+        let loud = add_note(&mut song, track_id, 480, 60, 64, 90);
+        let cc = add_control_change(&mut song, track_id, 240, 1, 120);
+
+        let updater = RelativeUpdater {
+            delta_ticks: 120,
+            delta_duration: -120,
+            delta_velocity: -10,
+            delta_value: 10,
+            ..Default::default()
+        };
+        let events = update_events_relative(&mut song, &[soft, loud, cc], &updater).unwrap();
+
+        let soft = events[0].as_note().unwrap();
+        assert_eq!(
+            (soft.ticks, soft.duration),
+            (Ticks::new(120), Ticks::new(120))
+        );
+        assert_eq!(soft.velocity, Velocity::new(30));
+        let loud = events[1].as_note().unwrap();
+        assert_eq!(
+            (loud.ticks, loud.duration),
+            (Ticks::new(600), Ticks::new(1))
+        );
+        assert_eq!(loud.velocity, Velocity::new(80));
+        let Event::ControlChange(cc) = &events[2] else {
+            unreachable!();
+        };
+        assert_eq!(cc.ticks, Ticks::new(360));
+        assert_eq!(cc.value, ControllerValue::new(127));
+    }
+}
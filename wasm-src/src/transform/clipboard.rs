@@ -0,0 +1,151 @@
+use crate::{
+    event::event::Event,
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        unit::time::Ticks,
+    },
+    song::song::Song,
+};
+
+/// Events copied out of the song, with their ticks relative to the earliest of them.
+#[derive(Debug, Clone, Default)]
+pub struct Clipboard {
+    events: Vec<Event>,
+}
+
+impl Clipboard {
+    /// Copies `event_ids`, which must all exist, in ticks order.
+    pub fn copy(song: &Song, event_ids: &[Id]) -> Result<Self, StoreError> {
+        let events = song
+            .try_get_events(event_ids)?
+            .into_iter()
+            .cloned()
+            .collect();
+        Ok(Clipboard::from_events(events))
+    }
+
+    /// Holds `events`, moved so that the earliest is at tick 0.
+    pub fn from_events(mut events: Vec<Event>) -> Self {
+        events.sort_by_key(|event| (event.get_ticks(), event.get_id()));
+
+        let start = events
+            .first()
+            .map_or(Ticks::new(0), |event| event.get_ticks());
+        let events = events
+            .into_iter()
+            .map(|event| {
+                let ticks = event.get_ticks() - start;
+                event.with_ticks(ticks)
+            })
+            .collect();
+        Clipboard { events }
+    }
+
+    pub fn get_events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Copies `event_ids` and removes them from the song.
+    pub fn cut(song: &mut Song, event_ids: &[Id]) -> Result<Self, StoreError> {
+        let clipboard = Clipboard::copy(song, event_ids)?;
+        for event_id in event_ids {
+            if song.get_event(event_id).is_some() {
+                song.remove_event(event_id);
+            }
+        }
+        Ok(clipboard)
+    }
+
+    /// Adds copies of the clipboard's events with fresh ids, the earliest at `at_ticks`.
+    ///
+    /// Events go to `target_track_id` when given, otherwise back to the tracks they were copied
+    /// from; markers stay on the song. Nothing is added if one of those tracks no longer exists.
+    /// Returns the new events.
+    pub fn paste(
+        &self,
+        song: &mut Song,
+        ids: &mut IdStrategy,
+        at_ticks: Ticks,
+        target_track_id: Option<Id>,
+    ) -> Result<Vec<Event>, StoreError> {
+        let missing_track = self
+            .events
+            .iter()
+            .filter_map(|event| target_track_id.or(event.get_track_id()))
+            .find(|track_id| song.get_track(track_id).is_none());
+        if let Some(track_id) = missing_track {
+            return Err(StoreError::TrackNotFound(track_id));
+        }
+
+        self.events
+            .iter()
+            .map(|event| {
+                let mut copy = event
+                    .clone()
+                    .with_id(ids.next_id()?)
+                    .with_ticks(at_ticks + event.get_ticks());
+                if let Some(track_id) = target_track_id {
+                    copy = copy.with_track_id(track_id);
+                }
+                song.add_event(copy).cloned()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        track::track::Track,
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_cut_and_paste() {
+        let (mut song, track_id) = song_with_track();
+        let note = add_note(&mut song, track_id, 480, 240, 60, 100);
+        let cc = add_control_change(&mut song, track_id, 600, 1, 64);
+
+        let clipboard = Clipboard::cut(&mut song, &[cc, note]).unwrap();
+        assert!(song.get_event(&note).is_none());
+
+        let pasted = clipboard
+            .paste(
+                &mut song,
+                &mut IdStrategy::default(),
+                Ticks::new(1920),
+                None,
+            )
+            .unwrap();
+        assert_eq!(pasted.len(), 2);
+        assert_ne!(pasted[0].get_id(), note);
+        assert_eq!(pasted[0].get_ticks(), Ticks::new(1920));
+        assert_eq!(pasted[0].get_duration(), Some(Ticks::new(240)));
+        assert_eq!(pasted[1].get_ticks(), Ticks::new(2040));
+
+        let other_track_id = Id::new();
+        song.add_track(Track::new(other_track_id));
+        let pasted = clipboard
+            .paste(
+                &mut song,
+                &mut IdStrategy::default(),
+                Ticks::new(0),
+                Some(other_track_id),
+            )
+            .unwrap();
+        assert!(pasted
+            .iter()
+            .all(|event| event.get_track_id() == Some(other_track_id)));
+        assert_eq!(song.get_track_events(&other_track_id).len(), 2);
+
+        song.remove_track(&track_id);
+        assert_eq!(
+            clipboard
+                .paste(&mut song, &mut IdStrategy::default(), Ticks::new(0), None)
+                .unwrap_err(),
+            StoreError::TrackNotFound(track_id)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{
+    event::{
+        control_change::{ControlChange, ControllerNumber, ControllerValue},
+        event::Event,
+    },
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        js::string_enum,
+        unit::time::Ticks,
+    },
+    song::song::Song,
+};
+use serde::Deserialize;
+use std::f64::consts::PI;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CURVE_INTERFACES: &'static str = r#"
+export interface CurveLane {
+  trackId: string;
+  controller: number;
+}
+
+export interface CurvePoint {
+  ticks: number;
+  value: number;
+}
+"#;
+
+string_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CurveShape {
+        Ramp = "ramp",
+        Sine = "sine",
+        Saw = "saw",
+        SCurve = "sCurve",
+    }
+}
+
+impl CurveShape {
+    /// The curve's position between the start value (0) and the end value (1) at `position`
+    /// through the curve, `cycle` being the length of one period of a periodic shape.
+    fn level_at(&self, position: f64, cycle: f64) -> f64 {
+        match self {
+            CurveShape::Sine => (1.0 - (2.0 * PI * position / cycle).cos()) / 2.0,
+            CurveShape::Saw => (position / cycle).fract(),
+            CurveShape::SCurve => (1.0 - (PI * position).cos()) / 2.0,
+            CurveShape::Ramp => position,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurveLane {
+    pub track_id: Id,
+    pub controller: u8,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CurvePoint {
+    pub ticks: Ticks,
+    pub value: u8,
+}
+
+/// Draws control changes from `start` to `end` every `resolution` ticks, replacing the lane's
+/// events in that span, and returns the added events.
+///
+/// Ramps and S-curves move from the start value to the end value. Sines and saws swing between
+/// the two values, starting at the start value, once per `period` ticks or once across the span
+/// when `period` is `None`. A point that repeats the previous value is left out, except the last.
+pub fn insert_curve(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    lane: CurveLane,
+    (start, end): (CurvePoint, CurvePoint),
+    shape: CurveShape,
+    resolution: Ticks,
+    period: Option<Ticks>,
+) -> Result<Vec<Event>, StoreError> {
+    let controller = ControllerNumber::new(lane.controller);
+    let (start, end) = if start.ticks <= end.ticks {
+        (start, end)
+    } else {
+        (end, start)
+    };
+
+    let replaced: Vec<Id> = song
+        .track_events(
+            song.get_track(&lane.track_id)
+                .ok_or(StoreError::TrackNotFound(lane.track_id))?,
+        )
+        .in_ticks_range(start.ticks, end.ticks + Ticks::new(1), false)
+        .into_iter()
+        .filter(|event| match event {
+            Event::ControlChange(cc) => cc.controller == controller,
+            _ => false,
+        })
+        .map(|event| event.get_id())
+        .collect();
+    for event_id in &replaced {
+        song.remove_event(event_id);
+    }
+
+    let span = (end.ticks - start.ticks).as_u64();
+    let step = resolution.as_u64().max(1);
+    let cycle = period.map_or(span, |period| period.as_u64()).max(1) as f64;
+    let (start_value, end_value) = (start.value as f64, end.value as f64);
+
+    let mut offsets: Vec<u64> = (0..span).step_by(step as usize).collect();
+    offsets.push(span);
+
+    let mut events = Vec::new();
+    let mut previous_value = None;
+
+    for (index, offset) in offsets.iter().enumerate() {
+        let position = if span > 0 {
+            *offset as f64 / span as f64
+        } else {
+            1.0
+        };
+        let level = match shape {
+            CurveShape::Sine | CurveShape::Saw => shape.level_at(*offset as f64, cycle),
+            CurveShape::Ramp | CurveShape::SCurve => shape.level_at(position, cycle),
+        };
+        let value = (start_value + (end_value - start_value) * level)
+            .round()
+            .clamp(0.0, 127.0) as u8;
+
+        if previous_value == Some(value) && index < offsets.len() - 1 {
+            continue;
+        }
+        previous_value = Some(value);
+
+        let event = Event::ControlChange(ControlChange {
+            id: ids.next_id()?,
+            ticks: start.ticks + Ticks::new(*offset),
+            controller,
+            value: ControllerValue::new(value),
+            track_id: lane.track_id,
+        });
+        events.push(song.add_event(event)?.clone());
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, song_with_track};
+
+    fn points(events: &[Event]) -> Vec<(u64, u8)> {
+        events
+            .iter()
+            .map(|event| match event {
+                Event::ControlChange(cc) => (cc.ticks.as_u64(), cc.value.as_u8()),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_curve() {
+        let (mut song, track_id) = song_with_track();
+        let replaced = add_control_change(&mut song, track_id, 240, 1, 10);
+        let other = add_control_change(&mut song, track_id, 240, 7, 10);
+        let lane = CurveLane {
+            track_id,
+            controller: 1,
+        };
+        let point = |ticks: u64, value: u8| CurvePoint {
+            ticks: Ticks::new(ticks),
+            value,
+        };
+
+        let ramp = insert_curve(
+            &mut song,
+            &mut IdStrategy::default(),
+            lane,
+            (point(0, 0), point(480, 100)),
+            CurveShape::Ramp,
+            Ticks::new(120),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            points(&ramp),
+            vec![(0, 0), (120, 25), (240, 50), (360, 75), (480, 100)]
+        );
+        assert!(song.get_event(&replaced).is_none());
+        assert!(song.get_event(&other).is_some());
+
+        let s_curve = insert_curve(
+            &mut song,
+            &mut IdStrategy::default(),
+            lane,
+            (point(960, 0), point(1920, 100)),
+            CurveShape::SCurve,
+            Ticks::new(240),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            points(&s_curve),
+            vec![(960, 0), (1200, 15), (1440, 50), (1680, 85), (1920, 100)]
+        );
+
+        // Two cycles of a sine between 20 and 60, replacing the S-curve.
+        let sine = insert_curve(
+            &mut song,
+            &mut IdStrategy::default(),
+            lane,
+            (point(960, 20), point(1920, 60)),
+            CurveShape::Sine,
+            Ticks::new(120),
+            Some(Ticks::new(480)),
+        )
+        .unwrap();
+        assert_eq!(
+            points(&sine),
+            vec![
+                (960, 20),
+                (1080, 40),
+                (1200, 60),
+                (1320, 40),
+                (1440, 20),
+                (1560, 40),
+                (1680, 60),
+                (1800, 40),
+                (1920, 20),
+            ]
+        );
+
+        let saw = insert_curve(
+            &mut song,
+            &mut IdStrategy::default(),
+            lane,
+            (point(2400, 0), point(2880, 120)),
+            CurveShape::Saw,
+            Ticks::new(60),
+            Some(Ticks::new(240)),
+        )
+        .unwrap();
+        assert_eq!(
+            points(&saw),
+            vec![
+                (2400, 0),
+                (2460, 30),
+                (2520, 60),
+                (2580, 90),
+                (2640, 0),
+                (2700, 30),
+                (2760, 60),
+                (2820, 90),
+                (2880, 0),
+            ]
+        );
+        assert_eq!(song.get_track_events(&track_id).len(), 24);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    event::{event::Event, note::NoteNumber},
+    shared::{error::StoreError, id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+use std::collections::{HashMap, HashSet};
+
+pub enum DedupeScope {
+    Track(Id),
+    Events(Vec<Id>),
+}
+
+/// Removes notes that repeat the pitch of an earlier note on the same track starting at most
+/// `tolerance` ticks before them, and returns the ids of the removed notes.
+pub fn dedupe_notes(
+    song: &mut Song,
+    scope: DedupeScope,
+    tolerance: Ticks,
+) -> Result<Vec<Id>, StoreError> {
+    let mut candidates: Vec<Event> = match scope {
+        DedupeScope::Track(track_id) => song
+            .track_events(
+                song.get_track(&track_id)
+                    .ok_or(StoreError::TrackNotFound(track_id))?,
+            )
+            .iter()
+            .cloned()
+            .collect(),
+        DedupeScope::Events(mut event_ids) => {
+            // an id given twice must not count as its own duplicate
+            let mut seen = HashSet::new();
+            event_ids.retain(|event_id| seen.insert(*event_id));
+            song.try_get_events(&event_ids)?
+                .into_iter()
+                .cloned()
+                .collect()
+        }
+    };
+    candidates.sort_by_key(|event| event.get_ticks());
+
+    let mut kept_starts: HashMap<(Id, NoteNumber), Ticks> = HashMap::new();
+    let mut removed_ids = Vec::new();
+
+    for event in candidates {
+        let Some(note) = event.as_note() else {
+            continue;
+        };
+        let key = (note.track_id, note.note_number);
+
+        match kept_starts.get(&key) {
+            Some(kept_ticks) if note.ticks - *kept_ticks <= tolerance => {
+                removed_ids.push(note.id);
+            }
+            _ => {
+                kept_starts.insert(key, note.ticks);
+            }
+        }
+    }
+
+    for event_id in &removed_ids {
+        song.remove_event(event_id);
+    }
+
+    Ok(removed_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_dedupe_notes_in_track() {
+        let (mut song, track_id) = song_with_track();
+
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 0, 480, 60, 90);
+        add_note(&mut song, track_id, 5, 480, 60, 90);
+        let other_pitch = add_note(&mut song, track_id, 0, 480, 64, 100);
+        let later = add_note(&mut song, track_id, 480, 480, 60, 100);
+
+        let removed =
+            dedupe_notes(&mut song, DedupeScope::Track(track_id), Ticks::new(10)).unwrap();
+
+        assert_eq!(removed.len(), 2);
+        assert!(song.get_event(&other_pitch).is_some());
+        assert!(song.get_event(&later).is_some());
+        assert_eq!(song.get_events(None).len(), 3);
+        assert_eq!(song.get_track_events(&track_id).len(), 3);
+    }
+
+    #[test]
+    fn test_dedupe_notes_within_selection() {
+        let (mut song, track_id) = song_with_track();
+
+        let id1 = add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        let id3 = add_note(&mut song, track_id, 0, 480, 60, 100);
+
+        let removed = dedupe_notes(
+            &mut song,
+            DedupeScope::Events(vec![id1, id3]),
+            Ticks::new(0),
+        )
+        .unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(song.get_events(None).len(), 2);
+    }
+
+    #[test]
+    fn test_dedupe_notes_with_repeated_id() {
+        let (mut song, track_id) = song_with_track();
+
+        let unique = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let other = add_note(&mut song, track_id, 480, 480, 60, 100);
+
+        let removed = dedupe_notes(
+            &mut song,
+            DedupeScope::Events(vec![unique, other, unique]),
+            Ticks::new(0),
+        )
+        .unwrap();
+
+        assert!(removed.is_empty());
+        assert!(song.get_event(&unique).is_some());
+        assert_eq!(song.get_events(None).len(), 2);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    event::{event::Event, note::Note},
+    query::preview::{preview_transform, TransformDelta},
+    shared::{error::StoreError, id::Id, unit::grid::Grid},
+    song::song::Song,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DRAG_INTERFACES: &'static str = r#"
+export interface MoveConstraints {
+  /** Keep note numbers, ignoring `deltaNotes`. */
+  lockPitch?: boolean;
+  /** Keep positions, ignoring `deltaTicks` and any grid. */
+  lockTime?: boolean;
+  /** Snaps the earliest event to this grid, in place of the delta's own `grid`. */
+  snapGrid?: Grid | null;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MoveConstraints {
+    pub lock_pitch: bool,
+    pub lock_time: bool,
+    pub snap_grid: Option<Grid>,
+}
+
+impl MoveConstraints {
+    fn constrain(&self, delta: TransformDelta) -> TransformDelta {
+        let delta_notes = match self.lock_pitch {
+            true => 0,
+            false => delta.delta_notes,
+        };
+        match self.lock_time {
+            true => TransformDelta {
+                delta_ticks: 0,
+                delta_notes,
+                grid: None,
+            },
+            false => TransformDelta {
+                delta_ticks: delta.delta_ticks,
+                delta_notes,
+                grid: self.snap_grid.or(delta.grid),
+            },
+        }
+    }
+}
+
+/// Moves `event_ids` as a block by `delta` within `constraints`, landing where
+/// `preview_transform` says they would. Returns the moved events in `event_ids` order.
+pub fn move_events(
+    song: &mut Song,
+    event_ids: &[Id],
+    delta: TransformDelta,
+    constraints: &MoveConstraints,
+) -> Result<Vec<Event>, StoreError> {
+    let preview = preview_transform(song, event_ids, constraints.constrain(delta))?;
+
+    preview
+        .events
+        .into_iter()
+        .map(|moved| {
+            let event = song.get_event(&moved.id).unwrap().clone();
+            let event = match (event, moved.note_number) {
+                (Event::Note(note), Some(note_number)) => Event::Note(Note {
+                    note_number,
+                    ..note
+                }),
+                (event, _) => event,
+            };
+            song.replace_event(event.with_ticks(moved.ticks)).cloned()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::note::NoteNumber,
+        shared::unit::time::Ticks,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_move_events() {
+        let (mut song, track_id) = song_with_track();
+        let first = add_note(&mut song, track_id, 10, 240, 60, 100);
+        let second = add_note(&mut song, track_id, 250, 240, 64, 100);
+        let delta = TransformDelta {
+            delta_ticks: 500,
+            delta_notes: 2,
+            grid: None,
+        };
+
+        let constraints = MoveConstraints {
+            lock_pitch: true,
+            snap_grid: Some(Grid {
+                division: 4,
+                tuplet: None,
+            }),
+            ..Default::default()
+        };
+        let events = move_events(&mut song, &[first, second], delta, &constraints).unwrap();
+        assert_eq!(events[0].get_ticks(), Ticks::new(480));
+        assert_eq!(events[1].get_ticks(), Ticks::new(720));
+        assert_eq!(
+            events[1].as_note().unwrap().note_number,
+            NoteNumber::new(64)
+        );
+
+        let constraints = MoveConstraints {
+            lock_time: true,
+            ..Default::default()
+        };
+        let events = move_events(&mut song, &[first, second], delta, &constraints).unwrap();
+        assert_eq!(events[0].get_ticks(), Ticks::new(480));
+        assert_eq!(
+            events[0].as_note().unwrap().note_number,
+            NoteNumber::new(62)
+        );
+        let note = song
+            .get_track_events(&track_id)
+            .into_iter()
+            .find(|event| event.get_id() == second)
+            .and_then(|event| event.as_note())
+            .unwrap();
+        assert_eq!(note.note_number, NoteNumber::new(66));
+    }
+}
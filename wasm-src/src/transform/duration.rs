@@ -0,0 +1,143 @@
+use crate::{
+    event::event::Event,
+    shared::{error::StoreError, id::Id, unit::time::Ticks},
+    song::song::Song,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum DurationChange {
+    Fixed(Ticks),
+    Scale(f64),
+}
+
+impl DurationChange {
+    fn apply(&self, duration: Ticks) -> Ticks {
+        match self {
+            DurationChange::Fixed(ticks) => *ticks,
+            DurationChange::Scale(scale) => {
+                let scaled = (duration.as_f64() * scale).round().max(1.0);
+                Ticks::new(scaled as u64)
+            }
+        }
+    }
+}
+
+/// Sets or scales the duration of every event in `event_ids`.
+///
+/// Events without a duration are returned unchanged.
+pub fn set_durations(
+    song: &mut Song,
+    event_ids: &[Id],
+    change: DurationChange,
+) -> Result<Vec<Event>, StoreError> {
+    song.try_get_events(event_ids)?;
+
+    event_ids
+        .iter()
+        .map(|event_id| {
+            let event = song.get_event(event_id).unwrap().clone();
+
+            match event.get_duration() {
+                Some(duration) => song
+                    .replace_event(event.with_duration(change.apply(duration)))
+                    .cloned(),
+                None => Ok(event),
+            }
+        })
+        .collect()
+}
+
+/// Sets the duration of every note in `event_ids` to `ratio` times the gap to the next note of
+/// the same pitch on its track, so 1 makes the notes touch, below 1 detaches them and above 1
+/// overlaps them.
+///
+/// Notes without a following note of the same pitch, and other events, are returned unchanged.
+pub fn set_overlap_ratio(
+    song: &mut Song,
+    event_ids: &[Id],
+    ratio: f64,
+) -> Result<Vec<Event>, StoreError> {
+    let events: Vec<Event> = song
+        .try_get_events(event_ids)?
+        .into_iter()
+        .cloned()
+        .collect();
+
+    events
+        .into_iter()
+        .map(|event| {
+            let Some(note) = event.as_note() else {
+                return Ok(event);
+            };
+            let next_ticks = song
+                .get_track_events(&note.track_id)
+                .into_iter()
+                .filter_map(Event::as_note)
+                .find(|next| next.note_number == note.note_number && next.ticks > note.ticks)
+                .map(|next| next.ticks);
+
+            match next_ticks {
+                Some(next_ticks) => {
+                    let gap = (next_ticks - note.ticks).as_f64();
+                    let duration = Ticks::new((gap * ratio).round().max(1.0) as u64);
+                    song.replace_event(event.with_duration(duration)).cloned()
+                }
+                None => Ok(event),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_set_durations() {
+        let (mut song, track_id) = song_with_track();
+
+        let id1 = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let id2 = add_note(&mut song, track_id, 480, 240, 60, 100);
+
+        let events = set_durations(
+            &mut song,
+            &[id1, id2],
+            DurationChange::Fixed(Ticks::new(120)),
+        )
+        .unwrap();
+        assert_eq!(events[0].get_duration(), Some(Ticks::new(120)));
+        assert_eq!(events[1].get_duration(), Some(Ticks::new(120)));
+
+        let events = set_durations(&mut song, &[id1], DurationChange::Scale(1.5)).unwrap();
+        assert_eq!(events[0].get_duration(), Some(Ticks::new(180)));
+
+        // the track index must pick up the new duration
+        let events =
+            song.get_track_events_in_ticks_range(&track_id, Ticks::new(150), Ticks::new(160), true);
+        assert_eq!(events.len(), 1);
+
+        let events = song.get_events_in_ticks_range(Ticks::new(150), Ticks::new(160), true, None);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_set_overlap_ratio() {
+        let (mut song, track_id) = song_with_track();
+
+        let id1 = add_note(&mut song, track_id, 0, 100, 60, 100);
+        let id2 = add_note(&mut song, track_id, 240, 960, 62, 100);
+        let id3 = add_note(&mut song, track_id, 480, 100, 60, 100);
+        let id4 = add_note(&mut song, track_id, 960, 100, 60, 100);
+
+        let events = set_overlap_ratio(&mut song, &[id1, id2, id3, id4], 0.5).unwrap();
+        let durations: Vec<u64> = events
+            .iter()
+            .map(|event| event.get_duration().unwrap().as_u64())
+            .collect();
+        assert_eq!(durations, vec![240, 960, 240, 100]);
+
+        let events = set_overlap_ratio(&mut song, &[id1], 1.1).unwrap();
+        assert_eq!(events[0].get_duration(), Some(Ticks::new(528)));
+    }
+}
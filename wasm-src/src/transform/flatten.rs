@@ -0,0 +1,91 @@
+use crate::{
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+    },
+    song::song::Song,
+    track::track::Track,
+};
+
+/// Copies the events of `track_ids` into a new track with fresh ids.
+///
+/// With `apply_settings`, each source track's non-destructive settings are baked into its copied
+/// events; the new track itself starts with neutral settings.
+pub fn flatten_tracks<'a>(
+    song: &'a mut Song,
+    ids: &mut IdStrategy,
+    track_ids: &[Id],
+    apply_settings: bool,
+) -> Result<&'a Track, StoreError> {
+    let new_track_id = ids.next_id()?;
+    let ppq = song.ppq;
+    let mut events = Vec::new();
+
+    for track_id in track_ids {
+        let track = song
+            .get_track(track_id)
+            .ok_or(StoreError::TrackNotFound(*track_id))?;
+
+        for event in song.track_events(track).iter() {
+            let event = if apply_settings {
+                track.settings.apply(event.clone(), ppq, &song.grooves)
+            } else {
+                event.clone()
+            };
+            events.push(event.with_id(ids.next_id()?).with_track_id(new_track_id));
+        }
+    }
+
+    song.add_track_with_events(Track::new(new_track_id), events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::unit::time::Ticks,
+        track::track::TrackSettings,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_flatten_tracks() {
+        let (mut song, track_id1) = song_with_track();
+        let track_id2 = Id::new();
+        song.add_track(Track::new(track_id2));
+
+        add_note(&mut song, track_id1, 0, 480, 60, 100);
+        add_note(&mut song, track_id2, 240, 240, 48, 80);
+
+        song.get_track_mut(&track_id2).unwrap().settings = TrackSettings {
+            transpose: -12,
+            velocity_offset: 10,
+            swing: 0.0,
+            ..TrackSettings::default()
+        };
+
+        let flattened = flatten_tracks(
+            &mut song,
+            &mut IdStrategy::default(),
+            &[track_id1, track_id2],
+            true,
+        )
+        .unwrap();
+        let flattened_id = flattened.id;
+        let events = song.get_track_events(&flattened_id);
+
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| event.get_track_id() == Some(flattened_id)));
+
+        let bass = events[1].as_note().unwrap();
+        assert_eq!(bass.ticks, Ticks::new(240));
+        assert_eq!(bass.note_number.as_u8(), 36);
+        assert_eq!(bass.velocity.as_u8(), 90);
+
+        // sources are untouched and the copies are indexed by the song
+        assert_eq!(song.get_events(None).len(), 4);
+        assert_eq!(song.get_track_events(&track_id2).len(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    event::event::Event,
+    shared::{error::StoreError, id::Id},
+    song::song::Song,
+};
+
+/// Writes `amount` of the offsets of the groove `groove_id` into the events, moving each by the
+/// step of the groove nearest to it. Unlike a track's groove setting this changes the events
+/// themselves. Returns the updated events in `event_ids` order.
+pub fn apply_groove(
+    song: &mut Song,
+    event_ids: &[Id],
+    groove_id: &Id,
+    amount: f64,
+) -> Result<Vec<Event>, StoreError> {
+    let groove = song
+        .grooves
+        .get(groove_id)
+        .cloned()
+        .ok_or_else(|| StoreError::invalid("Groove not found"))?;
+    let events: Vec<Event> = song
+        .try_get_events(event_ids)?
+        .into_iter()
+        .cloned()
+        .collect();
+
+    events
+        .into_iter()
+        .map(|event| song.replace_event(groove.apply(event, amount)).cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shared::unit::time::Ticks,
+        song::groove::{Groove, GrooveStep},
+        transform::test_helpers::{add_control_change, add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_apply_groove() {
+        let (mut song, track_id) = song_with_track();
+        let groove_id = Id::new();
+        song.grooves.put(Groove {
+            id: groove_id,
+            name: "Shuffle".to_string(),
+            step_ticks: Ticks::new(120),
+            steps: vec![
+                GrooveStep {
+                    timing: 0,
+                    velocity: 10,
+                },
+                GrooveStep {
+                    timing: 40,
+                    velocity: -20,
+                },
+            ],
+        });
+        let on_beat = add_note(&mut song, track_id, 0, 120, 42, 100);
+        let off_beat = add_note(&mut song, track_id, 125, 120, 42, 100);
+        let cc = add_control_change(&mut song, track_id, 360, 1, 64);
+
+        let events = apply_groove(&mut song, &[off_beat, on_beat, cc], &groove_id, 0.5).unwrap();
+        assert_eq!(events[0].get_id(), off_beat);
+
+        let note = |id| song.get_event(&id).unwrap().as_note().unwrap().clone();
+        assert_eq!(note(on_beat).ticks, Ticks::new(0));
+        assert_eq!(note(on_beat).velocity.as_u8(), 105);
+        assert_eq!(note(off_beat).ticks, Ticks::new(145));
+        assert_eq!(note(off_beat).velocity.as_u8(), 90);
+        assert_eq!(note(off_beat).duration, Ticks::new(120));
+        assert_eq!(song.get_event(&cc).unwrap().get_ticks(), Ticks::new(380));
+        assert_eq!(
+            apply_groove(&mut song, &[cc], &Id::new(), 1.0).unwrap_err(),
+            StoreError::invalid("Groove not found")
+        );
+    }
+}
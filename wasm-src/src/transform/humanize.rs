@@ -0,0 +1,129 @@
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, Velocity},
+    },
+    shared::{error::StoreError, id::Id, rng::Rng, unit::time::Ticks},
+    song::song::Song,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_HUMANIZE_OPTIONS: &'static str = r#"
+export interface HumanizeOptions {
+  /** Notes move up to this many ticks earlier or later. Defaults to 0. */
+  timingJitterTicks?: number;
+  /** Velocities change by up to this much either way. Defaults to 0. */
+  velocityJitter?: number;
+  /** The same seed moves the same notes the same way. Defaults to 1. */
+  seed?: number;
+}
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HumanizeOptions {
+    pub timing_jitter_ticks: u64,
+    pub velocity_jitter: u8,
+    pub seed: u64,
+}
+
+impl Default for HumanizeOptions {
+    fn default() -> Self {
+        HumanizeOptions {
+            timing_jitter_ticks: 0,
+            velocity_jitter: 0,
+            seed: 1,
+        }
+    }
+}
+
+/// Moves each note by a random offset of up to `timing_jitter_ticks` and changes its velocity by
+/// up to `velocity_jitter`, drawing offsets in `event_ids` order from a generator seeded with
+/// `seed`. Notes keep their lengths, don't move before the start of the song and keep velocities
+/// from 1 to 127. Other events are left alone. Returns the updated notes.
+pub fn humanize_events(
+    song: &mut Song,
+    event_ids: &[Id],
+    options: &HumanizeOptions,
+) -> Result<Vec<Event>, StoreError> {
+    let notes: Vec<Note> = song
+        .try_get_events(event_ids)?
+        .into_iter()
+        .filter_map(|event| event.as_note().cloned())
+        .collect();
+
+    let mut rng = Rng::new(options.seed);
+    let mut updated = Vec::new();
+
+    for note in notes {
+        let offset = rng.within(options.timing_jitter_ticks);
+        let velocity_offset = rng.within(options.velocity_jitter as u64);
+
+        let ticks = if offset < 0 {
+            note.ticks - Ticks::new(offset.unsigned_abs())
+        } else {
+            note.ticks + Ticks::new(offset as u64)
+        };
+        let velocity = (note.velocity.as_u8() as i64 + velocity_offset).clamp(1, 127);
+
+        let humanized = Note {
+            ticks,
+            velocity: Velocity::new(velocity as u8),
+            ..note
+        };
+        updated.push(song.replace_event(Event::Note(humanized))?.clone());
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_humanize_events() {
+        let humanized = || {
+            let (mut song, track_id) = song_with_track();
+            let mut ids: Vec<Id> = (0..8)
+                .map(|index| add_note(&mut song, track_id, index * 480 + 5, 240, 60, 126))
+                .collect();
+            ids.push(add_control_change(&mut song, track_id, 0, 7, 100));
+            let options = HumanizeOptions {
+                timing_jitter_ticks: 10,
+                velocity_jitter: 8,
+                seed: 42,
+            };
+            humanize_events(&mut song, &ids, &options)
+                .unwrap()
+                .iter()
+                .map(|event| {
+                    let note = event.as_note().unwrap();
+                    (
+                        note.ticks.as_u64(),
+                        note.duration.as_u64(),
+                        note.velocity.as_u8(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = humanized();
+        assert_eq!(first, humanized());
+        assert_eq!(first.len(), 8);
+        for (index, (ticks, duration, velocity)) in first.iter().enumerate() {
+            assert!(ticks.abs_diff(index as u64 * 480 + 5) <= 10);
+            assert_eq!(*duration, 240);
+            assert!((118..=127).contains(velocity));
+        }
+        assert!(first
+            .iter()
+            .enumerate()
+            .any(|(index, (ticks, _, _))| *ticks != index as u64 * 480 + 5));
+    }
+}
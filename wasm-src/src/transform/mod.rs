@@ -0,0 +1,82 @@
+pub mod align;
+pub mod bulk;
+pub mod clipboard;
+pub mod curve;
+pub mod dedupe;
+pub mod drag;
+pub mod duration;
+pub mod flatten;
+pub mod groove;
+pub mod humanize;
+pub mod parameter;
+pub mod quantize;
+pub mod ramp;
+pub mod ratchet;
+pub mod region;
+pub mod repeat;
+pub mod split;
+pub mod strum;
+pub mod thin;
+pub mod transpose;
+
+#[cfg(test)]
+pub mod test_helpers {
+    use crate::{
+        event::{
+            control_change::{ControlChange, ControllerNumber, ControllerValue},
+            event::Event,
+            note::{Note, NoteNumber, NoteParameters, Velocity},
+        },
+        shared::{id::Id, unit::time::Ticks},
+        song::song::Song,
+        track::track::Track,
+    };
+
+    pub fn song_with_track() -> (Song, Id) {
+        let mut song = Song::new("test".to_string(), 480);
+        let track_id = Id::new();
+        song.add_track(Track::new(track_id));
+        (song, track_id)
+    }
+
+    pub fn add_note(
+        song: &mut Song,
+        track_id: Id,
+        ticks: u64,
+        duration: u64,
+        note_number: u8,
+        velocity: u8,
+    ) -> Id {
+        let id = Id::new();
+        song.add_event(Event::Note(Note {
+            id,
+            ticks: Ticks::new(ticks),
+            duration: Ticks::new(duration),
+            velocity: Velocity::new(velocity),
+            note_number: NoteNumber::new(note_number),
+            track_id,
+            parameters: NoteParameters::new(),
+        }))
+        .unwrap();
+        id
+    }
+
+    pub fn add_control_change(
+        song: &mut Song,
+        track_id: Id,
+        ticks: u64,
+        controller: u8,
+        value: u8,
+    ) -> Id {
+        let id = Id::new();
+        song.add_event(Event::ControlChange(ControlChange {
+            id,
+            ticks: Ticks::new(ticks),
+            controller: ControllerNumber::new(controller),
+            value: ControllerValue::new(value),
+            track_id,
+        }))
+        .unwrap();
+        id
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    event::event::Event,
+    shared::{error::StoreError, id::Id},
+    song::song::Song,
+};
+
+/// Sets the named per-note parameter on every note in `event_ids`, or clears it when `value` is
+/// `None`. Other event kinds are returned unchanged.
+pub fn set_note_parameter(
+    song: &mut Song,
+    event_ids: &[Id],
+    name: &str,
+    value: Option<f64>,
+) -> Result<Vec<Event>, StoreError> {
+    song.try_get_events(event_ids)?;
+
+    event_ids
+        .iter()
+        .map(|event_id| {
+            let event = song.get_event(event_id).unwrap();
+            let Event::Note(mut note) = event.clone() else {
+                return Ok(event.clone());
+            };
+
+            match value {
+                Some(value) => note.parameters.insert(name.to_string(), value),
+                None => note.parameters.remove(name),
+            };
+
+            song.replace_event(Event::Note(note)).cloned()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_set_note_parameter() {
+        let (mut song, track_id) = song_with_track();
+
+        let id1 = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let id2 = add_note(&mut song, track_id, 480, 480, 62, 100);
+
+        set_note_parameter(&mut song, &[id1, id2], "pan", Some(-0.5)).unwrap();
+        set_note_parameter(&mut song, &[id1], "brightness", Some(0.8)).unwrap();
+        set_note_parameter(&mut song, &[id2], "pan", None).unwrap();
+
+        let note1 = song.get_event(&id1).unwrap().as_note().unwrap();
+        assert_eq!(note1.parameters.get("pan"), Some(&-0.5));
+        assert_eq!(note1.parameters.get("brightness"), Some(&0.8));
+
+        let note2 = song.get_event(&id2).unwrap().as_note().unwrap();
+        assert!(note2.parameters.is_empty());
+    }
+}
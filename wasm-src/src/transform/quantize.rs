@@ -0,0 +1,177 @@
+use crate::{
+    event::event::Event,
+    shared::{
+        error::StoreError,
+        id::Id,
+        unit::{
+            grid::{too_fine_error, Grid},
+            time::Ticks,
+        },
+    },
+    song::{meter::MeterMap, song::Song},
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_QUANTIZE_INTERFACES: &'static str = r#"
+export interface QuantizeOptions {
+  grid: Grid;
+  /** How far events move towards the grid, from 0 to 1. Defaults to 1. */
+  strength?: number;
+  /** Snap note ends to the grid as well. Defaults to false. */
+  quantizeDuration?: boolean;
+  /** Pushes every other grid line late; 0 = straight, 1 = triplet feel. Defaults to 0. */
+  swing?: number;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuantizeOptions {
+    pub grid: Grid,
+    #[serde(default = "full_strength")]
+    pub strength: f64,
+    #[serde(default)]
+    pub quantize_duration: bool,
+    #[serde(default)]
+    pub swing: f64,
+}
+
+fn full_strength() -> f64 {
+    1.0
+}
+
+impl QuantizeOptions {
+    /// Ticks of the `index`-th grid line from `origin`, with odd lines swung late by up to a
+    /// third of a step.
+    fn swung_line(&self, ppq: u32, origin: Ticks, index: u64) -> Result<f64, StoreError> {
+        let line_at = |index| {
+            self.grid
+                .line_at(ppq, origin, index)
+                .ok_or_else(too_fine_error)
+        };
+        let line = line_at(index)?.as_f64();
+        if index.is_multiple_of(2) || self.swing == 0.0 {
+            return Ok(line);
+        }
+        let step = line_at(index + 1)?.as_f64() - line;
+        Ok(line + self.swing.clamp(0.0, 1.0) * step / 3.0)
+    }
+
+    /// Moves `ticks` towards `target` by the options' strength.
+    fn pull(&self, ticks: Ticks, target: f64) -> Ticks {
+        let ticks = ticks.as_f64();
+        let pulled = ticks + self.strength.clamp(0.0, 1.0) * (target - ticks);
+        Ticks::new(pulled.round().max(0.0) as u64)
+    }
+}
+
+/// Moves the start of every event in `event_ids` towards its nearest grid line.
+///
+/// With `quantize_duration`, the ends of events with a duration move towards their nearest grid
+/// line too, but never onto or before the line their start snapped to; the next line is used
+/// instead. Otherwise events keep their durations. Grid lines start over at every meter change.
+/// Returns the events in `event_ids` order.
+pub fn quantize_events(
+    song: &mut Song,
+    event_ids: &[Id],
+    options: &QuantizeOptions,
+) -> Result<Vec<Event>, StoreError> {
+    song.try_get_events(event_ids)?;
+    let meters = MeterMap::new(song);
+    let ppq = meters.ppq();
+    // grid lines start over at every meter change
+    let origin = |ticks| meters.segment_at(ticks).ticks;
+
+    event_ids
+        .iter()
+        .map(|event_id| {
+            let event = song.get_event(event_id).unwrap().clone();
+            let start = event.get_ticks();
+            let start_origin = origin(start);
+            let start_index = options
+                .grid
+                .nearest_index(ppq, start_origin, start)
+                .ok_or_else(too_fine_error)?;
+            let ticks = options.pull(start, options.swung_line(ppq, start_origin, start_index)?);
+
+            let quantized = match event.get_duration() {
+                Some(duration) if options.quantize_duration => {
+                    let end = start + duration;
+                    let end_origin = origin(end);
+                    let mut end_index = options
+                        .grid
+                        .nearest_index(ppq, end_origin, end)
+                        .ok_or_else(too_fine_error)?;
+                    if end_origin == start_origin {
+                        end_index = end_index.max(start_index + 1);
+                    }
+                    let end = options.pull(end, options.swung_line(ppq, end_origin, end_index)?);
+                    let duration = Ticks::new(end.as_u64().saturating_sub(ticks.as_u64()).max(1));
+                    event.with_ticks(ticks).with_duration(duration)
+                }
+                _ => event.with_ticks(ticks),
+            };
+            song.replace_event(quantized).cloned()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_quantize_events() {
+        let (mut song, track_id) = song_with_track();
+        let early = add_note(&mut song, track_id, 110, 100, 60, 100);
+        let late = add_note(&mut song, track_id, 250, 20, 62, 100);
+        let sixteenths = Grid {
+            division: 16,
+            tuplet: None,
+        };
+
+        let options = QuantizeOptions {
+            grid: sixteenths,
+            strength: 1.0,
+            quantize_duration: false,
+            swing: 0.0,
+        };
+        let events = quantize_events(&mut song, &[early, late], &options).unwrap();
+        assert_eq!(events[0].get_ticks(), Ticks::new(120));
+        assert_eq!(events[0].get_duration(), Some(Ticks::new(100)));
+        assert_eq!(events[1].get_ticks(), Ticks::new(240));
+
+        let options = QuantizeOptions {
+            quantize_duration: true,
+            swing: 1.0,
+            ..options
+        };
+        let events = quantize_events(&mut song, &[early, late], &options).unwrap();
+        // Odd sixteenths move a third of a step late; the short note keeps one step.
+        assert_eq!(events[0].get_ticks(), Ticks::new(160));
+        assert_eq!(events[0].get_duration(), Some(Ticks::new(80)));
+        assert_eq!(events[1].get_ticks(), Ticks::new(240));
+        assert_eq!(events[1].get_duration(), Some(Ticks::new(160)));
+
+        let options = QuantizeOptions {
+            strength: 0.5,
+            ..options
+        };
+        add_note(&mut song, track_id, 0, 480, 64, 100);
+        let loose = add_note(&mut song, track_id, 500, 100, 65, 100);
+        let events = quantize_events(&mut song, &[loose], &options).unwrap();
+        assert_eq!(events[0].get_ticks(), Ticks::new(490));
+        let events = song.get_track_events_in_ticks_range(
+            &track_id,
+            Ticks::new(490),
+            Ticks::new(491),
+            false,
+        );
+        assert_eq!(events[0].get_id(), loose);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{
+    event::{control_change::ControllerNumber, event::Event},
+    shared::{error::StoreError, id::Id, js::string_enum, unit::time::Ticks},
+    song::song::Song,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RAMP_INTERFACES: &'static str = r#"
+export interface RampLane {
+  trackId: string;
+  controller?: number;
+  startTicks: number;
+  endTicks: number;
+}
+
+/** A corner of a line drawn over a lane, or a sample of a controller's automation. */
+export interface ValuePoint {
+  ticks: number;
+  value: number;
+}
+"#;
+
+string_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RampCurve {
+        Linear = "linear",
+        Exponential = "exponential",
+    }
+}
+
+impl RampCurve {
+    fn value_at(&self, start: f64, end: f64, position: f64) -> f64 {
+        match self {
+            RampCurve::Exponential => {
+                let (start, end) = (start.max(1.0), end.max(1.0));
+                start * (end / start).powf(position)
+            }
+            RampCurve::Linear => start + (end - start) * position,
+        }
+    }
+}
+
+string_enum! {
+    /// The field `applyValueLine` sets.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ValueLineField {
+        /// Velocities of notes and values of control changes.
+        Value = "value",
+        /// Durations of notes and audio regions, in ticks.
+        Duration = "duration",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValuePoint {
+    pub ticks: Ticks,
+    pub value: f64,
+}
+
+pub enum RampTarget {
+    Events(Vec<Id>),
+    /// Velocities of the track's notes, or the values of one controller when `controller` is set.
+    Lane {
+        track_id: Id,
+        controller: Option<ControllerNumber>,
+        start_ticks: Ticks,
+        end_ticks: Ticks,
+    },
+}
+
+/// Spreads values from `start_value` to `end_value` across the time span of the target events,
+/// setting velocities for notes and values for control changes.
+pub fn ramp_values(
+    song: &mut Song,
+    target: RampTarget,
+    start_value: u8,
+    end_value: u8,
+    curve: RampCurve,
+) -> Result<Vec<Event>, StoreError> {
+    let events: Vec<Event> = match target {
+        RampTarget::Events(event_ids) => song
+            .try_get_events(&event_ids)?
+            .into_iter()
+            .cloned()
+            .collect(),
+        RampTarget::Lane {
+            track_id,
+            controller,
+            start_ticks,
+            end_ticks,
+        } => song
+            .track_events(
+                song.get_track(&track_id)
+                    .ok_or(StoreError::TrackNotFound(track_id))?,
+            )
+            .in_ticks_range(start_ticks, end_ticks, false)
+            .into_iter()
+            .filter(|event| match (event, controller) {
+                (Event::Note(_), None) => true,
+                (Event::ControlChange(cc), Some(controller)) => cc.controller == controller,
+                _ => false,
+            })
+            .cloned()
+            .collect(),
+    };
+
+    let Some(first) = events.iter().map(|event| event.get_ticks()).min() else {
+        return Ok(Vec::new());
+    };
+    let last = events.iter().map(|event| event.get_ticks()).max().unwrap();
+    let span = (last - first).as_f64();
+
+    events
+        .into_iter()
+        .map(|event| {
+            let position = if span > 0.0 {
+                (event.get_ticks() - first).as_f64() / span
+            } else {
+                0.0
+            };
+            let value = curve.value_at(start_value as f64, end_value as f64, position);
+            let value = value.round().clamp(0.0, 127.0) as u8;
+            song.replace_event(event.with_value(value)).cloned()
+        })
+        .collect()
+}
+
+/// The value of the polyline through `points`, sorted by ticks, at `ticks`, or `None` outside
+/// the ticks the points span.
+fn value_on_line(points: &[ValuePoint], ticks: Ticks) -> Option<f64> {
+    let after = points.partition_point(|point| point.ticks <= ticks);
+    match (
+        after.checked_sub(1).map(|index| points[index]),
+        points.get(after),
+    ) {
+        (Some(before), _) if before.ticks == ticks => Some(before.value),
+        (Some(before), Some(after)) => {
+            let position = (ticks - before.ticks).as_f64() / (after.ticks - before.ticks).as_f64();
+            Some(before.value + (after.value - before.value) * position)
+        }
+        _ => None,
+    }
+}
+
+/// Sets `field` of each of `event_ids` to the value of the line through `points` at the event's
+/// ticks, like a pencil drawn over a velocity lane. Events before the first point or after the
+/// last keep their value, as do events without the field. Values are rounded and clamped like
+/// `ramp_values`; durations stay at least one tick. Returns the changed events.
+pub fn apply_value_line(
+    song: &mut Song,
+    event_ids: &[Id],
+    points: &[ValuePoint],
+    field: ValueLineField,
+) -> Result<Vec<Event>, StoreError> {
+    let events: Vec<Event> = song
+        .try_get_events(event_ids)?
+        .into_iter()
+        .cloned()
+        .collect();
+    let mut points = points.to_vec();
+    points.sort_by_key(|point| point.ticks);
+
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let value = value_on_line(&points, event.get_ticks())?.round();
+            let event = match (field, event.get_duration()) {
+                (ValueLineField::Duration, Some(_)) => {
+                    event.with_duration(Ticks::new(value.clamp(1.0, Ticks::MAX.as_f64()) as u64))
+                }
+                (ValueLineField::Duration, None) => return None,
+                (ValueLineField::Value, _) => match event {
+                    Event::Note(_) | Event::ControlChange(_) => {
+                        event.with_value(value.clamp(0.0, 127.0) as u8)
+                    }
+                    _ => return None,
+                },
+            };
+            Some(song.replace_event(event).cloned())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    fn value_of(song: &Song, id: &Id) -> u8 {
+        match song.get_event(id).unwrap() {
+            Event::Note(note) => note.velocity.as_u8(),
+            Event::ControlChange(cc) => cc.value.as_u8(),
+            Event::Lyric(_)
+            | Event::AudioRegion(_)
+            | Event::TimeSignature(_)
+            | Event::Marker(_)
+            | Event::KeySignature(_)
+            | Event::ChordSymbol(_)
+            | Event::RawMidi(_) => {
+                unreachable!()
+            }
+        }
+    }
+
+    #[test]
+    fn test_ramp_velocities() {
+        let (mut song, track_id) = song_with_track();
+
+        let ids: Vec<Id> = (0..5)
+            .map(|i| add_note(&mut song, track_id, i * 480, 240, 60, 64))
+            .collect();
+
+        ramp_values(
+            &mut song,
+            RampTarget::Events(ids.clone()),
+            40,
+            120,
+            RampCurve::Linear,
+        )
+        .unwrap();
+
+        let velocities: Vec<u8> = ids.iter().map(|id| value_of(&song, id)).collect();
+        assert_eq!(velocities, vec![40, 60, 80, 100, 120]);
+
+        ramp_values(
+            &mut song,
+            RampTarget::Events(ids.clone()),
+            10,
+            90,
+            RampCurve::Exponential,
+        )
+        .unwrap();
+
+        let velocities: Vec<u8> = ids.iter().map(|id| value_of(&song, id)).collect();
+        assert_eq!(velocities, vec![10, 17, 30, 52, 90]);
+    }
+
+    #[test]
+    fn test_ramp_controller_lane() {
+        let (mut song, track_id) = song_with_track();
+
+        let note = add_note(&mut song, track_id, 0, 480, 60, 64);
+        let cc1 = add_control_change(&mut song, track_id, 0, 11, 0);
+        let cc2 = add_control_change(&mut song, track_id, 960, 11, 0);
+        let other = add_control_change(&mut song, track_id, 480, 1, 0);
+
+        let updated = ramp_values(
+            &mut song,
+            RampTarget::Lane {
+                track_id,
+                controller: Some(ControllerNumber::new(11)),
+                start_ticks: Ticks::new(0),
+                end_ticks: Ticks::new(1920),
+            },
+            0,
+            127,
+            RampCurve::Linear,
+        )
+        .unwrap();
+
+        assert_eq!(updated.len(), 2);
+        assert_eq!(value_of(&song, &cc1), 0);
+        assert_eq!(value_of(&song, &cc2), 127);
+        assert_eq!(value_of(&song, &other), 0);
+        assert_eq!(value_of(&song, &note), 64);
+    }
+
+    #[test]
+    fn test_apply_value_line() {
+        let (mut song, track_id) = song_with_track();
+        let ids: Vec<Id> = (0..5)
+            .map(|i| add_note(&mut song, track_id, i * 240, 240, 60, 64))
+            .collect();
+        let cc = add_control_change(&mut song, track_id, 480, 1, 0);
+        let point = |ticks: u64, value: f64| ValuePoint {
+            ticks: Ticks::new(ticks),
+            value,
+        };
+        let points = [point(960, 40.0), point(0, 100.0), point(480, 120.0)];
+
+        let mut event_ids = ids.clone();
+        event_ids.push(cc);
+        let changed =
+            apply_value_line(&mut song, &event_ids, &points, ValueLineField::Value).unwrap();
+        assert_eq!(changed.len(), 6);
+        let velocities: Vec<u8> = ids.iter().map(|id| value_of(&song, id)).collect();
+        assert_eq!(velocities, vec![100, 110, 120, 80, 40]);
+        assert_eq!(value_of(&song, &cc), 120);
+
+        let changed = apply_value_line(
+            &mut song,
+            &ids,
+            &[point(240, 120.0), point(480, 0.0)],
+            ValueLineField::Duration,
+        )
+        .unwrap();
+        let durations: Vec<u64> = changed
+            .iter()
+            .map(|event| event.get_duration().unwrap().as_u64())
+            .collect();
+        assert_eq!(durations, vec![120, 1]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, Velocity},
+    },
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        unit::time::Ticks,
+    },
+    song::song::Song,
+};
+
+/// Splits each note into `divisions` equally spaced repeats filling its duration, each repeat
+/// `velocity_decay` softer than the one before. The first repeat keeps the note's id.
+///
+/// Notes too short to split that many times get one repeat per tick. Other events are left
+/// alone. Returns every repeat, originals included.
+pub fn ratchet_notes(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    event_ids: &[Id],
+    divisions: u32,
+    velocity_decay: i32,
+) -> Result<Vec<Event>, StoreError> {
+    let notes: Vec<Note> = song
+        .try_get_events(event_ids)?
+        .into_iter()
+        .filter_map(|event| event.as_note().cloned())
+        .collect();
+
+    let mut ratcheted = Vec::new();
+
+    for note in notes {
+        let duration = note.duration.as_u64();
+        let divisions = (divisions as u64).clamp(1, duration.max(1));
+        let offset = |index: u64| duration * index / divisions;
+
+        for index in 0..divisions {
+            let start = offset(index);
+            let velocity = note.velocity.as_u8() as i32 - velocity_decay * index as i32;
+            let repeat = Event::Note(Note {
+                id: if index == 0 { note.id } else { ids.next_id()? },
+                ticks: note.ticks + Ticks::new(start),
+                duration: Ticks::new(offset(index + 1) - start),
+                velocity: Velocity::new(velocity.clamp(1, 127) as u8),
+                ..note.clone()
+            });
+
+            let repeat = if index == 0 {
+                song.replace_event(repeat)
+            } else {
+                song.add_event(repeat)
+            };
+            ratcheted.push(repeat?.clone());
+        }
+    }
+
+    Ok(ratcheted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_ratchet_notes() {
+        let (mut song, track_id) = song_with_track();
+        let id = add_note(&mut song, track_id, 480, 480, 38, 100);
+        let short = add_note(&mut song, track_id, 960, 2, 38, 100);
+
+        let ratcheted =
+            ratchet_notes(&mut song, &mut IdStrategy::default(), &[id, short], 3, 20).unwrap();
+        let repeats: Vec<(u64, u64, u8)> = ratcheted
+            .iter()
+            .map(|event| {
+                let note = event.as_note().unwrap();
+                (
+                    note.ticks.as_u64(),
+                    note.duration.as_u64(),
+                    note.velocity.as_u8(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            repeats,
+            vec![
+                (480, 160, 100),
+                (640, 160, 80),
+                (800, 160, 60),
+                (960, 1, 100),
+                (961, 1, 80),
+            ]
+        );
+        assert_eq!(ratcheted[0].get_id(), id);
+        assert_eq!(song.get_track_events(&track_id).len(), 5);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    event::event::Event,
+    shared::{error::StoreError, id::IdStrategy, unit::time::Ticks},
+    song::{meter::MeterMap, song::GetEventsFilter, song::Song},
+};
+
+/// Inserts `count` copies of the zero-based bars `[start_bar, end_bar)` right after them.
+///
+/// Events starting in the bars are copied, on the tracks in `filter` or all tracks, and every
+/// event of the song starting at or after the end of the bars moves later to make room. Time
+/// signatures in the bars are copied like any other event. Returns the newly created events.
+pub fn duplicate_region(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    start_bar: u64,
+    end_bar: u64,
+    count: u64,
+    filter: Option<GetEventsFilter>,
+) -> Result<Vec<Event>, StoreError> {
+    if end_bar <= start_bar || count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let meters = MeterMap::new(song);
+    let start = meters.bar_start(start_bar);
+    let end = meters.bar_start(end_bar);
+    let length = end - start;
+
+    let region: Vec<Event> = song
+        .get_events_in_ticks_range(start, end, false, filter)
+        .into_iter()
+        .cloned()
+        .collect();
+    let later: Vec<Event> = song
+        .iter_events_in_ticks_range(end, Ticks::MAX)
+        .cloned()
+        .collect();
+
+    let shift = Ticks::new(length.as_u64() * count);
+    for event in later {
+        let ticks = event.get_ticks() + shift;
+        song.replace_event(event.with_ticks(ticks))?;
+    }
+
+    paste_copies(song, ids, &region, length, count)
+}
+
+/// Pastes `count` copies of the events starting in `[start_ticks, end_ticks)` back-to-back
+/// right after the range, on the tracks in `filter` or all tracks.
+///
+/// Unlike `duplicate_region`, nothing moves: the copies land on top of whatever follows the
+/// range. Returns the newly created events.
+pub fn repeat_range(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    start_ticks: Ticks,
+    end_ticks: Ticks,
+    count: u64,
+    filter: Option<GetEventsFilter>,
+) -> Result<Vec<Event>, StoreError> {
+    if end_ticks <= start_ticks || count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let range: Vec<Event> = song
+        .get_events_in_ticks_range(start_ticks, end_ticks, false, filter)
+        .into_iter()
+        .cloned()
+        .collect();
+    paste_copies(song, ids, &range, end_ticks - start_ticks, count)
+}
+
+/// Adds `count` copies of `events` with fresh ids, each `length` later than the one before.
+fn paste_copies(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    events: &[Event],
+    length: Ticks,
+    count: u64,
+) -> Result<Vec<Event>, StoreError> {
+    let mut created = Vec::new();
+    for copy in 1..=count {
+        let offset = Ticks::new(length.as_u64() * copy);
+        for event in events {
+            let ticks = event.get_ticks() + offset;
+            let copy = event.clone().with_id(ids.next_id()?).with_ticks(ticks);
+            created.push(song.add_event(copy)?.clone());
+        }
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::time_signature::TimeSignature,
+        shared::id::Id,
+        track::track::Track,
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_duplicate_region() {
+        let (mut song, track_id) = song_with_track();
+        song.add_event(Event::TimeSignature(TimeSignature {
+            id: Id::new(),
+            ticks: Ticks::new(1920),
+            numerator: 3,
+            denominator: 4,
+            track_id,
+        }))
+        .unwrap();
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 1920, 480, 62, 100);
+        add_note(&mut song, track_id, 3360, 480, 64, 100);
+        let after = add_note(&mut song, track_id, 4800, 480, 65, 100);
+
+        let created =
+            duplicate_region(&mut song, &mut IdStrategy::default(), 1, 3, 2, None).unwrap();
+        assert_eq!(created.len(), 6);
+
+        let ticks: Vec<u64> = song
+            .get_track_events(&track_id)
+            .iter()
+            .filter(|event| event.as_note().is_some())
+            .map(|event| event.get_ticks().as_u64())
+            .collect();
+        assert_eq!(ticks, vec![0, 1920, 3360, 4800, 6240, 7680, 9120, 10560]);
+        assert_eq!(
+            song.get_event(&after).unwrap().get_ticks(),
+            Ticks::new(10560)
+        );
+        assert_eq!(MeterMap::new(&song).segments().len(), 4);
+    }
+
+    #[test]
+    fn test_repeat_range() {
+        let (mut song, track_id) = song_with_track();
+        let other = song.add_track(Track::new(Id::new())).id;
+        add_note(&mut song, track_id, 0, 480, 60, 100);
+        add_note(&mut song, track_id, 480, 480, 62, 100);
+        add_note(&mut song, other, 0, 960, 36, 100);
+        let after = add_note(&mut song, track_id, 960, 480, 64, 100);
+
+        let filter = GetEventsFilter {
+            track_ids: Some(vec![track_id]),
+        };
+        let created = repeat_range(
+            &mut song,
+            &mut IdStrategy::default(),
+            Ticks::new(0),
+            Ticks::new(960),
+            3,
+            Some(filter),
+        )
+        .unwrap();
+        let ticks: Vec<u64> = created
+            .iter()
+            .map(|event| event.get_ticks().as_u64())
+            .collect();
+        assert_eq!(ticks, vec![960, 1440, 1920, 2400, 2880, 3360]);
+        assert!(created
+            .iter()
+            .all(|event| event.get_track_id() == Some(track_id)));
+        assert_eq!(song.get_event(&after).unwrap().get_ticks(), Ticks::new(960));
+        assert!(repeat_range(
+            &mut song,
+            &mut IdStrategy::default(),
+            Ticks::new(960),
+            Ticks::new(960),
+            2,
+            None
+        )
+        .unwrap()
+        .is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    event::event::Event,
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        unit::time::Ticks,
+    },
+    song::song::Song,
+};
+
+/// Tiles the selection back-to-back until `until_ticks`, giving every copy a fresh id.
+///
+/// The tile length is the selection's extent, from the earliest start to the latest end. Copies
+/// starting at or after `until_ticks` are dropped. Returns the newly created events.
+pub fn repeat_events_to_fill(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    event_ids: &[Id],
+    until_ticks: Ticks,
+) -> Result<Vec<Event>, StoreError> {
+    let events: Vec<Event> = song
+        .try_get_events(event_ids)?
+        .into_iter()
+        .cloned()
+        .collect();
+
+    let Some(start) = events.iter().map(|event| event.get_ticks()).min() else {
+        return Ok(Vec::new());
+    };
+    let end = events
+        .iter()
+        .map(|event| event.get_ticks() + event.get_duration().unwrap_or(Ticks::new(0)))
+        .max()
+        .unwrap();
+    let length = end - start;
+    if length == Ticks::new(0) {
+        return Ok(Vec::new());
+    }
+
+    let mut created = Vec::new();
+    let mut offset = length;
+
+    while start + offset < until_ticks {
+        for event in &events {
+            let ticks = event.get_ticks() + offset;
+            if ticks < until_ticks {
+                let copy = event.clone().with_id(ids.next_id()?).with_ticks(ticks);
+                created.push(song.add_event(copy)?.clone());
+            }
+        }
+        offset = offset + length;
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_repeat_events_to_fill() {
+        let (mut song, track_id) = song_with_track();
+
+        let id1 = add_note(&mut song, track_id, 0, 960, 36, 100);
+        let id2 = add_note(&mut song, track_id, 960, 960, 38, 100);
+
+        let created = repeat_events_to_fill(
+            &mut song,
+            &mut IdStrategy::default(),
+            &[id1, id2],
+            Ticks::new(7680),
+        )
+        .unwrap();
+        assert_eq!(created.len(), 6);
+        assert!(created
+            .iter()
+            .all(|event| event.get_id() != id1 && event.get_id() != id2));
+
+        let ticks: Vec<u64> = song
+            .get_track_events(&track_id)
+            .iter()
+            .map(|event| event.get_ticks().as_u64())
+            .collect();
+        assert_eq!(ticks, vec![0, 960, 1920, 2880, 3840, 4800, 5760, 6720]);
+    }
+
+    #[test]
+    fn test_repeat_events_to_fill_partial_tile() {
+        let (mut song, track_id) = song_with_track();
+
+        let id1 = add_note(&mut song, track_id, 0, 480, 36, 100);
+        let id2 = add_note(&mut song, track_id, 480, 480, 38, 100);
+
+        let created = repeat_events_to_fill(
+            &mut song,
+            &mut IdStrategy::default(),
+            &[id1, id2],
+            Ticks::new(1500),
+        )
+        .unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[1].get_ticks(), Ticks::new(1440));
+    }
+}
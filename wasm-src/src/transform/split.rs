@@ -0,0 +1,299 @@
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, NoteNumber, Velocity},
+    },
+    shared::{
+        error::StoreError,
+        id::{Id, IdStrategy},
+        js::string_enum,
+    },
+    song::song::Song,
+    track::track::{Track, TrackDisplay},
+};
+use std::collections::BTreeMap;
+
+/// Name of the track holding MIDI channel `channel` (0-based) of a track named `name`.
+pub fn channel_track_name(name: Option<&str>, channel: u8) -> String {
+    match name {
+        Some(name) => format!("{} (Channel {})", name, channel + 1),
+        None => format!("Channel {}", channel + 1),
+    }
+}
+
+/// Moves the events of `track_id` onto one new track per MIDI channel they were imported on,
+/// and returns the ids of the new tracks by channel. New tracks copy the track's settings and
+/// mix and play on their channel; events without a channel stay on the original track.
+pub fn split_track_by_channel(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+) -> Result<Vec<Id>, StoreError> {
+    let track = song
+        .get_track(&track_id)
+        .ok_or(StoreError::TrackNotFound(track_id))?;
+    let mut by_channel: BTreeMap<u8, Vec<Event>> = BTreeMap::new();
+    for event in song.track_events(track).iter() {
+        if let Some(channel) = song.get_channel(&event.get_id()) {
+            by_channel.entry(channel).or_default().push(event.clone());
+        }
+    }
+    let settings = track.settings.clone();
+    let pitch_range = track.pitch_range;
+    let pitch_bend_range = track.pitch_bend_range;
+    let expression_map = track.expression_map.clone();
+    let mix = track.mix.clone();
+    let display = track.display.clone();
+    let output = track.output.clone();
+    let program = track.program;
+
+    let mut new_track_ids = Vec::new();
+    for (channel, events) in by_channel {
+        let mut new_track = Track::new(ids.next_id()?);
+        new_track.settings = settings.clone();
+        new_track.pitch_range = pitch_range;
+        new_track.pitch_bend_range = pitch_bend_range;
+        new_track.expression_map = expression_map.clone();
+        new_track.mix = mix.clone();
+        new_track.output = output.clone();
+        new_track.channel = Some(channel);
+        new_track.program = program;
+        new_track.display = TrackDisplay {
+            name: Some(channel_track_name(display.name.as_deref(), channel)),
+            color: display.color.clone(),
+        };
+        let new_track_id = song.add_track(new_track).id;
+
+        for event in events {
+            song.replace_event(event.with_track_id(new_track_id))?;
+        }
+        new_track_ids.push(new_track_id);
+    }
+
+    Ok(new_track_ids)
+}
+
+/// Moves the notes of `track_id` below `split_note` onto a new track right after it, and
+/// returns the new track's id. The new track copies the track's properties; events other than
+/// notes stay on the original track.
+pub fn split_track_by_pitch(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+    split_note: NoteNumber,
+) -> Result<Id, StoreError> {
+    split_notes_off(song, ids, track_id, "Low", |note| {
+        note.note_number < split_note
+    })
+}
+
+string_enum! {
+    /// Which notes `split_track_by_velocity` moves off the track.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum VelocitySplit {
+        /// Notes at or above the threshold, such as accents.
+        Above = "above",
+        /// Notes below the threshold, such as ghost notes.
+        Below = "below",
+    }
+}
+
+/// Moves the notes of `track_id` on the `split` side of `threshold` onto a new track right after
+/// it, and returns the new track's id. The new track copies the track's properties; events other
+/// than notes stay on the original track.
+pub fn split_track_by_velocity(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+    threshold: Velocity,
+    split: VelocitySplit,
+) -> Result<Id, StoreError> {
+    match split {
+        VelocitySplit::Below => split_notes_off(song, ids, track_id, "Soft", |note| {
+            note.velocity < threshold
+        }),
+        VelocitySplit::Above => split_notes_off(song, ids, track_id, "Accents", |note| {
+            note.velocity >= threshold
+        }),
+    }
+}
+
+/// Moves the notes of `track_id` matching `predicate` onto a copy of the track inserted right
+/// after it, named after the track with `suffix`.
+fn split_notes_off(
+    song: &mut Song,
+    ids: &mut IdStrategy,
+    track_id: Id,
+    suffix: &str,
+    predicate: impl Fn(&Note) -> bool,
+) -> Result<Id, StoreError> {
+    let track = song
+        .get_track(&track_id)
+        .ok_or(StoreError::TrackNotFound(track_id))?;
+    let notes: Vec<Event> = song
+        .track_events(track)
+        .iter()
+        .filter(|event| event.as_note().is_some_and(&predicate))
+        .cloned()
+        .collect();
+
+    let mut new_track = track.without_events();
+    new_track.id = ids.next_id()?;
+    new_track.display.name = Some(match &track.display.name {
+        Some(name) => format!("{} ({})", name, suffix),
+        None => suffix.to_string(),
+    });
+    let index = song.get_tracks().position(&track_id).unwrap();
+    let new_track_id = song.insert_track_at(index + 1, new_track, Vec::new())?.id;
+
+    for event in notes {
+        song.replace_event(event.with_track_id(new_track_id))?;
+    }
+    Ok(new_track_id)
+}
+
+/// Moves every event of `track_ids` onto `target_track_id` and removes the emptied tracks. All
+/// tracks must exist; `target_track_id` may be among `track_ids`.
+pub fn merge_tracks(
+    song: &mut Song,
+    track_ids: &[Id],
+    target_track_id: Id,
+) -> Result<(), StoreError> {
+    if let Some(missing) = track_ids
+        .iter()
+        .chain([&target_track_id])
+        .find(|track_id| song.get_track(track_id).is_none())
+    {
+        return Err(StoreError::TrackNotFound(*missing));
+    }
+
+    for track_id in track_ids {
+        if *track_id == target_track_id || song.get_track(track_id).is_none() {
+            continue;
+        }
+        let events: Vec<Event> = song
+            .get_track_events(track_id)
+            .into_iter()
+            .cloned()
+            .collect();
+        for event in events {
+            song.replace_event(event.with_track_id(target_track_id))?;
+        }
+        song.remove_track(track_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, add_note, song_with_track};
+
+    #[test]
+    fn test_split_track_by_channel() {
+        let (mut song, track_id) = song_with_track();
+        let drums = add_note(&mut song, track_id, 0, 120, 36, 100);
+        let bass = add_note(&mut song, track_id, 0, 480, 40, 100);
+        let bass_cc = add_control_change(&mut song, track_id, 0, 7, 90);
+        let unassigned = add_note(&mut song, track_id, 480, 480, 60, 100);
+        song.set_channel(drums, 9);
+        song.set_channel(bass, 1);
+        song.set_channel(bass_cc, 1);
+
+        let new_track_ids =
+            split_track_by_channel(&mut song, &mut IdStrategy::default(), track_id).unwrap();
+        assert_eq!(new_track_ids.len(), 2);
+
+        let track_of = |event_id: Id| song.get_event(&event_id).unwrap().get_track_id().unwrap();
+        assert_eq!(track_of(bass), new_track_ids[0]);
+        assert_eq!(track_of(bass_cc), new_track_ids[0]);
+        assert_eq!(track_of(drums), new_track_ids[1]);
+        assert_eq!(track_of(unassigned), track_id);
+        assert_eq!(song.get_channel(&drums), Some(9));
+        assert_eq!(song.get_track(&new_track_ids[1]).unwrap().channel, Some(9));
+
+        let names: Vec<_> = new_track_ids
+            .iter()
+            .map(|id| song.get_track(id).unwrap().display.name.clone())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("Channel 2".to_string()),
+                Some("Channel 10".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_track_by_pitch_and_merge_tracks() {
+        let (mut song, track_id) = song_with_track();
+        let other_track_id = song.add_track(Track::new(Id::new())).id;
+        let low = add_note(&mut song, track_id, 0, 480, 48, 100);
+        let high = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let cc = add_control_change(&mut song, track_id, 0, 64, 127);
+        song.get_track_mut(&track_id).unwrap().display.name = Some("Piano".to_string());
+
+        let low_track_id = split_track_by_pitch(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            NoteNumber::new(60),
+        )
+        .unwrap();
+        let track_of =
+            |song: &Song, event_id: Id| song.get_event(&event_id).unwrap().get_track_id().unwrap();
+        assert_eq!(track_of(&song, low), low_track_id);
+        assert_eq!(track_of(&song, high), track_id);
+        assert_eq!(track_of(&song, cc), track_id);
+        let low_track = song.get_tracks()[1].clone();
+        assert_eq!(low_track.id, low_track_id);
+        assert_eq!(low_track.display.name.as_deref(), Some("Piano (Low)"));
+
+        merge_tracks(&mut song, &[low_track_id, track_id], other_track_id).unwrap();
+        assert_eq!(song.get_tracks().len(), 1);
+        assert_eq!(song.get_track_events(&other_track_id).len(), 3);
+        assert_eq!(track_of(&song, low), other_track_id);
+        assert_eq!(
+            merge_tracks(&mut song, &[track_id], other_track_id).unwrap_err(),
+            StoreError::TrackNotFound(track_id)
+        );
+    }
+
+    #[test]
+    fn test_split_track_by_velocity() {
+        let (mut song, track_id) = song_with_track();
+        let accent = add_note(&mut song, track_id, 0, 120, 38, 110);
+        let ghost = add_note(&mut song, track_id, 240, 120, 38, 30);
+        let normal = add_note(&mut song, track_id, 480, 120, 38, 80);
+        song.get_track_mut(&track_id).unwrap().display.name = Some("Snare".to_string());
+
+        let accents_track_id = split_track_by_velocity(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            Velocity::new(100),
+            VelocitySplit::Above,
+        )
+        .unwrap();
+        let soft_track_id = split_track_by_velocity(
+            &mut song,
+            &mut IdStrategy::default(),
+            track_id,
+            Velocity::new(40),
+            VelocitySplit::Below,
+        )
+        .unwrap();
+
+        let track_of = |event_id: Id| song.get_event(&event_id).unwrap().get_track_id().unwrap();
+        assert_eq!(track_of(accent), accents_track_id);
+        assert_eq!(track_of(ghost), soft_track_id);
+        assert_eq!(track_of(normal), track_id);
+        let names: Vec<_> = song
+            .get_tracks()
+            .iter()
+            .map(|track| track.display.name.clone().unwrap())
+            .collect();
+        assert_eq!(names, vec!["Snare", "Snare (Soft)", "Snare (Accents)"]);
+    }
+}
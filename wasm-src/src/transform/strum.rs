@@ -0,0 +1,178 @@
+use crate::{
+    event::{
+        event::Event,
+        note::{Note, Velocity},
+    },
+    shared::{error::StoreError, id::Id, js::string_enum, unit::time::Ticks},
+    song::song::Song,
+};
+use std::collections::BTreeMap;
+
+string_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum StrumDirection {
+        Up = "up",
+        Down = "down",
+    }
+}
+
+/// Spreads notes that start on the same tick of the same track across `spread` ticks.
+///
+/// With `Up` the lowest note stays in place and higher notes follow; `Down` reverses that. Note
+/// ends are kept where they were, so a note shorter than its share of the spread moves only to
+/// the tick before its end. `velocity_ramp` is added progressively, reaching its full amount on
+/// the last note of each chord.
+pub fn strum_chords(
+    song: &mut Song,
+    event_ids: &[Id],
+    spread: Ticks,
+    direction: StrumDirection,
+    velocity_ramp: i32,
+) -> Result<Vec<Event>, StoreError> {
+    let mut chords: BTreeMap<(Ticks, Id), Vec<Note>> = BTreeMap::new();
+
+    for event in song.try_get_events(event_ids)? {
+        let Event::Note(note) = event.clone() else {
+            continue;
+        };
+        chords
+            .entry((note.ticks, note.track_id))
+            .or_default()
+            .push(note);
+    }
+
+    let mut updated = Vec::new();
+
+    for mut notes in chords.into_values() {
+        match direction {
+            StrumDirection::Down => notes.sort_by_key(|note| std::cmp::Reverse(note.note_number)),
+            StrumDirection::Up => notes.sort_by_key(|note| note.note_number),
+        }
+
+        let steps = (notes.len() as u64).saturating_sub(1).max(1);
+
+        for (index, note) in notes.iter().enumerate() {
+            // widened so large spreads and ramps over big chords can't overflow
+            let index = index as u128;
+            let offset = spread.as_u64() as u128 * index / steps as u128;
+            let last_tick = note.duration.as_u64().saturating_sub(1);
+            let offset = Ticks::new((offset as u64).min(last_tick));
+            let velocity_offset = velocity_ramp as i128 * index as i128 / steps as i128;
+            let velocity = (note.velocity.as_u8() as i128 + velocity_offset).clamp(1, 127);
+
+            let strummed = Note {
+                ticks: note.ticks + offset,
+                duration: note.duration - offset,
+                velocity: Velocity::new(velocity as u8),
+                ..note.clone()
+            };
+            updated.push(song.replace_event(Event::Note(strummed))?.clone());
+        }
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_note, song_with_track};
+
+    #[test]
+    fn test_strum_chords_up() {
+        let (mut song, track_id) = song_with_track();
+
+        let c = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let e = add_note(&mut song, track_id, 0, 480, 64, 100);
+        let g = add_note(&mut song, track_id, 0, 480, 67, 100);
+
+        strum_chords(
+            &mut song,
+            &[g, c, e],
+            Ticks::new(60),
+            StrumDirection::Up,
+            -20,
+        )
+        .unwrap();
+
+        let c = song.get_event(&c).unwrap().as_note().unwrap();
+        let e = song.get_event(&e).unwrap().as_note().unwrap();
+        let g = song.get_event(&g).unwrap().as_note().unwrap();
+
+        assert_eq!(c.ticks, Ticks::new(0));
+        assert_eq!(e.ticks, Ticks::new(30));
+        assert_eq!(g.ticks, Ticks::new(60));
+        assert_eq!(g.duration, Ticks::new(420));
+        assert_eq!(c.velocity, Velocity::new(100));
+        assert_eq!(e.velocity, Velocity::new(90));
+        assert_eq!(g.velocity, Velocity::new(80));
+    }
+
+    #[test]
+    fn test_strum_chords_down() {
+        let (mut song, track_id) = song_with_track();
+
+        let c = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let g = add_note(&mut song, track_id, 0, 480, 67, 100);
+        let single = add_note(&mut song, track_id, 480, 480, 60, 100);
+
+        strum_chords(
+            &mut song,
+            &[c, g, single],
+            Ticks::new(40),
+            StrumDirection::Down,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(song.get_event(&g).unwrap().get_ticks(), Ticks::new(0));
+        assert_eq!(song.get_event(&c).unwrap().get_ticks(), Ticks::new(40));
+        assert_eq!(
+            song.get_event(&single).unwrap().get_ticks(),
+            Ticks::new(480)
+        );
+    }
+
+    #[test]
+    fn test_strum_chords_keeps_ends_of_short_notes() {
+        let (mut song, track_id) = song_with_track();
+        let c = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let e = add_note(&mut song, track_id, 0, 60, 64, 100);
+        let g = add_note(&mut song, track_id, 0, 480, 67, 100);
+
+        strum_chords(
+            &mut song,
+            &[c, e, g],
+            Ticks::new(240),
+            StrumDirection::Up,
+            0,
+        )
+        .unwrap();
+
+        let e = song.get_event(&e).unwrap().as_note().unwrap();
+        assert_eq!((e.ticks, e.duration), (Ticks::new(59), Ticks::new(1)));
+        let g = song.get_event(&g).unwrap().as_note().unwrap();
+        assert_eq!((g.ticks, g.duration), (Ticks::new(240), Ticks::new(240)));
+    }
+
+    #[test]
+    fn test_strum_chords_with_extreme_values() {
+        let (mut song, track_id) = song_with_track();
+        let notes: Vec<Id> = (0..3000)
+            .map(|index| add_note(&mut song, track_id, 0, 480, (index % 128) as u8, 100))
+            .collect();
+
+        let events =
+            strum_chords(&mut song, &notes, Ticks::MAX, StrumDirection::Up, i32::MAX).unwrap();
+        assert_eq!(events.last().unwrap().get_ticks(), Ticks::new(479));
+        assert!(events
+            .iter()
+            .skip(1)
+            .all(|event| event.as_note().unwrap().velocity == Velocity::new(127)));
+
+        assert!(matches!(
+            "sideways".parse::<StrumDirection>(),
+            Err(StoreError::ValidationFailed(_))
+        ));
+    }
+}
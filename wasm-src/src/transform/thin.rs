@@ -0,0 +1,118 @@
+use crate::{
+    event::{control_change::ControllerNumber, event::Event},
+    shared::{error::StoreError, id::Id},
+    song::song::Song,
+};
+
+/// Removes control changes of one controller on a track that a straight line between their
+/// neighbours reproduces within `tolerance` values, and returns the ids of the removed events.
+///
+/// This is the Douglas-Peucker reduction measured along the value axis: the first and last
+/// events are kept, and the event furthest from the line between kept events is kept whenever
+/// it lies more than `tolerance` away from it.
+pub fn thin_controller_events(
+    song: &mut Song,
+    track_id: Id,
+    controller: ControllerNumber,
+    tolerance: f64,
+) -> Result<Vec<Id>, StoreError> {
+    let mut points: Vec<(Id, f64, f64)> = song
+        .track_events(
+            song.get_track(&track_id)
+                .ok_or(StoreError::TrackNotFound(track_id))?,
+        )
+        .iter()
+        .filter_map(|event| match event {
+            Event::ControlChange(cc) if cc.controller == controller => {
+                Some((cc.id, cc.ticks.as_f64(), cc.value.as_u8() as f64))
+            }
+            _ => None,
+        })
+        .collect();
+    points.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+
+    if points.len() < 3 {
+        return Ok(Vec::new());
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut spans = vec![(0, points.len() - 1)];
+
+    while let Some((first, last)) = spans.pop() {
+        let (_, first_ticks, first_value) = points[first];
+        let (_, last_ticks, last_value) = points[last];
+        let slope = if last_ticks > first_ticks {
+            (last_value - first_value) / (last_ticks - first_ticks)
+        } else {
+            0.0
+        };
+
+        let furthest = (first + 1..last)
+            .map(|index| {
+                let (_, ticks, value) = points[index];
+                let line = first_value + slope * (ticks - first_ticks);
+                (index, (value - line).abs())
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((index, distance)) = furthest {
+            if distance > tolerance {
+                keep[index] = true;
+                spans.push((first, index));
+                spans.push((index, last));
+            }
+        }
+    }
+
+    let removed_ids: Vec<Id> = points
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| !keep)
+        .map(|((id, ..), _)| *id)
+        .collect();
+
+    for event_id in &removed_ids {
+        song.remove_event(event_id);
+    }
+
+    Ok(removed_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::test_helpers::{add_control_change, song_with_track};
+
+    #[test]
+    fn test_thin_controller_events() {
+        let (mut song, track_id) = song_with_track();
+        // A recorded rise from 0 to 100 over 100 events, held, then a drop back to 0.
+        for step in 0..=100 {
+            add_control_change(&mut song, track_id, step * 10, 1, step as u8);
+        }
+        let peak_end = add_control_change(&mut song, track_id, 1500, 1, 100);
+        let end = add_control_change(&mut song, track_id, 1600, 1, 0);
+        let other = add_control_change(&mut song, track_id, 500, 7, 64);
+
+        let removed =
+            thin_controller_events(&mut song, track_id, ControllerNumber::new(1), 1.0).unwrap();
+
+        assert_eq!(removed.len(), 99);
+        let kept: Vec<(u64, u8)> = song
+            .get_track_events(&track_id)
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::ControlChange(cc) if cc.controller == ControllerNumber::new(1) => {
+                    Some((cc.ticks.as_u64(), cc.value.as_u8()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kept, vec![(0, 0), (1000, 100), (1500, 100), (1600, 0)]);
+        assert!(song.get_event(&peak_end).is_some());
+        assert!(song.get_event(&end).is_some());
+        assert!(song.get_event(&other).is_some());
+    }
+}
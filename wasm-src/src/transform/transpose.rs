@@ -0,0 +1,130 @@
+use crate::{
+    event::{
+        chord_symbol::ChordSymbol,
+        event::Event,
+        key_signature::KeySignature,
+        note::{Note, NoteNumber},
+    },
+    song::song::Song,
+};
+use serde::Deserialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TRANSPOSE_OPTIONS: &'static str = r#"
+export interface TransposeOptions {
+  /** Moves the roots of key signatures along with the notes. Defaults to `true`. */
+  updateKeySignatures?: boolean;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransposeOptions {
+    pub update_key_signatures: bool,
+}
+
+impl Default for TransposeOptions {
+    fn default() -> Self {
+        TransposeOptions {
+            update_key_signatures: true,
+        }
+    }
+}
+
+fn transpose_pitch_class(pitch_class: u8, semitones: i32) -> u8 {
+    (pitch_class as i32 + semitones).rem_euclid(12) as u8
+}
+
+/// Moves every note of the song by `semitones`, within 0 to 127, along with the roots and
+/// basses of chord symbols so they keep naming the notes. Key signatures follow when
+/// `options.update_key_signatures` is set. Returns how many events changed.
+pub fn transpose_song(song: &mut Song, semitones: i32, options: &TransposeOptions) -> usize {
+    if semitones == 0 {
+        return 0;
+    }
+
+    let transposed: Vec<Event> = song
+        .get_events(None)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Note(note) => {
+                let note_number = (note.note_number.as_u8() as i32 + semitones).clamp(0, 127);
+                (note_number != note.note_number.as_u8() as i32).then(|| {
+                    Event::Note(Note {
+                        note_number: NoteNumber::new(note_number as u8),
+                        ..note.clone()
+                    })
+                })
+            }
+            Event::ChordSymbol(chord) => Some(Event::ChordSymbol(ChordSymbol {
+                root: transpose_pitch_class(chord.root, semitones),
+                bass: chord
+                    .bass
+                    .map(|bass| transpose_pitch_class(bass, semitones)),
+                ..chord.clone()
+            })),
+            Event::KeySignature(key) if options.update_key_signatures => {
+                Some(Event::KeySignature(KeySignature {
+                    root: transpose_pitch_class(key.root, semitones),
+                    ..key.clone()
+                }))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let count = transposed.len();
+    for event in transposed {
+        song.replace_event(event)
+            .expect("Transposed events are in the song");
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::key_signature::KeyMode,
+        shared::{id::Id, unit::time::Ticks},
+        transform::test_helpers::{add_note, song_with_track},
+    };
+
+    #[test]
+    fn test_transpose_song() {
+        let (mut song, track_id) = song_with_track();
+        let note = add_note(&mut song, track_id, 0, 480, 60, 100);
+        let high = add_note(&mut song, track_id, 480, 480, 126, 100);
+        let key = Id::new();
+        song.add_event(Event::KeySignature(KeySignature {
+            id: key,
+            ticks: Ticks::new(0),
+            root: 10,
+            mode: KeyMode::Major,
+            track_id,
+        }))
+        .unwrap();
+
+        let options = TransposeOptions {
+            update_key_signatures: false,
+        };
+        assert_eq!(transpose_song(&mut song, -2, &options), 2);
+        let key_root = |song: &Song| match song.get_event(&key) {
+            Some(Event::KeySignature(key)) => key.root,
+            _ => unreachable!(),
+        };
+        assert_eq!(key_root(&song), 10);
+
+        assert_eq!(
+            transpose_song(&mut song, 4, &TransposeOptions::default()),
+            3
+        );
+        let pitch = |song: &Song, id| song.get_event(&id).unwrap().as_note().unwrap().note_number;
+        assert_eq!(pitch(&song, note), NoteNumber::new(62));
+        assert_eq!(pitch(&song, high), NoteNumber::new(127));
+        assert_eq!(key_root(&song), 2);
+    }
+}